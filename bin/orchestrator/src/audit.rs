@@ -0,0 +1,198 @@
+//! Cross-check of L1 portal events against L2 withdrawal initiations.
+//!
+//! The regular withdrawal flow derives everything from L2 `MessagePassed` events plus
+//! portal view calls. The audit builds an independent trail from the L1
+//! `WithdrawalProven` / `WithdrawalFinalized` events and reports where the two disagree.
+
+use crate::config::Config;
+use alloy_primitives::TxHash;
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use std::collections::HashSet;
+use withdrawal::{
+    state::WithdrawalStateProvider,
+    types::{L1WithdrawalEvent, L1WithdrawalEventKind, WithdrawalHash},
+};
+
+/// A disagreement between L1 portal events and our L2 withdrawal history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// A withdrawal from our EOA was proven on L1 but has no matching L2 initiation
+    /// in the lookback window.
+    ProvenButUnknown {
+        hash: WithdrawalHash,
+        tx_hash: TxHash,
+    },
+    /// One of our withdrawals was finalized but the inner call reported failure.
+    FinalizedWithFailure {
+        hash: WithdrawalHash,
+        tx_hash: TxHash,
+    },
+}
+
+/// Result of an audit run.
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    /// Withdrawals initiated on L2 in the lookback window
+    pub initiated: usize,
+    /// `WithdrawalProven` events for our EOA on L1
+    pub proven: usize,
+    /// `WithdrawalFinalized` events for our withdrawals on L1
+    pub finalized: usize,
+    /// Everything that did not line up
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+/// Compare L1 portal events against the set of hashes initiated on L2.
+///
+/// `events` is expected to already be restricted to proofs for our EOA; finalized
+/// events for hashes we don't know about are ignored since the portal is shared.
+pub fn reconcile(initiated: &HashSet<WithdrawalHash>, events: &[L1WithdrawalEvent]) -> AuditReport {
+    let proven_hashes: HashSet<WithdrawalHash> = events
+        .iter()
+        .filter(|e| matches!(e.kind, L1WithdrawalEventKind::Proven { .. }))
+        .map(|e| e.hash)
+        .collect();
+
+    let mut report = AuditReport {
+        initiated: initiated.len(),
+        ..Default::default()
+    };
+
+    for event in events {
+        match event.kind {
+            L1WithdrawalEventKind::Proven { .. } => {
+                report.proven += 1;
+                if !initiated.contains(&event.hash) {
+                    report.discrepancies.push(Discrepancy::ProvenButUnknown {
+                        hash: event.hash,
+                        tx_hash: event.tx_hash,
+                    });
+                }
+            }
+            L1WithdrawalEventKind::Finalized { success } => {
+                if !initiated.contains(&event.hash) && !proven_hashes.contains(&event.hash) {
+                    continue;
+                }
+                report.finalized += 1;
+                if !success {
+                    report
+                        .discrepancies
+                        .push(Discrepancy::FinalizedWithFailure {
+                            hash: event.hash,
+                            tx_hash: event.tx_hash,
+                        });
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Scan both chains over the configured withdrawal lookback and reconcile them.
+pub async fn audit_withdrawals<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    config: &Config,
+) -> eyre::Result<AuditReport>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let network = config.network_config();
+
+    let l1_current_block = l1_provider.get_block_number().await?;
+    let l2_current_block = l2_provider.get_block_number().await?;
+    let l1_from_block = l1_current_block
+        .saturating_sub(network.ethereum.blocks_in(config.withdrawal_lookback_secs));
+    let l2_from_block = l2_current_block
+        .saturating_sub(network.unichain.blocks_in(config.withdrawal_lookback_secs));
+
+    let state_provider = WithdrawalStateProvider::new(
+        l1_provider,
+        l2_provider,
+        network.unichain.l1_portal,
+        network.unichain.l2_to_l1_message_passer,
+    );
+
+    let initiated = state_provider
+        .get_initiated_hashes(
+            BlockNumberOrTag::Number(l2_from_block),
+            BlockNumberOrTag::Number(l2_current_block),
+            config.eoa_address,
+        )
+        .await?;
+
+    let events = state_provider
+        .scan_l1_withdrawal_events(
+            BlockNumberOrTag::Number(l1_from_block),
+            BlockNumberOrTag::Number(l1_current_block),
+            Some(config.eoa_address),
+        )
+        .await?;
+
+    Ok(reconcile(&initiated, &events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+
+    fn event(hash: u8, kind: L1WithdrawalEventKind) -> L1WithdrawalEvent {
+        L1WithdrawalEvent {
+            hash: B256::repeat_byte(hash),
+            kind,
+            tx_hash: B256::repeat_byte(0xff),
+            block_number: 1,
+            timestamp: 1,
+        }
+    }
+
+    const PROVEN: L1WithdrawalEventKind = L1WithdrawalEventKind::Proven {
+        from: Address::ZERO,
+        to: Address::ZERO,
+    };
+
+    #[test]
+    fn test_reconcile_clean() {
+        let initiated = HashSet::from([B256::repeat_byte(1)]);
+        let events = [
+            event(1, PROVEN),
+            event(1, L1WithdrawalEventKind::Finalized { success: true }),
+        ];
+
+        let report = reconcile(&initiated, &events);
+        assert_eq!(report.proven, 1);
+        assert_eq!(report.finalized, 1);
+        assert!(report.discrepancies.is_empty());
+    }
+
+    #[test]
+    fn test_reconcile_reports_discrepancies() {
+        let initiated = HashSet::from([B256::repeat_byte(1)]);
+        let events = [
+            event(2, PROVEN),
+            event(1, L1WithdrawalEventKind::Finalized { success: false }),
+            // Someone else's withdrawal finalized through the shared portal
+            event(9, L1WithdrawalEventKind::Finalized { success: false }),
+        ];
+
+        let report = reconcile(&initiated, &events);
+        assert_eq!(report.finalized, 1);
+        assert_eq!(
+            report.discrepancies,
+            vec![
+                Discrepancy::ProvenButUnknown {
+                    hash: B256::repeat_byte(2),
+                    tx_hash: B256::repeat_byte(0xff),
+                },
+                Discrepancy::FinalizedWithFailure {
+                    hash: B256::repeat_byte(1),
+                    tx_hash: B256::repeat_byte(0xff),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Backfill of a long withdrawal backlog through the state store.
+//!
+//! After long downtime the per-cycle scan has to re-query status for thousands of
+//! withdrawals every time. Backfill splits that up: discovery scans L2 once (and then
+//! only the blocks added since), statuses are refreshed in Multicall3 batches, and
+//! prove/finalize is driven from what the store says is still pending.
+
+use crate::{config::Config, process_withdrawals, store::StateStore, throttle::ThrottledLogger};
+use action::TransactionSigner;
+use alloy_provider::Provider;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+use withdrawal::{
+    portal::PortalParamsCache, proof::StorageLayoutCheck, state::WithdrawalStateProvider,
+    types::WithdrawalHash,
+};
+
+/// Counts from a backfill run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillSummary {
+    /// Withdrawals found by this run's discovery scan
+    pub discovered: usize,
+    /// Withdrawals in the store after the run
+    pub known: usize,
+    /// Withdrawals handed to prove/finalize
+    pub pending: usize,
+}
+
+/// Discover withdrawals into `store`, refresh their statuses and process pending ones.
+///
+/// Discovery and the status refresh are [`refresh_store`]. The store is saved after
+/// each, and after new proofs are recorded, so an interrupted run keeps its progress.
+pub async fn run_backfill<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    l1_signer: Arc<dyn TransactionSigner>,
+    config: &Config,
+    store: &mut StateStore,
+    rescan: bool,
+) -> eyre::Result<BackfillSummary>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let network = config.network_config();
+    // One run may prove and finalize thousands of withdrawals: read portal params once
+    let portal_params =
+        PortalParamsCache::new(Duration::from_secs(config.portal_params_refresh_secs));
+    let state_provider = WithdrawalStateProvider::new(
+        l1_provider.clone(),
+        l2_provider.clone(),
+        network.unichain.l1_portal,
+        network.unichain.l2_to_l1_message_passer,
+    )
+    .with_portal_params(portal_params.clone());
+
+    // Saved even if the status refresh failed, so a rerun doesn't scan again
+    let discovered = refresh_store(&state_provider, &l2_provider, config, store, rescan).await;
+    store.save()?;
+    let mut summary = BackfillSummary {
+        discovered: discovered?,
+        ..Default::default()
+    };
+
+    // Drive prove/finalize from the store
+    let pending = store.pending_withdrawals();
+    summary.known = store.withdrawal_count();
+
+    if state_provider.is_paused().await? {
+        warn!(
+            pending = pending.len(),
+            "OptimismPortal is paused, skipping prove/finalize"
+        );
+        return Ok(summary);
+    }
+    summary.pending = pending.len();
+
+    info!(
+        discovered = summary.discovered,
+        known = summary.known,
+        pending = summary.pending,
+        "Backfill: processing pending withdrawals from the store"
+    );
+
+    let retry_budget = config.retry_budget();
+    let pending_transactions = store.pending_transactions();
+    let processed = process_withdrawals(
+        l1_provider,
+        l2_provider,
+        l1_signer,
+        config,
+        &pending,
+        &portal_params,
+        &retry_budget,
+        &StorageLayoutCheck::default(),
+        &ThrottledLogger::default(),
+        &pending_transactions,
+        config.cycle_deadline(Instant::now()),
+    )
+    .await;
+    // Saved even if processing failed, so what was broadcast isn't sent again
+    let pending_changed = store.set_pending_transactions(pending_transactions.entries());
+    let processed = match processed {
+        Ok(processed) => processed,
+        Err(e) => {
+            if pending_changed {
+                store.save()?;
+            }
+            return Err(e);
+        }
+    };
+    for submitted in &processed.proofs {
+        store.record_proof(&submitted.withdrawal, submitted.proof);
+    }
+    if !processed.proofs.is_empty() || pending_changed {
+        store.save()?;
+    }
+    if let Some(shortage) = processed.insufficient_gas_funds {
+        return Err(shortage.into());
+    }
+
+    Ok(summary)
+}
+
+/// Scan L2 into `store` for withdrawals it hasn't seen and refresh the status of every
+/// one not finalized yet. Returns how many withdrawals the scan found.
+///
+/// The first run (or any run with `rescan`) scans the full `withdrawal_lookback_secs`;
+/// later runs only scan L2 blocks after the store's checkpoint. The store is not saved;
+/// that is left to the caller.
+pub async fn refresh_store<P1, P2>(
+    state_provider: &WithdrawalStateProvider<P1, P2>,
+    l2_provider: &P2,
+    config: &Config,
+    store: &mut StateStore,
+    rescan: bool,
+) -> eyre::Result<usize>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    // Discovery: one L2 scan over everything the store hasn't seen
+    let l2_current_block = l2_provider.get_block_number().await?;
+    let lookback_start = l2_current_block.saturating_sub(
+        config
+            .network_config()
+            .unichain
+            .blocks_in(config.withdrawal_lookback_secs),
+    );
+    let from_block = store
+        .withdrawals_scanned_to()
+        .filter(|_| !rescan)
+        .map_or(lookback_start, |block| block + 1);
+
+    let mut discovered = 0;
+    if from_block <= l2_current_block {
+        info!(
+            from = from_block,
+            to = l2_current_block,
+            "Backfill: scanning L2 for withdrawals"
+        );
+        let found = state_provider
+            .get_withdrawals_batched(from_block, l2_current_block, config.eoa_address)
+            .await?;
+
+        discovered = found.len();
+        store.upsert_withdrawals(&found);
+        store.set_withdrawals_scanned_to(l2_current_block);
+    }
+
+    // Refresh statuses of everything not yet finalized
+    let hashes: Vec<WithdrawalHash> = store.pending_withdrawals().iter().map(|w| w.hash).collect();
+    let statuses = state_provider
+        .query_withdrawal_statuses(&hashes, config.eoa_address)
+        .await?;
+    let updates: Vec<_> = hashes.into_iter().zip(statuses).collect();
+    store.update_statuses(&updates);
+
+    Ok(discovered)
+}
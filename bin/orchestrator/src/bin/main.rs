@@ -1,10 +1,33 @@
-use clap::Parser;
-use client::{local_signer_fn, remote_signer_fn, RemoteSigner, SignerFn};
+use action::{deposit::OutputOutOfBounds, gas::InsufficientGasFunds};
+use alloy_primitives::Address;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use client::{coverage::ScanCoverage, LocalSigner, PrivateKey, RemoteSigner, TransactionSigner};
 use orchestrator::{
-    config::Config,
+    alert_insufficient_gas_funds,
+    circuit::CircuitBreaker,
+    config::{Config, ExecutionMode},
+    events::audit_event_decoding,
     maybe_deposit, maybe_initiate_withdrawal,
-    metrics::{install_prometheus_exporter, Metrics},
-    process_pending_withdrawals, update_metrics,
+    mempool::{check_stuck_nonce, replace_transaction, Chain, NonceGapTracker, RecordingSigner},
+    metrics::{start_prometheus_exporter, Metrics},
+    preflight::{check_block_times, check_eoa_account, verify_contract_code},
+    price::PriceOracle,
+    process_pending_withdrawals,
+    proof_health::ProofHealthTracker,
+    reconcile::reconcile_finalized,
+    record_initiations,
+    recovery::recover_proven_withdrawals,
+    refunds::{track_deposit_refunds, DepositRefundTracker},
+    reject_secret_argument,
+    reorg::RegressionGuard,
+    report::update_withdrawal_costs,
+    schedule::IntervalSchedule,
+    shutdown::{shutdown, RunSummary},
+    snapshot::{SnapshotDiff, StateSnapshot},
+    stall::{check_chain_head, HeadTracker},
+    store::StateStore,
+    throttle::ThrottledLogger,
+    update_metrics, verify_finalized_withdrawals, verify_signer_address,
 };
 use std::{
     sync::{
@@ -13,8 +36,10 @@ use std::{
     },
     time::{Duration, Instant},
 };
-use tokio::time;
-use tracing::{info, warn};
+use tokio::{sync::Mutex, time};
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::fmt::format::FmtSpan;
+use withdrawal::{portal::PortalParamsCache, proof::StorageLayoutCheck};
 
 #[derive(Parser)]
 #[command(name = "orchestrator")]
@@ -25,13 +50,40 @@ struct Cli {
     config: String,
 
     /// Private key for signing transactions (hex string, with or without 0x prefix).
-    /// Required when remote_signer is not configured.
-    #[arg(short = 'k', long, env = "PRIVATE_KEY")]
-    private_key: Option<String>,
+    /// Required when remote_signer is not configured. Only read from the environment:
+    /// passing it on the command line is rejected
+    #[arg(short = 'k', long, env = "PRIVATE_KEY", hide_env_values = true)]
+    private_key: Option<PrivateKey>,
 
-    /// Dry-run mode: log actions without executing transactions
+    /// Dry-run mode: log actions without executing transactions. Same as
+    /// `--execution-mode log-only`
     #[arg(long)]
     dry_run: bool,
+
+    /// Submit transactions (live), eth_call them without submitting (simulate) or only
+    /// log them (log-only). Overrides execution_mode from the config
+    #[arg(long, env = "EXECUTION_MODE", conflicts_with = "dry_run")]
+    execution_mode: Option<ExecutionMode>,
+
+    /// Continue even if eoa_address differs from the signer's address
+    /// (for setups where the prover and the withdrawal sender are different accounts)
+    #[arg(long)]
+    allow_address_mismatch: bool,
+
+    /// Re-check a sample of the withdrawals recorded as finalized against L1 at startup,
+    /// instead of waiting for the next scheduled check
+    #[arg(long)]
+    verify_finalized: bool,
+
+    /// Only report state: serve metrics refreshed every cycle, with no signer and without
+    /// attempting any action. The state file is read but never written
+    #[arg(long, conflicts_with_all = ["dry_run", "execution_mode", "verify_finalized"])]
+    watch_only: bool,
+
+    /// Log what changed in pending withdrawals, in-flight deposits and balances since the
+    /// previous cycle, instead of nothing (for debugging)
+    #[arg(long)]
+    snapshot_diff: bool,
 }
 
 /// Result status for a cycle step
@@ -39,7 +91,6 @@ struct Cli {
 enum StepResult {
     Ok,
     Failed,
-    #[allow(dead_code)]
     Skipped,
 }
 
@@ -64,121 +115,430 @@ async fn main() -> eyre::Result<()> {
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
         )
+        // With trace_rpc, log how long each RPC span took when it closes
+        .with_span_events(if cfg!(feature = "trace_rpc") {
+            FmtSpan::CLOSE
+        } else {
+            FmtSpan::NONE
+        })
         .init();
 
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    reject_secret_argument(&matches, "private_key")?;
+    let cli = Cli::from_arg_matches(&matches)?;
 
     info!("Starting Orchestrator");
 
     let mut config = Config::from_file(&cli.config)?;
 
-    // Override dry_run from CLI flag
+    // Override the execution mode from CLI flags
     if cli.dry_run {
-        config.dry_run = true;
+        config.execution_mode = ExecutionMode::LogOnly;
+    }
+    if let Some(mode) = cli.execution_mode {
+        config.execution_mode = mode;
     }
     let network = config.network_config();
 
+    if cli.watch_only {
+        return watch_only(config, cli.snapshot_diff).await;
+    }
+
+    // Create signers based on configuration
+    let (l1_signer, l2_signer, signer_addresses): (
+        Arc<dyn TransactionSigner>,
+        Arc<dyn TransactionSigner>,
+        Vec<Address>,
+    ) = match (&config.remote_signer, cli.private_key.as_ref()) {
+        (Some(remote_config), _) => {
+            info!("Using remote signer at {}", remote_config.proxy_url);
+            let check_signed = !remote_config.skip_signed_transaction_check;
+            if !check_signed {
+                warn!("Signed transactions from the remote signer are broadcast unchecked");
+            }
+            let l1_remote = RemoteSigner::new(
+                &remote_config.proxy_url,
+                config.eoa_address,
+                network.ethereum.chain_id,
+            )
+            .with_signed_transaction_check(check_signed);
+            let l2_remote = RemoteSigner::new(
+                &remote_config.proxy_url,
+                config.eoa_address,
+                network.unichain.chain_id,
+            )
+            .with_signed_transaction_check(check_signed);
+            let accounts = l1_remote.accounts().await?;
+            (Arc::new(l1_remote), Arc::new(l2_remote), accounts)
+        }
+        (None, Some(pk)) => {
+            info!("Using local private key for signing");
+            let signer = Arc::new(LocalSigner::from_private_key(pk.expose_secret())?);
+            let address = signer.address();
+            (signer.clone(), signer, vec![address])
+        }
+        (None, None) => {
+            eyre::bail!(
+                "No signing method configured. Provide PRIVATE_KEY env var, \
+                     configure remote_signer in config, or use --watch-only to run without \
+                     a signer."
+            );
+        }
+    };
+
     info!("Loaded config:");
     info!("  Network: {:?}", config.network);
     info!("  L2 SpokePool: {}", network.unichain.spoke_pool);
     info!("  L1 Portal: {}", network.unichain.l1_portal);
     info!("  EOA: {}", config.eoa_address);
+    info!("  Signer: {:?}", signer_addresses);
     info!("  Cycle interval: {}s", config.cycle_interval_secs);
-    info!("  Dry-run: {}", config.dry_run);
+    info!("  Execution mode: {}", config.execution_mode.as_str());
     info!("  Metrics port: {}", config.metrics_port);
+    info!(
+        "  Max consecutive failures: {}",
+        config.max_consecutive_failures
+    );
+
+    verify_signer_address(
+        config.eoa_address,
+        &signer_addresses,
+        cli.allow_address_mismatch,
+    )?;
+
+    let store = Arc::new(Mutex::new(StateStore::load(&config.state_path)?));
+
+    // Record what we sign so stuck transactions can be re-sent with higher fees
+    let (l1_signer, l2_signer): (Arc<dyn TransactionSigner>, Arc<dyn TransactionSigner>) =
+        if config.bump_stuck_transactions {
+            (
+                Arc::new(RecordingSigner::new(l1_signer, store.clone())),
+                Arc::new(RecordingSigner::new(l2_signer, store.clone())),
+            )
+        } else {
+            (l1_signer, l2_signer)
+        };
 
-    if config.dry_run {
-        warn!("=== DRY-RUN MODE: No transactions will be submitted ===");
+    match config.execution_mode {
+        ExecutionMode::Live => {}
+        ExecutionMode::Simulate => {
+            warn!("=== SIMULATE MODE: Transactions will be simulated, not submitted ===");
+        }
+        ExecutionMode::LogOnly => {
+            warn!("=== DRY-RUN MODE: No transactions will be submitted ===");
+        }
     }
 
     // Start Prometheus metrics server
     info!("Starting metrics server on port {}...", config.metrics_port);
-    install_prometheus_exporter(config.metrics_port)?;
+    let metrics_running =
+        start_prometheus_exporter(config.metrics_port, config.metrics_required).await?;
     let metrics = Metrics::new();
 
     // Create providers (read-only, signing handled separately)
-    let l1_provider = client::create_provider(&config.l1_rpc_url).await?;
-    let l2_provider = client::create_provider(&config.l2_rpc_url).await?;
+    let l1_provider =
+        client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+    let l2_provider =
+        client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
 
-    // Create signers based on configuration
-    let (l1_signer, l2_signer): (SignerFn, SignerFn) =
-        match (&config.remote_signer, cli.private_key.as_deref()) {
-            (Some(remote_config), _) => {
-                info!("Using remote signer at {}", remote_config.proxy_url);
-                let l1_remote = RemoteSigner::new(
-                    &remote_config.proxy_url,
-                    config.eoa_address,
-                    network.ethereum.chain_id,
-                );
-                let l2_remote = RemoteSigner::new(
-                    &remote_config.proxy_url,
-                    config.eoa_address,
-                    network.unichain.chain_id,
-                );
-                (remote_signer_fn(l1_remote), remote_signer_fn(l2_remote))
-            }
-            (None, Some(pk)) => {
-                info!("Using local private key for signing");
-                let signer = local_signer_fn(pk)?;
-                (signer.clone(), signer)
-            }
-            (None, None) => {
-                eyre::bail!(
-                    "No signing method configured. Provide PRIVATE_KEY env var, \
-                     configure remote_signer in config, or use --dry-run mode."
-                );
-            }
-        };
+    // Catch wrong contract addresses before any transaction is attempted
+    verify_contract_code(&l1_provider, &l2_provider, &network).await?;
+    info!("Configured contract addresses verified");
+    check_block_times(&l1_provider, &l2_provider, &mut config, &metrics).await;
+    let contract_account = check_eoa_account(
+        &l1_provider,
+        &l2_provider,
+        config.eoa_address,
+        config.allow_contract_account,
+    )
+    .await?;
 
     // Set up graceful shutdown handling
-    let shutdown_requested = Arc::new(AtomicBool::new(false));
-    let shutdown_flag = shutdown_requested.clone();
+    let shutdown_requested = spawn_shutdown_listener();
+
+    // SIGUSR1 resets an open circuit breaker without restarting
+    let reset_requested = Arc::new(AtomicBool::new(false));
+    let reset_flag = reset_requested.clone();
 
     tokio::spawn(async move {
-        let mut sigint =
-            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt()).unwrap();
-        let mut sigterm =
-            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).unwrap();
+        let mut sigusr1 =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::user_defined1()).unwrap();
 
-        tokio::select! {
-            _ = sigint.recv() => {
-                info!("Received shutdown signal, completing current cycle...");
-            }
-            _ = sigterm.recv() => {
-                info!("Received shutdown signal, completing current cycle...");
-            }
+        while sigusr1.recv().await.is_some() {
+            info!("Received circuit breaker reset signal");
+            reset_flag.store(true, Ordering::SeqCst);
         }
-
-        shutdown_flag.store(true, Ordering::SeqCst);
     });
 
     info!("Starting main loop...");
 
+    let started = Instant::now();
+    let mut summary = RunSummary::default();
+
     let mut interval = time::interval(Duration::from_secs(config.cycle_interval_secs));
     let mut cycle_number: u64 = 0;
-
-    loop {
-        // Wait for next tick OR shutdown signal
-        tokio::select! {
-            _ = interval.tick() => {}
-            _ = async {
-                while !shutdown_requested.load(Ordering::SeqCst) {
-                    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut refund_tracker =
+        DepositRefundTracker::new().with_ledger(store.lock().await.refund_ledger());
+    let mut circuit = CircuitBreaker::new(config.max_consecutive_failures);
+    let mut portal_paused = false;
+    let mut recovery = IntervalSchedule::new(Duration::from_secs(
+        config.withdrawal_recovery_interval_secs,
+    ));
+    let mut cost_tracking =
+        IntervalSchedule::new(Duration::from_secs(config.withdrawal_cost_interval_secs));
+    let mut reconciliation =
+        IntervalSchedule::new(Duration::from_secs(config.reconcile_interval_secs));
+    let mut event_audit =
+        IntervalSchedule::new(Duration::from_secs(config.event_audit_interval_secs));
+    // Unfilled deposits and the last L1 block scanned, so cycles only scan new blocks
+    let mut deposit_checkpoint = store.lock().await.deposit_checkpoint();
+    let mut nonce_gaps = NonceGapTracker::new(Duration::from_secs(config.stuck_nonce_age_secs));
+    let mut chain_heads = HeadTracker::new(Duration::from_secs(config.max_stale_head_secs));
+    // Shared by every scan and proof request in a cycle, refilled at the start of the next one
+    let retry_budget = config.retry_budget();
+    // Blocks covered by every scan in a cycle, counted from zero each cycle
+    let scan_coverage = ScanCoverage::default();
+    // Respected game type and proof maturity delay, re-read from L1 once stale
+    let portal_params =
+        PortalParamsCache::new(Duration::from_secs(config.portal_params_refresh_secs));
+    // Checked once, against the first withdrawal to prove
+    let layout_check = StorageLayoutCheck::default();
+    // Failures that repeat every cycle are logged once per window
+    let log_throttle_window = Duration::from_secs(config.log_throttle_window_secs);
+    let withdrawal_log_throttle = ThrottledLogger::new(log_throttle_window);
+    let proof_health = ProofHealthTracker::default();
+    // Withdrawals seen proven, so a proof undone by an L1 reorg isn't redone right away
+    let regression_guard = RegressionGuard::new(Duration::from_secs(config.reorg_tolerance_secs));
+    // ETH / USD price for the USD cost metrics, re-read once stale
+    let price_oracle = config
+        .eth_usd_price_feed
+        .clone()
+        .map(|feed| {
+            PriceOracle::new(
+                feed,
+                Duration::from_secs(config.price_refresh_secs),
+                config.provider_timeouts(),
+            )
+        })
+        .transpose()?;
+    let mut snapshots = cli.snapshot_diff.then(SnapshotDiff::default);
+    let deposit_log_throttle = ThrottledLogger::new(log_throttle_window);
+    // Withdrawals known to be finalized, skipped by scans without reading the portal
+    let finalized = store.lock().await.finalized_set();
+    // Prove and finalize transactions broadcast without a receipt yet, not sent again
+    // while they wait in the mempool. Saved as soon as one is broadcast, so a restart
+    // during the receipt wait doesn't send it again.
+    let pending_transactions = store.lock().await.pending_transactions().with_persistence({
+        let store = store.clone();
+        Arc::new(move |entries| {
+            let store = store.clone();
+            Box::pin(async move {
+                let mut store = store.lock().await;
+                if store.set_pending_transactions(entries) {
+                    if let Err(e) = store.save() {
+                        warn!(error = %e, "Failed to save pending transactions");
+                    }
                 }
-            } => {
-                info!("Shutdown signal received, exiting immediately");
-                break;
-            }
-        }
+            })
+        })
+    });
+    let mut verify_finalized_requested = cli.verify_finalized;
+    metrics.set_circuit_open(false);
 
-        // Check again in case we woke up from interval but shutdown was requested
-        if shutdown_requested.load(Ordering::SeqCst) {
+    loop {
+        if !next_tick(&mut interval, &shutdown_requested).await {
             info!("Shutdown signal received, exiting immediately");
             break;
         }
 
+        if reset_requested.swap(false, Ordering::SeqCst) && circuit.is_open() {
+            info!("Circuit breaker reset, resuming");
+            circuit.reset();
+            metrics.set_circuit_open(false);
+        }
+
+        // Degraded mode: keep the gauges current but don't act on the chains
+        if circuit.is_open() {
+            retry_budget.reset();
+            scan_coverage.reset();
+            let snapshot = update_metrics(
+                l1_provider.clone(),
+                l2_provider.clone(),
+                &config,
+                &metrics,
+                &retry_budget,
+                &scan_coverage,
+                &mut deposit_checkpoint,
+                &proof_health,
+                &portal_params,
+            )
+            .await;
+            if let Some(snapshots) = snapshots.as_mut() {
+                log_state_changes(snapshots, snapshot);
+            }
+            continue;
+        }
+
         cycle_number += 1;
         let cycle_start = Instant::now();
+        retry_budget.reset();
+        scan_coverage.reset();
+
+        // A halted sequencer or stuck RPC node looks like a quiet cycle otherwise
+        if config.max_stale_head_secs > 0 {
+            for (chain, head) in [
+                (
+                    Chain::L1,
+                    check_chain_head(&l1_provider, Chain::L1, &mut chain_heads, cycle_start).await,
+                ),
+                (
+                    Chain::L2,
+                    check_chain_head(&l2_provider, Chain::L2, &mut chain_heads, cycle_start).await,
+                ),
+            ] {
+                match head {
+                    Ok(Some(stalled)) => {
+                        error!(
+                            chain = chain.as_str(),
+                            head = stalled.head,
+                            stalled_secs = stalled.age.as_secs(),
+                            "ALERT: chain head has not advanced, sequencer or RPC endpoint may be stuck"
+                        );
+                        metrics.set_chain_stalled(chain.as_str(), true);
+                    }
+                    Ok(None) => metrics.set_chain_stalled(chain.as_str(), false),
+                    Err(e) => {
+                        warn!(chain = chain.as_str(), error = %e, "Failed to read chain head")
+                    }
+                }
+            }
+        }
+
+        // Detect transactions stuck in the mempool before queueing more behind them. A
+        // contract account's nonce doesn't count our transactions.
+        if config.stuck_nonce_age_secs > 0 && !contract_account {
+            for (chain, provider, signer, chain_id) in [
+                (
+                    Chain::L1,
+                    &l1_provider,
+                    &l1_signer,
+                    network.ethereum.chain_id,
+                ),
+                (
+                    Chain::L2,
+                    &l2_provider,
+                    &l2_signer,
+                    network.unichain.chain_id,
+                ),
+            ] {
+                let stuck = match check_stuck_nonce(
+                    provider,
+                    chain,
+                    config.eoa_address,
+                    &mut nonce_gaps,
+                    cycle_start,
+                )
+                .await
+                {
+                    Ok(stuck) => stuck,
+                    Err(e) => {
+                        warn!(chain = chain.as_str(), error = %e, "Failed to check nonce gap");
+                        continue;
+                    }
+                };
+
+                let Some(stuck) = stuck else {
+                    metrics.set_stuck_nonce_gap(chain.as_str(), 0);
+                    continue;
+                };
+
+                error!(
+                    chain = chain.as_str(),
+                    nonce = stuck.nonce,
+                    queued = stuck.gap,
+                    stuck_secs = stuck.age.as_secs(),
+                    "ALERT: transaction stuck in the mempool, later transactions are queued behind it"
+                );
+                metrics.set_stuck_nonce_gap(chain.as_str(), stuck.gap);
+
+                if !config.bump_stuck_transactions || !config.execution_mode.is_live() {
+                    continue;
+                }
+
+                let request = store
+                    .lock()
+                    .await
+                    .sent_transaction(chain_id, stuck.nonce)
+                    .cloned();
+                let Some(request) = request else {
+                    warn!(
+                        chain = chain.as_str(),
+                        nonce = stuck.nonce,
+                        "No recorded request for the stuck nonce, can't bump its fees"
+                    );
+                    continue;
+                };
+
+                match replace_transaction(provider, signer, request, config.audit_log()).await {
+                    Ok(tx_hash) => {
+                        info!(
+                            chain = chain.as_str(),
+                            nonce = stuck.nonce,
+                            tx_hash = %tx_hash,
+                            "Re-sent stuck transaction with bumped fees"
+                        );
+                        nonce_gaps.reset(chain, cycle_start);
+                    }
+                    Err(e) => {
+                        warn!(chain = chain.as_str(), error = %e, "Failed to replace stuck transaction")
+                    }
+                }
+            }
+        }
+
+        // Periodically look for proven withdrawals that fell out of the lookback
+        let recovered = if recovery.is_due(cycle_start) {
+            recovery.record_run(cycle_start);
+            recover_proven_withdrawals(
+                l1_provider.clone(),
+                l2_provider.clone(),
+                &config,
+                &retry_budget,
+                &scan_coverage,
+            )
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = %e, "Failed to scan for withdrawals to recover");
+                vec![]
+            })
+        } else {
+            vec![]
+        };
+
+        // Spot-check withdrawals recorded as finalized, whose status scans no longer read
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+        let verify_due =
+            config.finalized_verify_interval_secs > 0
+                && store.lock().await.finalized_verified_at().is_none_or(|at| {
+                    now >= at.saturating_add(config.finalized_verify_interval_secs)
+                });
+        if verify_finalized_requested || verify_due {
+            verify_finalized_requested = false;
+            let mut store = store.lock().await;
+            match verify_finalized_withdrawals(&l1_provider, &config, &finalized, &mut store, now)
+                .await
+            {
+                Ok(_) => {
+                    if let Err(e) = store.save() {
+                        warn!(error = %e, "Failed to save finalized withdrawals");
+                    }
+                }
+                Err(e) => warn!(error = %e, "Failed to verify finalized withdrawals"),
+            }
+        }
 
         // 1. Process pending withdrawals (finalize + prove)
         let process_result = match process_pending_withdrawals(
@@ -186,10 +546,60 @@ async fn main() -> eyre::Result<()> {
             l2_provider.clone(),
             l1_signer.clone(),
             &config,
+            recovered,
+            &retry_budget,
+            &scan_coverage,
+            &portal_params,
+            &finalized,
+            &layout_check,
+            &withdrawal_log_throttle,
+            &pending_transactions,
+            &regression_guard,
+            config.cycle_deadline(cycle_start),
         )
         .await
         {
-            Ok(_) => StepResult::Ok,
+            Ok(pass) => {
+                summary.proofs_submitted += pass.proofs().len();
+                summary.finalized += pass.finalized();
+                let mut store = store.lock().await;
+                if !pass.proofs().is_empty() || pass.finalized_changed() {
+                    for submitted in pass.proofs() {
+                        store.record_proof(&submitted.withdrawal, submitted.proof);
+                    }
+                    store.set_finalized(&finalized);
+                    if let Err(e) = store.save() {
+                        warn!(error = %e, "Failed to save proof records");
+                    }
+                }
+                drop(store);
+
+                let paused = pass.is_portal_paused();
+                if paused && !portal_paused {
+                    error!("ALERT: OptimismPortal entered paused state, withdrawals are halted");
+                } else if !paused && portal_paused {
+                    info!("OptimismPortal unpaused, resuming withdrawals");
+                }
+                portal_paused = paused;
+                metrics.set_portal_paused(paused);
+                for failed in pass.failed_calls() {
+                    metrics.record_withdrawal_call_failed(failed.chain_id);
+                }
+                metrics.set_withdrawals_dust_skipped(pass.dust_skipped());
+
+                let out_of_gas = pass.insufficient_gas_funds();
+                if let Some(shortage) = out_of_gas {
+                    alert_insufficient_gas_funds(&metrics, Chain::L1, shortage);
+                }
+
+                if out_of_gas.is_some() {
+                    StepResult::Failed
+                } else if paused {
+                    StepResult::Skipped
+                } else {
+                    StepResult::Ok
+                }
+            }
             Err(e) => {
                 warn!(error = %e, "Failed to process pending withdrawals");
                 StepResult::Failed
@@ -204,8 +614,24 @@ async fn main() -> eyre::Result<()> {
         )
         .await
         {
-            Ok(_) => StepResult::Ok,
+            Ok(chunks) => {
+                summary.withdrawals_initiated += chunks
+                    .iter()
+                    .filter(|chunk| chunk.initiated.is_some())
+                    .count();
+                if chunks.iter().any(|chunk| chunk.initiated.is_some()) {
+                    let mut store = store.lock().await;
+                    record_initiations(&mut store, &chunks);
+                    if let Err(e) = store.save() {
+                        warn!(error = %e, "Failed to save initiation records");
+                    }
+                }
+                StepResult::Ok
+            }
             Err(e) => {
+                if let Some(shortage) = e.downcast_ref::<InsufficientGasFunds>() {
+                    alert_insufficient_gas_funds(&metrics, Chain::L2, shortage);
+                }
                 warn!(error = %e, "Failed to check/initiate withdrawal");
                 StepResult::Failed
             }
@@ -217,11 +643,24 @@ async fn main() -> eyre::Result<()> {
             l2_provider.clone(),
             l1_signer.clone(),
             &config,
+            &retry_budget,
+            &scan_coverage,
+            &mut deposit_checkpoint,
+            &deposit_log_throttle,
         )
         .await
         {
-            Ok(_) => StepResult::Ok,
+            Ok(deposited) => {
+                summary.deposits += usize::from(deposited.is_some());
+                StepResult::Ok
+            }
             Err(e) => {
+                if e.downcast_ref::<OutputOutOfBounds>().is_some() {
+                    metrics.record_deposit_output_out_of_bounds();
+                }
+                if let Some(shortage) = e.downcast_ref::<InsufficientGasFunds>() {
+                    alert_insufficient_gas_funds(&metrics, Chain::L1, shortage);
+                }
                 warn!(error = %e, "Failed to check/execute deposit");
                 StepResult::Failed
             }
@@ -234,16 +673,196 @@ async fn main() -> eyre::Result<()> {
             || deposit_result.is_failure();
 
         metrics.record_cycle(!has_failure, cycle_duration);
+        summary.record_cycle(!has_failure);
+
+        if circuit.record(!has_failure) {
+            error!(
+                consecutive_failures = circuit.consecutive_failures(),
+                "Circuit breaker open: halting until restart or SIGUSR1"
+            );
+            metrics.set_circuit_open(true);
+        }
 
         // Update state gauges (balances, in-flight counts)
-        update_metrics(l1_provider.clone(), l2_provider.clone(), &config, &metrics).await;
+        let snapshot = update_metrics(
+            l1_provider.clone(),
+            l2_provider.clone(),
+            &config,
+            &metrics,
+            &retry_budget,
+            &scan_coverage,
+            &mut deposit_checkpoint,
+            &proof_health,
+            &portal_params,
+        )
+        .await;
+        if let Some(snapshots) = snapshots.as_mut() {
+            log_state_changes(snapshots, snapshot);
+        }
+
+        // Report expired deposits and their refunds
+        if let Err(e) = track_deposit_refunds(
+            l1_provider.clone(),
+            l2_provider.clone(),
+            &config,
+            &metrics,
+            &mut refund_tracker,
+            &retry_budget,
+            &scan_coverage,
+            &mut deposit_checkpoint,
+        )
+        .await
+        {
+            warn!(error = %e, "Failed to track expired deposit refunds");
+        }
+
+        // Persist the deposit checkpoint and refund history so a restart resumes them too
+        {
+            let mut store = store.lock().await;
+            store.set_deposit_checkpoint(&deposit_checkpoint);
+            store.set_refund_ledger(refund_tracker.ledger());
+            if let Err(e) = store.save() {
+                warn!(error = %e, "Failed to save deposit checkpoint");
+            }
+        }
+
+        // Record the gas cost of withdrawals finalized since the last run
+        if config.withdrawal_cost_interval_secs > 0 && cost_tracking.is_due(cycle_start) {
+            cost_tracking.record_run(cycle_start);
+            match update_withdrawal_costs(
+                l1_provider.clone(),
+                l2_provider.clone(),
+                &config,
+                &mut *store.lock().await,
+                config.withdrawal_cost_interval_secs * 2,
+            )
+            .await
+            {
+                Ok(recorded) => {
+                    let eth_usd = match &price_oracle {
+                        Some(oracle) if !recorded.is_empty() => {
+                            oracle.eth_usd(&l1_provider, &l2_provider).await
+                        }
+                        _ => None,
+                    };
+                    if let Some(price) = eth_usd {
+                        metrics.set_eth_usd_price(price);
+                    }
+                    for record in &recorded {
+                        if let Some(ratio) = record.cost_ratio() {
+                            metrics.record_withdrawal_cost_ratio(ratio);
+                        }
+                        metrics.record_withdrawal_cost(
+                            record.total_cost_wei(),
+                            record.value,
+                            eth_usd,
+                        );
+                    }
+                }
+                Err(e) => warn!(error = %e, "Failed to record withdrawal costs"),
+            }
+        }
+
+        // Check the recipients of withdrawals finalized since the last run got their value
+        if config.reconcile_interval_secs > 0 && reconciliation.is_due(cycle_start) {
+            reconciliation.record_run(cycle_start);
+            match reconcile_finalized(
+                l1_provider.clone(),
+                l2_provider.clone(),
+                &config,
+                config.reconcile_interval_secs,
+            )
+            .await
+            {
+                Ok(report) => {
+                    for entry in report.mismatches() {
+                        error!(
+                            withdrawal_hash = %entry.withdrawal.hash,
+                            recipient = %entry.withdrawal.recipient,
+                            finalize_tx = %entry.withdrawal.finalize_tx,
+                            result = ?entry.result,
+                            "ALERT: finalized withdrawal's recipient did not receive its value"
+                        );
+                    }
+                    metrics.set_withdrawals_reconciled(
+                        report.matched(),
+                        report.mismatched(),
+                        report.unverifiable(),
+                        report.read_failed(),
+                    );
+                    let mut store = store.lock().await;
+                    store.set_reconciliation(report);
+                    if let Err(e) = store.save() {
+                        warn!(error = %e, "Failed to save reconciliation report");
+                    }
+                }
+                Err(e) => warn!(error = %e, "Failed to reconcile finalized withdrawals"),
+            }
+        }
+
+        // Check SpokePool logs still decode: an upgrade changing event signatures would
+        // otherwise make every deposit look filled
+        if config.event_audit_interval_secs > 0 && event_audit.is_due(cycle_start) {
+            event_audit.record_run(cycle_start);
+            match audit_event_decoding(&l1_provider, &l2_provider, &config).await {
+                Ok(audits) => {
+                    for audit in audits {
+                        let report = audit.report;
+                        if audit.alert {
+                            error!(
+                                chain = audit.chain.as_str(),
+                                spoke_pool = %audit.spoke_pool,
+                                from_block = audit.from_block,
+                                to_block = audit.to_block,
+                                undecodable = report.undecodable,
+                                total = report.total(),
+                                undecodable_bps = report.undecodable_bps(),
+                                "ALERT: SpokePool logs no longer decode, event signatures may have changed"
+                            );
+                            metrics.record_event_decode_failures(
+                                audit.chain.as_str(),
+                                report.undecodable,
+                            );
+                        } else {
+                            info!(
+                                chain = audit.chain.as_str(),
+                                deposits = report.deposits,
+                                fills = report.fills,
+                                other = report.other,
+                                undecodable = report.undecodable,
+                                "SpokePool event audit passed"
+                            );
+                        }
+                    }
+                }
+                Err(e) => warn!(error = %e, "Failed to audit SpokePool event decoding"),
+            }
+        }
+
+        // Scans stop retrying once the budget is spent; flag the cycle instead
+        let degraded = retry_budget.is_exhausted();
+        metrics.record_retry_budget(retry_budget.consumed(), degraded);
+        metrics.record_scan_coverage(scan_coverage.totals());
+        if degraded {
+            warn!(
+                retries = retry_budget.consumed(),
+                "RPC retry budget exhausted, cycle degraded"
+            );
+        }
 
         // Log cycle summary
-        let dry_run_marker = if config.dry_run { " [DRY-RUN]" } else { "" };
+        let mode_marker = match config.execution_mode {
+            ExecutionMode::Live => "",
+            ExecutionMode::Simulate => " [SIMULATE]",
+            ExecutionMode::LogOnly => " [DRY-RUN]",
+        };
+        // Without a metrics server the logs are the only record of the cycle
+        let metrics_marker = if metrics_running { "" } else { " [NO METRICS]" };
         info!(
-            "Cycle {}{} completed in {:.1}s: process_withdrawals={}, initiate_withdrawal={}, deposit={}",
+            "Cycle {}{}{} completed in {:.1}s: process_withdrawals={}, initiate_withdrawal={}, deposit={}",
             cycle_number,
-            dry_run_marker,
+            mode_marker,
+            metrics_marker,
             cycle_duration.as_secs_f64(),
             process_result.as_str(),
             initiate_result.as_str(),
@@ -257,5 +876,224 @@ async fn main() -> eyre::Result<()> {
         }
     }
 
+    let mut store = store.lock().await;
+    store.set_deposit_checkpoint(&deposit_checkpoint);
+    shutdown(&store, &metrics, &summary, started.elapsed());
+
+    Ok(())
+}
+
+/// Log the changes from the previous cycle's snapshot to `snapshot`.
+fn log_state_changes(snapshots: &mut SnapshotDiff, snapshot: StateSnapshot) {
+    let Some(changes) = snapshots.record(snapshot) else {
+        info!("Recorded first state snapshot, changes are logged from the next cycle");
+        return;
+    };
+    if changes.is_empty() {
+        debug!("No state changes since the previous cycle");
+    }
+    for change in changes {
+        info!(%change, "State changed since the previous cycle");
+    }
+}
+
+/// Set a flag once SIGINT or SIGTERM is received, so the current cycle can complete.
+fn spawn_shutdown_listener() -> Arc<AtomicBool> {
+    let shutdown_requested = Arc::new(AtomicBool::new(false));
+    let shutdown_flag = shutdown_requested.clone();
+
+    tokio::spawn(async move {
+        let mut sigint =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt()).unwrap();
+        let mut sigterm =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()).unwrap();
+
+        tokio::select! {
+            _ = sigint.recv() => {
+                info!("Received shutdown signal, completing current cycle...");
+            }
+            _ = sigterm.recv() => {
+                info!("Received shutdown signal, completing current cycle...");
+            }
+        }
+
+        shutdown_flag.store(true, Ordering::SeqCst);
+    });
+
+    shutdown_requested
+}
+
+/// Wait for the next tick of `interval`. Returns false if shutdown was requested instead.
+async fn next_tick(interval: &mut time::Interval, shutdown_requested: &AtomicBool) -> bool {
+    tokio::select! {
+        _ = interval.tick() => {}
+        _ = async {
+            while !shutdown_requested.load(Ordering::SeqCst) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        } => {}
+    }
+
+    // Check again in case we woke up from interval but shutdown was requested
+    !shutdown_requested.load(Ordering::SeqCst)
+}
+
+/// Monitoring-only loop: refresh the state gauges and report expired deposits every
+/// cycle, without a signer and without attempting any action.
+async fn watch_only(mut config: Config, snapshot_diff: bool) -> eyre::Result<()> {
+    let network = config.network_config();
+
+    warn!("=== WATCH-ONLY MODE: No actions will be attempted ===");
+    info!("Loaded config:");
+    info!("  Network: {:?}", config.network);
+    info!("  L2 SpokePool: {}", network.unichain.spoke_pool);
+    info!("  L1 Portal: {}", network.unichain.l1_portal);
+    info!("  EOA: {}", config.eoa_address);
+    info!("  Cycle interval: {}s", config.cycle_interval_secs);
+    info!("  Metrics port: {}", config.metrics_port);
+
+    info!("Starting metrics server on port {}...", config.metrics_port);
+    let metrics_running =
+        start_prometheus_exporter(config.metrics_port, config.metrics_required).await?;
+    let metrics = Metrics::new();
+
+    let l1_provider =
+        client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+    let l2_provider =
+        client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+
+    verify_contract_code(&l1_provider, &l2_provider, &network).await?;
+    info!("Configured contract addresses verified");
+    check_block_times(&l1_provider, &l2_provider, &mut config, &metrics).await;
+
+    let shutdown_requested = spawn_shutdown_listener();
+
+    info!("Starting watch loop...");
+
+    let started = Instant::now();
+    let mut interval = time::interval(Duration::from_secs(config.cycle_interval_secs));
+    let mut cycle_number: u64 = 0;
+    // Resume the deposit scan and refund history from the state file, which may belong
+    // to a live instance, but keep them in memory
+    let store = StateStore::load(&config.state_path)?;
+    let mut refund_tracker = DepositRefundTracker::new().with_ledger(store.refund_ledger());
+    let mut deposit_checkpoint = store.deposit_checkpoint();
+    let proof_health = ProofHealthTracker::default();
+    let portal_params =
+        PortalParamsCache::new(Duration::from_secs(config.portal_params_refresh_secs));
+    let mut snapshots = snapshot_diff.then(SnapshotDiff::default);
+    let retry_budget = config.retry_budget();
+    let scan_coverage = ScanCoverage::default();
+
+    loop {
+        if !next_tick(&mut interval, &shutdown_requested).await {
+            info!("Shutdown signal received, exiting immediately");
+            break;
+        }
+
+        cycle_number += 1;
+        let cycle_start = Instant::now();
+        retry_budget.reset();
+        scan_coverage.reset();
+
+        let snapshot = update_metrics(
+            l1_provider.clone(),
+            l2_provider.clone(),
+            &config,
+            &metrics,
+            &retry_budget,
+            &scan_coverage,
+            &mut deposit_checkpoint,
+            &proof_health,
+            &portal_params,
+        )
+        .await;
+        if let Some(snapshots) = snapshots.as_mut() {
+            log_state_changes(snapshots, snapshot);
+        }
+
+        let refunds_tracked = match track_deposit_refunds(
+            l1_provider.clone(),
+            l2_provider.clone(),
+            &config,
+            &metrics,
+            &mut refund_tracker,
+            &retry_budget,
+            &scan_coverage,
+            &mut deposit_checkpoint,
+        )
+        .await
+        {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(error = %e, "Failed to track expired deposit refunds");
+                false
+            }
+        };
+
+        let cycle_duration = cycle_start.elapsed();
+        metrics.record_cycle(refunds_tracked, cycle_duration);
+
+        let degraded = retry_budget.is_exhausted();
+        metrics.record_retry_budget(retry_budget.consumed(), degraded);
+        metrics.record_scan_coverage(scan_coverage.totals());
+        if degraded {
+            warn!(
+                retries = retry_budget.consumed(),
+                "RPC retry budget exhausted, cycle degraded"
+            );
+        }
+
+        let metrics_marker = if metrics_running { "" } else { " [NO METRICS]" };
+        info!(
+            "Cycle {} [WATCH-ONLY]{} completed in {:.1}s",
+            cycle_number,
+            metrics_marker,
+            cycle_duration.as_secs_f64(),
+        );
+
+        if shutdown_requested.load(Ordering::SeqCst) {
+            info!("Cycle completed, shutting down gracefully");
+            break;
+        }
+    }
+
+    metrics.record_shutdown(started.elapsed());
+    info!(
+        cycles = cycle_number,
+        uptime_secs = started.elapsed().as_secs(),
+        "Shutdown complete"
+    );
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allow_address_mismatch_flag() {
+        let cli = Cli::try_parse_from(["orchestrator"]).unwrap();
+        assert!(!cli.allow_address_mismatch);
+
+        let cli = Cli::try_parse_from(["orchestrator", "--allow-address-mismatch"]).unwrap();
+        assert!(cli.allow_address_mismatch);
+    }
+
+    #[test]
+    fn test_watch_only_flag() {
+        let cli = Cli::try_parse_from(["orchestrator"]).unwrap();
+        assert!(!cli.watch_only);
+
+        let cli = Cli::try_parse_from(["orchestrator", "--watch-only"]).unwrap();
+        assert!(cli.watch_only);
+
+        // Nothing is executed, so an execution mode makes no sense
+        assert!(Cli::try_parse_from(["orchestrator", "--watch-only", "--dry-run"]).is_err());
+
+        // Diffing snapshots works in watch-only mode too
+        let cli = Cli::try_parse_from(["orchestrator", "--watch-only", "--snapshot-diff"]).unwrap();
+        assert!(cli.snapshot_diff);
+    }
+}
@@ -3,14 +3,56 @@
 //! This binary allows running each main loop step independently:
 //! - `process-withdrawals`: Process pending L2→L1 withdrawals (prove + finalize)
 //! - `initiate-withdrawal`: Check L2 EOA balance and initiate withdrawal if threshold met
+//! - `drain`: Initiate several fixed-size withdrawals back to back, or with `--confirm` move
+//!   every fund home to L1 for decommissioning
 //! - `deposit`: Check SpokePool balance and deposit from L1 if needed
+//! - `audit`: Cross-check L1 portal events against L2 withdrawal initiations
+//! - `replay`: Simulate a saved transaction request and decode its revert reason
+//! - `backfill`: Discover a withdrawal backlog into the state store and process it
+//! - `report`: Print the gas cost of finalized withdrawals relative to their value
+//! - `status`: List withdrawals in the state store with the dispute game they were proven against,
+//!   every proof submitted for them and the gas their initiation cost
+//! - `wait`: Block until a withdrawal is finalized on L1
 
-use clap::{Parser, Subcommand};
-use client::local_signer_fn;
+use alloy_primitives::{utils::format_ether, TxHash, B256, U256};
+use alloy_rpc_types_eth::BlockId;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use client::{coverage::ScanCoverage, retry::RetryBudget, LocalSigner, PrivateKey};
 use orchestrator::{
-    config::Config, maybe_deposit, maybe_initiate_withdrawal, process_pending_withdrawals,
+    audit::{audit_withdrawals, Discrepancy},
+    backfill::{refresh_store, run_backfill},
+    config::{Config, ExecutionMode},
+    dispute_game_status,
+    drain::{check_drain_network, is_confirmed, plan_drain, run_drain},
+    initiate_withdrawal_chunks, maybe_deposit, maybe_initiate_withdrawal,
+    process_pending_withdrawals, proof_provenance,
+    reconcile::reconcile_finalized,
+    record_initiations,
+    recovery::recover_proven_withdrawals,
+    reject_secret_argument,
+    reorg::RegressionGuard,
+    replay::{load_transaction_request, replay_transaction},
+    report::{
+        initiation_record, parse_duration_secs, render_csv, render_table, update_withdrawal_costs,
+    },
+    store::{RecordStatus, StateStore},
+    throttle::ThrottledLogger,
+};
+use std::{
+    io::Write,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+use tracing_subscriber::fmt::format::FmtSpan;
+use withdrawal::{
+    game::status_name,
+    portal::PortalParamsCache,
+    proof::StorageLayoutCheck,
+    state::WithdrawalStateProvider,
+    wait::{wait_for_finalization, withdrawal_hash_of_tx, FinalizationOutcome},
 };
-use tracing::info;
 
 #[derive(Parser)]
 #[command(name = "step")]
@@ -20,14 +62,22 @@ struct Cli {
     #[arg(short, long, default_value = "config.toml")]
     config: String,
 
-    /// Private key for signing transactions (hex string, with or without 0x prefix)
-    #[arg(short = 'k', long, env = "PRIVATE_KEY")]
-    private_key: String,
+    /// Private key for signing transactions (hex string, with or without 0x prefix).
+    /// Required by the subcommands that send transactions. Only read from the
+    /// environment: passing it on the command line is rejected
+    #[arg(short = 'k', long, env = "PRIVATE_KEY", hide_env_values = true)]
+    private_key: Option<PrivateKey>,
 
-    /// Dry-run mode: log actions without executing transactions
+    /// Dry-run mode: log actions without executing transactions. Same as
+    /// `--execution-mode log-only`
     #[arg(long, env = "DRY_RUN")]
     dry_run: bool,
 
+    /// Submit transactions (live), eth_call them without submitting (simulate) or only
+    /// log them (log-only). Overrides execution_mode from the config
+    #[arg(long, env = "EXECUTION_MODE", conflicts_with = "dry_run")]
+    execution_mode: Option<ExecutionMode>,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -40,8 +90,128 @@ enum Command {
     /// Check L2 EOA balance and initiate withdrawal if threshold met
     InitiateWithdrawal,
 
+    /// Initiate up to `--max-chunks` withdrawals of exactly `--chunk-wei` each, or with
+    /// `--confirm` move every fund home to L1
+    Drain {
+        /// Size of each withdrawal in wei (defaults to withdrawal_chunk_wei)
+        #[arg(long, conflicts_with = "confirm")]
+        chunk_wei: Option<U256>,
+
+        /// Most withdrawals to initiate (defaults to max_withdrawal_chunks)
+        #[arg(long, conflicts_with = "confirm")]
+        max_chunks: Option<usize>,
+
+        /// Decommission: ignoring thresholds, claim relayer refunds, withdraw the whole
+        /// L2 balance and prove/finalize until L1 has received everything. Prints the
+        /// plan and asks before sending anything
+        #[arg(long)]
+        confirm: bool,
+
+        /// Don't ask before draining
+        #[arg(long, requires = "confirm")]
+        yes: bool,
+
+        /// Allow draining a mainnet environment
+        #[arg(long = "i-know-what-im-doing", requires = "confirm")]
+        i_know_what_im_doing: bool,
+
+        /// How long to wait for the last finalize, e.g. 8d, 12h or a number of seconds
+        #[arg(long, default_value = "8d", value_parser = parse_duration_secs, requires = "confirm")]
+        timeout: u64,
+
+        /// Time between prove/finalize passes, e.g. 5m or a number of seconds
+        #[arg(long, default_value = "5m", value_parser = parse_duration_secs, requires = "confirm")]
+        poll_interval: u64,
+    },
+
     /// Check SpokePool balance and deposit from L1 if needed
     Deposit,
+
+    /// Cross-check L1 proven/finalized events against L2 withdrawal initiations
+    Audit,
+
+    /// Replay a saved transaction request with eth_call and decode the revert reason
+    Replay {
+        /// Path to a JSON-encoded TransactionRequest
+        tx: PathBuf,
+
+        /// Chain to replay against
+        #[arg(long, value_enum, default_value_t = Chain::L1)]
+        chain: Chain,
+
+        /// Block number to simulate at (defaults to latest)
+        #[arg(long)]
+        block: Option<u64>,
+    },
+
+    /// Scan the withdrawal lookback into the state store once, then prove/finalize from it
+    Backfill {
+        /// Rescan the full lookback instead of resuming from the store's checkpoint
+        #[arg(long)]
+        rescan: bool,
+    },
+
+    /// Report the gas cost of finalized withdrawals as a fraction of the value moved
+    Report {
+        /// How far back to report on, e.g. 30d, 12h or a number of seconds
+        #[arg(long, default_value = "30d", value_parser = parse_duration_secs)]
+        since: u64,
+
+        /// Print CSV instead of a table
+        #[arg(long)]
+        csv: bool,
+    },
+
+    /// Check that the recipients of finalized withdrawals received their value on L1, and
+    /// save the report in the state store
+    Reconcile {
+        /// How far back to reconcile, e.g. 7d, 12h or a number of seconds
+        #[arg(long, default_value = "7d", value_parser = parse_duration_secs)]
+        since: u64,
+    },
+
+    /// List withdrawals in the state store with their dispute game and its current status,
+    /// and for proven ones every proof submitted and the game it was proven against
+    Status {
+        /// Include finalized withdrawals
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Block until a withdrawal is finalized on L1; exits with an error unless it succeeded
+    Wait {
+        /// L2 transaction that initiated the withdrawal
+        #[arg(long, required_unless_present = "hash", conflicts_with = "hash")]
+        tx: Option<TxHash>,
+
+        /// Withdrawal hash, instead of the initiating transaction
+        #[arg(long)]
+        hash: Option<B256>,
+
+        /// How long to wait, e.g. 8d, 12h or a number of seconds
+        #[arg(long, default_value = "8d", value_parser = parse_duration_secs)]
+        timeout: u64,
+
+        /// Time between status checks, e.g. 5m or a number of seconds
+        #[arg(long, default_value = "60", value_parser = parse_duration_secs)]
+        poll_interval: u64,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Chain {
+    L1,
+    L2,
+}
+
+/// Signer for the subcommands that send transactions, from `PRIVATE_KEY`.
+fn local_signer(private_key: Option<&PrivateKey>) -> eyre::Result<Arc<LocalSigner>> {
+    let private_key = private_key.ok_or_else(|| {
+        eyre::eyre!("PRIVATE_KEY must be set for this step, which signs transactions")
+    })?;
+    Ok(Arc::new(LocalSigner::from_private_key(
+        private_key.expose_secret(),
+    )?))
 }
 
 #[tokio::main]
@@ -51,14 +221,25 @@ async fn main() -> eyre::Result<()> {
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
         )
+        // With trace_rpc, log how long each RPC span took when it closes
+        .with_span_events(if cfg!(feature = "trace_rpc") {
+            FmtSpan::CLOSE
+        } else {
+            FmtSpan::NONE
+        })
         .init();
 
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    reject_secret_argument(&matches, "private_key")?;
+    let cli = Cli::from_arg_matches(&matches)?;
     let mut config = Config::from_file(&cli.config)?;
 
-    // Override dry_run from CLI flag
+    // Override the execution mode from CLI flags
     if cli.dry_run {
-        config.dry_run = true;
+        config.execution_mode = ExecutionMode::LogOnly;
+    }
+    if let Some(mode) = cli.execution_mode {
+        config.execution_mode = mode;
     }
 
     let network = config.network_config();
@@ -68,49 +249,233 @@ async fn main() -> eyre::Result<()> {
     info!("  L2 SpokePool: {}", network.unichain.spoke_pool);
     info!("  L1 Portal: {}", network.unichain.l1_portal);
     info!("  EOA: {}", config.eoa_address);
-    if config.dry_run {
-        info!("  Mode: DRY-RUN (no transactions will be executed)");
+    match config.execution_mode {
+        ExecutionMode::Live => {}
+        ExecutionMode::Simulate => {
+            info!("  Mode: SIMULATE (transactions will be simulated, not submitted)");
+        }
+        ExecutionMode::LogOnly => {
+            info!("  Mode: DRY-RUN (no transactions will be executed)");
+        }
     }
 
     match cli.command {
         Command::ProcessWithdrawals => {
             info!("Running: process-withdrawals");
 
-            let l1_provider = client::create_provider(&config.l1_rpc_url).await?;
-            let l2_provider = client::create_provider(&config.l2_rpc_url).await?;
-            let l1_signer = local_signer_fn(&cli.private_key)?;
+            let l1_provider =
+                client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+            let l1_signer = local_signer(cli.private_key.as_ref())?;
 
-            process_pending_withdrawals(l1_provider, l2_provider, l1_signer, &config).await?;
+            let retry_budget = RetryBudget::unlimited();
+            let scan_coverage = ScanCoverage::default();
+            let mut store = StateStore::load(&config.state_path)?;
+            let finalized = store.finalized_set();
+            let pending_transactions = store.pending_transactions();
+
+            let recovered = recover_proven_withdrawals(
+                l1_provider.clone(),
+                l2_provider.clone(),
+                &config,
+                &retry_budget,
+                &scan_coverage,
+            )
+            .await?;
+            let pass = process_pending_withdrawals(
+                l1_provider,
+                l2_provider,
+                l1_signer,
+                &config,
+                recovered,
+                &retry_budget,
+                &scan_coverage,
+                &PortalParamsCache::new(Duration::from_secs(config.portal_params_refresh_secs)),
+                &finalized,
+                &StorageLayoutCheck::default(),
+                &ThrottledLogger::default(),
+                &pending_transactions,
+                &RegressionGuard::default(),
+                config.cycle_deadline(Instant::now()),
+            )
+            .await;
+
+            // Saved even if processing failed, so what was broadcast isn't sent again
+            store.set_pending_transactions(pending_transactions.entries());
+            store.set_finalized(&finalized);
+            if let Ok(pass) = &pass {
+                for submitted in pass.proofs() {
+                    store.record_proof(&submitted.withdrawal, submitted.proof);
+                }
+            }
+            store.save()?;
+            pass?;
 
             info!("Step completed: process-withdrawals");
         }
         Command::InitiateWithdrawal => {
             info!("Running: initiate-withdrawal");
 
-            let l2_provider = client::create_provider(&config.l2_rpc_url).await?;
-            let l2_signer = local_signer_fn(&cli.private_key)?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+            let l2_signer = local_signer(cli.private_key.as_ref())?;
 
-            let result = maybe_initiate_withdrawal(l2_provider, l2_signer, &config).await?;
+            let chunks = maybe_initiate_withdrawal(l2_provider, l2_signer, &config).await?;
 
-            match result {
-                Some(amount) => {
-                    info!(amount = %alloy_primitives::utils::format_ether(amount), "Withdrawal initiated");
+            if chunks.is_empty() {
+                info!("No withdrawal initiated (threshold not met or nothing to withdraw)");
+            } else {
+                let total = chunks.iter().fold(U256::ZERO, |sum, c| sum + c.amount);
+                info!(amount = %format_ether(total), "Withdrawal initiated");
+            }
+            if chunks.iter().any(|c| c.initiated.is_some()) {
+                let mut store = StateStore::load(&config.state_path)?;
+                record_initiations(&mut store, &chunks);
+                store.save()?;
+            }
+
+            info!("Step completed: initiate-withdrawal");
+        }
+        Command::Drain {
+            confirm: true,
+            yes,
+            i_know_what_im_doing,
+            timeout,
+            poll_interval,
+            ..
+        } => {
+            info!("Running: drain --confirm");
+            check_drain_network(config.network, i_know_what_im_doing)?;
+
+            let l1_provider =
+                client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+            let signer = local_signer(cli.private_key.as_ref())?;
+            let mut store = StateStore::load(&config.state_path)?;
+
+            // Find every withdrawal still pending before planning
+            let state_provider = WithdrawalStateProvider::new(
+                l1_provider.clone(),
+                l2_provider.clone(),
+                network.unichain.l1_portal,
+                network.unichain.l2_to_l1_message_passer,
+            );
+            // A dry run leaves the state file as it was
+            refresh_store(&state_provider, &l2_provider, &config, &mut store, false).await?;
+            if config.execution_mode == ExecutionMode::Live {
+                store.save()?;
+            }
+            let plan = plan_drain(&l1_provider, &l2_provider, &config, &store).await?;
+            print!("{plan}");
+
+            if plan.is_empty() {
+                println!("Nothing to drain");
+            } else if config.execution_mode != ExecutionMode::Live {
+                println!("Dry run: nothing sent");
+            } else {
+                if !yes {
+                    print!("Type 'yes' to drain: ");
+                    std::io::stdout().flush()?;
+                    let mut answer = String::new();
+                    std::io::stdin().read_line(&mut answer)?;
+                    if !is_confirmed(&answer) {
+                        eyre::bail!("Drain not confirmed");
+                    }
                 }
-                None => {
-                    info!("No withdrawal initiated (threshold not met or nothing to withdraw)");
+
+                let outcome = run_drain(
+                    l1_provider,
+                    l2_provider,
+                    signer,
+                    &config,
+                    &mut store,
+                    &plan,
+                    Duration::from_secs(timeout),
+                    Duration::from_secs(poll_interval),
+                    |progress| {
+                        println!(
+                            "{} to prove, {} to finalize, {} ETH on its way",
+                            progress.initiated,
+                            progress.proven,
+                            format_ether(progress.value)
+                        )
+                    },
+                )
+                .await?;
+                println!(
+                    "Drain complete: {} ETH moved to L1 ({} ETH refund claimed, {} ETH \
+                     finalized)",
+                    format_ether(outcome.total()),
+                    format_ether(outcome.refund_claimed),
+                    format_ether(outcome.finalized)
+                );
+            }
+
+            info!("Step completed: drain");
+        }
+        Command::Drain {
+            chunk_wei,
+            max_chunks,
+            ..
+        } => {
+            info!("Running: drain");
+
+            let chunk = chunk_wei.unwrap_or(config.withdrawal_chunk_wei);
+            if chunk == U256::ZERO {
+                eyre::bail!("No chunk size: pass --chunk-wei or set withdrawal_chunk_wei");
+            }
+            let max_chunks = max_chunks.unwrap_or(config.max_withdrawal_chunks);
+
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+            let l2_signer = local_signer(cli.private_key.as_ref())?;
+
+            let chunks =
+                initiate_withdrawal_chunks(l2_provider, l2_signer, &config, chunk, max_chunks)
+                    .await?;
+
+            for c in &chunks {
+                match &c.initiated {
+                    Some(initiated) => {
+                        println!("{}\t{}", format_ether(c.amount), initiated.tx_hash)
+                    }
+                    None => println!("{}\t(dry-run)", format_ether(c.amount)),
                 }
             }
+            if chunks.iter().any(|c| c.initiated.is_some()) {
+                let mut store = StateStore::load(&config.state_path)?;
+                record_initiations(&mut store, &chunks);
+                store.save()?;
+            }
 
-            info!("Step completed: initiate-withdrawal");
+            info!(initiated = chunks.len(), "Step completed: drain");
         }
         Command::Deposit => {
             info!("Running: deposit");
 
-            let l1_provider = client::create_provider(&config.l1_rpc_url).await?;
-            let l2_provider = client::create_provider(&config.l2_rpc_url).await?;
-            let l1_signer = local_signer_fn(&cli.private_key)?;
+            let l1_provider =
+                client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+            let l1_signer = local_signer(cli.private_key.as_ref())?;
+            let mut store = StateStore::load(&config.state_path)?;
+            let mut checkpoint = store.deposit_checkpoint();
 
-            let result = maybe_deposit(l1_provider, l2_provider, l1_signer, &config).await?;
+            let result = maybe_deposit(
+                l1_provider,
+                l2_provider,
+                l1_signer,
+                &config,
+                &RetryBudget::unlimited(),
+                &ScanCoverage::default(),
+                &mut checkpoint,
+                &ThrottledLogger::default(),
+            )
+            .await?;
+            store.set_deposit_checkpoint(&checkpoint);
+            store.save()?;
 
             match result {
                 Some(amount) => {
@@ -123,6 +488,322 @@ async fn main() -> eyre::Result<()> {
 
             info!("Step completed: deposit");
         }
+        Command::Audit => {
+            info!("Running: audit");
+
+            let l1_provider =
+                client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+
+            let report = audit_withdrawals(l1_provider, l2_provider, &config).await?;
+
+            info!(
+                initiated = report.initiated,
+                proven = report.proven,
+                finalized = report.finalized,
+                discrepancies = report.discrepancies.len(),
+                "Audit summary"
+            );
+
+            for discrepancy in &report.discrepancies {
+                match discrepancy {
+                    Discrepancy::ProvenButUnknown { hash, tx_hash } => {
+                        warn!(
+                            withdrawal_hash = %hash,
+                            tx_hash = %tx_hash,
+                            "Proven on L1 but no matching L2 initiation in lookback"
+                        );
+                    }
+                    Discrepancy::FinalizedWithFailure { hash, tx_hash } => {
+                        warn!(
+                            withdrawal_hash = %hash,
+                            tx_hash = %tx_hash,
+                            "Finalized on L1 with success=false"
+                        );
+                    }
+                }
+            }
+
+            info!("Step completed: audit");
+        }
+        Command::Replay { tx, chain, block } => {
+            info!("Running: replay");
+
+            let request = load_transaction_request(&tx)?;
+            let rpc_url = match chain {
+                Chain::L1 => &config.l1_rpc_url,
+                Chain::L2 => &config.l2_rpc_url,
+            };
+            let provider = client::create_provider(rpc_url, config.provider_timeouts()).await?;
+            let block = block.map_or_else(BlockId::latest, BlockId::number);
+
+            let outcome = replay_transaction(&provider, request, block).await?;
+            info!(block = ?block, "Replay result: {}", outcome.describe());
+
+            info!("Step completed: replay");
+        }
+        Command::Backfill { rescan } => {
+            info!("Running: backfill");
+
+            let l1_provider =
+                client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+            let l1_signer = local_signer(cli.private_key.as_ref())?;
+            let mut store = StateStore::load(&config.state_path)?;
+
+            let summary = run_backfill(
+                l1_provider,
+                l2_provider,
+                l1_signer,
+                &config,
+                &mut store,
+                rescan,
+            )
+            .await?;
+
+            info!(
+                discovered = summary.discovered,
+                known = summary.known,
+                pending = summary.pending,
+                state_path = %config.state_path,
+                "Backfill summary"
+            );
+
+            info!("Step completed: backfill");
+        }
+        Command::Report { since, csv } => {
+            info!("Running: report");
+
+            let l1_provider =
+                client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+            let mut store = StateStore::load(&config.state_path)?;
+
+            update_withdrawal_costs(l1_provider, l2_provider, &config, &mut store, since).await?;
+
+            let cutoff = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs()
+                .saturating_sub(since);
+            let records = store.withdrawal_costs_since(cutoff);
+            if csv {
+                print!("{}", render_csv(&records));
+            } else {
+                print!("{}", render_table(&records));
+            }
+
+            info!("Step completed: report");
+        }
+        Command::Reconcile { since } => {
+            info!("Running: reconcile");
+
+            let l1_provider =
+                client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+            let mut store = StateStore::load(&config.state_path)?;
+
+            let report = reconcile_finalized(l1_provider, l2_provider, &config, since).await?;
+            print!("{}", report.render());
+            let mismatched = report.mismatched();
+            store.set_reconciliation(report);
+            store.save()?;
+
+            if mismatched > 0 {
+                warn!(
+                    mismatched,
+                    "Finalized withdrawals whose recipient did not receive the value"
+                );
+            }
+            info!("Step completed: reconcile");
+        }
+        Command::Status { all } => {
+            info!("Running: status");
+
+            let l1_provider =
+                client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+            let mut store = StateStore::load(&config.state_path)?;
+            let message_passer = config.network_config().unichain.l2_to_l1_message_passer;
+            let state_provider = WithdrawalStateProvider::new(
+                l1_provider.clone(),
+                l2_provider.clone(),
+                network.unichain.l1_portal,
+                message_passer,
+            );
+            let factories = config.dispute_game_factories();
+
+            let records: Vec<_> = store.withdrawals().into_iter().cloned().collect();
+            for record in records {
+                let (state, status_proxy, submitter) = match record.status {
+                    RecordStatus::Initiated => ("initiated", None, None),
+                    RecordStatus::Proven {
+                        dispute_game_proxy,
+                        proof_submitter,
+                        ..
+                    } => ("proven", Some(dispute_game_proxy), Some(proof_submitter)),
+                    RecordStatus::Finalized if !all => continue,
+                    RecordStatus::Finalized => ("finalized", None, None),
+                };
+
+                // Prefer the game of the proof finalize will use, which may be another
+                // submitter's; fall back to the game we proved against
+                let proxy = status_proxy
+                    .filter(|proxy| !proxy.is_zero())
+                    .or(record.proof.map(|proof| proof.dispute_game_proxy))
+                    .filter(|proxy| !proxy.is_zero());
+                // Anyone may prove a withdrawal, so list every proof and its game
+                let provenance = if matches!(record.status, RecordStatus::Proven { .. }) {
+                    proof_provenance(
+                        &state_provider,
+                        &l1_provider,
+                        &factories,
+                        record.hash,
+                        config.eoa_address,
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!(withdrawal_hash = %record.hash, error = %e, "Failed to read proofs");
+                        vec![]
+                    })
+                } else {
+                    vec![]
+                };
+                // Our own proofs record the game index; others' are looked up
+                let game_index = record
+                    .proof
+                    .filter(|proof| Some(proof.dispute_game_proxy) == proxy)
+                    .map(|proof| proof.dispute_game_index)
+                    .or_else(|| {
+                        provenance
+                            .iter()
+                            .find(|p| Some(p.proof.dispute_game_proxy) == proxy)
+                            .and_then(|p| p.game_index)
+                    })
+                    .map_or_else(|| "-".to_string(), |index| index.to_string());
+                let submitter = submitter
+                    .filter(|submitter| !submitter.is_zero())
+                    .map_or_else(|| "-".to_string(), |submitter| submitter.to_string());
+                let (game_proxy, game_status) = match proxy {
+                    Some(proxy) => (
+                        proxy.to_string(),
+                        dispute_game_status(l1_provider.clone(), proxy)
+                            .await
+                            .unwrap_or_else(|e| {
+                                warn!(game = %proxy, error = %e, "Failed to read dispute game status");
+                                "unknown"
+                            }),
+                    ),
+                    None => ("-".to_string(), "-"),
+                };
+
+                // Withdrawals initiated outside this instance are looked up on L2. Status
+                // only reads the state file, so they are looked up again next time.
+                let initiation = initiation_record(
+                    &l2_provider,
+                    message_passer,
+                    &mut store,
+                    record.hash,
+                    None,
+                )
+                .await
+                .unwrap_or_else(|e| {
+                    warn!(withdrawal_hash = %record.hash, error = %e, "Failed to look up initiation");
+                    None
+                });
+                let (initiation_tx, initiation_gas_used, initiation_gas_price) = initiation
+                    .map_or_else(
+                        || ("-".to_string(), "-".to_string(), "-".to_string()),
+                        |initiation| {
+                            (
+                                initiation.tx_hash.to_string(),
+                                initiation.gas_used.to_string(),
+                                initiation.effective_gas_price.to_string(),
+                            )
+                        },
+                    );
+
+                println!(
+                    "{} l2_block={} state={} proof_submitter={} game_index={} game_proxy={} game_status={} initiation_tx={} initiation_gas_used={} initiation_gas_price={}",
+                    record.hash,
+                    record.l2_block,
+                    state,
+                    submitter,
+                    game_index,
+                    game_proxy,
+                    game_status,
+                    initiation_tx,
+                    initiation_gas_used,
+                    initiation_gas_price
+                );
+                for entry in &provenance {
+                    println!(
+                        "  proof submitter={} game_index={} game_proxy={} game_type={} game_status={} proven_at={}",
+                        entry.proof.submitter,
+                        entry
+                            .game_index
+                            .map_or_else(|| "-".to_string(), |index| index.to_string()),
+                        entry.proof.dispute_game_proxy,
+                        entry.proof.game_type,
+                        status_name(entry.proof.game_status),
+                        entry.proof.timestamp
+                    );
+                }
+            }
+
+            info!("Step completed: status");
+        }
+        Command::Wait {
+            tx,
+            hash,
+            timeout,
+            poll_interval,
+        } => {
+            info!("Running: wait");
+
+            let l1_provider =
+                client::create_provider(&config.l1_rpc_url, config.provider_timeouts()).await?;
+            let l2_provider =
+                client::create_provider(&config.l2_rpc_url, config.provider_timeouts()).await?;
+
+            let hash = match (hash, tx) {
+                (Some(hash), _) => hash,
+                (None, Some(tx)) => withdrawal_hash_of_tx(&l2_provider, tx).await?,
+                (None, None) => eyre::bail!("Pass --tx or --hash"),
+            };
+            info!(withdrawal_hash = %hash, timeout_secs = timeout, "Waiting for finalization");
+
+            let outcome = wait_for_finalization(
+                l1_provider,
+                l2_provider,
+                network.unichain.l1_portal,
+                hash,
+                Duration::from_secs(poll_interval),
+                Duration::from_secs(timeout),
+                |stage| info!(withdrawal_hash = %hash, stage = ?stage, "Withdrawal progressed"),
+            )
+            .await?;
+
+            match outcome {
+                FinalizationOutcome::Succeeded { tx_hash } => {
+                    println!("{hash}\tfinalized\t{tx_hash}");
+                }
+                FinalizationOutcome::Failed { tx_hash } => {
+                    println!("{hash}\tfailed\t{tx_hash}");
+                    eyre::bail!("Withdrawal {hash} was finalized but its call reverted");
+                }
+                FinalizationOutcome::TimedOut { stage } => {
+                    println!("{hash}\ttimed-out\t{stage:?}");
+                    eyre::bail!("Withdrawal {hash} not finalized within {timeout}s");
+                }
+            }
+
+            info!("Step completed: wait");
+        }
     }
 
     Ok(())
@@ -0,0 +1,113 @@
+//! Circuit breaker for the main loop.
+//!
+//! After too many consecutive failed cycles the orchestrator stops submitting
+//! transactions and only keeps the metrics server up. The breaker stays open until the
+//! process is restarted or it is reset manually (SIGUSR1).
+
+/// Counts consecutive failed cycles and trips once the limit is reached.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    max_consecutive_failures: u64,
+    consecutive_failures: u64,
+    open: bool,
+}
+
+impl CircuitBreaker {
+    /// Create a breaker that opens after `max_consecutive_failures` failed cycles.
+    ///
+    /// A limit of 0 disables the breaker.
+    pub const fn new(max_consecutive_failures: u64) -> Self {
+        Self {
+            max_consecutive_failures,
+            consecutive_failures: 0,
+            open: false,
+        }
+    }
+
+    /// Whether the breaker has tripped and the orchestrator should stop acting.
+    pub const fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Number of failed cycles since the last success or reset.
+    pub const fn consecutive_failures(&self) -> u64 {
+        self.consecutive_failures
+    }
+
+    /// Record the outcome of a cycle.
+    ///
+    /// Returns true if this failure tripped the breaker.
+    pub const fn record(&mut self, success: bool) -> bool {
+        if success {
+            self.consecutive_failures = 0;
+            return false;
+        }
+
+        self.consecutive_failures += 1;
+        if !self.open
+            && self.max_consecutive_failures > 0
+            && self.consecutive_failures >= self.max_consecutive_failures
+        {
+            self.open = true;
+            return true;
+        }
+
+        false
+    }
+
+    /// Close the breaker and clear the failure count.
+    pub const fn reset(&mut self) {
+        self.consecutive_failures = 0;
+        self.open = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trips_after_consecutive_failures() {
+        let mut breaker = CircuitBreaker::new(3);
+
+        assert!(!breaker.record(false));
+        assert!(!breaker.record(false));
+        assert!(breaker.record(false));
+        assert!(breaker.is_open());
+
+        // Already open, does not trip again
+        assert!(!breaker.record(false));
+        assert!(breaker.is_open());
+    }
+
+    #[test]
+    fn test_success_resets_count() {
+        let mut breaker = CircuitBreaker::new(2);
+
+        breaker.record(false);
+        breaker.record(true);
+        assert_eq!(breaker.consecutive_failures(), 0);
+        assert!(!breaker.record(false));
+        assert!(!breaker.is_open());
+    }
+
+    #[test]
+    fn test_reset_closes_breaker() {
+        let mut breaker = CircuitBreaker::new(1);
+
+        assert!(breaker.record(false));
+        breaker.reset();
+        assert!(!breaker.is_open());
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_zero_limit_disables() {
+        let mut breaker = CircuitBreaker::new(0);
+
+        for _ in 0..100 {
+            assert!(!breaker.record(false));
+        }
+        assert!(!breaker.is_open());
+    }
+}
@@ -1,13 +1,209 @@
-use alloy_primitives::{Address, U256};
-pub use config::{NetworkConfig, NetworkType};
-use serde::{Deserialize, Serialize};
-use std::path::Path;
+use crate::{config_source::ConfigSources, mempool::Chain};
+use action::{audit::AuditLog, deposit::FillDeadline};
+use alloy_primitives::{uint, Address, Bytes, U256};
+use alloy_provider::Provider;
+use client::{retry::RetryBudget, ProviderTimeouts};
+pub use config::{NetworkConfig, NetworkType, UnichainConfig};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{
+    fmt,
+    path::Path,
+    str::FromStr,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+use tracing::info;
+use withdrawal::{
+    context::{ChainAddresses, ChainContext},
+    proof::GameSelectionStrategy,
+};
+
+/// Balance thresholds as they appear in the config file, `None` when left unset.
+///
+/// Their defaults depend on `network`, so they are resolved after the whole file is
+/// parsed, see [`Config::resolve_defaults`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExplicitThresholds {
+    pub spoke_pool_target_wei: Option<U256>,
+    pub spoke_pool_floor_wei: Option<U256>,
+    pub withdrawal_threshold_wei: Option<U256>,
+}
+
+/// Testnet defaults: small enough that the pipeline triggers with faucet-scale funds.
+const TESTNET_SPOKE_POOL_TARGET_WEI: U256 = uint!(50_000_000_000_000_000_U256); // 0.05 ETH
+const TESTNET_SPOKE_POOL_FLOOR_WEI: U256 = uint!(10_000_000_000_000_000_U256); // 0.01 ETH
+const TESTNET_WITHDRAWAL_THRESHOLD_WEI: U256 = uint!(50_000_000_000_000_000_U256); // 0.05 ETH
+
+/// How the orchestrator carries out the transactions it decides to send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecutionMode {
+    /// Sign and submit transactions
+    #[default]
+    Live,
+    /// Run each transaction through `eth_call` and report whether it would succeed,
+    /// without submitting it
+    Simulate,
+    /// Only log what would be done (formerly `dry_run = true`)
+    LogOnly,
+}
+
+impl ExecutionMode {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Live => "live",
+            Self::Simulate => "simulate",
+            Self::LogOnly => "log-only",
+        }
+    }
+
+    /// Whether transactions are actually submitted.
+    pub const fn is_live(self) -> bool {
+        matches!(self, Self::Live)
+    }
+}
+
+impl FromStr for ExecutionMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "live" => Ok(Self::Live),
+            "simulate" => Ok(Self::Simulate),
+            "log-only" => Ok(Self::LogOnly),
+            other => Err(format!(
+                "unknown execution mode {other:?}, expected live, simulate or log-only"
+            )),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ExecutionMode {
+    /// A mode name, or the boolean of the legacy `dry_run` key.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ModeVisitor;
+
+        impl Visitor<'_> for ModeVisitor {
+            type Value = ExecutionMode;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("\"live\", \"simulate\", \"log-only\" or a dry_run boolean")
+            }
+
+            fn visit_bool<E: de::Error>(self, dry_run: bool) -> Result<Self::Value, E> {
+                Ok(if dry_run {
+                    ExecutionMode::LogOnly
+                } else {
+                    ExecutionMode::Live
+                })
+            }
+
+            fn visit_str<E: de::Error>(self, mode: &str) -> Result<Self::Value, E> {
+                mode.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(ModeVisitor)
+    }
+}
+
+/// A deposit fill deadline: `"zero"`, or a window after the deposit's block such as
+/// `"1h"` or `"3600"`.
+fn deserialize_fill_deadline<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<FillDeadline, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    if value.trim() == "zero" {
+        return Ok(FillDeadline::ZeroForSlowFill);
+    }
+    let secs = crate::report::parse_duration_secs(&value).map_err(de::Error::custom)?;
+    u32::try_from(secs)
+        .map(FillDeadline::RelativeSecs)
+        .map_err(|_| de::Error::custom(format!("fill deadline window `{value}` is too long")))
+}
+
+/// Inverse of [`deserialize_fill_deadline`]. An explicit deadline, which the config
+/// can't express, is written as its timestamp.
+fn serialize_fill_deadline<S: Serializer>(
+    deadline: &FillDeadline,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match deadline {
+        FillDeadline::ZeroForSlowFill => serializer.serialize_str("zero"),
+        FillDeadline::RelativeSecs(secs) => serializer.serialize_str(&format!("{secs}s")),
+        FillDeadline::Explicit(timestamp) => serializer.serialize_str(&timestamp.to_string()),
+    }
+}
+
+/// A dispute game selection strategy, e.g. `"oldest-covering"`.
+fn deserialize_game_selection<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<GameSelectionStrategy, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(de::Error::custom)
+}
+
+/// Inverse of [`deserialize_game_selection`].
+fn serialize_game_selection<S: Serializer>(
+    strategy: &GameSelectionStrategy,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(strategy.as_str())
+}
 
 /// Configuration for remote transaction signing via signer-proxy.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoteSignerConfig {
     /// URL of the signer-proxy service (e.g., "http://localhost:9060")
     pub proxy_url: String,
+
+    /// Broadcast what the proxy returns without decoding it and checking it was signed by
+    /// `eoa_address` for the right chain. Only for proxies whose output doesn't decode as
+    /// an EIP-2718 transaction.
+    #[serde(default)]
+    pub skip_signed_transaction_check: bool,
+}
+
+/// Where the price of ETH in USD is read from. Both chains pay gas in ETH, so one
+/// feed prices gas and value on either.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum PriceFeedConfig {
+    /// Chainlink ETH / USD aggregator proxy
+    Chainlink {
+        /// Aggregator proxy address
+        feed: Address,
+        /// Chain the feed is deployed on: "l1" (default) or "l2"
+        #[serde(default)]
+        chain: FeedChain,
+        /// Answers last updated longer ago than this are not used (in seconds)
+        #[serde(default = "default_max_price_age_secs")]
+        max_age_secs: u64,
+    },
+    /// HTTP endpoint returning the price in a JSON body
+    Http {
+        url: String,
+        /// JSON pointer to the price in the body, e.g. `/ethereum/usd`
+        pointer: String,
+    },
+}
+
+/// Chain a Chainlink price feed is read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedChain {
+    #[default]
+    L1,
+    L2,
+}
+
+/// Chainlink's ETH / USD feeds update at least once a day (their heartbeat).
+const fn default_max_price_age_secs() -> u64 {
+    86_400
 }
 
 /// Top-level orchestrator configuration.
@@ -20,12 +216,36 @@ pub struct Config {
     /// L2 RPC endpoint url
     pub l2_rpc_url: String,
 
+    /// Optional L2 RPC endpoint used only for proof generation (`eth_getProof` and
+    /// the game block's header), typically an archive node, while `l2_rpc_url` serves
+    /// everything else. Falls back to `l2_rpc_url` when unset. When set, the block hash
+    /// each proof is built from is compared against `l2_rpc_url` and a divergence is
+    /// logged. Also accepted as `l2_archive_rpc_url`.
+    #[serde(alias = "l2_archive_rpc_url")]
+    pub l2_proof_rpc_url: Option<String>,
+
+    /// Optional gas-sponsoring relay (JSON-RPC `eth_sendRawTransaction`) that signed
+    /// finalize transactions are handed to instead of the L1 RPC, so the EOA needs no
+    /// L1 ETH to finalize.
+    pub sponsored_relay_url: Option<String>,
+
+    /// Seconds allowed to connect to an RPC endpoint, including DNS resolution.
+    pub rpc_connect_timeout_secs: u64,
+
+    /// Seconds allowed for a single RPC request, including reading the response. Must
+    /// leave room for `eth_getProof` on the proof RPC.
+    pub rpc_request_timeout_secs: u64,
+
     /// Network type (mainnet or testnet)
     pub network: NetworkType,
 
     /// EOA address
     pub eoa_address: Address,
 
+    /// Start even if `eoa_address` has contract code, i.e. is a smart account. Its nonce
+    /// then says nothing about our transactions, so stuck transactions aren't detected.
+    pub allow_contract_account: bool,
+
     /// Remote signer configuration (optional).
     /// When set, transactions are signed via the signer-proxy service.
     /// When None, PRIVATE_KEY env var is used for local signing.
@@ -34,29 +254,281 @@ pub struct Config {
     /// How far back to scan for in-flight deposits (in seconds).
     pub deposit_lookback_secs: u64,
 
+    /// Alert when the relayer refunds paid for our expired deposits differ from their
+    /// input amounts by more than this (in wei), either way.
+    pub refund_drift_tolerance_wei: U256,
+
+    /// Seconds after its fill deadline an expired deposit's refund is owed, for the
+    /// bundle refunding it to be executed. Until then it doesn't count as drift.
+    pub refund_grace_secs: u64,
+
+    /// Count the in-flight deposits of every depositor to Unichain, not just ours, when
+    /// projecting the SpokePool balance. Other teams topping up the same pool would
+    /// otherwise make us over-deposit. Deposits made by others before this is enabled
+    /// are only picked up once the checkpoint is reset.
+    pub track_all_depositors_for_destination: bool,
+
+    /// Deposit through `unsafeDepositV3` with a caller-chosen nonce so the deposit id is
+    /// known before the transaction lands. Requires a SpokePool version that supports it.
+    pub deterministic_deposit_ids: bool,
+
+    /// Hex-encoded message attached to deposits and executed on the recipient on L2.
+    /// Across only executes it when the recipient is a contract.
+    pub deposit_message: Bytes,
+
+    /// How long relayers have to fill a deposit, e.g. `"1h"`, capped at the
+    /// SpokePool's `fillDeadlineBuffer`. `"zero"` sets no deadline, which only some
+    /// SpokePool versions accept; deposits are simulated first and refused if the pool
+    /// rejects it.
+    #[serde(
+        serialize_with = "serialize_fill_deadline",
+        deserialize_with = "deserialize_fill_deadline"
+    )]
+    pub deposit_fill_deadline: FillDeadline,
+
+    /// Base URL of the Across API (e.g. `https://app.across.to/api`). When set, deposits
+    /// ask `/suggested-fees` for the relayer fee and set the output amount for a fast
+    /// fill; when unset or unreachable, a slow fill is forced.
+    pub across_api_url: Option<String>,
+
+    /// Highest quoted relayer fee accepted, in basis points of the deposit. Above it the
+    /// deposit falls back to a slow fill.
+    pub max_relay_fee_bps: u64,
+
+    /// Smallest accepted deposit output, in basis points of the input (10000 = 1x).
+    /// Deposits planned below it are refused.
+    pub min_output_ratio_bps: u64,
+
+    /// Largest accepted deposit output, in basis points of the input. Must leave room
+    /// for the 2x output used to force slow fills.
+    pub max_output_ratio_bps: u64,
+
+    /// Addresses deposits may be made to on L2. A deposit to any other recipient is
+    /// refused, guarding against a misconfigured or tampered `eoa_address`. Empty
+    /// allows any recipient.
+    pub allowed_deposit_recipients: Vec<Address>,
+
     /// Trigger deposit when L2 SpokePool balance exceeds this value.
+    /// Defaults to 75 ETH on mainnet and 0.05 ETH on testnet.
     pub spoke_pool_target_wei: U256,
 
     /// Minimum to leave in L2 SpokePool after deposit.
+    /// Defaults to 20 ETH on mainnet and 0.01 ETH on testnet.
     pub spoke_pool_floor_wei: U256,
 
+    /// Subtracted from the projected SpokePool balance before comparing it to the target,
+    /// as a margin for in-flight deposits missed near the lookback boundary.
+    pub spoke_pool_safety_buffer_wei: U256,
+
+    /// Warn and raise `orchestrator_spoke_pool_critical` when the SpokePool balance
+    /// drops below this value. 0 disables the alert.
+    pub spoke_pool_critical_wei: U256,
+
     /// Trigger L2→L1 withdrawal when L2 EOA balance exceeds this value.
+    /// Defaults to 75 ETH on mainnet and 0.05 ETH on testnet.
     pub withdrawal_threshold_wei: U256,
 
     /// Leave this much ETH on L2 EOA for gas.
     pub gas_buffer_wei: U256,
 
+    /// Minimum priority fee (in wei) for the L2 initiate-withdrawal transaction. The node's
+    /// estimate is raised to this floor when lower.
+    pub min_priority_fee_wei: u128,
+
+    /// Minimum priority fee (in wei) for the L1 refund claim sent by a drain. L1 tips
+    /// are set by a busier market than L2's, so it has a floor of its own.
+    pub l1_min_priority_fee_wei: u128,
+
+    /// When non-zero, withdrawals are initiated as transactions of exactly this size
+    /// instead of one transaction for the whole balance. 0 disables chunking.
+    pub withdrawal_chunk_wei: U256,
+
+    /// Most chunk-sized withdrawals initiated in one go.
+    pub max_withdrawal_chunks: usize,
+
+    /// Hex-encoded data attached to initiated withdrawals and passed to the target on L1.
+    /// The withdrawal gas limit is derived from its size.
+    pub withdrawal_data: Bytes,
+
+    /// Largest `withdrawal_data` accepted, in bytes.
+    pub max_withdrawal_data_bytes: usize,
+
     /// How far back to scan for pending withdrawals (in seconds).
     pub withdrawal_lookback_secs: u64,
 
+    /// Convert lookbacks into block counts with the block times measured at startup
+    /// instead of the network's. Measured times far off the network's are warned about
+    /// either way.
+    pub use_measured_block_times: bool,
+
+    /// Block times measured at startup in milliseconds, per chain, overriding the
+    /// network's when `use_measured_block_times` is set
+    #[serde(skip)]
+    pub measured_block_times: [Option<u64>; 2],
+
+    /// How far back the periodic recovery scan looks for withdrawals that were proven
+    /// but never finalized (in seconds). Only has an effect when longer than
+    /// `withdrawal_lookback_secs`; 0 disables recovery.
+    pub withdrawal_recovery_lookback_secs: u64,
+
+    /// How often to run the recovery scan (in seconds).
+    pub withdrawal_recovery_interval_secs: u64,
+
+    /// How long the portal's respected game type and proof maturity delay are cached
+    /// before being read again (in seconds). They only change through governance.
+    pub portal_params_refresh_secs: u64,
+
+    /// How long repeats of the same failure log, e.g. a withdrawal without a covering
+    /// dispute game, are suppressed after it was logged (in seconds). The next line let
+    /// through reports how many were suppressed. 0 logs every repeat.
+    pub log_throttle_window_secs: u64,
+
+    /// How long after a withdrawal was proven its dispute game may stay in progress
+    /// before the proof is reported at risk (in seconds). An unchallenged game resolves
+    /// within its max clock duration, 3.5 days on mainnet.
+    pub proof_at_risk_after_secs: u64,
+
+    /// Act on the withdrawals found so far when a scan chunk exhausts its retries,
+    /// instead of failing the whole step.
+    pub tolerate_partial_scans: bool,
+
+    /// Minimum number of dispute games the factory must hold before proofs are attempted.
+    /// Below this, proving is skipped for the cycle instead of failing.
+    pub min_dispute_games: u64,
+
+    /// When no dispute game covers a withdrawal yet but the game cadence predicts one
+    /// within this many seconds, wait for it in the same cycle and prove right away
+    /// instead of on a later cycle. Never waits past the end of the cycle, one
+    /// `cycle_interval_secs` after it started. 0 disables waiting.
+    pub wait_for_game_secs: u64,
+
+    /// Initiated withdrawals worth less than this are not proven. The rest are proven
+    /// largest first; withdrawals already proven are finalized regardless.
+    pub min_prove_value_wei: U256,
+
+    /// How long a withdrawal that regressed from proven to initiated is left alone
+    /// before it is proven again. The L1 block carrying its prove transaction may have
+    /// been reorged out, and the transaction is usually mined again shortly after.
+    /// 0 re-proves right away.
+    pub reorg_tolerance_secs: u64,
+
+    /// DisputeGameFactory addresses besides the network's own that proven withdrawals'
+    /// games are looked up in, e.g. the old factory of a chain that migrated. For
+    /// monitoring only: the portal reads a proof's game index from its own factory, so
+    /// proofs are only ever built against that one.
+    pub additional_dispute_game_factories: Vec<Address>,
+
+    /// Dispute game types withdrawals may be proven against, e.g. `[0]` for
+    /// permissionless Cannon games only. The portal only accepts its respected type,
+    /// so while it respects one outside this list nothing is proven. Empty allows
+    /// whichever type the portal respects.
+    pub allowed_game_types: Vec<u32>,
+
+    /// Which dispute game covering a withdrawal to prove against: `oldest-covering`
+    /// (usually resolves first), `newest-covering` (furthest past the withdrawal block,
+    /// resolves last) or `most-advanced-toward-resolution` (prefers a game already
+    /// resolved for the defender).
+    #[serde(
+        serialize_with = "serialize_game_selection",
+        deserialize_with = "deserialize_game_selection"
+    )]
+    pub game_selection_strategy: GameSelectionStrategy,
+
+    /// Most trie nodes accepted in a withdrawal's storage proof before it is submitted.
+    pub max_proof_nodes: usize,
+
+    /// Most total bytes accepted in a withdrawal's storage proof before it is submitted.
+    pub max_proof_bytes: usize,
+
+    /// Most covering-game searches, and storage proof requests, run at once when proving
+    /// a cycle's withdrawals. Prove transactions are still sent one at a time. 0 is
+    /// treated as 1.
+    pub max_concurrent_actions: usize,
+
     /// How often to run the main loop (in seconds).
     pub cycle_interval_secs: u64,
 
-    /// Dry-run mode: log actions without executing transactions.
-    pub dry_run: bool,
+    /// Stop acting after this many consecutive failed cycles (0 disables).
+    /// Once tripped, only the metrics keep updating until restart or SIGUSR1.
+    pub max_consecutive_failures: u64,
+
+    /// Total RPC retries allowed per cycle across all scans and proof requests (0
+    /// disables the budget).
+    /// Once spent, further failures return immediately and the cycle is marked degraded.
+    pub retry_budget_per_cycle: usize,
+
+    /// Alert when the EOA's pending nonce has been ahead of its latest nonce on either
+    /// chain for this long (in seconds), i.e. a transaction is stuck. 0 disables the check.
+    pub stuck_nonce_age_secs: u64,
+
+    /// Re-send a stuck transaction with bumped fees. Only possible for transactions whose
+    /// request was recorded in the state store, which happens while this is enabled.
+    pub bump_stuck_transactions: bool,
+
+    /// Alert when either chain's head block hasn't advanced for this long (in seconds),
+    /// i.e. the sequencer or the RPC endpoint is stuck. 0 disables the check.
+    pub max_stale_head_secs: u64,
+
+    /// Path of the JSON state store used by `step backfill`, the withdrawal cost report
+    /// and the in-flight deposit checkpoint.
+    pub state_path: String,
+
+    /// JSON lines file every broadcast transaction is recorded in, for compliance. Unset
+    /// disables the audit log.
+    pub audit_log_path: Option<String>,
+
+    /// Size in bytes at which the audit log is rotated to `<audit_log_path>.1`. Five
+    /// rotated files are kept.
+    pub audit_log_max_bytes: u64,
+
+    /// Audit log built from the two fields above on first use
+    #[serde(skip)]
+    pub(crate) opened_audit_log: OnceLock<AuditLog>,
+
+    /// How often the main loop records the gas cost of newly finalized withdrawals
+    /// (in seconds). 0 disables cost tracking.
+    pub withdrawal_cost_interval_secs: u64,
+
+    /// How often the main loop reconciles the withdrawals finalized since the last run
+    /// against what their recipients received on L1 (in seconds). 0 disables it.
+    pub reconcile_interval_secs: u64,
+
+    /// Optional ETH / USD price feed. When set, gas cost and value moved are also
+    /// exported in USD; while the feed is unavailable the USD metrics are left out.
+    pub eth_usd_price_feed: Option<PriceFeedConfig>,
+
+    /// How long a fetched ETH / USD price is reused before fetching it again (in seconds).
+    pub price_refresh_secs: u64,
+
+    /// How often the main loop checks that recent SpokePool logs still decode against the
+    /// bindings (in seconds). 0 disables the check.
+    pub event_audit_interval_secs: u64,
+
+    /// Number of recent blocks whose SpokePool logs each event audit classifies.
+    pub event_audit_window_blocks: u64,
+
+    /// Share of undecodable SpokePool logs, in basis points, above which the event audit
+    /// alerts.
+    pub max_undecodable_event_bps: u64,
+
+    /// How often the withdrawals recorded as finalized are spot-checked against L1 (in
+    /// seconds). 0 disables the check.
+    pub finalized_verify_interval_secs: u64,
+
+    /// Number of recorded finalized withdrawals each check re-reads from the portal.
+    pub finalized_verify_sample_size: usize,
+
+    /// Whether actions are submitted, simulated or only logged. The legacy boolean
+    /// `dry_run` key is still accepted: `true` is `log-only`, `false` is `live`.
+    #[serde(alias = "dry_run")]
+    pub execution_mode: ExecutionMode,
 
     /// Port for Prometheus metrics HTTP server.
     pub metrics_port: u16,
+
+    /// Whether failing to start the metrics server stops the orchestrator. When false,
+    /// the orchestrator logs an error and keeps rebalancing without metrics.
+    pub metrics_required: bool,
 }
 
 impl Default for Config {
@@ -64,18 +536,79 @@ impl Default for Config {
         Self {
             l1_rpc_url: String::new(),
             l2_rpc_url: String::new(),
+            l2_proof_rpc_url: None,
+            sponsored_relay_url: None,
+            rpc_connect_timeout_secs: 5,
+            rpc_request_timeout_secs: 120,
             network: NetworkType::Testnet,
             eoa_address: Address::ZERO,
+            allow_contract_account: false,
             remote_signer: None,
             deposit_lookback_secs: 43200, // 12 hours
+            refund_drift_tolerance_wei: U256::from(10_000_000_000_000_000_u128), // 0.01 ETH
+            refund_grace_secs: 10_800,    // 3 hours
+            track_all_depositors_for_destination: false,
+            deterministic_deposit_ids: false,
+            deposit_message: Bytes::new(),
+            deposit_fill_deadline: FillDeadline::default(),
+            across_api_url: None,
+            max_relay_fee_bps: 50,
+            min_output_ratio_bps: 9_500,
+            max_output_ratio_bps: 30_000,
+            allowed_deposit_recipients: vec![],
             spoke_pool_target_wei: U256::from(75_000_000_000_000_000_000_u128), // 75 ETH
-            spoke_pool_floor_wei: U256::from(20_000_000_000_000_000_000_u128), // 20 ETH
+            spoke_pool_floor_wei: U256::from(20_000_000_000_000_000_000_u128),  // 20 ETH
+            spoke_pool_safety_buffer_wei: U256::ZERO,
+            spoke_pool_critical_wei: U256::ZERO,
             withdrawal_threshold_wei: U256::from(75_000_000_000_000_000_000_u128), // 75 ETH
-            gas_buffer_wei: U256::from(10_000_000_000_000_000_u128), // 0.01 ETH
+            gas_buffer_wei: U256::from(10_000_000_000_000_000_u128),               // 0.01 ETH
+            min_priority_fee_wei: 0,
+            l1_min_priority_fee_wei: 0,
+            withdrawal_chunk_wei: U256::ZERO,
+            max_withdrawal_chunks: 10,
+            withdrawal_data: Bytes::new(),
+            max_withdrawal_data_bytes: 120_000,
             withdrawal_lookback_secs: 1_209_600, // 2 weeks
+            use_measured_block_times: false,
+            measured_block_times: [None; 2],
+            withdrawal_recovery_lookback_secs: 4_838_400, // 8 weeks
+            withdrawal_recovery_interval_secs: 3600,
+            portal_params_refresh_secs: 3600,
+            log_throttle_window_secs: 1800,
+            proof_at_risk_after_secs: 302_400, // 3.5 days
+            tolerate_partial_scans: false,
+            min_dispute_games: 1,
+            wait_for_game_secs: 0,
+            min_prove_value_wei: U256::ZERO,
+            reorg_tolerance_secs: 900,
+            additional_dispute_game_factories: vec![],
+            allowed_game_types: vec![],
+            game_selection_strategy: GameSelectionStrategy::OldestCovering,
+            max_proof_nodes: 32,
+            max_proof_bytes: 32_768,
+            max_concurrent_actions: 4,
             cycle_interval_secs: 30,
-            dry_run: false,
+            max_consecutive_failures: 10,
+            retry_budget_per_cycle: 50,
+            stuck_nonce_age_secs: 300,
+            bump_stuck_transactions: false,
+            max_stale_head_secs: 600,
+            state_path: "orchestrator-state.json".to_string(),
+            audit_log_path: None,
+            audit_log_max_bytes: 100 * 1024 * 1024,
+            opened_audit_log: OnceLock::new(),
+            withdrawal_cost_interval_secs: 3600,
+            reconcile_interval_secs: 86_400,
+            eth_usd_price_feed: None,
+            price_refresh_secs: 300,
+            event_audit_interval_secs: 3600,
+            event_audit_window_blocks: 300,
+            max_undecodable_event_bps: 5000,
+            finalized_verify_interval_secs: 604_800,
+            finalized_verify_sample_size: 100,
+            execution_mode: ExecutionMode::Live,
             metrics_port: 9090,
+            metrics_required: false,
         }
     }
 }
@@ -83,13 +616,696 @@ impl Default for Config {
 impl Config {
     pub fn from_file(path: impl AsRef<Path>) -> eyre::Result<Self> {
         let contents = std::fs::read_to_string(path)?;
-        let config: Self = toml::from_str(&contents)?;
+        Self::from_toml(&contents)
+    }
+
+    /// Parse a TOML config and resolve its network-dependent defaults.
+    ///
+    /// Values referencing an external source, e.g. `"vault:secret/data/orchestrator#l1_rpc_url"`,
+    /// are resolved through the built-in [`ConfigSources`].
+    pub fn from_toml(contents: &str) -> eyre::Result<Self> {
+        Self::from_toml_with_sources(contents, &ConfigSources::builtin())
+    }
+
+    /// Like [`Self::from_toml`], resolving external references through `sources`.
+    pub fn from_toml_with_sources(contents: &str, sources: &ConfigSources) -> eyre::Result<Self> {
+        let mut table: toml::Table = toml::from_str(contents)?;
+        let resolved = sources.resolve(&mut table)?;
+        for key in &resolved {
+            info!(key, "Resolved config value from external source");
+        }
+
+        // Parse the text itself when nothing was resolved, for errors with line numbers
+        let (mut config, explicit): (Self, ExplicitThresholds) = if resolved.is_empty() {
+            (toml::from_str(contents)?, toml::from_str(contents)?)
+        } else {
+            let value = toml::Value::Table(table);
+            (value.clone().try_into()?, value.try_into()?)
+        };
+
+        for (field, value) in config.resolve_defaults(&explicit) {
+            info!(
+                network = ?config.network,
+                "Using default {field} = {} ETH",
+                alloy_primitives::utils::format_ether(value)
+            );
+        }
+        config.validate()?;
 
         Ok(config)
     }
 
-    /// Get the network configuration based on the configured network type.
+    /// Reject settings that can't work together.
+    fn validate(&self) -> eyre::Result<()> {
+        eyre::ensure!(
+            self.min_output_ratio_bps > 0 && self.max_output_ratio_bps > 0,
+            "min_output_ratio_bps and max_output_ratio_bps must be non-zero"
+        );
+        eyre::ensure!(
+            self.min_output_ratio_bps <= self.max_output_ratio_bps,
+            "min_output_ratio_bps ({}) exceeds max_output_ratio_bps ({})",
+            self.min_output_ratio_bps,
+            self.max_output_ratio_bps
+        );
+        Ok(())
+    }
+
+    /// Timeouts for the providers built from the configured RPC URLs.
+    pub const fn provider_timeouts(&self) -> ProviderTimeouts {
+        ProviderTimeouts {
+            connect: Duration::from_secs(self.rpc_connect_timeout_secs),
+            request: Duration::from_secs(self.rpc_request_timeout_secs),
+        }
+    }
+
+    /// A fresh budget of `retry_budget_per_cycle` retries, unlimited when it is 0.
+    pub fn retry_budget(&self) -> RetryBudget {
+        match self.retry_budget_per_cycle {
+            0 => RetryBudget::unlimited(),
+            limit => RetryBudget::new(limit),
+        }
+    }
+
+    /// When a cycle started at `started` should be done, one `cycle_interval_secs` later.
+    pub fn cycle_deadline(&self, started: Instant) -> Instant {
+        started + Duration::from_secs(self.cycle_interval_secs)
+    }
+
+    /// Audit log broadcast transactions are recorded in; writes nothing when
+    /// `audit_log_path` is unset.
+    pub fn audit_log(&self) -> &AuditLog {
+        self.opened_audit_log.get_or_init(|| {
+            self.audit_log_path
+                .as_ref()
+                .map_or_else(AuditLog::default, |path| {
+                    AuditLog::new(path, self.audit_log_max_bytes)
+                })
+        })
+    }
+
+    /// Apply the `network`'s default to each balance threshold not set in `explicit`.
+    ///
+    /// Returns the fields that were defaulted along with the value applied.
+    pub fn resolve_defaults(&mut self, explicit: &ExplicitThresholds) -> Vec<(&'static str, U256)> {
+        let mainnet = Self::default();
+        let (target, floor, threshold) = match self.network {
+            NetworkType::Mainnet => (
+                mainnet.spoke_pool_target_wei,
+                mainnet.spoke_pool_floor_wei,
+                mainnet.withdrawal_threshold_wei,
+            ),
+            NetworkType::Testnet => (
+                TESTNET_SPOKE_POOL_TARGET_WEI,
+                TESTNET_SPOKE_POOL_FLOOR_WEI,
+                TESTNET_WITHDRAWAL_THRESHOLD_WEI,
+            ),
+        };
+
+        let mut applied = vec![];
+        for (field, value, explicit, default) in [
+            (
+                "spoke_pool_target_wei",
+                &mut self.spoke_pool_target_wei,
+                explicit.spoke_pool_target_wei,
+                target,
+            ),
+            (
+                "spoke_pool_floor_wei",
+                &mut self.spoke_pool_floor_wei,
+                explicit.spoke_pool_floor_wei,
+                floor,
+            ),
+            (
+                "withdrawal_threshold_wei",
+                &mut self.withdrawal_threshold_wei,
+                explicit.withdrawal_threshold_wei,
+                threshold,
+            ),
+        ] {
+            if explicit.is_none() {
+                *value = default;
+                applied.push((field, default));
+            }
+        }
+
+        applied
+    }
+
+    /// Get the network configuration based on the configured network type, with any
+    /// measured block times applied.
     pub const fn network_config(&self) -> NetworkConfig {
-        NetworkConfig::from_network_type(self.network)
+        let mut network = NetworkConfig::from_network_type(self.network);
+        if let Some(ms) = self.measured_block_times[0] {
+            network.ethereum.block_time_ms = ms;
+        }
+        if let Some(ms) = self.measured_block_times[1] {
+            network.unichain.block_time_ms = ms;
+        }
+        network
+    }
+
+    /// Use `ms` milliseconds as the block time of `chain` from now on.
+    pub const fn set_block_time(&mut self, chain: Chain, ms: u64) {
+        let index = match chain {
+            Chain::L1 => 0,
+            Chain::L2 => 1,
+        };
+        self.measured_block_times[index] = Some(ms);
+    }
+
+    /// Every DisputeGameFactory a proven withdrawal's game is looked up in, the network's
+    /// own first.
+    pub fn dispute_game_factories(&self) -> Vec<Address> {
+        std::iter::once(self.network_config().unichain.l1_dispute_game_factory)
+            .chain(self.additional_dispute_game_factories.iter().copied())
+            .collect()
+    }
+
+    /// Context for the configured rollup's withdrawals, reading L1 through `l1_provider`
+    /// and the rollup through `l2_provider`.
+    pub fn chain_context<P1, P2>(&self, l1_provider: P1, l2_provider: P2) -> ChainContext<P1, P2>
+    where
+        P1: Provider + Clone,
+        P2: Provider + Clone,
+    {
+        let unichain = self.network_config().unichain;
+        ChainContext::new(
+            unichain.chain_id,
+            l1_provider,
+            l2_provider,
+            ChainAddresses {
+                portal: unichain.l1_portal,
+                message_passer: unichain.l2_to_l1_message_passer,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_source::ValueResolver;
+
+    #[test]
+    fn test_execution_mode_parsing() {
+        assert_eq!(Config::default().execution_mode, ExecutionMode::Live);
+
+        for (toml, mode) in [
+            (r#"execution_mode = "live""#, ExecutionMode::Live),
+            (r#"execution_mode = "simulate""#, ExecutionMode::Simulate),
+            (r#"execution_mode = "log-only""#, ExecutionMode::LogOnly),
+            // Legacy key
+            ("dry_run = true", ExecutionMode::LogOnly),
+            ("dry_run = false", ExecutionMode::Live),
+        ] {
+            let config: Config = toml::from_str(toml).unwrap();
+            assert_eq!(config.execution_mode, mode, "{toml}");
+        }
+
+        assert!(toml::from_str::<Config>(r#"execution_mode = "dry""#).is_err());
+        assert_eq!("simulate".parse(), Ok(ExecutionMode::Simulate));
+        assert_eq!(ExecutionMode::LogOnly.as_str(), "log-only");
+    }
+
+    #[test]
+    fn test_deposit_message_parsing() {
+        let config: Config = toml::from_str(r#"deposit_message = """#).unwrap();
+        assert!(config.deposit_message.is_empty());
+
+        let config: Config = toml::from_str(r#"deposit_message = "0xdeadbeef""#).unwrap();
+        assert_eq!(
+            config.deposit_message,
+            Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef])
+        );
+
+        assert!(toml::from_str::<Config>(r#"deposit_message = "0xnothex""#).is_err());
+    }
+
+    #[test]
+    fn test_deposit_fill_deadline_parsing() {
+        assert_eq!(
+            Config::default().deposit_fill_deadline,
+            FillDeadline::RelativeSecs(3_600)
+        );
+
+        let parse = |value: &str| {
+            toml::from_str::<Config>(&format!("deposit_fill_deadline = \"{value}\""))
+                .map(|config| config.deposit_fill_deadline)
+        };
+        assert_eq!(parse("30m").unwrap(), FillDeadline::RelativeSecs(1_800));
+        assert_eq!(parse("7200").unwrap(), FillDeadline::RelativeSecs(7_200));
+        assert_eq!(parse("zero").unwrap(), FillDeadline::ZeroForSlowFill);
+        assert!(parse("soon").is_err());
+        assert!(parse("100000w").is_err());
+    }
+
+    #[test]
+    fn test_across_api_parsing() {
+        let config = Config::default();
+        assert_eq!(config.across_api_url, None);
+        assert_eq!(config.max_relay_fee_bps, 50);
+
+        let config: Config = toml::from_str(
+            r#"
+            across_api_url = "https://app.across.to/api"
+            max_relay_fee_bps = 10
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.across_api_url.as_deref(),
+            Some("https://app.across.to/api")
+        );
+        assert_eq!(config.max_relay_fee_bps, 10);
+    }
+
+    #[test]
+    fn test_price_feed_parsing() {
+        let config = Config::default();
+        assert_eq!(config.eth_usd_price_feed, None);
+        assert_eq!(config.price_refresh_secs, 300);
+
+        let config: Config = toml::from_str(
+            r#"
+            [eth_usd_price_feed]
+            source = "chainlink"
+            feed = "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.eth_usd_price_feed,
+            Some(PriceFeedConfig::Chainlink {
+                feed: "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"
+                    .parse()
+                    .unwrap(),
+                chain: FeedChain::L1,
+                max_age_secs: 86_400,
+            })
+        );
+
+        let config: Config = toml::from_str(
+            r#"
+            price_refresh_secs = 60
+            [eth_usd_price_feed]
+            source = "http"
+            url = "https://api.coingecko.com/api/v3/simple/price?ids=ethereum&vs_currencies=usd"
+            pointer = "/ethereum/usd"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.price_refresh_secs, 60);
+        assert!(matches!(
+            config.eth_usd_price_feed,
+            Some(PriceFeedConfig::Http { pointer, .. }) if pointer == "/ethereum/usd"
+        ));
+
+        let unknown = r#"
+            [eth_usd_price_feed]
+            source = "oracle"
+            "#;
+        assert!(toml::from_str::<Config>(unknown).is_err());
+    }
+
+    #[test]
+    fn test_output_ratio_parsing() {
+        let config = Config::default();
+        assert_eq!(config.min_output_ratio_bps, 9_500);
+        assert_eq!(config.max_output_ratio_bps, 30_000);
+
+        let config: Config = toml::from_str(
+            r#"
+            min_output_ratio_bps = 10000
+            max_output_ratio_bps = 20000
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.min_output_ratio_bps, 10_000);
+        assert_eq!(config.max_output_ratio_bps, 20_000);
+
+        for invalid in [
+            "min_output_ratio_bps = 0",
+            "max_output_ratio_bps = 0",
+            "min_output_ratio_bps = 20001\nmax_output_ratio_bps = 20000",
+        ] {
+            assert!(Config::from_toml(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_min_priority_fee_parsing() {
+        assert_eq!(Config::default().min_priority_fee_wei, 0);
+
+        let config: Config = toml::from_str("min_priority_fee_wei = 1000000").unwrap();
+        assert_eq!(config.min_priority_fee_wei, 1_000_000);
+        assert_eq!(config.l1_min_priority_fee_wei, 0);
+
+        let config: Config = toml::from_str("l1_min_priority_fee_wei = 2000000000").unwrap();
+        assert_eq!(config.l1_min_priority_fee_wei, 2_000_000_000);
+        assert_eq!(config.min_priority_fee_wei, 0);
+    }
+
+    #[test]
+    fn test_measured_block_times_override_network() {
+        let mut config: Config = toml::from_str("use_measured_block_times = true").unwrap();
+        assert!(config.use_measured_block_times);
+        assert_eq!(config.network_config().ethereum.block_time_ms, 12_000);
+
+        config.set_block_time(Chain::L2, 500);
+        assert_eq!(config.network_config().ethereum.block_time_ms, 12_000);
+        assert_eq!(config.network_config().unichain.block_time_ms, 500);
+        assert_eq!(config.network_config().unichain.blocks_in(60), 120);
+    }
+
+    #[test]
+    fn test_wait_for_game_parsing() {
+        assert_eq!(Config::default().wait_for_game_secs, 0);
+
+        let config: Config = toml::from_str("wait_for_game_secs = 600").unwrap();
+        assert_eq!(config.wait_for_game_secs, 600);
+    }
+
+    #[test]
+    fn test_withdrawal_chunk_parsing() {
+        let config = Config::default();
+        assert_eq!(config.withdrawal_chunk_wei, U256::ZERO);
+        assert_eq!(config.max_withdrawal_chunks, 10);
+
+        let config: Config = toml::from_str(
+            r#"
+            withdrawal_chunk_wei = "5000000000000000000"
+            max_withdrawal_chunks = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.withdrawal_chunk_wei, U256::from(5 * ETH));
+        assert_eq!(config.max_withdrawal_chunks, 3);
+    }
+
+    #[test]
+    fn test_spoke_pool_critical_parsing() {
+        assert_eq!(Config::default().spoke_pool_critical_wei, U256::ZERO);
+
+        let config: Config =
+            toml::from_str(r#"spoke_pool_critical_wei = "5000000000000000000""#).unwrap();
+        assert_eq!(config.spoke_pool_critical_wei, U256::from(5 * ETH));
+    }
+
+    #[test]
+    fn test_provider_timeouts_parsing() {
+        let config = Config::default();
+        assert_eq!(
+            config.provider_timeouts(),
+            ProviderTimeouts {
+                connect: Duration::from_secs(5),
+                request: Duration::from_secs(120),
+            }
+        );
+
+        let config: Config = toml::from_str(
+            r#"
+            rpc_connect_timeout_secs = 2
+            rpc_request_timeout_secs = 300
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.provider_timeouts().connect, Duration::from_secs(2));
+        assert_eq!(config.provider_timeouts().request, Duration::from_secs(300));
+    }
+
+    #[test]
+    fn test_audit_log_parsing() {
+        let config = Config::default();
+        assert!(config.audit_log().path().is_none());
+
+        let config: Config = toml::from_str(
+            r#"
+            audit_log_path = "/var/log/orchestrator/audit.jsonl"
+            audit_log_max_bytes = 1048576
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.audit_log_max_bytes, 1_048_576);
+        assert_eq!(
+            config.audit_log().path(),
+            Some(Path::new("/var/log/orchestrator/audit.jsonl"))
+        );
+    }
+
+    #[test]
+    fn test_event_audit_parsing() {
+        let config = Config::default();
+        assert_eq!(config.event_audit_interval_secs, 3600);
+        assert_eq!(config.event_audit_window_blocks, 300);
+        assert_eq!(config.max_undecodable_event_bps, 5000);
+
+        let config: Config = toml::from_str(
+            r#"
+            event_audit_interval_secs = 0
+            max_undecodable_event_bps = 2000
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.event_audit_interval_secs, 0);
+        assert_eq!(config.event_audit_window_blocks, 300);
+        assert_eq!(config.max_undecodable_event_bps, 2000);
+    }
+
+    #[test]
+    fn test_withdrawal_data_parsing() {
+        let config = Config::default();
+        assert!(config.withdrawal_data.is_empty());
+        assert_eq!(config.max_withdrawal_data_bytes, 120_000);
+
+        let config: Config = toml::from_str(
+            r#"
+            withdrawal_data = "0xdeadbeef"
+            max_withdrawal_data_bytes = 64
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.withdrawal_data,
+            Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(config.max_withdrawal_data_bytes, 64);
+    }
+
+    #[test]
+    fn test_additional_dispute_game_factories_parsing() {
+        assert!(Config::default()
+            .additional_dispute_game_factories
+            .is_empty());
+
+        let config: Config = toml::from_str(
+            r#"additional_dispute_game_factories = ["0x00000000000000000000000000000000000000fa"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.additional_dispute_game_factories,
+            [Address::with_last_byte(0xfa)]
+        );
+    }
+
+    #[test]
+    fn test_reorg_tolerance_parsing() {
+        assert_eq!(Config::default().reorg_tolerance_secs, 900);
+
+        let config: Config = toml::from_str("reorg_tolerance_secs = 0").unwrap();
+        assert_eq!(config.reorg_tolerance_secs, 0);
+    }
+
+    #[test]
+    fn test_allowed_game_types_parsing() {
+        assert!(Config::default().allowed_game_types.is_empty());
+
+        let config: Config = toml::from_str("allowed_game_types = [0, 1]").unwrap();
+        assert_eq!(config.allowed_game_types, [0, 1]);
+    }
+
+    #[test]
+    fn test_from_toml_resolves_external_values() {
+        struct Secrets;
+        impl ValueResolver for Secrets {
+            fn scheme(&self) -> &'static str {
+                "fake"
+            }
+            fn resolve(&self, reference: &str) -> eyre::Result<String> {
+                match reference {
+                    "rpc" => Ok("https://l1.example/secret".to_string()),
+                    "threshold" => Ok("7000000000000000000".to_string()),
+                    _ => eyre::bail!("no such secret"),
+                }
+            }
+        }
+        let sources = ConfigSources::default().with_resolver(Secrets);
+
+        let config = Config::from_toml_with_sources(
+            r#"
+            network = "Testnet"
+            l1_rpc_url = "fake:rpc"
+            withdrawal_threshold_wei = "fake:threshold"
+            "#,
+            &sources,
+        )
+        .unwrap();
+        assert_eq!(config.l1_rpc_url, "https://l1.example/secret");
+        // Resolved before the network defaults, so it counts as set explicitly
+        assert_eq!(
+            config.withdrawal_threshold_wei,
+            U256::from(7_000_000_000_000_000_000u64)
+        );
+
+        let err = Config::from_toml_with_sources(r#"l2_rpc_url = "fake:other""#, &sources)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`l2_rpc_url`"), "{err}");
+    }
+
+    #[test]
+    fn test_l2_proof_rpc_url_parsing() {
+        assert_eq!(Config::default().l2_proof_rpc_url, None);
+
+        for key in ["l2_proof_rpc_url", "l2_archive_rpc_url"] {
+            let config: Config = toml::from_str(&format!(r#"{key} = "https://archive""#)).unwrap();
+            assert_eq!(config.l2_proof_rpc_url.as_deref(), Some("https://archive"));
+        }
+    }
+
+    #[test]
+    fn test_game_selection_strategy_parsing() {
+        assert_eq!(
+            Config::default().game_selection_strategy,
+            GameSelectionStrategy::OldestCovering
+        );
+
+        let config: Config =
+            toml::from_str(r#"game_selection_strategy = "most-advanced-toward-resolution""#)
+                .unwrap();
+        assert_eq!(
+            config.game_selection_strategy,
+            GameSelectionStrategy::MostAdvancedTowardResolution
+        );
+
+        assert!(toml::from_str::<Config>(r#"game_selection_strategy = "newest""#).is_err());
+    }
+
+    #[test]
+    fn test_allowed_deposit_recipients_parsing() {
+        assert!(Config::default().allowed_deposit_recipients.is_empty());
+
+        let config: Config = toml::from_str(
+            r#"allowed_deposit_recipients = ["0x00000000000000000000000000000000000000aa"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.allowed_deposit_recipients,
+            [Address::with_last_byte(0xaa)]
+        );
+    }
+
+    #[test]
+    fn test_metrics_required_parsing() {
+        assert!(!Config::default().metrics_required);
+
+        let config: Config = toml::from_str("metrics_required = true").unwrap();
+        assert!(config.metrics_required);
+    }
+
+    #[test]
+    fn test_sponsored_relay_parsing() {
+        assert_eq!(Config::default().sponsored_relay_url, None);
+
+        let config: Config =
+            toml::from_str(r#"sponsored_relay_url = "https://relay.example/rpc""#).unwrap();
+        assert_eq!(
+            config.sponsored_relay_url.as_deref(),
+            Some("https://relay.example/rpc")
+        );
+    }
+
+    #[test]
+    fn test_proof_limit_parsing() {
+        let config = Config::default();
+        assert_eq!(config.max_proof_nodes, 32);
+        assert_eq!(config.max_proof_bytes, 32_768);
+
+        let config: Config = toml::from_str(
+            r#"
+            max_proof_nodes = 16
+            max_proof_bytes = 8192
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.max_proof_nodes, 16);
+        assert_eq!(config.max_proof_bytes, 8192);
+    }
+
+    #[test]
+    fn test_max_concurrent_actions_parsing() {
+        assert_eq!(Config::default().max_concurrent_actions, 4);
+
+        let config: Config = toml::from_str("max_concurrent_actions = 8").unwrap();
+        assert_eq!(config.max_concurrent_actions, 8);
+    }
+
+    const ETH: u128 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn test_mainnet_threshold_defaults() {
+        let config = Config::from_toml(r#"network = "Mainnet""#).unwrap();
+
+        assert_eq!(config.spoke_pool_target_wei, U256::from(75 * ETH));
+        assert_eq!(config.spoke_pool_floor_wei, U256::from(20 * ETH));
+        assert_eq!(config.withdrawal_threshold_wei, U256::from(75 * ETH));
+    }
+
+    #[test]
+    fn test_testnet_threshold_defaults() {
+        let config = Config::from_toml(r#"network = "Testnet""#).unwrap();
+
+        assert_eq!(config.spoke_pool_target_wei, U256::from(ETH / 20));
+        assert_eq!(config.spoke_pool_floor_wei, U256::from(ETH / 100));
+        assert_eq!(config.withdrawal_threshold_wei, U256::from(ETH / 20));
+
+        // Testnet is also the default network
+        let config = Config::from_toml("").unwrap();
+        assert_eq!(config.spoke_pool_target_wei, U256::from(ETH / 20));
+    }
+
+    #[test]
+    fn test_explicit_thresholds_override_defaults() {
+        let config = Config::from_toml(
+            r#"
+            network = "Testnet"
+            spoke_pool_target_wei = "75000000000000000000"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.spoke_pool_target_wei, U256::from(75 * ETH));
+        assert_eq!(config.spoke_pool_floor_wei, U256::from(ETH / 100));
+
+        let mut config = Config::from_toml(
+            r#"
+            network = "Mainnet"
+            spoke_pool_floor_wei = "1000"
+            withdrawal_threshold_wei = "2000"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.spoke_pool_target_wei, U256::from(75 * ETH));
+        assert_eq!(config.spoke_pool_floor_wei, U256::from(1000));
+        assert_eq!(config.withdrawal_threshold_wei, U256::from(2000));
+
+        // Only unset fields are reported as defaulted
+        let explicit = ExplicitThresholds {
+            spoke_pool_floor_wei: Some(U256::from(1000)),
+            withdrawal_threshold_wei: Some(U256::from(2000)),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.resolve_defaults(&explicit),
+            vec![("spoke_pool_target_wei", U256::from(75 * ETH))]
+        );
     }
 }
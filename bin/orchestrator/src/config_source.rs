@@ -0,0 +1,405 @@
+//! External sources for config values.
+//!
+//! A string value in the config TOML may point at a secret store instead of holding the
+//! value itself, e.g. `"vault:secret/data/orchestrator#l1_rpc_url"` or
+//! `"ssm:/orch/prod/l1_rpc_url"`. [`ConfigSources`] replaces every such reference after
+//! the TOML is parsed and before the config is built from it. Resolved values are never
+//! logged, only the keys they were resolved for.
+//!
+//! Each store is behind a cargo feature: `vault` reads through the `vault` CLI and
+//! `aws-ssm` through the `aws` CLI, so they authenticate the way those tools do
+//! (`VAULT_ADDR`/`VAULT_TOKEN`, the AWS credential chain). The features add no
+//! dependencies, they only build the resolvers in; the CLIs must be installed. A lookup
+//! that doesn't finish within [`CLI_TIMEOUT`] fails startup.
+//!
+//! Only the TOML is resolved. The private key comes from `PRIVATE_KEY` or `--private-key`
+//! and must hold the key itself; use a remote signer to keep it out of the environment.
+
+use eyre::eyre;
+use toml::{Table, Value};
+
+/// Resolves config value references of one scheme.
+pub trait ValueResolver: Send + Sync {
+    /// Prefix of the references this resolves, without the colon, e.g. `"vault"`.
+    fn scheme(&self) -> &'static str;
+
+    /// The value `reference`, the part after `<scheme>:`, points at.
+    fn resolve(&self, reference: &str) -> eyre::Result<String>;
+}
+
+/// Schemes of the built-in resolvers and the cargo feature each needs.
+const BUILTIN_SCHEMES: [(&str, &str); 2] = [("vault", "vault"), ("ssm", "aws-ssm")];
+
+/// Resolvers for the references in a config.
+#[derive(Default)]
+pub struct ConfigSources {
+    resolvers: Vec<Box<dyn ValueResolver>>,
+}
+
+impl ConfigSources {
+    /// The resolvers built into this binary.
+    pub fn builtin() -> Self {
+        #[allow(unused_mut)]
+        let mut sources = Self::default();
+        #[cfg(feature = "vault")]
+        {
+            sources = sources.with_resolver(vault::VaultResolver);
+        }
+        #[cfg(feature = "aws-ssm")]
+        {
+            sources = sources.with_resolver(ssm::SsmResolver);
+        }
+        sources
+    }
+
+    /// Also resolve references of `resolver`'s scheme, ahead of built-in ones.
+    pub fn with_resolver(mut self, resolver: impl ValueResolver + 'static) -> Self {
+        self.resolvers.insert(0, Box::new(resolver));
+        self
+    }
+
+    /// Replace every reference in `table` with the value it points at.
+    ///
+    /// Returns the dotted keys of the values resolved. Fails on the first reference
+    /// that can't be resolved, or whose scheme's resolver isn't built in.
+    pub fn resolve(&self, table: &mut Table) -> eyre::Result<Vec<String>> {
+        let mut resolved = vec![];
+        for (key, value) in table.iter_mut() {
+            self.resolve_value(key, value, &mut resolved)?;
+        }
+        Ok(resolved)
+    }
+
+    fn resolve_value(
+        &self,
+        path: &str,
+        value: &mut Value,
+        resolved: &mut Vec<String>,
+    ) -> eyre::Result<()> {
+        match value {
+            Value::String(s) => {
+                if let Some(secret) = self.resolve_string(path, s)? {
+                    *s = secret;
+                    resolved.push(path.to_string());
+                }
+            }
+            Value::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    self.resolve_value(&format!("{path}[{i}]"), item, resolved)?;
+                }
+            }
+            Value::Table(table) => {
+                for (key, item) in table.iter_mut() {
+                    self.resolve_value(&format!("{path}.{key}"), item, resolved)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The value `s` points at, or `None` if it isn't a reference.
+    fn resolve_string(&self, path: &str, s: &str) -> eyre::Result<Option<String>> {
+        for resolver in &self.resolvers {
+            let Some(reference) = strip_scheme(s, resolver.scheme()) else {
+                continue;
+            };
+            let scheme = resolver.scheme();
+            let value = resolver.resolve(reference).map_err(|e| {
+                eyre!("config value `{path}`: failed to resolve {scheme}:{reference}: {e}")
+            })?;
+            if value.is_empty() {
+                return Err(eyre!(
+                    "config value `{path}`: {scheme}:{reference} resolved to an empty value"
+                ));
+            }
+            return Ok(Some(value));
+        }
+
+        for (scheme, feature) in BUILTIN_SCHEMES {
+            if strip_scheme(s, scheme).is_some() {
+                return Err(eyre!(
+                    "config value `{path}` references {scheme}:, but the orchestrator was \
+                     built without the `{feature}` feature"
+                ));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The part of `s` after `<scheme>:`, if it starts with that.
+fn strip_scheme<'a>(s: &'a str, scheme: &str) -> Option<&'a str> {
+    s.strip_prefix(scheme)?.strip_prefix(':')
+}
+
+/// Longest a secret store CLI may take to print a value.
+pub const CLI_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Run `program` with `args` and return its trimmed stdout.
+///
+/// Stdout holds the secret, so only stderr goes into the error. The program is killed
+/// if it hasn't exited after `timeout`, e.g. waiting on a credential prompt.
+#[cfg_attr(not(any(feature = "vault", feature = "aws-ssm")), allow(dead_code))]
+fn run_cli(program: &str, args: &[&str], timeout: std::time::Duration) -> eyre::Result<String> {
+    use std::{
+        io::Read,
+        process::{Command, Stdio},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| eyre!("failed to run `{program}` (is it installed and on PATH?): {e}"))?;
+
+    // Pipes are drained while waiting so a chatty program can't block on a full one
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        thread::spawn(move || {
+            let mut buf = vec![];
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    };
+    let stdout = drain(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = drain(child.stderr.take().map(|p| Box::new(p) as _));
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(eyre!(
+                "`{program}` did not finish within {}s",
+                timeout.as_secs()
+            ));
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        return Err(eyre!(
+            "`{program}` exited with {status}: {}",
+            String::from_utf8_lossy(&stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8(stdout)
+        .map_err(|_| eyre!("`{program}` printed a value that is not UTF-8"))?;
+    Ok(stdout.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(feature = "vault")]
+mod vault {
+    use super::{run_cli, ValueResolver, CLI_TIMEOUT};
+    use eyre::eyre;
+
+    /// Reads `vault:<path>#<field>` with `vault read`, from KV version 1 or 2 mounts.
+    pub struct VaultResolver;
+
+    impl ValueResolver for VaultResolver {
+        fn scheme(&self) -> &'static str {
+            "vault"
+        }
+
+        fn resolve(&self, reference: &str) -> eyre::Result<String> {
+            let (path, field) = reference
+                .split_once('#')
+                .ok_or_else(|| eyre!("expected <path>#<field>"))?;
+            let output = run_cli("vault", &["read", "-format=json", path], CLI_TIMEOUT)?;
+            let response: serde_json::Value = serde_json::from_str(&output)
+                .map_err(|e| eyre!("`vault read` printed invalid JSON: {e}"))?;
+            secret_field(&response, field)
+                .ok_or_else(|| eyre!("secret at {path} has no field {field}"))
+        }
+    }
+
+    /// `field` of a `vault read` response: under `data.data` for KV version 2, `data`
+    /// for version 1.
+    pub(super) fn secret_field(response: &serde_json::Value, field: &str) -> Option<String> {
+        let data = &response["data"];
+        let value = data["data"].get(field).or_else(|| data.get(field))?;
+        match value {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Null => None,
+            other => Some(other.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "aws-ssm")]
+mod ssm {
+    use super::{run_cli, ValueResolver, CLI_TIMEOUT};
+
+    /// Reads `ssm:<parameter name>` with `aws ssm get-parameter`, decrypting
+    /// `SecureString` parameters.
+    pub struct SsmResolver;
+
+    impl ValueResolver for SsmResolver {
+        fn scheme(&self) -> &'static str {
+            "ssm"
+        }
+
+        fn resolve(&self, reference: &str) -> eyre::Result<String> {
+            run_cli(
+                "aws",
+                &[
+                    "ssm",
+                    "get-parameter",
+                    "--name",
+                    reference,
+                    "--with-decryption",
+                    "--query",
+                    "Parameter.Value",
+                    "--output",
+                    "text",
+                ],
+                CLI_TIMEOUT,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Resolves `fake:<key>` from a map.
+    struct FakeResolver(HashMap<&'static str, &'static str>);
+
+    impl ValueResolver for FakeResolver {
+        fn scheme(&self) -> &'static str {
+            "fake"
+        }
+
+        fn resolve(&self, reference: &str) -> eyre::Result<String> {
+            self.0
+                .get(reference)
+                .map(|value| value.to_string())
+                .ok_or_else(|| eyre!("no such secret"))
+        }
+    }
+
+    fn sources() -> ConfigSources {
+        ConfigSources::default().with_resolver(FakeResolver(HashMap::from([
+            ("rpc", "https://secret.example/key"),
+            ("factory", "0x00000000000000000000000000000000000000fa"),
+            ("empty", ""),
+        ])))
+    }
+
+    #[test]
+    fn test_resolves_nested_references() {
+        let mut table: Table = toml::from_str(
+            r#"
+            l1_rpc_url = "fake:rpc"
+            l2_rpc_url = "https://plain.example"
+            cycle_interval_secs = 30
+            additional_dispute_game_factories = ["fake:factory"]
+
+            [remote_signer]
+            proxy_url = "fake:rpc"
+            "#,
+        )
+        .unwrap();
+
+        let mut resolved = sources().resolve(&mut table).unwrap();
+        resolved.sort();
+
+        assert_eq!(
+            resolved,
+            [
+                "additional_dispute_game_factories[0]",
+                "l1_rpc_url",
+                "remote_signer.proxy_url"
+            ]
+        );
+        assert_eq!(
+            table["l1_rpc_url"].as_str(),
+            Some("https://secret.example/key")
+        );
+        assert_eq!(table["l2_rpc_url"].as_str(), Some("https://plain.example"));
+        assert_eq!(
+            table["remote_signer"]["proxy_url"].as_str(),
+            Some("https://secret.example/key")
+        );
+        assert_eq!(
+            table["additional_dispute_game_factories"][0].as_str(),
+            Some("0x00000000000000000000000000000000000000fa")
+        );
+    }
+
+    #[test]
+    fn test_resolution_errors_name_the_key() {
+        let mut table: Table = toml::from_str(r#"l1_rpc_url = "fake:missing""#).unwrap();
+        let err = sources().resolve(&mut table).unwrap_err().to_string();
+        assert!(err.contains("`l1_rpc_url`"), "{err}");
+        assert!(err.contains("fake:missing"), "{err}");
+        assert!(err.contains("no such secret"), "{err}");
+
+        let mut table: Table = toml::from_str(r#"l1_rpc_url = "fake:empty""#).unwrap();
+        let err = sources().resolve(&mut table).unwrap_err().to_string();
+        assert!(err.contains("empty value"), "{err}");
+    }
+
+    #[test]
+    fn test_run_cli() {
+        let timeout = std::time::Duration::from_secs(5);
+        assert_eq!(
+            run_cli("sh", &["-c", "echo secret"], timeout).unwrap(),
+            "secret"
+        );
+
+        let err = run_cli("sh", &["-c", "echo oops >&2; exit 3"], timeout)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("oops"), "{err}");
+    }
+
+    #[test]
+    fn test_run_cli_times_out() {
+        let started = std::time::Instant::now();
+        let err = run_cli("sleep", &["10"], std::time::Duration::from_millis(200))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("did not finish within"), "{err}");
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[cfg(not(feature = "vault"))]
+    #[test]
+    fn test_missing_feature_is_an_error() {
+        let mut table: Table =
+            toml::from_str(r#"l1_rpc_url = "vault:secret/data/orchestrator#l1_rpc_url""#).unwrap();
+        let err = ConfigSources::builtin()
+            .resolve(&mut table)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`vault` feature"), "{err}");
+    }
+
+    #[cfg(feature = "vault")]
+    #[test]
+    fn test_vault_secret_field() {
+        let kv2 =
+            serde_json::json!({"data": {"data": {"l1_rpc_url": "https://a"}, "metadata": {}}});
+        assert_eq!(
+            vault::secret_field(&kv2, "l1_rpc_url").as_deref(),
+            Some("https://a")
+        );
+        let kv1 = serde_json::json!({"data": {"threshold": 5}});
+        assert_eq!(vault::secret_field(&kv1, "threshold").as_deref(), Some("5"));
+        assert_eq!(vault::secret_field(&kv1, "missing"), None);
+    }
+}
@@ -0,0 +1,558 @@
+//! Drain for decommissioning: moving every fund home to L1.
+//!
+//! `step drain --confirm` ignores the withdrawal threshold and chunking. It claims the
+//! relayer refunds owed on L1, withdraws the whole L2 balance less a reserve for the
+//! withdrawal's own gas, then proves and finalizes every pending withdrawal until none
+//! is left. [`plan_drain`] works out what that involves, and roughly what it costs,
+//! before anything is sent.
+
+use crate::{
+    backfill::run_backfill,
+    config::{Config, NetworkType},
+    maybe_initiate_withdrawal, record_initiations,
+    store::{RecordStatus, StateStore},
+};
+use action::{
+    claim::{Claim, ClaimAction, CLAIM_GAS_LIMIT},
+    finalize::FINALIZE_GAS_OVERHEAD,
+    gas::estimate_max_fee,
+    prove::PROVE_GAS_LIMIT,
+    receipt::decode_events,
+    withdraw::{default_gas_limit, initiate_gas_limit},
+    Action, TransactionSigner,
+};
+use alloy_primitives::{utils::format_ether, U256};
+use alloy_provider::Provider;
+use binding::across::ISpokePool::{self, ClaimedRelayerRefund};
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+use withdrawal::types::{WithdrawalHash, WithdrawalStatus};
+
+/// Multiple of the estimated initiation fee kept back from the L2 balance. The estimate
+/// leaves out the L1 data fee charged on top, and fees may rise before the withdrawal
+/// is sent.
+const GAS_RESERVE_MULTIPLIER: u64 = 2;
+
+/// Refuse to drain mainnet unless `override_mainnet` is set.
+pub fn check_drain_network(network: NetworkType, override_mainnet: bool) -> eyre::Result<()> {
+    if network == NetworkType::Mainnet && !override_mainnet {
+        eyre::bail!("Refusing to drain a mainnet environment without --i-know-what-im-doing");
+    }
+    Ok(())
+}
+
+/// Whether `answer` to the confirmation prompt agrees to the drain.
+pub fn is_confirmed(answer: &str) -> bool {
+    answer.trim() == "yes"
+}
+
+/// What a drain will do, and what it will roughly cost.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrainPlan {
+    /// Relayer refunds claimable from the L1 SpokePool
+    pub claimable_refund: U256,
+    /// L2 EOA balance
+    pub l2_balance: U256,
+    /// Part of the L2 balance kept back to pay for the withdrawal
+    pub gas_reserve: U256,
+    /// Withdrawals initiated earlier and not finalized yet
+    pub pending: usize,
+    /// Value of the pending withdrawals
+    pub pending_value: U256,
+    /// Withdrawals still to be proven, the new one included
+    pub to_prove: usize,
+    /// L1 EOA balance, paying for the claim, proofs and finalizations
+    pub l1_balance: U256,
+    /// Estimated L1 cost of the claim, proofs and finalizations at current fees
+    pub l1_cost: U256,
+}
+
+impl DrainPlan {
+    /// Value of the new withdrawal: the L2 balance less the gas reserve.
+    pub const fn withdraw_amount(&self) -> U256 {
+        self.l2_balance.saturating_sub(self.gas_reserve)
+    }
+
+    /// Value that ends up on L1 once the drain completes.
+    pub fn total(&self) -> U256 {
+        self.claimable_refund + self.withdraw_amount() + self.pending_value
+    }
+
+    /// Whether there is nothing to claim, withdraw or finalize.
+    pub fn is_empty(&self) -> bool {
+        self.claimable_refund.is_zero() && self.withdraw_amount().is_zero() && self.pending == 0
+    }
+}
+
+impl fmt::Display for DrainPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Drain plan:")?;
+        if self.claimable_refund.is_zero() {
+            writeln!(f, "  1. Claim relayer refund: nothing to claim")?;
+        } else {
+            writeln!(
+                f,
+                "  1. Claim relayer refund: {} WETH from the L1 SpokePool",
+                format_ether(self.claimable_refund)
+            )?;
+        }
+        if self.withdraw_amount().is_zero() {
+            writeln!(f, "  2. Withdraw L2 balance: nothing to withdraw")?;
+        } else {
+            writeln!(
+                f,
+                "  2. Withdraw L2 balance: {} ETH, keeping {} ETH for gas",
+                format_ether(self.withdraw_amount()),
+                format_ether(self.gas_reserve)
+            )?;
+        }
+        let withdrawals = self.pending + usize::from(!self.withdraw_amount().is_zero());
+        writeln!(
+            f,
+            "  3. Prove and finalize: {withdrawals} withdrawals ({} pending, {} to prove)",
+            self.pending, self.to_prove
+        )?;
+        writeln!(f, "  Moved to L1: {} ETH", format_ether(self.total()))?;
+        writeln!(
+            f,
+            "  Estimated L1 cost: {} ETH (L1 balance {} ETH)",
+            format_ether(self.l1_cost),
+            format_ether(self.l1_balance)
+        )?;
+        if self.l1_cost > self.l1_balance {
+            writeln!(
+                f,
+                "  WARNING: the L1 balance does not cover the estimated cost"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Work out what draining takes, from the chains and the pending withdrawals in `store`.
+///
+/// Refresh the store with [`crate::backfill::refresh_store`] first, or withdrawals it
+/// hasn't seen are left out.
+pub async fn plan_drain<P1, P2>(
+    l1_provider: &P1,
+    l2_provider: &P2,
+    config: &Config,
+    store: &StateStore,
+) -> eyre::Result<DrainPlan>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let network = config.network_config();
+    let claimable_refund = ISpokePool::new(network.ethereum.spoke_pool, l1_provider)
+        .getRelayerRefund(network.ethereum.weth, config.eoa_address)
+        .call()
+        .await?;
+    let l2_balance = l2_provider.get_balance(config.eoa_address).await?;
+    let gas_reserve = estimate_max_fee(
+        l2_provider,
+        initiate_gas_limit(config.withdrawal_data.len()),
+    )
+    .await?
+        * U256::from(GAS_RESERVE_MULTIPLIER);
+
+    let mut plan = DrainPlan {
+        claimable_refund,
+        l2_balance,
+        gas_reserve,
+        l1_balance: l1_provider.get_balance(config.eoa_address).await?,
+        ..Default::default()
+    };
+
+    // Gas of every L1 transaction still to send
+    let mut l1_gas = if claimable_refund.is_zero() {
+        0
+    } else {
+        CLAIM_GAS_LIMIT
+    };
+    let finalize_gas = |withdrawal_gas_limit: U256| {
+        FINALIZE_GAS_OVERHEAD.saturating_add(withdrawal_gas_limit.saturating_to())
+    };
+    for withdrawal in store.pending_withdrawals() {
+        plan.pending += 1;
+        plan.pending_value += withdrawal.transaction.value;
+        if withdrawal.status == WithdrawalStatus::Initiated {
+            plan.to_prove += 1;
+            l1_gas = l1_gas.saturating_add(PROVE_GAS_LIMIT);
+        }
+        l1_gas = l1_gas.saturating_add(finalize_gas(withdrawal.transaction.gasLimit));
+    }
+    if !plan.withdraw_amount().is_zero() {
+        plan.to_prove += 1;
+        l1_gas = l1_gas
+            .saturating_add(PROVE_GAS_LIMIT)
+            .saturating_add(finalize_gas(default_gas_limit(
+                config.withdrawal_data.len(),
+            )));
+    }
+    plan.l1_cost = estimate_max_fee(l1_provider, l1_gas).await?;
+
+    Ok(plan)
+}
+
+/// `config` with the withdrawal threshold and chunking lifted, keeping back only
+/// `gas_reserve` of the L2 balance.
+pub fn drain_config(config: &Config, gas_reserve: U256) -> Config {
+    Config {
+        withdrawal_threshold_wei: U256::ZERO,
+        withdrawal_chunk_wei: U256::ZERO,
+        gas_buffer_wei: gas_reserve,
+        ..config.clone()
+    }
+}
+
+/// Withdrawals in the store not finalized yet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrainProgress {
+    /// Waiting to be proven
+    pub initiated: usize,
+    /// Proven, waiting to mature and be finalized
+    pub proven: usize,
+    /// Value still on its way to L1
+    pub value: U256,
+}
+
+impl DrainProgress {
+    pub fn of(store: &StateStore) -> Self {
+        store
+            .pending_withdrawals()
+            .iter()
+            .fold(Self::default(), |mut progress, withdrawal| {
+                match withdrawal.status {
+                    WithdrawalStatus::Initiated => progress.initiated += 1,
+                    _ => progress.proven += 1,
+                }
+                progress.value += withdrawal.transaction.value;
+                progress
+            })
+    }
+
+    pub const fn is_done(&self) -> bool {
+        self.initiated == 0 && self.proven == 0
+    }
+}
+
+/// What a completed drain moved to L1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrainOutcome {
+    /// Relayer refund claimed from the L1 SpokePool
+    pub refund_claimed: U256,
+    /// Value of the withdrawals finalized during the drain
+    pub finalized: U256,
+}
+
+impl DrainOutcome {
+    pub fn total(&self) -> U256 {
+        self.refund_claimed + self.finalized
+    }
+}
+
+/// Hashes of the withdrawals `store` records as finalized.
+fn finalized_hashes(store: &StateStore) -> HashSet<WithdrawalHash> {
+    store
+        .withdrawals()
+        .into_iter()
+        .filter(|record| record.status == RecordStatus::Finalized)
+        .map(|record| record.hash)
+        .collect()
+}
+
+/// Value of the withdrawals `store` records as finalized, other than `before`.
+fn finalized_since(store: &StateStore, before: &HashSet<WithdrawalHash>) -> U256 {
+    store
+        .withdrawals()
+        .into_iter()
+        .filter(|record| record.status == RecordStatus::Finalized)
+        .filter(|record| !before.contains(&record.hash))
+        .map(|record| record.value)
+        .sum()
+}
+
+/// Carry out `plan`: claim, withdraw, then prove and finalize until nothing is pending.
+///
+/// Every `poll_interval` a backfill pass refreshes the store and sends what is due, and
+/// `on_progress` is called with what is left. A failed pass is logged and retried on the
+/// next poll. Errors once `timeout` passes with withdrawals still pending; otherwise
+/// returns the refund claimed and the value finalized.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_drain<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    signer: Arc<dyn TransactionSigner>,
+    config: &Config,
+    store: &mut StateStore,
+    plan: &DrainPlan,
+    timeout: Duration,
+    poll_interval: Duration,
+    mut on_progress: impl FnMut(&DrainProgress),
+) -> eyre::Result<DrainOutcome>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let deadline = Instant::now() + timeout;
+    let finalized_before = finalized_hashes(store);
+
+    let mut outcome = DrainOutcome::default();
+    if !plan.claimable_refund.is_zero() {
+        outcome.refund_claimed = claim_refund(l1_provider.clone(), signer.clone(), config).await?;
+    }
+
+    if !plan.withdraw_amount().is_zero() {
+        let chunks = maybe_initiate_withdrawal(
+            l2_provider.clone(),
+            signer.clone(),
+            &drain_config(config, plan.gas_reserve),
+        )
+        .await?;
+        record_initiations(store, &chunks);
+        store.save()?;
+    }
+
+    loop {
+        // Statuses are refreshed at the start of a pass, so a finalize shows up as done
+        // on the pass after it
+        if let Err(e) = run_backfill(
+            l1_provider.clone(),
+            l2_provider.clone(),
+            signer.clone(),
+            config,
+            store,
+            false,
+        )
+        .await
+        {
+            warn!(error = %e, "Drain: prove/finalize pass failed, retrying next poll");
+        }
+
+        let progress = DrainProgress::of(store);
+        on_progress(&progress);
+        if progress.is_done() {
+            outcome.finalized = finalized_since(store, &finalized_before);
+            return Ok(outcome);
+        }
+        if Instant::now() + poll_interval > deadline {
+            eyre::bail!(
+                "Drain timed out with {} withdrawals ({} ETH) not finalized",
+                progress.initiated + progress.proven,
+                format_ether(progress.value)
+            );
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Claim the relayer refund owed to the EOA from the L1 SpokePool, returning the amount
+/// its `ClaimedRelayerRefund` events report.
+async fn claim_refund<P>(
+    l1_provider: P,
+    signer: Arc<dyn TransactionSigner>,
+    config: &Config,
+) -> eyre::Result<U256>
+where
+    P: Provider + Clone,
+{
+    let network = config.network_config();
+    let claim = Claim {
+        spoke_pool: network.ethereum.spoke_pool,
+        token: network.ethereum.weth,
+        refund_address: config.eoa_address,
+        relayer: config.eoa_address,
+    };
+    let mut action = ClaimAction::new(l1_provider.clone(), signer, claim)
+        .with_min_priority_fee(config.l1_min_priority_fee_wei)
+        .with_audit_log(config.audit_log().clone());
+
+    let result = action.execute().await?;
+    let receipt = l1_provider
+        .get_transaction_receipt(result.tx_hash)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Receipt of refund claim {} not found", result.tx_hash))?;
+    let claimed = decode_events::<ClaimedRelayerRefund>(receipt.inner.logs())
+        .filter(|event| event.address == network.ethereum.spoke_pool)
+        .filter(|event| event.relayer == config.eoa_address)
+        .map(|event| event.amount)
+        .sum();
+    info!(
+        tx_hash = %result.tx_hash,
+        amount = %format_ether(claimed),
+        "Relayer refund claimed"
+    );
+    Ok(claimed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, Bytes, B256};
+    use binding::opstack::WithdrawalTransaction;
+    use test_support::FakeChain;
+    use withdrawal::state::PendingWithdrawal;
+
+    fn withdrawal(byte: u8, value: u64, status: WithdrawalStatus) -> PendingWithdrawal {
+        PendingWithdrawal {
+            transaction: WithdrawalTransaction {
+                nonce: U256::from(byte),
+                sender: Address::repeat_byte(1),
+                target: Address::repeat_byte(1),
+                value: U256::from(value),
+                gasLimit: U256::from(100_000),
+                data: Bytes::new(),
+            },
+            hash: B256::repeat_byte(byte),
+            l2_block: u64::from(byte),
+            status,
+        }
+    }
+
+    fn store_with(name: &str, withdrawals: &[PendingWithdrawal]) -> StateStore {
+        let path = std::env::temp_dir().join(format!(
+            "orchestrator-drain-{}-{name}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut store = StateStore::load(path).unwrap();
+        store.upsert_withdrawals(withdrawals);
+        store
+    }
+
+    #[test]
+    fn test_check_drain_network() {
+        assert!(check_drain_network(NetworkType::Testnet, false).is_ok());
+        assert!(check_drain_network(NetworkType::Mainnet, false).is_err());
+        assert!(check_drain_network(NetworkType::Mainnet, true).is_ok());
+    }
+
+    #[test]
+    fn test_is_confirmed() {
+        assert!(is_confirmed("yes\n"));
+        assert!(!is_confirmed("y\n"));
+        assert!(!is_confirmed(""));
+    }
+
+    #[test]
+    fn test_drain_config_lifts_thresholds() {
+        let config = Config {
+            withdrawal_threshold_wei: U256::from(10u64.pow(18)),
+            withdrawal_chunk_wei: U256::from(10u64.pow(17)),
+            ..Default::default()
+        };
+
+        let drain = drain_config(&config, U256::from(42));
+
+        assert_eq!(drain.withdrawal_threshold_wei, U256::ZERO);
+        assert_eq!(drain.withdrawal_chunk_wei, U256::ZERO);
+        assert_eq!(drain.gas_buffer_wei, U256::from(42));
+    }
+
+    #[test]
+    fn test_drain_progress() {
+        let proven = WithdrawalStatus::Proven {
+            timestamp: 1,
+            dispute_game_proxy: Address::repeat_byte(0x22),
+            proof_submitter: Address::repeat_byte(0x33),
+        };
+        let mut store = store_with(
+            "progress",
+            &[
+                withdrawal(1, 100, WithdrawalStatus::Initiated),
+                withdrawal(2, 200, proven),
+                withdrawal(3, 400, WithdrawalStatus::Finalized),
+            ],
+        );
+
+        let progress = DrainProgress::of(&store);
+        assert_eq!(
+            progress,
+            DrainProgress {
+                initiated: 1,
+                proven: 1,
+                value: U256::from(300),
+            }
+        );
+        assert!(!progress.is_done());
+
+        let before = finalized_hashes(&store);
+        store.update_statuses(&[
+            (B256::repeat_byte(1), WithdrawalStatus::Finalized),
+            (B256::repeat_byte(2), WithdrawalStatus::Finalized),
+        ]);
+        assert!(DrainProgress::of(&store).is_done());
+        // Only what was finalized since counts, not the withdrawal already finalized
+        assert_eq!(finalized_since(&store, &before), U256::from(300));
+    }
+
+    #[tokio::test]
+    async fn test_plan_drain() {
+        let config = Config::default();
+        let network = config.network_config();
+        let (l1, l2) = (FakeChain::new(), FakeChain::new());
+        let l2_balance = U256::from(10u64.pow(18));
+        l2.set_balance(config.eoa_address, l2_balance);
+        l1.set_balance(config.eoa_address, U256::from(10u64.pow(17)));
+        l1.set_call::<ISpokePool::getRelayerRefundCall>(
+            network.ethereum.spoke_pool,
+            &U256::from(5_000),
+        );
+        let store = store_with(
+            "plan",
+            &[
+                withdrawal(1, 100, WithdrawalStatus::Initiated),
+                withdrawal(2, 400, WithdrawalStatus::Finalized),
+            ],
+        );
+
+        let plan = plan_drain(&l1.provider(), &l2.provider(), &config, &store)
+            .await
+            .unwrap();
+
+        let initiate_fee = estimate_max_fee(&l2.provider(), initiate_gas_limit(0))
+            .await
+            .unwrap();
+        assert_eq!(plan.gas_reserve, initiate_fee * U256::from(2));
+        assert_eq!(plan.withdraw_amount(), l2_balance - plan.gas_reserve);
+        assert_eq!((plan.pending, plan.pending_value), (1, U256::from(100)));
+        // The pending withdrawal and the new one
+        assert_eq!(plan.to_prove, 2);
+        let l1_gas = CLAIM_GAS_LIMIT
+            + 2 * PROVE_GAS_LIMIT
+            + (FINALIZE_GAS_OVERHEAD + 100_000)
+            + (FINALIZE_GAS_OVERHEAD + default_gas_limit(0).to::<u64>());
+        assert_eq!(
+            plan.l1_cost,
+            estimate_max_fee(&l1.provider(), l1_gas).await.unwrap()
+        );
+        assert_eq!(
+            plan.total(),
+            U256::from(5_000 + 100) + plan.withdraw_amount()
+        );
+        assert!(!plan.is_empty());
+        assert!(plan.to_string().contains("Claim relayer refund"));
+    }
+
+    #[tokio::test]
+    async fn test_plan_drain_empty() {
+        let config = Config::default();
+        let network = config.network_config();
+        let (l1, l2) = (FakeChain::new(), FakeChain::new());
+        l1.set_call::<ISpokePool::getRelayerRefundCall>(network.ethereum.spoke_pool, &U256::ZERO);
+        let store = store_with("empty", &[]);
+
+        let plan = plan_drain(&l1.provider(), &l2.provider(), &config, &store)
+            .await
+            .unwrap();
+
+        assert!(plan.is_empty());
+        assert_eq!(plan.l1_cost, U256::ZERO);
+    }
+}
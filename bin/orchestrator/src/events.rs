@@ -0,0 +1,198 @@
+//! Detection of SpokePool upgrades that break event decoding.
+//!
+//! Deposit and fill tracking filters SpokePool logs by the event signatures in the
+//! bindings. If Across upgrades the contract and changes those signatures, the filters
+//! silently match nothing: deposits look filled and the orchestrator keeps depositing.
+//! The event audit pulls every log the SpokePools emitted over a recent window, without
+//! a topic filter, and flags the chain when too many of them no longer decode.
+
+use crate::{config::Config, mempool::Chain};
+use alloy_primitives::Address;
+use alloy_provider::Provider;
+use deposit::events::{audit_spoke_pool_events, EventDecodeReport};
+
+/// Minimum number of logs in the window before the undecodable share is judged.
+/// A quiet window with a single unrelated log should not page anyone.
+pub const MIN_AUDITED_LOGS: usize = 10;
+
+/// Result of auditing one chain's SpokePool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainEventAudit {
+    pub chain: Chain,
+    pub spoke_pool: Address,
+    pub from_block: u64,
+    pub to_block: u64,
+    pub report: EventDecodeReport,
+    /// Whether the undecodable share exceeded `max_undecodable_event_bps`
+    pub alert: bool,
+}
+
+/// Classify the logs of both SpokePools over the last `event_audit_window_blocks` blocks.
+pub async fn audit_event_decoding<P1, P2>(
+    l1_provider: &P1,
+    l2_provider: &P2,
+    config: &Config,
+) -> eyre::Result<[ChainEventAudit; 2]>
+where
+    P1: Provider,
+    P2: Provider,
+{
+    let network = config.network_config();
+
+    let l1 = audit_chain(l1_provider, Chain::L1, network.ethereum.spoke_pool, config).await?;
+    let l2 = audit_chain(l2_provider, Chain::L2, network.unichain.spoke_pool, config).await?;
+
+    Ok([l1, l2])
+}
+
+async fn audit_chain<P: Provider>(
+    provider: &P,
+    chain: Chain,
+    spoke_pool: Address,
+    config: &Config,
+) -> eyre::Result<ChainEventAudit> {
+    let to_block = provider.get_block_number().await?;
+    let from_block = to_block.saturating_sub(config.event_audit_window_blocks.saturating_sub(1));
+
+    let report = audit_spoke_pool_events(provider, spoke_pool, from_block, to_block).await?;
+
+    Ok(ChainEventAudit {
+        chain,
+        spoke_pool,
+        from_block,
+        to_block,
+        report,
+        alert: report.exceeds(config.max_undecodable_event_bps, MIN_AUDITED_LOGS),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, LogData, B256, U256};
+    use alloy_provider::ProviderBuilder;
+    use alloy_rpc_types_eth::Log;
+    use alloy_sol_types::SolEvent;
+    use alloy_transport::mock::Asserter;
+    use binding::across::ISpokePool;
+
+    fn rpc_log(spoke_pool: Address, data: LogData) -> Log {
+        Log {
+            inner: alloy_primitives::Log {
+                address: spoke_pool,
+                data,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn refund_claim(spoke_pool: Address) -> Log {
+        rpc_log(
+            spoke_pool,
+            ISpokePool::ClaimedRelayerRefund {
+                token: Address::repeat_byte(1),
+                relayer: Address::repeat_byte(4),
+                amount: U256::from(5),
+            }
+            .encode_log_data(),
+        )
+    }
+
+    fn legacy_deposit(spoke_pool: Address) -> Log {
+        rpc_log(
+            spoke_pool,
+            ISpokePool::V3FundsDeposited {
+                inputToken: Address::repeat_byte(1),
+                outputToken: Address::repeat_byte(2),
+                inputAmount: U256::from(1_000),
+                outputAmount: U256::from(990),
+                destinationChainId: U256::from(130),
+                depositId: 1,
+                quoteTimestamp: 1,
+                fillDeadline: 100,
+                exclusivityDeadline: 0,
+                depositor: Address::repeat_byte(3),
+                recipient: Address::repeat_byte(3),
+                exclusiveRelayer: Address::ZERO,
+                message: Bytes::new(),
+            }
+            .encode_log_data(),
+        )
+    }
+
+    /// An event emitted under a signature the bindings don't know.
+    fn unknown(spoke_pool: Address) -> Log {
+        rpc_log(
+            spoke_pool,
+            LogData::new_unchecked(vec![B256::repeat_byte(0xee)], Bytes::new()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_audit_event_decoding() {
+        let config = Config::default();
+        let network = config.network_config();
+        let l1_pool = network.ethereum.spoke_pool;
+        let l2_pool = network.unichain.spoke_pool;
+
+        // L1: legacy deposits still decode, a couple of unknown logs stay under the limit
+        let l1 = Asserter::new();
+        l1.push_success(&U256::from(1_000));
+        let l1_logs: Vec<Log> = (0..10)
+            .map(|_| legacy_deposit(l1_pool))
+            .chain([unknown(l1_pool), unknown(l1_pool)])
+            .collect();
+        l1.push_success(&l1_logs);
+
+        // L2: after an upgrade fills come through under an unknown signature
+        let l2 = Asserter::new();
+        l2.push_success(&U256::from(5_000));
+        let l2_logs: Vec<Log> = (0..9)
+            .map(|_| unknown(l2_pool))
+            .chain([
+                refund_claim(l2_pool),
+                refund_claim(l2_pool),
+                refund_claim(l2_pool),
+            ])
+            .collect();
+        l2.push_success(&l2_logs);
+
+        let l1_provider = ProviderBuilder::new().connect_mocked_client(l1);
+        let l2_provider = ProviderBuilder::new().connect_mocked_client(l2);
+
+        let [l1_audit, l2_audit] = audit_event_decoding(&l1_provider, &l2_provider, &config)
+            .await
+            .unwrap();
+
+        assert_eq!(l1_audit.chain, Chain::L1);
+        assert_eq!((l1_audit.from_block, l1_audit.to_block), (701, 1_000));
+        assert_eq!(l1_audit.report.deposits, 10);
+        assert_eq!(l1_audit.report.undecodable, 2);
+        assert!(!l1_audit.alert);
+
+        assert_eq!(l2_audit.chain, Chain::L2);
+        assert_eq!(l2_audit.report.other, 3);
+        assert_eq!(l2_audit.report.undecodable, 9);
+        assert_eq!(l2_audit.report.undecodable_bps(), 7_500);
+        assert!(l2_audit.alert);
+    }
+
+    #[tokio::test]
+    async fn test_audit_ignores_quiet_window() {
+        let config = Config::default();
+        let l2_pool = config.network_config().unichain.spoke_pool;
+
+        let asserter = Asserter::new();
+        asserter.push_success(&U256::from(100));
+        asserter.push_success(&vec![unknown(l2_pool)]);
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let audit = audit_chain(&provider, Chain::L2, l2_pool, &config)
+            .await
+            .unwrap();
+
+        // Everything undecodable, but one log is not enough to alert on
+        assert_eq!(audit.report.undecodable_bps(), 10_000);
+        assert!(!audit.alert);
+    }
+}
@@ -1,54 +1,228 @@
+pub mod audit;
+pub mod backfill;
+pub mod circuit;
 pub mod config;
+pub mod config_source;
+pub mod drain;
+pub mod events;
+pub mod mempool;
 pub mod metrics;
+pub mod preflight;
+pub mod price;
+pub mod proof_health;
+pub mod reconcile;
+pub mod recovery;
+pub mod refunds;
+pub mod reorg;
+pub mod replay;
+pub mod report;
+pub mod schedule;
+pub mod shutdown;
+pub mod snapshot;
+pub mod stall;
+pub mod store;
+pub mod throttle;
 
-use crate::metrics::Metrics;
+use crate::{
+    config::{ExecutionMode, UnichainConfig},
+    mempool::Chain,
+    metrics::Metrics,
+    proof_health::ProofHealthTracker,
+    reorg::RegressionGuard,
+    snapshot::StateSnapshot,
+    store::{ProofRecord, StateStore},
+    throttle::{ThrottledLogger, WithdrawalLogKey},
+};
 use action::{
-    deposit::{DepositAction, DepositConfig},
-    finalize::{Finalize, FinalizeAction},
-    prove::{Prove, ProveAction},
-    withdraw::{Withdraw, WithdrawAction},
-    Action, SignerFn,
+    audit::{AuditLog, TransactionKind},
+    deposit::{check_recipient, DepositAction, DepositConfig, OutputBounds},
+    finalize::{Finalize, FinalizeAction, WithdrawalCallFailed},
+    gas::InsufficientGasFunds,
+    pending::PendingTransactions,
+    prove::{ProofLimits, Prove, ProveAction},
+    withdraw::{default_gas_limit, Initiated, Withdraw, WithdrawAction},
+    Action, TransactionSigner,
 };
-use alloy_primitives::{utils::format_ether, Address, Bytes, U256};
+use alloy_primitives::{utils::format_ether, Address, U256};
 use alloy_provider::Provider;
 use alloy_rpc_types_eth::BlockNumberOrTag;
 use balance::{monitor::BalanceMonitor, Balance, BalanceQuery, Monitor};
-use deposit::get_inflight_deposits;
-use tracing::{error, info, warn};
+use client::{coverage::ScanCoverage, retry::RetryBudget, SponsoredRelay};
+use deposit::{DepositCheckpoint, DepositStateProvider, InFlightDeposit};
+use futures::{stream, StreamExt};
+use std::{
+    collections::{BTreeMap, HashSet},
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, info, warn};
 use withdrawal::{
-    state::{PendingWithdrawal, WithdrawalStateProvider},
-    types::WithdrawalStatus,
+    context::ChainContext,
+    finalized::{verify_finalized, FinalizedSet},
+    game::{find_game_index, status_name, DisputeGameReader, FaultGameReader, GameCache},
+    portal::PortalParamsCache,
+    proof::{
+        CoveringGame, GameNotYetAvailable, ProveWithdrawalParams, StorageLayoutCheck,
+        StorageLayoutMismatch,
+    },
+    state::{PendingWithdrawal, WithdrawalState, WithdrawalStateProvider},
+    submitter::{
+        ProofCandidate, ProofHealth, GAME_STATUS_CHALLENGER_WINS, GAME_STATUS_DEFENDER_WINS,
+    },
+    types::{WithdrawalHash, WithdrawalStatus},
 };
 
+/// Fail if the secret argument `id` was passed on the command line instead of through
+/// its environment variable.
+///
+/// The command line of a process is readable by other users through `/proc` and `ps`,
+/// so keys must not travel on it.
+pub fn reject_secret_argument(matches: &clap::ArgMatches, id: &str) -> eyre::Result<()> {
+    if matches.value_source(id) == Some(clap::parser::ValueSource::CommandLine) {
+        eyre::bail!(
+            "--{} must not be passed on the command line, where other processes can read \
+             it; set the {} environment variable instead",
+            id.replace('_', "-"),
+            id.to_uppercase()
+        );
+    }
+    Ok(())
+}
+
+/// Check that the configured EOA is an address the signer actually signs for.
+///
+/// `signer_addresses` is the local key's address, or the remote signer's accounts.
+/// A mismatch is an error unless `allow_mismatch` is set, for setups where the
+/// monitored sender and the signing key are intentionally different.
+pub fn verify_signer_address(
+    configured: Address,
+    signer_addresses: &[Address],
+    allow_mismatch: bool,
+) -> eyre::Result<()> {
+    if signer_addresses.contains(&configured) {
+        return Ok(());
+    }
+
+    if allow_mismatch {
+        warn!(
+            configured = %configured,
+            signer = ?signer_addresses,
+            "Configured eoa_address does not match the signer, continuing (--allow-address-mismatch)"
+        );
+        return Ok(());
+    }
+
+    eyre::bail!(
+        "Configured eoa_address {} does not match the signer address(es) {:?}. \
+         Fix eoa_address or pass --allow-address-mismatch if this is intentional.",
+        configured,
+        signer_addresses
+    )
+}
+
 /// Convert ETH string from format_ether to f64 for metrics.
 fn eth_to_f64(eth_str: String) -> f64 {
     eth_str.parse::<f64>().unwrap_or(0.0)
 }
 
+/// Whether a SpokePool balance is below the `critical` threshold (0 disables it).
+pub fn is_spoke_pool_critical(balance: U256, critical: U256) -> bool {
+    balance < critical
+}
+
+/// In-flight deposits from the configured EOA to Unichain, resuming from `checkpoint`.
+///
+/// With `track_all_depositors_for_destination`, deposits from every depositor are
+/// returned, each with its own depositor. Only L1 blocks after the checkpoint are
+/// scanned for new deposits, and L2 only for fills of the known ones. On success
+/// `checkpoint` is advanced; persist it in the [`store::StateStore`] so a restart
+/// resumes too.
+pub async fn refresh_inflight_deposits<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    config: &config::Config,
+    retry_budget: &RetryBudget,
+    scan_coverage: &ScanCoverage,
+    checkpoint: &mut DepositCheckpoint,
+) -> eyre::Result<Vec<InFlightDeposit>>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let network = config.network_config();
+
+    *checkpoint = DepositStateProvider::new(
+        l1_provider,
+        l2_provider,
+        network.ethereum.spoke_pool,
+        network.unichain.spoke_pool,
+    )
+    .with_retry_budget(retry_budget.clone())
+    .with_scan_coverage(scan_coverage.clone())
+    .with_all_depositors(config.track_all_depositors_for_destination)
+    .refresh_inflight_deposits(
+        checkpoint,
+        config.eoa_address,
+        network.unichain.chain_id,
+        network.ethereum.chain_id,
+        config.deposit_lookback_secs,
+        network.ethereum.block_time_ms,
+        network.unichain.block_time_ms,
+    )
+    .await?;
+
+    Ok(checkpoint.inflight.clone())
+}
+
 /// Update all metrics gauges with current state.
 ///
-/// Queries balances, in-flight deposits, and pending withdrawals, then updates
+/// Queries balances, in-flight deposits, pending withdrawals and the proofs of proven
+/// ones (see [`check_proven_proofs`]), then updates
 /// the metrics accordingly. Errors are logged but don't fail the function.
+/// Scan retries are drawn from `retry_budget` and blocks covered added to
+/// `scan_coverage`; deposits resume from `deposit_checkpoint`. Proof health is compared
+/// with the previous cycle's in `proof_health`.
+///
+/// Returns what was read, for `--snapshot-diff`.
+#[allow(clippy::too_many_arguments)]
 pub async fn update_metrics<P1, P2>(
     l1_provider: P1,
     l2_provider: P2,
     config: &config::Config,
     metrics: &Metrics,
-) where
+    retry_budget: &RetryBudget,
+    scan_coverage: &ScanCoverage,
+    deposit_checkpoint: &mut DepositCheckpoint,
+    proof_health: &ProofHealthTracker,
+    portal_params: &PortalParamsCache,
+) -> StateSnapshot
+where
     P1: Provider + Clone,
     P2: Provider + Clone,
 {
     let network = config.network_config();
+    let mut snapshot = StateSnapshot::default();
 
     // 1. L1 EOA balance
-    match l1_provider.get_balance(config.eoa_address).await {
-        Ok(balance) => metrics.set_l1_eoa_balance_eth(eth_to_f64(format_ether(balance))),
-        Err(e) => warn!(error = %e, "Failed to get L1 EOA balance for metrics"),
-    }
+    let l1_balance = match l1_provider.get_balance(config.eoa_address).await {
+        Ok(balance) => {
+            metrics.set_l1_eoa_balance_eth(eth_to_f64(format_ether(balance)));
+            snapshot.l1_eoa_balance = Some(balance);
+            Some(balance)
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to get L1 EOA balance for metrics");
+            None
+        }
+    };
 
     // 2. L2 EOA balance
     match l2_provider.get_balance(config.eoa_address).await {
-        Ok(balance) => metrics.set_l2_eoa_balance_eth(eth_to_f64(format_ether(balance))),
+        Ok(balance) => {
+            metrics.set_l2_eoa_balance_eth(eth_to_f64(format_ether(balance)));
+            snapshot.l2_eoa_balance = Some(balance);
+        }
         Err(e) => warn!(error = %e, "Failed to get L2 EOA balance for metrics"),
     }
 
@@ -61,28 +235,52 @@ pub async fn update_metrics<P1, P2>(
     )
     .await
     {
-        Ok(balance) => metrics.set_spoke_pool_balance_eth(eth_to_f64(format_ether(balance.amount))),
+        Ok(balance) => {
+            metrics.set_spoke_pool_balance_eth(eth_to_f64(format_ether(balance.amount)));
+            snapshot.spoke_pool_balance = Some(balance.amount);
+
+            let critical = is_spoke_pool_critical(balance.amount, config.spoke_pool_critical_wei);
+            if critical {
+                warn!(
+                    spoke_pool_balance = %format_ether(balance.amount),
+                    critical_threshold = %format_ether(config.spoke_pool_critical_wei),
+                    l1_eoa_balance = ?l1_balance.map(format_ether),
+                    "ALERT: SpokePool balance below critical threshold"
+                );
+            }
+            metrics.set_spoke_pool_critical(critical);
+        }
         Err(e) => warn!(error = %e, "Failed to get SpokePool balance for metrics"),
     }
 
     // 4. In-flight deposits
-    match get_inflight_deposits(
+    match refresh_inflight_deposits(
         l1_provider.clone(),
         l2_provider.clone(),
-        network.ethereum.spoke_pool,
-        network.unichain.spoke_pool,
-        config.eoa_address,
-        network.unichain.chain_id,
-        network.ethereum.chain_id,
-        config.deposit_lookback_secs,
-        network.ethereum.block_time_secs,
-        network.unichain.block_time_secs,
+        config,
+        retry_budget,
+        scan_coverage,
+        deposit_checkpoint,
     )
     .await
     {
         Ok(deposits) => {
-            let total: U256 = deposits.iter().map(|d| d.input_amount).sum();
-            metrics.set_inflight_deposits(deposits.len(), eth_to_f64(format_ether(total)));
+            let (ours, others): (Vec<_>, Vec<_>) = deposits
+                .iter()
+                .partition(|d| d.depositor == config.eoa_address);
+            let total = |deposits: &[&InFlightDeposit]| {
+                eth_to_f64(format_ether(
+                    deposits.iter().map(|d| d.input_amount).sum::<U256>(),
+                ))
+            };
+            metrics.set_inflight_deposits(ours.len(), total(&ours));
+            metrics.set_inflight_deposits_of_others(others.len(), total(&others));
+            snapshot.deposits = Some(
+                deposits
+                    .iter()
+                    .map(|d| (d.deposit_id, d.input_amount))
+                    .collect(),
+            );
         }
         Err(e) => warn!(error = %e, "Failed to get in-flight deposits for metrics"),
     }
@@ -92,18 +290,20 @@ pub async fn update_metrics<P1, P2>(
         Ok(b) => b,
         Err(e) => {
             warn!(error = %e, "Failed to get L2 block number for withdrawal metrics");
-            return;
+            return snapshot;
         }
     };
-    let lookback_blocks = config.withdrawal_lookback_secs / network.unichain.block_time_secs;
+    let lookback_blocks = network.unichain.blocks_in(config.withdrawal_lookback_secs);
     let from_block = l2_current_block.saturating_sub(lookback_blocks);
 
     let state_provider = WithdrawalStateProvider::new(
-        l1_provider,
+        l1_provider.clone(),
         l2_provider,
         network.unichain.l1_portal,
         network.unichain.l2_to_l1_message_passer,
-    );
+    )
+    .with_retry_budget(retry_budget.clone())
+    .with_scan_coverage(scan_coverage.clone());
 
     match state_provider
         .get_pending_withdrawals(
@@ -133,15 +333,226 @@ pub async fn update_metrics<P1, P2>(
                 }
             }
 
+            snapshot.withdrawals =
+                Some(pending.iter().map(|w| (w.hash, w.status.clone())).collect());
             metrics.set_inflight_withdrawals(
                 initiated_count,
                 eth_to_f64(format_ether(initiated_amount)),
                 proven_count,
                 eth_to_f64(format_ether(proven_amount)),
             );
+
+            // 6. Proofs of proven withdrawals and their time to maturity
+            let params = portal_params
+                .get(&l1_provider, network.unichain.l1_portal)
+                .await;
+            let latest = l1_provider
+                .get_block_by_number(BlockNumberOrTag::Latest)
+                .await;
+            let (params, now) = match (params, latest) {
+                (Ok(params), Ok(Some(block))) => (params, block.header.timestamp),
+                (Err(e), _) => {
+                    warn!(error = %e, "Failed to read portal parameters for proof metrics");
+                    return snapshot;
+                }
+                (_, Err(e)) => {
+                    warn!(error = %e, "Failed to get latest L1 block for proof metrics");
+                    return snapshot;
+                }
+                (_, Ok(None)) => {
+                    warn!("Latest L1 block not found for proof metrics");
+                    return snapshot;
+                }
+            };
+            let (games, health) = check_proven_proofs(
+                &state_provider,
+                &pending,
+                params.respected_game_type,
+                now,
+                config.proof_at_risk_after_secs,
+                proof_health,
+            )
+            .await;
+            metrics.set_proven_withdrawal_games(
+                games.in_progress,
+                games.defender_wins,
+                games.challenger_wins,
+            );
+            metrics.set_proven_withdrawal_proofs(health.healthy, health.at_risk, health.invalid);
+            metrics.record_proofs_invalidated(health.invalidated);
+
+            let etas = finalize_etas(&pending, params.proof_maturity_delay, now);
+            metrics
+                .set_next_finalize_eta_seconds(etas.iter().map(|(_, remaining)| *remaining).min());
+            if !etas.is_empty() {
+                let summary = etas
+                    .iter()
+                    .map(|(hash, remaining)| format!("{hash} in {}", format_remaining(*remaining)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                info!(proven = etas.len(), "Proven withdrawals mature: {summary}");
+            }
         }
         Err(e) => warn!(error = %e, "Failed to get pending withdrawals for metrics"),
     }
+
+    snapshot
+}
+
+/// Seconds until a withdrawal proven at `proven_at` can be finalized at `now`, zero once
+/// the maturity delay has elapsed.
+pub const fn remaining_maturity(proven_at: u64, maturity_delay: u64, now: u64) -> u64 {
+    proven_at.saturating_add(maturity_delay).saturating_sub(now)
+}
+
+/// Remaining maturity of each proven withdrawal in `pending`, soonest first.
+pub fn finalize_etas(
+    pending: &[PendingWithdrawal],
+    maturity_delay: u64,
+    now: u64,
+) -> Vec<(WithdrawalHash, u64)> {
+    let mut etas: Vec<_> = pending
+        .iter()
+        .filter_map(|w| match w.status {
+            WithdrawalStatus::Proven { timestamp, .. } => {
+                Some((w.hash, remaining_maturity(timestamp, maturity_delay, now)))
+            }
+            _ => None,
+        })
+        .collect();
+    etas.sort_by_key(|(_, remaining)| *remaining);
+    etas
+}
+
+/// Format a number of seconds as e.g. `6d 23h 5m 3s`, leaving out zero units.
+fn format_remaining(secs: u64) -> String {
+    if secs == 0 {
+        return "0s".to_string();
+    }
+    let units = [
+        (secs / 86_400, "d"),
+        (secs % 86_400 / 3_600, "h"),
+        (secs % 3_600 / 60, "m"),
+        (secs % 60, "s"),
+    ];
+    units
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Proven withdrawals counted by the status of the dispute game they were proven against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProvenGameStatuses {
+    pub in_progress: usize,
+    pub defender_wins: usize,
+    pub challenger_wins: usize,
+}
+
+/// Proven withdrawals counted by the health of their proof, see [`ProofHealth`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProofHealthCounts {
+    pub healthy: usize,
+    pub at_risk: usize,
+    pub invalid: usize,
+    /// Invalid proofs that were not invalid the previous time they were checked
+    pub invalidated: usize,
+}
+
+/// Read the dispute game of the proof of each proven withdrawal in `pending` and
+/// classify the proof's health at `now`.
+///
+/// A proof that turned invalid can never be finalized, so the withdrawal must be
+/// proven again; that is alerted on once, when `tracker` first sees it invalid, rather
+/// than when finalizing fails after the maturity delay. A game still in progress
+/// `at_risk_after_secs` after proving is warned about. Proofs whose game can't be read
+/// are logged and left out of the counts.
+pub async fn check_proven_proofs<P1, P2>(
+    state_provider: &WithdrawalStateProvider<P1, P2>,
+    pending: &[PendingWithdrawal],
+    respected_game_type: u32,
+    now: u64,
+    at_risk_after_secs: u64,
+    tracker: &ProofHealthTracker,
+) -> (ProvenGameStatuses, ProofHealthCounts)
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let mut statuses = ProvenGameStatuses::default();
+    let mut counts = ProofHealthCounts::default();
+    let mut proven = HashSet::new();
+    for withdrawal in pending {
+        let WithdrawalStatus::Proven {
+            timestamp,
+            dispute_game_proxy,
+            proof_submitter,
+        } = withdrawal.status
+        else {
+            continue;
+        };
+        proven.insert(withdrawal.hash);
+
+        let proof = match state_provider
+            .proof_candidate(proof_submitter, dispute_game_proxy, timestamp)
+            .await
+        {
+            Ok(proof) => proof,
+            Err(e) => {
+                warn!(
+                    withdrawal_hash = %withdrawal.hash,
+                    dispute_game_proxy = %dispute_game_proxy,
+                    error = %e,
+                    "Failed to read dispute game of proven withdrawal"
+                );
+                continue;
+            }
+        };
+        match proof.game_status {
+            GAME_STATUS_CHALLENGER_WINS => statuses.challenger_wins += 1,
+            GAME_STATUS_DEFENDER_WINS => statuses.defender_wins += 1,
+            _ => statuses.in_progress += 1,
+        }
+
+        let health = proof.health(respected_game_type, now, at_risk_after_secs);
+        let turned_invalid = tracker.record(withdrawal.hash, health);
+        match health {
+            ProofHealth::Healthy => counts.healthy += 1,
+            ProofHealth::AtRisk => {
+                counts.at_risk += 1;
+                warn!(
+                    withdrawal_hash = %withdrawal.hash,
+                    dispute_game_proxy = %dispute_game_proxy,
+                    proven_secs_ago = now.saturating_sub(timestamp),
+                    "Dispute game of proven withdrawal is still in progress past its expected resolution"
+                );
+            }
+            ProofHealth::Invalid(rejection) => {
+                counts.invalid += 1;
+                if turned_invalid {
+                    counts.invalidated += 1;
+                    error!(
+                        withdrawal_hash = %withdrawal.hash,
+                        dispute_game_proxy = %dispute_game_proxy,
+                        reason = rejection.as_str(),
+                        "ALERT: proof of proven withdrawal turned invalid, the withdrawal must be proven again"
+                    );
+                }
+            }
+        }
+        debug!(
+            withdrawal_hash = %withdrawal.hash,
+            dispute_game_proxy = %dispute_game_proxy,
+            game_status = status_name(proof.game_status),
+            proof_health = health.as_str(),
+            "Proof health of proven withdrawal"
+        );
+    }
+    tracker.retain(&proven);
+
+    (statuses, counts)
 }
 
 pub async fn check_l2_spoke_pool_balance<P>(
@@ -173,140 +584,1089 @@ where
     Ok(balance)
 }
 
+/// A proof submitted while processing withdrawals, to be kept in the state store.
+#[derive(Debug, Clone)]
+pub struct SubmittedProof {
+    pub withdrawal: PendingWithdrawal,
+    pub proof: ProofRecord,
+}
+
+/// Outcome of [`process_withdrawals`].
+#[derive(Debug, Clone, Default)]
+pub struct ProcessedWithdrawals {
+    /// Proofs submitted
+    pub proofs: Vec<SubmittedProof>,
+    /// Finalize transactions sent, including those in `failed_calls`
+    pub finalized: usize,
+    /// Set when the L1 signer couldn't pay for gas. The withdrawals after the one that
+    /// hit it were skipped, as they would fail the same way.
+    pub insufficient_gas_funds: Option<InsufficientGasFunds>,
+    /// Withdrawals finalized without their funds being delivered
+    pub failed_calls: Vec<WithdrawalCallFailed>,
+    /// Initiated withdrawals left unproven for being worth less than
+    /// `min_prove_value_wei`
+    pub dust_skipped: usize,
+    /// Set when the scan added withdrawals to the [`FinalizedSet`], which then needs
+    /// persisting
+    pub finalized_changed: bool,
+}
+
+impl ProcessedWithdrawals {
+    /// Record `e` if it is the signer running out of gas funds, returning whether it was.
+    fn stop_if_out_of_gas(&mut self, e: &eyre::Report) -> bool {
+        self.insufficient_gas_funds = e.downcast_ref::<InsufficientGasFunds>().copied();
+        self.insufficient_gas_funds.is_some()
+    }
+}
+
+/// Outcome of [`process_pending_withdrawals`].
+#[derive(Debug, Clone)]
+pub enum WithdrawalPass {
+    /// Pending withdrawals were scanned and processed
+    Processed(ProcessedWithdrawals),
+    /// The portal is paused; prove/finalize work was skipped
+    PortalPaused,
+}
+
+impl WithdrawalPass {
+    pub const fn is_portal_paused(&self) -> bool {
+        matches!(self, Self::PortalPaused)
+    }
+
+    /// Proofs submitted during the pass.
+    pub fn proofs(&self) -> &[SubmittedProof] {
+        match self {
+            Self::Processed(processed) => &processed.proofs,
+            Self::PortalPaused => &[],
+        }
+    }
+
+    /// Finalize transactions sent during the pass.
+    pub const fn finalized(&self) -> usize {
+        match self {
+            Self::Processed(processed) => processed.finalized,
+            Self::PortalPaused => 0,
+        }
+    }
+
+    /// Set when the pass stopped because the L1 signer couldn't pay for gas.
+    pub const fn insufficient_gas_funds(&self) -> Option<&InsufficientGasFunds> {
+        match self {
+            Self::Processed(processed) => processed.insufficient_gas_funds.as_ref(),
+            Self::PortalPaused => None,
+        }
+    }
+
+    /// Withdrawals finalized during the pass whose call failed.
+    pub fn failed_calls(&self) -> &[WithdrawalCallFailed] {
+        match self {
+            Self::Processed(processed) => &processed.failed_calls,
+            Self::PortalPaused => &[],
+        }
+    }
+
+    /// Whether the pass changed the [`FinalizedSet`].
+    pub const fn finalized_changed(&self) -> bool {
+        match self {
+            Self::Processed(processed) => processed.finalized_changed,
+            Self::PortalPaused => false,
+        }
+    }
+
+    /// Initiated withdrawals the pass left unproven as dust.
+    pub const fn dust_skipped(&self) -> usize {
+        match self {
+            Self::Processed(processed) => processed.dust_skipped,
+            Self::PortalPaused => 0,
+        }
+    }
+}
+
+/// Current status of a dispute game, e.g. `in_progress` or `challenger_wins`.
+///
+/// All supported game types share the `IFaultDisputeGame` `status()` ABI.
+pub async fn dispute_game_status<P>(l1_provider: P, game: Address) -> eyre::Result<&'static str>
+where
+    P: Provider + Clone,
+{
+    let status = FaultGameReader::new(l1_provider).status(game).await?;
+    Ok(status_name(status))
+}
+
+/// A proof of a withdrawal with the factory index of its dispute game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofProvenance {
+    pub proof: ProofCandidate,
+    /// `None` when none of the searched factories created the game
+    pub game_index: Option<U256>,
+}
+
+/// Every proof submitted for `hash`, with who submitted it and the dispute game it was
+/// proven against.
+///
+/// Submitters are enumerated from the portal, `also_check` included. Each game's index
+/// is looked up in `factories` in order; a failed lookup leaves it unknown rather than
+/// failing the whole withdrawal.
+pub async fn proof_provenance<P1, P2>(
+    state_provider: &WithdrawalStateProvider<P1, P2>,
+    l1_provider: &P1,
+    factories: &[Address],
+    hash: WithdrawalHash,
+    also_check: Address,
+) -> eyre::Result<Vec<ProofProvenance>>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let mut provenance = vec![];
+    for proof in state_provider.proof_candidates(hash, also_check).await? {
+        let mut game_index = None;
+        for factory in factories {
+            match find_game_index(l1_provider, *factory, proof.dispute_game_proxy).await {
+                Ok(Some(index)) => {
+                    game_index = Some(index);
+                    break;
+                }
+                Ok(None) => {}
+                Err(e) => warn!(
+                    game = %proof.dispute_game_proxy,
+                    factory = %factory,
+                    error = %e,
+                    "Failed to look up dispute game index"
+                ),
+            }
+        }
+        provenance.push(ProofProvenance { proof, game_index });
+    }
+    Ok(provenance)
+}
+
 /// Process all pending withdrawals - finalize mature ones, prove initiated ones.
 ///
 /// Scans for withdrawals based on lookback time and processes them based on their status:
 /// - Proven + mature: Execute finalize
 /// - Initiated: Execute prove
 ///
-/// Errors are logged but don't halt processing of other withdrawals.
+/// `recovered` holds withdrawals found outside the lookback by
+/// [`recovery::recover_proven_withdrawals`]; they are processed alongside the scan results.
+///
+/// Skips everything if the portal is paused, since proving and finalizing would revert.
+/// Scan retries are drawn from `retry_budget`. Withdrawals in `finalized` are skipped
+/// without reading the portal, and those found finalized are added to it. Proving waits
+/// for `layout_check` to pass.
+///
+/// Errors are logged but don't halt processing of other withdrawals. Repeats of the same
+/// failure for a withdrawal are throttled by `log_throttle`. Transactions broadcast
+/// without a receipt yet are kept in `pending_transactions`, and a withdrawal isn't
+/// sent again while one is still in the mempool. Withdrawals that regressed from proven
+/// are held back by `regression_guard` in case an L1 reorg undid the proof. Waiting for
+/// covering dispute games stops at `cycle_deadline`.
+#[allow(clippy::too_many_arguments)]
 pub async fn process_pending_withdrawals<P1, P2>(
     l1_provider: P1,
     l2_provider: P2,
-    l1_signer: SignerFn,
+    l1_signer: Arc<dyn TransactionSigner>,
     config: &config::Config,
-) -> eyre::Result<()>
+    recovered: Vec<PendingWithdrawal>,
+    retry_budget: &RetryBudget,
+    scan_coverage: &ScanCoverage,
+    portal_params: &PortalParamsCache,
+    finalized: &FinalizedSet,
+    layout_check: &StorageLayoutCheck,
+    log_throttle: &ThrottledLogger<WithdrawalLogKey>,
+    pending_transactions: &PendingTransactions,
+    regression_guard: &RegressionGuard,
+    cycle_deadline: Instant,
+) -> eyre::Result<WithdrawalPass>
 where
     P1: Provider + Clone,
     P2: Provider + Clone,
+{
+    let state_provider = config
+        .chain_context(l1_provider.clone(), l2_provider.clone())
+        .state_provider()
+        .with_retry_budget(retry_budget.clone())
+        .with_scan_coverage(scan_coverage.clone())
+        .with_finalized_set(finalized.clone())
+        .with_portal_params(portal_params.clone());
+    let executor = ActionExecutor::new(
+        l1_provider,
+        l2_provider.clone(),
+        l1_signer,
+        config,
+        portal_params,
+        retry_budget,
+        layout_check,
+        log_throttle,
+        pending_transactions,
+        cycle_deadline,
+    );
+
+    let mut pass = run_withdrawal_pass(
+        &state_provider,
+        &executor,
+        &l2_provider,
+        config,
+        recovered,
+        log_throttle,
+        regression_guard,
+    )
+    .await?;
+    if let WithdrawalPass::Processed(processed) = &mut pass {
+        processed.finalized_changed = finalized.take_changed();
+    }
+    Ok(pass)
+}
+
+/// Re-check a sample of the withdrawals recorded as finalized in `store` against the
+/// portal, dropping any that are not.
+///
+/// `finalized` is the set scans use; it is updated along with the store. Successive
+/// checks start at different offsets so the whole set is covered over time. Returns the
+/// hashes that were dropped.
+pub async fn verify_finalized_withdrawals<P: Provider>(
+    l1_provider: &P,
+    config: &config::Config,
+    finalized: &FinalizedSet,
+    store: &mut StateStore,
+    now: u64,
+) -> eyre::Result<Vec<WithdrawalHash>> {
+    let sample = finalized.sample(config.finalized_verify_sample_size, now as usize);
+    let removed = verify_finalized(
+        l1_provider,
+        config.network_config().unichain.l1_portal,
+        finalized,
+        &sample,
+    )
+    .await?;
+
+    store.set_finalized(finalized);
+    store.set_finalized_verified_at(now);
+    Ok(removed)
+}
+
+/// [`process_pending_withdrawals`] reading withdrawals from `state` and proving and
+/// finalizing them through `executor`.
+pub async fn run_withdrawal_pass<S, E, P>(
+    state: &S,
+    executor: &E,
+    l2_provider: &P,
+    config: &config::Config,
+    recovered: Vec<PendingWithdrawal>,
+    log_throttle: &ThrottledLogger<WithdrawalLogKey>,
+    regression_guard: &RegressionGuard,
+) -> eyre::Result<WithdrawalPass>
+where
+    S: WithdrawalState,
+    E: WithdrawalExecutor,
+    P: Provider,
 {
     let network = config.network_config();
 
+    if state.is_paused().await? {
+        warn!(
+            portal = %network.unichain.l1_portal,
+            "OptimismPortal is paused, skipping prove/finalize"
+        );
+        return Ok(WithdrawalPass::PortalPaused);
+    }
+
     // Calculate from_block based on lookback time
     let l2_current_block = l2_provider.get_block_number().await?;
-    let lookback_blocks = config.withdrawal_lookback_secs / network.unichain.block_time_secs;
+    let lookback_blocks = network.unichain.blocks_in(config.withdrawal_lookback_secs);
     let from_block = l2_current_block.saturating_sub(lookback_blocks);
 
-    let state_provider = WithdrawalStateProvider::new(
-        l1_provider.clone(),
-        l2_provider.clone(),
-        network.unichain.l1_portal,
-        network.unichain.l2_to_l1_message_passer,
-    );
-
-    let pending = state_provider
-        .get_pending_withdrawals(
+    let scan = state
+        .scan_pending_withdrawals(
             BlockNumberOrTag::Number(from_block),
             BlockNumberOrTag::Latest,
             config.eoa_address,
+            config.tolerate_partial_scans,
         )
         .await?;
 
+    if !scan.complete {
+        warn!(
+            found = scan.items.len(),
+            scanned_to = ?scan.scanned_to,
+            "Withdrawal scan incomplete, acting on partial results"
+        );
+    }
+    let mut pending = scan.items;
+
+    let scanned: HashSet<_> = pending.iter().map(|w| w.hash).collect();
+    let recovered: Vec<_> = recovered
+        .into_iter()
+        .filter(|w| !scanned.contains(&w.hash))
+        .collect();
+    if !recovered.is_empty() {
+        info!(
+            count = recovered.len(),
+            "Adding recovered withdrawals from outside the lookback"
+        );
+        pending.extend(recovered);
+    }
+    let pending = regression_guard.filter(pending, Instant::now());
+
     if pending.is_empty() {
         info!("No pending withdrawals found");
-        return Ok(());
+        return Ok(WithdrawalPass::Processed(ProcessedWithdrawals::default()));
     }
 
     info!(count = pending.len(), "Found pending withdrawals");
 
-    for withdrawal in &pending {
-        match &withdrawal.status {
-            WithdrawalStatus::Proven { .. } => {
-                if let Err(e) = finalize_withdrawal(
-                    l1_provider.clone(),
-                    l2_provider.clone(),
-                    l1_signer.clone(),
-                    network.unichain.l1_portal,
-                    config.eoa_address,
-                    withdrawal,
-                    config.dry_run,
-                )
-                .await
-                {
-                    warn!(
-                        withdrawal_hash = %withdrawal.hash,
-                        error = %e,
-                        "Failed to finalize withdrawal"
-                    );
-                }
-            }
-            WithdrawalStatus::Initiated => {
-                if let Err(e) = prove_withdrawal(
-                    l1_provider.clone(),
-                    l2_provider.clone(),
-                    l1_signer.clone(),
-                    network.unichain.l1_portal,
-                    network.unichain.l1_dispute_game_factory,
-                    config.eoa_address,
-                    withdrawal,
-                    config.dry_run,
-                )
-                .await
-                {
-                    warn!(
-                        withdrawal_hash = %withdrawal.hash,
-                        error = %e,
-                        "Failed to prove withdrawal"
-                    );
-                }
-            }
-            WithdrawalStatus::Finalized => {
-                // Should not appear in pending list, but handle gracefully
-            }
-        }
-    }
+    let processed =
+        route_withdrawals(executor, &pending, config.min_prove_value_wei, log_throttle).await?;
 
-    Ok(())
+    Ok(WithdrawalPass::Processed(processed))
 }
 
-/// Finalize a single proven withdrawal.
-async fn finalize_withdrawal<P1, P2>(
+/// Prove or finalize each of `pending` according to its status.
+///
+/// Initiated withdrawals are proven through [`prove_initiated`]. Does not check whether
+/// the portal is paused; see [`process_pending_withdrawals`]. Errors on individual
+/// withdrawals are logged and don't halt the others, except the signer running out of
+/// gas funds, which stops the pass. Waiting for covering dispute games stops at
+/// `cycle_deadline`.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_withdrawals<P1, P2>(
     l1_provider: P1,
     l2_provider: P2,
-    signer: SignerFn,
-    portal_address: Address,
-    proof_submitter: Address,
-    withdrawal: &PendingWithdrawal,
-    dry_run: bool,
-) -> eyre::Result<()>
+    l1_signer: Arc<dyn TransactionSigner>,
+    config: &config::Config,
+    pending: &[PendingWithdrawal],
+    portal_params: &PortalParamsCache,
+    retry_budget: &RetryBudget,
+    layout_check: &StorageLayoutCheck,
+    log_throttle: &ThrottledLogger<WithdrawalLogKey>,
+    pending_transactions: &PendingTransactions,
+    cycle_deadline: Instant,
+) -> eyre::Result<ProcessedWithdrawals>
 where
     P1: Provider + Clone,
     P2: Provider + Clone,
 {
-    let finalize = Finalize {
-        portal_address,
-        withdrawal: withdrawal.transaction.clone(),
-        withdrawal_hash: withdrawal.hash,
-        proof_submitter,
-        from: proof_submitter,
-    };
+    let executor = ActionExecutor::new(
+        l1_provider,
+        l2_provider,
+        l1_signer,
+        config,
+        portal_params,
+        retry_budget,
+        layout_check,
+        log_throttle,
+        pending_transactions,
+        cycle_deadline,
+    );
+    route_withdrawals(&executor, pending, config.min_prove_value_wei, log_throttle).await
+}
 
-    let mut action = FinalizeAction::new(l1_provider, l2_provider, signer, finalize);
+/// Hand each of `pending` to `executor` according to its status: proven ones are
+/// finalized one by one, initiated ones proven together, finalized ones left alone.
+///
+/// Initiated withdrawals worth less than `min_prove_value` are not proven, and the
+/// others are proven largest first.
+async fn route_withdrawals<E: WithdrawalExecutor>(
+    executor: &E,
+    pending: &[PendingWithdrawal],
+    min_prove_value: U256,
+    log_throttle: &ThrottledLogger<WithdrawalLogKey>,
+) -> eyre::Result<ProcessedWithdrawals> {
+    let mut initiated = vec![];
+    let mut failed_calls = vec![];
+    let mut finalized = 0;
+    for withdrawal in pending {
+        match &withdrawal.status {
+            WithdrawalStatus::Proven { .. } => match executor.finalize(withdrawal).await {
+                Ok(sent) => finalized += usize::from(sent),
+                Err(e) => {
+                    // Finalized nonetheless, and already alerted on
+                    if let Some(failed) = e.downcast_ref::<WithdrawalCallFailed>() {
+                        failed_calls.push(*failed);
+                        finalized += 1;
+                        continue;
+                    }
+                    if let Some(repeats) = log_throttle.check((withdrawal.hash, "finalize")) {
+                        warn!(
+                            withdrawal_hash = %withdrawal.hash,
+                            error = %e,
+                            repeats = repeats.note(),
+                            "Failed to finalize withdrawal"
+                        );
+                    }
+                    let mut processed = ProcessedWithdrawals {
+                        failed_calls: failed_calls.clone(),
+                        finalized,
+                        ..Default::default()
+                    };
+                    if processed.stop_if_out_of_gas(&e) {
+                        return Ok(processed);
+                    }
+                }
+            },
+            WithdrawalStatus::Initiated => initiated.push(withdrawal),
+            WithdrawalStatus::Finalized => {
+                // Should not appear in pending list, but handle gracefully
+            }
+        }
+    }
 
-    if !action.is_ready().await? {
+    let (mut initiated, dust): (Vec<_>, Vec<_>) = initiated
+        .into_iter()
+        .partition(|w| w.transaction.value >= min_prove_value);
+    if !dust.is_empty() {
         info!(
-            withdrawal_hash = %withdrawal.hash,
-            "Withdrawal not ready to finalize (proof not mature)"
+            count = dust.len(),
+            total_eth = %format_ether(dust.iter().map(|w| w.transaction.value).sum::<U256>()),
+            min_prove_value_eth = %format_ether(min_prove_value),
+            "Not proving withdrawals below the minimum value"
         );
-        return Ok(());
     }
+    initiated.sort_by_key(|w| std::cmp::Reverse(w.transaction.value));
 
-    if dry_run {
-        info!(
-            withdrawal_hash = %withdrawal.hash,
-            "[DRY-RUN] Would finalize withdrawal"
-        );
-        return Ok(());
+    let mut processed = executor.prove(&initiated).await?;
+    processed.failed_calls = failed_calls;
+    processed.finalized = finalized;
+    processed.dust_skipped = dust.len();
+    Ok(processed)
+}
+
+/// Proves and finalizes the withdrawals of a withdrawal pass.
+pub trait WithdrawalExecutor: Send + Sync {
+    /// Finalize a proven withdrawal once its proof has matured, returning whether a
+    /// finalize transaction was sent.
+    fn finalize(
+        &self,
+        withdrawal: &PendingWithdrawal,
+    ) -> impl Future<Output = eyre::Result<bool>> + Send;
+
+    /// Prove `initiated` withdrawals.
+    fn prove(
+        &self,
+        initiated: &[&PendingWithdrawal],
+    ) -> impl Future<Output = eyre::Result<ProcessedWithdrawals>> + Send;
+}
+
+/// [`WithdrawalExecutor`] that sends transactions through the prove and finalize
+/// actions, as configured by `config`.
+struct ActionExecutor<'a, P1, P2> {
+    l1_provider: P1,
+    l2_provider: P2,
+    l1_signer: Arc<dyn TransactionSigner>,
+    config: &'a config::Config,
+    portal_params: &'a PortalParamsCache,
+    retry_budget: &'a RetryBudget,
+    layout_check: &'a StorageLayoutCheck,
+    log_throttle: &'a ThrottledLogger<WithdrawalLogKey>,
+    pending_transactions: &'a PendingTransactions,
+    cycle_deadline: Instant,
+    relay: Option<SponsoredRelay>,
+}
+
+impl<'a, P1, P2> ActionExecutor<'a, P1, P2> {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        l1_provider: P1,
+        l2_provider: P2,
+        l1_signer: Arc<dyn TransactionSigner>,
+        config: &'a config::Config,
+        portal_params: &'a PortalParamsCache,
+        retry_budget: &'a RetryBudget,
+        layout_check: &'a StorageLayoutCheck,
+        log_throttle: &'a ThrottledLogger<WithdrawalLogKey>,
+        pending_transactions: &'a PendingTransactions,
+        cycle_deadline: Instant,
+    ) -> Self {
+        let relay = config
+            .sponsored_relay_url
+            .as_deref()
+            .map(SponsoredRelay::new);
+        Self {
+            l1_provider,
+            l2_provider,
+            l1_signer,
+            config,
+            portal_params,
+            retry_budget,
+            layout_check,
+            log_throttle,
+            pending_transactions,
+            cycle_deadline,
+            relay,
+        }
+    }
+}
+
+impl<P1, P2> WithdrawalExecutor for ActionExecutor<'_, P1, P2>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    async fn finalize(&self, withdrawal: &PendingWithdrawal) -> eyre::Result<bool> {
+        finalize_withdrawal(
+            self.l1_provider.clone(),
+            self.l2_provider.clone(),
+            self.l1_signer.clone(),
+            &self.config.network_config().unichain,
+            withdrawal,
+            self.portal_params,
+            self.relay.as_ref(),
+            self.config.execution_mode,
+            self.config.audit_log().clone(),
+            self.pending_transactions,
+        )
+        .await
+    }
+
+    async fn prove(&self, initiated: &[&PendingWithdrawal]) -> eyre::Result<ProcessedWithdrawals> {
+        // Dedicated endpoint for eth_getProof, if configured, paired with the main L2
+        // endpoint to cross-check the block the proof is built from
+        let processed = match &self.config.l2_proof_rpc_url {
+            Some(url) => {
+                prove_initiated(
+                    self.l1_provider.clone(),
+                    client::create_provider(url, self.config.provider_timeouts()).await?,
+                    Some(
+                        client::create_provider(
+                            &self.config.l2_rpc_url,
+                            self.config.provider_timeouts(),
+                        )
+                        .await?,
+                    ),
+                    self.l1_signer.clone(),
+                    self.config,
+                    initiated,
+                    self.portal_params,
+                    self.retry_budget,
+                    self.layout_check,
+                    self.log_throttle,
+                    self.pending_transactions,
+                    self.cycle_deadline,
+                )
+                .await
+            }
+            None => {
+                prove_initiated(
+                    self.l1_provider.clone(),
+                    self.l2_provider.clone(),
+                    None,
+                    self.l1_signer.clone(),
+                    self.config,
+                    initiated,
+                    self.portal_params,
+                    self.retry_budget,
+                    self.layout_check,
+                    self.log_throttle,
+                    self.pending_transactions,
+                    self.cycle_deadline,
+                )
+                .await
+            }
+        };
+
+        Ok(processed)
+    }
+}
+
+/// Prove `initiated` withdrawals, with one storage proof request per dispute game.
+///
+/// Up to `max_concurrent_actions` covering-game searches run at once, sharing the chain
+/// context's game cache, so withdrawals from nearby L2 blocks mostly reuse each other's
+/// game reads. Withdrawals are grouped by the game that covers them and each group's
+/// proofs come from a single `eth_getProof` call, as many groups at once, retried while
+/// `retry_budget` allows. Prove transactions are sent one at a time. If a group's
+/// request fails, its withdrawals are proven one by one so a single bad withdrawal
+/// doesn't hold back the rest, unless the signer runs out of gas funds. In log-only mode
+/// nothing is generated.
+///
+/// Nothing is proven until `layout_check` confirms the message passer's storage layout
+/// against one of `initiated`. Repeated failures for the same withdrawal are throttled
+/// by `log_throttle`. Withdrawals whose prove transaction is still in the mempool are
+/// left out. Waiting for a covering dispute game stops at `cycle_deadline`.
+#[allow(clippy::too_many_arguments)]
+async fn prove_initiated<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    reference_l2_provider: Option<P2>,
+    signer: Arc<dyn TransactionSigner>,
+    config: &config::Config,
+    initiated: &[&PendingWithdrawal],
+    portal_params: &PortalParamsCache,
+    retry_budget: &RetryBudget,
+    layout_check: &StorageLayoutCheck,
+    log_throttle: &ThrottledLogger<WithdrawalLogKey>,
+    pending_transactions: &PendingTransactions,
+    cycle_deadline: Instant,
+) -> ProcessedWithdrawals
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let chain = config.chain_context(l1_provider.clone(), l2_provider.clone());
+
+    // Proofs from the wrong slot would be rejected by the portal
+    let checked = initiated
+        .first()
+        .filter(|_| config.execution_mode != ExecutionMode::LogOnly);
+    if let Some(first) = checked {
+        if let Err(e) = layout_check
+            .ensure(
+                chain.l2_provider(),
+                chain.addresses().message_passer,
+                first.hash,
+            )
+            .await
+        {
+            if e.downcast_ref::<StorageLayoutMismatch>().is_some() {
+                error!(
+                    error = %e,
+                    "ALERT: message passer storage layout changed, refusing to prove withdrawals"
+                );
+            } else {
+                warn!(error = %e, "Failed to verify message passer storage layout, not proving this cycle");
+            }
+            return ProcessedWithdrawals::default();
+        }
+    }
+
+    let mut unsent = Vec::with_capacity(initiated.len());
+    for &withdrawal in initiated {
+        if !awaiting_broadcast(
+            &l1_provider,
+            pending_transactions,
+            withdrawal.hash,
+            TransactionKind::ProveWithdrawal,
+        )
+        .await
+        {
+            unsent.push(withdrawal);
+        }
+    }
+
+    let concurrency = config.max_concurrent_actions.max(1);
+    let (ungrouped, unsent) = if config.execution_mode == ExecutionMode::LogOnly {
+        (unsent, vec![])
+    } else {
+        (vec![], unsent)
+    };
+    // Futures are built up front: a closure inside the stream would make this
+    // function's future not `Send`
+    let searches: Vec<_> = unsent
+        .into_iter()
+        .map(|withdrawal| {
+            let chain = &chain;
+            async move {
+                let game = chain
+                    .find_covering_game(
+                        portal_params,
+                        withdrawal.l2_block,
+                        config.min_dispute_games,
+                        &config.allowed_game_types,
+                        config.game_selection_strategy,
+                    )
+                    .await;
+                (withdrawal, game)
+            }
+        })
+        .collect();
+    let lookups: Vec<_> = stream::iter(searches).buffered(concurrency).collect().await;
+    let stats = chain.game_cache().stats();
+    debug!(
+        withdrawals = lookups.len(),
+        hits = stats.hits,
+        misses = stats.misses,
+        "Searched for covering dispute games"
+    );
+
+    // Group by covering game, oldest first. Game indices are per factory.
+    let mut by_game: BTreeMap<(U256, Address), (CoveringGame, Vec<&PendingWithdrawal>)> =
+        BTreeMap::new();
+    let mut awaiting_game = vec![];
+    for (withdrawal, game) in lookups {
+        match game {
+            Ok(game) => by_game
+                .entry((game.index, game.factory))
+                .or_insert_with(|| (game, vec![]))
+                .1
+                .push(withdrawal),
+            Err(e) if e.downcast_ref::<GameNotYetAvailable>().is_some() => {
+                let expected_at = e
+                    .downcast_ref::<GameNotYetAvailable>()
+                    .and_then(|e| e.expected_at);
+                if let (Some(expected_at), true) = (expected_at, config.wait_for_game_secs > 0) {
+                    awaiting_game.push((withdrawal, expected_at));
+                } else if let Some(repeats) = log_throttle.check((withdrawal.hash, "no_game")) {
+                    info!(
+                        withdrawal_hash = %withdrawal.hash,
+                        reason = %e,
+                        repeats = repeats.note(),
+                        "No dispute game covers withdrawal yet, will retry next cycle"
+                    );
+                }
+            }
+            Err(e) => {
+                if let Some(repeats) = log_throttle.check((withdrawal.hash, "find_game")) {
+                    warn!(
+                        withdrawal_hash = %withdrawal.hash,
+                        error = %e,
+                        repeats = repeats.note(),
+                        "Failed to find dispute game for withdrawal"
+                    );
+                }
+            }
+        }
+    }
+
+    // Games expected within the wait window are waited for rather than left to a later
+    // cycle
+    if !awaiting_game.is_empty() {
+        for (game, withdrawal) in
+            wait_for_covering_games(&chain, config, portal_params, awaiting_game, cycle_deadline)
+                .await
+        {
+            by_game
+                .entry((game.index, game.factory))
+                .or_insert_with(|| (game, vec![]))
+                .1
+                .push(withdrawal);
+        }
+    }
+
+    let requests: Vec<_> = by_game
+        .into_values()
+        .map(|(game, withdrawals)| {
+            let chain = &chain;
+            async move {
+                let batch: Vec<_> = withdrawals
+                    .iter()
+                    .map(|w| (w.hash, w.transaction.clone()))
+                    .collect();
+                let proofs = chain
+                    .generate_proofs_for_game(&game, &batch, retry_budget)
+                    .await;
+                (game, withdrawals, proofs)
+            }
+        })
+        .collect();
+    let generated: Vec<_> = stream::iter(requests).buffered(concurrency).collect().await;
+
+    let mut batches: Vec<(&PendingWithdrawal, Option<ProveWithdrawalParams>)> =
+        ungrouped.into_iter().map(|w| (w, None)).collect();
+    for (game, withdrawals, proofs) in generated {
+        match proofs {
+            Ok(generated) => {
+                debug!(
+                    game_factory = %game.factory,
+                    game_index = %game.index,
+                    withdrawals = generated.len(),
+                    "Generated storage proofs for dispute game"
+                );
+                batches.extend(withdrawals.into_iter().zip(generated.into_iter().map(Some)));
+            }
+            Err(e) => {
+                warn!(
+                    game_factory = %game.factory,
+                    game_index = %game.index,
+                    withdrawals = withdrawals.len(),
+                    error = %e,
+                    "Failed to generate batched proofs, proving individually"
+                );
+                batches.extend(withdrawals.into_iter().map(|w| (w, None)));
+            }
+        }
+    }
+
+    let mut processed = ProcessedWithdrawals::default();
+    for (withdrawal, proof) in batches {
+        match prove_withdrawal(
+            l1_provider.clone(),
+            l2_provider.clone(),
+            reference_l2_provider.clone(),
+            signer.clone(),
+            config,
+            withdrawal,
+            proof,
+            portal_params,
+            chain.game_cache(),
+            retry_budget,
+            pending_transactions,
+        )
+        .await
+        {
+            Ok(Some(proof)) => processed.proofs.push(SubmittedProof {
+                withdrawal: withdrawal.clone(),
+                proof,
+            }),
+            Ok(None) => {}
+            Err(e) => {
+                if let Some(repeats) = log_throttle.check((withdrawal.hash, "prove")) {
+                    warn!(
+                        withdrawal_hash = %withdrawal.hash,
+                        error = %e,
+                        repeats = repeats.note(),
+                        "Failed to prove withdrawal"
+                    );
+                }
+                if processed.stop_if_out_of_gas(&e) {
+                    break;
+                }
+            }
+        }
+    }
+
+    processed
+}
+
+/// How often [`wait_for_covering_games`] checks the factory for a new game.
+const GAME_WAIT_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+/// Wait for dispute games covering `awaiting`, each paired with the time its covering
+/// game is expected, and return the ones covered in time with their game.
+///
+/// Only withdrawals whose game is expected within `wait_for_game_secs` are waited for,
+/// and never past `cycle_deadline` so the cycle keeps its pace however long its earlier
+/// steps took. The rest are left to a later cycle.
+async fn wait_for_covering_games<'a, P1, P2>(
+    chain: &ChainContext<P1, P2>,
+    config: &config::Config,
+    portal_params: &PortalParamsCache,
+    awaiting: Vec<(&'a PendingWithdrawal, u64)>,
+    cycle_deadline: Instant,
+) -> Vec<(CoveringGame, &'a PendingWithdrawal)>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let deadline =
+        cycle_deadline.min(Instant::now() + Duration::from_secs(config.wait_for_game_secs));
+    let window = deadline.saturating_duration_since(Instant::now()).as_secs();
+
+    let latest = match chain
+        .l1_provider()
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await
+    {
+        Ok(Some(block)) => block.header,
+        Ok(None) => {
+            warn!("Latest L1 block not found, not waiting for dispute games");
+            return vec![];
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to fetch latest L1 block, not waiting for dispute games");
+            return vec![];
+        }
+    };
+
+    let (mut waiting, later): (Vec<_>, Vec<_>) = awaiting
+        .into_iter()
+        .partition(|(_, expected_at)| *expected_at <= latest.timestamp + window);
+    for (withdrawal, expected_at) in later {
+        debug!(
+            withdrawal_hash = %withdrawal.hash,
+            expected_in = expected_at.saturating_sub(latest.timestamp),
+            "Covering dispute game not expected this cycle, will retry next cycle"
+        );
+    }
+
+    let mut covered = vec![];
+    while let Some(lowest) = waiting.iter().map(|(w, _)| w.l2_block).min() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        info!(
+            withdrawals = waiting.len(),
+            l2_block = lowest,
+            timeout_secs = remaining.as_secs(),
+            "Waiting for dispute game covering withdrawals"
+        );
+        match chain
+            .wait_for_covering_game(
+                portal_params,
+                lowest,
+                latest.number,
+                GAME_WAIT_POLL_INTERVAL,
+                remaining,
+            )
+            .await
+        {
+            Ok(Some(proxy)) => debug!(game = %proxy, "Covering dispute game created"),
+            Ok(None) => {
+                info!(
+                    withdrawals = waiting.len(),
+                    "No covering dispute game within the wait, will retry next cycle"
+                );
+                break;
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to wait for covering dispute game");
+                break;
+            }
+        }
+
+        // The new game may cover only some of them, or need more games on top
+        let before = covered.len();
+        let mut still_waiting = vec![];
+        for (withdrawal, expected_at) in waiting {
+            match chain
+                .find_covering_game(
+                    portal_params,
+                    withdrawal.l2_block,
+                    config.min_dispute_games,
+                    &config.allowed_game_types,
+                    config.game_selection_strategy,
+                )
+                .await
+            {
+                Ok(game) => covered.push((game, withdrawal)),
+                Err(_) => still_waiting.push((withdrawal, expected_at)),
+            }
+        }
+        waiting = still_waiting;
+        if covered.len() == before {
+            break;
+        }
+    }
+
+    covered
+}
+
+/// Alert that the signer on `chain` can't pay for gas.
+///
+/// Unlike a failed RPC call this won't clear up on a later cycle: the EOA needs topping up.
+pub fn alert_insufficient_gas_funds(
+    metrics: &Metrics,
+    chain: Chain,
+    shortage: &InsufficientGasFunds,
+) {
+    error!(
+        chain = chain.as_str(),
+        balance = %format_ether(shortage.balance),
+        needed = %format_ether(shortage.needed),
+        "ALERT: signer cannot pay for gas, top up the EOA"
+    );
+    metrics.record_insufficient_gas_funds(chain.as_str());
+}
+
+/// Log the outcome of simulating `action` in [`ExecutionMode::Simulate`].
+///
+/// Returns whether the transaction would succeed.
+fn report_simulation(action: &str, outcome: eyre::Result<()>) -> bool {
+    match outcome {
+        Ok(()) => {
+            info!("[SIMULATE] {action}: would succeed");
+            true
+        }
+        Err(e) => {
+            warn!(error = %e, "[SIMULATE] {action}: would revert");
+            false
+        }
+    }
+}
+
+/// Whether the `kind` transaction last broadcast for `withdrawal_hash` is still in the
+/// mempool, in which case another must not be sent.
+///
+/// If that can't be told, it is assumed to be: skipping a cycle costs less than a
+/// transaction bound to revert.
+async fn awaiting_broadcast<P: Provider>(
+    l1_provider: &P,
+    pending_transactions: &PendingTransactions,
+    withdrawal_hash: WithdrawalHash,
+    kind: TransactionKind,
+) -> bool {
+    match pending_transactions
+        .outstanding(l1_provider, withdrawal_hash, kind)
+        .await
+    {
+        Ok(None) => false,
+        Ok(Some(tx_hash)) => {
+            info!(
+                %withdrawal_hash,
+                %tx_hash,
+                ?kind,
+                "Earlier transaction still pending, not sending another"
+            );
+            true
+        }
+        Err(e) => {
+            warn!(
+                %withdrawal_hash,
+                ?kind,
+                error = %e,
+                "Failed to look up pending transaction, not sending another this cycle"
+            );
+            true
+        }
+    }
+}
+
+/// Finalize a single proven withdrawal.
+///
+/// The withdrawal is finalized with whichever valid proof on L1 matures first, ours or
+/// another submitter's. With `relay`, the signed transaction is submitted through the
+/// sponsoring relay. Unless `mode` is live, it is only simulated or logged. Nothing is
+/// sent while an earlier finalize recorded in `pending_transactions` is still in the
+/// mempool. Returns whether a finalize transaction was sent.
+#[allow(clippy::too_many_arguments)]
+async fn finalize_withdrawal<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    signer: Arc<dyn TransactionSigner>,
+    unichain: &UnichainConfig,
+    withdrawal: &PendingWithdrawal,
+    portal_params: &PortalParamsCache,
+    relay: Option<&SponsoredRelay>,
+    mode: ExecutionMode,
+    audit_log: AuditLog,
+    pending_transactions: &PendingTransactions,
+) -> eyre::Result<bool>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    if awaiting_broadcast(
+        &l1_provider,
+        pending_transactions,
+        withdrawal.hash,
+        TransactionKind::FinalizeWithdrawal,
+    )
+    .await
+    {
+        return Ok(false);
+    }
+
+    let finalize = Finalize::from_pending(withdrawal, unichain)?;
+
+    let mut action = FinalizeAction::new(l1_provider.clone(), l2_provider, signer, finalize)
+        .with_portal_params(portal_params.clone())
+        .with_audit_log(audit_log)
+        .with_pending_transactions(pending_transactions.clone());
+    if let Some(relay) = relay {
+        action = action.with_sponsored_relay(relay.clone());
+    }
+
+    if !action.is_ready().await? {
+        info!(
+            withdrawal_hash = %withdrawal.hash,
+            "Withdrawal not ready to finalize (proof not mature)"
+        );
+        return Ok(false);
+    }
+
+    match mode {
+        ExecutionMode::Live => {}
+        ExecutionMode::Simulate => {
+            report_simulation(
+                &format!("Finalizing withdrawal {}", withdrawal.hash),
+                action.simulate().await,
+            );
+            return Ok(false);
+        }
+        ExecutionMode::LogOnly => {
+            info!(
+                withdrawal_hash = %withdrawal.hash,
+                "[DRY-RUN] Would finalize withdrawal"
+            );
+            return Ok(false);
+        }
     }
 
     info!(withdrawal_hash = %withdrawal.hash, "Finalizing withdrawal");
@@ -316,74 +1676,154 @@ where
             info!(
                 withdrawal_hash = %withdrawal.hash,
                 tx_hash = %result.tx_hash,
+                proof_submitter = ?action.proof_submitter(),
                 "Withdrawal finalized"
             );
         }
         Err(e) => {
-            error!(
-                withdrawal_hash = %withdrawal.hash,
-                error = %e,
-                "Failed to execute finalize"
-            );
+            if let Some(failed) = e.downcast_ref::<WithdrawalCallFailed>() {
+                error!(
+                    chain_id = failed.chain_id,
+                    withdrawal_hash = %failed.withdrawal_hash,
+                    tx_hash = %failed.tx_hash,
+                    gas_limit = %failed.gas_limit,
+                    "ALERT: withdrawal finalized but its call failed, funds were not delivered and the portal does not allow a retry"
+                );
+                return Err(e);
+            }
+            // The game proven against is the first thing to check when finalize fails
+            if let WithdrawalStatus::Proven {
+                dispute_game_proxy, ..
+            } = withdrawal.status
+            {
+                let game_status = dispute_game_status(l1_provider, dispute_game_proxy)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!(error = %e, "Failed to read dispute game status");
+                        "unknown"
+                    });
+                error!(
+                    withdrawal_hash = %withdrawal.hash,
+                    dispute_game_proxy = %dispute_game_proxy,
+                    game_status,
+                    error = %e,
+                    "Failed to execute finalize"
+                );
+            } else {
+                error!(
+                    withdrawal_hash = %withdrawal.hash,
+                    error = %e,
+                    "Failed to execute finalize"
+                );
+            }
             return Err(e);
         }
     }
 
-    Ok(())
+    Ok(true)
 }
 
 /// Prove a single initiated withdrawal.
+///
+/// Submits `proof` if given, otherwise generates one with retries drawn from
+/// `retry_budget`. With `reference_l2_provider`, the
+/// proof's block hash is cross-checked against it before submitting. Returns the
+/// submitted proof, or `None` if nothing was sent.
+///
+/// The caller checks that no earlier prove recorded in `pending_transactions` is still
+/// in the mempool; the action records the one it sends there.
 #[allow(clippy::too_many_arguments)]
 async fn prove_withdrawal<P1, P2>(
     l1_provider: P1,
     l2_provider: P2,
-    signer: SignerFn,
-    portal_address: Address,
-    factory_address: Address,
-    from: Address,
+    reference_l2_provider: Option<P2>,
+    signer: Arc<dyn TransactionSigner>,
+    config: &config::Config,
     withdrawal: &PendingWithdrawal,
-    dry_run: bool,
-) -> eyre::Result<()>
+    proof: Option<ProveWithdrawalParams>,
+    portal_params: &PortalParamsCache,
+    game_cache: &GameCache,
+    retry_budget: &RetryBudget,
+    pending_transactions: &PendingTransactions,
+) -> eyre::Result<Option<ProofRecord>>
 where
     P1: Provider + Clone,
     P2: Provider + Clone,
 {
-    let prove = Prove {
-        portal_address,
-        factory_address,
-        withdrawal: withdrawal.transaction.clone(),
-        withdrawal_hash: withdrawal.hash,
-        l2_block: withdrawal.l2_block,
-        from,
-    };
+    let prove = Prove::from_pending(withdrawal, &config.network_config().unichain)?
+        .with_min_game_count(config.min_dispute_games)
+        .with_allowed_game_types(config.allowed_game_types.clone())
+        .with_game_selection(config.game_selection_strategy)
+        .with_proof_limits(ProofLimits::new(
+            config.max_proof_nodes,
+            config.max_proof_bytes,
+        ));
 
-    let mut action = ProveAction::new(l1_provider, l2_provider, signer, prove);
+    let mut action = ProveAction::new(l1_provider, l2_provider, signer, prove)
+        .with_portal_params(portal_params.clone())
+        .with_game_cache(game_cache.clone())
+        .with_retry_budget(retry_budget.clone())
+        .with_audit_log(config.audit_log().clone())
+        .with_pending_transactions(pending_transactions.clone());
+    if let Some(reference) = reference_l2_provider {
+        action = action.with_reference_l2_provider(reference);
+    }
+    if let Some(proof) = proof {
+        action = action.with_proof(proof);
+    }
 
     if !action.is_ready().await? {
         info!(
             withdrawal_hash = %withdrawal.hash,
             "Withdrawal already proven"
         );
-        return Ok(());
+        return Ok(None);
     }
 
-    if dry_run {
-        info!(
-            withdrawal_hash = %withdrawal.hash,
-            "[DRY-RUN] Would prove withdrawal"
-        );
-        return Ok(());
+    match config.execution_mode {
+        ExecutionMode::Live => {}
+        ExecutionMode::Simulate => {
+            report_simulation(
+                &format!("Proving withdrawal {}", withdrawal.hash),
+                action.simulate().await,
+            );
+            return Ok(None);
+        }
+        ExecutionMode::LogOnly => {
+            info!(
+                withdrawal_hash = %withdrawal.hash,
+                "[DRY-RUN] Would prove withdrawal"
+            );
+            return Ok(None);
+        }
     }
 
     info!(withdrawal_hash = %withdrawal.hash, "Proving withdrawal");
 
     match action.execute().await {
         Ok(result) => {
+            let game = action
+                .proven_game()
+                .ok_or_else(|| eyre::eyre!("Prove succeeded without a dispute game"))?;
             info!(
                 withdrawal_hash = %withdrawal.hash,
                 tx_hash = %result.tx_hash,
+                dispute_game_index = %game.index,
+                dispute_game_proxy = %game.proxy,
                 "Withdrawal proven"
             );
+            return Ok(Some(ProofRecord {
+                dispute_game_index: game.index,
+                dispute_game_proxy: game.proxy,
+                tx_hash: result.tx_hash,
+            }));
+        }
+        Err(e) if e.downcast_ref::<GameNotYetAvailable>().is_some() => {
+            info!(
+                withdrawal_hash = %withdrawal.hash,
+                reason = %e,
+                "No dispute game covers withdrawal yet, will retry next cycle"
+            );
         }
         Err(e) => {
             error!(
@@ -395,21 +1835,22 @@ where
         }
     }
 
-    Ok(())
+    Ok(None)
 }
 
 /// Check L2 EOA balance and initiate withdrawal if threshold met.
 ///
-/// Returns the withdrawal amount if a withdrawal was initiated, None otherwise.
+/// Returns the withdrawals initiated (one, or one per chunk when `withdrawal_chunk_wei`
+/// is set), empty if the threshold isn't met. Pass them to [`record_initiations`] to
+/// keep their L2 gas cost.
 pub async fn maybe_initiate_withdrawal<P>(
     l2_provider: P,
-    l2_signer: SignerFn,
+    l2_signer: Arc<dyn TransactionSigner>,
     config: &config::Config,
-) -> eyre::Result<Option<U256>>
+) -> eyre::Result<Vec<InitiatedChunk>>
 where
     P: Provider + Clone,
 {
-    let network = config.network_config();
     let balance = l2_provider.get_balance(config.eoa_address).await?;
 
     if balance <= config.withdrawal_threshold_wei {
@@ -418,56 +1859,322 @@ where
             threshold = %format_ether(config.withdrawal_threshold_wei),
             "L2 EOA balance below threshold, skipping withdrawal"
         );
-        return Ok(None);
+        return Ok(vec![]);
+    }
+
+    // Withdraw everything except gas buffer
+    let withdrawal_amount = balance.saturating_sub(config.gas_buffer_wei);
+
+    if withdrawal_amount == U256::ZERO {
+        info!("Nothing to withdraw after gas buffer");
+        return Ok(vec![]);
+    }
+
+    if config.withdrawal_chunk_wei > U256::ZERO {
+        return initiate_withdrawal_chunks(
+            l2_provider,
+            l2_signer,
+            config,
+            config.withdrawal_chunk_wei,
+            config.max_withdrawal_chunks,
+        )
+        .await;
+    }
+
+    let would_initiate = vec![InitiatedChunk {
+        amount: withdrawal_amount,
+        initiated: None,
+    }];
+    match config.execution_mode {
+        ExecutionMode::Live => {}
+        ExecutionMode::Simulate => {
+            let action = withdraw_action(l2_provider, l2_signer, config, withdrawal_amount)?;
+            let succeeds = report_simulation(
+                &format!(
+                    "Initiating L2→L1 withdrawal of {} ETH",
+                    format_ether(withdrawal_amount)
+                ),
+                action.simulate().await,
+            );
+            return Ok(if succeeds { would_initiate } else { vec![] });
+        }
+        ExecutionMode::LogOnly => {
+            info!(
+                balance = %format_ether(balance),
+                withdrawal_amount = %format_ether(withdrawal_amount),
+                "[DRY-RUN] Would initiate L2→L1 withdrawal"
+            );
+            return Ok(would_initiate);
+        }
     }
 
-    // Withdraw everything except gas buffer
-    let withdrawal_amount = balance.saturating_sub(config.gas_buffer_wei);
+    info!(
+        balance = %format_ether(balance),
+        withdrawal_amount = %format_ether(withdrawal_amount),
+        "Initiating L2→L1 withdrawal"
+    );
+
+    let initiated = initiate_withdrawal(l2_provider, l2_signer, config, withdrawal_amount).await?;
+    Ok(vec![InitiatedChunk {
+        amount: withdrawal_amount,
+        initiated: Some(initiated),
+    }])
+}
+
+/// A withdrawal initiated by [`maybe_initiate_withdrawal`] or [`initiate_withdrawal_chunks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitiatedChunk {
+    pub amount: U256,
+    /// Withdrawal and initiating transaction; None unless the execution mode is live
+    pub initiated: Option<Initiated>,
+}
+
+/// Store the withdrawals sent in `chunks`, with the gas their initiation cost.
+pub fn record_initiations(store: &mut StateStore, chunks: &[InitiatedChunk]) {
+    for initiated in chunks.iter().filter_map(|c| c.initiated.as_ref()) {
+        let withdrawal = PendingWithdrawal {
+            transaction: initiated.withdrawal.clone(),
+            hash: initiated.withdrawal_hash,
+            l2_block: initiated.l2_block,
+            status: WithdrawalStatus::Initiated,
+        };
+        store.record_initiation(&withdrawal, initiated.into());
+    }
+}
+
+/// Number of `chunk`-sized withdrawals that fit in `available`, capped at `max_chunks`.
+pub fn withdrawal_chunk_count(available: U256, chunk: U256, max_chunks: usize) -> usize {
+    if chunk == U256::ZERO {
+        return 0;
+    }
+    let fits = available / chunk;
+    usize::try_from(fits).map_or(max_chunks, |fits| fits.min(max_chunks))
+}
+
+/// Initiate up to `max_chunks` withdrawals of exactly `chunk` wei each.
+///
+/// The balance above `gas_buffer_wei` decides how many fit. Withdrawals are sent one
+/// after another, each waiting for its receipt, so every transaction picks up the next
+/// nonce. A failure stops the run; the chunks sent before it are logged but the error is
+/// returned.
+pub async fn initiate_withdrawal_chunks<P>(
+    l2_provider: P,
+    l2_signer: Arc<dyn TransactionSigner>,
+    config: &config::Config,
+    chunk: U256,
+    max_chunks: usize,
+) -> eyre::Result<Vec<InitiatedChunk>>
+where
+    P: Provider + Clone,
+{
+    let balance = l2_provider.get_balance(config.eoa_address).await?;
+    let available = balance.saturating_sub(config.gas_buffer_wei);
+    let count = withdrawal_chunk_count(available, chunk, max_chunks);
+
+    if count == 0 {
+        info!(
+            balance = %format_ether(balance),
+            chunk = %format_ether(chunk),
+            "Balance above gas buffer is smaller than one withdrawal chunk"
+        );
+        return Ok(vec![]);
+    }
+
+    let would_initiate = vec![
+        InitiatedChunk {
+            amount: chunk,
+            initiated: None,
+        };
+        count
+    ];
+    match config.execution_mode {
+        ExecutionMode::Live => {}
+        ExecutionMode::Simulate => {
+            // Every chunk is the same transaction: simulating one covers them all
+            let action = withdraw_action(l2_provider, l2_signer, config, chunk)?;
+            let succeeds = report_simulation(
+                &format!(
+                    "Initiating {count} chunked L2→L1 withdrawals of {} ETH",
+                    format_ether(chunk)
+                ),
+                action.simulate().await,
+            );
+            return Ok(if succeeds { would_initiate } else { vec![] });
+        }
+        ExecutionMode::LogOnly => {
+            info!(
+                balance = %format_ether(balance),
+                chunk = %format_ether(chunk),
+                count,
+                "[DRY-RUN] Would initiate chunked L2→L1 withdrawals"
+            );
+            return Ok(would_initiate);
+        }
+    }
+
+    info!(
+        balance = %format_ether(balance),
+        chunk = %format_ether(chunk),
+        count,
+        "Initiating chunked L2→L1 withdrawals"
+    );
+
+    let mut initiated = Vec::with_capacity(count);
+    for _ in 0..count {
+        let sent = initiate_withdrawal(l2_provider.clone(), l2_signer.clone(), config, chunk)
+            .await
+            .inspect_err(|_| {
+                if !initiated.is_empty() {
+                    warn!(
+                        sent = initiated.len(),
+                        count, "Chunked withdrawal stopped early"
+                    );
+                }
+            })?;
+        initiated.push(InitiatedChunk {
+            amount: chunk,
+            initiated: Some(sent),
+        });
+    }
+
+    Ok(initiated)
+}
+
+/// Send a single L2→L1 withdrawal of `amount` to the EOA's own address on L1.
+async fn initiate_withdrawal<P>(
+    l2_provider: P,
+    l2_signer: Arc<dyn TransactionSigner>,
+    config: &config::Config,
+    amount: U256,
+) -> eyre::Result<Initiated>
+where
+    P: Provider + Clone,
+{
+    let mut action = withdraw_action(l2_provider, l2_signer, config, amount)?;
+    if !action.is_ready().await? {
+        eyre::bail!("Withdrawal of {} ETH is not ready", format_ether(amount));
+    }
+
+    match action.execute().await {
+        Ok(result) => {
+            info!(
+                tx_hash = %result.tx_hash,
+                amount = %format_ether(amount),
+                "Withdrawal initiated"
+            );
+            action
+                .initiated()
+                .cloned()
+                .ok_or_else(|| eyre::eyre!("Withdrawal {} has no initiation", result.tx_hash))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to initiate withdrawal");
+            Err(e)
+        }
+    }
+}
+
+/// Action sending a single L2→L1 withdrawal of `amount` to the EOA's own address on L1.
+fn withdraw_action<P>(
+    l2_provider: P,
+    l2_signer: Arc<dyn TransactionSigner>,
+    config: &config::Config,
+    amount: U256,
+) -> eyre::Result<WithdrawAction<P>>
+where
+    P: Provider + Clone,
+{
+    // Send to same address on L1
+    let withdraw = Withdraw::simple_eth(
+        config.eoa_address,
+        config.eoa_address,
+        amount,
+        default_gas_limit(config.withdrawal_data.len()),
+    )?
+    .with_data(
+        config.withdrawal_data.clone(),
+        config.max_withdrawal_data_bytes,
+    )?
+    .with_min_priority_fee(config.min_priority_fee_wei);
+
+    Ok(WithdrawAction::new(l2_provider, l2_signer, withdraw)
+        .with_audit_log(config.audit_log().clone()))
+}
+
+/// SpokePool balance left once in-flight deposits are filled, minus a safety buffer for
+/// fills near the lookback boundary that the in-flight scan may have missed.
+pub const fn projected_spoke_pool_balance(actual: U256, inflight: U256, buffer: U256) -> U256 {
+    actual.saturating_sub(inflight).saturating_sub(buffer)
+}
 
-    if withdrawal_amount == U256::ZERO {
-        info!("Nothing to withdraw after gas buffer");
-        return Ok(None);
-    }
+/// How long to wait for the Across API before falling back to a slow fill.
+const RELAY_FEE_QUOTE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
-    if config.dry_run {
-        info!(
-            balance = %format_ether(balance),
-            withdrawal_amount = %format_ether(withdrawal_amount),
-            "[DRY-RUN] Would initiate L2→L1 withdrawal"
+/// Output amount for a deposit of `input`.
+///
+/// A quoted fee within `max_relay_fee_bps` of the input gives `input - fee`, so a
+/// relayer can fill right away. Otherwise the output is twice the input, which no relayer
+/// will fill, forcing a slow fill.
+pub fn deposit_output_amount(input: U256, relay_fee: Option<U256>, max_relay_fee_bps: u64) -> U256 {
+    let slow_fill = input * U256::from(2);
+    let Some(fee) = relay_fee else {
+        return slow_fill;
+    };
+
+    let max_fee = input * U256::from(max_relay_fee_bps) / U256::from(10_000);
+    if fee > max_fee {
+        warn!(
+            quoted_fee = %format_ether(fee),
+            max_fee = %format_ether(max_fee),
+            "Quoted relayer fee above max_relay_fee_bps, forcing slow fill"
         );
-        return Ok(Some(withdrawal_amount));
+        return slow_fill;
     }
 
-    info!(
-        balance = %format_ether(balance),
-        withdrawal_amount = %format_ether(withdrawal_amount),
-        "Initiating L2→L1 withdrawal"
-    );
+    input - fee
+}
 
-    let withdraw = Withdraw {
-        contract: network.unichain.l2_to_l1_message_passer,
-        source: config.eoa_address,
-        target: config.eoa_address, // Send to same address on L1
-        value: withdrawal_amount,
-        gas_limit: U256::from(300_000),
-        data: Bytes::new(),
-        tx_hash: None,
+/// Ask the Across API for the relayer fee on a deposit of `amount` from L1 to L2.
+///
+/// Returns None, after logging why, when the API is unreachable, slow to answer or
+/// considers the amount too low to relay.
+async fn quote_relay_fee(
+    api_url: &str,
+    network: &config::NetworkConfig,
+    amount: U256,
+) -> Option<U256> {
+    let query = client::across::FeeQuery {
+        input_token: network.ethereum.weth,
+        output_token: network.unichain.weth,
+        origin_chain_id: network.ethereum.chain_id,
+        destination_chain_id: network.unichain.chain_id,
+        amount,
     };
+    let api = client::across::AcrossApi::new(api_url);
 
-    let mut action = WithdrawAction::new(l2_provider, l2_signer, withdraw);
-
-    match action.execute().await {
-        Ok(result) => {
+    match tokio::time::timeout(RELAY_FEE_QUOTE_TIMEOUT, api.suggested_fees(&query)).await {
+        Ok(Ok(fees)) if fees.is_amount_too_low => {
+            warn!(
+                amount = %format_ether(amount),
+                "Across API reports the amount as too low to relay, forcing slow fill"
+            );
+            None
+        }
+        Ok(Ok(fees)) => {
             info!(
-                tx_hash = %result.tx_hash,
-                amount = %format_ether(withdrawal_amount),
-                "Withdrawal initiated"
+                amount = %format_ether(amount),
+                relay_fee = %format_ether(fees.total_relay_fee.total),
+                "Got relayer fee quote from Across API"
             );
-            Ok(Some(withdrawal_amount))
+            Some(fees.total_relay_fee.total)
         }
-        Err(e) => {
-            error!(error = %e, "Failed to initiate withdrawal");
-            Err(e)
+        Ok(Err(e)) => {
+            warn!(error = %e, "Across fee quote failed, forcing slow fill");
+            None
+        }
+        Err(_) => {
+            warn!("Across fee quote timed out, forcing slow fill");
+            None
         }
     }
 }
@@ -476,16 +2183,29 @@ where
 ///
 /// Logic:
 /// 1. Get actual L2 SpokePool balance
-/// 2. Get in-flight deposit total (initiated but not yet filled)
-/// 3. Calculate projected_balance = actual - inflight
+/// 2. Get in-flight deposit total (initiated but not yet filled), of every depositor
+///    with `track_all_depositors_for_destination`
+/// 3. Calculate projected_balance = actual - inflight - safety buffer
 /// 4. If projected_balance > target: deposit (projected - floor)
 ///
+/// The output amount is set from the Across relayer fee quote when `across_api_url` is
+/// configured, and forces a slow fill otherwise; see [`deposit_output_amount`].
+///
+/// In-flight deposits are resumed from `deposit_checkpoint`, see
+/// [`refresh_inflight_deposits`]. Repeats of the same reason to skip are throttled
+/// by `skip_log_throttle`.
+///
 /// Returns the deposit amount if a deposit was executed, None otherwise.
+#[allow(clippy::too_many_arguments)]
 pub async fn maybe_deposit<P1, P2>(
     l1_provider: P1,
     l2_provider: P2,
-    l1_signer: SignerFn,
+    l1_signer: Arc<dyn TransactionSigner>,
     config: &config::Config,
+    retry_budget: &RetryBudget,
+    scan_coverage: &ScanCoverage,
+    deposit_checkpoint: &mut DepositCheckpoint,
+    skip_log_throttle: &ThrottledLogger<&'static str>,
 ) -> eyre::Result<Option<U256>>
 where
     P1: Provider + Clone,
@@ -503,34 +2223,40 @@ where
     .await?;
 
     // Get in-flight deposit total
-    let inflight_deposits = get_inflight_deposits(
+    let inflight_deposits = refresh_inflight_deposits(
         l1_provider.clone(),
-        l2_provider,
-        network.ethereum.spoke_pool,
-        network.unichain.spoke_pool,
-        config.eoa_address,
-        network.unichain.chain_id,
-        network.ethereum.chain_id,
-        config.deposit_lookback_secs,
-        network.ethereum.block_time_secs,
-        network.unichain.block_time_secs,
+        l2_provider.clone(),
+        config,
+        retry_budget,
+        scan_coverage,
+        deposit_checkpoint,
     )
     .await?;
     let inflight_total: U256 = inflight_deposits.iter().map(|d| d.input_amount).sum();
 
     // Calculate projected balance
-    let projected_balance = actual_balance.amount.saturating_sub(inflight_total);
+    let projected_balance = projected_spoke_pool_balance(
+        actual_balance.amount,
+        inflight_total,
+        config.spoke_pool_safety_buffer_wei,
+    );
 
     info!(
         actual_balance = %format_ether(actual_balance.amount),
         inflight_total = %format_ether(inflight_total),
+        safety_buffer = %format_ether(config.spoke_pool_safety_buffer_wei),
         projected_balance = %format_ether(projected_balance),
         target = %format_ether(config.spoke_pool_target_wei),
         "Checking deposit conditions"
     );
 
     if projected_balance <= config.spoke_pool_target_wei {
-        info!("Projected balance below target, skipping deposit");
+        if let Some(repeats) = skip_log_throttle.check("below_target") {
+            info!(
+                repeats = repeats.note(),
+                "Projected balance below target, skipping deposit"
+            );
+        }
         return Ok(None);
     }
 
@@ -538,57 +2264,130 @@ where
     let deposit_amount = projected_balance.saturating_sub(config.spoke_pool_floor_wei);
 
     if deposit_amount == U256::ZERO {
-        info!("Nothing to deposit after floor");
+        if let Some(repeats) = skip_log_throttle.check("below_floor") {
+            info!(repeats = repeats.note(), "Nothing to deposit after floor");
+        }
         return Ok(None);
     }
 
     // Check L1 EOA balance
     let l1_balance = l1_provider.get_balance(config.eoa_address).await?;
     if l1_balance < deposit_amount {
+        if let Some(repeats) = skip_log_throttle.check("insufficient_l1_balance") {
+            warn!(
+                l1_balance = %format_ether(l1_balance),
+                deposit_amount = %format_ether(deposit_amount),
+                repeats = repeats.note(),
+                "Insufficient L1 balance for deposit"
+            );
+        }
+        return Ok(None);
+    }
+
+    // Nonces only need to be unique per depositor; one deposit per second is plenty
+    let deposit_nonce = config.deterministic_deposit_ids.then(|| {
+        U256::from(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_secs(),
+        )
+    });
+
+    // Across silently skips messages for recipients without code
+    if !config.deposit_message.is_empty()
+        && l2_provider
+            .get_code_at(config.eoa_address)
+            .await?
+            .is_empty()
+    {
         warn!(
-            l1_balance = %format_ether(l1_balance),
-            deposit_amount = %format_ether(deposit_amount),
-            "Insufficient L1 balance for deposit"
+            recipient = %config.eoa_address,
+            "deposit_message is set but the recipient is not a contract on L2, the message will not be executed"
         );
-        return Ok(None);
     }
 
-    if config.dry_run {
-        info!(
+    let relay_fee = match &config.across_api_url {
+        Some(url) => quote_relay_fee(url, &network, deposit_amount).await,
+        None => None,
+    };
+    let output_amount = deposit_output_amount(deposit_amount, relay_fee, config.max_relay_fee_bps);
+
+    // Refuse implausible outputs rather than sending them
+    let output_bounds = OutputBounds::new(config.min_output_ratio_bps, config.max_output_ratio_bps);
+    if let Err(e) = output_bounds.check(deposit_amount, output_amount) {
+        error!(
             deposit_amount = %format_ether(deposit_amount),
-            "[DRY-RUN] Would execute deposit"
+            output_amount = %format_ether(output_amount),
+            min_output_ratio_bps = config.min_output_ratio_bps,
+            max_output_ratio_bps = config.max_output_ratio_bps,
+            "Deposit output amount outside configured bounds, refusing to deposit"
         );
-        return Ok(Some(deposit_amount));
+        return Err(e.into());
     }
 
-    info!(
-        deposit_amount = %format_ether(deposit_amount),
-        "Executing deposit"
-    );
-
-    // Calculate fill deadline (current time + 1 hour)
-    let fill_deadline = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_secs() as u32
-        + 3600;
+    // Refuse recipients the operator hasn't vouched for
+    let recipient = config.eoa_address;
+    if let Err(e) = check_recipient(recipient, &config.allowed_deposit_recipients) {
+        error!(
+            recipient = %recipient,
+            deposit_amount = %format_ether(deposit_amount),
+            "ALERT: deposit recipient is not in allowed_deposit_recipients, refusing to deposit"
+        );
+        return Err(e.into());
+    }
 
     let deposit_config = DepositConfig {
         spoke_pool: network.ethereum.spoke_pool,
         depositor: config.eoa_address,
-        recipient: config.eoa_address,
+        recipient,
+        allowed_recipients: config.allowed_deposit_recipients.clone(),
         input_token: network.ethereum.weth,
         output_token: network.unichain.weth,
         input_amount: deposit_amount,
-        output_amount: deposit_amount * U256::from(2), // This is to enforce slow fill as no relayer would want to fill that
+        output_amount,
+        output_bounds,
         destination_chain_id: network.unichain.chain_id,
         exclusive_relayer: Address::ZERO,
-        fill_deadline,
+        fill_deadline: config.deposit_fill_deadline,
         exclusivity_parameter: 0,
-        message: Bytes::new(),
+        message: config.deposit_message.clone(),
+        deposit_nonce,
     };
 
-    let mut action = DepositAction::new(l1_provider, l1_signer, deposit_config);
+    let mut action = DepositAction::new(l1_provider, l1_signer, deposit_config)
+        .with_audit_log(config.audit_log().clone());
+
+    if config.execution_mode == ExecutionMode::Simulate {
+        let succeeds = report_simulation(
+            &format!("Depositing {} ETH", format_ether(deposit_amount)),
+            action.simulate().await,
+        );
+        return Ok(succeeds.then_some(deposit_amount));
+    }
+
+    if config.execution_mode == ExecutionMode::LogOnly {
+        let tx = action.prepare().await?;
+        info!(
+            deposit_amount = %format_ether(deposit_amount),
+            output_amount = %format_ether(output_amount),
+            to = ?tx.to,
+            nonce = ?tx.nonce,
+            gas = ?tx.gas,
+            max_fee_per_gas = ?tx.max_fee_per_gas,
+            calldata = %tx.input.input().cloned().unwrap_or_default(),
+            deposit_id = ?action.deposit_id(),
+            "[DRY-RUN] Would execute deposit"
+        );
+        return Ok(Some(deposit_amount));
+    }
+
+    info!(
+        deposit_amount = %format_ether(deposit_amount),
+        output_amount = %format_ether(output_amount),
+        deposit_id = ?action.deposit_id(),
+        "Executing deposit"
+    );
 
     match action.execute().await {
         Ok(result) => {
@@ -605,3 +2404,903 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use action::FnSigner;
+    use alloy_primitives::{Bytes, B256};
+    use alloy_provider::ProviderBuilder;
+    use alloy_transport::mock::Asserter;
+    use binding::opstack::{IDisputeGame, IOptimismPortal2, WithdrawalTransaction};
+    use test_support::FakeChain;
+    use withdrawal::state::ChunkedScan;
+    use withdrawal::submitter::ProofRejection;
+
+    #[test]
+    fn test_is_spoke_pool_critical() {
+        let critical = U256::from(5);
+        assert!(is_spoke_pool_critical(U256::from(4), critical));
+        assert!(!is_spoke_pool_critical(U256::from(5), critical));
+        assert!(!is_spoke_pool_critical(U256::from(6), critical));
+
+        // Disabled
+        assert!(!is_spoke_pool_critical(U256::ZERO, U256::ZERO));
+    }
+
+    #[test]
+    fn test_projected_spoke_pool_balance() {
+        let eth = U256::from(1_000_000_000_000_000_000_u128);
+
+        assert_eq!(
+            projected_spoke_pool_balance(eth * U256::from(100), eth * U256::from(10), U256::ZERO),
+            eth * U256::from(90)
+        );
+        assert_eq!(
+            projected_spoke_pool_balance(
+                eth * U256::from(100),
+                eth * U256::from(10),
+                eth * U256::from(5)
+            ),
+            eth * U256::from(85)
+        );
+        // Never underflows
+        assert_eq!(projected_spoke_pool_balance(eth, eth, eth), U256::ZERO);
+    }
+
+    #[test]
+    fn test_deposit_output_amount() {
+        let input = U256::from(10_000);
+
+        // No quote: slow fill
+        assert_eq!(deposit_output_amount(input, None, 50), U256::from(20_000));
+        // Quote within 0.5%
+        assert_eq!(
+            deposit_output_amount(input, Some(U256::from(50)), 50),
+            U256::from(9_950)
+        );
+        // Quote above the cap: slow fill
+        assert_eq!(
+            deposit_output_amount(input, Some(U256::from(51)), 50),
+            U256::from(20_000)
+        );
+
+        // Both fill modes pass the default output bounds
+        let config = config::Config::default();
+        let bounds = OutputBounds::new(config.min_output_ratio_bps, config.max_output_ratio_bps);
+        assert!(bounds
+            .check(input, deposit_output_amount(input, None, 50))
+            .is_ok());
+        assert!(bounds
+            .check(
+                input,
+                deposit_output_amount(input, Some(U256::from(50)), 50)
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_withdrawal_chunk_count() {
+        let chunk = U256::from(10);
+
+        assert_eq!(withdrawal_chunk_count(U256::from(35), chunk, 10), 3);
+        assert_eq!(withdrawal_chunk_count(U256::from(35), chunk, 2), 2);
+        assert_eq!(withdrawal_chunk_count(U256::from(9), chunk, 10), 0);
+        // Chunking disabled
+        assert_eq!(withdrawal_chunk_count(U256::from(35), U256::ZERO, 10), 0);
+        // More chunks than fit in a usize
+        assert_eq!(withdrawal_chunk_count(U256::MAX, U256::from(1), 4), 4);
+    }
+
+    #[test]
+    fn test_reject_secret_argument() {
+        let command = || {
+            clap::Command::new("test").arg(
+                clap::Arg::new("private_key")
+                    .short('k')
+                    .long("private-key")
+                    .env("TEST_REJECT_SECRET_ARGUMENT_KEY"),
+            )
+        };
+
+        let matches = command().get_matches_from(["test", "-k", "0xdeadbeef"]);
+        let err = reject_secret_argument(&matches, "private_key").unwrap_err();
+        assert!(!err.to_string().contains("deadbeef"));
+
+        // Unset, or read from the environment
+        let matches = command().get_matches_from(["test"]);
+        reject_secret_argument(&matches, "private_key").unwrap();
+    }
+
+    #[test]
+    fn test_verify_signer_address() {
+        let eoa = Address::repeat_byte(1);
+        let other = Address::repeat_byte(2);
+
+        assert!(verify_signer_address(eoa, &[eoa], false).is_ok());
+        assert!(verify_signer_address(eoa, &[other, eoa], false).is_ok());
+
+        let err = verify_signer_address(eoa, &[other], false).unwrap_err();
+        assert!(err.to_string().contains("--allow-address-mismatch"));
+        assert!(verify_signer_address(eoa, &[], false).is_err());
+    }
+
+    #[test]
+    fn test_verify_signer_address_allow_mismatch() {
+        let eoa = Address::repeat_byte(1);
+        let other = Address::repeat_byte(2);
+
+        assert!(verify_signer_address(eoa, &[other], true).is_ok());
+    }
+
+    fn panicking_signer() -> Arc<dyn TransactionSigner> {
+        Arc::new(FnSigner::new(
+            Address::ZERO,
+            None,
+            Arc::new(|_tx| Box::pin(async { panic!("signer should not be called") })),
+        ))
+    }
+
+    /// ABI-encoded `bool` return value.
+    fn encoded_bool(value: bool) -> Bytes {
+        let mut word = B256::ZERO;
+        word.0[31] = value as u8;
+        Bytes::from(word.0)
+    }
+
+    #[tokio::test]
+    async fn test_process_pending_withdrawals_skips_when_paused() {
+        let l1 = Asserter::new();
+        l1.push_success(&encoded_bool(true));
+
+        let pass = process_pending_withdrawals(
+            ProviderBuilder::new().connect_mocked_client(l1.clone()),
+            ProviderBuilder::new().connect_mocked_client(Asserter::new()),
+            panicking_signer(),
+            &config::Config::default(),
+            vec![],
+            &RetryBudget::unlimited(),
+            &ScanCoverage::default(),
+            &PortalParamsCache::default(),
+            &FinalizedSet::default(),
+            &StorageLayoutCheck::default(),
+            &ThrottledLogger::default(),
+            &PendingTransactions::default(),
+            &RegressionGuard::default(),
+            Instant::now(),
+        )
+        .await
+        .unwrap();
+
+        // The L2 mock has no responses queued, so any scan would have errored
+        assert!(pass.is_portal_paused());
+        assert!(l1.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_process_pending_withdrawals_runs_when_unpaused() {
+        let l1 = Asserter::new();
+        let l2 = Asserter::new();
+        l1.push_success(&encoded_bool(false));
+        // Current block, `Latest` resolution, then an empty MessagePassed scan
+        l2.push_success(&"0x64");
+        l2.push_success(&"0x64");
+        l2.push_success(&Vec::<()>::new());
+
+        let pass = process_pending_withdrawals(
+            ProviderBuilder::new().connect_mocked_client(l1),
+            ProviderBuilder::new().connect_mocked_client(l2.clone()),
+            panicking_signer(),
+            &config::Config::default(),
+            vec![],
+            &RetryBudget::unlimited(),
+            &ScanCoverage::default(),
+            &PortalParamsCache::default(),
+            &FinalizedSet::default(),
+            &StorageLayoutCheck::default(),
+            &ThrottledLogger::default(),
+            &PendingTransactions::default(),
+            &RegressionGuard::default(),
+            Instant::now(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!pass.is_portal_paused());
+        assert!(pass.proofs().is_empty());
+        assert!(l2.read_q().is_empty());
+    }
+
+    /// Withdrawal state served from a fixed list.
+    struct MockState {
+        paused: bool,
+        withdrawals: Vec<PendingWithdrawal>,
+    }
+
+    impl WithdrawalState for MockState {
+        async fn is_paused(&self) -> eyre::Result<bool> {
+            Ok(self.paused)
+        }
+
+        async fn scan_pending_withdrawals(
+            &self,
+            _from_block: BlockNumberOrTag,
+            _to_block: BlockNumberOrTag,
+            _withdrawal_initiator: Address,
+            _tolerate_partial: bool,
+        ) -> eyre::Result<ChunkedScan<PendingWithdrawal>> {
+            Ok(ChunkedScan {
+                items: self.withdrawals.clone(),
+                scanned_to: Some(100),
+                complete: true,
+            })
+        }
+    }
+
+    /// Executor recording the withdrawals it was asked to finalize and prove.
+    #[derive(Default)]
+    struct RecordingExecutor {
+        finalized: std::sync::Mutex<Vec<B256>>,
+        proven: std::sync::Mutex<Vec<B256>>,
+        /// Fail every finalization with this error
+        finalize_error: Option<InsufficientGasFunds>,
+        /// Finalize every withdrawal with a failed call
+        finalize_call_fails: bool,
+    }
+
+    impl WithdrawalExecutor for RecordingExecutor {
+        async fn finalize(&self, withdrawal: &PendingWithdrawal) -> eyre::Result<bool> {
+            self.finalized.lock().unwrap().push(withdrawal.hash);
+            if self.finalize_call_fails {
+                return Err(WithdrawalCallFailed {
+                    chain_id: 130,
+                    withdrawal_hash: withdrawal.hash,
+                    tx_hash: B256::repeat_byte(0xaa),
+                    gas_limit: withdrawal.transaction.gasLimit,
+                }
+                .into());
+            }
+            self.finalize_error.map_or(Ok(true), |e| Err(e.into()))
+        }
+
+        async fn prove(
+            &self,
+            initiated: &[&PendingWithdrawal],
+        ) -> eyre::Result<ProcessedWithdrawals> {
+            self.proven
+                .lock()
+                .unwrap()
+                .extend(initiated.iter().map(|w| w.hash));
+            Ok(ProcessedWithdrawals::default())
+        }
+    }
+
+    fn withdrawal(byte: u8, status: WithdrawalStatus) -> PendingWithdrawal {
+        PendingWithdrawal {
+            hash: B256::repeat_byte(byte),
+            transaction: WithdrawalTransaction {
+                nonce: U256::from(byte),
+                sender: Address::ZERO,
+                target: Address::ZERO,
+                value: U256::ZERO,
+                gasLimit: U256::ZERO,
+                data: Bytes::new(),
+            },
+            l2_block: 50,
+            status,
+        }
+    }
+
+    fn proven() -> WithdrawalStatus {
+        WithdrawalStatus::Proven {
+            timestamp: 1_700_000_000,
+            dispute_game_proxy: Address::repeat_byte(0x99),
+            proof_submitter: Address::ZERO,
+        }
+    }
+
+    /// Withdrawal whose hash is computed from its transaction, as the actions check.
+    fn hashed_withdrawal(status: WithdrawalStatus) -> PendingWithdrawal {
+        let mut withdrawal = withdrawal(1, status);
+        withdrawal.hash = withdrawal::hash::compute_withdrawal_hash(&withdrawal.transaction);
+        withdrawal
+    }
+
+    /// L2 provider answering the current block number query of a pass.
+    fn l2_at_block_100() -> impl Provider {
+        let l2 = Asserter::new();
+        l2.push_success(&"0x64");
+        ProviderBuilder::new().connect_mocked_client(l2)
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_pass_routes_by_status() {
+        let state = MockState {
+            paused: false,
+            withdrawals: vec![
+                withdrawal(1, WithdrawalStatus::Initiated),
+                withdrawal(2, proven()),
+                withdrawal(3, WithdrawalStatus::Finalized),
+                withdrawal(4, WithdrawalStatus::Initiated),
+            ],
+        };
+        // Recovered withdrawals join the scan results, without duplicates
+        let recovered = vec![withdrawal(2, proven()), withdrawal(5, proven())];
+        let executor = RecordingExecutor::default();
+
+        let pass = run_withdrawal_pass(
+            &state,
+            &executor,
+            &l2_at_block_100(),
+            &config::Config::default(),
+            recovered,
+            &ThrottledLogger::default(),
+            &RegressionGuard::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(!pass.is_portal_paused());
+        assert_eq!(
+            *executor.finalized.lock().unwrap(),
+            [B256::repeat_byte(2), B256::repeat_byte(5)]
+        );
+        assert_eq!(
+            *executor.proven.lock().unwrap(),
+            [B256::repeat_byte(1), B256::repeat_byte(4)]
+        );
+        assert_eq!(pass.finalized(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_pass_skips_proving_dust() {
+        let worth = |byte, status, value: u64| {
+            let mut w = withdrawal(byte, status);
+            w.transaction.value = U256::from(value);
+            w
+        };
+        let state = MockState {
+            paused: false,
+            withdrawals: vec![
+                worth(1, WithdrawalStatus::Initiated, 5),
+                worth(2, proven(), 1),
+                worth(3, WithdrawalStatus::Initiated, 20),
+                worth(4, WithdrawalStatus::Initiated, 50),
+                worth(5, WithdrawalStatus::Initiated, 10),
+            ],
+        };
+        let config = config::Config {
+            min_prove_value_wei: U256::from(10),
+            ..Default::default()
+        };
+        let executor = RecordingExecutor::default();
+
+        let pass = run_withdrawal_pass(
+            &state,
+            &executor,
+            &l2_at_block_100(),
+            &config,
+            vec![],
+            &ThrottledLogger::default(),
+            &RegressionGuard::default(),
+        )
+        .await
+        .unwrap();
+
+        // Proven dust is still finalized; the rest is proven largest first
+        assert_eq!(*executor.finalized.lock().unwrap(), [B256::repeat_byte(2)]);
+        assert_eq!(
+            *executor.proven.lock().unwrap(),
+            [
+                B256::repeat_byte(4),
+                B256::repeat_byte(3),
+                B256::repeat_byte(5)
+            ]
+        );
+        assert_eq!(pass.dust_skipped(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_pass_does_nothing_when_paused() {
+        let state = MockState {
+            paused: true,
+            withdrawals: vec![
+                withdrawal(1, WithdrawalStatus::Initiated),
+                withdrawal(2, proven()),
+            ],
+        };
+        let executor = RecordingExecutor::default();
+
+        let pass = run_withdrawal_pass(
+            &state,
+            &executor,
+            &ProviderBuilder::new().connect_mocked_client(Asserter::new()),
+            &config::Config::default(),
+            vec![],
+            &ThrottledLogger::default(),
+            &RegressionGuard::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(pass.is_portal_paused());
+        assert!(executor.finalized.lock().unwrap().is_empty());
+        assert!(executor.proven.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_pass_stops_when_out_of_gas() {
+        let shortage = InsufficientGasFunds {
+            chain: 1,
+            balance: U256::from(1),
+            needed: U256::from(2),
+        };
+        let state = MockState {
+            paused: false,
+            withdrawals: vec![
+                withdrawal(1, proven()),
+                withdrawal(2, proven()),
+                withdrawal(3, WithdrawalStatus::Initiated),
+            ],
+        };
+        let executor = RecordingExecutor {
+            finalize_error: Some(shortage),
+            ..Default::default()
+        };
+
+        let pass = run_withdrawal_pass(
+            &state,
+            &executor,
+            &l2_at_block_100(),
+            &config::Config::default(),
+            vec![],
+            &ThrottledLogger::default(),
+            &RegressionGuard::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(pass.insufficient_gas_funds(), Some(&shortage));
+        assert_eq!(*executor.finalized.lock().unwrap(), [B256::repeat_byte(1)]);
+        assert!(executor.proven.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_withdrawal_pass_reports_failed_calls() {
+        let state = MockState {
+            paused: false,
+            withdrawals: vec![
+                withdrawal(1, proven()),
+                withdrawal(2, proven()),
+                withdrawal(3, WithdrawalStatus::Initiated),
+            ],
+        };
+        let executor = RecordingExecutor {
+            finalize_call_fails: true,
+            ..Default::default()
+        };
+
+        let pass = run_withdrawal_pass(
+            &state,
+            &executor,
+            &l2_at_block_100(),
+            &config::Config::default(),
+            vec![],
+            &ThrottledLogger::default(),
+            &RegressionGuard::default(),
+        )
+        .await
+        .unwrap();
+
+        // Every withdrawal is still handled
+        let hashes: Vec<_> = pass
+            .failed_calls()
+            .iter()
+            .map(|failed| failed.withdrawal_hash)
+            .collect();
+        assert_eq!(hashes, [B256::repeat_byte(1), B256::repeat_byte(2)]);
+        assert_eq!(pass.finalized(), 2);
+        assert_eq!(*executor.proven.lock().unwrap(), [B256::repeat_byte(3)]);
+        assert!(pass.insufficient_gas_funds().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_simulated_withdrawal_is_not_submitted() {
+        let audit_path =
+            std::env::temp_dir().join(format!("orchestrator-{}-audit.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&audit_path);
+        let config = config::Config {
+            execution_mode: ExecutionMode::Simulate,
+            audit_log_path: Some(audit_path.display().to_string()),
+            eoa_address: Address::repeat_byte(0xe0),
+            withdrawal_threshold_wei: U256::from(1_000),
+            gas_buffer_wei: U256::from(100),
+            ..Default::default()
+        };
+        let l2 = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(l2.clone());
+
+        // Balance, then the eth_call of the withdrawal; the signer panics if called
+        l2.push_success(&U256::from(2_000));
+        l2.push_success(&Bytes::new());
+        let chunks = maybe_initiate_withdrawal(provider.clone(), panicking_signer(), &config)
+            .await
+            .unwrap();
+        assert_eq!(
+            chunks,
+            [InitiatedChunk {
+                amount: U256::from(1_900),
+                initiated: None,
+            }]
+        );
+
+        // A predicted revert means nothing would be withdrawn
+        l2.push_success(&U256::from(2_000));
+        l2.push_failure_msg("execution reverted");
+        let chunks = maybe_initiate_withdrawal(provider, panicking_signer(), &config)
+            .await
+            .unwrap();
+        assert!(chunks.is_empty());
+        assert!(l2.read_q().is_empty());
+        // Nothing was broadcast, so nothing was audited
+        assert!(!audit_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_covering_games_stops_at_cycle_deadline() {
+        let l1 = FakeChain::new();
+        l1.mine(1_000);
+        let config = config::Config {
+            wait_for_game_secs: 600,
+            ..Default::default()
+        };
+        let chain = config.chain_context(l1.provider(), FakeChain::new().provider());
+        let withdrawal = withdrawal(1, WithdrawalStatus::Initiated);
+
+        // The game is well within `wait_for_game_secs`, but the cycle has no time left
+        let covered = wait_for_covering_games(
+            &chain,
+            &config,
+            &PortalParamsCache::default(),
+            vec![(&withdrawal, 1_060)],
+            Instant::now(),
+        )
+        .await;
+        assert!(covered.is_empty());
+        assert_eq!(l1.request_count("eth_call"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_finalize_waits_for_pending_broadcast() {
+        let l1 = FakeChain::new();
+        let withdrawal = hashed_withdrawal(proven());
+        let pending = PendingTransactions::default();
+        let tx_hash = B256::repeat_byte(0xaa);
+        let finalize = |pending: PendingTransactions| {
+            let withdrawal = withdrawal.clone();
+            let l1 = l1.provider();
+            async move {
+                finalize_withdrawal(
+                    l1.clone(),
+                    l1,
+                    panicking_signer(),
+                    &config::Config::default().network_config().unichain,
+                    &withdrawal,
+                    &PortalParamsCache::default(),
+                    None,
+                    ExecutionMode::Live,
+                    AuditLog::default(),
+                    &pending,
+                )
+                .await
+            }
+        };
+
+        // Broadcast last cycle, but the receipt wait gave up with it still in the mempool
+        pending.record(
+            withdrawal.hash,
+            TransactionKind::FinalizeWithdrawal,
+            tx_hash,
+        );
+        l1.set_transaction(tx_hash, None);
+        for _ in 0..2 {
+            assert!(!finalize(pending.clone()).await.unwrap());
+        }
+        assert_eq!(l1.request_count("eth_call"), 0);
+        assert_eq!(
+            pending.get(withdrawal.hash, TransactionKind::FinalizeWithdrawal),
+            Some(tx_hash)
+        );
+
+        // Once mined, the portal is read again rather than the transaction waited on
+        l1.set_transaction(tx_hash, Some(10));
+        let _ = finalize(pending.clone()).await;
+        assert!(l1.request_count("eth_call") > 0);
+        assert!(pending.entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_finalize_sends_after_evicted_broadcast() {
+        let l1 = FakeChain::new();
+        let withdrawal = hashed_withdrawal(proven());
+        let pending = PendingTransactions::default();
+
+        // The node no longer knows the transaction: it won't land, so don't wait on it
+        pending.record(
+            withdrawal.hash,
+            TransactionKind::FinalizeWithdrawal,
+            B256::repeat_byte(0xaa),
+        );
+        let _ = finalize_withdrawal(
+            l1.provider(),
+            l1.provider(),
+            panicking_signer(),
+            &config::Config::default().network_config().unichain,
+            &withdrawal,
+            &PortalParamsCache::default(),
+            None,
+            ExecutionMode::Live,
+            AuditLog::default(),
+            &pending,
+        )
+        .await;
+        assert!(l1.request_count("eth_call") > 0);
+        assert!(pending.entries().is_empty());
+    }
+
+    #[test]
+    fn test_record_initiations_skips_dry_runs() {
+        let transaction = WithdrawalTransaction {
+            nonce: U256::from(3),
+            sender: Address::repeat_byte(0x11),
+            target: Address::repeat_byte(0x11),
+            value: U256::from(1_000),
+            gasLimit: U256::from(100_000),
+            data: Bytes::new(),
+        };
+        let initiated = Initiated {
+            withdrawal_hash: B256::repeat_byte(1),
+            withdrawal: transaction,
+            tx_hash: B256::repeat_byte(0xaa),
+            l2_block: 42,
+            gas_used: 95_000,
+            effective_gas_price: 1_000_250,
+        };
+        let chunks = [
+            InitiatedChunk {
+                amount: U256::from(1_000),
+                initiated: Some(initiated.clone()),
+            },
+            InitiatedChunk {
+                amount: U256::from(1_000),
+                initiated: None,
+            },
+        ];
+
+        let path = std::env::temp_dir().join(format!(
+            "orchestrator-lib-{}-initiations.json",
+            std::process::id()
+        ));
+        let mut store = StateStore::load(path).unwrap();
+        record_initiations(&mut store, &chunks);
+
+        assert_eq!(store.withdrawal_count(), 1);
+        let record = store.withdrawal(&B256::repeat_byte(1)).unwrap();
+        assert_eq!(record.l2_block, 42);
+        assert_eq!(record.initiation, Some((&initiated).into()));
+    }
+
+    #[test]
+    fn test_remaining_maturity() {
+        const DELAY: u64 = 604_800;
+        assert_eq!(remaining_maturity(1_000, DELAY, 1_000), DELAY);
+        assert_eq!(remaining_maturity(1_000, DELAY, 1_000 + DELAY - 1), 1);
+        // Already mature: zero, not negative
+        assert_eq!(remaining_maturity(1_000, DELAY, 1_000 + DELAY), 0);
+        assert_eq!(remaining_maturity(1_000, DELAY, 1_000 + 2 * DELAY), 0);
+        assert_eq!(remaining_maturity(u64::MAX, DELAY, 0), u64::MAX);
+    }
+
+    #[test]
+    fn test_finalize_etas() {
+        let proven_at = |byte, timestamp| {
+            withdrawal(
+                byte,
+                WithdrawalStatus::Proven {
+                    timestamp,
+                    dispute_game_proxy: Address::repeat_byte(0x99),
+                    proof_submitter: Address::ZERO,
+                },
+            )
+        };
+        let pending = [
+            proven_at(1, 10_000),
+            withdrawal(2, WithdrawalStatus::Initiated),
+            proven_at(3, 1_000),
+            proven_at(4, 0),
+        ];
+
+        let etas = finalize_etas(&pending, 5_000, 5_500);
+
+        assert_eq!(
+            etas,
+            vec![
+                (B256::repeat_byte(4), 0),
+                (B256::repeat_byte(3), 500),
+                (B256::repeat_byte(1), 9_500),
+            ]
+        );
+        assert!(finalize_etas(&pending[1..2], 5_000, 5_500).is_empty());
+    }
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(0), "0s");
+        assert_eq!(format_remaining(59), "59s");
+        assert_eq!(format_remaining(3_600), "1h");
+        assert_eq!(
+            format_remaining(6 * 86_400 + 23 * 3_600 + 5 * 60 + 3),
+            "6d 23h 5m 3s"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_proof_provenance() {
+        use binding::opstack::IDisputeGameFactory::{self, gameAtIndexReturn};
+
+        const PORTAL: Address = Address::repeat_byte(0x50);
+        const SUBMITTER: Address = Address::repeat_byte(0x5b);
+        const GAME: Address = Address::repeat_byte(0x91);
+        let other_factory = Address::repeat_byte(0xf0);
+        let factory = Address::repeat_byte(0xfa);
+        let l1 = FakeChain::new();
+        let state_provider =
+            WithdrawalStateProvider::new(l1.provider(), l1.provider(), PORTAL, Address::ZERO);
+        let hash = B256::repeat_byte(1);
+
+        // Proven by another party only
+        l1.set_call::<IOptimismPortal2::numProofSubmittersCall>(PORTAL, &U256::from(1));
+        l1.set_call::<IOptimismPortal2::proofSubmittersCall>(PORTAL, &SUBMITTER);
+        l1.set_call::<IOptimismPortal2::provenWithdrawalsCall>(
+            PORTAL,
+            &IOptimismPortal2::ProvenWithdrawal {
+                disputeGameProxy: GAME,
+                timestamp: 1_000,
+            },
+        );
+        l1.set_call::<IOptimismPortal2::disputeGameBlacklistCall>(PORTAL, &false);
+        l1.set_call::<IDisputeGame::gameTypeCall>(GAME, &1);
+        l1.set_call::<IDisputeGame::statusCall>(GAME, &0);
+        l1.set_call::<IDisputeGame::createdAtCall>(GAME, &900);
+        // `factory` created the game; `other_factory` only an unrelated one at the same time
+        l1.set_call::<IDisputeGameFactory::gameCountCall>(other_factory, &U256::from(1));
+        l1.set_call::<IDisputeGameFactory::gameAtIndexCall>(
+            other_factory,
+            &gameAtIndexReturn {
+                gameType_: 1,
+                timestamp_: 900,
+                proxy_: Address::repeat_byte(0x92),
+            },
+        );
+        l1.set_call::<IDisputeGameFactory::gameCountCall>(factory, &U256::from(3));
+        l1.set_call::<IDisputeGameFactory::gameAtIndexCall>(
+            factory,
+            &gameAtIndexReturn {
+                gameType_: 1,
+                timestamp_: 900,
+                proxy_: GAME,
+            },
+        );
+
+        let provenance = proof_provenance(
+            &state_provider,
+            &l1.provider(),
+            &[other_factory, factory],
+            hash,
+            SUBMITTER,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(provenance.len(), 1);
+        assert_eq!(provenance[0].proof.submitter, SUBMITTER);
+        assert_eq!(provenance[0].proof.dispute_game_proxy, GAME);
+        assert_eq!(provenance[0].proof.game_type, 1);
+        // Every game reads the same, so the search lands on the first index
+        assert_eq!(provenance[0].game_index, Some(U256::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_check_proven_proofs() {
+        const PORTAL: Address = Address::repeat_byte(0x50);
+        const RESPECTED: u32 = 1;
+        const PROVEN_AT: u64 = 1_000;
+        const AT_RISK_AFTER: u64 = 302_400;
+        let l1 = FakeChain::new();
+        let state_provider =
+            WithdrawalStateProvider::new(l1.provider(), l1.provider(), PORTAL, Address::ZERO);
+        let proven_against = |byte: u8, game: u8| {
+            withdrawal(
+                byte,
+                WithdrawalStatus::Proven {
+                    timestamp: PROVEN_AT,
+                    dispute_game_proxy: Address::repeat_byte(game),
+                    proof_submitter: Address::ZERO,
+                },
+            )
+        };
+        let set_game = |game: u8, game_type: u32, status: u8| {
+            let game = Address::repeat_byte(game);
+            l1.set_call::<IDisputeGame::gameTypeCall>(game, &game_type);
+            l1.set_call::<IDisputeGame::statusCall>(game, &status);
+        };
+        l1.set_call::<IOptimismPortal2::disputeGameBlacklistCall>(PORTAL, &false);
+        set_game(0x91, RESPECTED, 0);
+        set_game(0x92, RESPECTED, GAME_STATUS_CHALLENGER_WINS);
+        set_game(0x93, RESPECTED, GAME_STATUS_DEFENDER_WINS);
+        set_game(0x94, 0, GAME_STATUS_DEFENDER_WINS);
+        // The game of withdrawal 6 can't be read
+        let pending = [
+            withdrawal(1, WithdrawalStatus::Initiated),
+            proven_against(2, 0x91),
+            proven_against(3, 0x92),
+            proven_against(4, 0x93),
+            proven_against(5, 0x94),
+            proven_against(6, 0x95),
+            withdrawal(7, WithdrawalStatus::Finalized),
+        ];
+        let tracker = ProofHealthTracker::default();
+
+        let (games, health) = check_proven_proofs(
+            &state_provider,
+            &pending,
+            RESPECTED,
+            PROVEN_AT + 60,
+            AT_RISK_AFTER,
+            &tracker,
+        )
+        .await;
+        assert_eq!(
+            games,
+            ProvenGameStatuses {
+                in_progress: 1,
+                defender_wins: 2,
+                challenger_wins: 1,
+            }
+        );
+        // Won by the challenger, and proven against a game type no longer respected
+        assert_eq!(
+            health,
+            ProofHealthCounts {
+                healthy: 2,
+                at_risk: 0,
+                invalid: 2,
+                invalidated: 2,
+            }
+        );
+
+        // Next cycle the in-progress game overran; the invalid proofs are not new
+        let (_, health) = check_proven_proofs(
+            &state_provider,
+            &pending,
+            RESPECTED,
+            PROVEN_AT + AT_RISK_AFTER,
+            AT_RISK_AFTER,
+            &tracker,
+        )
+        .await;
+        assert_eq!(
+            health,
+            ProofHealthCounts {
+                healthy: 1,
+                at_risk: 1,
+                invalid: 2,
+                invalidated: 0,
+            }
+        );
+        assert_eq!(
+            tracker.get(B256::repeat_byte(3)),
+            Some(ProofHealth::Invalid(ProofRejection::ChallengerWins))
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Detection of transactions stuck in the mempool.
+//!
+//! When a transaction from the EOA sits unmined, everything sent after it queues behind
+//! its nonce and the orchestrator looks healthy while nothing lands. Each cycle compares
+//! the pending and latest nonces per chain; a gap that persists past the configured age
+//! is reported, and the stuck transaction can be re-sent with higher fees when its
+//! request was recorded by a [`RecordingSigner`].
+
+use crate::store::StateStore;
+use action::{
+    audit::{AuditEntry, AuditLog, AuditOutcome, TransactionKind},
+    TransactionSigner,
+};
+use alloy_dyn_abi::TypedData;
+use alloy_primitives::{Address, Bytes, Signature, TxHash};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::TransactionRequest;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Chain a nonce gap was observed on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Chain {
+    #[default]
+    L1,
+    L2,
+}
+
+impl Chain {
+    /// Label used in logs and metrics.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::L1 => "l1",
+            Self::L2 => "l2",
+        }
+    }
+}
+
+/// Latest (mined) and pending nonce of an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceGap {
+    /// Next nonce to be mined, i.e. the oldest stuck nonce when there is a gap
+    pub latest: u64,
+    /// Next nonce including transactions in the mempool
+    pub pending: u64,
+}
+
+impl NonceGap {
+    /// Number of transactions waiting in the mempool.
+    pub const fn size(&self) -> u64 {
+        self.pending.saturating_sub(self.latest)
+    }
+}
+
+/// Query the latest and pending nonce of `address`.
+pub async fn query_nonce_gap<P: Provider>(
+    provider: &P,
+    address: Address,
+) -> eyre::Result<NonceGap> {
+    let latest = provider.get_transaction_count(address).latest().await?;
+    let pending = provider.get_transaction_count(address).pending().await?;
+    Ok(NonceGap { latest, pending })
+}
+
+/// A nonce gap that outlived the configured age.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StuckNonce {
+    /// Nonce of the oldest transaction that hasn't been mined
+    pub nonce: u64,
+    /// Transactions waiting behind (and including) it
+    pub gap: u64,
+    /// How long the nonce has been stuck
+    pub age: Duration,
+}
+
+/// Tracks nonce gaps across cycles to tell a stuck transaction from one that is simply
+/// waiting for the next block.
+#[derive(Debug, Clone)]
+pub struct NonceGapTracker {
+    max_age: Duration,
+    /// Per chain: the stuck nonce and when it was first seen stuck
+    first_seen: HashMap<Chain, (u64, Instant)>,
+}
+
+impl NonceGapTracker {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            first_seen: HashMap::new(),
+        }
+    }
+
+    /// Record the gap observed on `chain` at `now`.
+    ///
+    /// Returns the stuck nonce once the same nonce has been waiting for at least the
+    /// maximum age. The age restarts whenever the latest nonce moves.
+    pub fn observe(&mut self, chain: Chain, gap: NonceGap, now: Instant) -> Option<StuckNonce> {
+        if gap.size() == 0 {
+            self.first_seen.remove(&chain);
+            return None;
+        }
+
+        let (nonce, since) = *self
+            .first_seen
+            .entry(chain)
+            .and_modify(|(nonce, since)| {
+                if *nonce != gap.latest {
+                    *nonce = gap.latest;
+                    *since = now;
+                }
+            })
+            .or_insert((gap.latest, now));
+
+        let age = now.saturating_duration_since(since);
+        (age >= self.max_age).then_some(StuckNonce {
+            nonce,
+            gap: gap.size(),
+            age,
+        })
+    }
+
+    /// Restart the age of the gap on `chain`, e.g. after replacing the stuck transaction.
+    pub fn reset(&mut self, chain: Chain, now: Instant) {
+        if let Some((_, since)) = self.first_seen.get_mut(&chain) {
+            *since = now;
+        }
+    }
+}
+
+/// Query the nonce gap of `address` and feed it to `tracker`.
+pub async fn check_stuck_nonce<P: Provider>(
+    provider: &P,
+    chain: Chain,
+    address: Address,
+    tracker: &mut NonceGapTracker,
+    now: Instant,
+) -> eyre::Result<Option<StuckNonce>> {
+    let gap = query_nonce_gap(provider, address).await?;
+    Ok(tracker.observe(chain, gap, now))
+}
+
+/// Raise the fees of `tx` by 12.5%, enough for nodes to accept it as a replacement
+/// (geth requires at least 10% on both the fee cap and the tip).
+pub fn bump_fees(mut tx: TransactionRequest) -> TransactionRequest {
+    let bump = |fee: u128| fee + fee / 8 + 1;
+    tx.max_fee_per_gas = tx.max_fee_per_gas.map(bump);
+    tx.max_priority_fee_per_gas = tx.max_priority_fee_per_gas.map(bump);
+    tx.gas_price = tx.gas_price.map(bump);
+    tx
+}
+
+/// Re-send a recorded request with bumped fees and the same nonce, recording it in
+/// `audit_log`.
+///
+/// Doesn't wait for the replacement to be mined; the next cycles' nonce check shows
+/// whether it landed.
+pub async fn replace_transaction<P: Provider>(
+    provider: &P,
+    signer: &Arc<dyn TransactionSigner>,
+    tx: TransactionRequest,
+    audit_log: &AuditLog,
+) -> eyre::Result<TxHash> {
+    let tx = bump_fees(tx);
+    let signed = signer.sign_transaction(tx.clone()).await?;
+    let sent = provider.send_raw_transaction(&signed).await;
+
+    let entry = AuditEntry::new(TransactionKind::Replacement, &tx, &signed);
+    audit_log.record(&match &sent {
+        Ok(_) => entry,
+        Err(e) => entry.with_outcome(AuditOutcome::Failed {
+            error: e.to_string(),
+        }),
+    });
+
+    Ok(*sent?.tx_hash())
+}
+
+/// Signer that records every request it signs in the state store, so a stuck
+/// transaction can later be replaced by [`replace_transaction`].
+pub struct RecordingSigner {
+    inner: Arc<dyn TransactionSigner>,
+    store: Arc<Mutex<StateStore>>,
+}
+
+impl RecordingSigner {
+    pub const fn new(inner: Arc<dyn TransactionSigner>, store: Arc<Mutex<StateStore>>) -> Self {
+        Self { inner, store }
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for RecordingSigner {
+    fn address(&self) -> Address {
+        self.inner.address()
+    }
+
+    fn chain_id(&self) -> Option<u64> {
+        self.inner.chain_id()
+    }
+
+    async fn sign_transaction(&self, tx: TransactionRequest) -> eyre::Result<Bytes> {
+        {
+            let mut store = self.store.lock().await;
+            store.record_sent_transaction(&tx);
+            // Losing the record only disables the fee bump, don't fail the send for it
+            if let Err(e) = store.save() {
+                warn!(error = %e, "Failed to save sent transaction request");
+            }
+        }
+
+        self.inner.sign_transaction(tx).await
+    }
+
+    async fn sign_typed_data(&self, payload: &TypedData) -> eyre::Result<Signature> {
+        self.inner.sign_typed_data(payload).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_provider::ProviderBuilder;
+    use alloy_transport::mock::Asserter;
+
+    const EOA: Address = Address::repeat_byte(0x11);
+
+    /// Stub the `(latest, pending)` transaction counts returned by the node.
+    fn push_nonces(asserter: &Asserter, latest: u64, pending: u64) {
+        asserter.push_success(&format!("{latest:#x}"));
+        asserter.push_success(&format!("{pending:#x}"));
+    }
+
+    #[tokio::test]
+    async fn test_query_nonce_gap() {
+        let asserter = Asserter::new();
+        push_nonces(&asserter, 7, 10);
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let gap = query_nonce_gap(&provider, EOA).await.unwrap();
+
+        assert_eq!(
+            gap,
+            NonceGap {
+                latest: 7,
+                pending: 10
+            }
+        );
+        assert_eq!(gap.size(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stuck_nonce_reported_after_age() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let mut tracker = NonceGapTracker::new(Duration::from_secs(300));
+        let start = Instant::now();
+
+        // Gap appears: not stuck yet
+        push_nonces(&asserter, 7, 10);
+        let stuck = check_stuck_nonce(&provider, Chain::L1, EOA, &mut tracker, start)
+            .await
+            .unwrap();
+        assert_eq!(stuck, None);
+
+        // Same nonce still waiting after the age
+        push_nonces(&asserter, 7, 11);
+        let later = start + Duration::from_secs(300);
+        let stuck = check_stuck_nonce(&provider, Chain::L1, EOA, &mut tracker, later)
+            .await
+            .unwrap();
+        assert_eq!(
+            stuck,
+            Some(StuckNonce {
+                nonce: 7,
+                gap: 4,
+                age: Duration::from_secs(300),
+            })
+        );
+
+        // Mempool drained
+        push_nonces(&asserter, 11, 11);
+        let stuck = check_stuck_nonce(&provider, Chain::L1, EOA, &mut tracker, later)
+            .await
+            .unwrap();
+        assert_eq!(stuck, None);
+    }
+
+    #[test]
+    fn test_tracker_restarts_age_when_nonce_moves() {
+        let mut tracker = NonceGapTracker::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let gap = |latest, pending| NonceGap { latest, pending };
+
+        assert_eq!(tracker.observe(Chain::L2, gap(5, 6), start), None);
+
+        // Nonce 5 landed, 6 is now waiting: a fresh gap, not a stuck one
+        let t1 = start + Duration::from_secs(90);
+        assert_eq!(tracker.observe(Chain::L2, gap(6, 7), t1), None);
+
+        // Chains are tracked independently
+        assert_eq!(tracker.observe(Chain::L1, gap(1, 2), start), None);
+        assert!(tracker
+            .observe(Chain::L1, gap(1, 2), start + Duration::from_secs(60))
+            .is_some());
+
+        let t2 = t1 + Duration::from_secs(60);
+        assert_eq!(tracker.observe(Chain::L2, gap(6, 7), t2).unwrap().nonce, 6);
+
+        // After a replacement the age starts over
+        tracker.reset(Chain::L2, t2);
+        assert_eq!(tracker.observe(Chain::L2, gap(6, 7), t2), None);
+    }
+
+    #[test]
+    fn test_bump_fees() {
+        let tx = TransactionRequest::default()
+            .max_fee_per_gas(800)
+            .max_priority_fee_per_gas(8);
+
+        let bumped = bump_fees(tx);
+
+        assert_eq!(bumped.max_fee_per_gas, Some(901));
+        assert_eq!(bumped.max_priority_fee_per_gas, Some(10));
+        assert_eq!(bumped.gas_price, None);
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! All metrics are aggregated in the [`Metrics`] struct for easy tracking and management.
 
+use crate::price::wei_to_usd;
+use alloy_primitives::U256;
+use client::coverage::CoverageTotals;
 use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
 use std::time::Duration;
+use tracing::{error, warn};
 
 /// Aggregated metrics for the orchestrator.
 ///
@@ -46,6 +50,67 @@ impl Metrics {
             "orchestrator_cycle_duration_seconds",
             "Duration of each orchestrator cycle in seconds"
         );
+        describe_gauge!(
+            "orchestrator_shutting_down",
+            "1 once the orchestrator has begun a graceful shutdown"
+        );
+        describe_gauge!(
+            "orchestrator_uptime_seconds",
+            "Seconds the orchestrator ran before its last graceful shutdown"
+        );
+        describe_gauge!(
+            "orchestrator_circuit_open",
+            "1 when the circuit breaker has halted the orchestrator, 0 otherwise"
+        );
+
+        describe_gauge!(
+            "orchestrator_retry_budget_consumed",
+            "RPC retries spent from the per-cycle retry budget in the last cycle"
+        );
+        describe_counter!(
+            "orchestrator_cycles_degraded_total",
+            "Total number of cycles that exhausted the RPC retry budget"
+        );
+
+        describe_gauge!(
+            "orchestrator_scan_covered_blocks",
+            "Blocks covered by log scans in the last cycle, summed over scans"
+        );
+        describe_counter!(
+            "orchestrator_scan_gaps_total",
+            "Block ranges skipped between consecutive chunks of a log scan"
+        );
+        describe_counter!(
+            "orchestrator_scan_overlaps_total",
+            "Block ranges scanned twice by consecutive chunks of a log scan"
+        );
+
+        describe_counter!(
+            "orchestrator_event_decode_failures_total",
+            "SpokePool logs the event audit could not decode, by chain, counted when above max_undecodable_event_bps"
+        );
+
+        describe_counter!(
+            "orchestrator_insufficient_gas_funds_total",
+            "Actions refused because the signer could not pay for gas, by chain"
+        );
+
+        describe_gauge!(
+            "orchestrator_stuck_nonce_gap",
+            "Transactions queued behind a nonce stuck longer than stuck_nonce_age_secs, by chain"
+        );
+        describe_gauge!(
+            "orchestrator_block_time_configured_secs",
+            "Block time of the network config, by chain"
+        );
+        describe_gauge!(
+            "orchestrator_block_time_measured_secs",
+            "Average block time measured at startup, by chain"
+        );
+        describe_gauge!(
+            "orchestrator_chain_stalled",
+            "1 while the chain's head block hasn't advanced for longer than max_stale_head_secs, by chain"
+        );
 
         // Balance gauges (point-in-time, queried fresh each cycle)
         describe_gauge!(
@@ -60,6 +125,10 @@ impl Metrics {
             "orchestrator_spoke_pool_balance_eth",
             "Current Unichain SpokePool WETH balance in ETH"
         );
+        describe_gauge!(
+            "orchestrator_spoke_pool_critical",
+            "1 when the SpokePool balance is below spoke_pool_critical_wei, 0 otherwise"
+        );
 
         // In-flight deposits
         describe_gauge!(
@@ -70,6 +139,44 @@ impl Metrics {
             "orchestrator_inflight_deposits_eth",
             "Total amount of in-flight deposits in ETH"
         );
+        describe_gauge!(
+            "orchestrator_inflight_deposits_others_count",
+            "Number of in-flight deposits by other depositors to the SpokePool, when tracked"
+        );
+        describe_gauge!(
+            "orchestrator_inflight_deposits_others_eth",
+            "Total amount of in-flight deposits by other depositors in ETH, when tracked"
+        );
+        describe_counter!(
+            "orchestrator_deposits_expired_total",
+            "Total number of deposits that passed their fill deadline unfilled"
+        );
+        describe_counter!(
+            "orchestrator_deposits_refunded_total",
+            "Total number of expired deposits refunded on L1"
+        );
+        describe_gauge!(
+            "orchestrator_refund_drift_eth",
+            "Input amounts of our expired deposits minus the relayer refunds paid for them, in ETH"
+        );
+        describe_counter!(
+            "orchestrator_deposit_output_out_of_bounds_total",
+            "Total number of deposits refused because their output amount was outside the configured bounds"
+        );
+
+        // Portal state
+        describe_gauge!(
+            "orchestrator_portal_paused",
+            "1 when the OptimismPortal is paused, 0 otherwise"
+        );
+        describe_counter!(
+            "orchestrator_withdrawal_call_failed_total",
+            "Total number of withdrawals finalized whose call failed, leaving their funds undelivered, by chain ID"
+        );
+        describe_gauge!(
+            "orchestrator_withdrawals_dust_skipped",
+            "Initiated withdrawals left unproven in the last cycle for being below min_prove_value_wei"
+        );
 
         // In-flight withdrawals (total)
         describe_gauge!(
@@ -98,6 +205,52 @@ impl Metrics {
             "orchestrator_withdrawals_proven_eth",
             "Total amount of proven withdrawals in ETH"
         );
+        describe_gauge!(
+            "orchestrator_proven_withdrawal_games",
+            "Proven withdrawals by the status of the dispute game they were proven against"
+        );
+        describe_gauge!(
+            "orchestrator_proven_withdrawal_proofs",
+            "Proven withdrawals by the health of their proof (healthy, at_risk, invalid)"
+        );
+        describe_counter!(
+            "orchestrator_proofs_invalidated_total",
+            "Proofs of proven withdrawals seen turning invalid"
+        );
+        describe_gauge!(
+            "orchestrator_next_finalize_eta_seconds",
+            "Seconds until the next proven withdrawal can be finalized (-1 when none is proven)"
+        );
+
+        // Withdrawal cost
+        describe_histogram!(
+            "orchestrator_withdrawal_cost_ratio",
+            "Gas cost of a finalized withdrawal (initiate + prove + finalize) divided by its value"
+        );
+        describe_histogram!(
+            "orchestrator_withdrawal_cost_eth",
+            "Gas cost of a finalized withdrawal (initiate + prove + finalize) in ETH"
+        );
+        describe_histogram!(
+            "orchestrator_withdrawal_cost_usd",
+            "Gas cost of a finalized withdrawal in USD, when the ETH / USD price is available"
+        );
+        describe_histogram!(
+            "orchestrator_withdrawal_value_eth",
+            "Value moved by a finalized withdrawal in ETH"
+        );
+        describe_histogram!(
+            "orchestrator_withdrawal_value_usd",
+            "Value moved by a finalized withdrawal in USD, when the ETH / USD price is available"
+        );
+        describe_gauge!(
+            "orchestrator_withdrawals_reconciled",
+            "Withdrawals in the latest reconciliation by outcome (matched, mismatched, unverifiable, read_failed)"
+        );
+        describe_gauge!(
+            "orchestrator_eth_usd_price",
+            "Last ETH / USD price read from the configured price feed"
+        );
     }
 
     // ─────────────────────────────────────────────────────────────────────────────
@@ -116,10 +269,74 @@ impl Metrics {
         }
     }
 
+    /// Record a graceful shutdown after `uptime`, for the final scrape.
+    pub fn record_shutdown(&self, uptime: Duration) {
+        gauge!("orchestrator_uptime_seconds").set(uptime.as_secs_f64());
+        gauge!("orchestrator_shutting_down").set(1.0);
+    }
+
+    /// Set whether the circuit breaker is open.
+    pub fn set_circuit_open(&self, open: bool) {
+        gauge!("orchestrator_circuit_open").set(if open { 1.0 } else { 0.0 });
+    }
+
+    /// Record the retries a cycle spent and whether it exhausted the budget.
+    pub fn record_retry_budget(&self, consumed: usize, exhausted: bool) {
+        gauge!("orchestrator_retry_budget_consumed").set(consumed as f64);
+        if exhausted {
+            counter!("orchestrator_cycles_degraded_total").increment(1);
+        }
+    }
+
+    /// Record the blocks a cycle's log scans covered and the gaps and overlaps between
+    /// their chunks.
+    pub fn record_scan_coverage(&self, totals: CoverageTotals) {
+        gauge!("orchestrator_scan_covered_blocks").set(totals.covered_blocks as f64);
+        counter!("orchestrator_scan_gaps_total").increment(totals.gaps);
+        counter!("orchestrator_scan_overlaps_total").increment(totals.overlaps);
+    }
+
+    /// Record an action refused because the signer on `chain` could not pay for gas.
+    pub fn record_insufficient_gas_funds(&self, chain: &'static str) {
+        counter!("orchestrator_insufficient_gas_funds_total", "chain" => chain).increment(1);
+    }
+
+    /// Set the number of transactions stuck in the mempool on `chain` (0 when none).
+    pub fn set_stuck_nonce_gap(&self, chain: &'static str, gap: u64) {
+        gauge!("orchestrator_stuck_nonce_gap", "chain" => chain).set(gap as f64);
+    }
+
+    /// Set the configured and, if measured, the observed block time of `chain`.
+    pub fn set_block_time(
+        &self,
+        chain: &'static str,
+        configured_secs: f64,
+        measured_secs: Option<f64>,
+    ) {
+        gauge!("orchestrator_block_time_configured_secs", "chain" => chain).set(configured_secs);
+        if let Some(measured) = measured_secs {
+            gauge!("orchestrator_block_time_measured_secs", "chain" => chain).set(measured);
+        }
+    }
+
+    /// Set whether the head of `chain` has stopped advancing.
+    pub fn set_chain_stalled(&self, chain: &'static str, stalled: bool) {
+        gauge!("orchestrator_chain_stalled", "chain" => chain).set(if stalled { 1.0 } else { 0.0 });
+    }
+
+    /// Record SpokePool logs on `chain` that an alerting event audit could not decode.
+    pub fn record_event_decode_failures(&self, chain: &'static str, count: usize) {
+        counter!("orchestrator_event_decode_failures_total", "chain" => chain)
+            .increment(count as u64);
+    }
+
     // ─────────────────────────────────────────────────────────────────────────────
     // Balance gauges
     // ─────────────────────────────────────────────────────────────────────────────
 
+    // Amounts are exported in ETH, converted with `format_ether`. There are no wei
+    // gauges: wei values above 2^53 don't survive the cast to f64.
+
     /// Set the current L1 EOA balance in ETH.
     pub fn set_l1_eoa_balance_eth(&self, balance_eth: f64) {
         gauge!("orchestrator_l1_eoa_balance_eth").set(balance_eth);
@@ -135,16 +352,68 @@ impl Metrics {
         gauge!("orchestrator_spoke_pool_balance_eth").set(balance_eth);
     }
 
+    /// Set whether the SpokePool balance is below the critical threshold.
+    pub fn set_spoke_pool_critical(&self, critical: bool) {
+        gauge!("orchestrator_spoke_pool_critical").set(if critical { 1.0 } else { 0.0 });
+    }
+
     // ─────────────────────────────────────────────────────────────────────────────
     // In-flight deposits
     // ─────────────────────────────────────────────────────────────────────────────
 
-    /// Set the current in-flight deposit count and total amount.
+    /// Set the count and total amount of our in-flight deposits.
     pub fn set_inflight_deposits(&self, count: usize, amount_eth: f64) {
         gauge!("orchestrator_inflight_deposits_count").set(count as f64);
         gauge!("orchestrator_inflight_deposits_eth").set(amount_eth);
     }
 
+    /// Set the in-flight deposits of other depositors to our SpokePool, tracked with
+    /// `track_all_depositors_for_destination`.
+    pub fn set_inflight_deposits_of_others(&self, count: usize, amount_eth: f64) {
+        gauge!("orchestrator_inflight_deposits_others_count").set(count as f64);
+        gauge!("orchestrator_inflight_deposits_others_eth").set(amount_eth);
+    }
+
+    /// Record deposits that newly passed their fill deadline unfilled.
+    pub fn record_deposits_expired(&self, count: usize) {
+        counter!("orchestrator_deposits_expired_total").increment(count as u64);
+    }
+
+    /// Record expired deposits that were newly refunded on L1.
+    pub fn record_deposits_refunded(&self, count: usize) {
+        counter!("orchestrator_deposits_refunded_total").increment(count as u64);
+    }
+
+    /// Set the refunds owed for our expired deposits minus those paid, in ETH.
+    pub fn set_refund_drift_eth(&self, drift_eth: f64) {
+        gauge!("orchestrator_refund_drift_eth").set(drift_eth);
+    }
+
+    /// Record a deposit refused for an implausible output amount.
+    pub fn record_deposit_output_out_of_bounds(&self) {
+        counter!("orchestrator_deposit_output_out_of_bounds_total").increment(1);
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────────
+    // Portal state
+    // ─────────────────────────────────────────────────────────────────────────────
+
+    /// Set whether the OptimismPortal is paused.
+    pub fn set_portal_paused(&self, paused: bool) {
+        gauge!("orchestrator_portal_paused").set(if paused { 1.0 } else { 0.0 });
+    }
+
+    /// Set the number of initiated withdrawals the last pass didn't prove as dust.
+    pub fn set_withdrawals_dust_skipped(&self, count: usize) {
+        gauge!("orchestrator_withdrawals_dust_skipped").set(count as f64);
+    }
+
+    /// Record a withdrawal from chain `chain_id` finalized with a failed call.
+    pub fn record_withdrawal_call_failed(&self, chain_id: u64) {
+        counter!("orchestrator_withdrawal_call_failed_total", "chain_id" => chain_id.to_string())
+            .increment(1);
+    }
+
     // ─────────────────────────────────────────────────────────────────────────────
     // In-flight withdrawals
     // ─────────────────────────────────────────────────────────────────────────────
@@ -169,11 +438,128 @@ impl Metrics {
         gauge!("orchestrator_withdrawals_proven_count").set(proven_count as f64);
         gauge!("orchestrator_withdrawals_proven_eth").set(proven_eth);
     }
+
+    /// Set the number of proven withdrawals by the status of their dispute game.
+    pub fn set_proven_withdrawal_games(
+        &self,
+        in_progress: usize,
+        defender_wins: usize,
+        challenger_wins: usize,
+    ) {
+        gauge!("orchestrator_proven_withdrawal_games", "status" => "in_progress")
+            .set(in_progress as f64);
+        gauge!("orchestrator_proven_withdrawal_games", "status" => "defender_wins")
+            .set(defender_wins as f64);
+        gauge!("orchestrator_proven_withdrawal_games", "status" => "challenger_wins")
+            .set(challenger_wins as f64);
+    }
+
+    /// Set the number of proven withdrawals by the health of their proof.
+    pub fn set_proven_withdrawal_proofs(&self, healthy: usize, at_risk: usize, invalid: usize) {
+        gauge!("orchestrator_proven_withdrawal_proofs", "health" => "healthy").set(healthy as f64);
+        gauge!("orchestrator_proven_withdrawal_proofs", "health" => "at_risk").set(at_risk as f64);
+        gauge!("orchestrator_proven_withdrawal_proofs", "health" => "invalid").set(invalid as f64);
+    }
+
+    /// Record proofs that turned invalid.
+    pub fn record_proofs_invalidated(&self, count: usize) {
+        counter!("orchestrator_proofs_invalidated_total").increment(count as u64);
+    }
+
+    /// Set the time until the soonest proven withdrawal matures, `None` if none is proven.
+    pub fn set_next_finalize_eta_seconds(&self, remaining: Option<u64>) {
+        gauge!("orchestrator_next_finalize_eta_seconds").set(remaining.map_or(-1.0, |r| r as f64));
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────────
+    // Withdrawal cost
+    // ─────────────────────────────────────────────────────────────────────────────
+
+    /// Record the cost ratio of a newly finalized withdrawal.
+    pub fn record_withdrawal_cost_ratio(&self, ratio: f64) {
+        histogram!("orchestrator_withdrawal_cost_ratio").record(ratio);
+    }
+
+    /// Record the gas cost (`None` when a leg is unknown) and value of a newly finalized
+    /// withdrawal, also in USD when `eth_usd` is known.
+    pub fn record_withdrawal_cost(
+        &self,
+        cost_wei: Option<U256>,
+        value_wei: U256,
+        eth_usd: Option<f64>,
+    ) {
+        histogram!("orchestrator_withdrawal_value_eth").record(f64::from(value_wei) / 1e18);
+        if let Some(cost_wei) = cost_wei {
+            histogram!("orchestrator_withdrawal_cost_eth").record(f64::from(cost_wei) / 1e18);
+        }
+
+        let Some(eth_usd) = eth_usd else {
+            return;
+        };
+        histogram!("orchestrator_withdrawal_value_usd").record(wei_to_usd(value_wei, eth_usd));
+        if let Some(cost_wei) = cost_wei {
+            histogram!("orchestrator_withdrawal_cost_usd").record(wei_to_usd(cost_wei, eth_usd));
+        }
+    }
+
+    /// Set the number of withdrawals in the latest reconciliation by outcome.
+    pub fn set_withdrawals_reconciled(
+        &self,
+        matched: usize,
+        mismatched: usize,
+        unverifiable: usize,
+        read_failed: usize,
+    ) {
+        gauge!("orchestrator_withdrawals_reconciled", "outcome" => "matched").set(matched as f64);
+        gauge!("orchestrator_withdrawals_reconciled", "outcome" => "mismatched")
+            .set(mismatched as f64);
+        gauge!("orchestrator_withdrawals_reconciled", "outcome" => "unverifiable")
+            .set(unverifiable as f64);
+        gauge!("orchestrator_withdrawals_reconciled", "outcome" => "read_failed")
+            .set(read_failed as f64);
+    }
+
+    /// Set the last ETH / USD price read.
+    pub fn set_eth_usd_price(&self, price: f64) {
+        gauge!("orchestrator_eth_usd_price").set(price);
+    }
+}
+
+/// Delay before retrying a failed exporter start, long enough for a restarted
+/// instance's old listener to be released.
+const EXPORTER_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Start the Prometheus exporter, retrying once after a short delay.
+///
+/// Returns whether the exporter is running. Failing to start is only an error when
+/// `required`; otherwise it is logged and the orchestrator runs without metrics.
+pub async fn start_prometheus_exporter(port: u16, required: bool) -> eyre::Result<bool> {
+    let error = match install_prometheus_exporter(port) {
+        Ok(()) => return Ok(true),
+        Err(e) => e,
+    };
+    warn!(port, error = %error, "Failed to start metrics server, retrying");
+    tokio::time::sleep(EXPORTER_RETRY_DELAY).await;
+
+    match install_prometheus_exporter(port) {
+        Ok(()) => Ok(true),
+        Err(e) if required => Err(e),
+        Err(e) => {
+            error!(
+                port,
+                error = %e,
+                "Metrics server unavailable, continuing without metrics"
+            );
+            Ok(false)
+        }
+    }
 }
 
 /// Install the Prometheus metrics exporter and start the HTTP server.
 ///
-/// Returns an error if the server fails to bind to the specified port.
+/// The listener is bound with `SO_REUSEADDR` (the default for std listeners on Unix),
+/// so a port left in `TIME_WAIT` by a previous instance can be reused. Returns an error
+/// if the server fails to bind to the specified port.
 pub fn install_prometheus_exporter(port: u16) -> eyre::Result<()> {
     use metrics_exporter_prometheus::PrometheusBuilder;
     use std::net::SocketAddr;
@@ -187,3 +573,20 @@ pub fn install_prometheus_exporter(port: u16) -> eyre::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_start_exporter_with_port_in_use() {
+        let listener = TcpListener::bind("0.0.0.0:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Not required: the orchestrator carries on without metrics
+        assert!(!start_prometheus_exporter(port, false).await.unwrap());
+
+        assert!(start_prometheus_exporter(port, true).await.is_err());
+    }
+}
@@ -0,0 +1,511 @@
+//! Startup checks run before the orchestrator sends anything.
+
+use crate::{
+    config::{Config, NetworkConfig},
+    mempool::Chain,
+    metrics::Metrics,
+};
+use alloy_primitives::Address;
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use binding::{
+    across::ISpokePool,
+    opstack::{IDisputeGameFactory, IOptimismPortal2},
+};
+use tracing::{info, warn};
+
+/// Blocks between the two headers a block time is measured over.
+pub const BLOCK_TIME_SAMPLE_BLOCKS: u64 = 1_000;
+
+/// Fraction a measured block time may be off the configured one before it is warned
+/// about.
+const MAX_BLOCK_TIME_DEVIATION: f64 = 0.25;
+
+/// Contracts the orchestrator calls on each chain, by config field name.
+pub const fn expected_contracts(network: &NetworkConfig) -> [(Chain, &'static str, Address); 7] {
+    [
+        (
+            Chain::L1,
+            "ethereum.spoke_pool",
+            network.ethereum.spoke_pool,
+        ),
+        (Chain::L1, "ethereum.weth", network.ethereum.weth),
+        (Chain::L1, "unichain.l1_portal", network.unichain.l1_portal),
+        (
+            Chain::L1,
+            "unichain.l1_dispute_game_factory",
+            network.unichain.l1_dispute_game_factory,
+        ),
+        (
+            Chain::L2,
+            "unichain.spoke_pool",
+            network.unichain.spoke_pool,
+        ),
+        (Chain::L2, "unichain.weth", network.unichain.weth),
+        (
+            Chain::L2,
+            "unichain.l2_to_l1_message_passer",
+            network.unichain.l2_to_l1_message_passer,
+        ),
+    ]
+}
+
+/// Fail if any configured contract address has no code on its chain, or has code that
+/// isn't the contract expected there.
+///
+/// A typo'd address otherwise only shows up as reverts, or as ABI decode errors deep in
+/// proving, on every cycle. All addresses are checked so the error names every wrong
+/// one at once. Contracts with code are then probed with a view call each: the spoke
+/// pools must wrap the configured WETH, the portal must report its respected game type
+/// and the dispute game factory its game count.
+pub async fn verify_contract_code<P1, P2>(
+    l1_provider: &P1,
+    l2_provider: &P2,
+    network: &NetworkConfig,
+) -> eyre::Result<()>
+where
+    P1: Provider,
+    P2: Provider,
+{
+    let mut missing = vec![];
+    for (chain, name, address) in expected_contracts(network) {
+        let code = match chain {
+            Chain::L1 => l1_provider.get_code_at(address).await?,
+            Chain::L2 => l2_provider.get_code_at(address).await?,
+        };
+        if code.is_empty() {
+            missing.push(format!("{name} ({address}) on {}", chain.as_str()));
+        }
+    }
+
+    if !missing.is_empty() {
+        eyre::bail!(
+            "No contract code at configured address: {}",
+            missing.join(", ")
+        );
+    }
+
+    let wrong = unexpected_contracts(l1_provider, l2_provider, network).await;
+    if !wrong.is_empty() {
+        eyre::bail!(
+            "Configured address is not the expected contract: {}",
+            wrong.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Configured contracts whose view calls fail or disagree with the config, described
+/// for the startup error.
+async fn unexpected_contracts<P1, P2>(
+    l1_provider: &P1,
+    l2_provider: &P2,
+    network: &NetworkConfig,
+) -> Vec<String>
+where
+    P1: Provider,
+    P2: Provider,
+{
+    let mut wrong = vec![];
+    let spoke_pools = [
+        (
+            Chain::L1,
+            "ethereum.spoke_pool",
+            network.ethereum.spoke_pool,
+            network.ethereum.weth,
+        ),
+        (
+            Chain::L2,
+            "unichain.spoke_pool",
+            network.unichain.spoke_pool,
+            network.unichain.weth,
+        ),
+    ];
+    for (chain, name, address, weth) in spoke_pools {
+        let wrapped = match chain {
+            Chain::L1 => {
+                ISpokePool::new(address, l1_provider)
+                    .wrappedNativeToken()
+                    .call()
+                    .await
+            }
+            Chain::L2 => {
+                ISpokePool::new(address, l2_provider)
+                    .wrappedNativeToken()
+                    .call()
+                    .await
+            }
+        };
+        match wrapped {
+            Ok(wrapped) if wrapped == weth => {}
+            Ok(wrapped) => wrong.push(format!(
+                "{name} ({address}) on {} wraps {wrapped}, not the configured WETH {weth}",
+                chain.as_str()
+            )),
+            Err(e) => wrong.push(format!(
+                "{name} ({address}) on {} fails wrappedNativeToken(): {e}",
+                chain.as_str()
+            )),
+        }
+    }
+
+    let portal = network.unichain.l1_portal;
+    if let Err(e) = IOptimismPortal2::new(portal, l1_provider)
+        .respectedGameType()
+        .call()
+        .await
+    {
+        wrong.push(format!(
+            "unichain.l1_portal ({portal}) on l1 fails respectedGameType(): {e}"
+        ));
+    }
+    let factory = network.unichain.l1_dispute_game_factory;
+    if let Err(e) = IDisputeGameFactory::new(factory, l1_provider)
+        .gameCount()
+        .call()
+        .await
+    {
+        wrong.push(format!(
+            "unichain.l1_dispute_game_factory ({factory}) on l1 fails gameCount(): {e}"
+        ));
+    }
+
+    wrong
+}
+
+/// Average block time over the last `span` blocks, in seconds.
+///
+/// Fewer blocks are sampled on a chain shorter than `span`.
+pub async fn measure_block_time<P: Provider>(provider: &P, span: u64) -> eyre::Result<f64> {
+    let latest = provider.get_block_number().await?;
+    let earlier = latest.saturating_sub(span);
+    if earlier == latest {
+        eyre::bail!("No blocks before {latest} to measure the block time over");
+    }
+
+    let mut timestamps = [0; 2];
+    for (timestamp, number) in timestamps.iter_mut().zip([earlier, latest]) {
+        *timestamp = provider
+            .get_block_by_number(BlockNumberOrTag::Number(number))
+            .await?
+            .ok_or_else(|| eyre::eyre!("Block {number} not found"))?
+            .header
+            .timestamp;
+    }
+
+    let elapsed = timestamps[1].saturating_sub(timestamps[0]);
+    Ok(elapsed as f64 / (latest - earlier) as f64)
+}
+
+/// Whether `measured_secs` is more than 25% off `configured_ms`.
+pub fn block_time_deviates(configured_ms: u64, measured_secs: f64) -> bool {
+    let configured = configured_ms as f64 / 1_000.0;
+    (measured_secs - configured).abs() > configured * MAX_BLOCK_TIME_DEVIATION
+}
+
+/// Measure the block time of both chains and compare it with the network's.
+///
+/// A block time off by more than 25% silently scales every lookback converted to
+/// blocks with it, so it is warned about. With `use_measured_block_times` the measured
+/// times (to the millisecond, at least one) replace the network's in `config`. A chain whose
+/// measurement fails keeps its configured block time.
+pub async fn check_block_times<P1, P2>(
+    l1_provider: &P1,
+    l2_provider: &P2,
+    config: &mut Config,
+    metrics: &Metrics,
+) where
+    P1: Provider,
+    P2: Provider,
+{
+    let network = config.network_config();
+    for (chain, configured) in [
+        (Chain::L1, network.ethereum.block_time_ms),
+        (Chain::L2, network.unichain.block_time_ms),
+    ] {
+        let configured_secs = configured as f64 / 1_000.0;
+        let measured = match chain {
+            Chain::L1 => measure_block_time(l1_provider, BLOCK_TIME_SAMPLE_BLOCKS).await,
+            Chain::L2 => measure_block_time(l2_provider, BLOCK_TIME_SAMPLE_BLOCKS).await,
+        };
+        let measured = match measured {
+            Ok(measured) => measured,
+            Err(e) => {
+                warn!(chain = chain.as_str(), error = %e, "Failed to measure block time");
+                metrics.set_block_time(chain.as_str(), configured_secs, None);
+                continue;
+            }
+        };
+        metrics.set_block_time(chain.as_str(), configured_secs, Some(measured));
+
+        if block_time_deviates(configured, measured) {
+            warn!(
+                chain = chain.as_str(),
+                configured_secs,
+                measured_secs = measured,
+                "Measured block time is more than 25% off the configured one, lookback windows \
+                 converted to blocks are scaled by the difference"
+            );
+        } else {
+            info!(
+                chain = chain.as_str(),
+                configured_secs,
+                measured_secs = measured,
+                "Block time measured"
+            );
+        }
+
+        if config.use_measured_block_times {
+            config.set_block_time(chain, ((measured * 1_000.0).round() as u64).max(1));
+        }
+    }
+}
+
+/// Code of an EOA that delegated to a contract with EIP-7702 starts with this. The
+/// account still signs with its own key and nonce, so it is treated as an EOA.
+const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// Check that `eoa_address` is an EOA on both chains, i.e. has no contract code.
+///
+/// A smart account (e.g. a Safe) doesn't sign with the configured key, and its nonce
+/// doesn't count our transactions. Fails if it has code on either chain unless
+/// `allow_contract_account` is set; returns whether it is a contract account then.
+pub async fn check_eoa_account<P1, P2>(
+    l1_provider: &P1,
+    l2_provider: &P2,
+    eoa_address: Address,
+    allow_contract_account: bool,
+) -> eyre::Result<bool>
+where
+    P1: Provider,
+    P2: Provider,
+{
+    let mut contract_on = vec![];
+    for chain in [Chain::L1, Chain::L2] {
+        let code = match chain {
+            Chain::L1 => l1_provider.get_code_at(eoa_address).await?,
+            Chain::L2 => l2_provider.get_code_at(eoa_address).await?,
+        };
+        if !code.is_empty() && !code.starts_with(&DELEGATION_PREFIX) {
+            contract_on.push(chain.as_str());
+        }
+    }
+
+    if contract_on.is_empty() {
+        return Ok(false);
+    }
+    if !allow_contract_account {
+        eyre::bail!(
+            "eoa_address {eoa_address} is a contract on {}, not an EOA. Set \
+             allow_contract_account to run with a smart account",
+            contract_on.join(", ")
+        );
+    }
+
+    warn!(
+        eoa_address = %eoa_address,
+        chains = %contract_on.join(", "),
+        "eoa_address is a contract account: stuck transaction detection is disabled, and \
+         withdrawals it initiates carry it as the MessagePassed sender the scans filter on"
+    );
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, U256};
+    use alloy_provider::ProviderBuilder;
+    use alloy_transport::mock::Asserter;
+    use test_support::FakeChain;
+
+    /// L1 and L2 with every contract of `network` deployed and answering as expected.
+    fn deployed(network: &NetworkConfig) -> (FakeChain, FakeChain) {
+        let l1 = FakeChain::new();
+        let l2 = FakeChain::new();
+        for (chain, _, address) in expected_contracts(network) {
+            let fake = match chain {
+                Chain::L1 => &l1,
+                Chain::L2 => &l2,
+            };
+            fake.set_code(address, Bytes::from_static(&[0x60, 0x80]));
+        }
+        l1.set_call::<ISpokePool::wrappedNativeTokenCall>(
+            network.ethereum.spoke_pool,
+            &network.ethereum.weth,
+        );
+        l2.set_call::<ISpokePool::wrappedNativeTokenCall>(
+            network.unichain.spoke_pool,
+            &network.unichain.weth,
+        );
+        l1.set_call::<IOptimismPortal2::respectedGameTypeCall>(network.unichain.l1_portal, &1);
+        l1.set_call::<IDisputeGameFactory::gameCountCall>(
+            network.unichain.l1_dispute_game_factory,
+            &U256::from(5_000),
+        );
+        (l1, l2)
+    }
+
+    /// A chain of `BLOCK_TIME_SAMPLE_BLOCKS + 1` blocks, `millis` apart.
+    fn chain_with_block_time(millis: u64) -> FakeChain {
+        let chain = FakeChain::new();
+        for i in 0..=BLOCK_TIME_SAMPLE_BLOCKS {
+            chain.mine(1_700_000_000 + i * millis / 1_000);
+        }
+        chain
+    }
+
+    #[tokio::test]
+    async fn test_measure_block_time() {
+        let l1 = chain_with_block_time(12_000);
+        let measured = measure_block_time(&l1.provider(), BLOCK_TIME_SAMPLE_BLOCKS)
+            .await
+            .unwrap();
+        assert_eq!(measured, 12.0);
+        assert!(!block_time_deviates(12_000, measured));
+
+        // Half-second blocks on a chain configured for 1s
+        let fast = chain_with_block_time(500);
+        let measured = measure_block_time(&fast.provider(), BLOCK_TIME_SAMPLE_BLOCKS)
+            .await
+            .unwrap();
+        assert_eq!(measured, 0.5);
+        assert!(block_time_deviates(1_000, measured));
+
+        // Nothing to measure before the first block
+        assert!(measure_block_time(&FakeChain::new().provider(), 10)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_block_time_deviation() {
+        assert!(!block_time_deviates(12_000, 15.0));
+        assert!(block_time_deviates(12_000, 15.1));
+        assert!(!block_time_deviates(12_000, 9.0));
+        assert!(block_time_deviates(12_000, 8.9));
+        assert!(block_time_deviates(12_000, 24.0));
+        assert!(!block_time_deviates(500, 0.5));
+    }
+
+    #[tokio::test]
+    async fn test_check_block_times() {
+        // L1 producing a block every 24s, L2 every half second
+        let l1 = chain_with_block_time(24_000);
+        let l2 = chain_with_block_time(500);
+
+        let mut config = Config::default();
+        check_block_times(&l1.provider(), &l2.provider(), &mut config, &Metrics::new()).await;
+        // Only warned about
+        assert_eq!(config.network_config().ethereum.block_time_ms, 12_000);
+
+        config.use_measured_block_times = true;
+        check_block_times(&l1.provider(), &l2.provider(), &mut config, &Metrics::new()).await;
+        assert_eq!(config.network_config().ethereum.block_time_ms, 24_000);
+        assert_eq!(config.network_config().unichain.block_time_ms, 500);
+
+        // A chain that can't be measured keeps its block time
+        let mut config = Config {
+            use_measured_block_times: true,
+            ..Default::default()
+        };
+        check_block_times(
+            &l1.provider(),
+            &FakeChain::new().provider(),
+            &mut config,
+            &Metrics::new(),
+        )
+        .await;
+        assert_eq!(config.network_config().ethereum.block_time_ms, 24_000);
+        assert_eq!(config.measured_block_times[1], None);
+    }
+
+    #[tokio::test]
+    async fn test_verify_contract_code() {
+        let network = NetworkConfig::mainnet();
+        let (l1, l2) = deployed(&network);
+        verify_contract_code(&l1.provider(), &l2.provider(), &network)
+            .await
+            .unwrap();
+
+        // L1 portal and L2 WETH are empty
+        l1.set_code(network.unichain.l1_portal, Bytes::new());
+        l2.set_code(network.unichain.weth, Bytes::new());
+        let err = verify_contract_code(&l1.provider(), &l2.provider(), &network)
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains(&format!(
+            "unichain.l1_portal ({}) on l1",
+            network.unichain.l1_portal
+        )));
+        assert!(err.contains("unichain.weth"));
+        assert!(!err.contains("spoke_pool"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_contract_code_rejects_wrong_contracts() {
+        let network = NetworkConfig::mainnet();
+        let (l1, l2) = deployed(&network);
+        // The L2 spoke pool is set to some other contract, and the factory to a
+        // contract without gameCount()
+        l2.set_call::<ISpokePool::wrappedNativeTokenCall>(
+            network.unichain.spoke_pool,
+            &Address::repeat_byte(0x99),
+        );
+        l1.set_call_revert::<IDisputeGameFactory::gameCountCall>(
+            network.unichain.l1_dispute_game_factory,
+            Bytes::new(),
+        );
+
+        let err = verify_contract_code(&l1.provider(), &l2.provider(), &network)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Configured address is not the expected contract"));
+        assert!(err.contains(&format!(
+            "unichain.spoke_pool ({}) on l2 wraps {}",
+            network.unichain.spoke_pool,
+            Address::repeat_byte(0x99)
+        )));
+        assert!(err.contains("unichain.l1_dispute_game_factory"));
+        assert!(err.contains("fails gameCount()"));
+        assert!(!err.contains("ethereum.spoke_pool"));
+        assert!(!err.contains("l1_portal"));
+    }
+
+    #[tokio::test]
+    async fn test_check_eoa_account() {
+        let eoa = Address::repeat_byte(0x11);
+        let l1 = Asserter::new();
+        let l2 = Asserter::new();
+        let l1_provider = ProviderBuilder::new().connect_mocked_client(l1.clone());
+        let l2_provider = ProviderBuilder::new().connect_mocked_client(l2.clone());
+
+        // Plain EOA, and one delegating with EIP-7702
+        l1.push_success(&"0x");
+        l2.push_success(&"0xef01002222222222222222222222222222222222222222");
+        assert!(!check_eoa_account(&l1_provider, &l2_provider, eoa, false)
+            .await
+            .unwrap());
+
+        // A Safe on L2 is refused
+        l1.push_success(&"0x");
+        l2.push_success(&"0x6080");
+        let err = check_eoa_account(&l1_provider, &l2_provider, eoa, false)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("is a contract on l2"));
+
+        // ...unless allowed
+        l1.push_success(&"0x6080");
+        l2.push_success(&"0x6080");
+        assert!(check_eoa_account(&l1_provider, &l2_provider, eoa, true)
+            .await
+            .unwrap());
+        assert!(l1.read_q().is_empty() && l2.read_q().is_empty());
+    }
+}
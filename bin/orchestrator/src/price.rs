@@ -0,0 +1,292 @@
+//! ETH / USD price for USD-denominated cost metrics.
+//!
+//! The price comes from the configured [`PriceFeedConfig`]: a Chainlink aggregator on
+//! either chain or an HTTP endpoint. [`PriceOracle`] keeps it for `price_refresh_secs`
+//! so each cycle doesn't read the feed again, and reports no price while the feed is
+//! unavailable so the USD metrics are left out rather than computed from a stale price.
+
+use crate::config::{FeedChain, PriceFeedConfig};
+use alloy_primitives::{Address, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use binding::chainlink::AggregatorV3Interface;
+use client::{price::HttpPriceFeed, ProviderTimeouts};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// Value of `wei` in USD at `eth_usd`.
+pub fn wei_to_usd(wei: U256, eth_usd: f64) -> f64 {
+    f64::from(wei) / 1e18 * eth_usd
+}
+
+/// Latest answer of the Chainlink feed at `feed`, unless last updated more than
+/// `max_age_secs` before the chain's latest block.
+pub async fn chainlink_price<P: Provider>(
+    provider: &P,
+    feed: Address,
+    max_age_secs: u64,
+) -> eyre::Result<f64> {
+    let aggregator = AggregatorV3Interface::new(feed, provider);
+    let decimals = aggregator.decimals().call().await?;
+    let round = aggregator.latestRoundData().call().await?;
+    let latest = provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Latest block not found"))?;
+
+    let updated_at: u64 = round.updatedAt.try_into().unwrap_or(u64::MAX);
+    let age = latest.header.timestamp.saturating_sub(updated_at);
+    if age > max_age_secs {
+        eyre::bail!("Price feed {feed} last updated {age}s ago, over {max_age_secs}s");
+    }
+    if !round.answer.is_positive() {
+        eyre::bail!("Price feed {feed} answered {}", round.answer);
+    }
+
+    let answer = f64::from(round.answer.into_raw());
+    Ok(answer / 10f64.powi(i32::from(decimals)))
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Chainlink {
+        feed: Address,
+        chain: FeedChain,
+        max_age_secs: u64,
+    },
+    Http(HttpPriceFeed),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cached {
+    price: f64,
+    fetched_at: Instant,
+}
+
+/// ETH / USD price read from the configured feed and reused for the TTL.
+///
+/// Clones share the same cached price.
+#[derive(Debug, Clone)]
+pub struct PriceOracle {
+    source: Source,
+    ttl: Duration,
+    cached: Arc<Mutex<Option<Cached>>>,
+}
+
+impl PriceOracle {
+    /// Oracle reading `feed`, with HTTP feeds giving up on a request after `timeouts`.
+    pub fn new(
+        feed: PriceFeedConfig,
+        ttl: Duration,
+        timeouts: ProviderTimeouts,
+    ) -> eyre::Result<Self> {
+        let source = match feed {
+            PriceFeedConfig::Chainlink {
+                feed,
+                chain,
+                max_age_secs,
+            } => Source::Chainlink {
+                feed,
+                chain,
+                max_age_secs,
+            },
+            PriceFeedConfig::Http { url, pointer } => {
+                Source::Http(HttpPriceFeed::new(url, pointer, timeouts)?)
+            }
+        };
+        Ok(Self {
+            source,
+            ttl,
+            cached: Arc::default(),
+        })
+    }
+
+    /// Current ETH / USD price, or `None` (logged) if the feed can't be read.
+    ///
+    /// A Chainlink feed is read through `l1_provider` or `l2_provider` depending on
+    /// the chain it is configured on.
+    pub async fn eth_usd<P1: Provider, P2: Provider>(
+        &self,
+        l1_provider: &P1,
+        l2_provider: &P2,
+    ) -> Option<f64> {
+        self.eth_usd_at(l1_provider, l2_provider, Instant::now())
+            .await
+    }
+
+    async fn eth_usd_at<P1: Provider, P2: Provider>(
+        &self,
+        l1_provider: &P1,
+        l2_provider: &P2,
+        now: Instant,
+    ) -> Option<f64> {
+        if let Some(price) = self.fresh(now) {
+            return Some(price);
+        }
+
+        let fetched = match &self.source {
+            Source::Chainlink {
+                feed,
+                chain: FeedChain::L1,
+                max_age_secs,
+            } => chainlink_price(l1_provider, *feed, *max_age_secs).await,
+            Source::Chainlink {
+                feed,
+                chain: FeedChain::L2,
+                max_age_secs,
+            } => chainlink_price(l2_provider, *feed, *max_age_secs).await,
+            Source::Http(http) => http.price().await,
+        };
+
+        match fetched {
+            Ok(price) => {
+                debug!(eth_usd = price, "Refreshed ETH / USD price");
+                *self.lock() = Some(Cached {
+                    price,
+                    fetched_at: now,
+                });
+                Some(price)
+            }
+            Err(e) => {
+                warn!(error = %e, "ETH / USD price unavailable, leaving out USD metrics");
+                *self.lock() = None;
+                None
+            }
+        }
+    }
+
+    /// Cached price, unless older than the TTL.
+    fn fresh(&self, now: Instant) -> Option<f64> {
+        self.lock()
+            .filter(|c| now.saturating_duration_since(c.fetched_at) < self.ttl)
+            .map(|c| c.price)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Cached>> {
+        self.cached.lock().expect("price lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{aliases::U80, I256};
+    use test_support::FakeChain;
+
+    const FEED: Address = Address::repeat_byte(0xfe);
+
+    fn set_answer(chain: &FakeChain, answer: i64, updated_at: u64) {
+        chain.set_call::<AggregatorV3Interface::decimalsCall>(FEED, &8u8);
+        chain.set_call::<AggregatorV3Interface::latestRoundDataCall>(
+            FEED,
+            &AggregatorV3Interface::latestRoundDataReturn {
+                roundId: U80::from(1),
+                answer: I256::try_from(answer).unwrap(),
+                startedAt: U256::from(updated_at),
+                updatedAt: U256::from(updated_at),
+                answeredInRound: U80::from(1),
+            },
+        );
+    }
+
+    fn chainlink(chain: FeedChain) -> PriceFeedConfig {
+        PriceFeedConfig::Chainlink {
+            feed: FEED,
+            chain,
+            max_age_secs: 3_600,
+        }
+    }
+
+    #[test]
+    fn test_wei_to_usd() {
+        let half_eth = U256::from(500_000_000_000_000_000_u64);
+        assert_eq!(wei_to_usd(half_eth, 3_000.0), 1_500.0);
+        assert_eq!(wei_to_usd(U256::ZERO, 3_000.0), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_chainlink_price() {
+        let chain = FakeChain::new();
+        chain.mine(10_000);
+
+        set_answer(&chain, 315_042_000_000, 9_000);
+        let price = chainlink_price(&chain.provider(), FEED, 3_600)
+            .await
+            .unwrap();
+        assert_eq!(price, 3_150.42);
+
+        // Stale
+        set_answer(&chain, 315_042_000_000, 6_000);
+        let err = chainlink_price(&chain.provider(), FEED, 3_600)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("last updated 4000s ago"));
+
+        set_answer(&chain, 0, 9_000);
+        assert!(chainlink_price(&chain.provider(), FEED, 3_600)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_oracle_caches_for_ttl() {
+        let l1 = FakeChain::new();
+        let l2 = FakeChain::new();
+        l2.mine(10_000);
+        set_answer(&l2, 300_000_000_000, 9_000);
+        let oracle = PriceOracle::new(
+            chainlink(FeedChain::L2),
+            Duration::from_secs(300),
+            ProviderTimeouts::default(),
+        )
+        .unwrap();
+        let start = Instant::now();
+
+        let price = oracle
+            .eth_usd_at(&l1.provider(), &l2.provider(), start)
+            .await;
+        assert_eq!(price, Some(3_000.0));
+        let calls = l2.request_count("eth_call");
+
+        // Reused within the TTL, read again after it
+        set_answer(&l2, 310_000_000_000, 9_000);
+        let within = start + Duration::from_secs(299);
+        let price = oracle
+            .eth_usd_at(&l1.provider(), &l2.provider(), within)
+            .await;
+        assert_eq!(price, Some(3_000.0));
+        assert_eq!(l2.request_count("eth_call"), calls);
+
+        let after = start + Duration::from_secs(300);
+        let price = oracle
+            .eth_usd_at(&l1.provider(), &l2.provider(), after)
+            .await;
+        assert_eq!(price, Some(3_100.0));
+    }
+
+    #[tokio::test]
+    async fn test_oracle_reports_no_price_when_feed_unavailable() {
+        let l1 = FakeChain::new();
+        let l2 = FakeChain::new();
+        l1.mine(10_000);
+        set_answer(&l1, 300_000_000_000, 9_000);
+        let oracle = PriceOracle::new(
+            chainlink(FeedChain::L1),
+            Duration::ZERO,
+            ProviderTimeouts::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            oracle.eth_usd(&l1.provider(), &l2.provider()).await,
+            Some(3_000.0)
+        );
+
+        // Gone stale: no price rather than the last one
+        l1.mine(20_000);
+        assert_eq!(oracle.eth_usd(&l1.provider(), &l2.provider()).await, None);
+    }
+}
@@ -0,0 +1,86 @@
+//! Health of the proofs of proven withdrawals across cycles.
+//!
+//! A proof can be invalidated long after it was submitted: the challenger wins its
+//! dispute game, the guardian blacklists the game, or the portal moves on to another
+//! game type. The withdrawal then can't be finalized until it is proven again.
+//! [`ProofHealthTracker`] remembers each withdrawal's last [`ProofHealth`] so such a
+//! proof is alerted on once, when it turns invalid, rather than every cycle.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+use withdrawal::{submitter::ProofHealth, types::WithdrawalHash};
+
+/// Last seen proof health of each proven withdrawal, shared by every clone.
+#[derive(Debug, Clone, Default)]
+pub struct ProofHealthTracker {
+    last: Arc<Mutex<HashMap<WithdrawalHash, ProofHealth>>>,
+}
+
+impl ProofHealthTracker {
+    /// Record the current health of the proof of `hash`.
+    ///
+    /// Returns whether the proof just turned invalid, i.e. was not known to be invalid
+    /// before.
+    pub fn record(&self, hash: WithdrawalHash, health: ProofHealth) -> bool {
+        let previous = self.lock().insert(hash, health);
+        matches!(health, ProofHealth::Invalid(_))
+            && !matches!(previous, Some(ProofHealth::Invalid(_)))
+    }
+
+    /// Last recorded health of the proof of `hash`.
+    pub fn get(&self, hash: WithdrawalHash) -> Option<ProofHealth> {
+        self.lock().get(&hash).copied()
+    }
+
+    /// Forget withdrawals that are no longer proven, e.g. finalized or proven again.
+    pub fn retain(&self, proven: &HashSet<WithdrawalHash>) {
+        self.lock().retain(|hash, _| proven.contains(hash));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<WithdrawalHash, ProofHealth>> {
+        self.last.lock().expect("proof health lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use withdrawal::submitter::ProofRejection;
+
+    const INVALID: ProofHealth = ProofHealth::Invalid(ProofRejection::ChallengerWins);
+
+    #[test]
+    fn test_reports_transition_into_invalid_once() {
+        let tracker = ProofHealthTracker::default();
+        let hash = WithdrawalHash::repeat_byte(1);
+
+        assert!(!tracker.record(hash, ProofHealth::Healthy));
+        assert!(!tracker.record(hash, ProofHealth::AtRisk));
+        assert!(tracker.record(hash, INVALID));
+        assert!(!tracker.record(hash, INVALID));
+        // Another reason is still the same invalid proof
+        assert!(!tracker.record(hash, ProofHealth::Invalid(ProofRejection::Blacklisted)));
+        assert_eq!(
+            tracker.get(hash),
+            Some(ProofHealth::Invalid(ProofRejection::Blacklisted))
+        );
+    }
+
+    #[test]
+    fn test_forgets_withdrawals_no_longer_proven() {
+        let tracker = ProofHealthTracker::default();
+        let kept = WithdrawalHash::repeat_byte(1);
+        let reproven = WithdrawalHash::repeat_byte(2);
+        assert!(tracker.record(kept, INVALID));
+        assert!(tracker.record(reproven, INVALID));
+
+        tracker.retain(&HashSet::from([kept]));
+
+        assert_eq!(tracker.get(reproven), None);
+        assert!(!tracker.record(kept, INVALID));
+        // Invalidated again after being proven again
+        assert!(tracker.record(reproven, INVALID));
+    }
+}
@@ -0,0 +1,696 @@
+//! Reconciliation of finalized withdrawals against the value their recipients received.
+//!
+//! A successful `WithdrawalFinalized` event says the portal made the withdrawal's call,
+//! not that the value arrived. Without `debug_trace*`, which most RPC providers don't
+//! serve, [`reconcile_withdrawals`] checks it with heuristics. The finalize receipt must
+//! carry the withdrawal's successful `WithdrawalFinalized` log, the portal's record of
+//! the target call's result. Then the recipient's balance is read on both sides of the
+//! finalize block. An EOA's balance moves by exactly the value once the fees it paid in
+//! that block are added back, so a shortfall is a mismatch. A contract may forward what
+//! it receives, so for one the call result is the check and a balance change that
+//! doesn't add up leaves the withdrawal unverifiable rather than mismatched.
+//!
+//! The balance and nonce reads are at past blocks, which needs an L1 archive node once
+//! the finalize block is more than about 128 blocks old. A failed read is reported as
+//! such, apart from withdrawals the heuristics can't decide.
+
+use crate::config::Config;
+use alloy_primitives::{utils::format_ether, Address, TxHash, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{BlockNumberOrTag, TransactionReceipt};
+use alloy_sol_types::SolEvent;
+use binding::opstack::IOptimismPortal2::WithdrawalFinalized;
+use serde::{Deserialize, Serialize};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use tracing::{info, warn};
+use withdrawal::{
+    state::WithdrawalStateProvider,
+    types::{L1WithdrawalEventKind, WithdrawalHash},
+};
+
+/// A withdrawal finalized with a successful call, as found on L1 and L2.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalizedWithdrawal {
+    pub hash: WithdrawalHash,
+    /// Target of the withdrawal, which the portal sent the value to
+    pub recipient: Address,
+    /// Value in wei
+    pub value: U256,
+    /// L1 transaction that finalized the withdrawal
+    pub finalize_tx: TxHash,
+    /// L1 block of the finalize transaction
+    pub block_number: u64,
+    /// Unix timestamp of that block
+    pub finalized_at: u64,
+}
+
+/// Whether a withdrawal's recipient is seen to have received its value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Reconciliation {
+    /// The value arrived
+    Matched,
+    /// The chain contradicts the withdrawal having paid out its value
+    Mismatched { reason: String },
+    /// The heuristics can't tell either way
+    Unverifiable { reason: String },
+    /// The recipient's state at the finalize block couldn't be read, e.g. from a node
+    /// without archive state
+    ReadFailed { reason: String },
+}
+
+impl Reconciliation {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Matched => "matched",
+            Self::Mismatched { .. } => "mismatched",
+            Self::Unverifiable { .. } => "unverifiable",
+            Self::ReadFailed { .. } => "read_failed",
+        }
+    }
+
+    fn reason(&self) -> &str {
+        match self {
+            Self::Matched => "",
+            Self::Mismatched { reason }
+            | Self::Unverifiable { reason }
+            | Self::ReadFailed { reason } => reason,
+        }
+    }
+}
+
+/// A finalized withdrawal and the outcome of reconciling it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconciledWithdrawal {
+    pub withdrawal: FinalizedWithdrawal,
+    pub result: Reconciliation,
+}
+
+/// Outcome of reconciling the withdrawals finalized over a window.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    /// Unix time the report was made
+    pub generated_at: u64,
+    /// Start of the window, as a Unix timestamp
+    pub since: u64,
+    /// Withdrawals finalized in the window, oldest first
+    pub entries: Vec<ReconciledWithdrawal>,
+}
+
+impl ReconciliationReport {
+    fn count(&self, outcome: &str) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.result.as_str() == outcome)
+            .count()
+    }
+
+    pub fn matched(&self) -> usize {
+        self.count("matched")
+    }
+
+    pub fn mismatched(&self) -> usize {
+        self.count("mismatched")
+    }
+
+    pub fn unverifiable(&self) -> usize {
+        self.count("unverifiable")
+    }
+
+    pub fn read_failed(&self) -> usize {
+        self.count("read_failed")
+    }
+
+    /// Entries whose recipient is seen not to have received the value.
+    pub fn mismatches(&self) -> impl Iterator<Item = &ReconciledWithdrawal> {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry.result, Reconciliation::Mismatched { .. }))
+    }
+
+    /// Render as text: a line per withdrawal, then the totals.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for entry in &self.entries {
+            let withdrawal = &entry.withdrawal;
+            let line = format!(
+                "{}  {}  {}  {:<12}  {}",
+                withdrawal.hash,
+                withdrawal.recipient,
+                format_ether(withdrawal.value),
+                entry.result.as_str(),
+                entry.result.reason()
+            );
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "total {}: {} matched, {} mismatched, {} unverifiable, {} read failed\n",
+            self.entries.len(),
+            self.matched(),
+            self.mismatched(),
+            self.unverifiable(),
+            self.read_failed()
+        ));
+        out
+    }
+}
+
+/// Reconcile our withdrawals finalized in the last `since_secs`.
+///
+/// L2 is scanned over `since_secs + withdrawal_lookback_secs` for the withdrawals we
+/// initiated, which give each finalized withdrawal's recipient and value. Finalized
+/// withdrawals initiated before that are left out.
+pub async fn reconcile_finalized<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    config: &Config,
+    since_secs: u64,
+) -> eyre::Result<ReconciliationReport>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let network = config.network_config();
+    let window_secs = since_secs.saturating_add(config.withdrawal_lookback_secs);
+
+    let l1_current_block = l1_provider.get_block_number().await?;
+    let l2_current_block = l2_provider.get_block_number().await?;
+    let l1_from_block = l1_current_block.saturating_sub(network.ethereum.blocks_in(since_secs));
+    let l2_from_block = l2_current_block.saturating_sub(network.unichain.blocks_in(window_secs));
+
+    let state_provider = WithdrawalStateProvider::new(
+        l1_provider.clone(),
+        l2_provider.clone(),
+        network.unichain.l1_portal,
+        network.unichain.l2_to_l1_message_passer,
+    );
+
+    let generated_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    let since = generated_at.saturating_sub(since_secs);
+
+    let events = state_provider
+        .scan_l1_withdrawal_events(
+            BlockNumberOrTag::Number(l1_from_block),
+            BlockNumberOrTag::Number(l1_current_block),
+            Some(config.eoa_address),
+        )
+        .await?;
+    let finalized: Vec<_> = events
+        .iter()
+        .filter(|e| matches!(e.kind, L1WithdrawalEventKind::Finalized { success: true }))
+        .filter(|e| e.timestamp >= since)
+        .collect();
+
+    let mut withdrawals = vec![];
+    if !finalized.is_empty() {
+        // Finalized events carry no address; the L2 scan tells which are ours
+        let initiated: HashMap<WithdrawalHash, _> = state_provider
+            .get_initiated_withdrawals(l2_from_block, l2_current_block, config.eoa_address)
+            .await?
+            .into_iter()
+            .map(|w| (w.hash, w))
+            .collect();
+
+        for event in finalized {
+            let Some(initiation) = initiated.get(&event.hash) else {
+                continue;
+            };
+            withdrawals.push(FinalizedWithdrawal {
+                hash: event.hash,
+                recipient: initiation.transaction.target,
+                value: initiation.transaction.value,
+                finalize_tx: event.tx_hash,
+                block_number: event.block_number,
+                finalized_at: event.timestamp,
+            });
+        }
+    }
+
+    let entries =
+        reconcile_withdrawals(&l1_provider, network.unichain.l1_portal, &withdrawals).await?;
+    let report = ReconciliationReport {
+        generated_at,
+        since,
+        entries,
+    };
+    info!(
+        withdrawals = report.entries.len(),
+        matched = report.matched(),
+        mismatched = report.mismatched(),
+        unverifiable = report.unverifiable(),
+        read_failed = report.read_failed(),
+        "Reconciled finalized withdrawals"
+    );
+    Ok(report)
+}
+
+/// Reconcile `withdrawals`, finalized through `portal`, against the L1 state.
+///
+/// Withdrawals paying the same recipient in the same block are checked together,
+/// against their summed value, since the balance reads can't tell them apart.
+pub async fn reconcile_withdrawals<P: Provider>(
+    l1_provider: &P,
+    portal: Address,
+    withdrawals: &[FinalizedWithdrawal],
+) -> eyre::Result<Vec<ReconciledWithdrawal>> {
+    let mut receipts: HashMap<TxHash, Option<TransactionReceipt>> = HashMap::new();
+    for withdrawal in withdrawals {
+        if let Entry::Vacant(entry) = receipts.entry(withdrawal.finalize_tx) {
+            entry.insert(
+                l1_provider
+                    .get_transaction_receipt(withdrawal.finalize_tx)
+                    .await?,
+            );
+        }
+    }
+
+    let mut results: Vec<Option<Reconciliation>> = withdrawals
+        .iter()
+        .map(|withdrawal| {
+            check_receipt(
+                withdrawal,
+                receipts[&withdrawal.finalize_tx].as_ref(),
+                portal,
+            )
+        })
+        .collect();
+
+    // Withdrawals whose receipt checks out, by block and recipient
+    let mut groups: BTreeMap<(u64, Address), Vec<usize>> = BTreeMap::new();
+    for (i, withdrawal) in withdrawals.iter().enumerate() {
+        if results[i].is_none() {
+            groups
+                .entry((withdrawal.block_number, withdrawal.recipient))
+                .or_default()
+                .push(i);
+        }
+    }
+
+    for ((block, recipient), members) in groups {
+        let mut finalize_txs: Vec<TxHash> = members
+            .iter()
+            .map(|&i| withdrawals[i].finalize_tx)
+            .collect();
+        finalize_txs.sort();
+        finalize_txs.dedup();
+
+        // The recipient's own finalize transactions are the only ones it's expected
+        // to have paid for in the block
+        let sent: Vec<&TransactionReceipt> = finalize_txs
+            .iter()
+            .filter_map(|tx| receipts[tx].as_ref())
+            .filter(|receipt| receipt.from == recipient)
+            .collect();
+        let fees = sent.iter().fold(U256::ZERO, |total, receipt| {
+            total + U256::from(receipt.gas_used) * U256::from(receipt.effective_gas_price)
+        });
+        let expected = members
+            .iter()
+            .fold(U256::ZERO, |total, &i| total + withdrawals[i].value);
+
+        let result =
+            match check_balance(l1_provider, recipient, block, expected, fees, sent.len()).await {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!(
+                        recipient = %recipient,
+                        block,
+                        error = %e,
+                        "Failed to read the recipient's state, is the L1 node an archive node?"
+                    );
+                    Reconciliation::ReadFailed {
+                        reason: format!("failed to read the recipient's state: {e}"),
+                    }
+                }
+            };
+        for i in members {
+            results[i] = Some(result.clone());
+        }
+    }
+
+    Ok(withdrawals
+        .iter()
+        .zip(results)
+        .map(|(withdrawal, result)| ReconciledWithdrawal {
+            withdrawal: withdrawal.clone(),
+            result: result.expect("every withdrawal is reconciled"),
+        })
+        .collect())
+}
+
+/// Reconcile `withdrawal` from its finalize receipt alone, `None` if that checks out.
+fn check_receipt(
+    withdrawal: &FinalizedWithdrawal,
+    receipt: Option<&TransactionReceipt>,
+    portal: Address,
+) -> Option<Reconciliation> {
+    let Some(receipt) = receipt else {
+        return Some(Reconciliation::Unverifiable {
+            reason: "finalize receipt not found".to_string(),
+        });
+    };
+    if !receipt.status() {
+        return Some(Reconciliation::Mismatched {
+            reason: "finalize transaction reverted".to_string(),
+        });
+    }
+
+    let finalized = receipt.logs().iter().find_map(|log| {
+        if log.address() != portal {
+            return None;
+        }
+        WithdrawalFinalized::decode_log(&log.inner)
+            .ok()
+            .filter(|event| event.withdrawalHash == withdrawal.hash)
+    });
+    match finalized {
+        Some(event) if event.success => None,
+        Some(_) => Some(Reconciliation::Mismatched {
+            reason: "finalize receipt reports the withdrawal's call as failed".to_string(),
+        }),
+        None => Some(Reconciliation::Mismatched {
+            reason: "finalize receipt has no WithdrawalFinalized log for the withdrawal"
+                .to_string(),
+        }),
+    }
+}
+
+/// Compare the balance change of `recipient` across `block` with `expected`.
+///
+/// `fees` is what the recipient paid for the `sent` finalize transactions it sent in
+/// the block; any other transaction it sent there makes the change unattributable.
+/// Reads the recipient's nonce and balance at `block` and the one before, so an older
+/// block needs an archive node.
+async fn check_balance<P: Provider>(
+    l1_provider: &P,
+    recipient: Address,
+    block: u64,
+    expected: U256,
+    fees: U256,
+    sent: usize,
+) -> eyre::Result<Reconciliation> {
+    let before = block.saturating_sub(1);
+    let nonce_before = l1_provider
+        .get_transaction_count(recipient)
+        .number(before)
+        .await?;
+    let nonce_after = l1_provider
+        .get_transaction_count(recipient)
+        .number(block)
+        .await?;
+    if nonce_after.saturating_sub(nonce_before) != sent as u64 {
+        return Ok(Reconciliation::Unverifiable {
+            reason: format!(
+                "recipient sent {} transactions in block {block}, of which {sent} finalized",
+                nonce_after.saturating_sub(nonce_before)
+            ),
+        });
+    }
+
+    let balance_before = l1_provider.get_balance(recipient).number(before).await?;
+    let balance_after = l1_provider.get_balance(recipient).number(block).await?;
+    let is_contract = !l1_provider
+        .get_code_at(recipient)
+        .number(block)
+        .await?
+        .is_empty();
+
+    // Balance after, with the fees paid added back, less the balance before
+    let received = (balance_after + fees).checked_sub(balance_before);
+    if received == Some(expected) {
+        return Ok(Reconciliation::Matched);
+    }
+
+    let received = received.map_or_else(
+        || format!("-{}", format_ether(balance_before - balance_after - fees)),
+        format_ether,
+    );
+    let detail = format!(
+        "received {received} ETH, expected {}",
+        format_ether(expected)
+    );
+    Ok(if is_contract {
+        Reconciliation::Unverifiable {
+            reason: format!("contract recipient may have moved funds: {detail}"),
+        }
+    } else if balance_after + fees > balance_before + expected {
+        Reconciliation::Unverifiable {
+            reason: format!("recipient also received other transfers: {detail}"),
+        }
+    } else {
+        Reconciliation::Mismatched { reason: detail }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, B256};
+    use alloy_rpc_types_eth::{Receipt, ReceiptEnvelope, ReceiptWithBloom};
+    use test_support::FakeChain;
+
+    const PORTAL: Address = Address::repeat_byte(0xbe);
+    const EOA: Address = Address::repeat_byte(0x11);
+    const RELAYER: Address = Address::repeat_byte(0x22);
+    /// Fee of every finalize receipt: 100k gas at 1 gwei
+    const FEE: u64 = 100_000 * 1_000_000_000;
+
+    fn eth(milli: u64) -> U256 {
+        U256::from(milli) * U256::from(10u64.pow(15))
+    }
+
+    fn withdrawal(byte: u8, recipient: Address, value: U256, block: u64) -> FinalizedWithdrawal {
+        FinalizedWithdrawal {
+            hash: B256::repeat_byte(byte),
+            recipient,
+            value,
+            finalize_tx: B256::repeat_byte(0xf0 + byte),
+            block_number: block,
+            finalized_at: 1_700_000_000 + block * 12,
+        }
+    }
+
+    /// Receipt of `withdrawal`'s finalize transaction sent by `from`, logging each of
+    /// `finalized` with its call's success.
+    fn finalize_receipt(
+        withdrawal: &FinalizedWithdrawal,
+        from: Address,
+        finalized: &[(WithdrawalHash, bool)],
+    ) -> TransactionReceipt {
+        let logs = finalized
+            .iter()
+            .map(|(hash, success)| alloy_rpc_types_eth::Log {
+                inner: alloy_primitives::Log {
+                    address: PORTAL,
+                    data: WithdrawalFinalized {
+                        withdrawalHash: *hash,
+                        success: *success,
+                    }
+                    .encode_log_data(),
+                },
+                ..Default::default()
+            })
+            .collect();
+
+        TransactionReceipt {
+            inner: ReceiptEnvelope::Eip1559(ReceiptWithBloom {
+                receipt: Receipt {
+                    status: true.into(),
+                    cumulative_gas_used: 100_000,
+                    logs,
+                },
+                logs_bloom: Default::default(),
+            }),
+            transaction_hash: withdrawal.finalize_tx,
+            transaction_index: Some(0),
+            block_hash: None,
+            block_number: Some(withdrawal.block_number),
+            gas_used: 100_000,
+            effective_gas_price: 1_000_000_000,
+            blob_gas_used: None,
+            blob_gas_price: None,
+            from,
+            to: Some(PORTAL),
+            contract_address: None,
+        }
+    }
+
+    /// Stub `address`'s balance and nonce before and after `block`.
+    fn stub_account(
+        chain: &FakeChain,
+        address: Address,
+        block: u64,
+        balances: (U256, U256),
+        nonces: (u64, u64),
+    ) {
+        chain.set_balance_at(address, block - 1, balances.0);
+        chain.set_balance_at(address, block, balances.1);
+        chain.set_nonce_at(address, block - 1, nonces.0);
+        chain.set_nonce_at(address, block, nonces.1);
+    }
+
+    async fn reconcile(
+        chain: &FakeChain,
+        withdrawals: &[FinalizedWithdrawal],
+    ) -> Vec<Reconciliation> {
+        reconcile_withdrawals(&chain.provider(), PORTAL, withdrawals)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.result)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_eoa_balance_delta() {
+        let chain = FakeChain::new();
+
+        // Finalized by the recipient itself: the fee it paid is added back
+        let own = withdrawal(1, EOA, eth(1_000), 100);
+        chain.set_receipt(finalize_receipt(&own, EOA, &[(own.hash, true)]));
+        let after = eth(5_000) + eth(1_000) - U256::from(FEE);
+        stub_account(&chain, EOA, 100, (eth(5_000), after), (7, 8));
+
+        // Finalized by a relayer: the recipient's balance moves by the value alone
+        let relayed = withdrawal(2, RELAYER, eth(300), 200);
+        chain.set_receipt(finalize_receipt(&relayed, EOA, &[(relayed.hash, true)]));
+        stub_account(&chain, RELAYER, 200, (eth(50), eth(350)), (3, 3));
+
+        // The recipient received less than the value
+        let short = withdrawal(3, EOA, eth(2_000), 300);
+        chain.set_receipt(finalize_receipt(&short, EOA, &[(short.hash, true)]));
+        let after = eth(5_000) + eth(1_500) - U256::from(FEE);
+        stub_account(&chain, EOA, 300, (eth(5_000), after), (9, 10));
+
+        let results = reconcile(&chain, &[own, relayed, short]).await;
+        assert_eq!(results[0], Reconciliation::Matched);
+        assert_eq!(results[1], Reconciliation::Matched);
+        assert_eq!(
+            results[2],
+            Reconciliation::Mismatched {
+                reason: "received 1.500000000000000000 ETH, expected 2.000000000000000000"
+                    .to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_eoa_balance_delta_sums_withdrawals_in_block() {
+        let chain = FakeChain::new();
+        let first = withdrawal(1, EOA, eth(1_000), 100);
+        let mut second = withdrawal(2, EOA, eth(500), 100);
+        second.finalize_tx = first.finalize_tx;
+        chain.set_receipt(finalize_receipt(
+            &first,
+            RELAYER,
+            &[(first.hash, true), (second.hash, true)],
+        ));
+        stub_account(&chain, EOA, 100, (eth(10), eth(1_510)), (0, 0));
+
+        let results = reconcile(&chain, &[first.clone(), second.clone()]).await;
+        assert_eq!(results, [Reconciliation::Matched, Reconciliation::Matched]);
+
+        // More than the value arrived: other transfers are indistinguishable
+        stub_account(&chain, EOA, 100, (eth(10), eth(1_600)), (0, 0));
+        let results = reconcile(&chain, &[first.clone(), second.clone()]).await;
+        assert!(matches!(results[0], Reconciliation::Unverifiable { .. }));
+
+        // The recipient sent a transaction of its own in the block
+        stub_account(&chain, EOA, 100, (eth(10), eth(1_500)), (0, 1));
+        let results = reconcile(&chain, &[first, second]).await;
+        assert!(matches!(results[1], Reconciliation::Unverifiable { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_receipt_checks_and_contract_recipient() {
+        let chain = FakeChain::new();
+
+        let missing = withdrawal(1, EOA, eth(1_000), 100);
+        let unlogged = withdrawal(2, EOA, eth(1_000), 200);
+        chain.set_receipt(finalize_receipt(
+            &unlogged,
+            RELAYER,
+            &[(missing.hash, true)],
+        ));
+        let failed = withdrawal(3, EOA, eth(1_000), 300);
+        chain.set_receipt(finalize_receipt(&failed, RELAYER, &[(failed.hash, false)]));
+
+        // A contract that forwarded part of what it received
+        let contract = Address::repeat_byte(0xcc);
+        chain.set_code(contract, Bytes::from_static(&[0x60, 0x80]));
+        let forwarded = withdrawal(4, contract, eth(1_000), 400);
+        chain.set_receipt(finalize_receipt(
+            &forwarded,
+            RELAYER,
+            &[(forwarded.hash, true)],
+        ));
+        stub_account(&chain, contract, 400, (U256::ZERO, eth(400)), (1, 1));
+
+        let results = reconcile(&chain, &[missing, unlogged, failed, forwarded]).await;
+        assert_eq!(
+            results
+                .iter()
+                .map(Reconciliation::as_str)
+                .collect::<Vec<_>>(),
+            ["unverifiable", "mismatched", "mismatched", "unverifiable"]
+        );
+    }
+
+    #[test]
+    fn test_report_counts_and_render() {
+        let entry = |byte, result| ReconciledWithdrawal {
+            withdrawal: withdrawal(byte, EOA, eth(1_000), 100),
+            result,
+        };
+        let report = ReconciliationReport {
+            generated_at: 1_700_100_000,
+            since: 1_700_000_000,
+            entries: vec![
+                entry(1, Reconciliation::Matched),
+                entry(
+                    2,
+                    Reconciliation::Mismatched {
+                        reason: "finalize transaction reverted".to_string(),
+                    },
+                ),
+                entry(
+                    3,
+                    Reconciliation::ReadFailed {
+                        reason: "failed to read the recipient's state: missing trie node"
+                            .to_string(),
+                    },
+                ),
+            ],
+        };
+
+        assert_eq!(
+            (
+                report.matched(),
+                report.mismatched(),
+                report.unverifiable(),
+                report.read_failed()
+            ),
+            (1, 1, 0, 1)
+        );
+        assert_eq!(report.mismatches().count(), 1);
+        let rendered = report.render();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert!(lines[0].ends_with("1.000000000000000000  matched"));
+        assert!(lines[1].ends_with("mismatched    finalize transaction reverted"));
+        assert!(lines[2].contains("read_failed   failed to read the recipient's state"));
+        assert_eq!(
+            lines[3],
+            "total 3: 1 matched, 1 mismatched, 0 unverifiable, 1 read failed"
+        );
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains(r#""outcome":"mismatched""#));
+        assert_eq!(
+            serde_json::from_str::<ReconciliationReport>(&json).unwrap(),
+            report
+        );
+    }
+}
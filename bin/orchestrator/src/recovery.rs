@@ -0,0 +1,70 @@
+//! Recovery of proven withdrawals that fell out of the regular lookback.
+//!
+//! A withdrawal proven more than `withdrawal_lookback_secs` ago (e.g. during an outage)
+//! no longer shows up in the per-cycle scan even though it still needs finalizing. A
+//! low-frequency scan over a much longer window finds those and hands them to
+//! [`crate::process_pending_withdrawals`].
+
+use crate::config::Config;
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use client::{coverage::ScanCoverage, retry::RetryBudget};
+use withdrawal::state::{PendingWithdrawal, WithdrawalStateProvider};
+
+/// Find proven but unfinalized withdrawals initiated before the regular lookback.
+///
+/// L1 is searched over `withdrawal_recovery_lookback_secs`; L2 only over the part of that
+/// window older than `withdrawal_lookback_secs`, since newer withdrawals are covered by
+/// the regular scan. Returns nothing when recovery is disabled. Scan retries are drawn
+/// from `retry_budget` and blocks covered added to `scan_coverage`.
+pub async fn recover_proven_withdrawals<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    config: &Config,
+    retry_budget: &RetryBudget,
+    scan_coverage: &ScanCoverage,
+) -> eyre::Result<Vec<PendingWithdrawal>>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    if config.withdrawal_recovery_lookback_secs <= config.withdrawal_lookback_secs {
+        return Ok(vec![]);
+    }
+
+    let network = config.network_config();
+
+    let l1_current_block = l1_provider.get_block_number().await?;
+    let l2_current_block = l2_provider.get_block_number().await?;
+    let l1_from_block = l1_current_block.saturating_sub(
+        network
+            .ethereum
+            .blocks_in(config.withdrawal_recovery_lookback_secs),
+    );
+    let l2_from_block = l2_current_block.saturating_sub(
+        network
+            .unichain
+            .blocks_in(config.withdrawal_recovery_lookback_secs),
+    );
+    let l2_to_block = l2_current_block
+        .saturating_sub(network.unichain.blocks_in(config.withdrawal_lookback_secs));
+
+    let state_provider = WithdrawalStateProvider::new(
+        l1_provider,
+        l2_provider,
+        network.unichain.l1_portal,
+        network.unichain.l2_to_l1_message_passer,
+    )
+    .with_retry_budget(retry_budget.clone())
+    .with_scan_coverage(scan_coverage.clone());
+
+    state_provider
+        .recover_proven_withdrawals(
+            BlockNumberOrTag::Number(l1_from_block),
+            BlockNumberOrTag::Number(l1_current_block),
+            l2_from_block,
+            l2_to_block,
+            config.eoa_address,
+        )
+        .await
+}
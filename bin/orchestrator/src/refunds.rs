@@ -0,0 +1,760 @@
+//! Tracking of expired deposits and their origin-chain refunds.
+//!
+//! A deposit that passes its fill deadline unfilled is refunded on L1 by Across. The
+//! tracker notices both transitions once per process lifetime so the
+//! `orchestrator_deposits_expired_total` / `orchestrator_deposits_refunded_total`
+//! counters stay monotonic across cycles.
+//!
+//! Refunds are also reconciled against our deposit history: every expired deposit's
+//! input amount should come back, either transferred by its refund leaf or accrued in
+//! the SpokePool's `getRelayerRefund` until claimed. [`RefundLedger`] keeps the history
+//! across restarts, and each cycle reports the drift between what is owed and what was
+//! paid.
+
+use crate::{config::Config, metrics::Metrics, refresh_inflight_deposits};
+use alloy_primitives::{utils::format_ether, I256, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use binding::across::ISpokePool;
+use client::{coverage::ScanCoverage, retry::RetryBudget};
+use deposit::{
+    match_refunds, ClaimedRefund, DepositCheckpoint, DepositStateProvider, InFlightDeposit,
+    RefundedDeposit,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use tracing::{debug, error, info, warn};
+
+/// Our refund history, persisted in the state store.
+///
+/// Expired deposits and directly transferred refunds are keyed by deposit id, so
+/// seeing them again on later cycles doesn't count them twice. The ledger only
+/// accounts for deposits that expire after it [started](Self::start): refunds owed or
+/// claimed before then are for deposits it never saw.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefundLedger {
+    /// Input amounts of our deposits that expired unfilled
+    #[serde(default)]
+    expired: BTreeMap<U256, U256>,
+    /// Fill deadlines of the deposits in `expired`
+    #[serde(default)]
+    fill_deadlines: BTreeMap<U256, u64>,
+    /// Refunds transferred to us by their leaf rather than deferred
+    #[serde(default)]
+    transferred: BTreeMap<U256, U256>,
+    /// Deposits whose refund was deferred into the SpokePool
+    #[serde(default)]
+    deferred: BTreeSet<U256>,
+    /// Input amounts of expired deposits pruned from `expired`
+    #[serde(default)]
+    pruned_expected_wei: U256,
+    /// Transferred refunds of the deposits pruned from `expired`
+    #[serde(default)]
+    pruned_transferred_wei: U256,
+    /// Deferred refunds claimed from the SpokePool
+    #[serde(default)]
+    claimed_wei: U256,
+    /// Last L1 block scanned for our claims
+    #[serde(default)]
+    claims_scanned_to: Option<u64>,
+    /// L2 timestamp the ledger started at; deposits that expired earlier aren't counted
+    #[serde(default)]
+    started_at: Option<u64>,
+    /// Refunds already claimable when the ledger started
+    #[serde(default)]
+    preexisting_wei: U256,
+}
+
+impl RefundLedger {
+    /// Start accounting at L2 timestamp `now` and L1 block `l1_block`, with `claimable`
+    /// already accrued in the SpokePool for deposits before then.
+    ///
+    /// Deposits already recorded as expired are dropped, as their refunds may be part
+    /// of `claimable` or have been claimed before `l1_block`.
+    pub fn start(&mut self, now: u64, l1_block: u64, claimable: U256) {
+        self.expired.clear();
+        self.fill_deadlines.clear();
+        self.transferred.clear();
+        self.deferred.clear();
+        self.started_at = Some(now);
+        self.preexisting_wei = claimable;
+        self.claims_scanned_to = Some(l1_block);
+    }
+
+    /// Record `deposits` as expired unfilled, unless they expired before the ledger
+    /// started.
+    pub fn record_expired(&mut self, deposits: &[InFlightDeposit]) {
+        let started_at = self.started_at.unwrap_or_default();
+        for deposit in deposits
+            .iter()
+            .filter(|d| u64::from(d.fill_deadline) >= started_at)
+        {
+            self.expired
+                .insert(deposit.deposit_id, deposit.input_amount);
+            self.fill_deadlines
+                .insert(deposit.deposit_id, deposit.fill_deadline.into());
+        }
+    }
+
+    /// Record the refunds of `refunded` for deposits in the ledger.
+    pub fn record_refunds(&mut self, refunded: &[RefundedDeposit]) {
+        for refunded in refunded
+            .iter()
+            .filter(|r| self.expired.contains_key(&r.deposit_id))
+        {
+            if refunded.refund.deferred {
+                self.deferred.insert(refunded.deposit_id);
+            } else {
+                self.transferred
+                    .insert(refunded.deposit_id, refunded.refund.amount);
+            }
+        }
+    }
+
+    /// Add `claims`, found by a scan up to L1 block `scanned_to`.
+    pub fn record_claims(&mut self, claims: &[ClaimedRefund], scanned_to: u64) {
+        self.claimed_wei = claims
+            .iter()
+            .fold(self.claimed_wei, |sum, claim| sum + claim.amount);
+        self.claims_scanned_to = Some(scanned_to);
+    }
+
+    pub const fn claims_scanned_to(&self) -> Option<u64> {
+        self.claims_scanned_to
+    }
+
+    /// Fold the deposits whose fill deadline is more than `retention_secs` before `now`
+    /// into running totals. Deposit scans don't reach back that far, so they won't be
+    /// recorded again.
+    pub fn prune(&mut self, now: u64, retention_secs: u64) {
+        let cutoff = now.saturating_sub(retention_secs);
+        let pruned: Vec<U256> = self
+            .expired
+            .keys()
+            // Entries from before fill deadlines were recorded are kept
+            .filter(|id| self.fill_deadlines.get(id).is_some_and(|&d| d < cutoff))
+            .copied()
+            .collect();
+        for id in pruned {
+            self.pruned_expected_wei += self.expired.remove(&id).unwrap_or_default();
+            self.pruned_transferred_wei += self.transferred.remove(&id).unwrap_or_default();
+            self.fill_deadlines.remove(&id);
+            self.deferred.remove(&id);
+        }
+    }
+
+    /// Compare the refunds owed at L2 timestamp `now` with those paid, given
+    /// `claimable` still accrued in the SpokePool.
+    ///
+    /// A deposit that expired less than `grace_secs` ago is only owed once its refund
+    /// was seen: until then its refund bundle may simply not have been executed yet.
+    pub fn reconcile(&self, claimable: U256, now: u64, grace_secs: u64) -> RefundReconciliation {
+        let expected = self
+            .expired
+            .iter()
+            .filter(|(id, _)| {
+                let deadline = self.fill_deadlines.get(id).copied().unwrap_or_default();
+                deadline.saturating_add(grace_secs) <= now
+                    || self.transferred.contains_key(id)
+                    || self.deferred.contains(id)
+            })
+            .map(|(_, amount)| *amount)
+            .sum::<U256>();
+        RefundReconciliation {
+            expected: expected + self.pruned_expected_wei,
+            transferred: self.transferred.values().sum::<U256>() + self.pruned_transferred_wei,
+            claimable,
+            claimed: self.claimed_wei,
+            preexisting: self.preexisting_wei,
+        }
+    }
+}
+
+/// Refunds owed for our expired deposits against those paid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefundReconciliation {
+    /// Input amounts of our expired deposits past the grace period, or refunded
+    pub expected: U256,
+    /// Refunds transferred to us directly
+    pub transferred: U256,
+    /// Refunds accrued in `getRelayerRefund`, not claimed yet
+    pub claimable: U256,
+    /// Refunds claimed so far
+    pub claimed: U256,
+    /// Refunds that were already claimable when the ledger started
+    pub preexisting: U256,
+}
+
+impl RefundReconciliation {
+    /// Refunds paid one way or another, for deposits the ledger accounts for.
+    pub fn received(&self) -> U256 {
+        (self.transferred + self.claimable + self.claimed).saturating_sub(self.preexisting)
+    }
+
+    /// Refunds owed but not paid (positive), or paid beyond what is owed (negative).
+    ///
+    /// Deposits past the grace period count as owed until their refund bundle is
+    /// executed.
+    pub fn drift(&self) -> I256 {
+        I256::from_raw(self.expected) - I256::from_raw(self.received())
+    }
+
+    /// Whether the drift, either way, is over `tolerance`.
+    pub fn exceeds(&self, tolerance: U256) -> bool {
+        self.drift().unsigned_abs() > tolerance
+    }
+}
+
+/// Remembers which deposits have already been reported as expired or refunded.
+#[derive(Debug, Default)]
+pub struct DepositRefundTracker {
+    expired: HashSet<U256>,
+    refunded: HashSet<U256>,
+    ledger: RefundLedger,
+}
+
+impl DepositRefundTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add to `ledger`, e.g. the one persisted by an earlier run.
+    pub fn with_ledger(mut self, ledger: RefundLedger) -> Self {
+        self.ledger = ledger;
+        self
+    }
+
+    /// Refund history, including this process's observations.
+    pub const fn ledger(&self) -> &RefundLedger {
+        &self.ledger
+    }
+
+    /// Record this cycle's observations.
+    ///
+    /// Returns the deposits that expired and the refunds that landed since the last
+    /// call. Deposits already reported are filtered out.
+    pub fn observe<'a>(
+        &mut self,
+        expired: &'a [InFlightDeposit],
+        refunded: &[RefundedDeposit],
+    ) -> (Vec<&'a InFlightDeposit>, Vec<RefundedDeposit>) {
+        let newly_expired = expired
+            .iter()
+            .filter(|d| self.expired.insert(d.deposit_id))
+            .collect();
+
+        let newly_refunded = refunded
+            .iter()
+            .filter(|r| self.refunded.insert(r.deposit_id))
+            .cloned()
+            .collect();
+
+        (newly_expired, newly_refunded)
+    }
+}
+
+/// Detect expired deposits and match them with refunds paid on L1.
+///
+/// Logs "deposit X expired" once per deposit and "deposit X expired and was refunded
+/// in tx Y" once the refund is seen, incrementing the matching counters. Scan retries
+/// are drawn from `retry_budget` and blocks covered added to `scan_coverage`; unfilled
+/// deposits resume from `deposit_checkpoint`.
+///
+/// The refunds are then reconciled with `tracker`'s ledger; see [`reconcile_refunds`].
+#[allow(clippy::too_many_arguments)]
+pub async fn track_deposit_refunds<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    config: &Config,
+    metrics: &Metrics,
+    tracker: &mut DepositRefundTracker,
+    retry_budget: &RetryBudget,
+    scan_coverage: &ScanCoverage,
+    deposit_checkpoint: &mut DepositCheckpoint,
+) -> eyre::Result<()>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let network = config.network_config();
+
+    let state_provider = DepositStateProvider::new(
+        l1_provider.clone(),
+        l2_provider.clone(),
+        network.ethereum.spoke_pool,
+        network.unichain.spoke_pool,
+    )
+    .with_retry_budget(retry_budget.clone())
+    .with_scan_coverage(scan_coverage.clone());
+
+    let unfilled = refresh_inflight_deposits(
+        l1_provider.clone(),
+        l2_provider.clone(),
+        config,
+        retry_budget,
+        scan_coverage,
+        deposit_checkpoint,
+    )
+    .await?;
+
+    // Fill deadlines are enforced against the destination chain's clock
+    let now = l2_provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Failed to get latest L2 block"))?
+        .header
+        .timestamp;
+
+    // Only our own deposits are refunded to us
+    let expired: Vec<InFlightDeposit> = unfilled
+        .into_iter()
+        .filter(|d| d.depositor == config.eoa_address && d.is_expired(now))
+        .collect();
+
+    if expired.is_empty() {
+        debug!("No expired deposits");
+    } else {
+        report_expired(
+            &state_provider,
+            &l1_provider,
+            config,
+            metrics,
+            tracker,
+            &expired,
+        )
+        .await?;
+    }
+
+    reconcile_refunds(&state_provider, &l1_provider, config, metrics, tracker, now).await?;
+
+    Ok(())
+}
+
+/// Match `expired` deposits with refunds paid on L1 and report the new ones.
+async fn report_expired<P1, P2>(
+    state_provider: &DepositStateProvider<P1, P2>,
+    l1_provider: &P1,
+    config: &Config,
+    metrics: &Metrics,
+    tracker: &mut DepositRefundTracker,
+    expired: &[InFlightDeposit],
+) -> eyre::Result<()>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let network = config.network_config();
+
+    let from_block = expired
+        .iter()
+        .map(|d| d.block_number)
+        .min()
+        .unwrap_or_default();
+    let to_block = l1_provider.get_block_number().await?;
+    let refunds = state_provider
+        .scan_l1_refunds(
+            config.eoa_address,
+            network.ethereum.weth,
+            network.ethereum.chain_id,
+            from_block,
+            to_block,
+        )
+        .await?;
+
+    let refunded = match_refunds(expired, &refunds);
+    tracker.ledger.record_expired(expired);
+    tracker.ledger.record_refunds(&refunded);
+    let (newly_expired, newly_refunded) = tracker.observe(expired, &refunded);
+
+    for deposit in &newly_expired {
+        warn!(
+            deposit_id = %deposit.deposit_id,
+            amount = %format_ether(deposit.input_amount),
+            fill_deadline = deposit.fill_deadline,
+            "Deposit expired unfilled, awaiting refund"
+        );
+    }
+
+    for refund in &newly_refunded {
+        info!(
+            deposit_id = %refund.deposit_id,
+            refund_tx = %refund.refund.tx_hash,
+            amount = %format_ether(refund.refund.amount),
+            deferred = refund.refund.deferred,
+            "Deposit {} expired and was refunded in tx {}",
+            refund.deposit_id,
+            refund.refund.tx_hash
+        );
+    }
+
+    metrics.record_deposits_expired(newly_expired.len());
+    metrics.record_deposits_refunded(newly_refunded.len());
+
+    Ok(())
+}
+
+/// Reconcile the refunds owed for our expired deposits with those paid, at L2
+/// timestamp `now`.
+///
+/// Claims are scanned from where the ledger's last scan stopped. A new ledger starts
+/// from the current block and claimable balance instead, as earlier claims and refunds
+/// are for deposits it never saw. Deposits that expired less than `refund_grace_secs`
+/// ago aren't owed yet. The drift is exported each cycle and alerted on when it is
+/// over `refund_drift_tolerance_wei`.
+pub async fn reconcile_refunds<P1, P2>(
+    state_provider: &DepositStateProvider<P1, P2>,
+    l1_provider: &P1,
+    config: &Config,
+    metrics: &Metrics,
+    tracker: &mut DepositRefundTracker,
+    now: u64,
+) -> eyre::Result<RefundReconciliation>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let network = config.network_config();
+    let to_block = l1_provider.get_block_number().await?;
+    let spoke_pool = ISpokePool::new(network.ethereum.spoke_pool, l1_provider);
+    let claimable = || async {
+        spoke_pool
+            .getRelayerRefund(network.ethereum.weth, config.eoa_address)
+            .call()
+            .await
+    };
+
+    match tracker.ledger.claims_scanned_to() {
+        None => {
+            let claimable = claimable().await?;
+            info!(
+                l1_block = to_block,
+                claimable = %format_ether(claimable),
+                "Starting relayer refund ledger"
+            );
+            tracker.ledger.start(now, to_block, claimable);
+        }
+        Some(scanned_to) if scanned_to < to_block => {
+            let claims = state_provider
+                .scan_l1_claimed_refunds(
+                    config.eoa_address,
+                    network.ethereum.weth,
+                    scanned_to + 1,
+                    to_block,
+                )
+                .await?;
+            tracker.ledger.record_claims(&claims, to_block);
+        }
+        Some(_) => {}
+    }
+    tracker.ledger.prune(
+        now,
+        config
+            .deposit_lookback_secs
+            .saturating_add(config.refund_grace_secs),
+    );
+
+    let claimable = claimable().await?;
+    let reconciliation = tracker
+        .ledger
+        .reconcile(claimable, now, config.refund_grace_secs);
+    let drift = reconciliation.drift();
+
+    if reconciliation.exceeds(config.refund_drift_tolerance_wei) {
+        error!(
+            expected = %format_ether(reconciliation.expected),
+            received = %format_ether(reconciliation.received()),
+            claimable = %format_ether(reconciliation.claimable),
+            drift = %format_ether(drift),
+            tolerance = %format_ether(config.refund_drift_tolerance_wei),
+            "ALERT: relayer refunds drifted from our expired deposits"
+        );
+    } else {
+        debug!(
+            expected = %format_ether(reconciliation.expected),
+            received = %format_ether(reconciliation.received()),
+            drift = %format_ether(drift),
+            "Relayer refunds reconciled"
+        );
+    }
+    metrics.set_refund_drift_eth(format_ether(drift).parse().unwrap_or(0.0));
+
+    Ok(reconciliation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+    use alloy_rpc_types_eth::Log;
+    use alloy_sol_types::SolEvent;
+    use deposit::RefundEntry;
+    use test_support::FakeChain;
+
+    fn deposit(id: u64) -> InFlightDeposit {
+        InFlightDeposit {
+            deposit_id: U256::from(id),
+            origin_chain_id: 1,
+            destination_chain_id: 130,
+            input_amount: U256::from(100),
+            output_amount: U256::from(99),
+            depositor: Address::repeat_byte(1),
+            block_number: 10,
+            fill_deadline: 1_000,
+        }
+    }
+
+    fn refunded(id: u64) -> RefundedDeposit {
+        RefundedDeposit {
+            deposit_id: U256::from(id),
+            refund: RefundEntry {
+                tx_hash: B256::repeat_byte(0xaa),
+                block_number: 20,
+                amount: U256::from(100),
+                deferred: false,
+            },
+        }
+    }
+
+    /// Well past every test deposit's fill deadline and grace period
+    const NOW: u64 = 100_000;
+
+    fn claim(amount: u64, block_number: u64) -> ClaimedRefund {
+        ClaimedRefund {
+            tx_hash: B256::repeat_byte(0xcc),
+            block_number,
+            amount: U256::from(amount),
+        }
+    }
+
+    #[test]
+    fn test_ledger_reconciles_transferred_and_deferred_refunds() {
+        let mut ledger = RefundLedger::default();
+
+        // Deposits 1 and 2 expire; nothing paid yet
+        ledger.record_expired(&[deposit(1), deposit(2)]);
+        let reconciliation = ledger.reconcile(U256::ZERO, NOW, 0);
+        assert_eq!(reconciliation.expected, U256::from(200));
+        assert_eq!(reconciliation.drift(), I256::try_from(200).unwrap());
+
+        // Deposit 1 refunded by transfer, deposit 2's refund deferred into the pool
+        let mut deferred = refunded(2);
+        deferred.refund.deferred = true;
+        ledger.record_refunds(&[refunded(1), deferred]);
+        let reconciliation = ledger.reconcile(U256::from(100), NOW, 0);
+        assert_eq!(reconciliation.transferred, U256::from(100));
+        assert_eq!(reconciliation.drift(), I256::ZERO);
+
+        // Deposit 2's refund claimed: no longer claimable, counted as claimed
+        ledger.record_claims(&[claim(100, 30)], 40);
+        let reconciliation = ledger.reconcile(U256::ZERO, NOW, 0);
+        assert_eq!(reconciliation.claimed, U256::from(100));
+        assert_eq!(reconciliation.received(), U256::from(200));
+        assert_eq!(reconciliation.drift(), I256::ZERO);
+        assert_eq!(ledger.claims_scanned_to(), Some(40));
+    }
+
+    #[test]
+    fn test_ledger_counts_repeat_observations_once() {
+        let mut ledger = RefundLedger::default();
+        for _ in 0..3 {
+            ledger.record_expired(&[deposit(1)]);
+            ledger.record_refunds(&[refunded(1)]);
+        }
+        let reconciliation = ledger.reconcile(U256::ZERO, NOW, 0);
+        assert_eq!(reconciliation.expected, U256::from(100));
+        assert_eq!(reconciliation.transferred, U256::from(100));
+    }
+
+    #[test]
+    fn test_drift_tolerance_applies_both_ways() {
+        let mut ledger = RefundLedger::default();
+        ledger.record_expired(&[deposit(1)]);
+        let tolerance = U256::from(10);
+
+        // Refund not paid yet
+        assert!(ledger.reconcile(U256::ZERO, NOW, 0).exceeds(tolerance));
+        // Paid within the tolerance
+        assert!(!ledger.reconcile(U256::from(95), NOW, 0).exceeds(tolerance));
+        // Claimable beyond what our deposits explain
+        let over = ledger.reconcile(U256::from(150), NOW, 0);
+        assert_eq!(over.drift(), I256::try_from(-50).unwrap());
+        assert!(over.exceeds(tolerance));
+    }
+
+    #[test]
+    fn test_grace_period_defers_expected_refunds() {
+        let mut ledger = RefundLedger::default();
+        ledger.record_expired(&[deposit(1), deposit(2)]);
+
+        // Expired at 1_000: neither refund is owed within the grace period
+        let reconciliation = ledger.reconcile(U256::ZERO, 1_500, 600);
+        assert_eq!(reconciliation.expected, U256::ZERO);
+
+        // Unless it was already seen
+        let mut deferred = refunded(2);
+        deferred.refund.deferred = true;
+        ledger.record_refunds(&[deferred]);
+        let reconciliation = ledger.reconcile(U256::from(100), 1_500, 600);
+        assert_eq!(reconciliation.expected, U256::from(100));
+        assert_eq!(reconciliation.drift(), I256::ZERO);
+
+        // Past the grace period, the missing refund is drift
+        let reconciliation = ledger.reconcile(U256::from(100), 1_600, 600);
+        assert_eq!(reconciliation.drift(), I256::try_from(100).unwrap());
+    }
+
+    #[test]
+    fn test_ledger_ignores_what_predates_its_start() {
+        let mut ledger = RefundLedger::default();
+        ledger.record_expired(&[deposit(1)]);
+
+        // 50 already claimable for deposits the ledger never saw
+        ledger.start(2_000, 40, U256::from(50));
+        assert_eq!(ledger.claims_scanned_to(), Some(40));
+        assert_eq!(ledger.reconcile(U256::from(50), NOW, 0).drift(), I256::ZERO);
+
+        // Deposit 1 expired before the start and is not counted again
+        ledger.record_expired(&[deposit(1)]);
+        let late = InFlightDeposit {
+            fill_deadline: 3_000,
+            ..deposit(2)
+        };
+        ledger.record_expired(&[late]);
+        let reconciliation = ledger.reconcile(U256::from(150), NOW, 0);
+        assert_eq!(reconciliation.expected, U256::from(100));
+        assert_eq!(reconciliation.received(), U256::from(100));
+        assert_eq!(reconciliation.drift(), I256::ZERO);
+    }
+
+    #[test]
+    fn test_prune_keeps_totals() {
+        let mut ledger = RefundLedger::default();
+        let late = InFlightDeposit {
+            fill_deadline: 50_000,
+            ..deposit(2)
+        };
+        ledger.record_expired(&[deposit(1), late]);
+        ledger.record_refunds(&[refunded(1)]);
+        let before = ledger.reconcile(U256::ZERO, NOW, 0);
+
+        ledger.prune(NOW, 60_000);
+        assert_eq!(ledger.expired.len(), 1);
+        assert!(ledger.transferred.is_empty());
+        assert_eq!(ledger.reconcile(U256::ZERO, NOW, 0), before);
+
+        // Seeing the pruned deposit again would count it twice, which the retention
+        // rules out; pruning again is a no-op
+        ledger.prune(NOW, 60_000);
+        assert_eq!(ledger.reconcile(U256::ZERO, NOW, 0), before);
+    }
+
+    #[test]
+    fn test_ledger_round_trips_through_json() {
+        let mut ledger = RefundLedger::default();
+        ledger.record_expired(&[deposit(1), deposit(7)]);
+        ledger.record_refunds(&[refunded(7)]);
+        ledger.record_claims(&[claim(5, 10)], 12);
+
+        let json = serde_json::to_string(&ledger).unwrap();
+        assert_eq!(serde_json::from_str::<RefundLedger>(&json).unwrap(), ledger);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_scans_claims_incrementally() {
+        let config = Config {
+            eoa_address: Address::repeat_byte(1),
+            refund_grace_secs: 100,
+            ..Default::default()
+        };
+        let network = config.network_config();
+        let l1 = FakeChain::new();
+        for timestamp in 1..=10 {
+            l1.mine(timestamp);
+        }
+        let claim_log = |amount: u64, block_number: u64| Log {
+            inner: alloy_primitives::Log {
+                address: network.ethereum.spoke_pool,
+                data: ISpokePool::ClaimedRelayerRefund {
+                    token: network.ethereum.weth,
+                    relayer: config.eoa_address,
+                    amount: U256::from(amount),
+                }
+                .encode_log_data(),
+            },
+            block_number: Some(block_number),
+            transaction_hash: Some(B256::repeat_byte(block_number as u8)),
+            ..Default::default()
+        };
+        let state_provider = DepositStateProvider::new(
+            l1.provider(),
+            l1.provider(),
+            network.ethereum.spoke_pool,
+            network.unichain.spoke_pool,
+        );
+        let mut tracker = DepositRefundTracker::new();
+        let reconcile = async |tracker: &mut DepositRefundTracker, now| {
+            reconcile_refunds(
+                &state_provider,
+                &l1.provider(),
+                &config,
+                &Metrics::new(),
+                tracker,
+                now,
+            )
+            .await
+            .unwrap()
+        };
+
+        // The first cycle starts the ledger: the claim and the claimable refund before
+        // it are for deposits it never saw
+        l1.add_log(claim_log(60, 5));
+        l1.set_call::<ISpokePool::getRelayerRefundCall>(
+            network.ethereum.spoke_pool,
+            &U256::from(25),
+        );
+        let reconciliation = reconcile(&mut tracker, 500).await;
+        assert_eq!(reconciliation.claimed, U256::ZERO);
+        assert_eq!(reconciliation.drift(), I256::ZERO);
+        assert_eq!(tracker.ledger().claims_scanned_to(), Some(10));
+
+        // Deposit 1 expires at 1_000, its refund is not owed yet
+        tracker.ledger.record_expired(&[deposit(1)]);
+        let reconciliation = reconcile(&mut tracker, 1_050).await;
+        assert_eq!(reconciliation.drift(), I256::ZERO);
+
+        // The next cycles only scan the new blocks
+        l1.mine(11);
+        l1.add_log(claim_log(60, 11));
+        let reconciliation = reconcile(&mut tracker, 1_200).await;
+        assert_eq!(reconciliation.claimed, U256::from(60));
+        assert_eq!(reconciliation.drift(), I256::try_from(40).unwrap());
+
+        l1.mine(12);
+        l1.add_log(claim_log(40, 12));
+        let reconciliation = reconcile(&mut tracker, 1_300).await;
+        assert_eq!(reconciliation.claimed, U256::from(100));
+        assert_eq!(reconciliation.drift(), I256::ZERO);
+        assert_eq!(tracker.ledger().claims_scanned_to(), Some(12));
+    }
+
+    #[test]
+    fn test_tracker_reports_each_transition_once() {
+        let mut tracker = DepositRefundTracker::new();
+        let expired = [deposit(1), deposit(2)];
+
+        // Cycle 1: both expire, no refunds yet
+        let (new_expired, new_refunded) = tracker.observe(&expired, &[]);
+        assert_eq!(new_expired.len(), 2);
+        assert!(new_refunded.is_empty());
+
+        // Cycle 2: deposit 1 refunded
+        let (new_expired, new_refunded) = tracker.observe(&expired, &[refunded(1)]);
+        assert!(new_expired.is_empty());
+        assert_eq!(new_refunded, vec![refunded(1)]);
+
+        // Cycle 3: same observations, nothing new
+        let (new_expired, new_refunded) = tracker.observe(&expired, &[refunded(1)]);
+        assert!(new_expired.is_empty());
+        assert!(new_refunded.is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+//! Tolerance for L1 reorgs undoing a proof.
+//!
+//! A proven withdrawal reads as unproven again when the L1 block carrying its prove
+//! transaction is reorged out. That is usually transient: the transaction is mined again
+//! within a few blocks. [`RegressionGuard`] remembers which withdrawals were proven and
+//! holds back any that regress to initiated for `reorg_tolerance_secs`, so they aren't
+//! proven a second time in the meantime.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+use withdrawal::{
+    state::PendingWithdrawal,
+    types::{WithdrawalHash, WithdrawalStatus},
+};
+
+/// Withdrawals seen proven and those that regressed since, shared by every clone.
+///
+/// The default has no tolerance: regressions are warned about but acted on right away.
+#[derive(Debug, Clone, Default)]
+pub struct RegressionGuard {
+    tolerance: Duration,
+    state: Arc<Mutex<GuardState>>,
+}
+
+#[derive(Debug, Default)]
+struct GuardState {
+    /// Withdrawals proven as of the latest pass
+    proven: HashSet<WithdrawalHash>,
+    /// Withdrawals that regressed from proven to initiated, and when that was first seen
+    regressed: HashMap<WithdrawalHash, Instant>,
+}
+
+impl RegressionGuard {
+    pub fn new(tolerance: Duration) -> Self {
+        Self {
+            tolerance,
+            state: Arc::default(),
+        }
+    }
+
+    /// Record the statuses of `pending`, read at `now`, and drop the withdrawals whose
+    /// regression from proven to initiated is more recent than the tolerance.
+    ///
+    /// A regression outlasting the tolerance is taken as real: the withdrawal is kept, to
+    /// be proven again, and forgotten. Withdrawals missing from `pending` are forgotten.
+    pub fn filter(&self, pending: Vec<PendingWithdrawal>, now: Instant) -> Vec<PendingWithdrawal> {
+        let mut state = self.state.lock().expect("regression guard lock poisoned");
+        let seen: HashSet<_> = pending.iter().map(|w| w.hash).collect();
+        state.proven.retain(|hash| seen.contains(hash));
+        state.regressed.retain(|hash, _| seen.contains(hash));
+
+        let mut kept = Vec::with_capacity(pending.len());
+        for withdrawal in pending {
+            let hash = withdrawal.hash;
+            match withdrawal.status {
+                WithdrawalStatus::Proven { .. } => {
+                    if state.regressed.remove(&hash).is_some() {
+                        debug!(withdrawal_hash = %hash, "Withdrawal reads as proven again");
+                    }
+                    state.proven.insert(hash);
+                }
+                WithdrawalStatus::Initiated => {
+                    let since = if state.proven.remove(&hash) {
+                        warn!(
+                            withdrawal_hash = %hash,
+                            tolerance_secs = self.tolerance.as_secs(),
+                            "Proven withdrawal reads as unproven, possibly an L1 reorg; holding off re-proving"
+                        );
+                        *state.regressed.entry(hash).or_insert(now)
+                    } else if let Some(since) = state.regressed.get(&hash) {
+                        *since
+                    } else {
+                        kept.push(withdrawal);
+                        continue;
+                    };
+
+                    if now.saturating_duration_since(since) < self.tolerance {
+                        continue;
+                    }
+                    if !self.tolerance.is_zero() {
+                        warn!(
+                            withdrawal_hash = %hash,
+                            tolerance_secs = self.tolerance.as_secs(),
+                            "Withdrawal still unproven after the reorg tolerance, proving it again"
+                        );
+                    }
+                    state.regressed.remove(&hash);
+                }
+                WithdrawalStatus::Finalized => {
+                    state.proven.remove(&hash);
+                    state.regressed.remove(&hash);
+                }
+            }
+            kept.push(withdrawal);
+        }
+        kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, Bytes, U256};
+    use binding::opstack::WithdrawalTransaction;
+
+    fn withdrawal(byte: u8, status: WithdrawalStatus) -> PendingWithdrawal {
+        PendingWithdrawal {
+            transaction: WithdrawalTransaction {
+                nonce: U256::from(byte),
+                sender: Address::repeat_byte(1),
+                target: Address::repeat_byte(1),
+                value: U256::from(1_000),
+                gasLimit: U256::from(100_000),
+                data: Bytes::new(),
+            },
+            hash: WithdrawalHash::repeat_byte(byte),
+            l2_block: u64::from(byte),
+            status,
+        }
+    }
+
+    fn proven(byte: u8) -> PendingWithdrawal {
+        withdrawal(
+            byte,
+            WithdrawalStatus::Proven {
+                timestamp: 1,
+                dispute_game_proxy: Address::repeat_byte(0x22),
+                proof_submitter: Address::repeat_byte(0x33),
+            },
+        )
+    }
+
+    fn initiated(byte: u8) -> PendingWithdrawal {
+        withdrawal(byte, WithdrawalStatus::Initiated)
+    }
+
+    fn hashes(pending: &[PendingWithdrawal]) -> Vec<u8> {
+        pending.iter().map(|w| w.hash[0]).collect()
+    }
+
+    #[test]
+    fn test_holds_regression_for_tolerance() {
+        let guard = RegressionGuard::new(Duration::from_secs(600));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let kept = guard.filter(vec![proven(1), initiated(2)], start);
+        assert_eq!(hashes(&kept), [1, 2]);
+
+        // 1 regressed: held, unlike 2 which was never proven
+        let kept = guard.filter(vec![initiated(1), initiated(2)], at(30));
+        assert_eq!(hashes(&kept), [2]);
+        let kept = guard.filter(vec![initiated(1), initiated(2)], at(629));
+        assert_eq!(hashes(&kept), [2]);
+
+        // Tolerance counts from when the regression was first seen
+        let kept = guard.filter(vec![initiated(1), initiated(2)], at(630));
+        assert_eq!(hashes(&kept), [1, 2]);
+        // ... after which it is an ordinary initiated withdrawal
+        let kept = guard.filter(vec![initiated(1)], at(631));
+        assert_eq!(hashes(&kept), [1]);
+    }
+
+    #[test]
+    fn test_reproven_clears_regression() {
+        let guard = RegressionGuard::new(Duration::from_secs(600));
+        let start = Instant::now();
+
+        guard.filter(vec![proven(1)], start);
+        assert!(guard.filter(vec![initiated(1)], start).is_empty());
+        // The prove transaction was mined again
+        assert_eq!(hashes(&guard.filter(vec![proven(1)], start)), [1]);
+
+        // A later regression gets the full tolerance again
+        let later = start + Duration::from_secs(1_000);
+        assert!(guard.filter(vec![initiated(1)], later).is_empty());
+    }
+
+    #[test]
+    fn test_no_tolerance_acts_right_away() {
+        let guard = RegressionGuard::default();
+        let now = Instant::now();
+
+        guard.filter(vec![proven(1)], now);
+        assert_eq!(hashes(&guard.filter(vec![initiated(1)], now)), [1]);
+    }
+
+    #[test]
+    fn test_forgets_missing_withdrawals() {
+        let guard = RegressionGuard::new(Duration::from_secs(600));
+        let now = Instant::now();
+
+        guard.filter(vec![proven(1)], now);
+        guard.filter(vec![], now);
+        assert_eq!(hashes(&guard.filter(vec![initiated(1)], now)), [1]);
+    }
+}
@@ -0,0 +1,152 @@
+//! Replay of a recorded transaction request via `eth_call`.
+//!
+//! Turns a reverted action into a reproducible investigation: the saved request is
+//! simulated against a chosen block and the revert reason is decoded.
+
+use alloy_primitives::{hex, Bytes};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{BlockId, TransactionRequest};
+use alloy_sol_types::decode_revert_reason;
+use std::path::Path;
+
+/// Result of replaying a transaction request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayOutcome {
+    /// The call succeeded and returned this data
+    Success(Bytes),
+    /// The call reverted
+    Reverted {
+        /// Decoded reason, if the revert data is `Error(string)` or `Panic(uint256)`
+        reason: Option<String>,
+        /// Raw revert data (empty if the node didn't return any)
+        data: Bytes,
+        /// Error message returned by the node
+        message: String,
+    },
+}
+
+impl ReplayOutcome {
+    /// Human-readable summary of the outcome.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Success(output) => format!("call succeeded, returned 0x{}", hex::encode(output)),
+            // Already prefixed with "revert: " / "panic: "
+            Self::Reverted {
+                reason: Some(reason),
+                ..
+            } => reason.clone(),
+            Self::Reverted { data, message, .. } if !data.is_empty() => {
+                format!(
+                    "reverted ({message}) with undecoded data 0x{}",
+                    hex::encode(data)
+                )
+            }
+            Self::Reverted { message, .. } => format!("reverted: {message}"),
+        }
+    }
+}
+
+/// Load a JSON-encoded `TransactionRequest` from disk.
+pub fn load_transaction_request(path: impl AsRef<Path>) -> eyre::Result<TransactionRequest> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Simulate `tx` with `eth_call` at `block` and decode any revert.
+///
+/// Errors that are not reverts (transport failures, unknown block, ...) are returned
+/// as errors.
+pub async fn replay_transaction<P: Provider>(
+    provider: &P,
+    tx: TransactionRequest,
+    block: BlockId,
+) -> eyre::Result<ReplayOutcome> {
+    match provider.call(tx).block(block).await {
+        Ok(output) => Ok(ReplayOutcome::Success(output)),
+        Err(e) => {
+            let Some(resp) = e.as_error_resp() else {
+                return Err(e.into());
+            };
+            let data = resp.as_revert_data().unwrap_or_default();
+            if data.is_empty() && !resp.message.contains("revert") {
+                return Err(e.into());
+            }
+
+            Ok(ReplayOutcome::Reverted {
+                reason: decode_revert_reason(&data),
+                message: resp.message.to_string(),
+                data,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_provider::ProviderBuilder;
+    use alloy_sol_types::{Revert, SolError};
+    use alloy_transport::mock::Asserter;
+
+    fn revert_payload(data: &[u8]) -> alloy_json_rpc::ErrorPayload {
+        alloy_json_rpc::ErrorPayload {
+            code: 3,
+            message: "execution reverted".into(),
+            data: Some(
+                serde_json::value::to_raw_value(&format!("0x{}", hex::encode(data))).unwrap(),
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_decodes_revert_reason() {
+        let asserter = Asserter::new();
+        let data = Revert::from("OptimismPortal: paused").abi_encode();
+        asserter.push_failure(revert_payload(&data));
+
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let outcome = replay_transaction(
+            &provider,
+            TransactionRequest::default(),
+            BlockId::number(100),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            outcome,
+            ReplayOutcome::Reverted {
+                reason: Some("revert: OptimismPortal: paused".to_string()),
+                data: data.into(),
+                message: "execution reverted".to_string(),
+            }
+        );
+        assert!(outcome.describe().contains("OptimismPortal: paused"));
+    }
+
+    #[tokio::test]
+    async fn test_replay_success() {
+        let asserter = Asserter::new();
+        asserter.push_success(&"0x01");
+
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let outcome =
+            replay_transaction(&provider, TransactionRequest::default(), BlockId::latest())
+                .await
+                .unwrap();
+
+        assert_eq!(outcome, ReplayOutcome::Success(Bytes::from(vec![1])));
+    }
+
+    #[tokio::test]
+    async fn test_replay_propagates_non_revert_errors() {
+        let asserter = Asserter::new();
+        asserter.push_failure_msg("header not found");
+
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+        let result =
+            replay_transaction(&provider, TransactionRequest::default(), BlockId::number(1)).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,688 @@
+//! Gas cost of finalized withdrawals relative to the value they moved.
+//!
+//! For every withdrawal of ours that was finalized, the initiate (L2), prove and
+//! finalize (L1) transactions are located from the chain events and their receipts
+//! priced. Legs sent by another account are recorded as unknown rather than free, so a
+//! withdrawal only gets a cost ratio when all three legs were paid by us.
+
+use crate::{
+    config::Config,
+    store::{InitiationRecord, LegCost, StateStore, WithdrawalCostRecord},
+};
+use alloy_primitives::{
+    utils::{format_ether, format_units},
+    Address, TxHash, U256,
+};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{BlockNumberOrTag, Filter};
+use alloy_sol_types::SolEvent;
+use binding::opstack::IL2ToL1MessagePasser::MessagePassed;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::{debug, info};
+use withdrawal::{
+    state::WithdrawalStateProvider,
+    types::{L1WithdrawalEventKind, WithdrawalHash},
+};
+
+/// The receipt fields needed to price a transaction.
+///
+/// Fetched as raw JSON so the OP Stack `l1Fee` field is kept for L2 receipts.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeeReceipt {
+    from: Address,
+    gas_used: U256,
+    effective_gas_price: U256,
+    /// L1 data fee charged on OP Stack chains, absent on L1
+    #[serde(default)]
+    l1_fee: Option<U256>,
+}
+
+impl FeeReceipt {
+    fn fee_wei(&self) -> U256 {
+        self.gas_used * self.effective_gas_price + self.l1_fee.unwrap_or_default()
+    }
+}
+
+/// Price the first of `tx_hashes` that was sent by `sender`.
+///
+/// If none of them was (or there are none), the leg is unknown; the first transaction
+/// hash is still kept for reference.
+pub async fn leg_cost<P: Provider>(
+    provider: &P,
+    tx_hashes: &[TxHash],
+    sender: Address,
+) -> eyre::Result<LegCost> {
+    for tx_hash in tx_hashes {
+        let receipt: Option<FeeReceipt> = provider
+            .raw_request("eth_getTransactionReceipt".into(), (tx_hash,))
+            .await?;
+
+        match receipt {
+            Some(receipt) if receipt.from == sender => {
+                return Ok(LegCost {
+                    tx_hash: Some(*tx_hash),
+                    cost_wei: Some(receipt.fee_wei()),
+                });
+            }
+            Some(receipt) => {
+                debug!(tx_hash = %tx_hash, from = %receipt.from, "Leg sent by a third party");
+            }
+            None => debug!(tx_hash = %tx_hash, "Receipt not found"),
+        }
+    }
+
+    Ok(LegCost {
+        tx_hash: tx_hashes.first().copied(),
+        cost_wei: None,
+    })
+}
+
+/// Initiation of the withdrawal `hash`: from the store, or else priced from its L2 receipt.
+///
+/// The initiating transaction is `tx_hash` when known, otherwise it is located by the
+/// withdrawal's `MessagePassed` event in the L2 block the store has for it. A looked-up
+/// initiation is set on the store's record of the withdrawal, so once the caller saves
+/// the store, withdrawals initiated outside this instance are only looked up once.
+/// `None` if the transaction isn't found.
+pub async fn initiation_record<P: Provider>(
+    l2_provider: &P,
+    message_passer: Address,
+    store: &mut StateStore,
+    hash: WithdrawalHash,
+    tx_hash: Option<TxHash>,
+) -> eyre::Result<Option<InitiationRecord>> {
+    let record = store.withdrawal(&hash);
+    if let Some(initiation) = record.and_then(|record| record.initiation) {
+        return Ok(Some(initiation));
+    }
+
+    let tx_hash = match (tx_hash, record) {
+        (Some(tx_hash), _) => Some(tx_hash),
+        (None, Some(record)) => {
+            initiation_tx_hash(
+                l2_provider,
+                message_passer,
+                record.sender,
+                record.l2_block,
+                hash,
+            )
+            .await?
+        }
+        (None, None) => None,
+    };
+    let Some(tx_hash) = tx_hash else {
+        debug!(withdrawal_hash = %hash, "Initiating transaction not found");
+        return Ok(None);
+    };
+
+    let receipt: Option<FeeReceipt> = l2_provider
+        .raw_request("eth_getTransactionReceipt".into(), (tx_hash,))
+        .await?;
+    let Some(receipt) = receipt else {
+        debug!(tx_hash = %tx_hash, "Receipt not found");
+        return Ok(None);
+    };
+
+    let initiation = InitiationRecord {
+        tx_hash,
+        gas_used: receipt.gas_used.saturating_to(),
+        effective_gas_price: receipt.effective_gas_price.saturating_to(),
+    };
+    store.set_initiation(&hash, initiation);
+    Ok(Some(initiation))
+}
+
+/// Transaction in `l2_block` whose `MessagePassed` event from `sender` is withdrawal `hash`.
+async fn initiation_tx_hash<P: Provider>(
+    l2_provider: &P,
+    message_passer: Address,
+    sender: Address,
+    l2_block: u64,
+    hash: WithdrawalHash,
+) -> eyre::Result<Option<TxHash>> {
+    let filter = Filter::new()
+        .address(message_passer)
+        .event_signature(MessagePassed::SIGNATURE_HASH)
+        .topic2(sender.into_word())
+        .from_block(l2_block)
+        .to_block(l2_block);
+
+    let logs = l2_provider.get_logs(&filter).await?;
+    Ok(logs
+        .iter()
+        .find(|log| {
+            MessagePassed::decode_log(&log.inner).is_ok_and(|event| event.withdrawalHash == hash)
+        })
+        .and_then(|log| log.transaction_hash))
+}
+
+/// Record the cost of withdrawals finalized in the last `since_secs` into `store`.
+///
+/// L1 and L2 are scanned over `since_secs + withdrawal_lookback_secs`, so that the
+/// initiation and proof of a withdrawal finalized at the start of the window are still
+/// found. Withdrawals that already have a cost record are skipped. Returns the newly
+/// recorded costs; the store is saved when there are any.
+pub async fn update_withdrawal_costs<P1, P2>(
+    l1_provider: P1,
+    l2_provider: P2,
+    config: &Config,
+    store: &mut StateStore,
+    since_secs: u64,
+) -> eyre::Result<Vec<WithdrawalCostRecord>>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    let network = config.network_config();
+    let window_secs = since_secs.saturating_add(config.withdrawal_lookback_secs);
+
+    let l1_current_block = l1_provider.get_block_number().await?;
+    let l2_current_block = l2_provider.get_block_number().await?;
+    let l1_from_block = l1_current_block.saturating_sub(network.ethereum.blocks_in(window_secs));
+    let l2_from_block = l2_current_block.saturating_sub(network.unichain.blocks_in(window_secs));
+
+    let state_provider = WithdrawalStateProvider::new(
+        l1_provider.clone(),
+        l2_provider.clone(),
+        network.unichain.l1_portal,
+        network.unichain.l2_to_l1_message_passer,
+    );
+
+    let events = state_provider
+        .scan_l1_withdrawal_events(
+            BlockNumberOrTag::Number(l1_from_block),
+            BlockNumberOrTag::Number(l1_current_block),
+            Some(config.eoa_address),
+        )
+        .await?;
+
+    let cutoff = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs()
+        .saturating_sub(since_secs);
+    let finalized: Vec<_> = events
+        .iter()
+        .filter(|e| matches!(e.kind, L1WithdrawalEventKind::Finalized { success: true }))
+        .filter(|e| e.timestamp >= cutoff && !store.has_withdrawal_cost(&e.hash))
+        .collect();
+
+    if finalized.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Finalized events are not filtered by sender; the L2 scan tells which are ours
+    let initiated: HashMap<WithdrawalHash, _> = state_provider
+        .get_initiated_withdrawals(l2_from_block, l2_current_block, config.eoa_address)
+        .await?
+        .into_iter()
+        .map(|w| (w.hash, w))
+        .collect();
+
+    let mut recorded = vec![];
+    for event in finalized {
+        let Some(initiation) = initiated.get(&event.hash) else {
+            continue;
+        };
+
+        let proofs: Vec<TxHash> = events
+            .iter()
+            .filter(|e| e.hash == event.hash && e.block_number <= event.block_number)
+            .filter(|e| matches!(e.kind, L1WithdrawalEventKind::Proven { .. }))
+            .map(|e| e.tx_hash)
+            .collect();
+
+        let record = WithdrawalCostRecord {
+            hash: event.hash,
+            value: initiation.transaction.value,
+            finalized_at: event.timestamp,
+            initiate: leg_cost(
+                &l2_provider,
+                initiation.tx_hash.as_slice(),
+                config.eoa_address,
+            )
+            .await?,
+            prove: leg_cost(&l1_provider, &proofs, config.eoa_address).await?,
+            finalize: leg_cost(&l1_provider, &[event.tx_hash], config.eoa_address).await?,
+            initiation: initiation_record(
+                &l2_provider,
+                network.unichain.l2_to_l1_message_passer,
+                store,
+                event.hash,
+                initiation.tx_hash,
+            )
+            .await?,
+        };
+
+        store.record_withdrawal_cost(record.clone());
+        recorded.push(record);
+    }
+
+    if !recorded.is_empty() {
+        info!(count = recorded.len(), "Recorded withdrawal costs");
+        store.save()?;
+    }
+
+    Ok(recorded)
+}
+
+/// Aggregate figures over a set of withdrawal cost records.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CostSummary {
+    /// Withdrawals in the report
+    pub withdrawals: usize,
+    /// Withdrawals whose three legs all have a known cost
+    pub fully_known: usize,
+    /// Value moved by all withdrawals
+    pub total_value_wei: U256,
+    /// Value moved by the fully known withdrawals
+    pub known_value_wei: U256,
+    /// Cost of the fully known withdrawals
+    pub known_cost_wei: U256,
+}
+
+impl CostSummary {
+    pub fn from_records(records: &[WithdrawalCostRecord]) -> Self {
+        let mut summary = Self {
+            withdrawals: records.len(),
+            ..Default::default()
+        };
+
+        for record in records {
+            summary.total_value_wei += record.value;
+            if let Some(cost) = record.total_cost_wei() {
+                summary.fully_known += 1;
+                summary.known_value_wei += record.value;
+                summary.known_cost_wei += cost;
+            }
+        }
+
+        summary
+    }
+
+    /// Cost as a fraction of value over the fully known withdrawals.
+    pub fn cost_ratio(&self) -> Option<f64> {
+        if self.known_value_wei.is_zero() {
+            return None;
+        }
+        Some(f64::from(self.known_cost_wei) / f64::from(self.known_value_wei))
+    }
+}
+
+const REPORT_HEADER: [&str; 10] = [
+    "withdrawal_hash",
+    "finalized_at",
+    "value_eth",
+    "initiate_eth",
+    "initiate_gas_used",
+    "initiate_gas_price_gwei",
+    "prove_eth",
+    "finalize_eth",
+    "total_eth",
+    "cost_ratio",
+];
+
+fn format_cost(cost: Option<U256>) -> String {
+    cost.map_or_else(|| "unknown".to_string(), format_ether)
+}
+
+fn format_gas_price(initiation: Option<InitiationRecord>) -> String {
+    initiation.map_or_else(
+        || "unknown".to_string(),
+        |initiation| format_units(initiation.effective_gas_price, "gwei").unwrap_or_default(),
+    )
+}
+
+fn format_ratio(ratio: Option<f64>) -> String {
+    ratio.map_or_else(|| "unknown".to_string(), |ratio| format!("{ratio:.6}"))
+}
+
+/// Report rows: one per withdrawal, then the aggregate.
+fn report_rows(records: &[WithdrawalCostRecord]) -> Vec<[String; 10]> {
+    let mut rows: Vec<[String; 10]> = records
+        .iter()
+        .map(|record| {
+            [
+                record.hash.to_string(),
+                record.finalized_at.to_string(),
+                format_ether(record.value),
+                format_cost(record.initiate.cost_wei),
+                record.initiation.map_or_else(
+                    || "unknown".to_string(),
+                    |initiation| initiation.gas_used.to_string(),
+                ),
+                format_gas_price(record.initiation),
+                format_cost(record.prove.cost_wei),
+                format_cost(record.finalize.cost_wei),
+                format_cost(record.total_cost_wei()),
+                format_ratio(record.cost_ratio()),
+            ]
+        })
+        .collect();
+
+    let summary = CostSummary::from_records(records);
+    rows.push([
+        format!(
+            "total ({}/{} fully known)",
+            summary.fully_known, summary.withdrawals
+        ),
+        String::new(),
+        format_ether(summary.total_value_wei),
+        String::new(),
+        String::new(),
+        String::new(),
+        String::new(),
+        String::new(),
+        format_ether(summary.known_cost_wei),
+        format_ratio(summary.cost_ratio()),
+    ]);
+
+    rows
+}
+
+/// Render the report as CSV, with the aggregate as the last row.
+pub fn render_csv(records: &[WithdrawalCostRecord]) -> String {
+    let mut out = REPORT_HEADER.join(",");
+    out.push('\n');
+    for row in report_rows(records) {
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Render the report as an aligned text table, with the aggregate as the last row.
+pub fn render_table(records: &[WithdrawalCostRecord]) -> String {
+    let rows = report_rows(records);
+    let widths: Vec<usize> = (0..REPORT_HEADER.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .chain([REPORT_HEADER[i].len()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let format_line = |cells: Vec<&str>| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        format!("{}\n", line.join("  ").trim_end())
+    };
+
+    let mut out = format_line(REPORT_HEADER.to_vec());
+    for row in &rows {
+        out.push_str(&format_line(row.iter().map(String::as_str).collect()));
+    }
+    out
+}
+
+/// Parse a duration like `30d`, `12h`, `45m`, `90s` or a plain number of seconds.
+pub fn parse_duration_secs(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let (digits, multiplier) = match input.char_indices().last() {
+        Some((i, 'w')) => (&input[..i], 7 * 86_400),
+        Some((i, 'd')) => (&input[..i], 86_400),
+        Some((i, 'h')) => (&input[..i], 3_600),
+        Some((i, 'm')) => (&input[..i], 60),
+        Some((i, 's')) => (&input[..i], 1),
+        _ => (input, 1),
+    };
+
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|value| value.checked_mul(multiplier))
+        .ok_or_else(|| format!("invalid duration `{input}`, expected e.g. 30d, 12h or 3600"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+    use alloy_provider::ProviderBuilder;
+    use alloy_transport::mock::Asserter;
+    use binding::opstack::WithdrawalTransaction;
+    use withdrawal::{
+        hash::compute_withdrawal_hash, state::PendingWithdrawal, types::WithdrawalStatus,
+    };
+
+    const EOA: Address = Address::repeat_byte(0x11);
+    const PASSER: Address = Address::repeat_byte(0x16);
+
+    fn receipt_json(from: Address, l1_fee: Option<&str>) -> serde_json::Value {
+        let mut receipt = serde_json::json!({
+            "from": from,
+            "gasUsed": "0x5208",            // 21000
+            "effectiveGasPrice": "0x3b9aca00", // 1 gwei
+        });
+        if let Some(l1_fee) = l1_fee {
+            receipt["l1Fee"] = l1_fee.into();
+        }
+        receipt
+    }
+
+    #[tokio::test]
+    async fn test_leg_cost_includes_l1_fee() {
+        let asserter = Asserter::new();
+        asserter.push_success(&receipt_json(EOA, Some("0x64")));
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let leg = leg_cost(&provider, &[B256::repeat_byte(1)], EOA)
+            .await
+            .unwrap();
+
+        assert_eq!(leg.tx_hash, Some(B256::repeat_byte(1)));
+        assert_eq!(
+            leg.cost_wei,
+            Some(U256::from(21_000u64 * 1_000_000_000 + 100))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_leg_cost_third_party_is_unknown() {
+        let asserter = Asserter::new();
+        asserter.push_success(&receipt_json(Address::repeat_byte(0x22), None));
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+
+        let leg = leg_cost(&provider, &[B256::repeat_byte(1)], EOA)
+            .await
+            .unwrap();
+        assert_eq!(leg.tx_hash, Some(B256::repeat_byte(1)));
+        assert_eq!(leg.cost_wei, None);
+
+        // First proof by someone else, second one ours
+        asserter.push_success(&receipt_json(Address::repeat_byte(0x22), None));
+        asserter.push_success(&receipt_json(EOA, None));
+        let leg = leg_cost(
+            &provider,
+            &[B256::repeat_byte(1), B256::repeat_byte(2)],
+            EOA,
+        )
+        .await
+        .unwrap();
+        assert_eq!(leg.tx_hash, Some(B256::repeat_byte(2)));
+        assert_eq!(leg.cost_wei, Some(U256::from(21_000u64 * 1_000_000_000)));
+    }
+
+    #[tokio::test]
+    async fn test_leg_cost_without_transaction_is_unknown() {
+        let provider = ProviderBuilder::new().connect_mocked_client(Asserter::new());
+        assert_eq!(
+            leg_cost(&provider, &[], EOA).await.unwrap(),
+            LegCost::default()
+        );
+    }
+
+    fn record(hash: u8, value: u64, prove: Option<u64>) -> WithdrawalCostRecord {
+        let leg = |cost: Option<u64>| LegCost {
+            tx_hash: Some(B256::repeat_byte(hash)),
+            cost_wei: cost.map(U256::from),
+        };
+        WithdrawalCostRecord {
+            hash: B256::repeat_byte(hash),
+            value: U256::from(value),
+            finalized_at: 1_700_000_000,
+            initiate: leg(Some(1_000)),
+            prove: leg(prove),
+            finalize: leg(Some(1_000)),
+            initiation: prove.map(|_| InitiationRecord {
+                tx_hash: B256::repeat_byte(hash),
+                gas_used: 21_000,
+                effective_gas_price: 1_500_000_000,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_summary_only_counts_fully_known() {
+        let records = [
+            record(1, 1_000_000, Some(2_000)),
+            record(2, 3_000_000, None),
+        ];
+        let summary = CostSummary::from_records(&records);
+
+        assert_eq!(summary.withdrawals, 2);
+        assert_eq!(summary.fully_known, 1);
+        assert_eq!(summary.total_value_wei, U256::from(4_000_000));
+        assert_eq!(summary.known_cost_wei, U256::from(4_000));
+        assert_eq!(summary.cost_ratio(), Some(0.004));
+        assert_eq!(CostSummary::default().cost_ratio(), None);
+    }
+
+    #[test]
+    fn test_render_csv_marks_unknown() {
+        let csv = render_csv(&[record(1, 1_000_000, None)]);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], REPORT_HEADER.join(","));
+        let fields: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(fields[4..6], ["unknown", "unknown"]);
+        assert_eq!(fields[6], "unknown");
+        assert_eq!(fields[8..], ["unknown", "unknown"]);
+        assert!(lines[2].starts_with("total (0/1 fully known),"));
+    }
+
+    #[test]
+    fn test_render_table_aligns_columns() {
+        let table = render_table(&[record(1, 1_000_000, Some(2_000))]);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        let column = lines[0].find("finalized_at").unwrap();
+        assert_eq!(lines[1].find("1700000000"), Some(column));
+        assert!(lines[1].contains(" 21000 "));
+        assert!(lines[1].contains(" 1.500000000 "));
+        assert!(lines[2].ends_with("0.004000"));
+    }
+
+    fn pending_withdrawal(sender: Address) -> PendingWithdrawal {
+        let transaction = WithdrawalTransaction {
+            nonce: U256::from(7),
+            sender,
+            target: sender,
+            value: U256::from(1_000_000),
+            gasLimit: U256::from(100_000),
+            data: Default::default(),
+        };
+        PendingWithdrawal {
+            hash: compute_withdrawal_hash(&transaction),
+            transaction,
+            l2_block: 500,
+            status: WithdrawalStatus::Initiated,
+        }
+    }
+
+    fn temp_store(name: &str) -> StateStore {
+        let path = std::env::temp_dir().join(format!(
+            "orchestrator-report-{}-{name}.json",
+            std::process::id()
+        ));
+        StateStore::load(path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_initiation_record_prefers_store() {
+        let pending = pending_withdrawal(EOA);
+        let recorded = InitiationRecord {
+            tx_hash: B256::repeat_byte(9),
+            gas_used: 80_000,
+            effective_gas_price: 1_000,
+        };
+        let mut store = temp_store("initiation-stored");
+        store.record_initiation(&pending, recorded);
+
+        // No responses queued: any RPC call would fail
+        let provider = ProviderBuilder::new().connect_mocked_client(Asserter::new());
+        let found = initiation_record(&provider, PASSER, &mut store, pending.hash, None)
+            .await
+            .unwrap();
+        assert_eq!(found, Some(recorded));
+    }
+
+    #[tokio::test]
+    async fn test_initiation_record_looks_up_scanned_withdrawal() {
+        let pending = pending_withdrawal(EOA);
+        let mut store = temp_store("initiation-lookup");
+        store.upsert_withdrawals(std::slice::from_ref(&pending));
+
+        let tx = &pending.transaction;
+        let event = MessagePassed {
+            nonce: tx.nonce,
+            sender: tx.sender,
+            target: tx.target,
+            value: tx.value,
+            gasLimit: tx.gasLimit,
+            data: tx.data.clone(),
+            withdrawalHash: pending.hash,
+        };
+        let log = alloy_rpc_types_eth::Log {
+            inner: alloy_primitives::Log {
+                address: PASSER,
+                data: event.encode_log_data(),
+            },
+            block_number: Some(pending.l2_block),
+            transaction_hash: Some(B256::repeat_byte(0xaa)),
+            ..Default::default()
+        };
+
+        let asserter = Asserter::new();
+        asserter.push_success(&vec![log]);
+        asserter.push_success(&receipt_json(EOA, Some("0x64")));
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+
+        let expected = InitiationRecord {
+            tx_hash: B256::repeat_byte(0xaa),
+            gas_used: 21_000,
+            effective_gas_price: 1_000_000_000,
+        };
+        let found = initiation_record(&provider, PASSER, &mut store, pending.hash, None)
+            .await
+            .unwrap();
+        assert_eq!(found, Some(expected));
+        assert!(asserter.read_q().is_empty());
+
+        // Saved on the record, so the next lookup doesn't hit the node
+        assert_eq!(
+            store.withdrawal(&pending.hash).unwrap().initiation,
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_parse_duration_secs() {
+        assert_eq!(parse_duration_secs("30d"), Ok(30 * 86_400));
+        assert_eq!(parse_duration_secs("2w"), Ok(14 * 86_400));
+        assert_eq!(parse_duration_secs("12h"), Ok(43_200));
+        assert_eq!(parse_duration_secs("45m"), Ok(2_700));
+        assert_eq!(parse_duration_secs("90s"), Ok(90));
+        assert_eq!(parse_duration_secs("3600"), Ok(3_600));
+        assert!(parse_duration_secs("d").is_err());
+        assert!(parse_duration_secs("1y").is_err());
+    }
+}
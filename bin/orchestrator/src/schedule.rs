@@ -0,0 +1,47 @@
+//! Scheduling of low-frequency tasks run from the main loop.
+
+use std::time::{Duration, Instant};
+
+/// Decides when a task that runs every `interval` is due.
+#[derive(Debug, Clone)]
+pub struct IntervalSchedule {
+    interval: Duration,
+    last_run: Option<Instant>,
+}
+
+impl IntervalSchedule {
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_run: None,
+        }
+    }
+
+    /// Whether the task should run at `now`. The first check is always due.
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.last_run
+            .is_none_or(|last| now.saturating_duration_since(last) >= self.interval)
+    }
+
+    /// Record that the task ran at `now`, successful or not.
+    pub const fn record_run(&mut self, now: Instant) {
+        self.last_run = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_runs_once_per_interval() {
+        let mut schedule = IntervalSchedule::new(Duration::from_secs(3600));
+        let start = Instant::now();
+
+        assert!(schedule.is_due(start));
+        schedule.record_run(start);
+
+        assert!(!schedule.is_due(start + Duration::from_secs(60)));
+        assert!(schedule.is_due(start + Duration::from_secs(3600)));
+    }
+}
@@ -0,0 +1,98 @@
+//! Graceful shutdown of the main loop.
+//!
+//! The main loop tallies what it did in a [`RunSummary`]. Once it exits, [`shutdown`]
+//! saves the state store, records the final metrics and logs the summary, so a rolling
+//! restart loses neither state nor the last cycle's metrics.
+
+use crate::{metrics::Metrics, store::StateStore};
+use std::time::Duration;
+use tracing::{error, info};
+
+/// What the main loop did since startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunSummary {
+    /// Cycles run, including failed ones
+    pub cycles: u64,
+    pub failed_cycles: u64,
+    pub proofs_submitted: usize,
+    pub finalized: usize,
+    /// Withdrawals initiated, one per chunk sent
+    pub withdrawals_initiated: usize,
+    pub deposits: usize,
+}
+
+impl RunSummary {
+    /// Record a completed cycle.
+    pub const fn record_cycle(&mut self, success: bool) {
+        self.cycles += 1;
+        if !success {
+            self.failed_cycles += 1;
+        }
+    }
+
+    /// Transactions sent since startup.
+    pub const fn actions(&self) -> usize {
+        self.proofs_submitted + self.finalized + self.withdrawals_initiated + self.deposits
+    }
+}
+
+/// Save `store`, record the final metrics and log `summary`.
+///
+/// A failed save is logged rather than returned, so the rest of the sequence still runs.
+pub fn shutdown(store: &StateStore, metrics: &Metrics, summary: &RunSummary, uptime: Duration) {
+    if let Err(e) = store.save() {
+        error!(error = %e, "Failed to save state store on shutdown");
+    }
+
+    metrics.record_shutdown(uptime);
+
+    info!(
+        cycles = summary.cycles,
+        failed_cycles = summary.failed_cycles,
+        actions = summary.actions(),
+        proofs_submitted = summary.proofs_submitted,
+        finalized = summary.finalized,
+        withdrawals_initiated = summary.withdrawals_initiated,
+        deposits = summary.deposits,
+        uptime_secs = uptime.as_secs(),
+        "Shutdown complete"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_summary_counts() {
+        let mut summary = RunSummary::default();
+        summary.record_cycle(true);
+        summary.record_cycle(false);
+        summary.proofs_submitted += 2;
+        summary.finalized += 4;
+        summary.withdrawals_initiated += 3;
+        summary.deposits += 1;
+
+        assert_eq!(summary.cycles, 2);
+        assert_eq!(summary.failed_cycles, 1);
+        assert_eq!(summary.actions(), 10);
+    }
+
+    #[test]
+    fn test_shutdown_saves_store() {
+        let path =
+            std::env::temp_dir().join(format!("orchestrator-{}-shutdown.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = StateStore::load(&path).unwrap();
+
+        shutdown(
+            &store,
+            &Metrics::new(),
+            &RunSummary::default(),
+            Duration::from_secs(60),
+        );
+
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,313 @@
+//! Changes to the observed state between cycles, for `--snapshot-diff`.
+//!
+//! Each cycle the metrics refresh reads balances, in-flight deposits and pending
+//! withdrawals into a [`StateSnapshot`]. Logging all of it every cycle buries the one
+//! withdrawal that flipped from initiated to proven, so [`SnapshotDiff`] compares each
+//! snapshot with the previous one and reports only the [`StateChange`]s.
+
+use alloy_primitives::{utils::format_ether, U256};
+use std::{collections::BTreeMap, fmt};
+use withdrawal::types::{WithdrawalHash, WithdrawalStatus};
+
+/// State read by one cycle. A part that failed to read is `None` and not compared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateSnapshot {
+    pub l1_eoa_balance: Option<U256>,
+    pub l2_eoa_balance: Option<U256>,
+    pub spoke_pool_balance: Option<U256>,
+    /// Input amount of each in-flight deposit, by deposit ID
+    pub deposits: Option<BTreeMap<U256, U256>>,
+    /// Status of each pending withdrawal
+    pub withdrawals: Option<BTreeMap<WithdrawalHash, WithdrawalStatus>>,
+}
+
+/// A difference between two consecutive snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateChange {
+    Balance {
+        name: &'static str,
+        from: U256,
+        to: U256,
+    },
+    DepositAdded {
+        deposit_id: U256,
+        input_amount: U256,
+    },
+    /// Filled, expired or out of the lookback window
+    DepositRemoved { deposit_id: U256 },
+    WithdrawalAdded {
+        hash: WithdrawalHash,
+        status: WithdrawalStatus,
+    },
+    WithdrawalStatus {
+        hash: WithdrawalHash,
+        from: WithdrawalStatus,
+        to: WithdrawalStatus,
+    },
+    /// Finalized or out of the lookback window
+    WithdrawalRemoved { hash: WithdrawalHash },
+}
+
+impl fmt::Display for StateChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Balance { name, from, to } => {
+                let (sign, delta) = if to >= from {
+                    ('+', to - from)
+                } else {
+                    ('-', from - to)
+                };
+                write!(
+                    f,
+                    "{name} balance {} -> {} ETH ({sign}{})",
+                    format_ether(*from),
+                    format_ether(*to),
+                    format_ether(delta)
+                )
+            }
+            Self::DepositAdded {
+                deposit_id,
+                input_amount,
+            } => write!(
+                f,
+                "deposit {deposit_id} in flight ({} ETH)",
+                format_ether(*input_amount)
+            ),
+            Self::DepositRemoved { deposit_id } => {
+                write!(f, "deposit {deposit_id} no longer in flight")
+            }
+            Self::WithdrawalAdded { hash, status } => {
+                write!(f, "withdrawal {hash} pending ({})", status_label(status))
+            }
+            Self::WithdrawalStatus { hash, from, to } => write!(
+                f,
+                "withdrawal {hash} {} -> {}",
+                status_label(from),
+                status_label(to)
+            ),
+            Self::WithdrawalRemoved { hash } => write!(f, "withdrawal {hash} no longer pending"),
+        }
+    }
+}
+
+fn status_label(status: &WithdrawalStatus) -> String {
+    match status {
+        WithdrawalStatus::Initiated => "initiated".to_string(),
+        WithdrawalStatus::Proven {
+            dispute_game_proxy, ..
+        } => format!("proven against {dispute_game_proxy}"),
+        WithdrawalStatus::Finalized => "finalized".to_string(),
+    }
+}
+
+/// Changes from `prev` to `next`, balances first, then deposits and withdrawals.
+pub fn diff(prev: &StateSnapshot, next: &StateSnapshot) -> Vec<StateChange> {
+    let mut changes = vec![];
+
+    let balances = [
+        ("L1 EOA", prev.l1_eoa_balance, next.l1_eoa_balance),
+        ("L2 EOA", prev.l2_eoa_balance, next.l2_eoa_balance),
+        (
+            "SpokePool",
+            prev.spoke_pool_balance,
+            next.spoke_pool_balance,
+        ),
+    ];
+    for (name, from, to) in balances {
+        if let (Some(from), Some(to)) = (from, to) {
+            if from != to {
+                changes.push(StateChange::Balance { name, from, to });
+            }
+        }
+    }
+
+    if let (Some(prev), Some(next)) = (&prev.deposits, &next.deposits) {
+        for (&deposit_id, &input_amount) in next {
+            if !prev.contains_key(&deposit_id) {
+                changes.push(StateChange::DepositAdded {
+                    deposit_id,
+                    input_amount,
+                });
+            }
+        }
+        for &deposit_id in prev.keys() {
+            if !next.contains_key(&deposit_id) {
+                changes.push(StateChange::DepositRemoved { deposit_id });
+            }
+        }
+    }
+
+    if let (Some(prev), Some(next)) = (&prev.withdrawals, &next.withdrawals) {
+        for (&hash, status) in next {
+            match prev.get(&hash) {
+                None => changes.push(StateChange::WithdrawalAdded {
+                    hash,
+                    status: status.clone(),
+                }),
+                Some(from) if from != status => changes.push(StateChange::WithdrawalStatus {
+                    hash,
+                    from: from.clone(),
+                    to: status.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for &hash in prev.keys() {
+            if !next.contains_key(&hash) {
+                changes.push(StateChange::WithdrawalRemoved { hash });
+            }
+        }
+    }
+
+    changes
+}
+
+/// The previous cycle's snapshot, to diff the next one against.
+#[derive(Debug, Default)]
+pub struct SnapshotDiff {
+    prev: Option<StateSnapshot>,
+}
+
+impl SnapshotDiff {
+    /// Changes since the previous snapshot, or `None` for the first one.
+    ///
+    /// Parts of the previous snapshot that failed to read are carried over from the one
+    /// before, so a failed read doesn't replay every change since.
+    pub fn record(&mut self, mut next: StateSnapshot) -> Option<Vec<StateChange>> {
+        let Some(prev) = self.prev.take() else {
+            self.prev = Some(next);
+            return None;
+        };
+
+        let changes = diff(&prev, &next);
+        next.l1_eoa_balance = next.l1_eoa_balance.or(prev.l1_eoa_balance);
+        next.l2_eoa_balance = next.l2_eoa_balance.or(prev.l2_eoa_balance);
+        next.spoke_pool_balance = next.spoke_pool_balance.or(prev.spoke_pool_balance);
+        next.deposits = next.deposits.or(prev.deposits);
+        next.withdrawals = next.withdrawals.or(prev.withdrawals);
+        self.prev = Some(next);
+        Some(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{utils::parse_ether, Address};
+
+    fn proven() -> WithdrawalStatus {
+        WithdrawalStatus::Proven {
+            timestamp: 1_000,
+            dispute_game_proxy: Address::repeat_byte(0x99),
+            proof_submitter: Address::ZERO,
+        }
+    }
+
+    fn snapshot(
+        l1_eoa_balance: &str,
+        deposits: &[u64],
+        withdrawals: &[(u8, WithdrawalStatus)],
+    ) -> StateSnapshot {
+        StateSnapshot {
+            l1_eoa_balance: Some(parse_ether(l1_eoa_balance).unwrap()),
+            l2_eoa_balance: Some(U256::ZERO),
+            spoke_pool_balance: None,
+            deposits: Some(
+                deposits
+                    .iter()
+                    .map(|&id| (U256::from(id), U256::from(id)))
+                    .collect(),
+            ),
+            withdrawals: Some(
+                withdrawals
+                    .iter()
+                    .map(|(byte, status)| (WithdrawalHash::repeat_byte(*byte), status.clone()))
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_only_changes() {
+        let prev = snapshot(
+            "1.5",
+            &[1, 2],
+            &[
+                (1, WithdrawalStatus::Initiated),
+                (2, proven()),
+                (3, proven()),
+            ],
+        );
+        let next = snapshot(
+            "1.25",
+            &[2, 3],
+            &[
+                (1, proven()),
+                (2, proven()),
+                (4, WithdrawalStatus::Initiated),
+            ],
+        );
+
+        let changes = diff(&prev, &next);
+
+        assert_eq!(
+            changes,
+            vec![
+                StateChange::Balance {
+                    name: "L1 EOA",
+                    from: parse_ether("1.5").unwrap(),
+                    to: parse_ether("1.25").unwrap(),
+                },
+                StateChange::DepositAdded {
+                    deposit_id: U256::from(3),
+                    input_amount: U256::from(3),
+                },
+                StateChange::DepositRemoved {
+                    deposit_id: U256::from(1)
+                },
+                StateChange::WithdrawalStatus {
+                    hash: WithdrawalHash::repeat_byte(1),
+                    from: WithdrawalStatus::Initiated,
+                    to: proven(),
+                },
+                StateChange::WithdrawalAdded {
+                    hash: WithdrawalHash::repeat_byte(4),
+                    status: WithdrawalStatus::Initiated,
+                },
+                StateChange::WithdrawalRemoved {
+                    hash: WithdrawalHash::repeat_byte(3)
+                },
+            ]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "L1 EOA balance 1.500000000000000000 -> 1.250000000000000000 ETH (-0.250000000000000000)"
+        );
+        assert!(diff(&next, &next).is_empty());
+    }
+
+    #[test]
+    fn test_failed_reads_are_not_reported_as_changes() {
+        let mut snapshots = SnapshotDiff::default();
+        let full = snapshot("1", &[1], &[(1, WithdrawalStatus::Initiated)]);
+        assert_eq!(snapshots.record(full.clone()), None);
+
+        // Withdrawals failed to read this cycle
+        let partial = StateSnapshot {
+            withdrawals: None,
+            ..full
+        };
+        assert_eq!(snapshots.record(partial), Some(vec![]));
+
+        // Compared with the last withdrawals that were read
+        let next = snapshot("1", &[1], &[(1, proven())]);
+        assert_eq!(
+            snapshots.record(next),
+            Some(vec![StateChange::WithdrawalStatus {
+                hash: WithdrawalHash::repeat_byte(1),
+                from: WithdrawalStatus::Initiated,
+                to: proven(),
+            }])
+        );
+    }
+}
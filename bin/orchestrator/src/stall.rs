@@ -0,0 +1,136 @@
+//! Chain head stall detection.
+//!
+//! A halted sequencer or a stuck RPC node keeps answering `eth_blockNumber` with the
+//! same block. Nothing fails, so cycles look quiet rather than broken. [`HeadTracker`]
+//! remembers when each chain's head last moved and reports a chain as stalled once it
+//! hasn't for longer than `max_stale_head_secs`.
+
+use crate::mempool::Chain;
+use alloy_provider::Provider;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// A chain head that hasn't advanced for at least the maximum age.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalledHead {
+    /// Block number the head is stuck at
+    pub head: u64,
+    /// How long since the head last advanced
+    pub age: Duration,
+}
+
+/// Tracks each chain's head block across cycles.
+#[derive(Debug, Clone)]
+pub struct HeadTracker {
+    max_age: Duration,
+    /// Per chain: the highest head seen and when it was first seen
+    last_advanced: HashMap<Chain, (u64, Instant)>,
+}
+
+impl HeadTracker {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            last_advanced: HashMap::new(),
+        }
+    }
+
+    /// Record the head observed on `chain` at `now`.
+    ///
+    /// Returns the stalled head once the head hasn't advanced for more than the maximum
+    /// age. A head lower than one seen before (a node behind a load balancer lagging
+    /// the others) doesn't count as progress.
+    pub fn observe(&mut self, chain: Chain, head: u64, now: Instant) -> Option<StalledHead> {
+        let (head, since) = *self
+            .last_advanced
+            .entry(chain)
+            .and_modify(|(last, since)| {
+                if head > *last {
+                    *last = head;
+                    *since = now;
+                }
+            })
+            .or_insert((head, now));
+
+        let age = now.saturating_duration_since(since);
+        (age > self.max_age).then_some(StalledHead { head, age })
+    }
+}
+
+/// Query the head of `chain` and feed it to `tracker`.
+pub async fn check_chain_head<P: Provider>(
+    provider: &P,
+    chain: Chain,
+    tracker: &mut HeadTracker,
+    now: Instant,
+) -> eyre::Result<Option<StalledHead>> {
+    let head = provider.get_block_number().await?;
+    Ok(tracker.observe(chain, head, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::FakeChain;
+
+    #[test]
+    fn test_stall_after_max_age() {
+        let mut tracker = HeadTracker::new(Duration::from_secs(120));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert_eq!(tracker.observe(Chain::L2, 100, start), None);
+        assert_eq!(tracker.observe(Chain::L2, 100, at(120)), None);
+        assert_eq!(
+            tracker.observe(Chain::L2, 100, at(121)),
+            Some(StalledHead {
+                head: 100,
+                age: Duration::from_secs(121)
+            })
+        );
+        // Tracked per chain
+        assert_eq!(tracker.observe(Chain::L1, 7, at(121)), None);
+
+        // Progress clears the stall and restarts the age
+        assert_eq!(tracker.observe(Chain::L2, 101, at(130)), None);
+        assert_eq!(tracker.observe(Chain::L2, 101, at(250)), None);
+        assert!(tracker.observe(Chain::L2, 101, at(251)).is_some());
+    }
+
+    #[test]
+    fn test_lower_head_is_not_progress() {
+        let mut tracker = HeadTracker::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        tracker.observe(Chain::L1, 100, start);
+        let stalled = tracker.observe(Chain::L1, 99, start + Duration::from_secs(61));
+        assert_eq!(stalled.map(|s| s.head), Some(100));
+    }
+
+    #[tokio::test]
+    async fn test_check_chain_head() {
+        let chain = FakeChain::new();
+        chain.mine(1_000);
+        let mut tracker = HeadTracker::new(Duration::from_secs(30));
+        let start = Instant::now();
+
+        let stalled = check_chain_head(&chain.provider(), Chain::L2, &mut tracker, start)
+            .await
+            .unwrap();
+        assert_eq!(stalled, None);
+
+        let later = start + Duration::from_secs(31);
+        let stalled = check_chain_head(&chain.provider(), Chain::L2, &mut tracker, later)
+            .await
+            .unwrap();
+        assert!(stalled.is_some());
+
+        chain.mine(1_012);
+        let stalled = check_chain_head(&chain.provider(), Chain::L2, &mut tracker, later)
+            .await
+            .unwrap();
+        assert_eq!(stalled, None);
+    }
+}
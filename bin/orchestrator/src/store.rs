@@ -0,0 +1,903 @@
+//! File-backed state store.
+//!
+//! Keeps what the orchestrator has learned between runs, so long scans only have to
+//! happen once. The store is a single JSON file that is rewritten atomically on
+//! [`StateStore::save`]. It holds:
+//!
+//! - the withdrawals found so far, with the L2 transaction that initiated each and the
+//!   dispute game it was proven against
+//! - the cost records of finalized withdrawals
+//! - the last transaction requests sent per chain
+//! - the in-flight deposits found by the last deposit scan
+//! - the hashes of withdrawals known to be finalized
+//! - the prove and finalize transactions still awaiting a receipt
+//! - the relayer refund history of expired deposits
+//! - the latest reconciliation of finalized withdrawals
+
+use crate::{reconcile::ReconciliationReport, refunds::RefundLedger};
+use action::{
+    pending::{PendingTransaction, PendingTransactions},
+    withdraw::Initiated,
+};
+use alloy_primitives::{Address, Bytes, TxHash, U256};
+use alloy_rpc_types_eth::TransactionRequest;
+use binding::opstack::WithdrawalTransaction;
+use deposit::{DepositCheckpoint, InFlightDeposit};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+use withdrawal::{
+    finalized::FinalizedSet,
+    state::PendingWithdrawal,
+    types::{WithdrawalHash, WithdrawalStatus},
+};
+
+/// Withdrawal status as persisted in the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum RecordStatus {
+    Initiated,
+    Proven {
+        timestamp: u64,
+        /// Zero in stores written before the proxy was recorded
+        #[serde(default)]
+        dispute_game_proxy: Address,
+        /// Zero in stores written before the submitter was recorded
+        #[serde(default)]
+        proof_submitter: Address,
+    },
+    Finalized,
+}
+
+impl From<&WithdrawalStatus> for RecordStatus {
+    fn from(status: &WithdrawalStatus) -> Self {
+        match status {
+            WithdrawalStatus::Initiated => Self::Initiated,
+            WithdrawalStatus::Proven {
+                timestamp,
+                dispute_game_proxy,
+                proof_submitter,
+            } => Self::Proven {
+                timestamp: *timestamp,
+                dispute_game_proxy: *dispute_game_proxy,
+                proof_submitter: *proof_submitter,
+            },
+            WithdrawalStatus::Finalized => Self::Finalized,
+        }
+    }
+}
+
+impl From<RecordStatus> for WithdrawalStatus {
+    fn from(status: RecordStatus) -> Self {
+        match status {
+            RecordStatus::Initiated => Self::Initiated,
+            RecordStatus::Proven {
+                timestamp,
+                dispute_game_proxy,
+                proof_submitter,
+            } => Self::Proven {
+                timestamp,
+                dispute_game_proxy,
+                proof_submitter,
+            },
+            RecordStatus::Finalized => Self::Finalized,
+        }
+    }
+}
+
+/// Proof submitted by the orchestrator for a withdrawal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofRecord {
+    /// Index of the dispute game in the DisputeGameFactory
+    pub dispute_game_index: U256,
+    /// Address of the dispute game proxy
+    pub dispute_game_proxy: Address,
+    /// L1 transaction that submitted the proof
+    pub tx_hash: TxHash,
+}
+
+/// L2 transaction that initiated a withdrawal and the gas it cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InitiationRecord {
+    pub tx_hash: TxHash,
+    pub gas_used: u64,
+    /// Price paid per unit of L2 gas, in wei
+    pub effective_gas_price: u128,
+}
+
+impl InitiationRecord {
+    /// L2 execution fee in wei, without the L1 data fee.
+    pub fn execution_fee_wei(&self) -> U256 {
+        U256::from(self.gas_used) * U256::from(self.effective_gas_price)
+    }
+}
+
+impl From<&Initiated> for InitiationRecord {
+    fn from(initiated: &Initiated) -> Self {
+        Self {
+            tx_hash: initiated.tx_hash,
+            gas_used: initiated.gas_used,
+            effective_gas_price: initiated.effective_gas_price,
+        }
+    }
+}
+
+/// A withdrawal as persisted in the store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalRecord {
+    pub hash: WithdrawalHash,
+    pub l2_block: u64,
+    pub nonce: U256,
+    pub sender: Address,
+    pub target: Address,
+    pub value: U256,
+    pub gas_limit: U256,
+    pub data: Bytes,
+    pub status: RecordStatus,
+    /// Set when the orchestrator proved the withdrawal itself
+    #[serde(default)]
+    pub proof: Option<ProofRecord>,
+    /// Recorded when this instance initiated the withdrawal, or looked up later
+    #[serde(default)]
+    pub initiation: Option<InitiationRecord>,
+}
+
+impl From<&PendingWithdrawal> for WithdrawalRecord {
+    fn from(withdrawal: &PendingWithdrawal) -> Self {
+        let tx = &withdrawal.transaction;
+        Self {
+            hash: withdrawal.hash,
+            l2_block: withdrawal.l2_block,
+            nonce: tx.nonce,
+            sender: tx.sender,
+            target: tx.target,
+            value: tx.value,
+            gas_limit: tx.gasLimit,
+            data: tx.data.clone(),
+            status: (&withdrawal.status).into(),
+            proof: None,
+            initiation: None,
+        }
+    }
+}
+
+impl From<&WithdrawalRecord> for PendingWithdrawal {
+    fn from(record: &WithdrawalRecord) -> Self {
+        Self {
+            transaction: WithdrawalTransaction {
+                nonce: record.nonce,
+                sender: record.sender,
+                target: record.target,
+                value: record.value,
+                gasLimit: record.gas_limit,
+                data: record.data.clone(),
+            },
+            hash: record.hash,
+            l2_block: record.l2_block,
+            status: record.status.into(),
+        }
+    }
+}
+
+/// An unfilled deposit as persisted in the store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositRecord {
+    pub deposit_id: U256,
+    pub origin_chain_id: u64,
+    pub destination_chain_id: u64,
+    pub input_amount: U256,
+    /// Zero in stores written before output amounts were recorded
+    #[serde(default)]
+    pub output_amount: U256,
+    pub depositor: Address,
+    pub block_number: u64,
+    pub fill_deadline: u32,
+}
+
+impl From<&InFlightDeposit> for DepositRecord {
+    fn from(deposit: &InFlightDeposit) -> Self {
+        Self {
+            deposit_id: deposit.deposit_id,
+            origin_chain_id: deposit.origin_chain_id,
+            destination_chain_id: deposit.destination_chain_id,
+            input_amount: deposit.input_amount,
+            output_amount: deposit.output_amount,
+            depositor: deposit.depositor,
+            block_number: deposit.block_number,
+            fill_deadline: deposit.fill_deadline,
+        }
+    }
+}
+
+impl From<&DepositRecord> for InFlightDeposit {
+    fn from(record: &DepositRecord) -> Self {
+        Self {
+            deposit_id: record.deposit_id,
+            origin_chain_id: record.origin_chain_id,
+            destination_chain_id: record.destination_chain_id,
+            input_amount: record.input_amount,
+            output_amount: record.output_amount,
+            depositor: record.depositor,
+            block_number: record.block_number,
+            fill_deadline: record.fill_deadline,
+        }
+    }
+}
+
+/// Sent transaction requests kept per chain; older nonces are dropped first.
+const MAX_SENT_TRANSACTIONS: usize = 64;
+
+/// Fee paid for one leg (initiate, prove or finalize) of a withdrawal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LegCost {
+    /// Transaction that performed the leg, if it was located
+    pub tx_hash: Option<TxHash>,
+    /// Total fee paid in wei (including the L1 data fee on L2). `None` when unknown:
+    /// the leg was sent by a third party or its transaction was not found.
+    pub cost_wei: Option<U256>,
+}
+
+/// Gas cost of a finalized withdrawal across all three legs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithdrawalCostRecord {
+    pub hash: WithdrawalHash,
+    /// Value moved by the withdrawal in wei
+    pub value: U256,
+    /// Timestamp of the L1 block the withdrawal was finalized in
+    pub finalized_at: u64,
+    pub initiate: LegCost,
+    pub prove: LegCost,
+    pub finalize: LegCost,
+    /// Gas of the initiating L2 transaction, when known
+    #[serde(default)]
+    pub initiation: Option<InitiationRecord>,
+}
+
+impl WithdrawalCostRecord {
+    /// Sum of all three legs, or `None` if any leg is unknown.
+    pub fn total_cost_wei(&self) -> Option<U256> {
+        [self.initiate, self.prove, self.finalize]
+            .iter()
+            .try_fold(U256::ZERO, |total, leg| Some(total + leg.cost_wei?))
+    }
+
+    /// Total cost as a fraction of the value moved, or `None` if it can't be computed.
+    pub fn cost_ratio(&self) -> Option<f64> {
+        let total = self.total_cost_wei()?;
+        if self.value.is_zero() {
+            return None;
+        }
+        Some(f64::from(total) / f64::from(self.value))
+    }
+}
+
+/// Serialized contents of the store file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoreContents {
+    /// Last L2 block scanned for withdrawal initiations
+    withdrawals_scanned_to: Option<u64>,
+    /// Known withdrawals keyed by hash
+    withdrawals: BTreeMap<WithdrawalHash, WithdrawalRecord>,
+    /// Cost records of finalized withdrawals keyed by hash
+    #[serde(default)]
+    withdrawal_costs: BTreeMap<WithdrawalHash, WithdrawalCostRecord>,
+    /// Last signed transaction requests, keyed by chain id and nonce
+    #[serde(default)]
+    sent_transactions: BTreeMap<u64, BTreeMap<u64, TransactionRequest>>,
+    /// Last L1 block scanned for deposits
+    #[serde(default)]
+    deposits_scanned_to: Option<u64>,
+    /// Deposits unfilled as of `deposits_scanned_to`
+    #[serde(default)]
+    inflight_deposits: Vec<DepositRecord>,
+    /// Withdrawals observed as finalized, whose status scans don't read from L1
+    #[serde(default)]
+    finalized_withdrawals: BTreeSet<WithdrawalHash>,
+    /// Unix time of the last check of `finalized_withdrawals` against L1
+    #[serde(default)]
+    finalized_verified_at: Option<u64>,
+    /// Prove and finalize transactions broadcast without a receipt seen yet
+    #[serde(default)]
+    pending_transactions: Vec<PendingTransaction>,
+    /// Expired deposits and the refunds paid for them
+    #[serde(default)]
+    refund_ledger: RefundLedger,
+    /// Latest reconciliation of finalized withdrawals against L1 balances
+    #[serde(default)]
+    reconciliation: Option<ReconciliationReport>,
+}
+
+/// Orchestrator state persisted to a JSON file.
+#[derive(Debug)]
+pub struct StateStore {
+    path: PathBuf,
+    contents: StoreContents,
+}
+
+impl StateStore {
+    /// Load the store at `path`, starting empty if the file does not exist yet.
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => StoreContents::default(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self { path, contents })
+    }
+
+    /// Write the store to disk, replacing the previous file atomically.
+    pub fn save(&self) -> eyre::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&self.contents)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Last L2 block covered by a withdrawal discovery scan.
+    pub const fn withdrawals_scanned_to(&self) -> Option<u64> {
+        self.contents.withdrawals_scanned_to
+    }
+
+    pub const fn set_withdrawals_scanned_to(&mut self, block: u64) {
+        self.contents.withdrawals_scanned_to = Some(block);
+    }
+
+    /// Insert or replace withdrawals by hash, keeping any recorded proof.
+    pub fn upsert_withdrawals<'a>(
+        &mut self,
+        withdrawals: impl IntoIterator<Item = &'a PendingWithdrawal>,
+    ) {
+        for withdrawal in withdrawals {
+            let mut record = WithdrawalRecord::from(withdrawal);
+            if let Some(existing) = self.contents.withdrawals.get(&withdrawal.hash) {
+                record.proof = existing.proof;
+                record.initiation = existing.initiation;
+            }
+            self.contents.withdrawals.insert(withdrawal.hash, record);
+        }
+    }
+
+    /// Record the proof submitted for `withdrawal`, adding the withdrawal if unknown.
+    ///
+    /// The status is left alone; the next status refresh picks up the proof timestamp.
+    pub fn record_proof(&mut self, withdrawal: &PendingWithdrawal, proof: ProofRecord) {
+        self.contents
+            .withdrawals
+            .entry(withdrawal.hash)
+            .or_insert_with(|| withdrawal.into())
+            .proof = Some(proof);
+    }
+
+    /// Record the transaction that initiated `withdrawal`, adding the withdrawal if unknown.
+    pub fn record_initiation(
+        &mut self,
+        withdrawal: &PendingWithdrawal,
+        initiation: InitiationRecord,
+    ) {
+        self.contents
+            .withdrawals
+            .entry(withdrawal.hash)
+            .or_insert_with(|| withdrawal.into())
+            .initiation = Some(initiation);
+    }
+
+    /// Set the initiation of a known withdrawal. Unknown hashes are ignored.
+    pub fn set_initiation(&mut self, hash: &WithdrawalHash, initiation: InitiationRecord) {
+        if let Some(record) = self.contents.withdrawals.get_mut(hash) {
+            record.initiation = Some(initiation);
+        }
+    }
+
+    /// All known withdrawals, oldest first.
+    pub fn withdrawals(&self) -> Vec<&WithdrawalRecord> {
+        let mut records: Vec<_> = self.contents.withdrawals.values().collect();
+        records.sort_by_key(|record| record.l2_block);
+        records
+    }
+
+    /// Update the status of known withdrawals. Unknown hashes are ignored.
+    pub fn update_statuses(&mut self, updates: &[(WithdrawalHash, WithdrawalStatus)]) {
+        for (hash, status) in updates {
+            if let Some(record) = self.contents.withdrawals.get_mut(hash) {
+                record.status = status.into();
+            }
+        }
+    }
+
+    pub fn withdrawal(&self, hash: &WithdrawalHash) -> Option<&WithdrawalRecord> {
+        self.contents.withdrawals.get(hash)
+    }
+
+    /// Number of withdrawals in the store.
+    pub fn withdrawal_count(&self) -> usize {
+        self.contents.withdrawals.len()
+    }
+
+    /// Withdrawals that are not finalized yet, oldest first.
+    pub fn pending_withdrawals(&self) -> Vec<PendingWithdrawal> {
+        let mut pending: Vec<PendingWithdrawal> = self
+            .contents
+            .withdrawals
+            .values()
+            .filter(|record| record.status != RecordStatus::Finalized)
+            .map(PendingWithdrawal::from)
+            .collect();
+        pending.sort_by_key(|w| w.l2_block);
+        pending
+    }
+
+    /// Whether a cost record exists for `hash`.
+    pub fn has_withdrawal_cost(&self, hash: &WithdrawalHash) -> bool {
+        self.contents.withdrawal_costs.contains_key(hash)
+    }
+
+    /// Insert or replace the cost record of a finalized withdrawal.
+    pub fn record_withdrawal_cost(&mut self, record: WithdrawalCostRecord) {
+        self.contents.withdrawal_costs.insert(record.hash, record);
+    }
+
+    /// Cost records of withdrawals finalized at or after `timestamp`, oldest first.
+    pub fn withdrawal_costs_since(&self, timestamp: u64) -> Vec<WithdrawalCostRecord> {
+        let mut costs: Vec<WithdrawalCostRecord> = self
+            .contents
+            .withdrawal_costs
+            .values()
+            .filter(|record| record.finalized_at >= timestamp)
+            .cloned()
+            .collect();
+        costs.sort_by_key(|record| record.finalized_at);
+        costs
+    }
+
+    /// Where the last in-flight deposit scan stopped, to resume from.
+    pub fn deposit_checkpoint(&self) -> DepositCheckpoint {
+        DepositCheckpoint {
+            scanned_to: self.contents.deposits_scanned_to,
+            inflight: self
+                .contents
+                .inflight_deposits
+                .iter()
+                .map(InFlightDeposit::from)
+                .collect(),
+        }
+    }
+
+    /// Replace the deposit checkpoint with the result of a newer scan.
+    pub fn set_deposit_checkpoint(&mut self, checkpoint: &DepositCheckpoint) {
+        self.contents.deposits_scanned_to = checkpoint.scanned_to;
+        self.contents.inflight_deposits = checkpoint
+            .inflight
+            .iter()
+            .map(DepositRecord::from)
+            .collect();
+    }
+
+    /// Withdrawals known to be finalized, including known withdrawals whose recorded
+    /// status is finalized.
+    pub fn finalized_set(&self) -> FinalizedSet {
+        FinalizedSet::from_hashes(
+            self.contents.finalized_withdrawals.iter().copied().chain(
+                self.contents
+                    .withdrawals
+                    .values()
+                    .filter(|record| record.status == RecordStatus::Finalized)
+                    .map(|record| record.hash),
+            ),
+        )
+    }
+
+    /// Replace the known finalized withdrawals with the contents of `set`.
+    pub fn set_finalized(&mut self, set: &FinalizedSet) {
+        self.contents.finalized_withdrawals = set.hashes().into_iter().collect();
+    }
+
+    /// Unix time the known finalized withdrawals were last checked against L1.
+    pub const fn finalized_verified_at(&self) -> Option<u64> {
+        self.contents.finalized_verified_at
+    }
+
+    pub const fn set_finalized_verified_at(&mut self, timestamp: u64) {
+        self.contents.finalized_verified_at = Some(timestamp);
+    }
+
+    pub fn refund_ledger(&self) -> RefundLedger {
+        self.contents.refund_ledger.clone()
+    }
+
+    pub fn set_refund_ledger(&mut self, ledger: &RefundLedger) {
+        self.contents.refund_ledger = ledger.clone();
+    }
+
+    /// Latest reconciliation report, if one was made.
+    pub const fn reconciliation(&self) -> Option<&ReconciliationReport> {
+        self.contents.reconciliation.as_ref()
+    }
+
+    /// Replace the reconciliation report with `report`.
+    pub fn set_reconciliation(&mut self, report: ReconciliationReport) {
+        self.contents.reconciliation = Some(report);
+    }
+
+    /// Prove and finalize transactions broadcast without a receipt seen yet.
+    pub fn pending_transactions(&self) -> PendingTransactions {
+        PendingTransactions::from_entries(self.contents.pending_transactions.iter().copied())
+    }
+
+    /// Replace the pending transactions with `entries`. Returns whether they changed.
+    pub fn set_pending_transactions(&mut self, entries: Vec<PendingTransaction>) -> bool {
+        let changed = entries != self.contents.pending_transactions;
+        self.contents.pending_transactions = entries;
+        changed
+    }
+
+    /// Remember a filled transaction request by its chain id and nonce, replacing any
+    /// earlier request with the same nonce. Requests without either are ignored.
+    pub fn record_sent_transaction(&mut self, tx: &TransactionRequest) {
+        let (Some(chain_id), Some(nonce)) = (tx.chain_id, tx.nonce) else {
+            return;
+        };
+
+        let sent = self.contents.sent_transactions.entry(chain_id).or_default();
+        sent.insert(nonce, tx.clone());
+        while sent.len() > MAX_SENT_TRANSACTIONS {
+            sent.pop_first();
+        }
+    }
+
+    /// The last request sent with `nonce` on `chain_id`, if recorded.
+    pub fn sent_transaction(&self, chain_id: u64, nonce: u64) -> Option<&TransactionRequest> {
+        self.contents
+            .sent_transactions
+            .get(&chain_id)
+            .and_then(|sent| sent.get(&nonce))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use action::audit::TransactionKind;
+    use alloy_network::TransactionBuilder;
+    use alloy_primitives::B256;
+
+    fn withdrawal(hash: u8, l2_block: u64, status: WithdrawalStatus) -> PendingWithdrawal {
+        PendingWithdrawal {
+            transaction: WithdrawalTransaction {
+                nonce: U256::from(hash),
+                sender: Address::repeat_byte(1),
+                target: Address::repeat_byte(1),
+                value: U256::from(1000),
+                gasLimit: U256::from(100_000),
+                data: Bytes::new(),
+            },
+            hash: B256::repeat_byte(hash),
+            l2_block,
+            status,
+        }
+    }
+
+    fn proven(timestamp: u64) -> WithdrawalStatus {
+        WithdrawalStatus::Proven {
+            timestamp,
+            dispute_game_proxy: Address::repeat_byte(0x22),
+            proof_submitter: Address::repeat_byte(0x33),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("orchestrator-{}-{name}.json", std::process::id()))
+    }
+
+    #[test]
+    fn test_store_roundtrip() {
+        let path = temp_path("roundtrip");
+        let mut store = StateStore::load(&path).unwrap();
+        assert_eq!(store.withdrawal_count(), 0);
+        assert_eq!(store.withdrawals_scanned_to(), None);
+
+        store.upsert_withdrawals(&[
+            withdrawal(1, 20, WithdrawalStatus::Initiated),
+            withdrawal(2, 10, proven(5)),
+            withdrawal(3, 30, WithdrawalStatus::Finalized),
+        ]);
+        store.set_withdrawals_scanned_to(40);
+        store.save().unwrap();
+
+        let store = StateStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(store.withdrawal_count(), 3);
+        assert_eq!(store.withdrawals_scanned_to(), Some(40));
+
+        let pending = store.pending_withdrawals();
+        assert_eq!(
+            pending.iter().map(|w| w.l2_block).collect::<Vec<_>>(),
+            vec![10, 20]
+        );
+        assert_eq!(pending[0].status, proven(5));
+        assert_eq!(
+            withdrawal::hash::compute_withdrawal_hash(&pending[1].transaction),
+            withdrawal::hash::compute_withdrawal_hash(
+                &withdrawal(1, 20, WithdrawalStatus::Initiated).transaction
+            )
+        );
+    }
+
+    #[test]
+    fn test_update_statuses() {
+        let mut store = StateStore::load(temp_path("update")).unwrap();
+        store.upsert_withdrawals(&[withdrawal(1, 20, WithdrawalStatus::Initiated)]);
+
+        store.update_statuses(&[
+            (B256::repeat_byte(1), WithdrawalStatus::Finalized),
+            (B256::repeat_byte(9), WithdrawalStatus::Finalized),
+        ]);
+
+        assert_eq!(
+            store.withdrawal(&B256::repeat_byte(1)).unwrap().status,
+            RecordStatus::Finalized
+        );
+        assert!(store.pending_withdrawals().is_empty());
+        assert_eq!(store.withdrawal_count(), 1);
+    }
+
+    #[test]
+    fn test_record_proof_survives_upsert() {
+        let mut store = StateStore::load(temp_path("proof")).unwrap();
+        let initiated = withdrawal(1, 20, WithdrawalStatus::Initiated);
+        let proof = ProofRecord {
+            dispute_game_index: U256::from(7),
+            dispute_game_proxy: Address::repeat_byte(0x22),
+            tx_hash: TxHash::repeat_byte(0x33),
+        };
+
+        // Unknown withdrawals are added
+        store.record_proof(&initiated, proof);
+        assert_eq!(store.withdrawal_count(), 1);
+
+        // A later scan refreshes the status without dropping the proof
+        store.upsert_withdrawals(&[withdrawal(1, 20, proven(5))]);
+        let record = store.withdrawal(&B256::repeat_byte(1)).unwrap();
+        assert_eq!(record.proof, Some(proof));
+        assert_eq!(
+            record.status,
+            RecordStatus::Proven {
+                timestamp: 5,
+                dispute_game_proxy: Address::repeat_byte(0x22),
+                proof_submitter: Address::repeat_byte(0x33),
+            }
+        );
+    }
+
+    #[test]
+    fn test_record_initiation_survives_upsert() {
+        let mut store = StateStore::load(temp_path("initiation")).unwrap();
+        let initiation = InitiationRecord {
+            tx_hash: TxHash::repeat_byte(0x44),
+            gas_used: 95_000,
+            effective_gas_price: 1_000_250,
+        };
+        assert_eq!(
+            initiation.execution_fee_wei(),
+            U256::from(95_023_750_000_u64)
+        );
+
+        // Sent by this instance before any scan saw it
+        store.record_initiation(&withdrawal(1, 20, WithdrawalStatus::Initiated), initiation);
+        store.upsert_withdrawals(&[withdrawal(1, 20, proven(5))]);
+        assert_eq!(
+            store.withdrawal(&B256::repeat_byte(1)).unwrap().initiation,
+            Some(initiation)
+        );
+
+        // Looked up later: only known withdrawals are updated
+        store.upsert_withdrawals(&[withdrawal(2, 30, WithdrawalStatus::Initiated)]);
+        store.set_initiation(&B256::repeat_byte(2), initiation);
+        store.set_initiation(&B256::repeat_byte(9), initiation);
+        assert_eq!(
+            store.withdrawal(&B256::repeat_byte(2)).unwrap().initiation,
+            Some(initiation)
+        );
+        assert_eq!(store.withdrawal_count(), 2);
+    }
+
+    #[test]
+    fn test_record_without_proof_fields_loads() {
+        let raw = r#"{
+            "withdrawals_scanned_to": 1,
+            "withdrawals": {
+                "0x0101010101010101010101010101010101010101010101010101010101010101": {
+                    "hash": "0x0101010101010101010101010101010101010101010101010101010101010101",
+                    "l2_block": 20,
+                    "nonce": "0x1",
+                    "sender": "0x0101010101010101010101010101010101010101",
+                    "target": "0x0101010101010101010101010101010101010101",
+                    "value": "0x3e8",
+                    "gas_limit": "0x186a0",
+                    "data": "0x",
+                    "status": { "state": "proven", "timestamp": 5 }
+                }
+            }
+        }"#;
+
+        let contents: StoreContents = serde_json::from_str(raw).unwrap();
+        let record = &contents.withdrawals[&B256::repeat_byte(1)];
+        assert_eq!(record.proof, None);
+        assert_eq!(
+            record.status,
+            RecordStatus::Proven {
+                timestamp: 5,
+                dispute_game_proxy: Address::ZERO,
+                proof_submitter: Address::ZERO,
+            }
+        );
+    }
+
+    fn leg(cost_wei: Option<u64>) -> LegCost {
+        LegCost {
+            tx_hash: Some(B256::repeat_byte(0xaa)),
+            cost_wei: cost_wei.map(U256::from),
+        }
+    }
+
+    fn cost_record(hash: u8, finalized_at: u64, prove: LegCost) -> WithdrawalCostRecord {
+        WithdrawalCostRecord {
+            hash: B256::repeat_byte(hash),
+            value: U256::from(1_000_000),
+            finalized_at,
+            initiate: leg(Some(100)),
+            prove,
+            finalize: leg(Some(300)),
+            initiation: None,
+        }
+    }
+
+    #[test]
+    fn test_cost_ratio_marks_unknown_legs() {
+        let known = cost_record(1, 10, leg(Some(600)));
+        assert_eq!(known.total_cost_wei(), Some(U256::from(1000)));
+        assert_eq!(known.cost_ratio(), Some(0.001));
+
+        // A leg sent by a third party must not count as free
+        let third_party = cost_record(2, 10, leg(None));
+        assert_eq!(third_party.total_cost_wei(), None);
+        assert_eq!(third_party.cost_ratio(), None);
+    }
+
+    #[test]
+    fn test_withdrawal_costs_roundtrip() {
+        let path = temp_path("costs");
+        let mut store = StateStore::load(&path).unwrap();
+        store.record_withdrawal_cost(cost_record(1, 30, leg(Some(600))));
+        store.record_withdrawal_cost(cost_record(2, 10, leg(None)));
+        store.record_withdrawal_cost(cost_record(3, 20, leg(Some(600))));
+        store.save().unwrap();
+
+        let store = StateStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(store.has_withdrawal_cost(&B256::repeat_byte(2)));
+        assert_eq!(
+            store
+                .withdrawal_costs_since(20)
+                .iter()
+                .map(|r| r.finalized_at)
+                .collect::<Vec<_>>(),
+            vec![20, 30]
+        );
+        assert_eq!(store.withdrawal_costs_since(0)[0].prove.cost_wei, None);
+    }
+
+    #[test]
+    fn test_deposit_checkpoint_roundtrip() {
+        let path = temp_path("deposits");
+        let mut store = StateStore::load(&path).unwrap();
+        assert!(store.deposit_checkpoint().scanned_to.is_none());
+
+        let deposit = InFlightDeposit {
+            deposit_id: U256::from(42),
+            origin_chain_id: 1,
+            destination_chain_id: 130,
+            input_amount: U256::from(1000),
+            output_amount: U256::from(990),
+            depositor: Address::repeat_byte(1),
+            block_number: 100,
+            fill_deadline: 1_700_000_000,
+        };
+        store.set_deposit_checkpoint(&DepositCheckpoint {
+            scanned_to: Some(150),
+            inflight: vec![deposit],
+        });
+        store.save().unwrap();
+
+        let store = StateStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let checkpoint = store.deposit_checkpoint();
+        assert_eq!(checkpoint.scanned_to, Some(150));
+        assert_eq!(checkpoint.inflight.len(), 1);
+        assert_eq!(checkpoint.inflight[0].deposit_id, U256::from(42));
+        assert_eq!(checkpoint.inflight[0].input_amount, U256::from(1000));
+        assert_eq!(checkpoint.inflight[0].fill_deadline, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_finalized_set_survives_restart() {
+        let path = temp_path("finalized");
+        let mut store = StateStore::load(&path).unwrap();
+        store.upsert_withdrawals(&[withdrawal(1, 10, WithdrawalStatus::Finalized)]);
+
+        // Observed during a scan through a clone of the set
+        let finalized = store.finalized_set();
+        finalized.clone().insert(B256::repeat_byte(2));
+        store.set_finalized(&finalized);
+        store.set_finalized_verified_at(1_700_000_000);
+        store.save().unwrap();
+
+        let store = StateStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let finalized = store.finalized_set();
+        assert!(finalized.contains(B256::repeat_byte(1)));
+        assert!(finalized.contains(B256::repeat_byte(2)));
+        assert!(!finalized.contains(B256::repeat_byte(3)));
+        assert_eq!(store.finalized_verified_at(), Some(1_700_000_000));
+    }
+
+    #[test]
+    fn test_pending_transactions_survive_restart() {
+        let path = temp_path("pending_txs");
+        let mut store = StateStore::load(&path).unwrap();
+        let pending = store.pending_transactions();
+        pending.record(
+            B256::repeat_byte(1),
+            TransactionKind::FinalizeWithdrawal,
+            B256::repeat_byte(0xaa),
+        );
+        assert!(store.set_pending_transactions(pending.entries()));
+        assert!(!store.set_pending_transactions(pending.entries()));
+        store.save().unwrap();
+
+        let store = StateStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let pending = store.pending_transactions();
+        assert_eq!(
+            pending.get(B256::repeat_byte(1), TransactionKind::FinalizeWithdrawal),
+            Some(B256::repeat_byte(0xaa))
+        );
+        assert_eq!(pending.entries().len(), 1);
+    }
+
+    #[test]
+    fn test_sent_transactions_are_capped_per_chain() {
+        let path = temp_path("sent");
+        let mut store = StateStore::load(&path).unwrap();
+
+        let count = MAX_SENT_TRANSACTIONS as u64 + 2;
+        for nonce in 0..count {
+            store.record_sent_transaction(
+                &TransactionRequest::default()
+                    .nonce(nonce)
+                    .with_chain_id(130),
+            );
+        }
+        // Missing nonce: not recorded
+        store.record_sent_transaction(&TransactionRequest::default().with_chain_id(130));
+        store.save().unwrap();
+
+        let store = StateStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(store.sent_transaction(130, 1).is_none());
+        assert_eq!(
+            store.sent_transaction(130, count - 1).unwrap().nonce,
+            Some(count - 1)
+        );
+        assert!(store.sent_transaction(1, count - 1).is_none());
+    }
+}
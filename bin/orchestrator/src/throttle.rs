@@ -0,0 +1,176 @@
+//! Throttling of log lines that repeat every cycle.
+//!
+//! A withdrawal that can't be proven for hours, or a deposit skipped for the same reason
+//! cycle after cycle, would log the same line every 30 seconds and bury new problems.
+//! [`ThrottledLogger`] lets the first occurrence through, suppresses repeats for a
+//! window and reports how many were suppressed when the next one is let through.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::field::{display, DisplayValue};
+use withdrawal::types::WithdrawalHash;
+
+/// Key of a withdrawal failure log: the withdrawal and what failed, e.g. `"prove"`.
+pub type WithdrawalLogKey = (WithdrawalHash, &'static str);
+
+/// Number of times a log line was suppressed before the one let through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeats(pub u64);
+
+impl Repeats {
+    /// Field value for the log line, omitted when nothing was suppressed.
+    pub fn note(self) -> Option<DisplayValue<Self>> {
+        (self.0 > 0).then(|| display(self))
+    }
+}
+
+impl fmt::Display for Repeats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "previous message repeated {} times", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    logged_at: Instant,
+    suppressed: u64,
+}
+
+/// Lets a log line with a given key through at most once per window.
+///
+/// Clones share the same state. The default has a zero window, i.e. lets every line
+/// through, which is what a single step run on its own wants.
+#[derive(Debug, Clone)]
+pub struct ThrottledLogger<K> {
+    window: Duration,
+    entries: Arc<Mutex<HashMap<K, Entry>>>,
+}
+
+impl<K> Default for ThrottledLogger<K> {
+    fn default() -> Self {
+        Self::new(Duration::ZERO)
+    }
+}
+
+impl<K> ThrottledLogger<K> {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Arc::default(),
+        }
+    }
+}
+
+impl<K: Hash + Eq> ThrottledLogger<K> {
+    /// Whether to log the line for `key` now.
+    ///
+    /// Returns the number of repeats suppressed since it was last logged if so, and
+    /// counts this one as suppressed otherwise.
+    pub fn check(&self, key: K) -> Option<Repeats> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: K, now: Instant) -> Option<Repeats> {
+        let mut entries = self.entries.lock().expect("log throttle lock poisoned");
+        if let Some(entry) = entries.get_mut(&key) {
+            if now.saturating_duration_since(entry.logged_at) < self.window {
+                entry.suppressed += 1;
+                return None;
+            }
+        }
+
+        let suppressed = entries.remove(&key).map_or(0, |entry| entry.suppressed);
+        // Lines that stopped repeating are forgotten once their window has passed
+        let window = self.window;
+        entries.retain(|_, entry| now.saturating_duration_since(entry.logged_at) < window);
+        entries.insert(
+            key,
+            Entry {
+                logged_at: now,
+                suppressed: 0,
+            },
+        );
+        Some(Repeats(suppressed))
+    }
+
+    /// Forget `key`, e.g. once the condition it logs has cleared, so the next
+    /// occurrence is logged right away.
+    pub fn clear(&self, key: &K) {
+        self.entries
+            .lock()
+            .expect("log throttle lock poisoned")
+            .remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(600);
+
+    #[test]
+    fn test_suppresses_repeats_within_window() {
+        let throttle = ThrottledLogger::new(WINDOW);
+        let start = Instant::now();
+
+        assert_eq!(throttle.check_at("skip", start), Some(Repeats(0)));
+        for i in 1..=119 {
+            assert_eq!(
+                throttle.check_at("skip", start + Duration::from_secs(i * 5)),
+                None
+            );
+        }
+
+        // Next line after the window reports what was suppressed
+        assert_eq!(
+            throttle.check_at("skip", start + WINDOW),
+            Some(Repeats(119))
+        );
+        assert_eq!(
+            throttle.check_at("skip", start + WINDOW + Duration::from_secs(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_keys_are_throttled_independently() {
+        let throttle = ThrottledLogger::new(WINDOW);
+        let start = Instant::now();
+        let hash = WithdrawalHash::repeat_byte(1);
+
+        assert!(throttle.check_at((hash, "prove"), start).is_some());
+        assert!(throttle.check_at((hash, "finalize"), start).is_some());
+        assert!(throttle
+            .check_at((WithdrawalHash::repeat_byte(2), "prove"), start)
+            .is_some());
+        assert!(throttle.check_at((hash, "prove"), start).is_none());
+
+        // Cleared keys are logged right away
+        throttle.clear(&(hash, "prove"));
+        assert_eq!(throttle.check_at((hash, "prove"), start), Some(Repeats(0)));
+    }
+
+    #[test]
+    fn test_default_logs_every_line() {
+        let throttle = ThrottledLogger::default();
+        let now = Instant::now();
+
+        assert_eq!(throttle.check_at("skip", now), Some(Repeats(0)));
+        assert_eq!(throttle.check_at("skip", now), Some(Repeats(0)));
+    }
+
+    #[test]
+    fn test_repeats_note() {
+        assert!(Repeats(0).note().is_none());
+        assert_eq!(
+            Repeats(119).to_string(),
+            "previous message repeated 119 times"
+        );
+    }
+}
@@ -25,7 +25,7 @@ async fn test_l1_native_balance_query() {
     println!("L1 EOA: {}", config.eoa_address);
 
     // Create provider and monitor
-    let provider = client::create_provider(&config.l1_rpc_url)
+    let provider = client::create_provider(&config.l1_rpc_url, config.provider_timeouts())
         .await
         .expect("Failed to create L1 provider");
 
@@ -53,13 +53,13 @@ async fn test_both_chains_integration() {
     println!("Testing full integration with both L1 and L2");
 
     // Create L1 provider and monitor
-    let l1_provider = client::create_provider(&config.l1_rpc_url)
+    let l1_provider = client::create_provider(&config.l1_rpc_url, config.provider_timeouts())
         .await
         .expect("Failed to create L1 provider");
     let l1_monitor = BalanceMonitor::new(l1_provider);
 
     // Create L2 provider and monitor
-    let l2_provider = client::create_provider(&config.l2_rpc_url)
+    let l2_provider = client::create_provider(&config.l2_rpc_url, config.provider_timeouts())
         .await
         .expect("Failed to create L2 provider");
     let l2_monitor = BalanceMonitor::new(l2_provider);
@@ -104,7 +104,7 @@ async fn test_l2_spoke_pool_weth_balance() {
     println!("WETH Token: {}", weth_address);
 
     // Create provider and monitor
-    let provider = client::create_provider(&config.l2_rpc_url)
+    let provider = client::create_provider(&config.l2_rpc_url, config.provider_timeouts())
         .await
         .expect("Failed to create L2 provider");
 
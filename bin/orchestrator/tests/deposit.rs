@@ -10,7 +10,7 @@
 mod setup;
 
 use action::{
-    deposit::{DepositAction, DepositConfig},
+    deposit::{DepositAction, DepositConfig, FillDeadline, OutputBounds},
     Action,
 };
 use alloy_primitives::{Address, Bytes, U256};
@@ -18,32 +18,29 @@ use config::NetworkConfig;
 use setup::{load_test_config, mock_signer, setup_provider, setup_signer};
 
 /// Helper to create a test deposit config for Ethereum -> Unichain
+///
+/// The fill deadline is the default relative window, as in `maybe_deposit`.
 fn create_test_deposit_config(depositor: Address, network_config: &NetworkConfig) -> DepositConfig {
     // Use small amounts for testing
     let input_amount = U256::from(1_000_000); // 1M wei = 0.000001 ETH (very small amount)
     let output_amount = U256::from(2_000_000); // Make it higher than input amount which will guarantee slow fill.
 
-    // Calculate fill_deadline as now + 2 hours
-    let two_hours_in_seconds = 2 * 60 * 60; // 7200 seconds
-    let current_timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_secs() as u32;
-    let fill_deadline = current_timestamp + two_hours_in_seconds;
-
     DepositConfig {
         spoke_pool: network_config.ethereum.spoke_pool,
         depositor,
         recipient: depositor,
+        allowed_recipients: vec![],
         input_token: network_config.ethereum.weth, // WETH on Ethereum
         output_token: network_config.unichain.weth, // WETH on Unichain
         input_amount,
         output_amount,
+        output_bounds: OutputBounds::default(),
         destination_chain_id: network_config.unichain.chain_id,
         exclusive_relayer: Address::ZERO, // No exclusive relayer
-        fill_deadline,
+        fill_deadline: FillDeadline::default(),
         exclusivity_parameter: 0, // No exclusivity period
         message: Bytes::new(),
+        deposit_nonce: None,
     }
 }
 
@@ -187,7 +184,7 @@ async fn test_deposit_action_execute() {
 
     // Use provider and signer
     let provider = setup_provider(&config.l1_rpc_url).await;
-    let signer = setup_signer();
+    let (signer, _spend) = setup_signer(&provider).await;
 
     println!("\nTest Depositor: {}", config.eoa_address);
     println!("Make sure the depositor has sufficient ETH for the deposit + gas");
@@ -207,10 +204,7 @@ async fn test_deposit_action_execute() {
         "  Destination Chain: {}",
         deposit_config.destination_chain_id
     );
-    println!(
-        "  Fill Deadline: {} (unix timestamp)",
-        deposit_config.fill_deadline
-    );
+    println!("  Fill Deadline: {:?}", deposit_config.fill_deadline);
     println!(
         "  Exclusivity Parameter: {}",
         deposit_config.exclusivity_parameter
@@ -225,16 +219,19 @@ async fn test_deposit_action_execute() {
         "\nCurrent Timestamp: {} (unix timestamp)",
         current_timestamp
     );
-    println!(
-        "Time until deadline: {} seconds",
-        deposit_config
-            .fill_deadline
-            .saturating_sub(current_timestamp)
-    );
 
     // Create deposit action
     let mut action = DepositAction::new(provider, signer, deposit_config);
 
+    let fill_deadline = action
+        .resolve_fill_deadline(current_timestamp)
+        .await
+        .expect("Failed to resolve fill deadline");
+    println!(
+        "Time until deadline: {} seconds",
+        fill_deadline.saturating_sub(current_timestamp)
+    );
+
     // Verify action is ready
     assert!(
         action.is_ready().await.expect("Failed to check is_ready"),
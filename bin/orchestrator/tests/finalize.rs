@@ -4,16 +4,32 @@
 //! - Find a proven withdrawal on L1
 //! - Check if proof maturity delay has passed
 //! - Execute real finalize transaction
+//! - Recover proven withdrawals that fell out of the lookback
+//! - Wait for a withdrawal to be finalized
 
 use crate::setup::{load_test_config, setup_provider, setup_signer};
 use action::{
     finalize::{Finalize, FinalizeAction},
+    pending::PendingTransactions,
     Action,
 };
 use alloy_provider::Provider;
 use alloy_rpc_types_eth::BlockNumberOrTag;
 use binding::opstack::{MESSAGE_PASSER_ADDRESS, SECONDS_PER_DAY, SECONDS_PER_HOUR};
-use withdrawal::{state::WithdrawalStateProvider, types::WithdrawalStatus};
+use client::{coverage::ScanCoverage, retry::RetryBudget};
+use orchestrator::{
+    process_pending_withdrawals, recovery::recover_proven_withdrawals, reorg::RegressionGuard,
+    throttle::ThrottledLogger,
+};
+use std::time::{Duration, Instant};
+use withdrawal::{
+    finalized::FinalizedSet,
+    portal::PortalParamsCache,
+    proof::StorageLayoutCheck,
+    state::WithdrawalStateProvider,
+    types::WithdrawalStatus,
+    wait::{wait_for_finalization, FinalizationOutcome},
+};
 
 #[path = "setup.rs"]
 mod setup;
@@ -44,7 +60,7 @@ async fn test_finalize_action_execute() {
     // Use provider and signer for L1 (needs to sign transactions)
     let l1_provider = setup_provider(&config.l1_rpc_url).await;
     let l2_provider = setup_provider(&config.l2_rpc_url).await;
-    let l1_signer = setup_signer();
+    let (l1_signer, _spend) = setup_signer(&l1_provider).await;
 
     // Find pending withdrawals
     let state_provider = WithdrawalStateProvider::new(
@@ -87,7 +103,7 @@ async fn test_finalize_action_execute() {
 
     let withdrawal = proven_withdrawal.unwrap();
     let proven_timestamp = match withdrawal.status {
-        WithdrawalStatus::Proven { timestamp } => timestamp,
+        WithdrawalStatus::Proven { timestamp, .. } => timestamp,
         _ => unreachable!(),
     };
 
@@ -100,13 +116,9 @@ async fn test_finalize_action_execute() {
     println!("  Proven at timestamp: {}", proven_timestamp);
 
     // Create finalize action
-    let finalize = Finalize {
-        portal_address: config.network_config().unichain.l1_portal,
-        withdrawal: withdrawal.transaction.clone(),
-        withdrawal_hash: withdrawal.hash,
-        proof_submitter: config.eoa_address, // Assuming we proved it ourselves
-        from: config.eoa_address,
-    };
+    // The proof to finalize with is selected from every submitter's proofs on L1
+    let finalize = Finalize::from_pending(withdrawal, &config.network_config().unichain)
+        .expect("Failed to build finalize input");
 
     let mut action = FinalizeAction::new(l1_provider, l2_provider, l1_signer, finalize);
 
@@ -215,7 +227,7 @@ async fn test_check_proven_withdrawal_status() {
 
     for withdrawal in &withdrawals {
         match withdrawal.status {
-            WithdrawalStatus::Proven { timestamp } => {
+            WithdrawalStatus::Proven { timestamp, .. } => {
                 let ready_at = timestamp + maturity_delay_secs;
                 if current_timestamp >= ready_at {
                     println!(
@@ -241,3 +253,172 @@ async fn test_check_proven_withdrawal_status() {
         }
     }
 }
+
+/// Test that a proven withdrawal older than the lookback is still finalized
+///
+/// This test:
+/// 1. Shrinks the lookback so the regular scan no longer sees older proven withdrawals
+/// 2. Runs the recovery scan and checks it finds them
+/// 3. Feeds them through process_pending_withdrawals, which finalizes mature ones
+#[tokio::test]
+#[ignore = "requires a proven withdrawal older than one day onchain and submits actual transaction - run with: just run-recovery"]
+async fn test_recover_proven_withdrawal() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init();
+
+    let mut config = load_test_config();
+    config.withdrawal_lookback_secs = SECONDS_PER_DAY;
+    config.withdrawal_recovery_lookback_secs = 8 * 7 * SECONDS_PER_DAY;
+
+    let l1_provider = setup_provider(&config.l1_rpc_url).await;
+    let l2_provider = setup_provider(&config.l2_rpc_url).await;
+    let (l1_signer, _spend) = setup_signer(&l1_provider).await;
+
+    let retry_budget = RetryBudget::unlimited();
+    let scan_coverage = ScanCoverage::default();
+    let recovered = recover_proven_withdrawals(
+        l1_provider.clone(),
+        l2_provider.clone(),
+        &config,
+        &retry_budget,
+        &scan_coverage,
+    )
+    .await
+    .expect("Failed to run recovery scan");
+
+    println!("Recovered {} proven withdrawals", recovered.len());
+    if recovered.is_empty() {
+        println!("⚠ No proven, unfinalized withdrawals older than the lookback");
+        println!("  Prove a withdrawal and wait at least a day, then run this test");
+        return;
+    }
+
+    let hashes: Vec<_> = recovered.iter().map(|w| w.hash).collect();
+    for withdrawal in &recovered {
+        println!("  {} (L2 block {})", withdrawal.hash, withdrawal.l2_block);
+        assert!(matches!(withdrawal.status, WithdrawalStatus::Proven { .. }));
+    }
+
+    process_pending_withdrawals(
+        l1_provider.clone(),
+        l2_provider.clone(),
+        l1_signer,
+        &config,
+        recovered,
+        &retry_budget,
+        &scan_coverage,
+        &PortalParamsCache::default(),
+        &FinalizedSet::default(),
+        &StorageLayoutCheck::default(),
+        &ThrottledLogger::default(),
+        &PendingTransactions::default(),
+        &RegressionGuard::default(),
+        config.cycle_deadline(Instant::now()),
+    )
+    .await
+    .expect("Failed to process recovered withdrawals");
+
+    let state_provider = WithdrawalStateProvider::new(
+        l1_provider,
+        l2_provider,
+        config.network_config().unichain.l1_portal,
+        MESSAGE_PASSER_ADDRESS,
+    );
+    for hash in hashes {
+        let finalized = state_provider.is_finalized(hash).await.unwrap();
+        println!(
+            "  {} - {}",
+            hash,
+            if finalized {
+                "FINALIZED"
+            } else {
+                "not finalized (proof not mature yet)"
+            }
+        );
+    }
+}
+
+/// Test the prove → finalize pipeline end to end with `wait_for_finalization`
+///
+/// This test:
+/// 1. Scans L2 for pending withdrawals for the configured EOA
+/// 2. Feeds them through process_pending_withdrawals, finalizing mature ones
+/// 3. Waits for the oldest proven one to be finalized and checks it succeeded
+#[tokio::test]
+#[ignore = "requires a proven withdrawal past maturity delay onchain and submits actual transaction - run with: just run-wait"]
+async fn test_wait_for_finalization() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .try_init();
+
+    let config = load_test_config();
+    let portal = config.network_config().unichain.l1_portal;
+
+    let l1_provider = setup_provider(&config.l1_rpc_url).await;
+    let l2_provider = setup_provider(&config.l2_rpc_url).await;
+    let (l1_signer, _spend) = setup_signer(&l1_provider).await;
+
+    let state_provider = WithdrawalStateProvider::new(
+        l1_provider.clone(),
+        l2_provider.clone(),
+        portal,
+        MESSAGE_PASSER_ADDRESS,
+    );
+    let current_block = l2_provider.get_block_number().await.unwrap();
+    let withdrawals = state_provider
+        .get_pending_withdrawals(
+            BlockNumberOrTag::Number(current_block.saturating_sub(1_200_000)),
+            BlockNumberOrTag::Latest,
+            config.eoa_address,
+        )
+        .await
+        .expect("Failed to scan withdrawals");
+
+    let Some(withdrawal) = withdrawals
+        .iter()
+        .find(|w| matches!(w.status, WithdrawalStatus::Proven { .. }))
+        .cloned()
+    else {
+        println!("⚠ No proven withdrawals found - cannot test the pipeline");
+        println!("  Prove a withdrawal first and wait for the maturity delay, then run this test");
+        return;
+    };
+    println!("Waiting for withdrawal {}", withdrawal.hash);
+
+    process_pending_withdrawals(
+        l1_provider.clone(),
+        l2_provider.clone(),
+        l1_signer,
+        &config,
+        vec![],
+        &RetryBudget::unlimited(),
+        &ScanCoverage::default(),
+        &PortalParamsCache::default(),
+        &FinalizedSet::default(),
+        &StorageLayoutCheck::default(),
+        &ThrottledLogger::default(),
+        &PendingTransactions::default(),
+        &RegressionGuard::default(),
+        config.cycle_deadline(Instant::now()),
+    )
+    .await
+    .expect("Failed to process pending withdrawals");
+
+    let outcome = wait_for_finalization(
+        l1_provider,
+        l2_provider,
+        portal,
+        withdrawal.hash,
+        Duration::from_secs(12),
+        Duration::from_secs(10 * 60),
+        |stage| println!("  stage: {stage:?}"),
+    )
+    .await
+    .expect("Failed to wait for finalization");
+
+    assert!(
+        matches!(outcome, FinalizationOutcome::Succeeded { .. }),
+        "Withdrawal not finalized successfully: {outcome:?}"
+    );
+}
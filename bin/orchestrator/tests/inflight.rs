@@ -63,8 +63,8 @@ async fn test_get_inflight_deposits_no_deposits() {
         network.unichain.chain_id,
         network.ethereum.chain_id,
         3600, // 1 hour lookback
-        network.ethereum.block_time_secs,
-        network.unichain.block_time_secs,
+        network.ethereum.block_time_ms,
+        network.unichain.block_time_ms,
     )
     .await
     .expect("Failed to get in-flight deposits");
@@ -98,8 +98,8 @@ async fn test_get_inflight_deposits_scan() {
 
     // Use 12 hour lookback (matches default config)
     let lookback_secs = 43200;
-    let l1_lookback_blocks = lookback_secs / network.ethereum.block_time_secs;
-    let l2_lookback_blocks = lookback_secs / network.unichain.block_time_secs;
+    let l1_lookback_blocks = network.ethereum.blocks_in(lookback_secs);
+    let l2_lookback_blocks = network.unichain.blocks_in(lookback_secs);
 
     println!(
         "Lookback: {} seconds ({} L1 blocks, {} L2 blocks)",
@@ -115,8 +115,8 @@ async fn test_get_inflight_deposits_scan() {
         network.unichain.chain_id,
         network.ethereum.chain_id,
         lookback_secs,
-        network.ethereum.block_time_secs,
-        network.unichain.block_time_secs,
+        network.ethereum.block_time_ms,
+        network.unichain.block_time_ms,
     )
     .await
     .expect("Failed to get in-flight deposits");
@@ -155,8 +155,8 @@ async fn test_get_inflight_deposit_total() {
         network.unichain.chain_id,
         network.ethereum.chain_id,
         43200, // 12 hours
-        network.ethereum.block_time_secs,
-        network.unichain.block_time_secs,
+        network.ethereum.block_time_ms,
+        network.unichain.block_time_ms,
     )
     .await
     .expect("Failed to get in-flight deposit total");
@@ -181,18 +181,18 @@ async fn test_lookback_calculation() {
     let lookback_secs: u64 = 43200;
 
     // L1 (Ethereum): 12 second blocks
-    let l1_blocks = lookback_secs / network.ethereum.block_time_secs;
+    let l1_blocks = network.ethereum.blocks_in(lookback_secs);
     println!(
-        "L1 lookback: {} seconds / {} block_time = {} blocks",
-        lookback_secs, network.ethereum.block_time_secs, l1_blocks
+        "L1 lookback: {} seconds / {}ms block_time = {} blocks",
+        lookback_secs, network.ethereum.block_time_ms, l1_blocks
     );
     assert_eq!(l1_blocks, 3600); // 43200 / 12 = 3600 blocks
 
     // L2 (Unichain): 1 second blocks
-    let l2_blocks = lookback_secs / network.unichain.block_time_secs;
+    let l2_blocks = network.unichain.blocks_in(lookback_secs);
     println!(
-        "L2 lookback: {} seconds / {} block_time = {} blocks",
-        lookback_secs, network.unichain.block_time_secs, l2_blocks
+        "L2 lookback: {} seconds / {}ms block_time = {} blocks",
+        lookback_secs, network.unichain.block_time_ms, l2_blocks
     );
     assert_eq!(l2_blocks, 43200); // 43200 / 1 = 43200 blocks
 
@@ -212,8 +212,8 @@ async fn test_long_lookback_scan_slow() {
 
     // 24 hour lookback
     let lookback_secs = 86400 * 7;
-    let l1_blocks = lookback_secs / network.ethereum.block_time_secs;
-    let l2_blocks = lookback_secs / network.unichain.block_time_secs;
+    let l1_blocks = network.ethereum.blocks_in(lookback_secs);
+    let l2_blocks = network.unichain.blocks_in(lookback_secs);
 
     println!("Scanning {} L1 blocks, {} L2 blocks", l1_blocks, l2_blocks);
 
@@ -226,8 +226,8 @@ async fn test_long_lookback_scan_slow() {
         network.unichain.chain_id,
         network.ethereum.chain_id,
         lookback_secs,
-        network.ethereum.block_time_secs,
-        network.unichain.block_time_secs,
+        network.ethereum.block_time_ms,
+        network.unichain.block_time_ms,
     )
     .await
     .expect("Failed to get in-flight deposits");
@@ -7,7 +7,7 @@
 
 use crate::setup::{load_test_config, setup_provider, setup_signer};
 use action::{
-    prove::{Prove, ProveAction},
+    prove::{ProofLimits, Prove, ProveAction},
     Action,
 };
 use alloy_provider::Provider;
@@ -48,7 +48,7 @@ async fn test_prove_action_execute() {
     // Use provider and signer for L1 (needs to sign transactions)
     let l1_provider = setup_provider(&config.l1_rpc_url).await;
     let l2_provider = setup_provider(&config.l2_rpc_url).await;
-    let l1_signer = setup_signer();
+    let (l1_signer, _spend) = setup_signer(&l1_provider).await;
 
     // Find pending withdrawals
     let state_provider = WithdrawalStateProvider::new(
@@ -98,14 +98,14 @@ async fn test_prove_action_execute() {
     println!("  Value: {}", withdrawal.transaction.value);
 
     // Create prove action
-    let prove = Prove {
-        portal_address: config.network_config().unichain.l1_portal,
-        factory_address: config.network_config().unichain.l1_dispute_game_factory,
-        withdrawal: withdrawal.transaction.clone(),
-        withdrawal_hash: withdrawal.hash,
-        l2_block: withdrawal.l2_block,
-        from: config.eoa_address,
-    };
+    let prove = Prove::from_pending(withdrawal, &config.network_config().unichain)
+        .expect("Failed to build prove input")
+        .with_min_game_count(config.min_dispute_games)
+        .with_allowed_game_types(config.allowed_game_types.clone())
+        .with_proof_limits(ProofLimits::new(
+            config.max_proof_nodes,
+            config.max_proof_bytes,
+        ));
 
     let mut action = ProveAction::new(l1_provider, l2_provider, l1_signer, prove);
 
@@ -156,7 +156,8 @@ async fn test_prove_action_execute() {
 async fn test_debug_output_root_proof() {
     use alloy_primitives::keccak256;
     use binding::opstack::{IDisputeGameFactory, IFaultDisputeGame};
-    use withdrawal::proof::generate_proof;
+    use client::retry::RetryBudget;
+    use withdrawal::{game::GameCache, portal::PortalParamsCache, proof::generate_proof};
 
     let _ = tracing_subscriber::fmt()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
@@ -200,10 +201,16 @@ async fn test_debug_output_root_proof() {
         &l1_provider,
         &l2_provider,
         config.network_config().unichain.l1_portal,
-        config.network_config().unichain.l1_dispute_game_factory,
+        config.network_config().unichain.l2_to_l1_message_passer,
+        &PortalParamsCache::default(),
+        &GameCache::default(),
         withdrawal.hash,
         withdrawal.transaction.clone(),
         withdrawal.l2_block,
+        config.min_dispute_games,
+        &config.allowed_game_types,
+        config.game_selection_strategy,
+        &RetryBudget::unlimited(),
     )
     .await
     .expect("Failed to generate proof");
@@ -1,19 +1,41 @@
 //! Common test setup utilities shared across integration tests.
 #![allow(dead_code)] // used in ignored tests
 
-use action::SignerFn;
-use alloy_provider::{Provider, ProviderBuilder};
-use alloy_signer_local::PrivateKeySigner;
+use action::{FnSigner, TransactionSigner};
+use alloy_primitives::{utils::format_ether, Address, U256};
+use alloy_provider::Provider;
+use client::PrivateKey;
+use config::{EthereumConfig, UnichainConfig};
 use orchestrator::config::Config;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use test_support::{SpendBudget, SpendLimitedSigner};
+
+/// Most the transactions of a test run may spend when no `max_test_spend_wei` is set
+/// (0.01 ETH).
+const DEFAULT_MAX_TEST_SPEND_WEI: u64 = 10_000_000_000_000_000;
+
+/// Chains that transaction-submitting tests may run against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RequireNetwork {
+    /// Refuse mainnet chain ids
+    #[default]
+    Testnet,
+    /// Any chain, mainnet included
+    Any,
+}
 
 /// Local configuration overrides (git-ignored file)
 #[derive(Debug, Default, Deserialize)]
 struct LocalConfig {
-    private_key: Option<String>,
+    private_key: Option<PrivateKey>,
     l1_rpc_url: Option<String>,
     l2_rpc_url: Option<String>,
+    /// Most the transactions of a test run may spend, value plus max gas cost
+    max_test_spend_wei: Option<U256>,
+    #[serde(default)]
+    require_network: RequireNetwork,
 }
 
 /// Load local config overrides from tests/test-config.local.toml
@@ -74,11 +96,11 @@ pub fn load_test_config() -> Config {
 /// 2. tests/test-config.local.toml file (git-ignored)
 ///
 /// Returns None if no private key is found.
-pub fn load_private_key() -> Option<String> {
+pub fn load_private_key() -> Option<PrivateKey> {
     // Try 1: Environment variable
     if let Ok(pk) = std::env::var("PRIVATE_KEY") {
         eprintln!("✓ Loaded private key from PRIVATE_KEY environment variable");
-        return Some(pk);
+        return Some(PrivateKey::from(pk));
     }
 
     // Try 2: Local config file (git-ignored)
@@ -96,7 +118,7 @@ pub fn load_private_key() -> Option<String> {
 
 /// Common test setup: load config and create provider
 pub async fn setup_provider(url: &str) -> impl Provider + Clone {
-    client::create_provider(url)
+    client::create_provider(url, client::ProviderTimeouts::default())
         .await
         .expect("Failed to create L1 provider")
 }
@@ -117,40 +139,114 @@ pub async fn setup_wallet_provider(url: &str) -> impl Provider + Clone {
     );
 
     // Parse private key (handles with or without 0x prefix)
-    let signer: PrivateKeySigner = private_key
-        .parse()
+    let address = client::local_signer_address(private_key.expose_secret())
         .expect("Invalid private key format. Expected hex string with optional 0x prefix.");
+    eprintln!("✓ Created signer with address: {}", address);
 
-    eprintln!("✓ Created signer with address: {}", signer.address());
-
-    // Create wallet from signer
-    let wallet = alloy_network::EthereumWallet::from(signer);
-
-    // Build provider with wallet for signing
-    let url = url.parse().expect("Invalid L1 RPC URL");
-    ProviderBuilder::new().wallet(wallet).connect_http(url)
+    client::create_wallet_provider(url, private_key.expose_secret())
+        .expect("Failed to create wallet provider")
 }
 
 /// Create a mock signer for tests that don't execute transactions.
 /// Will panic if actually called.
-pub fn mock_signer() -> SignerFn {
-    Arc::new(|_tx| Box::pin(async { panic!("mock signer should not be called") }))
+pub fn mock_signer() -> Arc<dyn TransactionSigner> {
+    Arc::new(FnSigner::new(
+        Address::ZERO,
+        None,
+        Arc::new(|_tx| Box::pin(async { panic!("mock signer should not be called") })),
+    ))
+}
+
+/// Spend budget shared by every signer of this test run (test binary).
+///
+/// Read from MAX_TEST_SPEND_WEI or `max_test_spend_wei` in tests/test-config.local.toml,
+/// 0.01 ETH if neither is set.
+fn spend_budget() -> SpendBudget {
+    static BUDGET: OnceLock<SpendBudget> = OnceLock::new();
+    BUDGET
+        .get_or_init(|| {
+            let limit = std::env::var("MAX_TEST_SPEND_WEI").map_or_else(
+                |_| {
+                    load_local_config()
+                        .max_test_spend_wei
+                        .unwrap_or(U256::from(DEFAULT_MAX_TEST_SPEND_WEI))
+                },
+                |limit| limit.parse().expect("Invalid MAX_TEST_SPEND_WEI"),
+            );
+            SpendBudget::new(limit)
+        })
+        .clone()
+}
+
+/// Prints what the test signed when dropped, also when the test panics.
+#[must_use = "the summary is printed when the guard is dropped"]
+pub struct SpendSummary {
+    budget: SpendBudget,
+}
+
+impl Drop for SpendSummary {
+    fn drop(&mut self) {
+        eprintln!(
+            "Spend summary: {} transactions signed, at most {} ETH spent, {} ETH of the {} ETH budget left",
+            self.budget.signed(),
+            format_ether(self.budget.spent()),
+            format_ether(self.budget.remaining()),
+            format_ether(self.budget.limit())
+        );
+    }
 }
 
-/// Create a real SignerFn for tests that do execute transactions.
+/// Create a real signer for tests that do execute transactions on `provider`'s chain.
+///
+/// The signer refuses any transaction that could take the test run over its spend
+/// budget (see [`spend_budget`]). Mainnet chains are refused unless
+/// tests/test-config.local.toml sets `require_network = "any"`. The signer's balance is
+/// printed before the test; keep the returned [`SpendSummary`] until the test ends.
 ///
 /// Requires a private key from either:
 /// - PRIVATE_KEY environment variable, or
 /// - tests/test-config.local.toml file
 ///
 /// # Panics
-/// Panics if no private key is found or if the private key is invalid.
-pub fn setup_signer() -> SignerFn {
+/// Panics if no private key is found, if the private key is invalid, or if the chain is
+/// not allowed.
+pub async fn setup_signer<P: Provider>(provider: &P) -> (Arc<dyn TransactionSigner>, SpendSummary) {
     let private_key = load_private_key().expect(
         "Private key required for transaction signing.\n\
          Set PRIVATE_KEY environment variable or create tests/test-config.local.toml\n\
          See tests/test-config.local.toml.example for template.",
     );
+    let signer = client::LocalSigner::from_private_key(private_key.expose_secret())
+        .expect("Failed to create local signer");
+
+    let chain_id = provider
+        .get_chain_id()
+        .await
+        .expect("Failed to get chain id");
+    let mainnet = [
+        EthereumConfig::mainnet().chain_id,
+        UnichainConfig::mainnet().chain_id,
+    ];
+    assert!(
+        load_local_config().require_network == RequireNetwork::Any || !mainnet.contains(&chain_id),
+        "Refusing to submit test transactions on mainnet chain {chain_id}. \
+         Set require_network = \"any\" in tests/test-config.local.toml to allow it."
+    );
+
+    let address = signer.address();
+    let balance = provider
+        .get_balance(address)
+        .await
+        .expect("Failed to get signer balance");
+    let budget = spend_budget();
+    eprintln!(
+        "✓ Signer {address} holds {} ETH on chain {chain_id}, {} ETH of the spend budget left",
+        format_ether(balance),
+        format_ether(budget.remaining())
+    );
 
-    client::local_signer_fn(&private_key).expect("Failed to create local signer")
+    (
+        Arc::new(SpendLimitedSigner::new(Arc::new(signer), budget.clone())),
+        SpendSummary { budget },
+    )
 }
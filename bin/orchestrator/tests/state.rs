@@ -139,8 +139,8 @@ async fn test_query_withdrawal_status() {
         match (&withdrawal.status, &status) {
             (WithdrawalStatus::Initiated, WithdrawalStatus::Initiated) => {}
             (
-                WithdrawalStatus::Proven { timestamp: t1 },
-                WithdrawalStatus::Proven { timestamp: t2 },
+                WithdrawalStatus::Proven { timestamp: t1, .. },
+                WithdrawalStatus::Proven { timestamp: t2, .. },
             ) => {
                 assert_eq!(t1, t2, "Timestamps should match");
             }
@@ -258,32 +258,46 @@ async fn test_is_proven_check() {
         return;
     }
 
-    // Check proven status for found withdrawals
+    // Check proven status for found withdrawals against the selected submitter's proof
     for withdrawal in &withdrawals {
-        let proven_result = state_provider
-            .is_proven(withdrawal.hash, config.eoa_address)
-            .await
-            .expect("Failed to check proven status");
-
-        match (&withdrawal.status, &proven_result) {
-            (WithdrawalStatus::Initiated, None) => {
-                println!("✓ Initiated withdrawal {} is not proven", withdrawal.hash);
+        match &withdrawal.status {
+            WithdrawalStatus::Initiated => {
+                println!(
+                    "✓ Initiated withdrawal {} has no valid proof",
+                    withdrawal.hash
+                );
             }
-            (WithdrawalStatus::Proven { timestamp }, Some(proven)) => {
+            WithdrawalStatus::Proven {
+                timestamp,
+                dispute_game_proxy,
+                proof_submitter,
+            } => {
+                let proven = state_provider
+                    .is_proven(withdrawal.hash, *proof_submitter)
+                    .await
+                    .expect("Failed to check proven status")
+                    .expect("Selected submitter should have a proof");
                 assert_eq!(
                     timestamp, &proven.timestamp,
                     "Timestamp mismatch for withdrawal {}",
                     withdrawal.hash
                 );
+                assert_eq!(
+                    dispute_game_proxy, &proven.disputeGameProxy,
+                    "Dispute game mismatch for withdrawal {}",
+                    withdrawal.hash
+                );
                 println!(
-                    "✓ Proven withdrawal {} has timestamp {}",
-                    withdrawal.hash, proven.timestamp
+                    "✓ Proven withdrawal {} has timestamp {} (submitter {})",
+                    withdrawal.hash, proven.timestamp, proof_submitter
                 );
             }
-            _ => panic!(
-                "Status/proven mismatch for {}: status={:?}, proven={:?}",
-                withdrawal.hash, withdrawal.status, proven_result
-            ),
+            WithdrawalStatus::Finalized => {
+                panic!(
+                    "Pending scan returned finalized withdrawal {}",
+                    withdrawal.hash
+                )
+            }
         }
     }
 
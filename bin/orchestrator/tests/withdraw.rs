@@ -3,7 +3,7 @@ use action::{
     withdraw::{Withdraw, WithdrawAction},
     Action,
 };
-use alloy_primitives::{Address, Bytes, U256};
+use alloy_primitives::{Address, U256};
 use alloy_provider::Provider;
 use binding::opstack::MESSAGE_PASSER_ADDRESS;
 
@@ -14,15 +14,7 @@ fn create_test_withdrawal(source: Address, target: Address) -> Withdraw {
     let value = U256::from(1_000_000);
     let gas_limit = U256::from(200_000); // Seems to be common with good buffer
 
-    Withdraw {
-        contract: MESSAGE_PASSER_ADDRESS,
-        source,
-        target,
-        value,
-        gas_limit,
-        data: Bytes::new(),
-        tx_hash: None,
-    }
+    Withdraw::simple_eth(source, target, value, gas_limit).expect("Invalid withdrawal input")
 }
 
 #[tokio::test]
@@ -160,7 +152,7 @@ async fn test_withdraw_action_execute() {
 
     // Use provider and signer
     let provider = setup_provider(&config.l2_rpc_url).await;
-    let signer = setup_signer();
+    let (signer, _spend) = setup_signer(&provider).await;
 
     println!("\nTest Source (L2): {}", config.eoa_address);
     println!("Test Target (L1): {}", config.eoa_address);
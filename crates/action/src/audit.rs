@@ -0,0 +1,491 @@
+//! Append-only audit trail of broadcast transactions.
+//!
+//! Compliance needs a record of every transaction the orchestrator signed that a log
+//! level change can't suppress, so [`AuditLog`] writes JSON lines to its own file instead
+//! of going through `tracing`. Actions broadcast through [`AuditLog::broadcast`], which
+//! records an [`AuditEntry`] as soon as the node accepted the transaction and a second
+//! one for the same `tx_hash` once its receipt is read, so a process killed while
+//! waiting still leaves a record. Transactions that are only simulated or logged are
+//! never broadcast and leave no entry.
+
+use alloy_primitives::{keccak256, Address, Bytes, FixedBytes, TxHash, B256, U256};
+use alloy_provider::{network::Ethereum, PendingTransactionBuilder};
+use alloy_rpc_types::TransactionRequest;
+use alloy_rpc_types_eth::TransactionReceipt;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsString,
+    fs::OpenOptions,
+    future::Future,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::error;
+use withdrawal::rpc::traced;
+
+/// Rotated files kept next to the active one, as `<path>.1` (newest) to `<path>.5`.
+const ROTATED_FILES: u32 = 5;
+
+/// What a broadcast transaction does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    InitiateWithdrawal,
+    ProveWithdrawal,
+    FinalizeWithdrawal,
+    Deposit,
+    ClaimRefund,
+    SpeedUpDeposit,
+    /// Re-send of a stuck transaction with bumped fees
+    Replacement,
+}
+
+/// How a broadcast ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// Included and executed successfully
+    Succeeded {
+        block_number: Option<u64>,
+        gas_used: u64,
+    },
+    /// Included, but reverted
+    Reverted {
+        block_number: Option<u64>,
+        gas_used: u64,
+    },
+    /// Accepted by the node, receipt not seen yet
+    Submitted,
+    /// Rejected by the node or relay, or its receipt could not be read
+    Failed { error: String },
+}
+
+impl AuditOutcome {
+    pub fn from_receipt(receipt: &eyre::Result<TransactionReceipt>) -> Self {
+        match receipt {
+            Ok(receipt) if receipt.status() => Self::Succeeded {
+                block_number: receipt.block_number,
+                gas_used: receipt.gas_used,
+            },
+            Ok(receipt) => Self::Reverted {
+                block_number: receipt.block_number,
+                gas_used: receipt.gas_used,
+            },
+            Err(e) => Self::Failed {
+                error: e.to_string(),
+            },
+        }
+    }
+}
+
+/// One broadcast transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditEntry {
+    /// Unix time the entry was recorded
+    pub timestamp: u64,
+    pub chain_id: Option<u64>,
+    pub action: TransactionKind,
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    /// First four bytes of the calldata, if any
+    pub selector: Option<FixedBytes<4>>,
+    pub value: U256,
+    pub nonce: Option<u64>,
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u128>,
+    pub max_priority_fee_per_gas: Option<u128>,
+    pub calldata_hash: B256,
+    /// Hash of the signed transaction
+    pub tx_hash: TxHash,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawal_hash: Option<B256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposit_id: Option<U256>,
+    pub outcome: AuditOutcome,
+}
+
+impl AuditEntry {
+    /// [`AuditOutcome::Submitted`] entry for `signed_tx`, the signature of `tx`.
+    pub fn new(action: TransactionKind, tx: &TransactionRequest, signed_tx: &Bytes) -> Self {
+        let calldata = tx.input.input().cloned().unwrap_or_default();
+        Self {
+            timestamp: unix_now(),
+            chain_id: tx.chain_id,
+            action,
+            from: tx.from,
+            to: tx.to.and_then(|to| to.to().copied()),
+            selector: calldata.get(..4).map(FixedBytes::from_slice),
+            value: tx.value.unwrap_or_default(),
+            nonce: tx.nonce,
+            gas_limit: tx.gas,
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+            calldata_hash: keccak256(&calldata),
+            tx_hash: keccak256(signed_tx),
+            withdrawal_hash: None,
+            deposit_id: None,
+            outcome: AuditOutcome::Submitted,
+        }
+    }
+
+    /// The same transaction with `outcome`, recorded now.
+    pub fn with_outcome(mut self, outcome: AuditOutcome) -> Self {
+        self.timestamp = unix_now();
+        self.outcome = outcome;
+        self
+    }
+
+    pub const fn with_withdrawal_hash(mut self, hash: B256) -> Self {
+        self.withdrawal_hash = Some(hash);
+        self
+    }
+
+    pub const fn with_deposit_id(mut self, deposit_id: Option<U256>) -> Self {
+        self.deposit_id = deposit_id;
+        self
+    }
+}
+
+/// JSON lines file that [`AuditEntry`]s are appended to, rotated once it reaches
+/// `max_bytes`.
+///
+/// The default writes nothing, for actions run outside the orchestrator. Each entry
+/// opens the file in append mode while holding an exclusive lock on `<path>.lock`, so
+/// several processes (the daemon and a `step` command) can share one file without one
+/// rotating it from under the other.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    max_bytes: u64,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            path: Some(path.into()),
+            max_bytes,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Append `entry`.
+    ///
+    /// The transaction was already broadcast, so a failed write is logged rather than
+    /// returned: failing the action now would only hide that it was sent.
+    pub fn record(&self, entry: &AuditEntry) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(e) = self.append(path, entry) {
+            error!(
+                path = %path.display(),
+                tx_hash = %entry.tx_hash,
+                error = %e,
+                "ALERT: Failed to write transaction audit log entry"
+            );
+        }
+    }
+
+    /// Broadcast a signed transaction through `send` and wait for its receipt, recording
+    /// `entry` once the node accepted it and again with the receipt's outcome.
+    ///
+    /// `annotate` adds what only the receipt tells to the outcome entry.
+    pub async fn broadcast_with(
+        &self,
+        entry: AuditEntry,
+        send: impl Future<Output = eyre::Result<PendingTransactionBuilder<Ethereum>>>,
+        annotate: impl FnOnce(AuditEntry, &TransactionReceipt) -> AuditEntry,
+    ) -> eyre::Result<TransactionReceipt> {
+        let pending = match send.await {
+            Ok(pending) => pending,
+            Err(e) => {
+                self.record(&entry.with_outcome(AuditOutcome::Failed {
+                    error: e.to_string(),
+                }));
+                return Err(e);
+            }
+        };
+        self.record(&entry);
+
+        let receipt = traced("eth_getTransactionReceipt", pending.get_receipt())
+            .await
+            .map_err(eyre::Report::from);
+        let outcome = AuditOutcome::from_receipt(&receipt);
+        let entry = match &receipt {
+            Ok(receipt) => annotate(entry, receipt),
+            Err(_) => entry,
+        };
+        self.record(&entry.with_outcome(outcome));
+        receipt
+    }
+
+    /// [`Self::broadcast_with`] without anything to add from the receipt.
+    pub async fn broadcast(
+        &self,
+        entry: AuditEntry,
+        send: impl Future<Output = eyre::Result<PendingTransactionBuilder<Ethereum>>>,
+    ) -> eyre::Result<TransactionReceipt> {
+        self.broadcast_with(entry, send, |entry, _| entry).await
+    }
+
+    fn append(&self, path: &Path, entry: &AuditEntry) -> eyre::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        // Held until the entry is written, so no other writer rotates in between
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(suffixed(path, "lock"))?;
+        lock.lock()?;
+
+        let size = match std::fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            rotate(path)?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// Shift `<path>.N` to `<path>.N+1`, dropping the oldest, and move `path` to `<path>.1`.
+fn rotate(path: &Path) -> std::io::Result<()> {
+    for index in (1..ROTATED_FILES).rev() {
+        match std::fs::rename(rotated(path, index), rotated(path, index + 1)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    std::fs::rename(path, rotated(path, 1))
+}
+
+fn rotated(path: &Path, index: u32) -> PathBuf {
+    suffixed(path, &index.to_string())
+}
+
+fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".{suffix}"));
+    name.into()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::bytes;
+    use alloy_provider::Provider;
+    use alloy_rpc_types_eth::{Receipt, ReceiptEnvelope, ReceiptWithBloom};
+    use test_support::FakeChain;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("action-{}-{name}.jsonl", std::process::id()))
+    }
+
+    fn entry(nonce: u64) -> AuditEntry {
+        let tx = TransactionRequest::default()
+            .from(Address::repeat_byte(1))
+            .to(Address::repeat_byte(2))
+            .value(U256::from(1_000))
+            .nonce(nonce)
+            .input(bytes!("a9059cbb0000").into());
+        AuditEntry::new(
+            TransactionKind::Deposit,
+            &tx,
+            &Bytes::from(vec![nonce as u8]),
+        )
+        .with_deposit_id(Some(U256::from(7)))
+        .with_outcome(AuditOutcome::Failed {
+            error: "nonce too low".into(),
+        })
+    }
+
+    fn remove_all(path: &Path) {
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(suffixed(path, "lock"));
+        for index in 1..=ROTATED_FILES {
+            let _ = std::fs::remove_file(rotated(path, index));
+        }
+    }
+
+    fn receipt(tx_hash: TxHash) -> TransactionReceipt {
+        TransactionReceipt {
+            inner: ReceiptEnvelope::Eip1559(ReceiptWithBloom {
+                receipt: Receipt {
+                    status: true.into(),
+                    cumulative_gas_used: 60_000,
+                    logs: vec![],
+                },
+                logs_bloom: Default::default(),
+            }),
+            transaction_hash: tx_hash,
+            transaction_index: Some(0),
+            block_hash: None,
+            block_number: Some(42),
+            gas_used: 60_000,
+            effective_gas_price: 1_000,
+            blob_gas_used: None,
+            blob_gas_price: None,
+            from: Address::repeat_byte(1),
+            to: Some(Address::repeat_byte(2)),
+            contract_address: None,
+        }
+    }
+
+    fn lines(path: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_appends_json_lines() {
+        let path = temp_path("append");
+        let _ = std::fs::remove_file(&path);
+        let log = AuditLog::new(&path, u64::MAX);
+
+        log.record(&entry(1));
+        log.record(&entry(2));
+
+        let lines = lines(&path);
+        remove_all(&path);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["action"], "deposit");
+        assert_eq!(lines[0]["selector"], "0xa9059cbb");
+        assert_eq!(lines[0]["deposit_id"], "0x7");
+        assert_eq!(lines[0]["outcome"]["status"], "failed");
+        assert_eq!(lines[1]["nonce"], 2);
+        assert!(lines[0].get("withdrawal_hash").is_none());
+    }
+
+    #[test]
+    fn test_rotates_at_max_size() {
+        let path = temp_path("rotate");
+        remove_all(&path);
+        let line_len = serde_json::to_vec(&entry(1)).unwrap().len() as u64 + 1;
+        let log = AuditLog::new(&path, line_len * 2);
+
+        for nonce in 1..=3 {
+            log.record(&entry(nonce));
+        }
+
+        let active = lines(&path);
+        let previous = lines(&rotated(&path, 1));
+        remove_all(&path);
+        assert_eq!(previous.len(), 2);
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0]["nonce"], 3);
+    }
+
+    #[test]
+    fn test_concurrent_writers_lose_no_entries() {
+        let path = temp_path("concurrent");
+        remove_all(&path);
+        let line_len = serde_json::to_vec(&entry(1)).unwrap().len() as u64 + 1;
+
+        // Separately built logs, as in two processes, rotating every few entries
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                let log = AuditLog::new(&path, line_len * 4);
+                scope.spawn(move || {
+                    for nonce in 0..5 {
+                        log.record(&entry(nonce));
+                    }
+                });
+            }
+        });
+
+        let written: usize = std::iter::once(path.clone())
+            .chain((1..=ROTATED_FILES).map(|index| rotated(&path, index)))
+            .filter(|file| file.exists())
+            .map(|file| {
+                let lines = lines(&file);
+                assert!(lines.len() <= 4);
+                lines.len()
+            })
+            .sum();
+        remove_all(&path);
+        assert_eq!(written, 10);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_records_submission_then_outcome() {
+        let path = temp_path("broadcast");
+        remove_all(&path);
+        let log = AuditLog::new(&path, u64::MAX);
+        let chain = FakeChain::new();
+        let provider = chain.provider();
+        let signed = Bytes::from(vec![1]);
+        let tx_hash = keccak256(&signed);
+        chain.set_receipt(receipt(tx_hash));
+
+        let tx = TransactionRequest::default().nonce(1);
+        let mined = log
+            .broadcast_with(
+                AuditEntry::new(TransactionKind::InitiateWithdrawal, &tx, &signed),
+                async {
+                    // Nothing is written before the node accepted the transaction
+                    assert!(!path.exists());
+                    Ok(PendingTransactionBuilder::new(
+                        provider.root().clone(),
+                        tx_hash,
+                    ))
+                },
+                |entry, _| entry.with_withdrawal_hash(B256::repeat_byte(0x11)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(mined.transaction_hash, tx_hash);
+
+        // A rejected broadcast leaves a single failed entry
+        let err = log
+            .broadcast(
+                AuditEntry::new(TransactionKind::Deposit, &tx, &signed),
+                async { Err(eyre::eyre!("nonce too low")) },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "nonce too low");
+
+        let lines = lines(&path);
+        remove_all(&path);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["outcome"]["status"], "submitted");
+        assert!(lines[0].get("withdrawal_hash").is_none());
+        assert_eq!(lines[1]["tx_hash"], lines[0]["tx_hash"]);
+        assert_eq!(lines[1]["outcome"]["status"], "succeeded");
+        assert_eq!(lines[1]["outcome"]["block_number"], 42);
+        assert_eq!(
+            lines[1]["withdrawal_hash"],
+            B256::repeat_byte(0x11).to_string()
+        );
+        assert_eq!(lines[2]["action"], "deposit");
+        assert_eq!(lines[2]["outcome"]["status"], "failed");
+    }
+
+    #[test]
+    fn test_default_writes_nothing() {
+        let log = AuditLog::default();
+        log.record(&entry(1));
+        assert!(log.path().is_none());
+    }
+}
@@ -1,7 +1,14 @@
-use crate::SignerFn;
+use crate::{
+    audit::{AuditEntry, AuditLog, TransactionKind},
+    gas::{check_gas_funds, estimate_max_fee},
+    receipt::confirm,
+    TransactionSigner,
+};
 use alloy_primitives::{Address, U256};
 use alloy_provider::Provider;
+use alloy_rpc_types::TransactionRequest;
 use binding::across::ISpokePool;
+use std::sync::Arc;
 
 /// Input for a claim action.
 #[derive(Debug, Clone)]
@@ -16,25 +23,46 @@ pub struct Claim {
     pub relayer: Address,
 }
 
+/// Gas a `claimRelayerRefund` call is assumed to use.
+pub const CLAIM_GAS_LIMIT: u64 = 150_000;
+
 /// Claim action for claiming relayer refunds from ISpokePool.
 pub struct ClaimAction<P> {
     provider: P,
-    signer: SignerFn,
+    signer: Arc<dyn TransactionSigner>,
     claim: Claim,
+    audit_log: AuditLog,
+    /// Floor for the priority fee, in wei
+    min_priority_fee_wei: u128,
 }
 
 impl<P> ClaimAction<P>
 where
     P: Provider + Clone,
 {
-    pub fn new(provider: P, signer: SignerFn, claim: Claim) -> Self {
+    pub fn new(provider: P, signer: Arc<dyn TransactionSigner>, claim: Claim) -> Self {
         Self {
             provider,
             signer,
             claim,
+            audit_log: AuditLog::default(),
+            min_priority_fee_wei: 0,
         }
     }
 
+    /// Never pay less than `min_priority_fee_wei` as priority fee, whatever the node
+    /// estimates. Pick a floor that suits the chain the SpokePool is on.
+    pub const fn with_min_priority_fee(mut self, min_priority_fee_wei: u128) -> Self {
+        self.min_priority_fee_wei = min_priority_fee_wei;
+        self
+    }
+
+    /// Record the broadcast transaction in `log`.
+    pub fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = log;
+        self
+    }
+
     fn validate_claim(&self) -> eyre::Result<()> {
         if self.claim.spoke_pool == Address::ZERO {
             eyre::bail!("Spoke pool must not be zero");
@@ -64,6 +92,15 @@ where
             .await?;
         Ok(balance)
     }
+
+    /// Unfilled `claimRelayerRefund` transaction sent from the relayer.
+    pub fn transaction_request(&self) -> TransactionRequest {
+        let contract = ISpokePool::new(self.claim.spoke_pool, &self.provider);
+        contract
+            .claimRelayerRefund(self.claim.token)
+            .into_transaction_request()
+            .from(self.claim.relayer)
+    }
 }
 
 impl<P> crate::Action for ClaimAction<P>
@@ -72,6 +109,8 @@ where
 {
     async fn is_ready(&self) -> eyre::Result<bool> {
         // TODO: check against strategy
+        let cost = estimate_max_fee(&self.provider, CLAIM_GAS_LIMIT).await?;
+        check_gas_funds(&self.provider, self.claim.relayer, cost).await?;
         Ok(true)
     }
 
@@ -90,27 +129,30 @@ where
         }
 
         // Build the transaction request
-        let contract = ISpokePool::new(self.claim.spoke_pool, &self.provider);
-        let call = contract.claimRelayerRefund(self.claim.token);
-        let tx_request = call.into_transaction_request().from(self.claim.relayer);
+        let tx_request = self.transaction_request();
 
-        // Fill transaction fields (nonce, gas, fees) using our provider
-        let filled_tx = client::fill_transaction(tx_request, &self.provider).await?;
+        // Fill transaction fields (nonce, gas, fees) using our provider, same as the
+        // other actions
+        let filled_tx = client::apply_min_priority_fee(
+            client::fill_transaction(tx_request, &self.provider).await?,
+            self.min_priority_fee_wei,
+        );
 
         // Sign externally
-        let signed_tx = (self.signer)(filled_tx).await?;
+        let signed_tx = self.signer.sign_transaction(filled_tx.clone()).await?;
 
         // Broadcast the signed transaction
-        let pending = self.provider.send_raw_transaction(&signed_tx).await?;
-        let tx_hash = *pending.tx_hash();
-        let receipt = pending.get_receipt().await?;
-
-        if !receipt.status() {
-            eyre::bail!("Transaction reverted");
-        }
+        let receipt = self
+            .audit_log
+            .broadcast(
+                AuditEntry::new(TransactionKind::ClaimRefund, &filled_tx, &signed_tx),
+                async { Ok(self.provider.send_raw_transaction(&signed_tx).await?) },
+            )
+            .await?;
+        let receipt = confirm(&self.provider, &filled_tx, receipt).await?;
 
         Ok(crate::Result {
-            tx_hash,
+            tx_hash: receipt.transaction_hash,
             block_number: receipt.block_number,
             gas_used: Some(U256::from(receipt.gas_used)),
         })
@@ -128,9 +170,14 @@ where
 mod tests {
     use super::*;
     use crate::{
-        test_utils::{mock_signer, MockProvider},
+        gas::InsufficientGasFunds,
+        test_utils::{calldata, mock_signer, push_fee_history, push_latest_block, MAX_FEE_PER_GAS},
         Action,
     };
+    use alloy_primitives::hex;
+    use alloy_provider::ProviderBuilder;
+    use alloy_transport::mock::Asserter;
+    use test_support::FakeChain;
 
     #[test]
     fn test_claim_validation() {
@@ -141,7 +188,7 @@ mod tests {
             relayer: Address::repeat_byte(4),
         };
 
-        let action = ClaimAction::new(MockProvider, mock_signer(), valid_claim);
+        let action = ClaimAction::new(FakeChain::new().provider(), mock_signer(), valid_claim);
         assert!(action.validate_claim().is_ok());
     }
 
@@ -154,7 +201,7 @@ mod tests {
             relayer: Address::repeat_byte(4),
         };
 
-        let action = ClaimAction::new(MockProvider, mock_signer(), invalid_claim);
+        let action = ClaimAction::new(FakeChain::new().provider(), mock_signer(), invalid_claim);
         let result = action.validate_claim();
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Spoke pool"));
@@ -169,7 +216,7 @@ mod tests {
             relayer: Address::repeat_byte(4),
         };
 
-        let action = ClaimAction::new(MockProvider, mock_signer(), invalid_claim);
+        let action = ClaimAction::new(FakeChain::new().provider(), mock_signer(), invalid_claim);
         let result = action.validate_claim();
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Token"));
@@ -184,7 +231,7 @@ mod tests {
             relayer: Address::repeat_byte(4),
         };
 
-        let action = ClaimAction::new(MockProvider, mock_signer(), invalid_claim);
+        let action = ClaimAction::new(FakeChain::new().provider(), mock_signer(), invalid_claim);
         let result = action.validate_claim();
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Refund address"));
@@ -199,7 +246,7 @@ mod tests {
             relayer: Address::ZERO,
         };
 
-        let action = ClaimAction::new(MockProvider, mock_signer(), invalid_claim);
+        let action = ClaimAction::new(FakeChain::new().provider(), mock_signer(), invalid_claim);
         let result = action.validate_claim();
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("Relayer"));
@@ -214,9 +261,18 @@ mod tests {
             relayer: Address::repeat_byte(4),
         };
 
-        let action = ClaimAction::new(MockProvider, mock_signer(), claim);
-        // Currently always returns true (TODO in implementation)
+        let relayer = claim.relayer;
+        let chain = FakeChain::new();
+        let action = ClaimAction::new(chain.provider(), mock_signer(), claim);
+
+        // Ready whenever the relayer can pay for gas (strategy check is a TODO)
+        let needed = U256::from(CLAIM_GAS_LIMIT as u128 * MAX_FEE_PER_GAS);
+        chain.set_balance(relayer, needed);
         assert!(action.is_ready().await.unwrap());
+
+        chain.set_balance(relayer, needed - U256::from(1));
+        let err = action.is_ready().await.unwrap_err();
+        assert!(err.downcast_ref::<InsufficientGasFunds>().is_some());
     }
 
     #[test]
@@ -228,7 +284,7 @@ mod tests {
             relayer: Address::repeat_byte(4),
         };
 
-        let action = ClaimAction::new(MockProvider, mock_signer(), claim);
+        let action = ClaimAction::new(FakeChain::new().provider(), mock_signer(), claim);
         let desc = action.description();
 
         assert!(desc.contains("Claim relayer refund"));
@@ -236,4 +292,88 @@ mod tests {
         assert!(desc.contains("0x0202020202020202020202020202020202020202")); // token
         assert!(desc.contains("0x0303030303030303030303030303030303030303")); // refund_address
     }
+
+    #[tokio::test]
+    async fn test_execute_records_one_audit_entry() {
+        let path =
+            std::env::temp_dir().join(format!("action-{}-claim-audit.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // Anvil's first default account
+        let signer = client::LocalSigner::from_private_key(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let claim = Claim {
+            spoke_pool: Address::repeat_byte(1),
+            token: Address::repeat_byte(2),
+            refund_address: Address::repeat_byte(3),
+            relayer: signer.address(),
+        };
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let mut action = ClaimAction::new(provider, Arc::new(signer), claim)
+            .with_audit_log(AuditLog::new(&path, u64::MAX))
+            .with_min_priority_fee(2_000_000_000);
+
+        // Out of gas funds: nothing is signed or broadcast
+        push_fee_history(&asserter);
+        asserter.push_success(&U256::ZERO);
+        asserter.push_success(&U256::from(1)); // eth_chainId
+        assert!(action.execute().await.is_err());
+        assert!(!path.exists());
+
+        push_fee_history(&asserter);
+        asserter.push_success(&U256::MAX);
+        asserter.push_success(&U256::from(1)); // eth_chainId
+        asserter.push_success(&U256::from(5)); // eth_getTransactionCount
+        push_fee_history(&asserter);
+        asserter.push_success(&U256::from(100_000)); // eth_estimateGas
+        push_latest_block(&asserter);
+        asserter.push_failure_msg("nonce too low");
+        let Err(err) = action.execute().await else {
+            panic!("broadcast should fail");
+        };
+        assert!(err.to_string().contains("nonce too low"));
+
+        let raw = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(path.with_extension("jsonl.lock")).unwrap();
+        let entries: Vec<serde_json::Value> = raw
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["action"], "claim_refund");
+        assert_eq!(entries[0]["nonce"], 5);
+        // The estimated 1 gwei tip is raised to the floor, along with the fee cap
+        assert_eq!(entries[0]["max_priority_fee_per_gas"], 2_000_000_000_u64);
+        assert_eq!(entries[0]["max_fee_per_gas"], 4_000_000_000_u64);
+        assert_eq!(entries[0]["gas_limit"], 120_000);
+        assert_eq!(entries[0]["outcome"]["status"], "failed");
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[test]
+    fn test_transaction_request_calldata() {
+        let claim = Claim {
+            spoke_pool: Address::repeat_byte(1),
+            token: Address::repeat_byte(2),
+            refund_address: Address::repeat_byte(3),
+            relayer: Address::repeat_byte(4),
+        };
+        let tx = ClaimAction::new(FakeChain::new().provider(), mock_signer(), claim)
+            .transaction_request();
+
+        assert_eq!(tx.from, Some(Address::repeat_byte(4)));
+        assert_eq!(tx.to, Some(Address::repeat_byte(1).into()));
+        // claimRelayerRefund(address)
+        assert_eq!(
+            calldata(&tx)[..],
+            hex!(
+                "4ead45ee"
+                "0000000000000000000000000202020202020202020202020202020202020202"
+            )
+        );
+    }
 }
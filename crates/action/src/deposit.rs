@@ -1,8 +1,17 @@
-use crate::SignerFn;
-use alloy_primitives::{utils::format_ether, Address, Bytes, U256};
+use crate::{
+    audit::{AuditEntry, AuditLog, TransactionKind},
+    gas::{check_gas_funds, estimate_max_fee},
+    receipt::confirm,
+    TransactionSigner,
+};
+use alloy_primitives::{keccak256, utils::format_ether, Address, Bytes, U256};
 use alloy_provider::Provider;
+use alloy_rpc_types::TransactionRequest;
 use alloy_rpc_types_eth::BlockNumberOrTag;
+use alloy_sol_types::SolError;
 use binding::across::ISpokePool;
+use std::sync::Arc;
+use thiserror::Error;
 
 /// Configuration for a deposit action.
 #[derive(Debug, Clone)]
@@ -13,6 +22,8 @@ pub struct DepositConfig {
     pub depositor: Address,
     /// Recipient address on destination chain
     pub recipient: Address,
+    /// Recipients the deposit may be made to; any if empty
+    pub allowed_recipients: Vec<Address>,
     /// Input token on source chain
     /// See <https://github.com/across-protocol/contracts/blob/68a31fd4e9bdc080c86136650420d2c2ecbd1268/contracts/ISpokePool.sol#L591-L593>
     /// for details. TLDR:
@@ -24,23 +35,214 @@ pub struct DepositConfig {
     pub input_amount: U256,
     /// Amount recipient receives (after fees)
     pub output_amount: U256,
+    /// Range `output_amount` must fall in relative to `input_amount`
+    pub output_bounds: OutputBounds,
     /// Destination chain ID
     pub destination_chain_id: u64,
     /// Exclusive relayer (address(0) for any relayer)
     pub exclusive_relayer: Address,
-    /// Fill deadline (unix timestamp in seconds)
-    pub fill_deadline: u32,
+    /// When the deposit may be filled by, resolved against the SpokePool when the
+    /// deposit is prepared
+    pub fill_deadline: FillDeadline,
     /// Exclusivity parameter (0 for no exclusivity)
     pub exclusivity_parameter: u32,
     /// Optional message data
     pub message: Bytes,
+    /// Caller-chosen deposit nonce.
+    ///
+    /// When set, the deposit goes through `unsafeDepositV3` and its deposit id is known
+    /// up front (see [`compute_unsafe_deposit_id`]). When `None`, `depositV3` is used and
+    /// the SpokePool assigns the next sequential id.
+    pub deposit_nonce: Option<U256>,
+}
+
+/// Plausible range for a deposit's output amount, as a multiple of its input amount in
+/// basis points (10_000 = 1x).
+///
+/// An output far above the input invites abuse of the deposit message, one far below it
+/// gets filled instantly at a large spread. The default only requires
+/// `output >= input`, which forces a slow fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputBounds {
+    /// Smallest accepted output, in basis points of the input
+    pub min_bps: u64,
+    /// Largest accepted output, in basis points of the input
+    pub max_bps: u64,
+}
+
+impl Default for OutputBounds {
+    fn default() -> Self {
+        Self {
+            min_bps: BPS_PER_UNIT,
+            max_bps: u64::MAX,
+        }
+    }
+}
+
+/// Basis points in 1x.
+const BPS_PER_UNIT: u64 = 10_000;
+
+impl OutputBounds {
+    pub const fn new(min_bps: u64, max_bps: u64) -> Self {
+        Self { min_bps, max_bps }
+    }
+
+    /// Check that `output` lies within the bounds for a deposit of `input`.
+    pub fn check(&self, input: U256, output: U256) -> Result<(), OutputOutOfBounds> {
+        let scaled_output = output.saturating_mul(U256::from(BPS_PER_UNIT));
+        let min = input.saturating_mul(U256::from(self.min_bps));
+        let max = input.saturating_mul(U256::from(self.max_bps));
+
+        if scaled_output < min || scaled_output > max {
+            return Err(OutputOutOfBounds {
+                input,
+                output,
+                bounds: *self,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A deposit output amount outside its [`OutputBounds`].
+#[derive(Debug, Clone, Copy, Error)]
+#[error(
+    "Output amount {output} outside bounds for input {input} ({}-{} bps)",
+    bounds.min_bps,
+    bounds.max_bps
+)]
+pub struct OutputOutOfBounds {
+    pub input: U256,
+    pub output: U256,
+    pub bounds: OutputBounds,
+}
+
+/// A deposit recipient missing from the allowed recipients.
+#[derive(Debug, Clone, Error)]
+#[error("Recipient {recipient} is not an allowed deposit recipient ({})", allowed.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+pub struct RecipientNotAllowed {
+    pub recipient: Address,
+    pub allowed: Vec<Address>,
+}
+
+/// Check that `recipient` is one of `allowed`, unless that is empty.
+pub fn check_recipient(recipient: Address, allowed: &[Address]) -> Result<(), RecipientNotAllowed> {
+    if allowed.is_empty() || allowed.contains(&recipient) {
+        return Ok(());
+    }
+    Err(RecipientNotAllowed {
+        recipient,
+        allowed: allowed.to_vec(),
+    })
 }
 
+/// Compute the deposit id the SpokePool assigns to an `unsafeDepositV3` call.
+///
+/// Mirrors `SpokePool.getUnsafeDepositId`:
+/// `uint256(keccak256(abi.encodePacked(msgSender, bytes32(depositor), depositNonce)))`.
+pub fn compute_unsafe_deposit_id(msg_sender: Address, depositor: Address, nonce: U256) -> U256 {
+    let mut packed = [0u8; 20 + 32 + 32];
+    packed[..20].copy_from_slice(msg_sender.as_slice());
+    packed[20..52].copy_from_slice(depositor.into_word().as_slice());
+    packed[52..].copy_from_slice(&nonce.to_be_bytes::<32>());
+    U256::from_be_bytes(keccak256(packed).0)
+}
+
+/// How long after its quote a deposit may be filled, unless the SpokePool allows less.
+pub const DEFAULT_FILL_WINDOW_SECS: u32 = 3600;
+
+/// Fill deadline `window_secs` after `now`, capped at the SpokePool's `fillDeadlineBuffer`.
+///
+/// The SpokePool reverts deposits whose fill deadline is more than `fillDeadlineBuffer`
+/// seconds past the block timestamp.
+pub const fn fill_deadline(now: u32, window_secs: u32, fill_deadline_buffer: u32) -> u32 {
+    let window = if window_secs < fill_deadline_buffer {
+        window_secs
+    } else {
+        fill_deadline_buffer
+    };
+    now.saturating_add(window)
+}
+
+/// When a deposit may be filled by.
+///
+/// SpokePools revert deposits whose fill deadline is before the block timestamp or more
+/// than `fillDeadlineBuffer` seconds past it. Some older deployments accept zero,
+/// which leaves the deposit to a slow fill, while newer ones reject it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillDeadline {
+    /// This unix timestamp
+    Explicit(u32),
+    /// This many seconds after the deposit's block, capped at the `fillDeadlineBuffer`
+    RelativeSecs(u32),
+    /// Zero, for SpokePools that accept it as a request for a slow fill
+    ZeroForSlowFill,
+}
+
+impl Default for FillDeadline {
+    fn default() -> Self {
+        Self::RelativeSecs(DEFAULT_FILL_WINDOW_SECS)
+    }
+}
+
+impl FillDeadline {
+    /// Concrete fill deadline for a deposit in a block at `now` to a SpokePool with
+    /// `fill_deadline_buffer`.
+    ///
+    /// [`Self::ZeroForSlowFill`] resolves to zero: whether the pool accepts it can only
+    /// be told by simulating the deposit.
+    pub const fn resolve(
+        self,
+        now: u32,
+        fill_deadline_buffer: u32,
+    ) -> Result<u32, UnsupportedFillDeadline> {
+        match self {
+            Self::Explicit(deadline) if deadline < now => {
+                Err(UnsupportedFillDeadline::Past { deadline, now })
+            }
+            Self::Explicit(deadline) if deadline - now > fill_deadline_buffer => {
+                Err(UnsupportedFillDeadline::BeyondBuffer {
+                    deadline,
+                    now,
+                    fill_deadline_buffer,
+                })
+            }
+            Self::Explicit(deadline) => Ok(deadline),
+            Self::RelativeSecs(window_secs) => {
+                Ok(fill_deadline(now, window_secs, fill_deadline_buffer))
+            }
+            Self::ZeroForSlowFill => Ok(0),
+        }
+    }
+}
+
+/// A [`FillDeadline`] the deployed SpokePool would reject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum UnsupportedFillDeadline {
+    #[error("Fill deadline {deadline} is before the latest block at {now}")]
+    Past { deadline: u32, now: u32 },
+    #[error(
+        "Fill deadline {deadline} is more than the SpokePool's fillDeadlineBuffer of \
+         {fill_deadline_buffer}s past the latest block at {now}"
+    )]
+    BeyondBuffer {
+        deadline: u32,
+        now: u32,
+        fill_deadline_buffer: u32,
+    },
+    #[error("SpokePool rejects a zero fill deadline, configure a relative one instead")]
+    ZeroRejected,
+}
+
+/// Gas a `depositV3` call is assumed to use.
+const DEPOSIT_GAS_LIMIT: u64 = 200_000;
+
 /// Deposit action for sending tokens cross-chain via Across Protocol.
 pub struct DepositAction<P> {
     provider: P,
-    signer: SignerFn,
+    signer: Arc<dyn TransactionSigner>,
     config: DepositConfig,
+    audit_log: AuditLog,
 }
 
 impl<P> DepositAction<P>
@@ -48,14 +250,21 @@ where
     P: Provider + Clone,
 {
     /// Create a new deposit action.
-    pub fn new(provider: P, signer: SignerFn, config: DepositConfig) -> Self {
+    pub fn new(provider: P, signer: Arc<dyn TransactionSigner>, config: DepositConfig) -> Self {
         Self {
             provider,
             signer,
             config,
+            audit_log: AuditLog::default(),
         }
     }
 
+    /// Record the broadcast transaction in `log`.
+    pub fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = log;
+        self
+    }
+
     /// Get the current block timestamp from the chain.
     ///
     /// This is more accurate than wall clock time for quote validation
@@ -78,15 +287,135 @@ where
         if self.config.recipient == Address::ZERO {
             eyre::bail!("Recipient address is zero");
         }
+        check_recipient(self.config.recipient, &self.config.allowed_recipients)?;
 
         if self.config.input_amount == U256::ZERO {
             eyre::bail!("Input amount is zero");
         }
 
-        if self.config.output_amount < self.config.input_amount {
-            eyre::bail!("Output amount smaller than input amount");
+        self.config
+            .output_bounds
+            .check(self.config.input_amount, self.config.output_amount)?;
+
+        Ok(())
+    }
+
+    /// Deposit id this deposit will be assigned, if it uses a caller-chosen nonce.
+    ///
+    /// The depositor is also `msg.sender`, since we sign with the depositor's key.
+    pub fn deposit_id(&self) -> Option<U256> {
+        self.config.deposit_nonce.map(|nonce| {
+            compute_unsafe_deposit_id(self.config.depositor, self.config.depositor, nonce)
+        })
+    }
+
+    /// Concrete fill deadline for a deposit quoted at `quote_timestamp`, the latest
+    /// block's timestamp.
+    ///
+    /// Fails with [`UnsupportedFillDeadline`] if the SpokePool would reject it. A zero
+    /// deadline is checked by simulating the deposit with it.
+    pub async fn resolve_fill_deadline(&self, quote_timestamp: u32) -> eyre::Result<u32> {
+        let buffer = ISpokePool::new(self.config.spoke_pool, &self.provider)
+            .fillDeadlineBuffer()
+            .call()
+            .await?;
+        let deadline = self.config.fill_deadline.resolve(quote_timestamp, buffer)?;
+
+        if self.config.fill_deadline == FillDeadline::ZeroForSlowFill {
+            if let Err(e) = self
+                .provider
+                .call(self.transaction_request(quote_timestamp, deadline))
+                .await
+            {
+                let revert = e.as_error_resp().and_then(|resp| resp.as_revert_data());
+                if revert.is_some_and(|data| {
+                    data.starts_with(&ISpokePool::InvalidFillDeadline::SELECTOR)
+                }) {
+                    return Err(UnsupportedFillDeadline::ZeroRejected.into());
+                }
+                return Err(eyre::Report::new(e).wrap_err("Failed to simulate zero fill deadline"));
+            }
         }
+        Ok(deadline)
+    }
+
+    /// Build and fill the deposit transaction without signing or broadcasting it.
+    ///
+    /// The returned request is exactly what [`crate::Action::execute`] would sign,
+    /// which makes it suitable for dry-run review of calldata, gas and fees.
+    pub async fn prepare(&self) -> eyre::Result<TransactionRequest> {
+        self.validate_config()?;
 
+        // Get current block timestamp for quote
+        let quote_timestamp = self.get_current_block_timestamp().await?;
+        let fill_deadline = self.resolve_fill_deadline(quote_timestamp).await?;
+        let tx_request = self.transaction_request(quote_timestamp, fill_deadline);
+
+        // Fill transaction fields (nonce, gas, fees) using our provider
+        client::fill_transaction(tx_request, &self.provider).await
+    }
+
+    /// Unfilled deposit transaction sent from the depositor, quoted at `quote_timestamp`
+    /// and fillable until `fill_deadline`.
+    pub fn transaction_request(
+        &self,
+        quote_timestamp: u32,
+        fill_deadline: u32,
+    ) -> TransactionRequest {
+        // Create contract instance
+        let contract = ISpokePool::new(self.config.spoke_pool, &self.provider);
+
+        // Build the transaction request
+        let call = self.config.deposit_nonce.map_or_else(
+            || {
+                contract
+                    .depositV3(
+                        self.config.depositor,
+                        self.config.recipient,
+                        self.config.input_token,
+                        self.config.output_token,
+                        self.config.input_amount,
+                        self.config.output_amount,
+                        U256::from(self.config.destination_chain_id),
+                        self.config.exclusive_relayer,
+                        quote_timestamp,
+                        fill_deadline,
+                        self.config.exclusivity_parameter,
+                        self.config.message.clone(),
+                    )
+                    .value(self.config.input_amount)
+                    .into_transaction_request()
+            },
+            |deposit_nonce| {
+                contract
+                    .unsafeDepositV3(
+                        self.config.depositor,
+                        self.config.recipient,
+                        self.config.input_token,
+                        self.config.output_token,
+                        self.config.input_amount,
+                        self.config.output_amount,
+                        U256::from(self.config.destination_chain_id),
+                        self.config.exclusive_relayer,
+                        deposit_nonce,
+                        quote_timestamp,
+                        fill_deadline,
+                        self.config.exclusivity_parameter,
+                        self.config.message.clone(),
+                    )
+                    .value(self.config.input_amount)
+                    .into_transaction_request()
+            },
+        );
+        call.from(self.config.depositor)
+    }
+
+    /// Run the prepared deposit through `eth_call` without signing or broadcasting it.
+    ///
+    /// Fails with the revert reason if the deposit would revert.
+    pub async fn simulate(&self) -> eyre::Result<()> {
+        let tx = self.prepare().await?;
+        self.provider.call(tx).await?;
         Ok(())
     }
 }
@@ -96,11 +425,29 @@ where
     P: Provider + Clone + Send + Sync,
 {
     async fn is_ready(&self) -> eyre::Result<bool> {
-        // Basic validation - can be executed synchronously
-        Ok(self.config.spoke_pool != Address::ZERO
+        // Basic validation, before any RPC call
+        let valid = self.config.spoke_pool != Address::ZERO
             && self.config.recipient != Address::ZERO
+            && check_recipient(self.config.recipient, &self.config.allowed_recipients).is_ok()
             && self.config.input_amount > U256::ZERO
-            && self.config.output_amount >= self.config.input_amount)
+            && self
+                .config
+                .output_bounds
+                .check(self.config.input_amount, self.config.output_amount)
+                .is_ok();
+        if !valid {
+            return Ok(false);
+        }
+
+        // The input amount is sent as value along with the gas
+        let cost = estimate_max_fee(&self.provider, DEPOSIT_GAS_LIMIT).await?;
+        check_gas_funds(
+            &self.provider,
+            self.config.depositor,
+            self.config.input_amount + cost,
+        )
+        .await?;
+        Ok(true)
     }
 
     async fn is_completed(&self) -> eyre::Result<bool> {
@@ -117,50 +464,25 @@ where
             eyre::bail!("Deposit not ready");
         }
 
-        // Get current block timestamp for quote
-        let quote_timestamp = self.get_current_block_timestamp().await?;
-
-        // Create contract instance
-        let contract = ISpokePool::new(self.config.spoke_pool, &self.provider);
-
-        // Build the transaction request
-        let call = contract
-            .depositV3(
-                self.config.depositor,
-                self.config.recipient,
-                self.config.input_token,
-                self.config.output_token,
-                self.config.input_amount,
-                self.config.output_amount,
-                U256::from(self.config.destination_chain_id),
-                self.config.exclusive_relayer,
-                quote_timestamp,
-                self.config.fill_deadline,
-                self.config.exclusivity_parameter,
-                self.config.message.clone(),
-            )
-            .value(self.config.input_amount);
-        let tx_request = call.into_transaction_request().from(self.config.depositor);
-
-        // Fill transaction fields (nonce, gas, fees) using our provider
-        let filled_tx = client::fill_transaction(tx_request, &self.provider).await?;
+        // Build and fill the transaction
+        let filled_tx = self.prepare().await?;
 
         // Sign externally
-        let signed_tx = (self.signer)(filled_tx).await?;
+        let signed_tx = self.signer.sign_transaction(filled_tx.clone()).await?;
 
-        // Broadcast the signed transaction
-        let pending_tx = self.provider.send_raw_transaction(&signed_tx).await?;
-        let tx_hash = *pending_tx.tx_hash();
-
-        // Wait for confirmation
-        let receipt = pending_tx.get_receipt().await?;
-
-        if !receipt.status() {
-            eyre::bail!("Transaction reverted");
-        }
+        // Broadcast the signed transaction and wait for confirmation
+        let receipt = self
+            .audit_log
+            .broadcast(
+                AuditEntry::new(TransactionKind::Deposit, &filled_tx, &signed_tx)
+                    .with_deposit_id(self.deposit_id()),
+                async { Ok(self.provider.send_raw_transaction(&signed_tx).await?) },
+            )
+            .await?;
+        let receipt = confirm(&self.provider, &filled_tx, receipt).await?;
 
         Ok(crate::Result {
-            tx_hash,
+            tx_hash: receipt.transaction_hash,
             block_number: receipt.block_number,
             gas_used: Some(U256::from(receipt.gas_used)),
         })
@@ -179,37 +501,48 @@ where
 mod tests {
     use super::*;
     use crate::{
-        test_utils::{mock_signer, MockProvider},
+        gas::InsufficientGasFunds,
+        test_utils::{calldata, mock_signer, MAX_FEE_PER_GAS},
         Action,
     };
+    use alloy_primitives::hex;
+    use test_support::FakeChain;
 
     fn mock_config() -> DepositConfig {
         DepositConfig {
             spoke_pool: Address::from([1u8; 20]),
             depositor: Address::from([2u8; 20]),
             recipient: Address::from([3u8; 20]),
+            allowed_recipients: vec![],
             input_token: Address::from([4u8; 20]),
             output_token: Address::from([5u8; 20]),
             input_amount: U256::from(1_000_000),
             output_amount: U256::from(2_000_000),
+            output_bounds: OutputBounds::default(),
             destination_chain_id: 130,
             exclusive_relayer: Address::ZERO,
-            fill_deadline: 1234567890,
+            fill_deadline: FillDeadline::Explicit(1234567890),
             exclusivity_parameter: 0,
             message: Bytes::new(),
+            deposit_nonce: None,
         }
     }
 
     #[tokio::test]
     async fn test_is_ready_with_valid_config() {
         let config = mock_config();
-        let action = DepositAction {
-            provider: MockProvider {},
-            signer: mock_signer(),
-            config,
-        };
+        let depositor = config.depositor;
+        let needed = config.input_amount + U256::from(DEPOSIT_GAS_LIMIT as u128 * MAX_FEE_PER_GAS);
+        let chain = FakeChain::new();
+        let action = DepositAction::new(chain.provider(), mock_signer(), config);
 
+        chain.set_balance(depositor, needed);
         assert!(action.is_ready().await.unwrap());
+
+        // One wei short of the value plus gas
+        chain.set_balance(depositor, needed - U256::from(1));
+        let err = action.is_ready().await.unwrap_err();
+        assert!(err.downcast_ref::<InsufficientGasFunds>().is_some());
     }
 
     #[tokio::test]
@@ -217,9 +550,10 @@ mod tests {
         let mut config = mock_config();
         config.spoke_pool = Address::ZERO;
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config,
+            audit_log: AuditLog::default(),
         };
 
         assert!(!action.is_ready().await.unwrap());
@@ -230,9 +564,10 @@ mod tests {
         let mut config = mock_config();
         config.recipient = Address::ZERO;
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config,
+            audit_log: AuditLog::default(),
         };
 
         assert!(!action.is_ready().await.unwrap());
@@ -243,9 +578,10 @@ mod tests {
         let mut config = mock_config();
         config.input_amount = U256::ZERO;
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config,
+            audit_log: AuditLog::default(),
         };
 
         assert!(!action.is_ready().await.unwrap());
@@ -256,22 +592,145 @@ mod tests {
         let mut config = mock_config();
         config.input_amount = U256::from(100);
         config.output_amount = U256::from(200);
-        let action = DepositAction {
-            provider: MockProvider {},
-            signer: mock_signer(),
-            config,
-        };
+        let chain = FakeChain::new();
+        chain.set_balance(config.depositor, U256::MAX);
+        let action = DepositAction::new(chain.provider(), mock_signer(), config);
 
         assert!(action.is_ready().await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_quote_timestamp_and_fill_deadline_from_latest_block() {
+        let config = mock_config();
+        let spoke_pool = config.spoke_pool;
+        let chain = FakeChain::new();
+        chain.mine(1_700_000_000);
+        chain.set_call::<ISpokePool::fillDeadlineBufferCall>(spoke_pool, &1_800);
+        let mut config = config;
+        config.fill_deadline = FillDeadline::RelativeSecs(3_600);
+        let action = DepositAction::new(chain.provider(), mock_signer(), config);
+
+        assert_eq!(
+            action.get_current_block_timestamp().await.unwrap(),
+            1_700_000_000
+        );
+        // The window is capped at the SpokePool's buffer
+        assert_eq!(
+            action.resolve_fill_deadline(1_700_000_000).await.unwrap(),
+            1_700_001_800
+        );
+
+        // Quotes follow the chain, not the wall clock
+        chain.mine(1_700_000_012);
+        assert_eq!(
+            action.get_current_block_timestamp().await.unwrap(),
+            1_700_000_012
+        );
+    }
+
+    #[test]
+    fn test_fill_deadline_resolve() {
+        // Explicit deadlines must land within [now, now + buffer]
+        let explicit = FillDeadline::Explicit(2_000);
+        assert_eq!(explicit.resolve(1_000, 1_800), Ok(2_000));
+        assert_eq!(explicit.resolve(2_000, 1_800), Ok(2_000));
+        assert_eq!(
+            explicit.resolve(2_001, 1_800),
+            Err(UnsupportedFillDeadline::Past {
+                deadline: 2_000,
+                now: 2_001
+            })
+        );
+        assert_eq!(
+            explicit.resolve(100, 1_800),
+            Err(UnsupportedFillDeadline::BeyondBuffer {
+                deadline: 2_000,
+                now: 100,
+                fill_deadline_buffer: 1_800
+            })
+        );
+
+        // Relative deadlines are capped rather than rejected
+        let relative = FillDeadline::RelativeSecs(3_600);
+        assert_eq!(relative.resolve(1_000, 21_600), Ok(4_600));
+        assert_eq!(relative.resolve(1_000, 1_800), Ok(2_800));
+        assert_eq!(FillDeadline::default(), relative);
+
+        assert_eq!(FillDeadline::ZeroForSlowFill.resolve(1_000, 1_800), Ok(0));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_fill_deadline_against_pool() {
+        let mut config = mock_config();
+        let spoke_pool = config.spoke_pool;
+        let chain = FakeChain::new();
+        chain.set_call::<ISpokePool::fillDeadlineBufferCall>(spoke_pool, &1_800);
+
+        config.fill_deadline = FillDeadline::Explicit(1_700_000_600);
+        let action = DepositAction::new(chain.provider(), mock_signer(), config.clone());
+        assert_eq!(
+            action.resolve_fill_deadline(1_700_000_000).await.unwrap(),
+            1_700_000_600
+        );
+        let err = action
+            .resolve_fill_deadline(1_699_990_000)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<UnsupportedFillDeadline>(),
+            Some(UnsupportedFillDeadline::BeyondBuffer { .. })
+        ));
+
+        // Zero is only used if the pool accepts it
+        config.fill_deadline = FillDeadline::ZeroForSlowFill;
+        let action = DepositAction::new(chain.provider(), mock_signer(), config);
+        chain.set_call_output(
+            spoke_pool,
+            <ISpokePool::depositV3Call as alloy_sol_types::SolCall>::SELECTOR.into(),
+            Bytes::new(),
+        );
+        assert_eq!(
+            action.resolve_fill_deadline(1_700_000_000).await.unwrap(),
+            0
+        );
+
+        chain.set_call_revert::<ISpokePool::depositV3Call>(
+            spoke_pool,
+            ISpokePool::InvalidFillDeadline {}.abi_encode().into(),
+        );
+        let err = action
+            .resolve_fill_deadline(1_700_000_000)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<UnsupportedFillDeadline>(),
+            Some(&UnsupportedFillDeadline::ZeroRejected)
+        );
+
+        // Other reverts are not mistaken for a rejected deadline
+        chain.set_call_revert::<ISpokePool::depositV3Call>(spoke_pool, Bytes::new());
+        let err = action
+            .resolve_fill_deadline(1_700_000_000)
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<UnsupportedFillDeadline>().is_none());
+    }
+
+    #[test]
+    fn test_fill_deadline_respects_buffer() {
+        assert_eq!(fill_deadline(1_000, 3_600, 21_600), 4_600);
+        assert_eq!(fill_deadline(1_000, 3_600, 1_800), 2_800);
+        assert_eq!(fill_deadline(u32::MAX - 10, 3_600, 21_600), u32::MAX);
+    }
+
     #[test]
     fn test_validate_config_success() {
         let config = mock_config();
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config,
+            audit_log: AuditLog::default(),
         };
 
         assert!(action.validate_config().is_ok());
@@ -282,9 +741,10 @@ mod tests {
         let mut config = mock_config();
         config.spoke_pool = Address::ZERO;
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config,
+            audit_log: AuditLog::default(),
         };
 
         let result = action.validate_config();
@@ -297,9 +757,10 @@ mod tests {
         let mut config = mock_config();
         config.recipient = Address::ZERO;
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config,
+            audit_log: AuditLog::default(),
         };
 
         let result = action.validate_config();
@@ -307,14 +768,36 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Recipient"));
     }
 
+    #[test]
+    fn test_validate_config_recipient_not_allowed() {
+        let mut config = mock_config();
+        config.allowed_recipients = vec![Address::from([9u8; 20])];
+        let mut action = DepositAction {
+            provider: FakeChain::new().provider(),
+            signer: mock_signer(),
+            config,
+            audit_log: AuditLog::default(),
+        };
+
+        let err = action.validate_config().unwrap_err();
+        assert!(err.downcast_ref::<RecipientNotAllowed>().is_some());
+
+        action
+            .config
+            .allowed_recipients
+            .push(action.config.recipient);
+        assert!(action.validate_config().is_ok());
+    }
+
     #[test]
     fn test_validate_config_zero_input_amount() {
         let mut config = mock_config();
         config.input_amount = U256::ZERO;
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config,
+            audit_log: AuditLog::default(),
         };
 
         let result = action.validate_config();
@@ -328,9 +811,10 @@ mod tests {
         config.input_amount = U256::from(100);
         config.output_amount = U256::from(200);
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config,
+            audit_log: AuditLog::default(),
         };
 
         let result = action.validate_config();
@@ -343,22 +827,95 @@ mod tests {
         config.input_amount = U256::from(100);
         config.output_amount = U256::from(90);
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config,
+            audit_log: AuditLog::default(),
         };
 
         let result = action.validate_config();
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_validate_config_output_within_bounds() {
+        let mut config = mock_config();
+        config.input_amount = U256::from(100);
+        config.output_amount = U256::from(95);
+        config.output_bounds = OutputBounds::new(9_500, 30_000);
+        let action = DepositAction {
+            provider: FakeChain::new().provider(),
+            signer: mock_signer(),
+            config: config.clone(),
+            audit_log: AuditLog::default(),
+        };
+        assert!(action.validate_config().is_ok());
+
+        config.output_amount = U256::from(94);
+        let action = DepositAction {
+            provider: FakeChain::new().provider(),
+            signer: mock_signer(),
+            config,
+            audit_log: AuditLog::default(),
+        };
+        let err = action.validate_config().unwrap_err();
+        assert!(err.downcast_ref::<OutputOutOfBounds>().is_some());
+    }
+
+    #[test]
+    fn test_output_bounds_check() {
+        let bounds = OutputBounds::new(9_500, 30_000);
+        let input = U256::from(1_000);
+
+        assert!(bounds.check(input, U256::from(950)).is_ok());
+        assert!(bounds.check(input, U256::from(3_000)).is_ok());
+        // Too small: instant fill at a large spread
+        assert!(bounds.check(input, U256::from(949)).is_err());
+        // Too large
+        let err = bounds.check(input, U256::from(3_001)).unwrap_err();
+        assert_eq!(err.output, U256::from(3_001));
+        assert!(err.to_string().contains("9500-30000 bps"));
+    }
+
+    #[test]
+    fn test_output_bounds_default_is_legacy_rule() {
+        // Output must be at least the input, with no practical upper limit
+        let bounds = OutputBounds::default();
+        let input = U256::from(1_000_000);
+
+        assert!(bounds.check(input, input).is_ok());
+        assert!(bounds.check(input, input * U256::from(2)).is_ok());
+        assert!(bounds
+            .check(input, input * U256::from(1_000_000_000_000_u64))
+            .is_ok());
+        assert!(bounds.check(input, input - U256::from(1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prepare_rejects_invalid_config() {
+        let mut config = mock_config();
+        config.input_amount = U256::ZERO;
+        let action = DepositAction {
+            provider: FakeChain::new().provider(),
+            signer: mock_signer(),
+            config,
+            audit_log: AuditLog::default(),
+        };
+
+        // Validation happens before any provider call
+        let result = action.prepare().await;
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("Input amount"));
+    }
+
     #[test]
     fn test_description() {
         let config = mock_config();
         let action = DepositAction {
-            provider: MockProvider {},
+            provider: FakeChain::new().provider(),
             signer: mock_signer(),
             config: config.clone(),
+            audit_log: AuditLog::default(),
         };
 
         let desc = action.description();
@@ -367,6 +924,57 @@ mod tests {
         assert!(desc.contains(&config.destination_chain_id.to_string()));
     }
 
+    #[test]
+    fn test_deposit_id_only_with_nonce() {
+        let mut config = mock_config();
+        let action = DepositAction {
+            provider: FakeChain::new().provider(),
+            signer: mock_signer(),
+            config: config.clone(),
+            audit_log: AuditLog::default(),
+        };
+        assert_eq!(action.deposit_id(), None);
+
+        config.deposit_nonce = Some(U256::from(42));
+        let action = DepositAction {
+            provider: FakeChain::new().provider(),
+            signer: mock_signer(),
+            config: config.clone(),
+            audit_log: AuditLog::default(),
+        };
+        assert_eq!(
+            action.deposit_id(),
+            Some(compute_unsafe_deposit_id(
+                config.depositor,
+                config.depositor,
+                U256::from(42)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_compute_unsafe_deposit_id() {
+        let sender = Address::from([2u8; 20]);
+        let depositor = Address::from([3u8; 20]);
+
+        // keccak256(sender ++ bytes32(depositor) ++ uint256(nonce))
+        let mut expected = Vec::new();
+        expected.extend_from_slice(sender.as_slice());
+        expected.extend_from_slice(&[0u8; 12]);
+        expected.extend_from_slice(depositor.as_slice());
+        expected.extend_from_slice(&U256::from(7).to_be_bytes::<32>());
+        let expected = U256::from_be_bytes(keccak256(&expected).0);
+
+        assert_eq!(
+            compute_unsafe_deposit_id(sender, depositor, U256::from(7)),
+            expected
+        );
+        assert_ne!(
+            compute_unsafe_deposit_id(sender, depositor, U256::from(7)),
+            compute_unsafe_deposit_id(sender, depositor, U256::from(8))
+        );
+    }
+
     #[test]
     fn test_deposit_config_fields() {
         let config = mock_config();
@@ -378,4 +986,69 @@ mod tests {
         assert!(config.output_amount > U256::ZERO);
         assert!(config.output_amount >= config.input_amount);
     }
+
+    #[test]
+    fn test_transaction_request_calldata() {
+        let mut config = mock_config();
+        config.message = Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]);
+        let action = DepositAction::new(FakeChain::new().provider(), mock_signer(), config);
+        let tx = action.transaction_request(1_700_000_000, 1234567890);
+
+        assert_eq!(tx.from, Some(Address::from([2u8; 20])));
+        assert_eq!(tx.to, Some(Address::from([1u8; 20]).into()));
+        assert_eq!(tx.value, Some(U256::from(1_000_000)));
+        // depositV3(address,address,address,address,uint256,uint256,uint256,address,uint32,
+        //     uint32,uint32,bytes)
+        assert_eq!(
+            calldata(&tx)[..],
+            hex!(
+                "7b939232"
+                "0000000000000000000000000202020202020202020202020202020202020202"
+                "0000000000000000000000000303030303030303030303030303030303030303"
+                "0000000000000000000000000404040404040404040404040404040404040404"
+                "0000000000000000000000000505050505050505050505050505050505050505"
+                "00000000000000000000000000000000000000000000000000000000000f4240"
+                "00000000000000000000000000000000000000000000000000000000001e8480"
+                "0000000000000000000000000000000000000000000000000000000000000082"
+                "0000000000000000000000000000000000000000000000000000000000000000"
+                "000000000000000000000000000000000000000000000000000000006553f100"
+                "00000000000000000000000000000000000000000000000000000000499602d2"
+                "0000000000000000000000000000000000000000000000000000000000000000"
+                "0000000000000000000000000000000000000000000000000000000000000180"
+                "0000000000000000000000000000000000000000000000000000000000000004"
+                "deadbeef00000000000000000000000000000000000000000000000000000000"
+            )
+        );
+    }
+
+    #[test]
+    fn test_unsafe_transaction_request_calldata() {
+        let mut config = mock_config();
+        config.deposit_nonce = Some(U256::from(42));
+        let action = DepositAction::new(FakeChain::new().provider(), mock_signer(), config);
+        let tx = action.transaction_request(1_700_000_000, 1234567890);
+
+        // unsafeDepositV3(address,address,address,address,uint256,uint256,uint256,address,
+        //     uint256,uint32,uint32,uint32,bytes)
+        assert_eq!(
+            calldata(&tx)[..],
+            hex!(
+                "9618bdf5"
+                "0000000000000000000000000202020202020202020202020202020202020202"
+                "0000000000000000000000000303030303030303030303030303030303030303"
+                "0000000000000000000000000404040404040404040404040404040404040404"
+                "0000000000000000000000000505050505050505050505050505050505050505"
+                "00000000000000000000000000000000000000000000000000000000000f4240"
+                "00000000000000000000000000000000000000000000000000000000001e8480"
+                "0000000000000000000000000000000000000000000000000000000000000082"
+                "0000000000000000000000000000000000000000000000000000000000000000"
+                "000000000000000000000000000000000000000000000000000000000000002a"
+                "000000000000000000000000000000000000000000000000000000006553f100"
+                "00000000000000000000000000000000000000000000000000000000499602d2"
+                "0000000000000000000000000000000000000000000000000000000000000000"
+                "00000000000000000000000000000000000000000000000000000000000001a0"
+                "0000000000000000000000000000000000000000000000000000000000000000"
+            )
+        );
+    }
 }
@@ -3,34 +3,117 @@
 //! Finalizes a proven withdrawal on L1, executing the withdrawal transaction
 //! and sending ETH/tokens to the recipient.
 
-use crate::{Action, SignerFn};
-use alloy_primitives::{Address, U256};
-use alloy_provider::Provider;
-use binding::opstack::{IOptimismPortal2, WithdrawalTransaction};
+use crate::{
+    audit::{AuditEntry, AuditLog, TransactionKind},
+    check_portal_input,
+    gas::{check_gas_funds, estimate_max_fee},
+    pending::PendingTransactions,
+    receipt::{confirm, decode_events},
+    Action, TransactionSigner,
+};
+use alloy_primitives::{Address, TxHash, U256};
+use alloy_provider::{PendingTransactionBuilder, Provider};
+use alloy_rpc_types::TransactionRequest;
+use alloy_rpc_types_eth::Log;
+use binding::opstack::{
+    IOptimismPortal2::{self, WithdrawalFinalized},
+    WithdrawalTransaction,
+};
+use client::SponsoredRelay;
+use config::UnichainConfig;
+use std::sync::Arc;
+use thiserror::Error;
 use tracing::info;
-use withdrawal::{state::WithdrawalStateProvider, types::WithdrawalHash};
+use withdrawal::{
+    portal::PortalParamsCache,
+    state::{PendingWithdrawal, WithdrawalStateProvider},
+    submitter::ProofCandidate,
+    types::WithdrawalHash,
+};
 
 /// Input data for finalizing a withdrawal on L1.
 #[derive(Clone, Debug)]
 pub struct Finalize {
+    /// Chain ID of the rollup the withdrawal was initiated on
+    pub chain_id: u64,
     /// OptimismPortal2 contract address on L1
     pub portal_address: Address,
     /// The withdrawal transaction details
     pub withdrawal: WithdrawalTransaction,
     /// Hash of the withdrawal
     pub withdrawal_hash: WithdrawalHash,
-    /// Address that submitted the proof (usually the same as withdrawal sender)
-    pub proof_submitter: Address,
-    /// Address that will submit the finalize transaction
-    pub from: Address,
+    /// Submitter whose proof is finalized against. The portal keys proofs by
+    /// `(withdrawal_hash, submitter)`; [`FinalizeAction`] picks the valid proof that
+    /// matures first and records its submitter here when executing.
+    pub proof_submitter: Option<Address>,
 }
 
+impl Finalize {
+    /// Build a finalize input for a proven withdrawal on the given chain.
+    pub fn from_pending(
+        pending: &PendingWithdrawal,
+        unichain: &UnichainConfig,
+    ) -> eyre::Result<Self> {
+        check_portal_input(pending, unichain)?;
+
+        Ok(Self {
+            chain_id: unichain.chain_id,
+            portal_address: unichain.l1_portal,
+            withdrawal: pending.transaction.clone(),
+            withdrawal_hash: pending.hash,
+            proof_submitter: None,
+        })
+    }
+}
+
+/// The finalize transaction succeeded but the withdrawal's own call failed, so its funds
+/// were not delivered.
+///
+/// OptimismPortal2 marks the withdrawal finalized before making the call, so it can't be
+/// finalized again with more gas. The portal's `callWithMinGas` makes the whole
+/// transaction revert if it was sent with too little gas for the withdrawal's gas limit,
+/// so the call ran out of that limit or reverted on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("Withdrawal {withdrawal_hash} was finalized by {tx_hash} but its call failed (gas limit {gas_limit})")]
+pub struct WithdrawalCallFailed {
+    /// Chain ID of the rollup the withdrawal was initiated on
+    pub chain_id: u64,
+    pub withdrawal_hash: WithdrawalHash,
+    /// The finalize transaction
+    pub tx_hash: TxHash,
+    /// Gas limit of the withdrawal's call, set when it was initiated on L2
+    pub gas_limit: U256,
+}
+
+/// Whether the call of withdrawal `hash` succeeded, from the `WithdrawalFinalized` event
+/// `portal` emitted among `logs`. `None` if there is no such event.
+pub fn withdrawal_call_succeeded(
+    logs: &[Log],
+    portal: Address,
+    hash: WithdrawalHash,
+) -> Option<bool> {
+    decode_events::<WithdrawalFinalized>(logs)
+        .find(|event| event.address == portal && event.withdrawalHash == hash)
+        .map(|event| event.success)
+}
+
+/// Gas used by `finalizeWithdrawalTransactionExternalProof` on top of the withdrawal's own
+/// gas limit.
+pub const FINALIZE_GAS_OVERHEAD: u64 = 200_000;
+
 /// Action to finalize a proven withdrawal on L1.
 pub struct FinalizeAction<P1, P2> {
     l1_provider: P1,
     l2_provider: P2,
-    signer: SignerFn,
+    signer: Arc<dyn TransactionSigner>,
     action: Finalize,
+    /// Proof maturity delay, shared across actions
+    portal_params: PortalParamsCache,
+    /// Relay the signed transaction is handed to instead of broadcasting it
+    relay: Option<SponsoredRelay>,
+    audit_log: AuditLog,
+    /// Broadcasts awaiting a receipt, shared across actions
+    pending_transactions: PendingTransactions,
 }
 
 impl<P1, P2> FinalizeAction<P1, P2>
@@ -38,20 +121,59 @@ where
     P1: Provider + Clone,
     P2: Provider + Clone,
 {
-    pub fn new(l1_provider: P1, l2_provider: P2, signer: SignerFn, action: Finalize) -> Self {
+    pub fn new(
+        l1_provider: P1,
+        l2_provider: P2,
+        signer: Arc<dyn TransactionSigner>,
+        action: Finalize,
+    ) -> Self {
         Self {
             l1_provider,
             l2_provider,
             signer,
             action,
+            portal_params: PortalParamsCache::default(),
+            relay: None,
+            audit_log: AuditLog::default(),
+            pending_transactions: PendingTransactions::default(),
         }
     }
 
+    /// Read the portal's proof maturity delay through `cache` instead of on every run.
+    pub fn with_portal_params(mut self, cache: PortalParamsCache) -> Self {
+        self.portal_params = cache;
+        self
+    }
+
+    /// Submit the signed finalize transaction through a gas-sponsoring relay, so the
+    /// signer doesn't need L1 ETH.
+    pub fn with_sponsored_relay(mut self, relay: SponsoredRelay) -> Self {
+        self.relay = Some(relay);
+        self
+    }
+
+    /// Record the broadcast transaction in `log`.
+    pub fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = log;
+        self
+    }
+
+    /// Record the broadcast transaction in `pending` until its receipt is seen.
+    pub fn with_pending_transactions(mut self, pending: PendingTransactions) -> Self {
+        self.pending_transactions = pending;
+        self
+    }
+
     /// Get the withdrawal hash for this action.
     pub const fn withdrawal_hash(&self) -> WithdrawalHash {
         self.action.withdrawal_hash
     }
 
+    /// Submitter of the proof the withdrawal was finalized with, once executed.
+    pub const fn proof_submitter(&self) -> Option<Address> {
+        self.action.proof_submitter
+    }
+
     /// Check if the withdrawal has been finalized using WithdrawalStateProvider.
     async fn check_is_finalized(&self) -> eyre::Result<bool> {
         let state = WithdrawalStateProvider::new(
@@ -64,8 +186,16 @@ where
         state.is_finalized(self.action.withdrawal_hash).await
     }
 
-    /// Check if the withdrawal has been proven and get the proof timestamp.
-    async fn check_is_proven(&self) -> eyre::Result<Option<u64>> {
+    /// Pick the proof to finalize with among those submitted by anyone (our signer's
+    /// always included), along with the proof maturity delay.
+    ///
+    /// `None` if there is no valid proof: every game is blacklisted, lost or of a type
+    /// the portal no longer respects.
+    async fn select_proof(&self) -> eyre::Result<(Option<ProofCandidate>, u64)> {
+        let params = self
+            .portal_params
+            .get(&self.l1_provider, self.action.portal_address)
+            .await?;
         let state = WithdrawalStateProvider::new(
             self.l1_provider.clone(),
             self.l2_provider.clone(),
@@ -73,18 +203,42 @@ where
             Address::ZERO, // message passer not needed for proven check
         );
 
-        let proven = state
-            .is_proven(self.action.withdrawal_hash, self.action.proof_submitter)
+        let best = state
+            .best_proof(
+                self.action.withdrawal_hash,
+                self.signer.address(),
+                params.respected_game_type,
+            )
             .await?;
 
-        Ok(proven.map(|p| p.timestamp))
+        Ok((best, params.proof_maturity_delay))
     }
 
-    /// Get the proof maturity delay from the portal contract.
-    async fn get_proof_maturity_delay(&self) -> eyre::Result<u64> {
+    /// Run the finalization through `eth_call` without signing or broadcasting it,
+    /// using the proof [`Action::execute`] would pick.
+    ///
+    /// Fails with the revert reason if the transaction would revert, e.g. because the
+    /// proof hasn't matured yet.
+    pub async fn simulate(&self) -> eyre::Result<()> {
+        let (Some(best), _) = self.select_proof().await? else {
+            eyre::bail!("Withdrawal has no valid proof")
+        };
+        self.l1_provider
+            .call(self.transaction_request(best.submitter))
+            .await?;
+        Ok(())
+    }
+
+    /// Unfilled finalize transaction using the proof submitted by `proof_submitter`.
+    pub fn transaction_request(&self, proof_submitter: Address) -> TransactionRequest {
         let portal = IOptimismPortal2::new(self.action.portal_address, &self.l1_provider);
-        let delay: U256 = portal.proofMaturityDelaySeconds().call().await?;
-        Ok(delay.try_into().unwrap_or(u64::MAX))
+        portal
+            .finalizeWithdrawalTransactionExternalProof(
+                self.action.withdrawal.clone(),
+                proof_submitter,
+            )
+            .into_transaction_request()
+            .from(self.signer.address())
     }
 
     /// Get the current L1 block timestamp.
@@ -109,17 +263,31 @@ where
             return Ok(false);
         }
 
-        // Check if proven and maturity delay has passed
-        let Some(proven_timestamp) = self.check_is_proven().await? else {
-            // Not proven yet
+        // Check if validly proven and the best proof's maturity delay has passed
+        let (Some(best), maturity_delay) = self.select_proof().await? else {
+            // Not proven yet, or only against invalid games
             return Ok(false);
         };
 
-        let maturity_delay = self.get_proof_maturity_delay().await?;
+        // The portal only finalizes against a game resolved for the defender
+        if !best.game_resolved() {
+            return Ok(false);
+        }
+
         let current_timestamp = self.get_current_timestamp().await?;
+        if current_timestamp < best.matures_at(maturity_delay) {
+            return Ok(false);
+        }
+
+        // A sponsoring relay pays for gas
+        if self.relay.is_none() {
+            let gas_limit = FINALIZE_GAS_OVERHEAD
+                .saturating_add(self.action.withdrawal.gasLimit.saturating_to());
+            let cost = estimate_max_fee(&self.l1_provider, gas_limit).await?;
+            check_gas_funds(&self.l1_provider, self.signer.address(), cost).await?;
+        }
 
-        // Ready if current time >= proven timestamp + maturity delay
-        Ok(current_timestamp >= proven_timestamp + maturity_delay)
+        Ok(true)
     }
 
     async fn is_completed(&self) -> eyre::Result<bool> {
@@ -131,48 +299,93 @@ where
             eyre::bail!("Withdrawal already finalized")
         }
 
-        // Verify the withdrawal is proven
-        let Some(proven_timestamp) = self.check_is_proven().await? else {
-            eyre::bail!("Withdrawal not proven yet")
+        // Verify the withdrawal has a valid proof
+        let (Some(best), maturity_delay) = self.select_proof().await? else {
+            eyre::bail!("Withdrawal has no valid proof")
         };
 
-        // Verify maturity delay has passed
-        let maturity_delay = self.get_proof_maturity_delay().await?;
+        if !best.game_resolved() {
+            eyre::bail!(
+                "Dispute game {} of the best proof has not resolved yet",
+                best.dispute_game_proxy
+            )
+        }
+
+        // Verify the best proof's maturity delay has passed
+        let matures_at = best.matures_at(maturity_delay);
         let current_timestamp = self.get_current_timestamp().await?;
 
-        if current_timestamp < proven_timestamp + maturity_delay {
-            let remaining = (proven_timestamp + maturity_delay) - current_timestamp;
+        if current_timestamp < matures_at {
+            let remaining = matures_at - current_timestamp;
             eyre::bail!(
                 "Proof maturity delay not elapsed. {} seconds remaining",
                 remaining
             )
         }
 
+        self.action.proof_submitter = Some(best.submitter);
         info!(
+            chain_id = self.action.chain_id,
             withdrawal_hash = %self.action.withdrawal_hash,
-            proof_submitter = %self.action.proof_submitter,
+            proof_submitter = %best.submitter,
+            dispute_game_proxy = %best.dispute_game_proxy,
+            proven_at = best.timestamp,
             "Finalizing withdrawal"
         );
 
         // Build the transaction request
-        let portal = IOptimismPortal2::new(self.action.portal_address, &self.l1_provider);
-        let call = portal.finalizeWithdrawalTransactionExternalProof(
-            self.action.withdrawal.clone(),
-            self.action.proof_submitter,
-        );
-        let tx_request = call.into_transaction_request().from(self.action.from);
+        let mut tx_request = self.transaction_request(best.submitter);
+
+        if self.relay.is_some() {
+            // Estimating with fees set fails for a sender without ETH
+            let gas = self.l1_provider.estimate_gas(tx_request.clone()).await?;
+            tx_request.gas = Some(gas + gas / 5);
+        }
 
         // Fill transaction fields (nonce, gas, fees) using our provider
         let filled_tx = client::fill_transaction(tx_request, &self.l1_provider).await?;
 
         // Sign externally
-        let signed_tx = (self.signer)(filled_tx).await?;
-
-        // Broadcast the signed transaction
-        let pending = self.l1_provider.send_raw_transaction(&signed_tx).await?;
-        let receipt = pending.get_receipt().await?;
+        let signed_tx = self.signer.sign_transaction(filled_tx.clone()).await?;
+
+        // Broadcast the signed transaction, or hand it to the sponsoring relay
+        let receipt = self
+            .audit_log
+            .broadcast(
+                AuditEntry::new(TransactionKind::FinalizeWithdrawal, &filled_tx, &signed_tx)
+                    .with_withdrawal_hash(self.action.withdrawal_hash),
+                async {
+                    let pending = match &self.relay {
+                        Some(relay) => {
+                            let tx_hash = relay.send_raw_transaction(&signed_tx).await?;
+                            info!(
+                                tx_hash = %tx_hash,
+                                relay = relay.url(),
+                                "Finalize transaction submitted to sponsored relay"
+                            );
+                            PendingTransactionBuilder::new(self.l1_provider.root().clone(), tx_hash)
+                        }
+                        None => self.l1_provider.send_raw_transaction(&signed_tx).await?,
+                    };
+                    self.pending_transactions.record(
+                        self.action.withdrawal_hash,
+                        TransactionKind::FinalizeWithdrawal,
+                        *pending.tx_hash(),
+                    );
+                    self.pending_transactions.persist().await;
+                    Ok(pending)
+                },
+            )
+            .await?;
+        self.pending_transactions.clear(
+            self.action.withdrawal_hash,
+            TransactionKind::FinalizeWithdrawal,
+        );
+        self.pending_transactions.persist().await;
+        let receipt = confirm(&self.l1_provider, &filled_tx, receipt).await?;
 
         info!(
+            chain_id = self.action.chain_id,
             tx_hash = %receipt.transaction_hash,
             block_number = receipt.block_number,
             gas_used = receipt.gas_used,
@@ -180,6 +393,21 @@ where
             "Withdrawal finalized on L1"
         );
 
+        let call_succeeded = withdrawal_call_succeeded(
+            receipt.logs(),
+            self.action.portal_address,
+            self.action.withdrawal_hash,
+        );
+        if call_succeeded == Some(false) {
+            return Err(WithdrawalCallFailed {
+                chain_id: self.action.chain_id,
+                withdrawal_hash: self.action.withdrawal_hash,
+                tx_hash: receipt.transaction_hash,
+                gas_limit: self.action.withdrawal.gasLimit,
+            }
+            .into());
+        }
+
         Ok(crate::Result {
             tx_hash: receipt.transaction_hash,
             block_number: receipt.block_number,
@@ -198,11 +426,25 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::{mock_signer, MockProvider};
-    use alloy_primitives::{address, b256, Bytes};
+    use crate::{
+        gas::InsufficientGasFunds,
+        test_utils::{calldata, mock_signer, pending_withdrawal, MAX_FEE_PER_GAS},
+    };
+    use alloy_primitives::{address, b256, hex, Bytes, B256};
+    use alloy_provider::RootProvider;
+    use alloy_sol_types::SolEvent;
+    use binding::opstack::IDisputeGame;
+    use test_support::FakeChain;
+
+    fn create_test_finalize_action() -> FinalizeAction<RootProvider, RootProvider> {
+        let l1 = FakeChain::new();
+        finalize_action_on(&l1)
+    }
 
-    fn create_test_finalize_action() -> FinalizeAction<MockProvider, MockProvider> {
+    /// Action finalizing through `l1`, with a 100k gas limit withdrawal.
+    fn finalize_action_on(l1: &FakeChain) -> FinalizeAction<RootProvider, RootProvider> {
         let finalize = Finalize {
+            chain_id: 1301,
             portal_address: address!("0d83dab629f0e0F9d36c0Cbc89B69a489f0751bD"),
             withdrawal: WithdrawalTransaction {
                 nonce: U256::from(1),
@@ -215,11 +457,122 @@ mod tests {
             withdrawal_hash: b256!(
                 "1111111111111111111111111111111111111111111111111111111111111111"
             ),
-            proof_submitter: address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1"),
-            from: address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1"),
+            proof_submitter: None,
         };
 
-        FinalizeAction::new(MockProvider, MockProvider, mock_signer(), finalize)
+        FinalizeAction::new(
+            l1.provider(),
+            FakeChain::new().provider(),
+            mock_signer(),
+            finalize,
+        )
+    }
+
+    /// Proven at `proven_at` by our signer against a respected game resolved for the
+    /// defender, with a one week maturity delay.
+    fn prove_on(l1: &FakeChain, portal: Address, proven_at: u64) {
+        let game = Address::repeat_byte(0x77);
+        l1.set_call::<IOptimismPortal2::finalizedWithdrawalsCall>(portal, &false);
+        l1.set_call::<IOptimismPortal2::respectedGameTypeCall>(portal, &1);
+        l1.set_call::<IOptimismPortal2::disputeGameFactoryCall>(
+            portal,
+            &Address::repeat_byte(0xfa),
+        );
+        l1.set_call::<IOptimismPortal2::proofMaturityDelaySecondsCall>(
+            portal,
+            &U256::from(604_800),
+        );
+        l1.set_call::<IOptimismPortal2::numProofSubmittersCall>(portal, &U256::ZERO);
+        l1.set_call::<IOptimismPortal2::provenWithdrawalsCall>(
+            portal,
+            &IOptimismPortal2::ProvenWithdrawal {
+                disputeGameProxy: game,
+                timestamp: proven_at,
+            },
+        );
+        l1.set_call::<IOptimismPortal2::disputeGameBlacklistCall>(portal, &false);
+        l1.set_call::<IDisputeGame::gameTypeCall>(game, &1);
+        l1.set_call::<IDisputeGame::statusCall>(game, &2);
+    }
+
+    #[tokio::test]
+    async fn test_is_ready_once_proof_matures() {
+        let l1 = FakeChain::new();
+        let action = finalize_action_on(&l1);
+        let portal = action.action.portal_address;
+        prove_on(&l1, portal, 1_000_000);
+        // Overhead plus the withdrawal's gas limit at the estimated fee
+        l1.set_balance(
+            action.signer.address(),
+            U256::from(300_000 * MAX_FEE_PER_GAS),
+        );
+
+        l1.mine(1_000_000 + 604_800 - 1);
+        assert!(!action.is_ready().await.unwrap());
+
+        l1.mine(1_000_000 + 604_800);
+        assert!(action.is_ready().await.unwrap());
+
+        // Mature, but the game is still in progress
+        l1.set_call::<IDisputeGame::statusCall>(Address::repeat_byte(0x77), &0);
+        assert!(!action.is_ready().await.unwrap());
+        l1.set_call::<IDisputeGame::statusCall>(Address::repeat_byte(0x77), &2);
+
+        // Mature, but the signer can't pay for gas
+        l1.set_balance(
+            action.signer.address(),
+            U256::from(300_000 * MAX_FEE_PER_GAS - 1),
+        );
+        let err = action.is_ready().await.unwrap_err();
+        assert!(err.downcast_ref::<InsufficientGasFunds>().is_some());
+
+        // Never ready once finalized
+        l1.set_call::<IOptimismPortal2::finalizedWithdrawalsCall>(portal, &true);
+        assert!(!action.is_ready().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_is_ready_needs_valid_proof() {
+        let l1 = FakeChain::new();
+        let action = finalize_action_on(&l1);
+        let portal = action.action.portal_address;
+        prove_on(&l1, portal, 1_000_000);
+        l1.mine(2_000_000);
+
+        // The game was blacklisted
+        l1.set_call::<IOptimismPortal2::disputeGameBlacklistCall>(portal, &true);
+        assert!(!action.is_ready().await.unwrap());
+
+        // Never proven
+        l1.set_call::<IOptimismPortal2::provenWithdrawalsCall>(
+            portal,
+            &IOptimismPortal2::ProvenWithdrawal {
+                disputeGameProxy: Address::ZERO,
+                timestamp: 0,
+            },
+        );
+        assert!(!action.is_ready().await.unwrap());
+    }
+
+    #[test]
+    fn test_from_pending() {
+        let pending = pending_withdrawal();
+        let unichain = UnichainConfig::mainnet();
+
+        let finalize = Finalize::from_pending(&pending, &unichain).unwrap();
+        assert_eq!(finalize.chain_id, unichain.chain_id);
+        assert_eq!(finalize.portal_address, unichain.l1_portal);
+        assert_eq!(finalize.withdrawal_hash, pending.hash);
+        // Selected from the proofs on L1 at execution
+        assert_eq!(finalize.proof_submitter, None);
+    }
+
+    #[test]
+    fn test_from_pending_rejects_hash_mismatch() {
+        let mut pending = pending_withdrawal();
+        pending.transaction.value += U256::from(1);
+        let err = Finalize::from_pending(&pending, &UnichainConfig::mainnet()).unwrap_err();
+        assert!(err.to_string().contains("hash mismatch"));
     }
 
     #[test]
@@ -238,4 +591,60 @@ mod tests {
             b256!("1111111111111111111111111111111111111111111111111111111111111111")
         );
     }
+
+    #[test]
+    fn test_withdrawal_call_succeeded() {
+        let portal = Address::repeat_byte(0x11);
+        let hash = B256::repeat_byte(0x22);
+        let log = |address, withdrawal_hash, success| Log {
+            inner: alloy_primitives::Log {
+                address,
+                data: WithdrawalFinalized {
+                    withdrawalHash: withdrawal_hash,
+                    success,
+                }
+                .encode_log_data(),
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(withdrawal_call_succeeded(&[], portal, hash), None);
+        assert_eq!(
+            withdrawal_call_succeeded(&[log(portal, hash, true)], portal, hash),
+            Some(true)
+        );
+        // Only the event of this withdrawal, emitted by the portal, counts
+        let logs = [
+            log(Address::repeat_byte(0x33), hash, true),
+            log(portal, B256::repeat_byte(0x44), true),
+            log(portal, hash, false),
+        ];
+        assert_eq!(withdrawal_call_succeeded(&logs, portal, hash), Some(false));
+    }
+
+    #[test]
+    fn test_transaction_request_calldata() {
+        let action = create_test_finalize_action();
+        let tx = action.transaction_request(Address::repeat_byte(0x55));
+
+        assert_eq!(tx.from, Some(action.signer.address()));
+        assert_eq!(tx.to, Some(action.action.portal_address.into()));
+        // finalizeWithdrawalTransactionExternalProof((uint256,address,address,uint256,uint256,
+        //     bytes),address)
+        assert_eq!(
+            calldata(&tx)[..],
+            hex!(
+                "43ca1c50"
+                "0000000000000000000000000000000000000000000000000000000000000040"
+                "0000000000000000000000005555555555555555555555555555555555555555"
+                "0000000000000000000000000000000000000000000000000000000000000001"
+                "0000000000000000000000005cffa347b0ae99cc01e5c01714ca5658e54a23d1"
+                "0000000000000000000000005cffa347b0ae99cc01e5c01714ca5658e54a23d1"
+                "00000000000000000000000000000000000000000000000000038d7ea4c68000"
+                "00000000000000000000000000000000000000000000000000000000000186a0"
+                "00000000000000000000000000000000000000000000000000000000000000c0"
+                "0000000000000000000000000000000000000000000000000000000000000000"
+            )
+        );
+    }
 }
@@ -0,0 +1,81 @@
+//! Gas funds checks run before an action sends its transaction.
+//!
+//! Without them an action whose signer is out of native token gets as far as
+//! `eth_estimateGas` or broadcast and fails with an error that doesn't say why.
+
+use alloy_primitives::{utils::format_ether, Address, U256};
+use alloy_provider::Provider;
+use thiserror::Error;
+
+/// The signer can't pay for gas on the chain its transaction lands on.
+///
+/// Unlike RPC failures this doesn't clear up on retry: the EOA needs topping up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error(
+    "Insufficient gas funds on chain {chain}: balance {} ETH, need {} ETH",
+    format_ether(*balance),
+    format_ether(*needed)
+)]
+pub struct InsufficientGasFunds {
+    /// Chain ID of the chain the transaction lands on
+    pub chain: u64,
+    /// Native balance of the signer
+    pub balance: U256,
+    /// Estimated cost of the transaction, including any value sent
+    pub needed: U256,
+}
+
+/// Most a transaction using up to `gas_limit` gas can cost at current fees.
+pub async fn estimate_max_fee<P: Provider>(provider: &P, gas_limit: u64) -> eyre::Result<U256> {
+    let fees = provider.estimate_eip1559_fees().await?;
+    Ok(U256::from(gas_limit) * U256::from(fees.max_fee_per_gas))
+}
+
+/// Fail with [`InsufficientGasFunds`] if `from` holds less than `estimated_cost` of the
+/// native token.
+pub async fn check_gas_funds<P: Provider>(
+    provider: &P,
+    from: Address,
+    estimated_cost: U256,
+) -> eyre::Result<()> {
+    let balance = provider.get_balance(from).await?;
+    if balance >= estimated_cost {
+        return Ok(());
+    }
+
+    Err(InsufficientGasFunds {
+        chain: provider.get_chain_id().await?,
+        balance,
+        needed: estimated_cost,
+    }
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::FakeChain;
+
+    const FROM: Address = Address::repeat_byte(0x42);
+
+    #[tokio::test]
+    async fn test_check_gas_funds() {
+        let chain = FakeChain::new().with_chain_id(130);
+        let provider = chain.provider();
+        let needed = U256::from(1_000_000);
+
+        chain.set_balance(FROM, needed + U256::from(1));
+        check_gas_funds(&provider, FROM, needed).await.unwrap();
+
+        chain.set_balance(FROM, needed - U256::from(1));
+        let err = check_gas_funds(&provider, FROM, needed).await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<InsufficientGasFunds>(),
+            Some(&InsufficientGasFunds {
+                chain: 130,
+                balance: needed - U256::from(1),
+                needed,
+            })
+        );
+    }
+}
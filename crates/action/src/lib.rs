@@ -1,23 +1,19 @@
+pub mod audit;
 pub mod claim;
 pub mod deposit;
 pub mod finalize;
+pub mod gas;
+pub mod pending;
 pub mod prove;
+pub mod receipt;
+pub mod speed_up;
 pub mod withdraw;
 
-use alloy_primitives::{Bytes, TxHash, U256};
-use alloy_rpc_types::TransactionRequest;
-pub use client::fill_transaction;
-use std::{future::Future, pin::Pin, sync::Arc};
-
-/// A function that signs a transaction request and returns signed bytes.
-///
-/// This abstraction allows actions to work with both local wallet signing
-/// and remote signing via a signer-proxy service.
-pub type SignerFn = Arc<
-    dyn Fn(TransactionRequest) -> Pin<Box<dyn Future<Output = eyre::Result<Bytes>> + Send>>
-        + Send
-        + Sync,
->;
+use alloy_primitives::{Address, TxHash, U256};
+pub use client::{fill_transaction, FnSigner, SignerFn, TransactionSigner};
+use config::UnichainConfig;
+use std::future::Future;
+use withdrawal::state::PendingWithdrawal;
 
 /// Trait for executable onchain actions.
 pub trait Action: Send + Sync {
@@ -50,25 +46,98 @@ pub struct Result {
     pub gas_used: Option<U256>,
 }
 
+/// Check the input shared by prove and finalize: the portal address is set and
+/// `pending`'s hash matches its transaction. A mismatched pair would otherwise only
+/// surface as an on-chain revert.
+pub(crate) fn check_portal_input(
+    pending: &PendingWithdrawal,
+    unichain: &UnichainConfig,
+) -> eyre::Result<()> {
+    if unichain.l1_portal == Address::ZERO {
+        eyre::bail!("Portal address must be set");
+    }
+    pending.validate()?;
+    Ok(())
+}
+
 #[cfg(test)]
 pub(crate) mod test_utils {
-    use super::SignerFn;
-    use alloy_provider::{network::Ethereum, Provider, RootProvider};
+    use super::TransactionSigner;
+    use alloy_primitives::{address, Address, Bytes};
+    use alloy_rpc_types::TransactionRequest;
     use std::sync::Arc;
+    use test_support::{BASE_FEE, PRIORITY_FEE};
+
+    /// Signer for tests that don't actually execute transactions.
+    pub struct MockSigner;
+
+    #[async_trait::async_trait]
+    impl TransactionSigner for MockSigner {
+        fn address(&self) -> Address {
+            address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1")
+        }
 
-    /// Mock provider for unit tests.
-    #[derive(Clone)]
-    pub struct MockProvider;
+        fn chain_id(&self) -> Option<u64> {
+            None
+        }
 
-    impl Provider for MockProvider {
-        fn root(&self) -> &RootProvider<Ethereum> {
-            todo!()
+        async fn sign_transaction(&self, _tx: TransactionRequest) -> eyre::Result<Bytes> {
+            panic!("mock signer should not be called")
         }
     }
 
+    /// Max fee per gas estimated from the fee history served by [`push_fee_history`] and
+    /// by a `FakeChain`: twice the base fee plus the tip.
+    pub const MAX_FEE_PER_GAS: u128 = 2 * BASE_FEE + PRIORITY_FEE;
+
+    /// Serve the `eth_feeHistory` response of an EIP-1559 fee estimate.
+    pub fn push_fee_history(asserter: &alloy_transport::mock::Asserter) {
+        asserter.push_success(&alloy_rpc_types_eth::FeeHistory {
+            base_fee_per_gas: vec![BASE_FEE; 2],
+            gas_used_ratio: vec![0.5],
+            reward: Some(vec![vec![PRIORITY_FEE]]),
+            oldest_block: 1,
+            ..Default::default()
+        });
+    }
+
+    /// Serve the latest block, read for its gas limit when filling a transaction.
+    pub fn push_latest_block(asserter: &alloy_transport::mock::Asserter) {
+        let mut block: alloy_rpc_types_eth::Block = Default::default();
+        block.header.inner.gas_limit = 30_000_000;
+        asserter.push_success(&block);
+    }
+
     /// Create a mock signer for testing that panics if called.
     /// Used for tests that don't actually execute transactions.
-    pub fn mock_signer() -> SignerFn {
-        Arc::new(|_tx| Box::pin(async { panic!("mock signer should not be called") }))
+    pub fn mock_signer() -> Arc<dyn TransactionSigner> {
+        Arc::new(MockSigner)
+    }
+
+    /// A pending withdrawal whose hash is consistent with its transaction.
+    pub fn pending_withdrawal() -> withdrawal::state::PendingWithdrawal {
+        use alloy_primitives::{address, Bytes, U256};
+        use binding::opstack::WithdrawalTransaction;
+
+        let transaction = WithdrawalTransaction {
+            nonce: U256::from(1),
+            sender: address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1"),
+            target: address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1"),
+            value: U256::from(1000000000000000u64), // 0.001 ETH
+            gasLimit: U256::from(100000),
+            data: Bytes::new(),
+        };
+
+        withdrawal::state::PendingWithdrawal {
+            hash: withdrawal::hash::compute_withdrawal_hash(&transaction),
+            transaction,
+            l2_block: 42276959,
+            status: withdrawal::types::WithdrawalStatus::Initiated,
+        }
+    }
+
+    /// Calldata of an unfilled transaction request.
+    pub fn calldata(tx: &TransactionRequest) -> &Bytes {
+        tx.input.input().expect("transaction request has calldata")
     }
 }
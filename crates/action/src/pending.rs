@@ -0,0 +1,264 @@
+//! Withdrawal transactions broadcast without a receipt seen yet.
+//!
+//! Prove and finalize actions wait for their receipt, but that wait can fail (an RPC
+//! timeout, the process being stopped) while the transaction is still in the mempool.
+//! Until it lands the portal reads as if nothing was sent, so the next cycle would
+//! broadcast again with a new nonce, and one of the two is bound to revert.
+//! [`PendingTransactions`] remembers each broadcast until its receipt is seen, so the
+//! next cycle can check on it first with [`PendingTransactions::outstanding`]. The
+//! records only outlive the process if their owner persists them, see
+//! [`PendingTransactions::with_persistence`].
+
+use crate::audit::TransactionKind;
+use alloy_primitives::TxHash;
+use alloy_provider::Provider;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use tracing::{debug, info};
+use withdrawal::types::WithdrawalHash;
+
+/// A broadcast transaction for a withdrawal, as persisted between runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    pub withdrawal_hash: WithdrawalHash,
+    pub kind: TransactionKind,
+    pub tx_hash: TxHash,
+}
+
+/// Saves every record, called by [`PendingTransactions::persist`].
+pub type Persist =
+    Arc<dyn Fn(Vec<PendingTransaction>) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Broadcast transactions awaiting a receipt, by withdrawal and kind.
+///
+/// Clones share the same records.
+#[derive(Clone, Default)]
+pub struct PendingTransactions {
+    sent: Arc<Mutex<BTreeMap<(WithdrawalHash, TransactionKind), TxHash>>>,
+    persist: Option<Persist>,
+}
+
+impl fmt::Debug for PendingTransactions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingTransactions")
+            .field("sent", &self.sent)
+            .field("persisted", &self.persist.is_some())
+            .finish()
+    }
+}
+
+impl PendingTransactions {
+    pub fn from_entries(entries: impl IntoIterator<Item = PendingTransaction>) -> Self {
+        let pending = Self::default();
+        for entry in entries {
+            pending.record(entry.withdrawal_hash, entry.kind, entry.tx_hash);
+        }
+        pending
+    }
+
+    /// Hand every record to `persist` on each [`Self::persist`], so a broadcast is on
+    /// disk before its receipt is waited for.
+    pub fn with_persistence(mut self, persist: Persist) -> Self {
+        self.persist = Some(persist);
+        self
+    }
+
+    /// Save the current records, if a way to was given with [`Self::with_persistence`].
+    pub async fn persist(&self) {
+        if let Some(persist) = &self.persist {
+            persist(self.entries()).await;
+        }
+    }
+
+    /// Every recorded transaction, ordered by withdrawal.
+    pub fn entries(&self) -> Vec<PendingTransaction> {
+        self.lock()
+            .iter()
+            .map(|(&(withdrawal_hash, kind), &tx_hash)| PendingTransaction {
+                withdrawal_hash,
+                kind,
+                tx_hash,
+            })
+            .collect()
+    }
+
+    /// Remember that `tx_hash` was broadcast to `kind` withdrawal `withdrawal_hash`,
+    /// replacing any earlier transaction of the same kind.
+    pub fn record(&self, withdrawal_hash: WithdrawalHash, kind: TransactionKind, tx_hash: TxHash) {
+        self.lock().insert((withdrawal_hash, kind), tx_hash);
+    }
+
+    /// Forget the `kind` transaction of `withdrawal_hash`, once its receipt was seen.
+    pub fn clear(&self, withdrawal_hash: WithdrawalHash, kind: TransactionKind) {
+        self.lock().remove(&(withdrawal_hash, kind));
+    }
+
+    pub fn get(&self, withdrawal_hash: WithdrawalHash, kind: TransactionKind) -> Option<TxHash> {
+        self.lock().get(&(withdrawal_hash, kind)).copied()
+    }
+
+    /// Hash of the `kind` transaction of `withdrawal_hash` if it is still waiting in
+    /// the mempool.
+    ///
+    /// A recorded transaction that was mined, or that the node no longer knows
+    /// (evicted or replaced), is forgotten and `None` returned, so the caller goes on
+    /// as usual: a mined transaction's effect shows in the portal's state.
+    pub async fn outstanding<P: Provider>(
+        &self,
+        provider: &P,
+        withdrawal_hash: WithdrawalHash,
+        kind: TransactionKind,
+    ) -> eyre::Result<Option<TxHash>> {
+        let Some(tx_hash) = self.get(withdrawal_hash, kind) else {
+            return Ok(None);
+        };
+
+        match provider.get_transaction_by_hash(tx_hash).await? {
+            Some(tx) if tx.block_number.is_none() => return Ok(Some(tx_hash)),
+            Some(tx) => debug!(
+                %withdrawal_hash,
+                %tx_hash,
+                block_number = tx.block_number,
+                "Pending withdrawal transaction was mined"
+            ),
+            None => info!(
+                %withdrawal_hash,
+                %tx_hash,
+                "Pending withdrawal transaction is no longer known to the node, dropping it"
+            ),
+        }
+        self.clear(withdrawal_hash, kind);
+        self.persist().await;
+        Ok(None)
+    }
+
+    fn lock(
+        &self,
+    ) -> std::sync::MutexGuard<'_, BTreeMap<(WithdrawalHash, TransactionKind), TxHash>> {
+        self.sent
+            .lock()
+            .expect("pending transactions lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+    use test_support::FakeChain;
+
+    const WITHDRAWAL: WithdrawalHash = B256::repeat_byte(0x11);
+    const TX: TxHash = B256::repeat_byte(0xaa);
+
+    #[tokio::test]
+    async fn test_outstanding_until_receipt() {
+        let chain = FakeChain::new();
+        let pending = PendingTransactions::default();
+        let kind = TransactionKind::FinalizeWithdrawal;
+        assert_eq!(
+            pending
+                .outstanding(&chain.provider(), WITHDRAWAL, kind)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(chain.request_count("eth_getTransactionByHash"), 0);
+
+        // Broadcast, but the receipt wait gave up before it was mined
+        pending.record(WITHDRAWAL, kind, TX);
+        chain.set_transaction(TX, None);
+        for _ in 0..2 {
+            assert_eq!(
+                pending
+                    .outstanding(&chain.provider(), WITHDRAWAL, kind)
+                    .await
+                    .unwrap(),
+                Some(TX)
+            );
+        }
+        // Only for this withdrawal and kind
+        assert_eq!(
+            pending
+                .outstanding(
+                    &chain.provider(),
+                    WITHDRAWAL,
+                    TransactionKind::ProveWithdrawal
+                )
+                .await
+                .unwrap(),
+            None
+        );
+
+        chain.set_transaction(TX, Some(5));
+        assert_eq!(
+            pending
+                .outstanding(&chain.provider(), WITHDRAWAL, kind)
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(pending.entries().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_evicted_transaction_is_dropped() {
+        let chain = FakeChain::new();
+        let pending = PendingTransactions::default();
+        let kind = TransactionKind::ProveWithdrawal;
+        pending.record(WITHDRAWAL, kind, TX);
+
+        assert_eq!(
+            pending
+                .outstanding(&chain.provider(), WITHDRAWAL, kind)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(pending.get(WITHDRAWAL, kind), None);
+    }
+
+    #[tokio::test]
+    async fn test_persist_hands_over_every_record() {
+        let saved = Arc::new(Mutex::new(Vec::new()));
+        let pending = PendingTransactions::default().with_persistence({
+            let saved = saved.clone();
+            Arc::new(move |entries| {
+                *saved.lock().unwrap() = entries;
+                Box::pin(async {})
+            })
+        });
+
+        pending.record(WITHDRAWAL, TransactionKind::ProveWithdrawal, TX);
+        assert!(saved.lock().unwrap().is_empty());
+        pending.clone().persist().await;
+        assert_eq!(*saved.lock().unwrap(), pending.entries());
+
+        // Dropped from the mempool
+        pending
+            .outstanding(
+                &FakeChain::new().provider(),
+                WITHDRAWAL,
+                TransactionKind::ProveWithdrawal,
+            )
+            .await
+            .unwrap();
+        assert!(saved.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_entries_round_trip() {
+        let pending = PendingTransactions::default();
+        pending.record(WITHDRAWAL, TransactionKind::ProveWithdrawal, TX);
+        pending.record(WITHDRAWAL, TransactionKind::FinalizeWithdrawal, TX);
+
+        let restored = PendingTransactions::from_entries(pending.entries());
+        assert_eq!(restored.entries(), pending.entries());
+        assert_eq!(restored.entries().len(), 2);
+    }
+}
@@ -2,36 +2,197 @@
 //!
 //! Submits a proof to L1 that a withdrawal was initiated on L2.
 
-use crate::{Action, SignerFn};
-use alloy_primitives::{Address, U256};
+use crate::{
+    audit::{AuditEntry, AuditLog, TransactionKind},
+    check_portal_input,
+    gas::{check_gas_funds, estimate_max_fee},
+    pending::PendingTransactions,
+    receipt::confirm,
+    Action, TransactionSigner,
+};
+use alloy_primitives::{Address, Bytes, U256};
 use alloy_provider::Provider;
+use alloy_rpc_types::TransactionRequest;
 use binding::opstack::{IOptimismPortal2, WithdrawalTransaction};
-use tracing::info;
-use withdrawal::{proof::generate_proof, state::WithdrawalStateProvider, types::WithdrawalHash};
+use client::retry::RetryBudget;
+use config::UnichainConfig;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, info, warn};
+use withdrawal::{
+    game::GameCache,
+    portal::PortalParamsCache,
+    proof::{compare_block_hash, generate_proof, GameSelectionStrategy, ProveWithdrawalParams},
+    state::{PendingWithdrawal, WithdrawalStateProvider},
+    types::WithdrawalHash,
+};
 
 /// Input data for proving a withdrawal on L1.
 #[derive(Clone, Debug)]
 pub struct Prove {
+    /// Chain ID of the rollup the withdrawal was initiated on
+    pub chain_id: u64,
     /// OptimismPortal2 contract address on L1
     pub portal_address: Address,
-    /// DisputeGameFactory contract address on L1
-    pub factory_address: Address,
+    /// L2ToL1MessagePasser contract address on L2, whose storage the proof is built from
+    pub message_passer: Address,
     /// The withdrawal transaction details
     pub withdrawal: WithdrawalTransaction,
     /// Hash of the withdrawal
     pub withdrawal_hash: WithdrawalHash,
     /// L2 block number where the withdrawal was initiated
     pub l2_block: u64,
-    /// Address that will submit the proof transaction
-    pub from: Address,
+    /// Minimum number of dispute games the factory must hold before proving
+    pub min_game_count: u64,
+    /// Dispute game types to prove against, empty for whichever the portal respects
+    pub allowed_game_types: Vec<u32>,
+    /// Which covering dispute game to prove against
+    pub game_selection: GameSelectionStrategy,
+    /// Largest storage proof that will be submitted
+    pub proof_limits: ProofLimits,
 }
 
+impl Prove {
+    /// Build a prove input for a pending withdrawal on the given chain.
+    ///
+    /// Requires at least one dispute game by default; see [`Self::with_min_game_count`].
+    pub fn from_pending(
+        pending: &PendingWithdrawal,
+        unichain: &UnichainConfig,
+    ) -> eyre::Result<Self> {
+        check_portal_input(pending, unichain)?;
+
+        Ok(Self {
+            chain_id: unichain.chain_id,
+            portal_address: unichain.l1_portal,
+            message_passer: unichain.l2_to_l1_message_passer,
+            withdrawal: pending.transaction.clone(),
+            withdrawal_hash: pending.hash,
+            l2_block: pending.l2_block,
+            min_game_count: 1,
+            allowed_game_types: vec![],
+            game_selection: GameSelectionStrategy::default(),
+            proof_limits: ProofLimits::default(),
+        })
+    }
+
+    /// Set the minimum number of dispute games required before proving.
+    pub const fn with_min_game_count(mut self, min_game_count: u64) -> Self {
+        self.min_game_count = min_game_count;
+        self
+    }
+
+    /// Set the largest storage proof that will be submitted.
+    pub const fn with_proof_limits(mut self, proof_limits: ProofLimits) -> Self {
+        self.proof_limits = proof_limits;
+        self
+    }
+
+    /// Only prove against dispute games of `game_types`.
+    pub fn with_allowed_game_types(mut self, game_types: Vec<u32>) -> Self {
+        self.allowed_game_types = game_types;
+        self
+    }
+
+    /// Choose among the dispute games covering the withdrawal by `strategy`.
+    pub const fn with_game_selection(mut self, strategy: GameSelectionStrategy) -> Self {
+        self.game_selection = strategy;
+        self
+    }
+}
+
+/// Upper bounds on a withdrawal's storage proof.
+///
+/// A storage proof in the L2ToL1MessagePasser is a handful of trie nodes of at most a
+/// few hundred bytes each. A much larger one points at a broken or malicious RPC, and
+/// submitting it would burn gas on calldata for a proof that can't verify.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofLimits {
+    /// Most trie nodes accepted
+    pub max_nodes: usize,
+    /// Most total bytes across all nodes accepted
+    pub max_bytes: usize,
+}
+
+impl Default for ProofLimits {
+    fn default() -> Self {
+        Self {
+            max_nodes: 32,
+            max_bytes: 32 * 1024,
+        }
+    }
+}
+
+impl ProofLimits {
+    pub const fn new(max_nodes: usize, max_bytes: usize) -> Self {
+        Self {
+            max_nodes,
+            max_bytes,
+        }
+    }
+
+    /// Check `proof` against the limits, returning its total size in bytes.
+    pub fn check(&self, proof: &[Bytes]) -> Result<usize, ProofTooLarge> {
+        let nodes = proof.len();
+        let bytes = proof.iter().map(|node| node.len()).sum();
+        if nodes > self.max_nodes || bytes > self.max_bytes {
+            return Err(ProofTooLarge {
+                nodes,
+                bytes,
+                limits: *self,
+            });
+        }
+        Ok(bytes)
+    }
+}
+
+/// A storage proof over its [`ProofLimits`].
+#[derive(Clone, Copy, Debug, Error)]
+#[error(
+    "Withdrawal proof implausibly large: {nodes} nodes, {bytes} bytes (max {} nodes, {} bytes)",
+    limits.max_nodes,
+    limits.max_bytes
+)]
+pub struct ProofTooLarge {
+    pub nodes: usize,
+    pub bytes: usize,
+    pub limits: ProofLimits,
+}
+
+/// Dispute game a withdrawal was proven against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProvenGame {
+    /// Index of the game in the DisputeGameFactory
+    pub index: U256,
+    /// Address of the game proxy
+    pub proxy: Address,
+}
+
+/// Gas a `proveWithdrawalTransaction` call is assumed to use, with room for a storage
+/// proof at [`ProofLimits`]' default size.
+pub const PROVE_GAS_LIMIT: u64 = 600_000;
+
 /// Action to prove a withdrawal on L1.
 pub struct ProveAction<P1, P2> {
     l1_provider: P1,
     l2_provider: P2,
-    signer: SignerFn,
+    signer: Arc<dyn TransactionSigner>,
     action: Prove,
+    /// Second L2 endpoint the proof's block hash is compared against, if any
+    reference_l2_provider: Option<P2>,
+    /// Respected game type, shared across actions
+    portal_params: PortalParamsCache,
+    /// Dispute games' L2 blocks, shared across actions
+    game_cache: GameCache,
+    /// Retries of the storage proof request are drawn from it
+    retry_budget: RetryBudget,
+    /// Proof generated ahead of time, submitted instead of generating one
+    proof: Option<ProveWithdrawalParams>,
+    /// Set once a proof has been submitted
+    proven_game: Option<ProvenGame>,
+    audit_log: AuditLog,
+    /// Broadcasts awaiting a receipt, shared across actions
+    pending_transactions: PendingTransactions,
 }
 
 impl<P1, P2> ProveAction<P1, P2>
@@ -39,20 +200,145 @@ where
     P1: Provider + Clone,
     P2: Provider + Clone,
 {
-    pub fn new(l1_provider: P1, l2_provider: P2, signer: SignerFn, action: Prove) -> Self {
+    pub fn new(
+        l1_provider: P1,
+        l2_provider: P2,
+        signer: Arc<dyn TransactionSigner>,
+        action: Prove,
+    ) -> Self {
         Self {
             l1_provider,
             l2_provider,
             signer,
             action,
+            reference_l2_provider: None,
+            portal_params: PortalParamsCache::default(),
+            game_cache: GameCache::default(),
+            retry_budget: RetryBudget::unlimited(),
+            proof: None,
+            proven_game: None,
+            audit_log: AuditLog::default(),
+            pending_transactions: PendingTransactions::default(),
         }
     }
 
+    /// Record the broadcast transaction in `log`.
+    pub fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = log;
+        self
+    }
+
+    /// Record the broadcast transaction in `pending` until its receipt is seen.
+    pub fn with_pending_transactions(mut self, pending: PendingTransactions) -> Self {
+        self.pending_transactions = pending;
+        self
+    }
+
+    /// Read the portal's respected game type through `cache` instead of on every run.
+    pub fn with_portal_params(mut self, cache: PortalParamsCache) -> Self {
+        self.portal_params = cache;
+        self
+    }
+
+    /// Read dispute games' L2 blocks through `cache`, shared with other searches.
+    pub fn with_game_cache(mut self, cache: GameCache) -> Self {
+        self.game_cache = cache;
+        self
+    }
+
+    /// Draw retries of the storage proof request from `budget`, e.g. the cycle's.
+    pub fn with_retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = budget;
+        self
+    }
+
+    /// Submit `proof` instead of generating one, e.g. a proof from a batched
+    /// [`generate_proofs_for_game`](withdrawal::proof::generate_proofs_for_game) call.
+    pub fn with_proof(mut self, proof: ProveWithdrawalParams) -> Self {
+        self.proof = Some(proof);
+        self
+    }
+
+    /// Compare the block hash the proof is built from with another L2 endpoint before
+    /// submitting, warning when they diverge. Useful when `l2_provider` is a dedicated
+    /// proof endpoint.
+    pub fn with_reference_l2_provider(mut self, provider: P2) -> Self {
+        self.reference_l2_provider = Some(provider);
+        self
+    }
+
     /// Get the withdrawal hash for this action.
     pub const fn withdrawal_hash(&self) -> WithdrawalHash {
         self.action.withdrawal_hash
     }
 
+    /// Dispute game the proof was submitted against, once [`Action::execute`] succeeded.
+    pub const fn proven_game(&self) -> Option<ProvenGame> {
+        self.proven_game
+    }
+
+    /// Run the proof submission through `eth_call` without signing or broadcasting it.
+    ///
+    /// The proof is generated unless one was supplied with [`Self::with_proof`]. Fails
+    /// with the revert reason if the transaction would revert.
+    pub async fn simulate(&self) -> eyre::Result<()> {
+        let proof_params = self.proof_params().await?;
+        self.action
+            .proof_limits
+            .check(&proof_params.withdrawal_proof)?;
+        self.l1_provider
+            .call(self.transaction_request(proof_params))
+            .await?;
+        Ok(())
+    }
+
+    /// The pre-generated proof, or a freshly generated one.
+    async fn proof_params(&self) -> eyre::Result<ProveWithdrawalParams> {
+        match &self.proof {
+            Some(proof) if proof.withdrawal == self.action.withdrawal => Ok(proof.clone()),
+            Some(_) => eyre::bail!("Pre-generated proof is for a different withdrawal"),
+            None => {
+                info!(
+                    chain_id = self.action.chain_id,
+                    withdrawal_hash = %self.action.withdrawal_hash,
+                    l2_block = self.action.l2_block,
+                    "Generating withdrawal proof"
+                );
+
+                generate_proof(
+                    &self.l1_provider,
+                    &self.l2_provider,
+                    self.action.portal_address,
+                    self.action.message_passer,
+                    &self.portal_params,
+                    &self.game_cache,
+                    self.action.withdrawal_hash,
+                    self.action.withdrawal.clone(),
+                    self.action.l2_block,
+                    self.action.min_game_count,
+                    &self.action.allowed_game_types,
+                    self.action.game_selection,
+                    &self.retry_budget,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Unfilled `proveWithdrawalTransaction` transaction submitting `proof_params`.
+    pub fn transaction_request(&self, proof_params: ProveWithdrawalParams) -> TransactionRequest {
+        let portal = IOptimismPortal2::new(self.action.portal_address, &self.l1_provider);
+        portal
+            .proveWithdrawalTransaction(
+                proof_params.withdrawal,
+                proof_params.dispute_game_index,
+                proof_params.output_root_proof,
+                proof_params.withdrawal_proof,
+            )
+            .into_transaction_request()
+            .from(self.signer.address())
+    }
+
     /// Check if the withdrawal has been proven using WithdrawalStateProvider.
     async fn check_is_proven(&self) -> eyre::Result<bool> {
         let state = WithdrawalStateProvider::new(
@@ -77,7 +363,13 @@ where
 {
     async fn is_ready(&self) -> eyre::Result<bool> {
         // Ready if not already proven
-        Ok(!self.check_is_proven().await?)
+        if self.check_is_proven().await? {
+            return Ok(false);
+        }
+
+        let cost = estimate_max_fee(&self.l1_provider, PROVE_GAS_LIMIT).await?;
+        check_gas_funds(&self.l1_provider, self.signer.address(), cost).await?;
+        Ok(true)
     }
 
     async fn is_completed(&self) -> eyre::Result<bool> {
@@ -89,58 +381,92 @@ where
             eyre::bail!("Withdrawal already proven")
         }
 
-        // Generate the proof
-        info!(
-            withdrawal_hash = %self.action.withdrawal_hash,
-            l2_block = self.action.l2_block,
-            "Generating withdrawal proof"
-        );
+        let proof_params = self.proof_params().await?;
 
-        let proof_params = generate_proof(
-            &self.l1_provider,
-            &self.l2_provider,
-            self.action.portal_address,
-            self.action.factory_address,
-            self.action.withdrawal_hash,
-            self.action.withdrawal.clone(),
-            self.action.l2_block,
-        )
-        .await?;
+        // Refuse a gas-bomb proof from a misbehaving RPC
+        let proof_bytes = self
+            .action
+            .proof_limits
+            .check(&proof_params.withdrawal_proof)?;
+        debug!(
+            proof_nodes = proof_params.withdrawal_proof.len(),
+            proof_bytes, "Proof size within limits"
+        );
 
         info!(
+            chain_id = self.action.chain_id,
             dispute_game_index = %proof_params.dispute_game_index,
+            dispute_game_proxy = %proof_params.dispute_game_proxy,
             proof_nodes = proof_params.withdrawal_proof.len(),
             "Proof generated, submitting to L1"
         );
 
+        if let Some(reference) = &self.reference_l2_provider {
+            if let Err(e) = compare_block_hash(
+                reference,
+                proof_params.l2_block,
+                proof_params.output_root_proof.latestBlockhash,
+            )
+            .await
+            {
+                warn!(
+                    block = proof_params.l2_block,
+                    error = %e,
+                    "Could not cross-check the proof's block hash"
+                );
+            }
+        }
+
+        let game = ProvenGame {
+            index: proof_params.dispute_game_index,
+            proxy: proof_params.dispute_game_proxy,
+        };
+
         // Build the transaction request
-        let portal = IOptimismPortal2::new(self.action.portal_address, &self.l1_provider);
-        let call = portal.proveWithdrawalTransaction(
-            proof_params.withdrawal,
-            proof_params.dispute_game_index,
-            proof_params.output_root_proof,
-            proof_params.withdrawal_proof,
-        );
-        let tx_request = call.into_transaction_request().from(self.action.from);
+        let tx_request = self.transaction_request(proof_params);
 
         // Fill transaction fields (nonce, gas, fees) using our provider
         let filled_tx = client::fill_transaction(tx_request, &self.l1_provider).await?;
 
         // Sign externally
-        let signed_tx = (self.signer)(filled_tx).await?;
+        let signed_tx = self.signer.sign_transaction(filled_tx.clone()).await?;
 
         // Broadcast the signed transaction
-        let pending = self.l1_provider.send_raw_transaction(&signed_tx).await?;
-        let receipt = pending.get_receipt().await?;
+        let receipt = self
+            .audit_log
+            .broadcast(
+                AuditEntry::new(TransactionKind::ProveWithdrawal, &filled_tx, &signed_tx)
+                    .with_withdrawal_hash(self.action.withdrawal_hash),
+                async {
+                    let pending = self.l1_provider.send_raw_transaction(&signed_tx).await?;
+                    self.pending_transactions.record(
+                        self.action.withdrawal_hash,
+                        TransactionKind::ProveWithdrawal,
+                        *pending.tx_hash(),
+                    );
+                    self.pending_transactions.persist().await;
+                    Ok(pending)
+                },
+            )
+            .await?;
+        self.pending_transactions.clear(
+            self.action.withdrawal_hash,
+            TransactionKind::ProveWithdrawal,
+        );
+        self.pending_transactions.persist().await;
+        let receipt = confirm(&self.l1_provider, &filled_tx, receipt).await?;
 
         info!(
+            chain_id = self.action.chain_id,
             tx_hash = %receipt.transaction_hash,
             block_number = receipt.block_number,
             gas_used = receipt.gas_used,
             withdrawal_hash = %self.action.withdrawal_hash,
-            dispute_game_index = %proof_params.dispute_game_index,
+            dispute_game_index = %game.index,
+            dispute_game_proxy = %game.proxy,
             "Withdrawal proven on L1"
         );
+        self.proven_game = Some(game);
 
         Ok(crate::Result {
             tx_hash: receipt.transaction_hash,
@@ -157,13 +483,17 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::{mock_signer, MockProvider};
-    use alloy_primitives::{address, b256, Bytes};
+    use crate::test_utils::{calldata, mock_signer, pending_withdrawal};
+    use alloy_primitives::{address, b256, hex, Bytes, B256};
+    use alloy_provider::RootProvider;
+    use binding::opstack::OutputRootProof;
+    use test_support::FakeChain;
 
-    fn create_test_prove_action() -> ProveAction<MockProvider, MockProvider> {
+    fn create_test_prove_action() -> ProveAction<RootProvider, RootProvider> {
         let prove = Prove {
+            chain_id: 1301,
             portal_address: address!("0d83dab629f0e0F9d36c0Cbc89B69a489f0751bD"),
-            factory_address: address!("eff73e5aa3B9AEC32c659Aa3E00444d20a84394b"),
+            message_passer: address!("4200000000000000000000000000000000000016"),
             withdrawal: WithdrawalTransaction {
                 nonce: U256::from(1),
                 sender: address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1"),
@@ -176,10 +506,73 @@ mod tests {
                 "1111111111111111111111111111111111111111111111111111111111111111"
             ),
             l2_block: 42276959,
-            from: address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1"),
+            min_game_count: 1,
+            allowed_game_types: vec![],
+            game_selection: GameSelectionStrategy::default(),
+            proof_limits: ProofLimits::default(),
         };
 
-        ProveAction::new(MockProvider, MockProvider, mock_signer(), prove)
+        ProveAction::new(
+            FakeChain::new().provider(),
+            FakeChain::new().provider(),
+            mock_signer(),
+            prove,
+        )
+    }
+
+    #[test]
+    fn test_from_pending() {
+        let pending = pending_withdrawal();
+        let unichain = UnichainConfig::mainnet();
+
+        let prove = Prove::from_pending(&pending, &unichain)
+            .unwrap()
+            .with_min_game_count(3)
+            .with_allowed_game_types(vec![0])
+            .with_game_selection(GameSelectionStrategy::NewestCovering);
+        assert_eq!(prove.chain_id, unichain.chain_id);
+        assert_eq!(prove.portal_address, unichain.l1_portal);
+        assert_eq!(prove.message_passer, unichain.l2_to_l1_message_passer);
+        assert_eq!(prove.withdrawal_hash, pending.hash);
+        assert_eq!(prove.l2_block, pending.l2_block);
+        assert_eq!(prove.min_game_count, 3);
+        assert_eq!(prove.allowed_game_types, [0]);
+        assert_eq!(prove.game_selection, GameSelectionStrategy::NewestCovering);
+    }
+
+    #[test]
+    fn test_from_pending_rejects_unset_portal() {
+        let pending = pending_withdrawal();
+        let mut unichain = UnichainConfig::mainnet();
+        unichain.l1_portal = Address::ZERO;
+        assert!(Prove::from_pending(&pending, &unichain).is_err());
+    }
+
+    #[test]
+    fn test_from_pending_rejects_hash_mismatch() {
+        let mut pending = pending_withdrawal();
+        pending.hash = WithdrawalHash::repeat_byte(0x11);
+        let err = Prove::from_pending(&pending, &UnichainConfig::mainnet()).unwrap_err();
+        assert!(err.to_string().contains("hash mismatch"));
+    }
+
+    #[test]
+    fn test_proof_limits() {
+        let limits = ProofLimits::new(3, 100);
+        let node = Bytes::from(vec![0u8; 40]);
+
+        assert_eq!(limits.check(&[node.clone(), node.clone()]).unwrap(), 80);
+        assert_eq!(limits.check(&[]).unwrap(), 0);
+
+        // Too many bytes
+        let err = limits.check(&vec![node; 3]).unwrap_err();
+        assert_eq!((err.nodes, err.bytes), (3, 120));
+
+        // Too many nodes
+        let tiny = Bytes::from(vec![0u8; 1]);
+        let err = limits.check(&vec![tiny; 4]).unwrap_err();
+        assert_eq!(err.nodes, 4);
+        assert!(err.to_string().contains("max 3 nodes, 100 bytes"));
     }
 
     #[test]
@@ -197,5 +590,63 @@ mod tests {
             action.withdrawal_hash(),
             b256!("1111111111111111111111111111111111111111111111111111111111111111")
         );
+        assert_eq!(action.proven_game(), None);
+    }
+
+    #[test]
+    fn test_transaction_request_calldata() {
+        let action = create_test_prove_action();
+        let mut withdrawal = action.action.withdrawal.clone();
+        withdrawal.data = Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]);
+        let proof_params = ProveWithdrawalParams {
+            withdrawal,
+            dispute_game_index: U256::from(7),
+            dispute_game_proxy: Address::repeat_byte(0x44),
+            l2_block: 100,
+            output_root_proof: OutputRootProof {
+                version: B256::ZERO,
+                stateRoot: B256::repeat_byte(0x11),
+                messagePasserStorageRoot: B256::repeat_byte(0x22),
+                latestBlockhash: B256::repeat_byte(0x33),
+            },
+            withdrawal_proof: vec![
+                Bytes::from_static(&[0xf8, 0x51]),
+                Bytes::from_static(&[0xe2, 0x10, 0x01]),
+            ],
+        };
+        let tx = action.transaction_request(proof_params);
+
+        assert_eq!(tx.from, Some(action.signer.address()));
+        assert_eq!(tx.to, Some(action.action.portal_address.into()));
+        // proveWithdrawalTransaction((uint256,address,address,uint256,uint256,bytes),uint256,
+        //     (bytes32,bytes32,bytes32,bytes32),bytes[])
+        assert_eq!(
+            calldata(&tx)[..],
+            hex!(
+                "4870496f"
+                "00000000000000000000000000000000000000000000000000000000000000e0"
+                "0000000000000000000000000000000000000000000000000000000000000007"
+                "0000000000000000000000000000000000000000000000000000000000000000"
+                "1111111111111111111111111111111111111111111111111111111111111111"
+                "2222222222222222222222222222222222222222222222222222222222222222"
+                "3333333333333333333333333333333333333333333333333333333333333333"
+                "00000000000000000000000000000000000000000000000000000000000001e0"
+                "0000000000000000000000000000000000000000000000000000000000000001"
+                "0000000000000000000000005cffa347b0ae99cc01e5c01714ca5658e54a23d1"
+                "0000000000000000000000005cffa347b0ae99cc01e5c01714ca5658e54a23d1"
+                "00000000000000000000000000000000000000000000000000038d7ea4c68000"
+                "00000000000000000000000000000000000000000000000000000000000186a0"
+                "00000000000000000000000000000000000000000000000000000000000000c0"
+                "0000000000000000000000000000000000000000000000000000000000000004"
+                "deadbeef00000000000000000000000000000000000000000000000000000000"
+                "0000000000000000000000000000000000000000000000000000000000000002"
+                "0000000000000000000000000000000000000000000000000000000000000040"
+                "0000000000000000000000000000000000000000000000000000000000000080"
+                "0000000000000000000000000000000000000000000000000000000000000002"
+                "f851000000000000000000000000000000000000000000000000000000000000"
+                "0000000000000000000000000000000000000000000000000000000000000003"
+                "e210010000000000000000000000000000000000000000000000000000000000"
+            )
+        );
     }
 }
@@ -0,0 +1,183 @@
+//! Checks on the receipt of a submitted transaction.
+//!
+//! Every action broadcasts its signed transaction and waits for the receipt. A receipt
+//! only says the transaction reverted, not why, so [`confirm`] replays a reverted
+//! transaction against the state it ran on and decodes the revert reason. All actions
+//! go through it and report reverts as [`TransactionReverted`].
+
+use alloy_primitives::{hex, Log as PrimitiveLog, TxHash};
+use alloy_provider::Provider;
+use alloy_rpc_types::TransactionRequest;
+use alloy_rpc_types_eth::{BlockId, Log, TransactionReceipt};
+use alloy_sol_types::{decode_revert_reason, SolEvent};
+use thiserror::Error;
+use tracing::debug;
+
+/// A mined transaction reverted.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "Transaction {tx_hash} reverted: {}",
+    reason.as_deref().unwrap_or("reason unknown")
+)]
+pub struct TransactionReverted {
+    pub tx_hash: TxHash,
+    pub block_number: Option<u64>,
+    /// Revert reason decoded from replaying the transaction, if it reverted again
+    pub reason: Option<String>,
+}
+
+/// Pass `receipt` of `tx` through if it succeeded, fail with [`TransactionReverted`]
+/// otherwise.
+///
+/// A reverted transaction is replayed with `eth_call` on top of the block before the one
+/// it was included in to recover the revert reason. Transactions earlier in the same
+/// block are not replayed, so the reason is missing if one of them made it revert.
+pub async fn confirm<P: Provider>(
+    provider: &P,
+    tx: &TransactionRequest,
+    receipt: TransactionReceipt,
+) -> eyre::Result<TransactionReceipt> {
+    if receipt.status() {
+        return Ok(receipt);
+    }
+
+    let reason = match receipt.block_number {
+        Some(block) => replay_revert_reason(provider, tx, block.saturating_sub(1)).await,
+        None => None,
+    };
+    Err(TransactionReverted {
+        tx_hash: receipt.transaction_hash,
+        block_number: receipt.block_number,
+        reason,
+    }
+    .into())
+}
+
+/// Revert reason of `tx` when simulated at `block`, if it reverts there.
+async fn replay_revert_reason<P: Provider>(
+    provider: &P,
+    tx: &TransactionRequest,
+    block: u64,
+) -> Option<String> {
+    let err = match provider
+        .call(tx.clone())
+        .block(BlockId::number(block))
+        .await
+    {
+        Ok(_) => {
+            debug!(block, "Reverted transaction succeeds when replayed");
+            return None;
+        }
+        Err(e) => e,
+    };
+    let Some(data) = err.as_error_resp().and_then(|resp| resp.as_revert_data()) else {
+        debug!(block, error = %err, "Could not replay reverted transaction");
+        return None;
+    };
+
+    // Custom errors are left for the reader to decode
+    Some(decode_revert_reason(&data).unwrap_or_else(|| format!("0x{}", hex::encode(&data))))
+}
+
+/// Events of type `E` among `logs`, with the address that emitted them.
+pub fn decode_events<E: SolEvent>(logs: &[Log]) -> impl Iterator<Item = PrimitiveLog<E>> + '_ {
+    logs.iter().filter_map(|log| E::decode_log(&log.inner).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+    use alloy_provider::ProviderBuilder;
+    use alloy_rpc_types_eth::{Receipt, ReceiptEnvelope, ReceiptWithBloom};
+    use alloy_sol_types::{Revert, SolError};
+    use alloy_transport::mock::Asserter;
+
+    fn receipt(status: bool) -> TransactionReceipt {
+        TransactionReceipt {
+            inner: ReceiptEnvelope::Eip1559(ReceiptWithBloom {
+                receipt: Receipt {
+                    status: status.into(),
+                    cumulative_gas_used: 60_000,
+                    logs: vec![],
+                },
+                logs_bloom: Default::default(),
+            }),
+            transaction_hash: B256::repeat_byte(0xaa),
+            transaction_index: Some(0),
+            block_hash: None,
+            block_number: Some(42),
+            gas_used: 60_000,
+            effective_gas_price: 1_000,
+            blob_gas_used: None,
+            blob_gas_price: None,
+            from: Address::repeat_byte(1),
+            to: Some(Address::repeat_byte(2)),
+            contract_address: None,
+        }
+    }
+
+    fn push_revert(asserter: &Asserter, data: &[u8]) {
+        asserter.push_failure(alloy_json_rpc::ErrorPayload {
+            code: 3,
+            message: "execution reverted".into(),
+            data: Some(
+                serde_json::value::to_raw_value(&format!("0x{}", hex::encode(data))).unwrap(),
+            ),
+        });
+    }
+
+    #[tokio::test]
+    async fn test_confirm_passes_successful_receipt() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter);
+
+        let confirmed = confirm(&provider, &TransactionRequest::default(), receipt(true))
+            .await
+            .unwrap();
+        assert_eq!(confirmed.transaction_hash, B256::repeat_byte(0xaa));
+    }
+
+    #[tokio::test]
+    async fn test_confirm_decodes_revert_reason() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        push_revert(
+            &asserter,
+            &Revert::from("OptimismPortal: paused").abi_encode(),
+        );
+
+        let err = confirm(&provider, &TransactionRequest::default(), receipt(false))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TransactionReverted>(),
+            Some(&TransactionReverted {
+                tx_hash: B256::repeat_byte(0xaa),
+                block_number: Some(42),
+                reason: Some("revert: OptimismPortal: paused".to_string()),
+            })
+        );
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_confirm_reports_revert_without_reason() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        asserter.push_failure_msg("header not found");
+
+        let err = confirm(&provider, &TransactionRequest::default(), receipt(false))
+            .await
+            .unwrap_err();
+        let reverted = err.downcast_ref::<TransactionReverted>().unwrap();
+        assert_eq!(reverted.reason, None);
+        assert_eq!(
+            reverted.to_string(),
+            format!(
+                "Transaction {} reverted: reason unknown",
+                B256::repeat_byte(0xaa)
+            )
+        );
+    }
+}
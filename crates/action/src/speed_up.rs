@@ -0,0 +1,512 @@
+//! Speeding up a deposit that is waiting for a slow fill.
+//!
+//! Deposits are made with an output amount at or above their input, which no relayer
+//! fills, so they wait for a slow fill. When the funds are needed sooner, the depositor
+//! signs a lower output amount: the difference is the relayer's fee, and a relayer
+//! fills the deposit fast. [`SpeedUpDepositAction`] signs the updated details (the
+//! EIP-712 [`UpdateDepositDetails`]) and publishes them with `speedUpV3Deposit` on the
+//! origin SpokePool.
+
+use crate::{
+    audit::{AuditEntry, AuditLog, TransactionKind},
+    gas::{check_gas_funds, estimate_max_fee},
+    receipt::confirm,
+    TransactionSigner,
+};
+use alloy_dyn_abi::{eip712::Resolver, TypedData};
+use alloy_primitives::{utils::format_ether, Address, Bytes, B256, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types::TransactionRequest;
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use alloy_sol_types::{eip712_domain, SolStruct};
+use binding::across::{ISpokePool, UpdateDepositDetails};
+use deposit::DepositStateProvider;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Gas a `speedUpV3Deposit` call is assumed to use.
+const SPEED_UP_GAS_LIMIT: u64 = 100_000;
+
+/// Updated details for an in-flight deposit.
+#[derive(Debug, Clone)]
+pub struct SpeedUp {
+    /// ISpokePool contract address on the origin chain, where the deposit was made
+    pub spoke_pool: Address,
+    /// ISpokePool contract address on the destination chain, where it is filled
+    pub destination_spoke_pool: Address,
+    /// Chain ID of the origin chain
+    pub origin_chain_id: u64,
+    /// Deposit to speed up
+    pub deposit_id: U256,
+    /// Depositor, who signs the updated details - must match the signer
+    pub depositor: Address,
+    /// Output amount the deposit was made with
+    pub output_amount: U256,
+    /// New output amount, below `output_amount`; the difference goes to the relayer
+    pub updated_output_amount: U256,
+    /// Recipient on the destination chain
+    pub updated_recipient: Address,
+    /// Message passed to the recipient
+    pub updated_message: Bytes,
+    /// Fill deadline of the deposit (destination chain timestamp), zero for none
+    pub fill_deadline: u32,
+    /// Origin chain block the deposit was made in
+    pub deposit_block: u64,
+    /// Destination chain block at or before the deposit, from which fills are searched
+    pub fill_search_from_block: u64,
+}
+
+/// Action that lowers the output amount of an unfilled deposit so it is filled fast.
+pub struct SpeedUpDepositAction<P1, P2> {
+    l1_provider: P1,
+    l2_provider: P2,
+    deposits: DepositStateProvider<P1, P2>,
+    signer: Arc<dyn TransactionSigner>,
+    speed_up: SpeedUp,
+    audit_log: AuditLog,
+}
+
+impl<P1, P2> SpeedUpDepositAction<P1, P2>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    /// `l1_provider` reads and writes the origin chain, `l2_provider` reads the
+    /// destination chain.
+    pub fn new(
+        l1_provider: P1,
+        l2_provider: P2,
+        signer: Arc<dyn TransactionSigner>,
+        speed_up: SpeedUp,
+    ) -> Self {
+        let deposits = DepositStateProvider::new(
+            l1_provider.clone(),
+            l2_provider.clone(),
+            speed_up.spoke_pool,
+            speed_up.destination_spoke_pool,
+        );
+        Self {
+            l1_provider,
+            l2_provider,
+            deposits,
+            signer,
+            speed_up,
+            audit_log: AuditLog::default(),
+        }
+    }
+
+    /// Record the broadcast transaction in `log`.
+    pub fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = log;
+        self
+    }
+
+    fn validate_speed_up(&self) -> eyre::Result<()> {
+        if self.speed_up.spoke_pool == Address::ZERO
+            || self.speed_up.destination_spoke_pool == Address::ZERO
+        {
+            eyre::bail!("Spoke pools must not be zero");
+        }
+
+        if self.speed_up.updated_recipient == Address::ZERO {
+            eyre::bail!("Updated recipient must not be zero");
+        }
+
+        if self.speed_up.updated_output_amount >= self.speed_up.output_amount {
+            eyre::bail!(
+                "Updated output amount {} must be below the deposit's output amount {}",
+                self.speed_up.updated_output_amount,
+                self.speed_up.output_amount
+            );
+        }
+
+        // The SpokePool checks the signature against the depositor
+        if self.speed_up.depositor != self.signer.address() {
+            eyre::bail!(
+                "Depositor {} is not the signer {}",
+                self.speed_up.depositor,
+                self.signer.address()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The updated details for the depositor to sign.
+    pub fn typed_data(&self) -> TypedData {
+        TypedData {
+            domain: eip712_domain! {
+                name: "ACROSS-V2",
+                version: "1.0.0",
+                chain_id: self.speed_up.origin_chain_id,
+            },
+            resolver: Resolver::from_struct::<UpdateDepositDetails>(),
+            primary_type: UpdateDepositDetails::NAME.to_string(),
+            message: json!({
+                "depositId": self.speed_up.deposit_id,
+                "originChainId": U256::from(self.speed_up.origin_chain_id),
+                "updatedOutputAmount": self.speed_up.updated_output_amount,
+                "updatedRecipient": self.speed_up.updated_recipient,
+                "updatedMessage": self.speed_up.updated_message,
+            }),
+        }
+    }
+
+    /// Unfilled `speedUpV3Deposit` transaction carrying `depositor_signature`.
+    pub fn transaction_request(&self, depositor_signature: Bytes) -> TransactionRequest {
+        let contract = ISpokePool::new(self.speed_up.spoke_pool, &self.l1_provider);
+        contract
+            .speedUpV3Deposit(
+                self.speed_up.depositor,
+                self.speed_up.deposit_id,
+                self.speed_up.updated_output_amount,
+                self.speed_up.updated_recipient,
+                self.speed_up.updated_message.clone(),
+                depositor_signature,
+            )
+            .into_transaction_request()
+            .from(self.signer.address())
+    }
+
+    /// Whether the deposit's fill deadline has passed on the destination chain. A zero
+    /// deadline never passes.
+    async fn is_expired(&self) -> eyre::Result<bool> {
+        if self.speed_up.fill_deadline == 0 {
+            return Ok(false);
+        }
+        let latest = self
+            .l2_provider
+            .get_block_by_number(BlockNumberOrTag::Latest)
+            .await?
+            .ok_or_else(|| eyre::eyre!("Latest destination block not found"))?;
+        Ok(u64::from(self.speed_up.fill_deadline) < latest.header.timestamp)
+    }
+}
+
+impl<P1, P2> crate::Action for SpeedUpDepositAction<P1, P2>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    async fn is_ready(&self) -> eyre::Result<bool> {
+        // Basic validation, before any RPC call
+        if self.validate_speed_up().is_err() {
+            return Ok(false);
+        }
+
+        // Only a deposit still in flight can be filled with the updated details
+        if self.is_expired().await? {
+            return Ok(false);
+        }
+        let filled = self
+            .deposits
+            .is_filled(
+                self.speed_up.origin_chain_id,
+                self.speed_up.deposit_id,
+                self.speed_up.fill_search_from_block,
+            )
+            .await?;
+        if filled {
+            return Ok(false);
+        }
+
+        let cost = estimate_max_fee(&self.l1_provider, SPEED_UP_GAS_LIMIT).await?;
+        check_gas_funds(&self.l1_provider, self.signer.address(), cost).await?;
+        Ok(true)
+    }
+
+    async fn is_completed(&self) -> eyre::Result<bool> {
+        // Done once details at least as generous to the relayer were published
+        let contract = ISpokePool::new(self.speed_up.spoke_pool, &self.l1_provider);
+        let requested = contract
+            .RequestedSpeedUpDeposit_filter()
+            .topic1(self.speed_up.deposit_id)
+            .topic2(B256::left_padding_from(self.speed_up.depositor.as_slice()))
+            .from_block(self.speed_up.deposit_block)
+            .to_block(BlockNumberOrTag::Latest)
+            .query()
+            .await?;
+        Ok(requested
+            .iter()
+            .any(|(event, _)| event.updatedOutputAmount <= self.speed_up.updated_output_amount))
+    }
+
+    async fn execute(&mut self) -> eyre::Result<crate::Result> {
+        self.validate_speed_up()?;
+
+        if !self.is_ready().await? {
+            eyre::bail!("Deposit can't be sped up: filled or past its fill deadline");
+        }
+
+        // The depositor signs the updated details, then the transaction publishing them
+        let signature = self.signer.sign_typed_data(&self.typed_data()).await?;
+        let tx_request = self.transaction_request(Bytes::from(signature.as_bytes()));
+        let filled_tx = client::fill_transaction(tx_request, &self.l1_provider).await?;
+        let signed_tx = self.signer.sign_transaction(filled_tx.clone()).await?;
+
+        // Broadcast the signed transaction
+        let receipt = self
+            .audit_log
+            .broadcast(
+                AuditEntry::new(TransactionKind::SpeedUpDeposit, &filled_tx, &signed_tx)
+                    .with_deposit_id(Some(self.speed_up.deposit_id)),
+                async { Ok(self.l1_provider.send_raw_transaction(&signed_tx).await?) },
+            )
+            .await?;
+        let receipt = confirm(&self.l1_provider, &filled_tx, receipt).await?;
+
+        Ok(crate::Result {
+            tx_hash: receipt.transaction_hash,
+            block_number: receipt.block_number,
+            gas_used: Some(U256::from(receipt.gas_used)),
+        })
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "Speed up deposit {} by lowering its output from {} to {} ETH",
+            self.speed_up.deposit_id,
+            format_ether(self.speed_up.output_amount),
+            format_ether(self.speed_up.updated_output_amount),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{calldata, mock_signer, MAX_FEE_PER_GAS},
+        Action,
+    };
+    use alloy_primitives::{keccak256, Log as PrimitiveLog};
+    use alloy_rpc_types_eth::Log;
+    use alloy_sol_types::{SolCall, SolEvent, SolValue};
+    use binding::across::{FillType, V3RelayExecutionEventInfo};
+    use test_support::FakeChain;
+
+    const L1_SPOKE_POOL: Address = Address::repeat_byte(0x51);
+    const L2_SPOKE_POOL: Address = Address::repeat_byte(0x52);
+
+    fn speed_up(depositor: Address) -> SpeedUp {
+        SpeedUp {
+            spoke_pool: L1_SPOKE_POOL,
+            destination_spoke_pool: L2_SPOKE_POOL,
+            origin_chain_id: 1,
+            deposit_id: U256::from(7),
+            depositor,
+            output_amount: U256::from(1_000_000),
+            updated_output_amount: U256::from(990_000),
+            updated_recipient: depositor,
+            updated_message: Bytes::new(),
+            fill_deadline: 1_000,
+            deposit_block: 1,
+            fill_search_from_block: 0,
+        }
+    }
+
+    fn fill_log(deposit_id: U256, block_number: u64) -> Log {
+        let fill = ISpokePool::FilledRelay {
+            inputToken: B256::ZERO,
+            outputToken: B256::ZERO,
+            inputAmount: U256::ZERO,
+            outputAmount: U256::ZERO,
+            repaymentChainId: U256::from(1),
+            originChainId: U256::from(1),
+            depositId: deposit_id,
+            fillDeadline: 0,
+            exclusivityDeadline: 0,
+            exclusiveRelayer: B256::ZERO,
+            relayer: B256::repeat_byte(4),
+            depositor: B256::ZERO,
+            recipient: B256::ZERO,
+            messageHash: B256::ZERO,
+            relayExecutionInfo: V3RelayExecutionEventInfo {
+                updatedRecipient: B256::ZERO,
+                updatedMessageHash: B256::ZERO,
+                updatedOutputAmount: U256::ZERO,
+                fillType: FillType::FastFill,
+            },
+        };
+        Log {
+            inner: PrimitiveLog {
+                address: L2_SPOKE_POOL,
+                data: fill.encode_log_data(),
+            },
+            block_number: Some(block_number),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        let signer = mock_signer();
+        let depositor = signer.address();
+        let chain = FakeChain::new();
+        let action = |speed_up| {
+            SpeedUpDepositAction::new(chain.provider(), chain.provider(), signer.clone(), speed_up)
+        };
+
+        assert!(action(speed_up(depositor)).validate_speed_up().is_ok());
+
+        let not_lower = SpeedUp {
+            updated_output_amount: U256::from(1_000_000),
+            ..speed_up(depositor)
+        };
+        let err = action(not_lower).validate_speed_up().unwrap_err();
+        assert!(err.to_string().contains("must be below"));
+
+        let other_depositor = speed_up(Address::repeat_byte(9));
+        let err = action(other_depositor).validate_speed_up().unwrap_err();
+        assert!(err.to_string().contains("is not the signer"));
+    }
+
+    #[test]
+    fn test_typed_data_matches_spoke_pool_hashing() {
+        let signer = mock_signer();
+        let chain = FakeChain::new();
+        let speed_up = speed_up(signer.address());
+        let action =
+            SpeedUpDepositAction::new(chain.provider(), chain.provider(), signer, speed_up.clone());
+
+        // As the SpokePool hashes it, with its chain-id-only domain
+        let type_hash = keccak256(
+            "UpdateDepositDetails(uint256 depositId,uint256 originChainId,\
+             uint256 updatedOutputAmount,address updatedRecipient,bytes updatedMessage)",
+        );
+        let struct_hash = keccak256(
+            (
+                type_hash,
+                speed_up.deposit_id,
+                U256::from(speed_up.origin_chain_id),
+                speed_up.updated_output_amount,
+                speed_up.updated_recipient,
+                keccak256(&speed_up.updated_message),
+            )
+                .abi_encode(),
+        );
+        let domain_separator = keccak256(
+            (
+                keccak256("EIP712Domain(string name,string version,uint256 chainId)"),
+                keccak256("ACROSS-V2"),
+                keccak256("1.0.0"),
+                U256::from(speed_up.origin_chain_id),
+            )
+                .abi_encode(),
+        );
+        let expected = keccak256(
+            [
+                &[0x19, 0x01][..],
+                domain_separator.as_slice(),
+                struct_hash.as_slice(),
+            ]
+            .concat(),
+        );
+
+        assert_eq!(action.typed_data().eip712_signing_hash().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_depositor_signature_recovers_to_depositor() {
+        // Anvil's first default account
+        let signer = client::LocalSigner::from_private_key(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        let depositor = signer.address();
+        let chain = FakeChain::new();
+        let action = SpeedUpDepositAction::new(
+            chain.provider(),
+            chain.provider(),
+            Arc::new(signer.clone()),
+            speed_up(depositor),
+        );
+
+        let typed_data = action.typed_data();
+        let signature = signer.sign_typed_data(&typed_data).await.unwrap();
+        let hash = typed_data.eip712_signing_hash().unwrap();
+        assert_eq!(
+            signature.recover_address_from_prehash(&hash).unwrap(),
+            depositor
+        );
+
+        let tx = action.transaction_request(Bytes::from(signature.as_bytes()));
+        let call = ISpokePool::speedUpV3DepositCall::abi_decode(calldata(&tx)).unwrap();
+        assert_eq!(tx.to, Some(L1_SPOKE_POOL.into()));
+        assert_eq!(call.depositor, depositor);
+        assert_eq!(call.depositId, U256::from(7));
+        assert_eq!(call.updatedOutputAmount, U256::from(990_000));
+        assert_eq!(call.depositorSignature.len(), 65);
+    }
+
+    #[tokio::test]
+    async fn test_is_ready_while_in_flight() {
+        let signer = mock_signer();
+        let depositor = signer.address();
+        let l1 = FakeChain::new();
+        let l2 = FakeChain::new().with_chain_id(130);
+        l1.set_balance(
+            depositor,
+            U256::from(SPEED_UP_GAS_LIMIT as u128 * MAX_FEE_PER_GAS),
+        );
+        let action =
+            SpeedUpDepositAction::new(l1.provider(), l2.provider(), signer, speed_up(depositor));
+
+        // Unfilled, before the fill deadline
+        l2.mine(900);
+        assert!(action.is_ready().await.unwrap());
+
+        // Filled since
+        l2.add_log(fill_log(U256::from(7), l2.mine(950)));
+        assert!(!action.is_ready().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_is_not_ready_past_fill_deadline() {
+        let signer = mock_signer();
+        let depositor = signer.address();
+        let l1 = FakeChain::new();
+        let l2 = FakeChain::new();
+        let action =
+            SpeedUpDepositAction::new(l1.provider(), l2.provider(), signer, speed_up(depositor));
+
+        l2.mine(1_001);
+        assert!(!action.is_ready().await.unwrap());
+        // Expired deposits are not searched for fills
+        assert_eq!(l2.request_count("eth_getLogs"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_is_completed_once_requested() {
+        let signer = mock_signer();
+        let depositor = signer.address();
+        let l1 = FakeChain::new();
+        let l2 = FakeChain::new();
+        let action =
+            SpeedUpDepositAction::new(l1.provider(), l2.provider(), signer, speed_up(depositor));
+        let requested = |updated_output_amount: u64, block_number| {
+            let event = ISpokePool::RequestedSpeedUpDeposit {
+                updatedOutputAmount: U256::from(updated_output_amount),
+                depositId: U256::from(7),
+                depositor: B256::left_padding_from(depositor.as_slice()),
+                updatedRecipient: B256::left_padding_from(depositor.as_slice()),
+                updatedMessage: Bytes::new(),
+                depositorSignature: Bytes::new(),
+            };
+            Log {
+                inner: PrimitiveLog {
+                    address: L1_SPOKE_POOL,
+                    data: event.encode_log_data(),
+                },
+                block_number: Some(block_number),
+                ..Default::default()
+            }
+        };
+
+        l1.add_log(requested(995_000, l1.mine(100)));
+        assert!(!action.is_completed().await.unwrap());
+
+        l1.add_log(requested(990_000, l1.mine(200)));
+        assert!(action.is_completed().await.unwrap());
+    }
+}
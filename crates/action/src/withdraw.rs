@@ -1,13 +1,43 @@
-use crate::{Action, SignerFn};
-use alloy_primitives::{utils::format_ether, Address, Bytes, B256, U256};
+use crate::{
+    audit::{AuditEntry, AuditLog, TransactionKind},
+    gas::{check_gas_funds, estimate_max_fee},
+    receipt::{confirm, decode_events},
+    Action, TransactionSigner,
+};
+use alloy_primitives::{utils::format_ether, Address, Bytes, TxHash, B256, U256};
 use alloy_provider::Provider;
-use alloy_sol_types::SolEvent;
-use binding::opstack::{IL2ToL1MessagePasser, WithdrawalTransaction};
-use tracing::info;
-use withdrawal::types::WithdrawalHash;
+use alloy_rpc_types::TransactionRequest;
+use alloy_rpc_types_eth::TransactionReceipt;
+use binding::opstack::{IL2ToL1MessagePasser, WithdrawalTransaction, MESSAGE_PASSER_ADDRESS};
+use std::sync::Arc;
+use tracing::{info, warn};
+use withdrawal::{gas::minimum_gas_limit, hash::verify_withdrawal_hash, types::WithdrawalHash};
+
+/// Gas left for the target's execution on top of the floor by [`default_gas_limit`].
+const DEFAULT_EXECUTION_GAS: u64 = 279_000;
+
+/// Gas an `initiateWithdrawal` call is assumed to use before its payload's calldata.
+const INITIATE_GAS_BASE: u64 = 150_000;
+
+/// Calldata gas per payload byte, priced as non-zero.
+const CALLDATA_GAS_PER_BYTE: u64 = 16;
+
+/// Largest withdrawal payload accepted unless configured otherwise, in bytes.
+pub const DEFAULT_MAX_DATA_BYTES: usize = 120_000;
+
+/// Gas limit for a withdrawal carrying `data_len` bytes: the OP Stack floor for the
+/// payload plus room for the target's execution. 300k for a plain ETH withdrawal.
+pub fn default_gas_limit(data_len: usize) -> U256 {
+    minimum_gas_limit(data_len) + U256::from(DEFAULT_EXECUTION_GAS)
+}
+
+/// Gas an `initiateWithdrawal` call carrying `data_len` bytes is assumed to use.
+pub const fn initiate_gas_limit(data_len: usize) -> u64 {
+    INITIATE_GAS_BASE.saturating_add(CALLDATA_GAS_PER_BYTE.saturating_mul(data_len as u64))
+}
 
 /// Withdraw input data.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Withdraw {
     /// withdrawal contract address
     /// should be the address of L2ToL1MessagePasser
@@ -17,25 +47,170 @@ pub struct Withdraw {
     pub value: U256,
     pub gas_limit: U256,
     pub data: Bytes,
+    /// Largest `data` accepted, in bytes
+    pub max_data_bytes: usize,
+    /// Floor for the priority fee of the initiating transaction, in wei
+    pub min_priority_fee_wei: u128,
     /// Optional: only exists on initiated withdrawal
     /// transaction hash from execution
     pub tx_hash: Option<B256>,
 }
 
+impl Withdraw {
+    /// Plain ETH withdrawal through the L2ToL1MessagePasser predeploy, with no calldata.
+    pub fn simple_eth(
+        source: Address,
+        target: Address,
+        value: U256,
+        gas_limit: U256,
+    ) -> eyre::Result<Self> {
+        if source == Address::ZERO || target == Address::ZERO {
+            eyre::bail!("Withdrawal source and target must not be the zero address");
+        }
+        if value.is_zero() {
+            eyre::bail!("Withdrawal value must be non-zero");
+        }
+        if gas_limit.is_zero() {
+            eyre::bail!("Withdrawal gas limit must be non-zero");
+        }
+
+        let withdraw = Self {
+            contract: MESSAGE_PASSER_ADDRESS,
+            source,
+            target,
+            value,
+            gas_limit,
+            data: Bytes::new(),
+            max_data_bytes: DEFAULT_MAX_DATA_BYTES,
+            min_priority_fee_wei: 0,
+            tx_hash: None,
+        };
+        withdraw.validate()?;
+        Ok(withdraw)
+    }
+
+    /// Attach `data` to the withdrawal, accepting at most `max_data_bytes`.
+    ///
+    /// The gas limit must cover the floor for the payload; see [`default_gas_limit`].
+    pub fn with_data(mut self, data: Bytes, max_data_bytes: usize) -> eyre::Result<Self> {
+        self.data = data;
+        self.max_data_bytes = max_data_bytes;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Check the payload size and that the gas limit meets the floor for it.
+    pub fn validate(&self) -> eyre::Result<()> {
+        if self.data.len() > self.max_data_bytes {
+            eyre::bail!(
+                "Withdrawal data is {} bytes, above the maximum of {}",
+                self.data.len(),
+                self.max_data_bytes
+            );
+        }
+
+        let minimum = minimum_gas_limit(self.data.len());
+        if self.gas_limit < minimum {
+            eyre::bail!(
+                "Withdrawal gas limit {} is below the minimum of {} for {} bytes of data",
+                self.gas_limit,
+                minimum,
+                self.data.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Never pay less than `min_priority_fee_wei` as priority fee, whatever the node estimates.
+    pub const fn with_min_priority_fee(mut self, min_priority_fee_wei: u128) -> Self {
+        self.min_priority_fee_wei = min_priority_fee_wei;
+        self
+    }
+}
+
+/// A withdrawal initiated on L2, with the gas its transaction cost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Initiated {
+    pub withdrawal: WithdrawalTransaction,
+    pub withdrawal_hash: WithdrawalHash,
+    /// L2 transaction that initiated the withdrawal
+    pub tx_hash: TxHash,
+    pub l2_block: u64,
+    pub gas_used: u64,
+    /// Price paid per unit of L2 gas, in wei
+    pub effective_gas_price: u128,
+}
+
+impl Initiated {
+    /// Read the withdrawal from the `MessagePassed` event in `receipt`.
+    pub fn from_receipt(receipt: &TransactionReceipt) -> eyre::Result<Self> {
+        let (withdrawal, withdrawal_hash) = parse_message_passed_event(receipt)?;
+        Ok(Self {
+            withdrawal,
+            withdrawal_hash,
+            tx_hash: receipt.transaction_hash,
+            l2_block: receipt
+                .block_number
+                .ok_or_else(|| eyre::eyre!("Receipt has no block number"))?,
+            gas_used: receipt.gas_used,
+            effective_gas_price: receipt.effective_gas_price,
+        })
+    }
+}
+
 pub struct WithdrawAction<P> {
     provider: P,
-    signer: SignerFn,
+    signer: Arc<dyn TransactionSigner>,
     action: Withdraw,
+    initiated: Option<Initiated>,
+    audit_log: AuditLog,
 }
 
 impl<P: Provider + Clone> WithdrawAction<P> {
-    pub fn new(provider: P, signer: SignerFn, action: Withdraw) -> Self {
+    pub fn new(provider: P, signer: Arc<dyn TransactionSigner>, action: Withdraw) -> Self {
         Self {
             provider,
             signer,
             action,
+            initiated: None,
+            audit_log: AuditLog::default(),
         }
     }
+
+    /// Record the broadcast transaction in `log`.
+    pub fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.audit_log = log;
+        self
+    }
+
+    /// The withdrawal and its L2 gas cost, once [`Action::execute`] succeeded.
+    pub const fn initiated(&self) -> Option<&Initiated> {
+        self.initiated.as_ref()
+    }
+
+    /// Run the withdrawal through `eth_call` without signing or broadcasting it.
+    ///
+    /// Fails with the revert reason if the transaction would revert.
+    pub async fn simulate(&self) -> eyre::Result<()> {
+        self.action.validate()?;
+        self.provider.call(self.transaction_request()).await?;
+        Ok(())
+    }
+
+    /// Unfilled `initiateWithdrawal` transaction sent from the withdrawal's source.
+    pub fn transaction_request(&self) -> TransactionRequest {
+        let contract = IL2ToL1MessagePasser::new(self.action.contract, &self.provider);
+        contract
+            .initiateWithdrawal(
+                self.action.target,
+                self.action.gas_limit,
+                self.action.data.clone(),
+            )
+            .value(self.action.value)
+            .into_transaction_request()
+            .from(self.action.source)
+    }
 }
 
 impl<P> Action for WithdrawAction<P>
@@ -51,8 +226,20 @@ where
             return Ok(false);
         }
 
+        if let Err(e) = self.action.validate() {
+            warn!(error = %e, "Withdrawal would be rejected, not ready");
+            return Ok(false);
+        }
+
         let balance = self.provider.get_balance(self.action.source).await?;
-        Ok(balance >= self.action.value)
+        if balance < self.action.value {
+            return Ok(false);
+        }
+
+        let gas_limit = initiate_gas_limit(self.action.data.len());
+        let cost = estimate_max_fee(&self.provider, gas_limit).await?;
+        check_gas_funds(&self.provider, self.action.source, self.action.value + cost).await?;
+        Ok(true)
     }
 
     async fn is_completed(&self) -> eyre::Result<bool> {
@@ -88,40 +275,49 @@ where
         if self.is_completed().await? {
             eyre::bail!("Withdrawal already initiated")
         }
-
-        let contract = IL2ToL1MessagePasser::new(self.action.contract, &self.provider);
+        self.action.validate()?;
 
         // Build the transaction request
-        let call = contract
-            .initiateWithdrawal(
-                self.action.target,
-                self.action.gas_limit,
-                self.action.data.clone(),
-            )
-            .value(self.action.value);
-        let tx_request = call.into_transaction_request().from(self.action.source);
+        let tx_request = self.transaction_request();
 
-        // Fill transaction fields (nonce, gas, fees) using our provider
-        let filled_tx = client::fill_transaction(tx_request, &self.provider).await?;
+        // Fill transaction fields (nonce, gas, fees) using our provider. L2 nodes can
+        // estimate a near-zero tip, which leaves the transaction sitting in the mempool.
+        let filled_tx = client::apply_min_priority_fee(
+            client::fill_transaction(tx_request, &self.provider).await?,
+            self.action.min_priority_fee_wei,
+        );
 
         // Sign externally
-        let signed_tx = (self.signer)(filled_tx).await?;
+        let signed_tx = self.signer.sign_transaction(filled_tx.clone()).await?;
 
         // Broadcast the signed transaction
-        let pending = self.provider.send_raw_transaction(&signed_tx).await?;
-        let receipt = pending.get_receipt().await?;
+        // The withdrawal hash is only known from the MessagePassed event
+        let receipt = self
+            .audit_log
+            .broadcast_with(
+                AuditEntry::new(TransactionKind::InitiateWithdrawal, &filled_tx, &signed_tx),
+                async { Ok(self.provider.send_raw_transaction(&signed_tx).await?) },
+                |entry, receipt| match Initiated::from_receipt(receipt) {
+                    Ok(initiated) => entry.with_withdrawal_hash(initiated.withdrawal_hash),
+                    Err(_) => entry,
+                },
+            )
+            .await?;
+        let receipt = confirm(&self.provider, &filled_tx, receipt).await?;
 
-        let (withdrawal_tx, withdrawal_hash) = parse_message_passed_event(&receipt)?;
+        let initiated = Initiated::from_receipt(&receipt)?;
         info!(
-            tx_hash = %receipt.transaction_hash,
-            block_number = receipt.block_number,
-            gas_used = receipt.gas_used,
-            withdrawal_hash = %withdrawal_hash,
-            withdrawal_tx = ?withdrawal_tx,
+            tx_hash = %initiated.tx_hash,
+            block_number = initiated.l2_block,
+            gas_used = initiated.gas_used,
+            effective_gas_price = initiated.effective_gas_price,
+            withdrawal_hash = %initiated.withdrawal_hash,
+            withdrawal_tx = ?initiated.withdrawal,
             "Withdrawal initiated."
         );
 
         self.action.tx_hash = Some(receipt.transaction_hash);
+        self.initiated = Some(initiated);
 
         Ok(crate::Result {
             tx_hash: receipt.transaction_hash,
@@ -137,24 +333,231 @@ where
 }
 
 fn parse_message_passed_event(
-    receipt: &alloy_rpc_types_eth::transaction::TransactionReceipt,
+    receipt: &TransactionReceipt,
 ) -> eyre::Result<(WithdrawalTransaction, WithdrawalHash)> {
-    for log in receipt.logs() {
-        if let Ok(event) = IL2ToL1MessagePasser::MessagePassed::decode_log(&log.inner) {
-            let tx = WithdrawalTransaction {
-                nonce: event.nonce,
-                sender: event.sender,
-                target: event.target,
-                value: event.value,
-                gasLimit: event.gasLimit,
-                data: event.data.data.clone(),
-            };
-
-            let hash = event.withdrawalHash;
-
-            return Ok((tx, hash));
+    let Some(event) = decode_events::<IL2ToL1MessagePasser::MessagePassed>(receipt.logs()).next()
+    else {
+        eyre::bail!("Message passed event not found in receipt")
+    };
+
+    let tx = WithdrawalTransaction::from(&event.data);
+    verify_withdrawal_hash(&tx, event.withdrawalHash)?;
+    Ok((tx, event.withdrawalHash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{calldata, mock_signer};
+    use alloy_primitives::{address, hex};
+    use alloy_rpc_types_eth::{Receipt, ReceiptEnvelope, ReceiptWithBloom};
+    use alloy_sol_types::SolEvent;
+    use test_support::FakeChain;
+    use withdrawal::hash::compute_withdrawal_hash;
+
+    const EOA: Address = address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1");
+
+    /// Receipt of an `initiateWithdrawal` transaction emitting `MessagePassed` for `tx`.
+    fn initiation_receipt(tx: &WithdrawalTransaction) -> TransactionReceipt {
+        let event = IL2ToL1MessagePasser::MessagePassed {
+            nonce: tx.nonce,
+            sender: tx.sender,
+            target: tx.target,
+            value: tx.value,
+            gasLimit: tx.gasLimit,
+            data: tx.data.clone(),
+            withdrawalHash: compute_withdrawal_hash(tx),
+        };
+        let log = alloy_rpc_types_eth::Log {
+            inner: alloy_primitives::Log {
+                address: MESSAGE_PASSER_ADDRESS,
+                data: event.encode_log_data(),
+            },
+            ..Default::default()
+        };
+
+        TransactionReceipt {
+            inner: ReceiptEnvelope::Eip1559(ReceiptWithBloom {
+                receipt: Receipt {
+                    status: true.into(),
+                    cumulative_gas_used: 120_000,
+                    logs: vec![log],
+                },
+                logs_bloom: Default::default(),
+            }),
+            transaction_hash: B256::repeat_byte(0xaa),
+            transaction_index: Some(1),
+            block_hash: None,
+            block_number: Some(42),
+            gas_used: 95_000,
+            effective_gas_price: 1_000_250,
+            blob_gas_used: None,
+            blob_gas_price: None,
+            from: EOA,
+            to: Some(MESSAGE_PASSER_ADDRESS),
+            contract_address: None,
         }
     }
 
-    eyre::bail!("Message passed event not found in receipt")
+    #[test]
+    fn test_initiated_from_receipt() {
+        let tx = WithdrawalTransaction {
+            nonce: U256::from(7),
+            sender: EOA,
+            target: EOA,
+            value: U256::from(1_000),
+            gasLimit: U256::from(300_000),
+            data: Bytes::new(),
+        };
+        let mut receipt = initiation_receipt(&tx);
+
+        let initiated = Initiated::from_receipt(&receipt).unwrap();
+        assert_eq!(
+            initiated,
+            Initiated {
+                withdrawal_hash: compute_withdrawal_hash(&tx),
+                withdrawal: tx,
+                tx_hash: B256::repeat_byte(0xaa),
+                l2_block: 42,
+                gas_used: 95_000,
+                effective_gas_price: 1_000_250,
+            }
+        );
+
+        // Pending receipts are not a completed initiation
+        receipt.block_number = None;
+        assert!(Initiated::from_receipt(&receipt).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_is_completed_matches_receipt_event() {
+        let withdraw =
+            Withdraw::simple_eth(EOA, EOA, U256::from(1_000), U256::from(300_000)).unwrap();
+        let tx = WithdrawalTransaction {
+            nonce: U256::from(7),
+            sender: withdraw.source,
+            target: withdraw.target,
+            value: withdraw.value,
+            gasLimit: withdraw.gas_limit,
+            data: Bytes::new(),
+        };
+        let chain = FakeChain::new();
+        let action = |tx_hash| {
+            WithdrawAction::new(
+                chain.provider(),
+                mock_signer(),
+                Withdraw {
+                    tx_hash,
+                    ..withdraw.clone()
+                },
+            )
+        };
+
+        // Not submitted yet, or not mined
+        assert!(!action(None).is_completed().await.unwrap());
+        let tx_hash = Some(B256::repeat_byte(0xaa));
+        assert!(!action(tx_hash).is_completed().await.unwrap());
+
+        chain.set_receipt(initiation_receipt(&tx));
+        assert!(action(tx_hash).is_completed().await.unwrap());
+
+        // A withdrawal of another amount at that hash is not ours
+        chain.set_receipt(initiation_receipt(&WithdrawalTransaction {
+            value: U256::from(999),
+            ..tx.clone()
+        }));
+        assert!(!action(tx_hash).is_completed().await.unwrap());
+
+        // Nor is a transaction that didn't initiate a withdrawal
+        let mut receipt = initiation_receipt(&tx);
+        receipt
+            .inner
+            .as_receipt_with_bloom_mut()
+            .unwrap()
+            .receipt
+            .logs
+            .clear();
+        chain.set_receipt(receipt);
+        assert!(!action(tx_hash).is_completed().await.unwrap());
+    }
+
+    #[test]
+    fn test_simple_eth() {
+        let withdraw =
+            Withdraw::simple_eth(EOA, EOA, U256::from(1_000), U256::from(200_000)).unwrap();
+        assert_eq!(withdraw.contract, MESSAGE_PASSER_ADDRESS);
+        assert!(withdraw.data.is_empty());
+        assert!(withdraw.tx_hash.is_none());
+    }
+
+    #[test]
+    fn test_simple_eth_rejects_invalid_inputs() {
+        let value = U256::from(1_000);
+        let gas = U256::from(200_000);
+
+        assert!(Withdraw::simple_eth(Address::ZERO, EOA, value, gas).is_err());
+        assert!(Withdraw::simple_eth(EOA, Address::ZERO, value, gas).is_err());
+        assert!(Withdraw::simple_eth(EOA, EOA, U256::ZERO, gas).is_err());
+        assert!(Withdraw::simple_eth(EOA, EOA, value, U256::ZERO).is_err());
+        // Below the floor for an empty payload
+        assert!(Withdraw::simple_eth(EOA, EOA, value, U256::from(20_999)).is_err());
+    }
+
+    #[test]
+    fn test_default_gas_limit() {
+        assert_eq!(default_gas_limit(0), U256::from(300_000));
+        assert_eq!(default_gas_limit(100), U256::from(304_000));
+    }
+
+    #[test]
+    fn test_with_data_enforces_size_and_gas_floor() {
+        let value = U256::from(1_000);
+        let data = Bytes::from(vec![0xab; 1_000]);
+
+        let withdraw = Withdraw::simple_eth(EOA, EOA, value, default_gas_limit(data.len()))
+            .unwrap()
+            .with_data(data.clone(), 1_000)
+            .unwrap();
+        assert_eq!(withdraw.data, data);
+
+        // Payload above the configured maximum
+        let err = Withdraw::simple_eth(EOA, EOA, value, default_gas_limit(data.len()))
+            .unwrap()
+            .with_data(data.clone(), 999)
+            .unwrap_err();
+        assert!(err.to_string().contains("above the maximum of 999"));
+
+        // 1000 bytes need 61000 gas
+        let err = Withdraw::simple_eth(EOA, EOA, value, U256::from(60_999))
+            .unwrap()
+            .with_data(data, 1_000)
+            .unwrap_err();
+        assert!(err.to_string().contains("minimum of 61000"));
+    }
+
+    #[test]
+    fn test_transaction_request_calldata() {
+        let withdraw = Withdraw {
+            data: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+            ..Withdraw::simple_eth(EOA, EOA, U256::from(1_000), U256::from(300_000)).unwrap()
+        };
+        let tx = WithdrawAction::new(FakeChain::new().provider(), mock_signer(), withdraw)
+            .transaction_request();
+
+        assert_eq!(tx.from, Some(EOA));
+        assert_eq!(tx.to, Some(MESSAGE_PASSER_ADDRESS.into()));
+        assert_eq!(tx.value, Some(U256::from(1_000)));
+        // initiateWithdrawal(address,uint256,bytes)
+        assert_eq!(
+            calldata(&tx)[..],
+            hex!(
+                "c2b3e5ac"
+                "0000000000000000000000005cffa347b0ae99cc01e5c01714ca5658e54a23d1"
+                "00000000000000000000000000000000000000000000000000000000000493e0"
+                "0000000000000000000000000000000000000000000000000000000000000060"
+                "0000000000000000000000000000000000000000000000000000000000000004"
+                "deadbeef00000000000000000000000000000000000000000000000000000000"
+            )
+        );
+    }
 }
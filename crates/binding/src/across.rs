@@ -29,6 +29,24 @@ sol! {
             bytes message
         );
 
+        /// Legacy V3 deposit event with address fields and a uint32 deposit id, emitted
+        /// by SpokePools before the bytes32 upgrade
+        event V3FundsDeposited(
+            address inputToken,
+            address outputToken,
+            uint256 inputAmount,
+            uint256 outputAmount,
+            uint256 indexed destinationChainId,
+            uint32 indexed depositId,
+            uint32 quoteTimestamp,
+            uint32 fillDeadline,
+            uint32 exclusivityDeadline,
+            address indexed depositor,
+            address recipient,
+            address exclusiveRelayer,
+            bytes message
+        );
+
         /// Emitted when a relay is filled on the destination chain
         event FilledRelay(
             bytes32 inputToken,
@@ -48,6 +66,31 @@ sol! {
             V3RelayExecutionEventInfo relayExecutionInfo
         );
 
+        /// Emitted when a relayer refund leaf is executed on this chain.
+        /// Refunds for expired deposits are paid to the depositor through these leaves.
+        event ExecutedRelayerRefundRoot(
+            uint256 amountToReturn,
+            uint256 indexed chainId,
+            uint256[] refundAmounts,
+            uint32 indexed rootBundleId,
+            uint32 indexed leafId,
+            address l2TokenAddress,
+            address[] refundAddresses,
+            bool deferredRefunds,
+            address caller
+        );
+
+        /// Emitted when a depositor signs new output amount, recipient and message for
+        /// an unfilled deposit, which relayers may fill with instead
+        event RequestedSpeedUpDeposit(
+            uint256 updatedOutputAmount,
+            uint256 indexed depositId,
+            bytes32 indexed depositor,
+            bytes32 updatedRecipient,
+            bytes updatedMessage,
+            bytes depositorSignature
+        );
+
         /// Emitted when a relayer refund is claimed
         event ClaimedRelayerRefund(
             address indexed token,
@@ -71,6 +114,50 @@ sol! {
             bytes calldata message
         ) external payable;
 
+        /// Deposit V3 with a caller-chosen nonce instead of the sequential deposit counter.
+        /// The resulting deposit id is `getUnsafeDepositId(msg.sender, depositor, depositNonce)`
+        /// and can be computed before the transaction lands.
+        function unsafeDepositV3(
+            address depositor,
+            address recipient,
+            address inputToken,
+            address outputToken,
+            uint256 inputAmount,
+            uint256 outputAmount,
+            uint256 destinationChainId,
+            address exclusiveRelayer,
+            uint256 depositNonce,
+            uint32 quoteTimestamp,
+            uint32 fillDeadline,
+            uint32 exclusivityParameter,
+            bytes calldata message
+        ) external payable;
+
+        /// Deposit id assigned to an unsafe deposit:
+        /// `uint256(keccak256(abi.encodePacked(msgSender, depositor, depositNonce)))`
+        function getUnsafeDepositId(address msgSender, bytes32 depositor, uint256 depositNonce)
+            external pure returns (uint256);
+
+        /// Publish updated deposit details signed by the depositor over
+        /// [`UpdateDepositDetails`]. Anyone may submit them.
+        function speedUpV3Deposit(
+            address depositor,
+            uint256 depositId,
+            uint256 updatedOutputAmount,
+            address updatedRecipient,
+            bytes calldata updatedMessage,
+            bytes calldata depositorSignature
+        ) external;
+
+        /// Wrapped native token (WETH) native deposits are converted to
+        function wrappedNativeToken() external view returns (address);
+
+        /// Furthest a deposit's fill deadline may be past the current block timestamp, in seconds
+        function fillDeadlineBuffer() external view returns (uint32);
+
+        /// A deposit's fill deadline is out of the range the pool accepts
+        error InvalidFillDeadline();
+
         /// Query relayer refund amount for a given token
         function getRelayerRefund(address token, address relayer)
             external view returns (uint256);
@@ -79,6 +166,16 @@ sol! {
         function claimRelayerRefund(address token) external;
     }
 
+    /// EIP-712 struct a depositor signs for `speedUpV3Deposit`, under the domain
+    /// `ACROSS-V2`, version `1.0.0` and the origin chain id, with no verifying contract.
+    struct UpdateDepositDetails {
+        uint256 depositId;
+        uint256 originChainId;
+        uint256 updatedOutputAmount;
+        address updatedRecipient;
+        bytes updatedMessage;
+    }
+
     /// Fill type for relay execution
     enum FillType {
         FastFill,
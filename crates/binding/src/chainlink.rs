@@ -0,0 +1,24 @@
+//! Chainlink price feed bindings.
+
+use alloy_sol_types::sol;
+
+sol! {
+    /// Chainlink aggregator proxy, e.g. the ETH / USD feed
+    #[sol(rpc)]
+    interface AggregatorV3Interface {
+        /// Decimals of the answer
+        function decimals() external view returns (uint8);
+
+        /// Latest answer and when it was last updated
+        function latestRoundData()
+            external
+            view
+            returns (
+                uint80 roundId,
+                int256 answer,
+                uint256 startedAt,
+                uint256 updatedAt,
+                uint80 answeredInRound
+            );
+    }
+}
@@ -4,9 +4,11 @@
 //! - Across Protocol contracts (SpokePool, HubPool)
 //! - OP Stack contracts (OptimismPortal2, L2ToL1MessagePasser, DisputeGameFactory)
 //! - ERC20 tokens
+//! - Chainlink price feeds
 //!
 //! All bindings are generated using alloy's `sol!` macro.
 
 pub mod across;
+pub mod chainlink;
 pub mod opstack;
 pub mod token;
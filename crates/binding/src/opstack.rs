@@ -5,7 +5,7 @@
 //! - OptimismPortal2 (L1 contract)
 //! - DisputeGameFactory (L1 contract)
 
-use alloy_primitives::{address, Address, B256};
+use alloy_primitives::{address, Address, B256, U256};
 use alloy_sol_types::sol;
 
 // ============================================================================
@@ -15,6 +15,11 @@ use alloy_sol_types::sol;
 /// L2ToL1MessagePasser predeploy address (same on all OP Stack chains).
 pub const MESSAGE_PASSER_ADDRESS: Address = address!("4200000000000000000000000000000000000016");
 
+/// Storage slot of the L2ToL1MessagePasser's `sentMessages` mapping, per the contract's
+/// storage layout snapshot:
+/// <https://github.com/ethereum-optimism/optimism/blob/develop/packages/contracts-bedrock/snapshots/storageLayout/L2ToL1MessagePasser.json>
+pub const SENT_MESSAGES_SLOT: U256 = U256::ZERO;
+
 /// WETH predeploy address on OP Stack L2s.
 pub const L2_WETH_ADDRESS: Address = address!("4200000000000000000000000000000000000006");
 
@@ -95,14 +100,32 @@ sol! {
         function finalizedWithdrawals(bytes32 withdrawalHash)
             external view returns (bool);
 
+        /// Number of addresses that submitted a proof for a withdrawal
+        function numProofSubmitters(bytes32 withdrawalHash)
+            external view returns (uint256);
+
+        /// Address that submitted the `index`-th proof for a withdrawal
+        function proofSubmitters(bytes32 withdrawalHash, uint256 index)
+            external view returns (address);
+
+        /// Whether a dispute game was blacklisted by the guardian
+        function disputeGameBlacklist(address disputeGame)
+            external view returns (bool);
+
         /// Get the proof maturity delay (usually 7 days = 604800 seconds)
         function proofMaturityDelaySeconds()
             external view returns (uint256);
 
+        /// Whether the portal is paused by the guardian (proving and finalizing revert)
+        function paused() external view returns (bool);
+
         /// Get the respected game type for filtering dispute games
         function respectedGameType()
             external view returns (uint32);
 
+        /// DisputeGameFactory whose games the portal accepts proofs against
+        function disputeGameFactory() external view returns (address);
+
         /// Prove a withdrawal transaction (requires merkle proof)
         function proveWithdrawalTransaction(
             WithdrawalTransaction calldata _tx,
@@ -130,6 +153,13 @@ sol! {
             bytes extraData;
         }
 
+        /// Emitted when a new dispute game is created
+        event DisputeGameCreated(
+            address indexed disputeProxy,
+            uint32 indexed gameType,
+            bytes32 indexed rootClaim
+        );
+
         /// Get the total number of dispute games created
         function gameCount() external view returns (uint256 gameCount_);
 
@@ -145,6 +175,19 @@ sol! {
         function gameAtIndex(uint256 _index) external view returns (uint32 gameType_, uint64 timestamp_, address proxy_);
     }
 
+    /// IDisputeGame - Reads shared by every dispute game implementation
+    #[sol(rpc)]
+    interface IDisputeGame {
+        /// Get the game type
+        function gameType() external view returns (uint32);
+
+        /// Get the game status
+        function status() external view returns (uint8);
+
+        /// Timestamp the game was created at
+        function createdAt() external view returns (uint64);
+    }
+
     /// IFaultDisputeGame - Standard interface for fault dispute games
     #[sol(rpc)]
     interface IFaultDisputeGame {
@@ -158,6 +201,19 @@ sol! {
         function rootClaim() external view returns (bytes32);
     }
 
+    /// IPermissionedDisputeGame - Permissioned (allowlisted proposer) dispute games
+    #[sol(rpc)]
+    interface IPermissionedDisputeGame {
+        /// Get the L2 block number this game is disputing (newer implementations)
+        function l2SequenceNumber() external view returns (uint256);
+
+        /// Get the game status
+        function status() external view returns (uint8);
+
+        /// Get the root claim (output root)
+        function rootClaim() external view returns (bytes32);
+    }
+
     /// Output root proof structure (used in proving withdrawals)
     #[derive(Debug)]
     struct OutputRootProof {
@@ -168,7 +224,7 @@ sol! {
     }
 
     /// Withdrawal transaction structure (shared across contracts)
-    #[derive(Debug)]
+    #[derive(Debug, PartialEq, Eq)]
     struct WithdrawalTransaction {
         uint256 nonce;
         address sender;
@@ -178,3 +234,18 @@ sol! {
         bytes data;
     }
 }
+
+impl From<&IL2ToL1MessagePasser::MessagePassed> for WithdrawalTransaction {
+    /// The withdrawal a `MessagePassed` event describes. Its hash is the event's
+    /// `withdrawalHash`.
+    fn from(event: &IL2ToL1MessagePasser::MessagePassed) -> Self {
+        Self {
+            nonce: event.nonce,
+            sender: event.sender,
+            target: event.target,
+            value: event.value,
+            gasLimit: event.gasLimit,
+            data: event.data.clone(),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Client for the Across HTTP API.
+//!
+//! Only the `/suggested-fees` endpoint is used: it quotes the fee a relayer currently
+//! expects for filling a deposit on a given route, so the deposit's output amount can be
+//! set for a fast fill instead of forcing a slow one.
+
+use alloy_primitives::{Address, U256};
+use eyre::{bail, Result};
+use serde::Deserialize;
+
+/// Public Across API.
+pub const DEFAULT_ACROSS_API_URL: &str = "https://app.across.to/api";
+
+/// Route and amount to quote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeQuery {
+    pub input_token: Address,
+    pub output_token: Address,
+    pub origin_chain_id: u64,
+    pub destination_chain_id: u64,
+    /// Input amount in wei
+    pub amount: U256,
+}
+
+/// Fee component of a `/suggested-fees` response.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RelayFee {
+    /// Fee as a fraction of the amount, scaled by 1e18
+    pub pct: U256,
+    /// Fee in wei of the input token
+    pub total: U256,
+}
+
+/// Response of `/suggested-fees`. Fields not used by the orchestrator are skipped.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedFees {
+    /// Everything the relayer keeps: LP fee, gas and capital cost
+    pub total_relay_fee: RelayFee,
+    /// Set when the amount is too small for relayers to fill at any fee
+    #[serde(default)]
+    pub is_amount_too_low: bool,
+}
+
+/// Client for the Across HTTP API.
+#[derive(Debug, Clone)]
+pub struct AcrossApi {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl AcrossApi {
+    /// Creates a client for the API at `base_url` (e.g. [`DEFAULT_ACROSS_API_URL`]).
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Creates a client with a custom HTTP client, e.g. one with a timeout.
+    pub fn with_client(client: reqwest::Client, base_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into(),
+        }
+    }
+
+    /// Query the suggested relayer fee for `query`.
+    pub async fn suggested_fees(&self, query: &FeeQuery) -> Result<SuggestedFees> {
+        let url = format!("{}/suggested-fees", self.base_url.trim_end_matches('/'));
+        let response = self
+            .client
+            .get(&url)
+            .query(&query_params(query))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "unknown".to_string());
+            bail!("Across API returned {status}: {body}");
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+/// Query string of a `/suggested-fees` request.
+fn query_params(query: &FeeQuery) -> [(&'static str, String); 5] {
+    [
+        ("inputToken", query.input_token.to_string()),
+        ("outputToken", query.output_token.to_string()),
+        ("originChainId", query.origin_chain_id.to_string()),
+        ("destinationChainId", query.destination_chain_id.to_string()),
+        ("amount", query.amount.to_string()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_params() {
+        let query = FeeQuery {
+            input_token: Address::repeat_byte(0x11),
+            output_token: Address::repeat_byte(0x22),
+            origin_chain_id: 1,
+            destination_chain_id: 130,
+            amount: U256::from(5_000_000_000_000_000_000_u128),
+        };
+
+        let params = query_params(&query);
+
+        assert_eq!(params[2], ("originChainId", "1".to_string()));
+        assert_eq!(params[3], ("destinationChainId", "130".to_string()));
+        // Decimal, not hex
+        assert_eq!(params[4], ("amount", "5000000000000000000".to_string()));
+    }
+
+    #[test]
+    fn test_parse_suggested_fees() {
+        let body = r#"{
+            "estimatedFillTimeSec": 4,
+            "totalRelayFee": { "pct": "78930919924823", "total": "394654599624115" },
+            "relayerCapitalFee": { "pct": "100000000000000", "total": "500000000000000" },
+            "timestamp": "1726669751",
+            "isAmountTooLow": false
+        }"#;
+
+        let fees: SuggestedFees = serde_json::from_str(body).unwrap();
+
+        assert_eq!(
+            fees.total_relay_fee.total,
+            U256::from(394_654_599_624_115_u64)
+        );
+        assert_eq!(fees.total_relay_fee.pct, U256::from(78_930_919_924_823_u64));
+        assert!(!fees.is_amount_too_low);
+    }
+}
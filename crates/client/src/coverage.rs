@@ -0,0 +1,213 @@
+//! Coverage of the block ranges walked by log scans.
+//!
+//! Scans walk a range in chunks, split chunks over the RPC's log limit, resume from
+//! checkpoints and may stop early on failure. A slip in any of these skips or re-scans
+//! blocks without an error. [`RangeCoverage`] follows one walk and checks that each
+//! range actually queried starts right after the previous one; [`ScanCoverage`] adds up
+//! what every walk covered and found wrong, e.g. over a cycle, for the metrics.
+
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tracing::warn;
+
+/// A range scanned out of order within a walk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum CoverageIssue {
+    /// Blocks between the previous range and this one were never scanned
+    #[error("blocks {from}..={to} were skipped")]
+    Gap { from: u64, to: u64 },
+    /// Blocks of this range were already scanned by an earlier one
+    #[error("blocks {from}..={to} were scanned twice")]
+    Overlap { from: u64, to: u64 },
+}
+
+/// Blocks covered by scans and issues found since the last reset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CoverageTotals {
+    /// Distinct blocks scanned, summed over walks
+    pub covered_blocks: u64,
+    pub gaps: u64,
+    pub overlaps: u64,
+}
+
+/// Coverage totals shared by every clone of the handle.
+#[derive(Debug, Clone, Default)]
+pub struct ScanCoverage {
+    totals: Arc<Mutex<CoverageTotals>>,
+}
+
+impl ScanCoverage {
+    /// Follow a walk named `scan` over blocks from `from_block` on.
+    pub fn track(&self, scan: &'static str, from_block: u64) -> RangeCoverage {
+        RangeCoverage {
+            scan,
+            walk: Mutex::new(Walk {
+                next: from_block,
+                covered_to: None,
+                contiguous: true,
+            }),
+            coverage: self.clone(),
+        }
+    }
+
+    /// Totals since creation or the last [`Self::reset`].
+    pub fn totals(&self) -> CoverageTotals {
+        *self.lock()
+    }
+
+    /// Start counting from zero again, e.g. at the start of a cycle.
+    pub fn reset(&self) {
+        *self.lock() = CoverageTotals::default();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CoverageTotals> {
+        self.totals.lock().expect("scan coverage lock poisoned")
+    }
+}
+
+/// Ranges scanned by one walk, which must follow each other without gaps or overlaps.
+///
+/// Recording takes `&self`, so the ranges can be recorded where each query succeeds,
+/// e.g. by [`crate::logs::retry_bisecting`] for every part of a split range.
+#[derive(Debug)]
+pub struct RangeCoverage {
+    scan: &'static str,
+    walk: Mutex<Walk>,
+    coverage: ScanCoverage,
+}
+
+/// Progress of a walk.
+#[derive(Debug)]
+struct Walk {
+    /// Block the next range should start at
+    next: u64,
+    /// End of the gapless run of ranges from the start of the walk
+    covered_to: Option<u64>,
+    contiguous: bool,
+}
+
+impl RangeCoverage {
+    /// Whether `from..=to` starts right after the last range recorded.
+    pub fn check(&self, from: u64, to: u64) -> Result<(), CoverageIssue> {
+        let next = self.lock().next;
+        if from > next {
+            return Err(CoverageIssue::Gap {
+                from: next,
+                to: from - 1,
+            });
+        }
+        if from < next {
+            return Err(CoverageIssue::Overlap {
+                from,
+                to: to.min(next - 1),
+            });
+        }
+        Ok(())
+    }
+
+    /// Record that `from..=to` was scanned.
+    ///
+    /// A gap or overlap is logged and counted in the shared totals; the walk goes on.
+    pub fn record(&self, from: u64, to: u64) {
+        let issue = self.check(from, to).err();
+        let mut walk = self.lock();
+        if let Some(issue) = issue {
+            warn!(scan = self.scan, %issue, "Scan ranges are not contiguous");
+
+            let mut totals = self.coverage.lock();
+            match issue {
+                CoverageIssue::Gap { .. } => {
+                    totals.gaps += 1;
+                    walk.contiguous = false;
+                }
+                CoverageIssue::Overlap { .. } => totals.overlaps += 1,
+            }
+        }
+
+        let new_from = from.max(walk.next);
+        if to >= new_from {
+            self.coverage.lock().covered_blocks += to - new_from + 1;
+            walk.next = to + 1;
+            if walk.contiguous {
+                walk.covered_to = Some(to);
+            }
+        }
+    }
+
+    /// Last block of the gapless run of ranges from the start of the walk, `None` if
+    /// nothing was recorded yet. A checkpoint advanced to this never skips a gap.
+    pub fn covered_to(&self) -> Option<u64> {
+        self.lock().covered_to
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Walk> {
+        self.walk.lock().expect("range coverage lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contiguous_ranges() {
+        let coverage = ScanCoverage::default();
+        let range = coverage.track("test", 100);
+        assert_eq!(range.covered_to(), None);
+
+        range.record(100, 199);
+        range.record(200, 250);
+        assert_eq!(range.covered_to(), Some(250));
+        assert_eq!(
+            coverage.totals(),
+            CoverageTotals {
+                covered_blocks: 151,
+                gaps: 0,
+                overlaps: 0,
+            }
+        );
+
+        coverage.reset();
+        assert_eq!(coverage.totals(), CoverageTotals::default());
+    }
+
+    #[test]
+    fn test_gap_detection() {
+        let coverage = ScanCoverage::default();
+        let range = coverage.track("test", 100);
+
+        range.record(100, 199);
+        assert_eq!(
+            range.check(210, 300),
+            Err(CoverageIssue::Gap { from: 200, to: 209 })
+        );
+        range.record(210, 300);
+        range.record(301, 400);
+
+        // The checkpoint stays before the gap
+        assert_eq!(range.covered_to(), Some(199));
+        let totals = coverage.totals();
+        assert_eq!(totals.gaps, 1);
+        assert_eq!(totals.covered_blocks, 100 + 91 + 100);
+    }
+
+    #[test]
+    fn test_overlap_detection() {
+        let coverage = ScanCoverage::default();
+        let range = coverage.track("test", 100);
+
+        range.record(100, 199);
+        assert_eq!(
+            range.check(150, 249),
+            Err(CoverageIssue::Overlap { from: 150, to: 199 })
+        );
+        range.record(150, 249);
+        // Entirely scanned already
+        range.record(120, 130);
+
+        assert_eq!(range.covered_to(), Some(249));
+        let totals = coverage.totals();
+        assert_eq!(totals.overlaps, 2);
+        assert_eq!(totals.covered_blocks, 150);
+    }
+}
@@ -0,0 +1,106 @@
+//! Minimal JSON-RPC over HTTP for the sidecar services the orchestrator talks to.
+
+use eyre::{bail, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Send `method` with `params` to the JSON-RPC endpoint at `url` and return its result.
+///
+/// `service` names the endpoint in errors, e.g. "signer-proxy".
+pub(crate) async fn call<T: Serialize, R: DeserializeOwned>(
+    client: &reqwest::Client,
+    url: &str,
+    service: &str,
+    method: &'static str,
+    params: T,
+) -> Result<R> {
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0",
+        method,
+        params,
+        id: 1,
+    };
+
+    let response = client.post(url).json(&request).send().await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "unknown".to_string());
+        bail!("{service} returned {status}: {body}");
+    }
+
+    let rpc_response: JsonRpcResponse<R> = response.json().await?;
+
+    match rpc_response.result {
+        Some(result) => Ok(result),
+        None => {
+            let error = rpc_response.error.unwrap_or(JsonRpcError {
+                code: -1,
+                message: "unknown error".to_string(),
+            });
+            bail!("JSON-RPC error {}: {}", error.code, error.message);
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonRpcRequest<T> {
+    jsonrpc: &'static str,
+    method: &'static str,
+    params: T,
+    id: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse<T> {
+    result: Option<T>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i32,
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Bytes;
+
+    #[test]
+    fn test_raw_transaction_request_encoding() {
+        let signed_tx = Bytes::from(vec![0x02, 0xf8, 0x01]);
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0",
+            method: "eth_sendRawTransaction",
+            params: [&signed_tx],
+            id: 1,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_sendRawTransaction",
+                "params": ["0x02f801"],
+                "id": 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_response_parsing() {
+        let response: JsonRpcResponse<String> = serde_json::from_str(
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"sponsorship denied"}}"#,
+        )
+        .unwrap();
+
+        assert!(response.result.is_none());
+        let error = response.error.unwrap();
+        assert_eq!(error.code, -32000);
+        assert_eq!(error.message, "sponsorship denied");
+    }
+}
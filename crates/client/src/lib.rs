@@ -1,24 +1,25 @@
+pub mod across;
+pub mod coverage;
+mod jsonrpc;
+pub mod logs;
+pub mod price;
 mod remote_signer;
+pub mod retry;
+mod secret;
+mod signer;
+mod sponsored_relay;
 
-use alloy_consensus::TxEnvelope;
-use alloy_network::{eip2718::Encodable2718, EthereumWallet, TransactionBuilder};
-use alloy_primitives::Bytes;
+use alloy_network::EthereumWallet;
+use alloy_primitives::Address;
 use alloy_provider::{Provider, ProviderBuilder};
-use alloy_rpc_types::TransactionRequest;
-use alloy_signer_local::PrivateKeySigner;
-pub use remote_signer::RemoteSigner;
-use std::{future::Future, pin::Pin, sync::Arc};
+use alloy_rpc_types::{BlockNumberOrTag, TransactionRequest};
+pub use remote_signer::{verify_signed_transaction, RemoteSigner, SignedTransactionRejected};
+pub use secret::PrivateKey;
+pub use signer::{FnSigner, LocalSigner, SignerFn, TransactionSigner};
+pub use sponsored_relay::SponsoredRelay;
+use std::time::Duration;
 use thiserror::Error;
-
-/// A function that signs a transaction request and returns signed bytes.
-///
-/// This type alias matches the one in the `action` crate and allows
-/// for both local and remote signing implementations.
-pub type SignerFn = Arc<
-    dyn Fn(TransactionRequest) -> Pin<Box<dyn Future<Output = eyre::Result<Bytes>> + Send>>
-        + Send
-        + Sync,
->;
+use tracing::warn;
 
 #[derive(Error, Debug)]
 pub enum ClientError {
@@ -39,12 +40,42 @@ pub enum ClientError {
     Other(String),
 }
 
+/// Timeouts of the HTTP transport behind a provider.
+///
+/// Kept apart so an unreachable endpoint fails fast while slow reads such as
+/// `eth_getProof` against an archive node still get time to complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderTimeouts {
+    /// Time allowed for DNS resolution and establishing the connection
+    pub connect: Duration,
+    /// Time allowed for a whole request, from sending it to reading the response
+    pub request: Duration,
+}
+
+impl Default for ProviderTimeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(5),
+            request: Duration::from_secs(120),
+        }
+    }
+}
+
 /// Convenience function to create an ethereum rpc provider from url.
-pub async fn create_provider(rpc_url: &str) -> Result<impl Provider + Clone, ClientError> {
+pub async fn create_provider(
+    rpc_url: &str,
+    timeouts: ProviderTimeouts,
+) -> Result<impl Provider + Clone, ClientError> {
     let url = rpc_url
         .parse()
         .map_err(|e| ClientError::InvalidUrl(format!("{}", e)))?;
-    let provider = ProviderBuilder::new().connect_http(url);
+    // The transport's own reqwest, which may differ from the one used elsewhere here
+    let client = alloy_transport_http::reqwest::Client::builder()
+        .connect_timeout(timeouts.connect)
+        .timeout(timeouts.request)
+        .build()
+        .map_err(|e| ClientError::Connection(format!("{}", e)))?;
+    let provider = ProviderBuilder::new().connect_reqwest(client, url);
 
     Ok(provider)
 }
@@ -58,9 +89,7 @@ pub fn create_wallet_provider(
         .parse()
         .map_err(|e| ClientError::InvalidUrl(format!("{}", e)))?;
 
-    let signer: PrivateKeySigner = private_key
-        .parse()
-        .map_err(|e| ClientError::InvalidPrivateKey(format!("{}", e)))?;
+    let signer = secret::parse_private_key(private_key)?;
 
     let wallet = EthereumWallet::from(signer);
 
@@ -69,46 +98,50 @@ pub fn create_wallet_provider(
     Ok(provider)
 }
 
-/// Create a SignerFn from a RemoteSigner.
-///
-/// The transaction must be fully filled (nonce, gas, fees, chain_id, from) before
-/// being passed to this signer. Use `fill_transaction` at the call site.
-pub fn remote_signer_fn(remote: RemoteSigner) -> SignerFn {
-    Arc::new(move |tx| {
-        let remote = remote.clone();
-        Box::pin(async move { remote.sign_transaction(tx).await })
-    })
+/// Derive the address of a local private key.
+pub fn local_signer_address(private_key: &str) -> Result<Address, ClientError> {
+    Ok(LocalSigner::from_private_key(private_key)?.address())
 }
 
-/// Create a SignerFn from a local private key.
-///
-/// The transaction must be fully filled (nonce, gas, fees, chain_id, from) before
-/// being passed to this signer. Use `fill_transaction` at the call site.
-pub fn local_signer_fn(private_key: &str) -> Result<SignerFn, ClientError> {
-    let signer: PrivateKeySigner = private_key
-        .parse()
-        .map_err(|e| ClientError::InvalidPrivateKey(format!("{}", e)))?;
-    let wallet = EthereumWallet::from(signer);
+/// The gas a transaction is estimated to use exceeds the block gas limit, so no block
+/// can include it and the node would reject it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("Gas estimate {estimate} exceeds the block gas limit {block_gas_limit}")]
+pub struct GasExceedsBlockLimit {
+    pub estimate: u64,
+    pub block_gas_limit: u64,
+}
+
+/// Gas limit for a transaction estimated to use `estimate`: 20% on top for safety,
+/// clamped to `block_gas_limit`.
+pub fn buffered_gas_limit(
+    estimate: u64,
+    block_gas_limit: u64,
+) -> Result<u64, GasExceedsBlockLimit> {
+    if estimate > block_gas_limit {
+        return Err(GasExceedsBlockLimit {
+            estimate,
+            block_gas_limit,
+        });
+    }
 
-    Ok(Arc::new(move |tx: TransactionRequest| {
-        let wallet = wallet.clone();
-        Box::pin(async move {
-            // Build and sign the typed transaction
-            let tx_envelope: TxEnvelope =
-                tx.build(&wallet).await.map_err(|e| eyre::eyre!("{}", e))?;
-
-            // Encode to EIP-2718 bytes
-            let mut encoded = Vec::new();
-            tx_envelope.encode_2718(&mut encoded);
-            Ok(Bytes::from(encoded))
-        })
-    }))
+    let buffer = estimate / 5;
+    let gas = estimate.saturating_add(buffer);
+    if gas > block_gas_limit {
+        warn!(
+            estimate,
+            buffer, block_gas_limit, "Gas buffer clamped to the block gas limit"
+        );
+        return Ok(block_gas_limit);
+    }
+    Ok(gas)
 }
 
 /// Fill missing transaction fields using the provider.
 ///
 /// The `from` address must be set on the transaction request before calling this function.
 /// This function will fill in chain_id, nonce, gas, and fee parameters if not already set.
+/// Estimated gas fails with [`GasExceedsBlockLimit`] if no block could include it.
 pub async fn fill_transaction<P>(
     mut tx: TransactionRequest,
     provider: &P,
@@ -146,20 +179,122 @@ where
     // Estimate gas if not set
     if tx.gas.is_none() {
         let gas_estimate = provider.estimate_gas(tx.clone()).await?;
-        // Add 20% buffer for safety
-        tx.gas = Some(gas_estimate + gas_estimate / 5);
+        let block_gas_limit = provider
+            .get_block_by_number(BlockNumberOrTag::Latest)
+            .await?
+            .ok_or_else(|| eyre::eyre!("Failed to get latest block"))?
+            .header
+            .gas_limit;
+        tx.gas = Some(buffered_gas_limit(gas_estimate, block_gas_limit)?);
     }
 
     Ok(tx)
 }
 
+/// Raise the priority fee of a filled EIP-1559 transaction to at least `min_priority_fee`.
+///
+/// `max_fee_per_gas` is raised by the same amount, so the fee cap still leaves the
+/// original headroom for the base fee.
+pub fn apply_min_priority_fee(
+    mut tx: TransactionRequest,
+    min_priority_fee: u128,
+) -> TransactionRequest {
+    let priority_fee = tx.max_priority_fee_per_gas.unwrap_or_default();
+    if priority_fee < min_priority_fee {
+        let bump = min_priority_fee - priority_fee;
+        tx.max_priority_fee_per_gas = Some(min_priority_fee);
+        tx.max_fee_per_gas = Some(tx.max_fee_per_gas.unwrap_or_default() + bump);
+    }
+    tx
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[tokio::test]
     async fn test_invalid_url() {
-        let result = create_provider("not a url").await;
+        let result = create_provider("not a url", ProviderTimeouts::default()).await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_request_timeout_fails_fast() {
+        let timeouts = ProviderTimeouts {
+            connect: Duration::from_secs(60),
+            request: Duration::from_millis(200),
+        };
+        // The kernel accepts the connection but nothing ever answers the request
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let provider = create_provider(&url, timeouts).await.unwrap();
+
+        let started = std::time::Instant::now();
+        assert!(provider.get_block_number().await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a network where 10.255.255.1 is unroutable"]
+    async fn test_connect_timeout_fails_fast() {
+        let timeouts = ProviderTimeouts {
+            connect: Duration::from_millis(200),
+            request: Duration::from_secs(60),
+        };
+        // Non-routable: the connection attempt hangs until the connect timeout
+        let provider = create_provider("http://10.255.255.1:8545", timeouts)
+            .await
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        assert!(provider.get_block_number().await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_buffered_gas_limit() {
+        assert_eq!(buffered_gas_limit(100_000, 30_000_000), Ok(120_000));
+        // The buffer would push it over the block gas limit
+        assert_eq!(buffered_gas_limit(29_000_000, 30_000_000), Ok(30_000_000));
+        assert_eq!(
+            buffered_gas_limit(30_000_001, 30_000_000),
+            Err(GasExceedsBlockLimit {
+                estimate: 30_000_001,
+                block_gas_limit: 30_000_000,
+            })
+        );
+        assert_eq!(buffered_gas_limit(u64::MAX, u64::MAX), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn test_local_signer_address() {
+        // Anvil's first default account
+        let address = local_signer_address(
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+        )
+        .unwrap();
+        assert_eq!(
+            address,
+            alloy_primitives::address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+        );
+
+        assert!(local_signer_address("not a key").is_err());
+    }
+
+    #[test]
+    fn test_apply_min_priority_fee() {
+        let tx = TransactionRequest::default()
+            .max_fee_per_gas(1_000)
+            .max_priority_fee_per_gas(1);
+
+        // Below the floor: tip raised, fee cap raised by the same amount
+        let raised = apply_min_priority_fee(tx.clone(), 100);
+        assert_eq!(raised.max_priority_fee_per_gas, Some(100));
+        assert_eq!(raised.max_fee_per_gas, Some(1_099));
+
+        // Already above the floor: untouched
+        let untouched = apply_min_priority_fee(tx, 0);
+        assert_eq!(untouched.max_priority_fee_per_gas, Some(1));
+        assert_eq!(untouched.max_fee_per_gas, Some(1_000));
+    }
 }
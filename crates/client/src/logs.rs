@@ -0,0 +1,280 @@
+//! Recovery from `eth_getLogs` result limits.
+//!
+//! Besides capping the block range, some providers cap the number of logs a query may
+//! return and reject it outright ("query returned more than 10000 results"). Retrying
+//! the same range fails the same way, so [`retry_bisecting`] splits such a range in half
+//! and scans each part, recursing down to single blocks, and merges what they return.
+
+use crate::{
+    coverage::RangeCoverage,
+    retry::{retry_if, RetryBudget},
+};
+use alloy_provider::transport::TransportError;
+use std::future::Future;
+use tracing::debug;
+
+/// Sign of a provider error meaning a log query matched too many results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogLimitPattern {
+    /// Case-insensitive substring of the error message
+    Message(String),
+    /// JSON-RPC error code of the response
+    Code(i64),
+}
+
+impl LogLimitPattern {
+    /// Whether `err`, whose lowercased message is `message`, matches the pattern.
+    fn matches(&self, err: &eyre::Report, message: &str) -> bool {
+        match self {
+            Self::Message(pattern) => message.contains(&pattern.to_lowercase()),
+            Self::Code(code) => error_code(err) == Some(*code),
+        }
+    }
+}
+
+/// JSON-RPC error code of the first error response in `err`'s chain.
+fn error_code(err: &eyre::Report) -> Option<i64> {
+    err.chain()
+        .find_map(|e| e.downcast_ref::<TransportError>())
+        .and_then(|e| e.as_error_resp())
+        .map(|resp| resp.code)
+}
+
+/// Messages known providers use when a log query matches too many results.
+pub const DEFAULT_LOG_LIMIT_MESSAGES: &[&str] = &[
+    // geth, Erigon, Infura
+    "query returned more than",
+    // Alchemy
+    "log response size exceeded",
+    // reth
+    "query exceeds max results",
+    // Nethermind, QuickNode
+    "too many logs",
+];
+
+/// Matches errors caused by a provider's log result limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLimitMatcher {
+    patterns: Vec<LogLimitPattern>,
+}
+
+impl Default for LogLimitMatcher {
+    fn default() -> Self {
+        Self {
+            patterns: DEFAULT_LOG_LIMIT_MESSAGES
+                .iter()
+                .map(|message| LogLimitPattern::Message((*message).to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl LogLimitMatcher {
+    /// Also treat errors matching `pattern` as result limit errors.
+    pub fn with_pattern(mut self, pattern: LogLimitPattern) -> Self {
+        self.patterns.push(pattern);
+        self
+    }
+
+    /// Whether `err`, or any error in its chain, matches one of the patterns.
+    pub fn matches(&self, err: &eyre::Report) -> bool {
+        let message = format!("{err:#}").to_lowercase();
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches(err, &message))
+    }
+}
+
+/// Scan `from_block..=to_block` with `scan`, halving ranges rejected by the log limit.
+///
+/// Each range is retried under `budget` like [`crate::retry::retry`], except that result
+/// limit errors are not retried: the range is split and both halves scanned, left first,
+/// so items come back in block order. A single block over the limit is an error. Every
+/// range that returned is recorded in `coverage`, so a split that loses blocks shows up
+/// as a gap.
+pub async fn retry_bisecting<T, F, Fut>(
+    budget: &RetryBudget,
+    matcher: &LogLimitMatcher,
+    coverage: &RangeCoverage,
+    from_block: u64,
+    to_block: u64,
+    mut scan: F,
+) -> eyre::Result<Vec<T>>
+where
+    F: FnMut(u64, u64) -> Fut,
+    Fut: Future<Output = eyre::Result<Vec<T>>>,
+{
+    let mut items = Vec::new();
+    let mut ranges = vec![(from_block, to_block)];
+
+    while let Some((from, to)) = ranges.pop() {
+        let result = retry_if(budget, || scan(from, to), |e| !matcher.matches(e)).await;
+        match result {
+            Ok(found) => {
+                coverage.record(from, to);
+                items.extend(found);
+            }
+            Err(e) if from < to && matcher.matches(&e) => {
+                let mid = from + (to - from) / 2;
+                debug!(
+                    from,
+                    to,
+                    error = %e,
+                    "Log query over the provider's result limit, splitting range"
+                );
+                ranges.push((mid + 1, to));
+                ranges.push((from, mid));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::{CoverageTotals, ScanCoverage};
+    use alloy_json_rpc::ErrorPayload;
+    use std::cell::RefCell;
+
+    /// Stub of a provider that rejects any range holding more than `limit` events.
+    struct CappedLogs {
+        event_blocks: Vec<u64>,
+        limit: usize,
+        queries: RefCell<Vec<(u64, u64)>>,
+    }
+
+    impl CappedLogs {
+        fn new(event_blocks: Vec<u64>, limit: usize) -> Self {
+            Self {
+                event_blocks,
+                limit,
+                queries: RefCell::default(),
+            }
+        }
+
+        async fn get_logs(&self, from: u64, to: u64) -> eyre::Result<Vec<u64>> {
+            self.queries.borrow_mut().push((from, to));
+            let logs: Vec<u64> = self
+                .event_blocks
+                .iter()
+                .copied()
+                .filter(|block| (from..=to).contains(block))
+                .collect();
+            if logs.len() > self.limit {
+                eyre::bail!(
+                    "server returned an error response: error code -32005: query returned \
+                     more than {} results",
+                    self.limit
+                );
+            }
+            Ok(logs)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bisects_until_ranges_fit() {
+        let events = vec![1, 2, 3, 50, 51, 52, 53, 99];
+        let stub = CappedLogs::new(events.clone(), 4);
+        let coverage = ScanCoverage::default();
+
+        let found = retry_bisecting(
+            &RetryBudget::unlimited(),
+            &LogLimitMatcher::default(),
+            &coverage.track("test", 0),
+            0,
+            99,
+            |from, to| stub.get_logs(from, to),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(found, events);
+        // The full range and the dense upper half were split, nothing was retried as-is
+        assert_eq!(
+            *stub.queries.borrow(),
+            [(0, 99), (0, 49), (50, 99), (50, 74), (75, 99)]
+        );
+        // Each part that returned was recorded, back to back
+        assert_eq!(
+            coverage.totals(),
+            CoverageTotals {
+                covered_blocks: 100,
+                gaps: 0,
+                overlaps: 0,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_single_block_over_limit_fails() {
+        let stub = CappedLogs::new(vec![10, 12, 12, 12], 2);
+
+        let err = retry_bisecting(
+            &RetryBudget::new(0),
+            &LogLimitMatcher::default(),
+            &ScanCoverage::default().track("test", 0),
+            0,
+            15,
+            |from, to| stub.get_logs(from, to),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.to_string().contains("more than 2 results"));
+        assert_eq!(stub.queries.borrow().last(), Some(&(12, 12)));
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_are_not_split() {
+        let calls = RefCell::new(0);
+
+        let err = retry_bisecting::<u64, _, _>(
+            &RetryBudget::new(0),
+            &LogLimitMatcher::default(),
+            &ScanCoverage::default().track("test", 0),
+            0,
+            100,
+            |_, _| {
+                *calls.borrow_mut() += 1;
+                async { Err(eyre::eyre!("header not found")) }
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "header not found");
+        assert_eq!(*calls.borrow(), 1);
+    }
+
+    #[test]
+    fn test_matcher_patterns() {
+        let matcher = LogLimitMatcher::default();
+        let infura =
+            eyre::eyre!("query returned more than 10000 results").wrap_err("L1 chunk scan failed");
+        assert!(matcher.matches(&infura));
+        assert!(matcher.matches(&eyre::eyre!(
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K \
+             block range"
+        )));
+        assert!(!matcher.matches(&eyre::eyre!("block range too large")));
+
+        let busy: ErrorPayload = ErrorPayload {
+            code: -32099,
+            message: "busy".into(),
+            data: None,
+        };
+        let custom =
+            eyre::Report::new(TransportError::err_resp(busy)).wrap_err("L1 chunk scan failed");
+        assert!(!matcher.matches(&custom));
+        let matcher = matcher
+            .with_pattern(LogLimitPattern::Code(-32099))
+            .with_pattern(LogLimitPattern::Message("Result Set Too Big".to_string()));
+        assert!(matcher.matches(&custom));
+        assert!(matcher.matches(&eyre::eyre!("result set too big")));
+        // The code is read from the error response, not the message
+        assert!(!matcher.matches(&eyre::eyre!("error code -32099: busy")));
+    }
+}
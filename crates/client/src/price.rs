@@ -0,0 +1,95 @@
+//! Client for an HTTP endpoint quoting the price of ETH.
+//!
+//! Any endpoint returning the price somewhere in a JSON body works, e.g. CoinGecko's
+//! `/simple/price?ids=ethereum&vs_currencies=usd` with the pointer `/ethereum/usd`.
+
+use crate::ProviderTimeouts;
+use eyre::{bail, eyre, Result};
+use serde_json::Value;
+
+/// Client for an HTTP price endpoint.
+#[derive(Debug, Clone)]
+pub struct HttpPriceFeed {
+    client: reqwest::Client,
+    url: String,
+    pointer: String,
+}
+
+impl HttpPriceFeed {
+    /// Creates a client reading the price at the JSON `pointer` (RFC 6901, e.g.
+    /// `/ethereum/usd`) of what `url` returns, giving up on a request after `timeouts`.
+    pub fn new(
+        url: impl Into<String>,
+        pointer: impl Into<String>,
+        timeouts: ProviderTimeouts,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(timeouts.connect)
+            .timeout(timeouts.request)
+            .build()?;
+        Ok(Self::with_client(client, url, pointer))
+    }
+
+    /// Creates a client with a custom HTTP client, e.g. one with a timeout.
+    pub fn with_client(
+        client: reqwest::Client,
+        url: impl Into<String>,
+        pointer: impl Into<String>,
+    ) -> Self {
+        Self {
+            client,
+            url: url.into(),
+            pointer: pointer.into(),
+        }
+    }
+
+    /// Fetch the current price.
+    pub async fn price(&self) -> Result<f64> {
+        let response = self.client.get(&self.url).send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            bail!("Price endpoint returned {status}");
+        }
+
+        let body: Value = response.json().await?;
+        price_at(&body, &self.pointer)
+    }
+}
+
+/// Price at `pointer` in `body`, given as a JSON number or a decimal string.
+fn price_at(body: &Value, pointer: &str) -> Result<f64> {
+    let value = body
+        .pointer(pointer)
+        .ok_or_else(|| eyre!("No price at {pointer} in the response"))?;
+    let price = match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| eyre!("Price at {pointer} is not a number: {value}"))?;
+
+    if !price.is_finite() || price <= 0.0 {
+        bail!("Price at {pointer} is not positive: {price}");
+    }
+    Ok(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_price_at() {
+        let body = json!({ "ethereum": { "usd": 3150.42 }, "data": { "amount": "3150.5" } });
+
+        assert_eq!(price_at(&body, "/ethereum/usd").unwrap(), 3150.42);
+        assert_eq!(price_at(&body, "/data/amount").unwrap(), 3150.5);
+
+        let err = price_at(&body, "/bitcoin/usd").unwrap_err();
+        assert!(err.to_string().contains("No price at /bitcoin/usd"));
+        assert!(price_at(&json!({ "usd": "n/a" }), "/usd").is_err());
+        assert!(price_at(&json!({ "usd": 0 }), "/usd").is_err());
+    }
+}
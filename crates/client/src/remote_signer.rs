@@ -1,12 +1,69 @@
 //! Remote transaction signer that delegates signing to a signer-proxy service.
 //!
-//! The remote signer sends `eth_signTransaction` JSON-RPC requests to a proxy service,
-//! which handles the actual signing (typically via an HSM or secure enclave).
+//! The remote signer sends `eth_signTransaction` and `eth_signTypedData_v4` JSON-RPC
+//! requests to a proxy service, which handles the actual signing (typically via an HSM
+//! or secure enclave).
+//!
+//! The proxy is trusted to sign with the right key, but a misconfigured one could sign
+//! for another account or chain. Signed transactions are decoded and checked against
+//! the expected sender and chain before they are handed back for broadcast; see
+//! [`verify_signed_transaction`].
 
-use alloy_primitives::{Address, Bytes};
+use crate::jsonrpc;
+use alloy_consensus::{transaction::SignerRecoverable, Transaction, TxEnvelope};
+use alloy_dyn_abi::TypedData;
+use alloy_network::eip2718::Decodable2718;
+use alloy_primitives::{Address, Bytes, Signature};
 use alloy_rpc_types::eth::TransactionRequest;
-use eyre::{bail, Result};
-use serde::{Deserialize, Serialize};
+use eyre::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+
+/// A transaction returned by the signer-proxy that must not be broadcast.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SignedTransactionRejected {
+    /// The raw bytes are not an EIP-2718 transaction envelope
+    #[error("Signed transaction can't be decoded: {0}")]
+    Undecodable(String),
+    /// The transaction was signed by another account
+    #[error("Signed transaction is from {signer}, expected {expected}")]
+    WrongSigner { expected: Address, signer: Address },
+    /// The transaction is for another chain, or has no chain ID (pre-EIP-155)
+    #[error("Signed transaction is for chain {chain_id:?}, expected {expected}")]
+    WrongChain {
+        expected: u64,
+        chain_id: Option<u64>,
+    },
+}
+
+/// Decode `raw` as an EIP-2718 transaction and check it was signed by `address` for
+/// `chain_id`.
+pub fn verify_signed_transaction(
+    raw: &[u8],
+    address: Address,
+    chain_id: u64,
+) -> Result<TxEnvelope, SignedTransactionRejected> {
+    let envelope = TxEnvelope::decode_2718_exact(raw)
+        .map_err(|e| SignedTransactionRejected::Undecodable(e.to_string()))?;
+
+    let signer = envelope
+        .recover_signer()
+        .map_err(|e| SignedTransactionRejected::Undecodable(e.to_string()))?;
+    if signer != address {
+        return Err(SignedTransactionRejected::WrongSigner {
+            expected: address,
+            signer,
+        });
+    }
+    if envelope.chain_id() != Some(chain_id) {
+        return Err(SignedTransactionRejected::WrongChain {
+            expected: chain_id,
+            chain_id: envelope.chain_id(),
+        });
+    }
+
+    Ok(envelope)
+}
 
 /// A remote signer that delegates transaction signing to a signer-proxy service.
 ///
@@ -26,6 +83,8 @@ pub struct RemoteSigner {
     proxy_url: String,
     address: Address,
     chain_id: u64,
+    /// Check signed transactions before returning them, see [`verify_signed_transaction`]
+    verify_signed: bool,
 }
 
 impl RemoteSigner {
@@ -41,6 +100,7 @@ impl RemoteSigner {
             proxy_url: proxy_url.into(),
             address,
             chain_id,
+            verify_signed: true,
         }
     }
 
@@ -56,9 +116,18 @@ impl RemoteSigner {
             proxy_url: proxy_url.into(),
             address,
             chain_id,
+            verify_signed: true,
         }
     }
 
+    /// Whether to decode signed transactions and check their sender and chain, which is
+    /// the default. Only turn this off for a proxy whose output doesn't decode as an
+    /// EIP-2718 envelope.
+    pub const fn with_signed_transaction_check(mut self, enabled: bool) -> Self {
+        self.verify_signed = enabled;
+        self
+    }
+
     /// Returns the signer's address.
     pub const fn address(&self) -> Address {
         self.address
@@ -72,46 +141,45 @@ impl RemoteSigner {
     /// Signs a transaction via the remote signer-proxy.
     ///
     /// Returns the signed transaction as raw bytes, ready to be broadcast
-    /// via `provider.send_raw_transaction()`.
+    /// via `provider.send_raw_transaction()`. Unless the check is turned off, fails with
+    /// [`SignedTransactionRejected`] if they are not signed by this signer's address for
+    /// its chain.
     pub async fn sign_transaction(&self, tx: TransactionRequest) -> Result<Bytes> {
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0",
-            method: "eth_signTransaction",
-            params: [tx],
-            id: 1,
-        };
+        let result: SignedTransactionResponse = self.request("eth_signTransaction", [tx]).await?;
+        let raw: Bytes = result.raw.parse()?;
+        if self.verify_signed {
+            verify_signed_transaction(&raw, self.address, self.chain_id)?;
+        }
+        Ok(raw)
+    }
 
-        let response = self
-            .client
-            .post(&self.proxy_url)
-            .json(&request)
-            .send()
+    /// Signs EIP-712 typed data via `eth_signTypedData_v4`.
+    pub async fn sign_typed_data(&self, payload: &TypedData) -> Result<Signature> {
+        let signature: Bytes = self
+            .request("eth_signTypedData_v4", (self.address, payload))
             .await?;
+        Ok(Signature::try_from(signature.as_ref())?)
+    }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "unknown".to_string());
-            bail!("signer-proxy returned {status}: {body}");
-        }
+    /// Lists the accounts the signer-proxy can sign for via `eth_accounts`.
+    pub async fn accounts(&self) -> Result<Vec<Address>> {
+        self.request("eth_accounts", [(); 0]).await
+    }
 
-        let rpc_response: JsonRpcResponse<SignedTransactionResponse> = response.json().await?;
-
-        match rpc_response.result {
-            Some(result) => {
-                let bytes: Bytes = result.raw.parse()?;
-                Ok(bytes)
-            }
-            None => {
-                let error = rpc_response.error.unwrap_or(JsonRpcError {
-                    code: -1,
-                    message: "unknown error".to_string(),
-                });
-                bail!("JSON-RPC error {}: {}", error.code, error.message);
-            }
-        }
+    /// Sends a JSON-RPC request to the signer-proxy and returns its result.
+    async fn request<T: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &'static str,
+        params: T,
+    ) -> Result<R> {
+        jsonrpc::call(
+            &self.client,
+            &self.proxy_url,
+            "signer-proxy",
+            method,
+            params,
+        )
+        .await
     }
 
     /// Helper to build a transaction request with the signer's address and chain ID pre-filled.
@@ -124,26 +192,6 @@ impl RemoteSigner {
     }
 }
 
-#[derive(Debug, Serialize)]
-struct JsonRpcRequest<T> {
-    jsonrpc: &'static str,
-    method: &'static str,
-    params: T,
-    id: u32,
-}
-
-#[derive(Debug, Deserialize)]
-struct JsonRpcResponse<T> {
-    result: Option<T>,
-    error: Option<JsonRpcError>,
-}
-
-#[derive(Debug, Deserialize)]
-struct JsonRpcError {
-    code: i32,
-    message: String,
-}
-
 /// Response from eth_signTransaction containing the signed transaction.
 #[derive(Debug, Deserialize)]
 struct SignedTransactionResponse {
@@ -154,7 +202,65 @@ struct SignedTransactionResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy_primitives::address;
+    use crate::{LocalSigner, TransactionSigner};
+    use alloy_primitives::{address, U256};
+
+    // Anvil's first default account
+    const PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    async fn signed_transaction(chain_id: u64) -> (Address, Bytes) {
+        let signer = LocalSigner::from_private_key(PRIVATE_KEY).unwrap();
+        let tx = TransactionRequest::default()
+            .from(signer.address())
+            .to(Address::repeat_byte(0x11))
+            .value(U256::from(1))
+            .nonce(0)
+            .gas_limit(21_000)
+            .max_fee_per_gas(2_000_000_000)
+            .max_priority_fee_per_gas(1_000_000_000);
+        let tx = TransactionRequest {
+            chain_id: Some(chain_id),
+            ..tx
+        };
+        (signer.address(), signer.sign_transaction(tx).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_verify_signed_transaction() {
+        let (address, raw) = signed_transaction(130).await;
+
+        let envelope = verify_signed_transaction(&raw, address, 130).unwrap();
+        assert_eq!(envelope.chain_id(), Some(130));
+
+        assert_eq!(
+            verify_signed_transaction(&raw, Address::repeat_byte(0x22), 130),
+            Err(SignedTransactionRejected::WrongSigner {
+                expected: Address::repeat_byte(0x22),
+                signer: address,
+            })
+        );
+        assert_eq!(
+            verify_signed_transaction(&raw, address, 1),
+            Err(SignedTransactionRejected::WrongChain {
+                expected: 1,
+                chain_id: Some(130),
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_undecodable_bytes() {
+        let address = Address::repeat_byte(0x22);
+        assert!(matches!(
+            verify_signed_transaction(&[0xde, 0xad, 0xbe, 0xef], address, 1),
+            Err(SignedTransactionRejected::Undecodable(_))
+        ));
+        // A signature alone, as some proxies return for eth_sign
+        assert!(matches!(
+            verify_signed_transaction(&[0u8; 65], address, 1),
+            Err(SignedTransactionRejected::Undecodable(_))
+        ));
+    }
 
     #[test]
     fn test_build_transaction() {
@@ -0,0 +1,225 @@
+//! Retries of RPC calls under a shared budget.
+//!
+//! Failed calls are retried with jittered exponential backoff. When the RPC is degraded
+//! every call retries, multiplying load exactly when the provider is struggling, so all
+//! retries draw from a [`RetryBudget`]: once it is spent, failures are returned
+//! immediately instead of being retried.
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio_retry::{
+    strategy::{jitter, ExponentialBackoff},
+    Retry, RetryIf,
+};
+
+/// Maximum retries of a single call, budget permitting.
+pub const MAX_RETRIES: usize = 5;
+
+/// Number of retries that may be spent, shared by every clone of the handle.
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    /// `None` for an unlimited budget
+    limit: Option<usize>,
+    used: Arc<AtomicUsize>,
+}
+
+impl Default for RetryBudget {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+impl RetryBudget {
+    /// Budget allowing `limit` retries in total.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            used: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Budget that never runs out; only the per-call [`MAX_RETRIES`] applies.
+    pub fn unlimited() -> Self {
+        Self {
+            limit: None,
+            used: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Spend one retry. Returns `false` if the budget is exhausted.
+    pub fn try_consume(&self) -> bool {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                self.limit
+                    .is_none_or(|limit| used < limit)
+                    .then_some(used + 1)
+            })
+            .is_ok()
+    }
+
+    /// Retries spent since creation or the last [`Self::reset`].
+    pub fn consumed(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Whether all retries have been spent.
+    pub fn is_exhausted(&self) -> bool {
+        self.limit.is_some_and(|limit| self.consumed() >= limit)
+    }
+
+    /// Make the full budget available again, e.g. at the start of a cycle.
+    pub fn reset(&self) {
+        self.used.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Jittered exponential backoff delays, ending early once `budget` is exhausted.
+///
+/// A retry is only taken from the budget when its delay is actually used.
+pub fn backoff(budget: &RetryBudget) -> impl Iterator<Item = Duration> {
+    let budget = budget.clone();
+    ExponentialBackoff::from_millis(100)
+        .map(jitter)
+        .take(MAX_RETRIES)
+        .take_while(move |_| budget.try_consume())
+}
+
+/// Run `operation`, retrying failures with [`backoff`] while `budget` allows.
+pub async fn retry<T, E, F, Fut>(budget: &RetryBudget, operation: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    Retry::start(backoff(budget), operation).await
+}
+
+/// Like [`retry`], but errors for which `should_retry` is false are returned at once
+/// without spending the budget.
+pub async fn retry_if<T, E, F, Fut, C>(
+    budget: &RetryBudget,
+    operation: F,
+    should_retry: C,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    C: FnMut(&E) -> bool,
+{
+    RetryIf::start(backoff(budget), operation, should_retry).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_exhaustion() {
+        let budget = RetryBudget::new(2);
+        let shared = budget.clone();
+
+        assert!(budget.try_consume());
+        assert!(shared.try_consume());
+        assert!(budget.is_exhausted());
+        assert!(!shared.try_consume());
+        assert_eq!(budget.consumed(), 2);
+
+        budget.reset();
+        assert!(!shared.is_exhausted());
+        assert!(shared.try_consume());
+    }
+
+    #[test]
+    fn test_unlimited_budget() {
+        let budget = RetryBudget::unlimited();
+        for _ in 0..1000 {
+            assert!(budget.try_consume());
+        }
+        assert!(!budget.is_exhausted());
+        assert_eq!(budget.consumed(), 1000);
+    }
+
+    #[test]
+    fn test_backoff_is_capped_and_jittered() {
+        let budget = RetryBudget::unlimited();
+        let delays: Vec<_> = backoff(&budget).collect();
+
+        assert_eq!(delays.len(), MAX_RETRIES);
+        assert!(delays[0] < Duration::from_millis(100));
+        assert_eq!(budget.consumed(), MAX_RETRIES);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_stops_when_budget_exhausted() {
+        let budget = RetryBudget::new(3);
+        let mut attempts = 0;
+
+        // First call: 1 attempt + 3 retries, then the budget is gone
+        let result: Result<(), &str> = retry(&budget, || {
+            attempts += 1;
+            async { Err("rpc down") }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 4);
+        assert!(budget.is_exhausted());
+
+        // Later calls fail on their first error without retrying
+        let mut attempts = 0;
+        let result: Result<(), &str> = retry(&budget, || {
+            attempts += 1;
+            async { Err("rpc down") }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+        assert_eq!(budget.consumed(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_if_skips_permanent_errors() {
+        let budget = RetryBudget::new(50);
+        let mut attempts = 0;
+
+        let result: Result<(), &str> = retry_if(
+            &budget,
+            || {
+                attempts += 1;
+                async { Err("method not found") }
+            },
+            |e| *e != "method not found",
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+        assert_eq!(budget.consumed(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_succeeds_after_failures() {
+        let budget = RetryBudget::new(50);
+        let mut attempts = 0;
+
+        let result: Result<u32, &str> = retry(&budget, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err("timeout")
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(3));
+        assert_eq!(budget.consumed(), 2);
+    }
+}
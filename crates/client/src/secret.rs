@@ -0,0 +1,122 @@
+//! Handling of private key material.
+//!
+//! A key is held in a [`PrivateKey`], which wipes it from memory when dropped and never
+//! prints it. Parsing failures are reported without echoing any part of the input.
+
+use crate::ClientError;
+use alloy_primitives::hex;
+use alloy_signer_local::PrivateKeySigner;
+use serde::Deserialize;
+use std::{convert::Infallible, fmt, str::FromStr};
+use zeroize::Zeroizing;
+
+/// A hex-encoded private key, with or without 0x prefix, zeroized on drop.
+///
+/// `Debug` output is redacted and there is no `Display`; [`Self::expose_secret`] is the
+/// only way to the key itself.
+#[derive(Clone, Deserialize)]
+#[serde(from = "String")]
+pub struct PrivateKey(Zeroizing<String>);
+
+impl PrivateKey {
+    /// The key as given. Don't keep copies of it around.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for PrivateKey {
+    fn from(key: String) -> Self {
+        Self(Zeroizing::new(key))
+    }
+}
+
+impl FromStr for PrivateKey {
+    type Err = Infallible;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(key.to_owned()))
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PrivateKey(<redacted>)")
+    }
+}
+
+/// Parse a hex-encoded private key into a signer.
+///
+/// The decoded bytes are zeroized once the signer holds them. Errors describe what is
+/// wrong with the key without quoting it.
+pub(crate) fn parse_private_key(private_key: &str) -> Result<PrivateKeySigner, ClientError> {
+    let hex_key = private_key.trim();
+    let hex_key = hex_key.strip_prefix("0x").unwrap_or(hex_key);
+
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    hex::decode_to_slice(hex_key, bytes.as_mut()).map_err(|_| {
+        ClientError::InvalidPrivateKey(format!(
+            "expected 64 hex characters with optional 0x prefix, got {} characters",
+            hex_key.len()
+        ))
+    })?;
+
+    PrivateKeySigner::from_slice(bytes.as_ref()).map_err(|_| {
+        ClientError::InvalidPrivateKey("not a valid secp256k1 private key".to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Anvil's first default account
+    const ANVIL_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    #[test]
+    fn test_private_key_debug_is_redacted() {
+        let key = PrivateKey::from(ANVIL_KEY.to_string());
+        let printed = format!("{key:?}");
+        assert!(!printed.contains("ac0974"));
+        assert_eq!(key.expose_secret(), ANVIL_KEY);
+
+        // Nor through a signer built from it
+        let signer = parse_private_key(key.expose_secret()).unwrap();
+        assert!(!format!("{signer:?}").contains("ac0974"));
+    }
+
+    #[test]
+    fn test_parse_errors_do_not_echo_key() {
+        // One character off from a valid key
+        let invalid = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ffzz";
+        for key in [
+            invalid,
+            &invalid[..40],
+            "0x00",
+            &format!("0x{}", "0".repeat(64)),
+        ] {
+            let err = parse_private_key(key).unwrap_err().to_string();
+            assert!(err.starts_with("Invalid private key"), "{err}");
+            assert!(!err.contains("ac0974") && !err.contains("zz"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_parse_accepts_with_and_without_prefix() {
+        let with = parse_private_key(ANVIL_KEY).unwrap();
+        let without = parse_private_key(&ANVIL_KEY[2..]).unwrap();
+        assert_eq!(with.address(), without.address());
+    }
+
+    #[test]
+    fn test_private_key_deserializes_from_string() {
+        #[derive(Deserialize)]
+        struct Local {
+            private_key: PrivateKey,
+        }
+
+        let local: Local =
+            serde_json::from_value(serde_json::json!({ "private_key": ANVIL_KEY })).unwrap();
+        assert_eq!(local.private_key.expose_secret(), ANVIL_KEY);
+    }
+}
@@ -0,0 +1,222 @@
+//! Transaction signing abstraction shared by all actions.
+//!
+//! Actions only need to know which address they send from and how to turn a filled
+//! transaction request into signed bytes. [`TransactionSigner`] captures that, with
+//! implementations for local private keys, the remote signer-proxy, and plain
+//! [`SignerFn`] closures.
+
+use crate::{secret, ClientError, RemoteSigner};
+use alloy_consensus::TxEnvelope;
+use alloy_dyn_abi::TypedData;
+use alloy_network::{eip2718::Encodable2718, EthereumWallet, TransactionBuilder};
+use alloy_primitives::{Address, Bytes, Signature};
+use alloy_rpc_types::TransactionRequest;
+use alloy_signer::Signer;
+use alloy_signer_local::PrivateKeySigner;
+use async_trait::async_trait;
+use std::{future::Future, pin::Pin, sync::Arc};
+
+/// A function that signs a transaction request and returns signed bytes.
+///
+/// Kept for callers that still build signing closures; wrap one in a [`FnSigner`] to use
+/// it where a [`TransactionSigner`] is expected.
+pub type SignerFn = Arc<
+    dyn Fn(TransactionRequest) -> Pin<Box<dyn Future<Output = eyre::Result<Bytes>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Signs transactions on behalf of a single address.
+///
+/// The transaction must be fully filled (nonce, gas, fees, chain_id, from) before being
+/// passed to [`Self::sign_transaction`]. Use [`crate::fill_transaction`] at the call site.
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    /// Address transactions are signed for.
+    fn address(&self) -> Address;
+
+    /// Chain the signer is bound to, or `None` if it can sign for any chain.
+    fn chain_id(&self) -> Option<u64>;
+
+    /// Sign a filled transaction request, returning the EIP-2718 encoded transaction.
+    async fn sign_transaction(&self, tx: TransactionRequest) -> eyre::Result<Bytes>;
+
+    /// Sign EIP-712 typed data.
+    ///
+    /// Signers that can't sign typed data keep the default, which returns an error.
+    async fn sign_typed_data(&self, _payload: &TypedData) -> eyre::Result<Signature> {
+        eyre::bail!(
+            "Signer {} does not support typed data signing",
+            self.address()
+        )
+    }
+}
+
+/// Signer backed by a private key held in memory.
+#[derive(Debug, Clone)]
+pub struct LocalSigner {
+    signer: PrivateKeySigner,
+}
+
+impl LocalSigner {
+    pub const fn new(signer: PrivateKeySigner) -> Self {
+        Self { signer }
+    }
+
+    /// Parse a hex-encoded private key.
+    ///
+    /// Errors don't quote the key; see [`crate::PrivateKey`] for holding it until then.
+    pub fn from_private_key(private_key: &str) -> Result<Self, ClientError> {
+        Ok(Self::new(secret::parse_private_key(private_key)?))
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for LocalSigner {
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    fn chain_id(&self) -> Option<u64> {
+        self.signer.chain_id()
+    }
+
+    async fn sign_transaction(&self, tx: TransactionRequest) -> eyre::Result<Bytes> {
+        let wallet = EthereumWallet::from(self.signer.clone());
+
+        // Build and sign the typed transaction
+        let tx_envelope: TxEnvelope = tx.build(&wallet).await.map_err(|e| eyre::eyre!("{}", e))?;
+
+        // Encode to EIP-2718 bytes
+        let mut encoded = Vec::new();
+        tx_envelope.encode_2718(&mut encoded);
+        Ok(Bytes::from(encoded))
+    }
+
+    async fn sign_typed_data(&self, payload: &TypedData) -> eyre::Result<Signature> {
+        Ok(self.signer.sign_dynamic_typed_data(payload).await?)
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for RemoteSigner {
+    fn address(&self) -> Address {
+        Self::address(self)
+    }
+
+    fn chain_id(&self) -> Option<u64> {
+        Some(Self::chain_id(self))
+    }
+
+    async fn sign_transaction(&self, tx: TransactionRequest) -> eyre::Result<Bytes> {
+        Self::sign_transaction(self, tx).await
+    }
+
+    async fn sign_typed_data(&self, payload: &TypedData) -> eyre::Result<Signature> {
+        Self::sign_typed_data(self, payload).await
+    }
+}
+
+/// Adapter that exposes a [`SignerFn`] closure as a [`TransactionSigner`].
+#[derive(Clone)]
+pub struct FnSigner {
+    address: Address,
+    chain_id: Option<u64>,
+    sign: SignerFn,
+}
+
+impl FnSigner {
+    /// Wrap `sign`, which must produce transactions signed by `address`.
+    pub fn new(address: Address, chain_id: Option<u64>, sign: SignerFn) -> Self {
+        Self {
+            address,
+            chain_id,
+            sign,
+        }
+    }
+}
+
+impl std::fmt::Debug for FnSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnSigner")
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for FnSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> Option<u64> {
+        self.chain_id
+    }
+
+    async fn sign_transaction(&self, tx: TransactionRequest) -> eyre::Result<Bytes> {
+        (self.sign)(tx).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, U256};
+
+    // Anvil's first default account
+    const ANVIL_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    #[tokio::test]
+    async fn test_local_signer_signs_for_its_address() {
+        let signer = LocalSigner::from_private_key(ANVIL_KEY).unwrap();
+        assert_eq!(
+            signer.address(),
+            address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+        );
+        assert_eq!(signer.chain_id(), None);
+
+        let tx = TransactionRequest::default()
+            .from(signer.address())
+            .to(Address::ZERO)
+            .value(U256::from(1))
+            .nonce(0)
+            .gas_limit(21_000)
+            .max_fee_per_gas(2)
+            .max_priority_fee_per_gas(1)
+            .with_chain_id(1);
+        let raw = signer.sign_transaction(tx).await.unwrap();
+        // EIP-1559 envelope
+        assert_eq!(raw[0], 0x02);
+    }
+
+    #[tokio::test]
+    async fn test_fn_signer_delegates_to_closure() {
+        let signer = FnSigner::new(
+            Address::repeat_byte(1),
+            Some(130),
+            Arc::new(|_tx| Box::pin(async { Ok(Bytes::from_static(&[0xab])) })),
+        );
+
+        assert_eq!(signer.address(), Address::repeat_byte(1));
+        assert_eq!(signer.chain_id(), Some(130));
+        assert_eq!(
+            signer
+                .sign_transaction(TransactionRequest::default())
+                .await
+                .unwrap(),
+            Bytes::from_static(&[0xab])
+        );
+
+        // Closures can't sign typed data
+        let payload: TypedData = serde_json::from_value(serde_json::json!({
+            "types": { "EIP712Domain": [] },
+            "primaryType": "EIP712Domain",
+            "domain": {},
+            "message": {}
+        }))
+        .unwrap();
+        assert!(signer.sign_typed_data(&payload).await.is_err());
+    }
+}
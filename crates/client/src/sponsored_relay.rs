@@ -0,0 +1,56 @@
+//! Submission of signed transactions through a gas-sponsoring relay.
+//!
+//! A sponsoring relay accepts a signed transaction over `eth_sendRawTransaction` and
+//! takes care of its gas (e.g. by funding the sender just before broadcasting it), so
+//! the signing EOA doesn't need to hold ETH on that chain.
+
+use crate::jsonrpc;
+use alloy_primitives::{Bytes, TxHash};
+use eyre::Result;
+
+/// Client for a gas-sponsoring relay endpoint.
+///
+/// # Example
+///
+/// ```ignore
+/// let relay = SponsoredRelay::new("https://relay.example/rpc");
+/// let signed_tx = signer.sign_transaction(tx_request).await?;
+/// let tx_hash = relay.send_raw_transaction(&signed_tx).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SponsoredRelay {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl SponsoredRelay {
+    /// Creates a client for the relay at `url`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_client(reqwest::Client::new(), url)
+    }
+
+    /// Creates a client with a custom HTTP client.
+    pub fn with_client(client: reqwest::Client, url: impl Into<String>) -> Self {
+        Self {
+            client,
+            url: url.into(),
+        }
+    }
+
+    /// Returns the relay URL.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Hands `signed_tx` to the relay, returning the transaction hash it reports.
+    pub async fn send_raw_transaction(&self, signed_tx: &Bytes) -> Result<TxHash> {
+        jsonrpc::call(
+            &self.client,
+            &self.url,
+            "sponsored relay",
+            "eth_sendRawTransaction",
+            [signed_tx],
+        )
+        .await
+    }
+}
@@ -22,11 +22,16 @@ pub struct EthereumConfig {
     pub weth: Address,
     /// Across SpokePool contract address
     pub spoke_pool: Address,
-    /// Block time in seconds (12 for Ethereum mainnet)
-    pub block_time_secs: u64,
+    /// Block time in milliseconds (12000 for Ethereum mainnet)
+    pub block_time_ms: u64,
 }
 
 impl EthereumConfig {
+    /// Number of blocks produced in `secs` seconds.
+    pub const fn blocks_in(&self, secs: u64) -> u64 {
+        secs.saturating_mul(1_000) / self.block_time_ms
+    }
+
     /// Ethereum mainnet configuration.
     pub const fn mainnet() -> Self {
         Self {
@@ -34,7 +39,7 @@ impl EthereumConfig {
             weth: address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"),
             // https://etherscan.io/address/0x5c7BCd6E7De5423a257D81B442095A1a6ced35C5
             spoke_pool: address!("0x5c7BCd6E7De5423a257D81B442095A1a6ced35C5"),
-            block_time_secs: 12,
+            block_time_ms: 12_000,
         }
     }
 
@@ -45,7 +50,7 @@ impl EthereumConfig {
             weth: address!("0xfFf9976782d46CC05630D1f6eBAb18b2324d6B14"),
             // https://sepolia.etherscan.io/address/0x5ef6C01E11889d86803e0B23e3cB3F9E9d97B662
             spoke_pool: address!("0x5ef6C01E11889d86803e0B23e3cB3F9E9d97B662"),
-            block_time_secs: 12,
+            block_time_ms: 12_000,
         }
     }
 }
@@ -65,14 +70,19 @@ pub struct UnichainConfig {
     pub l1_portal: Address,
     /// DisputeGameFactory contract address on L1 (for finding dispute games)
     pub l1_dispute_game_factory: Address,
-    /// Block time in seconds (1 for Unichain)
-    pub block_time_secs: u64,
+    /// Block time in milliseconds (1000 for Unichain)
+    pub block_time_ms: u64,
 }
 
 /// L2ToL1MessagePasser predeploy address (same on all OP Stack chains).
 const MESSAGE_PASSER: Address = address!("4200000000000000000000000000000000000016");
 
 impl UnichainConfig {
+    /// Number of blocks produced in `secs` seconds.
+    pub const fn blocks_in(&self, secs: u64) -> u64 {
+        secs.saturating_mul(1_000) / self.block_time_ms
+    }
+
     /// Unichain mainnet configuration.
     pub const fn mainnet() -> Self {
         Self {
@@ -85,7 +95,7 @@ impl UnichainConfig {
             l1_portal: address!("0x0bd48f6b86a26d3a217d0fa6ffe2b491b956a7a2"),
             // DisputeGameFactory on L1 for Unichain
             l1_dispute_game_factory: address!("0x2f12d621a16e2d3285929c9996f478508951dfe4"),
-            block_time_secs: 1,
+            block_time_ms: 1_000,
         }
     }
 
@@ -101,7 +111,7 @@ impl UnichainConfig {
             l1_portal: address!("0x0d83dab629f0e0f9d36c0cbc89b69a489f0751bd"),
             // DisputeGameFactory on L1 Sepolia for Unichain Sepolia
             l1_dispute_game_factory: address!("0xeff73e5aa3b9aec32c659aa3e00444d20a84394b"),
-            block_time_secs: 1,
+            block_time_ms: 1_000,
         }
     }
 }
@@ -0,0 +1,303 @@
+//! SpokePool event decoding across contract versions.
+//!
+//! Across has changed event shapes between SpokePool versions (`V3FundsDeposited` with
+//! address fields, then `FundsDeposited` with bytes32 fields). Deposits are decoded from
+//! either. A future upgrade that changes them again would make the filtered scans
+//! silently return nothing, so every deposit would look filled; [`EventDecodeReport`]
+//! classifies the unfiltered logs of a window to catch that.
+
+use alloy_contract::private::Provider;
+use alloy_primitives::{Address, B256, U256};
+use alloy_rpc_types_eth::{Filter, Log};
+use alloy_sol_types::SolEvent;
+use binding::across::ISpokePool;
+
+/// Topic0 of every supported deposit event version.
+pub const DEPOSIT_EVENT_SIGNATURES: [B256; 2] = [
+    ISpokePool::FundsDeposited::SIGNATURE_HASH,
+    ISpokePool::V3FundsDeposited::SIGNATURE_HASH,
+];
+
+/// Fields of a deposit event shared by all supported versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedDeposit {
+    pub deposit_id: U256,
+    pub input_amount: U256,
+    pub output_amount: U256,
+    pub fill_deadline: u32,
+    pub depositor: Address,
+}
+
+/// Fields of a `FilledRelay` event that identify the deposit it filled.
+///
+/// The relay data is echoed from the deposit, so these match the deposit's own fields;
+/// `output_amount` is the deposit's, not one updated by a speed-up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedFill {
+    pub deposit_id: U256,
+    pub input_amount: U256,
+    pub output_amount: U256,
+    pub fill_deadline: u32,
+    pub depositor: Address,
+}
+
+impl From<&ISpokePool::FilledRelay> for DecodedFill {
+    fn from(event: &ISpokePool::FilledRelay) -> Self {
+        Self {
+            deposit_id: event.depositId,
+            input_amount: event.inputAmount,
+            output_amount: event.outputAmount,
+            fill_deadline: event.fillDeadline,
+            // Same left-padding as the deposit, so legacy and bytes32 deposits compare equal
+            depositor: Address::from_word(event.depositor),
+        }
+    }
+}
+
+/// Decode `log` as a `FundsDeposited` event, falling back to the legacy
+/// `V3FundsDeposited`. Returns `None` if it is neither.
+pub fn decode_deposit(log: &Log) -> Option<DecodedDeposit> {
+    if let Ok(event) = log.log_decode::<ISpokePool::FundsDeposited>() {
+        let event = event.inner.data;
+        return Some(DecodedDeposit {
+            deposit_id: event.depositId,
+            input_amount: event.inputAmount,
+            output_amount: event.outputAmount,
+            fill_deadline: event.fillDeadline,
+            // EVM depositors are left-padded to 32 bytes
+            depositor: Address::from_word(event.depositor),
+        });
+    }
+
+    let event = log
+        .log_decode::<ISpokePool::V3FundsDeposited>()
+        .ok()?
+        .inner
+        .data;
+    Some(DecodedDeposit {
+        deposit_id: U256::from(event.depositId),
+        input_amount: event.inputAmount,
+        output_amount: event.outputAmount,
+        fill_deadline: event.fillDeadline,
+        depositor: event.depositor,
+    })
+}
+
+/// How the logs a SpokePool emitted over a window decode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventDecodeReport {
+    /// Deposit events of any supported version
+    pub deposits: usize,
+    /// `FilledRelay` events
+    pub fills: usize,
+    /// Other events in the binding, e.g. relayer refund leaves
+    pub other: usize,
+    /// Logs that match no known event, or carry a known topic but fail to decode
+    pub undecodable: usize,
+}
+
+impl EventDecodeReport {
+    /// Classify `logs`.
+    pub fn from_logs<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Self {
+        let mut report = Self::default();
+        for log in logs {
+            if decode_deposit(log).is_some() {
+                report.deposits += 1;
+            } else if log.log_decode::<ISpokePool::FilledRelay>().is_ok() {
+                report.fills += 1;
+            } else if log
+                .log_decode::<ISpokePool::ExecutedRelayerRefundRoot>()
+                .is_ok()
+                || log.log_decode::<ISpokePool::ClaimedRelayerRefund>().is_ok()
+            {
+                report.other += 1;
+            } else {
+                report.undecodable += 1;
+            }
+        }
+        report
+    }
+
+    /// Number of logs classified.
+    pub const fn total(&self) -> usize {
+        self.deposits + self.fills + self.other + self.undecodable
+    }
+
+    /// Share of undecodable logs, in basis points. 0 when there are no logs.
+    pub const fn undecodable_bps(&self) -> u64 {
+        match self.total() {
+            0 => 0,
+            total => (self.undecodable * 10_000 / total) as u64,
+        }
+    }
+
+    /// Whether more than `max_undecodable_bps` of the logs are undecodable, once the
+    /// window holds at least `min_logs` logs to judge by.
+    pub const fn exceeds(&self, max_undecodable_bps: u64, min_logs: usize) -> bool {
+        self.total() >= min_logs && self.undecodable_bps() > max_undecodable_bps
+    }
+}
+
+/// Fetch every log `spoke_pool` emitted in `from_block..=to_block`, with no topic filter,
+/// and classify it.
+pub async fn audit_spoke_pool_events<P: Provider>(
+    provider: &P,
+    spoke_pool: Address,
+    from_block: u64,
+    to_block: u64,
+) -> eyre::Result<EventDecodeReport> {
+    let filter = Filter::new()
+        .address(spoke_pool)
+        .from_block(from_block)
+        .to_block(to_block);
+    let logs = provider.get_logs(&filter).await?;
+
+    Ok(EventDecodeReport::from_logs(&logs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, LogData};
+
+    const SPOKE_POOL: Address = Address::repeat_byte(0x50);
+
+    fn rpc_log(data: LogData) -> Log {
+        Log {
+            inner: alloy_primitives::Log {
+                address: SPOKE_POOL,
+                data,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn deposit(deposit_id: u64) -> Log {
+        rpc_log(
+            ISpokePool::FundsDeposited {
+                inputToken: B256::repeat_byte(1),
+                outputToken: B256::repeat_byte(2),
+                inputAmount: U256::from(1_000),
+                outputAmount: U256::from(990),
+                destinationChainId: U256::from(130),
+                depositId: U256::from(deposit_id),
+                quoteTimestamp: 1,
+                fillDeadline: 100,
+                exclusivityDeadline: 0,
+                depositor: B256::repeat_byte(3),
+                recipient: B256::repeat_byte(3),
+                exclusiveRelayer: B256::ZERO,
+                message: Bytes::new(),
+            }
+            .encode_log_data(),
+        )
+    }
+
+    fn legacy_deposit(deposit_id: u32) -> Log {
+        rpc_log(
+            ISpokePool::V3FundsDeposited {
+                inputToken: Address::repeat_byte(1),
+                outputToken: Address::repeat_byte(2),
+                inputAmount: U256::from(2_000),
+                outputAmount: U256::from(1_990),
+                destinationChainId: U256::from(130),
+                depositId: deposit_id,
+                quoteTimestamp: 1,
+                fillDeadline: 200,
+                exclusivityDeadline: 0,
+                depositor: Address::repeat_byte(3),
+                recipient: Address::repeat_byte(3),
+                exclusiveRelayer: Address::ZERO,
+                message: Bytes::new(),
+            }
+            .encode_log_data(),
+        )
+    }
+
+    fn refund_claim() -> Log {
+        rpc_log(
+            ISpokePool::ClaimedRelayerRefund {
+                token: Address::repeat_byte(1),
+                relayer: Address::repeat_byte(4),
+                amount: U256::from(5),
+            }
+            .encode_log_data(),
+        )
+    }
+
+    /// A log with a topic no known event has, e.g. from an upgraded contract.
+    fn unknown() -> Log {
+        rpc_log(LogData::new_unchecked(
+            vec![B256::repeat_byte(0xee)],
+            Bytes::from(vec![0; 64]),
+        ))
+    }
+
+    #[test]
+    fn test_decode_deposit_versions() {
+        assert_eq!(
+            decode_deposit(&deposit(7)),
+            Some(DecodedDeposit {
+                deposit_id: U256::from(7),
+                input_amount: U256::from(1_000),
+                output_amount: U256::from(990),
+                fill_deadline: 100,
+                depositor: Address::repeat_byte(3),
+            })
+        );
+        assert_eq!(
+            decode_deposit(&legacy_deposit(8)),
+            Some(DecodedDeposit {
+                deposit_id: U256::from(8),
+                input_amount: U256::from(2_000),
+                output_amount: U256::from(1_990),
+                fill_deadline: 200,
+                depositor: Address::repeat_byte(3),
+            })
+        );
+        assert_eq!(decode_deposit(&refund_claim()), None);
+        assert_eq!(decode_deposit(&unknown()), None);
+    }
+
+    #[test]
+    fn test_report_classifies_mixed_logs() {
+        let logs = [
+            deposit(1),
+            legacy_deposit(2),
+            refund_claim(),
+            unknown(),
+            deposit(3),
+        ];
+
+        let report = EventDecodeReport::from_logs(&logs);
+
+        assert_eq!(
+            report,
+            EventDecodeReport {
+                deposits: 3,
+                fills: 0,
+                other: 1,
+                undecodable: 1,
+            }
+        );
+        assert_eq!(report.total(), 5);
+        assert_eq!(report.undecodable_bps(), 2_000);
+        assert!(!report.exceeds(5_000, 5));
+        assert!(report.exceeds(1_000, 5));
+        // Too few logs to judge
+        assert!(!report.exceeds(1_000, 6));
+    }
+
+    #[test]
+    fn test_report_flags_upgraded_contract() {
+        // After an upgrade every deposit is emitted under an unknown signature
+        let logs: Vec<_> = (0..20).map(|_| unknown()).chain([refund_claim()]).collect();
+
+        let report = EventDecodeReport::from_logs(&logs);
+
+        assert_eq!(report.deposits, 0);
+        assert_eq!(report.undecodable, 20);
+        assert!(report.exceeds(5_000, 10));
+        assert_eq!(EventDecodeReport::default().undecodable_bps(), 0);
+    }
+}
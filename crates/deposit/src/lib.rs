@@ -4,8 +4,12 @@
 //! via the Across Protocol. It queries on-chain events to determine which deposits
 //! have been initiated but not yet filled.
 
+pub mod events;
+pub mod refund;
 pub mod state;
 
+pub use refund::{match_refunds, ClaimedRefund, RefundEntry, RefundedDeposit};
 pub use state::{
-    get_inflight_deposit_total, get_inflight_deposits, DepositStateProvider, InFlightDeposit,
+    get_inflight_deposit_total, get_inflight_deposits, DepositCheckpoint, DepositStateProvider,
+    InFlightDeposit,
 };
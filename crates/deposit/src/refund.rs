@@ -0,0 +1,150 @@
+//! Refund tracking for expired Across deposits.
+//!
+//! A deposit whose fill deadline passes unfilled is refunded on the origin chain through
+//! a relayer refund leaf (`ExecutedRelayerRefundRoot`) that lists the depositor as a
+//! refund address. Refund leaves carry no deposit ID, so expired deposits are matched to
+//! refund entries by amount, oldest deposit first.
+
+use crate::state::InFlightDeposit;
+use alloy_primitives::{TxHash, U256};
+
+/// A refund paid to our depositor by an executed relayer refund leaf on the origin chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefundEntry {
+    /// Transaction that executed the refund leaf
+    pub tx_hash: TxHash,
+    /// Origin chain block containing the transaction
+    pub block_number: u64,
+    /// Amount refunded to the depositor
+    pub amount: U256,
+    /// Whether the refund was deferred into `getRelayerRefund` instead of transferred
+    pub deferred: bool,
+}
+
+/// A relayer refund claimed from the origin SpokePool (`ClaimedRelayerRefund`).
+///
+/// Refunds deferred by their leaf accrue in `getRelayerRefund` until claimed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimedRefund {
+    /// Transaction that claimed the refund
+    pub tx_hash: TxHash,
+    /// Origin chain block containing the transaction
+    pub block_number: u64,
+    /// Amount claimed
+    pub amount: U256,
+}
+
+/// An expired deposit matched to the refund that returned its funds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefundedDeposit {
+    /// Deposit ID on the origin chain
+    pub deposit_id: U256,
+    /// Refund that returned the deposit's input amount
+    pub refund: RefundEntry,
+}
+
+/// Match expired deposits to refund entries.
+///
+/// Each refund entry is used at most once and must land after the deposit. Deposits are
+/// processed in block order so that equal-amount deposits are paired with refunds
+/// in the order they were made.
+pub fn match_refunds(expired: &[InFlightDeposit], refunds: &[RefundEntry]) -> Vec<RefundedDeposit> {
+    let mut deposits: Vec<&InFlightDeposit> = expired.iter().collect();
+    deposits.sort_by_key(|d| (d.block_number, d.deposit_id));
+
+    let mut used = vec![false; refunds.len()];
+    let mut matched = Vec::new();
+
+    for deposit in deposits {
+        let candidate = refunds.iter().enumerate().find(|(i, refund)| {
+            !used[*i]
+                && refund.amount == deposit.input_amount
+                && refund.block_number > deposit.block_number
+        });
+
+        if let Some((i, refund)) = candidate {
+            used[i] = true;
+            matched.push(RefundedDeposit {
+                deposit_id: deposit.deposit_id,
+                refund: refund.clone(),
+            });
+        }
+    }
+
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+
+    fn deposit(id: u64, amount: u64, block_number: u64) -> InFlightDeposit {
+        InFlightDeposit {
+            deposit_id: U256::from(id),
+            origin_chain_id: 1,
+            destination_chain_id: 130,
+            input_amount: U256::from(amount),
+            output_amount: U256::from(amount),
+            depositor: Address::repeat_byte(1),
+            block_number,
+            fill_deadline: 1_000,
+        }
+    }
+
+    fn refund(tx: u8, amount: u64, block_number: u64) -> RefundEntry {
+        RefundEntry {
+            tx_hash: B256::repeat_byte(tx),
+            block_number,
+            amount: U256::from(amount),
+            deferred: false,
+        }
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let d = deposit(1, 100, 10);
+        assert!(!d.is_expired(999));
+        assert!(!d.is_expired(1_000));
+        assert!(d.is_expired(1_001));
+
+        // No deadline
+        let d = InFlightDeposit {
+            fill_deadline: 0,
+            ..d
+        };
+        assert!(!d.is_expired(1_001));
+    }
+
+    #[test]
+    fn test_expire_then_refund() {
+        let expired = [deposit(1, 100, 10), deposit(2, 200, 11)];
+        let refunds = [refund(0xaa, 200, 50), refund(0xbb, 100, 60)];
+
+        let matched = match_refunds(&expired, &refunds);
+        assert_eq!(matched.len(), 2);
+        assert_eq!(matched[0].deposit_id, U256::from(1));
+        assert_eq!(matched[0].refund.tx_hash, B256::repeat_byte(0xbb));
+        assert_eq!(matched[1].deposit_id, U256::from(2));
+        assert_eq!(matched[1].refund.tx_hash, B256::repeat_byte(0xaa));
+    }
+
+    #[test]
+    fn test_expired_without_refund_yet() {
+        let expired = [deposit(1, 100, 10), deposit(2, 100, 20)];
+        let refunds = [refund(0xaa, 100, 30)];
+
+        // Only one refund of this amount: the older deposit claims it
+        let matched = match_refunds(&expired, &refunds);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].deposit_id, U256::from(1));
+    }
+
+    #[test]
+    fn test_refund_before_deposit_is_ignored() {
+        let expired = [deposit(1, 100, 50)];
+        let refunds = [refund(0xaa, 100, 40), refund(0xbb, 99, 60)];
+
+        assert!(match_refunds(&expired, &refunds).is_empty());
+    }
+}
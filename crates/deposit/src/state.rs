@@ -1,13 +1,24 @@
 //! In-flight deposit tracking for Across Protocol.
 //!
 //! Tracks deposits initiated on L1 that haven't been filled on L2 yet.
-//! Uses `(originChainId, depositId)` as the correlation key.
+//! Uses `(originChainId, depositId)` as the correlation key, and only takes a fill as
+//! filling a deposit when the amounts, depositor and fill deadline agree too: a pool
+//! upgrade can restart deposit ids, so an id alone may name an older deposit.
 
+use crate::{
+    events::{decode_deposit, DecodedFill, DEPOSIT_EVENT_SIGNATURES},
+    refund::{ClaimedRefund, RefundEntry},
+};
 use alloy_contract::private::Provider;
-use alloy_primitives::{Address, FixedBytes, U256};
+use alloy_primitives::{Address, FixedBytes, B256, U256};
+use alloy_rpc_types_eth::Filter;
 use binding::across::ISpokePool;
-use std::collections::HashSet;
-use tokio_retry::{strategy::ExponentialBackoff, Retry};
+use client::{
+    coverage::{RangeCoverage, ScanCoverage},
+    logs::{retry_bisecting, LogLimitMatcher},
+    retry::RetryBudget,
+};
+use std::collections::HashMap;
 use tracing::{debug, warn};
 
 /// An in-flight deposit that has been initiated on L1 but not yet filled on L2.
@@ -21,10 +32,87 @@ pub struct InFlightDeposit {
     pub destination_chain_id: u64,
     /// Amount deposited (input amount)
     pub input_amount: U256,
+    /// Amount the relayer pays out on L2, as deposited; zero if not known
+    pub output_amount: U256,
     /// Depositor address
     pub depositor: Address,
     /// Block number on L1 where the deposit was initiated
     pub block_number: u64,
+    /// Timestamp after which the deposit can no longer be filled on L2; zero for none
+    pub fill_deadline: u32,
+}
+
+impl InFlightDeposit {
+    /// Whether the fill deadline has passed at `now` (a destination chain timestamp).
+    ///
+    /// Expired deposits will never be filled; Across refunds them on the origin chain.
+    /// A zero deadline, accepted by older SpokePools as a request for a slow fill, never
+    /// expires.
+    pub const fn is_expired(&self, now: u64) -> bool {
+        self.fill_deadline != 0 && (self.fill_deadline as u64) < now
+    }
+
+    /// Names of the fields on which `fill`, a fill of this deposit's id, disagrees with
+    /// this deposit. Empty if it is a fill of this deposit.
+    ///
+    /// An unknown (zero) output amount is not compared.
+    pub fn fill_mismatches(&self, fill: &DecodedFill) -> Vec<&'static str> {
+        let mut mismatches = Vec::new();
+        if fill.input_amount != self.input_amount {
+            mismatches.push("input_amount");
+        }
+        if !self.output_amount.is_zero() && fill.output_amount != self.output_amount {
+            mismatches.push("output_amount");
+        }
+        if fill.depositor != self.depositor {
+            mismatches.push("depositor");
+        }
+        if fill.fill_deadline != self.fill_deadline {
+            mismatches.push("fill_deadline");
+        }
+        mismatches
+    }
+}
+
+/// Whether one of `fills`, keyed by deposit id, is a fill of `deposit`.
+///
+/// A fill sharing the deposit's id but not its other fields is not counted, and is
+/// logged: the id was reused, or the event is not what it claims to be.
+fn is_filled_by(deposit: &InFlightDeposit, fills: &HashMap<U256, Vec<DecodedFill>>) -> bool {
+    let Some(fills) = fills.get(&deposit.deposit_id) else {
+        return false;
+    };
+    let mut filled = false;
+    for fill in fills {
+        let mismatches = deposit.fill_mismatches(fill);
+        if mismatches.is_empty() {
+            filled = true;
+        } else {
+            warn!(
+                deposit_id = %deposit.deposit_id,
+                origin_chain_id = deposit.origin_chain_id,
+                ?mismatches,
+                deposit_input_amount = %deposit.input_amount,
+                fill_input_amount = %fill.input_amount,
+                deposit_depositor = %deposit.depositor,
+                fill_depositor = %fill.depositor,
+                "Integrity: fill matches the deposit id but not its parameters, not treating the deposit as filled"
+            );
+        }
+    }
+    filled
+}
+
+/// In-flight deposits found by an earlier scan and the last L1 block it covered.
+///
+/// Persisting this lets the next scan pick up where the last one stopped: L1 is only
+/// scanned forward from `scanned_to`, and L2 only for fills of the known deposit ids.
+#[derive(Debug, Clone, Default)]
+pub struct DepositCheckpoint {
+    /// Last L1 block scanned for deposits, `None` before the first scan
+    pub scanned_to: Option<u64>,
+    /// Deposits that were unfilled as of `scanned_to`
+    pub inflight: Vec<InFlightDeposit>,
 }
 
 /// Provider for querying in-flight deposits across L1 and L2.
@@ -33,6 +121,14 @@ pub struct DepositStateProvider<P1, P2> {
     l2_provider: P2,
     l1_spoke_pool: Address,
     l2_spoke_pool: Address,
+    /// Retries available to chunk scans, shared with other users of the handle
+    retry_budget: RetryBudget,
+    /// Errors meaning a log query matched more results than the RPC returns
+    log_limit_matcher: LogLimitMatcher,
+    /// Track deposits of every depositor to the destination chain, not just ours
+    all_depositors: bool,
+    /// Blocks covered by chunk scans, shared with other users of the handle
+    scan_coverage: ScanCoverage,
 }
 
 impl<P1, P2> DepositStateProvider<P1, P2>
@@ -40,7 +136,8 @@ where
     P1: Provider + Clone,
     P2: Provider + Clone,
 {
-    pub const fn new(
+    /// Create a provider whose scans retry without a shared budget.
+    pub fn new(
         l1_provider: P1,
         l2_provider: P2,
         l1_spoke_pool: Address,
@@ -51,18 +148,51 @@ where
             l2_provider,
             l1_spoke_pool,
             l2_spoke_pool,
+            retry_budget: RetryBudget::unlimited(),
+            log_limit_matcher: LogLimitMatcher::default(),
+            all_depositors: false,
+            scan_coverage: ScanCoverage::default(),
         }
     }
 
+    /// Draw chunk scan retries from `budget`, e.g. one shared across a cycle.
+    pub fn with_retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = budget;
+        self
+    }
+
+    /// Recognize the RPC's log result limit errors with `matcher` instead of the default.
+    pub fn with_log_limit_matcher(mut self, matcher: LogLimitMatcher) -> Self {
+        self.log_limit_matcher = matcher;
+        self
+    }
+
+    /// Add the blocks covered by chunk scans to `coverage`, e.g. one shared across a cycle.
+    pub fn with_scan_coverage(mut self, coverage: ScanCoverage) -> Self {
+        self.scan_coverage = coverage;
+        self
+    }
+
+    /// Return the in-flight deposits of every depositor to the destination chain, not
+    /// only those of the depositor asked for.
+    ///
+    /// Each deposit keeps its own depositor. The unfiltered scans match far more logs;
+    /// chunks over the RPC's result limit are split as usual.
+    pub const fn with_all_depositors(mut self, all_depositors: bool) -> Self {
+        self.all_depositors = all_depositors;
+        self
+    }
+
     /// Get all in-flight deposits (initiated on L1 but not filled on L2).
     ///
     /// # Arguments
-    /// * `depositor` - Filter deposits by this depositor address
+    /// * `depositor` - Filter deposits by this depositor address, unless tracking all
+    ///   depositors; see [`Self::with_all_depositors`]
     /// * `destination_chain_id` - Filter deposits destined for this chain
     /// * `origin_chain_id` - The chain ID of L1 (typically 1 for Ethereum mainnet)
     /// * `lookback_secs` - How far back to scan (in seconds)
-    /// * `l1_block_time_secs` - L1 block time (12 for Ethereum)
-    /// * `l2_block_time_secs` - L2 block time (1 for Unichain)
+    /// * `l1_block_time_ms` - L1 block time in milliseconds (12000 for Ethereum)
+    /// * `l2_block_time_ms` - L2 block time in milliseconds (1000 for Unichain)
     ///
     /// # Returns
     /// A list of deposits that have been initiated but not yet filled.
@@ -72,12 +202,12 @@ where
         destination_chain_id: u64,
         origin_chain_id: u64,
         lookback_secs: u64,
-        l1_block_time_secs: u64,
-        l2_block_time_secs: u64,
+        l1_block_time_ms: u64,
+        l2_block_time_ms: u64,
     ) -> eyre::Result<Vec<InFlightDeposit>> {
         // Calculate lookback blocks for each chain
-        let l1_lookback_blocks = lookback_secs / l1_block_time_secs;
-        let l2_lookback_blocks = lookback_secs / l2_block_time_secs;
+        let l1_lookback_blocks = lookback_secs.saturating_mul(1_000) / l1_block_time_ms;
+        let l2_lookback_blocks = lookback_secs.saturating_mul(1_000) / l2_block_time_ms;
 
         // Get current block numbers
         let l1_current_block = self.l1_provider.get_block_number().await?;
@@ -97,48 +227,34 @@ where
             "Scanning for in-flight deposits"
         );
 
-        // Query L1 for FundsDeposited events
-        let l1_deposits = self
-            .scan_l1_deposits(
+        // Query L1 for FundsDeposited events and L2 for FilledRelay events concurrently.
+        // The fill scan doesn't depend on the deposits found, so the two chains' RPC
+        // latency can overlap; fills are matched against deposit IDs afterwards.
+        let ((l1_deposits, _), fills) = tokio::try_join!(
+            self.scan_l1_deposits(
                 depositor,
                 destination_chain_id,
                 l1_from_block,
                 l1_current_block,
-            )
-            .await?;
+            ),
+            self.get_fills(origin_chain_id, None, l2_from_block, l2_current_block),
+        )?;
 
         if l1_deposits.is_empty() {
             debug!("No L1 deposits found in range");
             return Ok(vec![]);
         }
 
-        // Collect deposit IDs to check on L2
-        let deposit_ids: Vec<U256> = l1_deposits.iter().map(|d| d.deposit_id).collect();
-
-        debug!(
-            count = l1_deposits.len(),
-            "Found L1 deposits, checking L2 for fills"
-        );
-
-        // Query L2 for FilledRelay events matching these deposit IDs
-        let filled_ids = self
-            .get_filled_deposit_ids(
-                origin_chain_id,
-                &deposit_ids,
-                l2_from_block,
-                l2_current_block,
-            )
-            .await?;
-
         debug!(
-            filled_count = filled_ids.len(),
-            "Found filled deposits on L2"
+            deposit_count = l1_deposits.len(),
+            filled_count = fills.len(),
+            "Found L1 deposits and L2 fills"
         );
 
         // Filter out filled deposits
         let inflight: Vec<InFlightDeposit> = l1_deposits
             .into_iter()
-            .filter(|d| !filled_ids.contains(&d.deposit_id))
+            .filter(|d| !is_filled_by(d, &fills))
             .collect();
 
         debug!(
@@ -149,60 +265,174 @@ where
         Ok(inflight)
     }
 
+    /// Get all in-flight deposits, resuming from `checkpoint`, and return the checkpoint
+    /// to resume from next time.
+    ///
+    /// Unlike [`Self::get_inflight_deposits`], L1 is only scanned from the block after
+    /// `checkpoint.scanned_to` (or the start of the lookback, if later), and the L2 fill
+    /// scan is restricted to the ids of known and newly found deposits. Deposits older
+    /// than the lookback are dropped, as a full scan would not see them either.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn refresh_inflight_deposits(
+        &self,
+        checkpoint: &DepositCheckpoint,
+        depositor: Address,
+        destination_chain_id: u64,
+        origin_chain_id: u64,
+        lookback_secs: u64,
+        l1_block_time_ms: u64,
+        l2_block_time_ms: u64,
+    ) -> eyre::Result<DepositCheckpoint> {
+        let l1_current_block = self.l1_provider.get_block_number().await?;
+        let l2_current_block = self.l2_provider.get_block_number().await?;
+
+        let l1_lookback_start =
+            l1_current_block.saturating_sub(lookback_secs.saturating_mul(1_000) / l1_block_time_ms);
+        let l2_from_block =
+            l2_current_block.saturating_sub(lookback_secs.saturating_mul(1_000) / l2_block_time_ms);
+        let l1_from_block = checkpoint
+            .scanned_to
+            .map_or(l1_lookback_start, |block| block.saturating_add(1))
+            .max(l1_lookback_start);
+
+        // Deposits recorded for another depositor or route (after a config change) or
+        // that fell out of the lookback are not carried over
+        let mut candidates: Vec<InFlightDeposit> = checkpoint
+            .inflight
+            .iter()
+            .filter(|d| {
+                (self.all_depositors || d.depositor == depositor)
+                    && d.destination_chain_id == destination_chain_id
+                    && d.origin_chain_id == origin_chain_id
+                    && d.block_number >= l1_lookback_start
+            })
+            .cloned()
+            .collect();
+
+        let (new_deposits, scanned_to) = if l1_from_block <= l1_current_block {
+            let (deposits, covered_to) = self
+                .scan_l1_deposits(
+                    depositor,
+                    destination_chain_id,
+                    l1_from_block,
+                    l1_current_block,
+                )
+                .await?;
+            (deposits, covered_to.or(checkpoint.scanned_to))
+        } else {
+            (vec![], Some(l1_current_block))
+        };
+
+        debug!(
+            known = candidates.len(),
+            new = new_deposits.len(),
+            l1_from = l1_from_block,
+            l1_to = l1_current_block,
+            "Resumed in-flight deposit scan from checkpoint"
+        );
+
+        for deposit in new_deposits {
+            if !candidates
+                .iter()
+                .any(|d| d.deposit_id == deposit.deposit_id)
+            {
+                candidates.push(deposit);
+            }
+        }
+
+        if !candidates.is_empty() {
+            let ids: Vec<U256> = candidates.iter().map(|d| d.deposit_id).collect();
+            // Every depositor's deposits may be too many ids for one topic filter
+            let fills = self
+                .get_fills(
+                    origin_chain_id,
+                    (!self.all_depositors).then_some(ids.as_slice()),
+                    l2_from_block,
+                    l2_current_block,
+                )
+                .await?;
+            candidates.retain(|d| !is_filled_by(d, &fills));
+        }
+
+        Ok(DepositCheckpoint {
+            scanned_to,
+            inflight: candidates,
+        })
+    }
+
     /// Scan L1 for FundsDeposited events in chunks.
+    ///
+    /// Returns the deposits found and the last block scanned without a gap since
+    /// `from_block`, which is how far a checkpoint may advance.
     async fn scan_l1_deposits(
         &self,
         depositor: Address,
         destination_chain_id: u64,
         from_block: u64,
         to_block: u64,
-    ) -> eyre::Result<Vec<InFlightDeposit>> {
+    ) -> eyre::Result<(Vec<InFlightDeposit>, Option<u64>)> {
         const CHUNK_SIZE: u64 = 9_500;
 
         let mut all_deposits = Vec::new();
+        let coverage = self.scan_coverage.track("l1_deposits", from_block);
         let mut current = from_block;
 
         while current <= to_block {
             let chunk_end = (current + CHUNK_SIZE - 1).min(to_block);
 
             let chunk_deposits = self
-                .scan_l1_chunk_with_retry(depositor, destination_chain_id, current, chunk_end)
+                .scan_l1_chunk_with_retry(
+                    &coverage,
+                    depositor,
+                    destination_chain_id,
+                    current,
+                    chunk_end,
+                )
                 .await?;
 
             all_deposits.extend(chunk_deposits);
             current = chunk_end + 1;
         }
 
-        Ok(all_deposits)
+        Ok((all_deposits, coverage.covered_to()))
     }
 
-    /// Scan a single L1 chunk with retry logic.
+    /// Scan a single L1 chunk with retry logic, splitting it on log result limit errors.
     async fn scan_l1_chunk_with_retry(
         &self,
+        coverage: &RangeCoverage,
         depositor: Address,
         destination_chain_id: u64,
         from_block: u64,
         to_block: u64,
     ) -> eyre::Result<Vec<InFlightDeposit>> {
-        let retry_strategy = ExponentialBackoff::from_millis(100).take(5);
-
-        Retry::spawn(retry_strategy, || async {
-            self.scan_l1_chunk(depositor, destination_chain_id, from_block, to_block)
-                .await
-                .map_err(|e| {
-                    warn!(
-                        from = from_block,
-                        to = to_block,
-                        error = %e,
-                        "L1 chunk scan failed, will retry"
-                    );
-                    e
-                })
-        })
+        retry_bisecting(
+            &self.retry_budget,
+            &self.log_limit_matcher,
+            coverage,
+            from_block,
+            to_block,
+            |from, to| async move {
+                self.scan_l1_chunk(depositor, destination_chain_id, from, to)
+                    .await
+                    .map_err(|e| {
+                        warn!(
+                            from,
+                            to,
+                            error = %e,
+                            "L1 chunk scan failed, will retry"
+                        );
+                        e
+                    })
+            },
+        )
         .await
     }
 
-    /// Scan a single chunk of L1 blocks for FundsDeposited events.
+    /// Scan a single chunk of L1 blocks for deposit events.
+    ///
+    /// Matches both `FundsDeposited` and the legacy `V3FundsDeposited`: the indexed
+    /// topics are laid out the same, so one filter covers deposits from either version.
     async fn scan_l1_chunk(
         &self,
         depositor: Address,
@@ -210,97 +440,135 @@ where
         from_block: u64,
         to_block: u64,
     ) -> eyre::Result<Vec<InFlightDeposit>> {
-        let contract = ISpokePool::new(self.l1_spoke_pool, &self.l1_provider);
-
-        // Convert depositor address to bytes32 for filtering
-        let depositor_bytes32 = address_to_bytes32(depositor);
-
-        let filter = contract
-            .FundsDeposited_filter()
+        let mut filter = Filter::new()
+            .address(self.l1_spoke_pool)
+            .event_signature(DEPOSIT_EVENT_SIGNATURES.to_vec())
             .topic1(U256::from(destination_chain_id)) // destinationChainId (indexed)
-            .topic3(depositor_bytes32) // depositor (indexed)
             .from_block(from_block)
             .to_block(to_block);
+        if !self.all_depositors {
+            // depositor (indexed), as bytes32
+            filter = filter.topic3(address_to_bytes32(depositor));
+        }
 
-        let events = filter.query().await?;
+        let logs = self.l1_provider.get_logs(&filter).await?;
 
         let origin_chain_id = self.l1_provider.get_chain_id().await?;
 
-        let deposits: Vec<InFlightDeposit> = events
-            .into_iter()
-            .map(|(event, log)| InFlightDeposit {
-                deposit_id: event.depositId,
+        let mut deposits = Vec::with_capacity(logs.len());
+        for log in &logs {
+            let Some(event) = decode_deposit(log) else {
+                warn!(
+                    tx = ?log.transaction_hash,
+                    "Skipping deposit log that does not decode as any known version"
+                );
+                continue;
+            };
+            // In case the RPC ignored the depositor topic
+            if !self.all_depositors && event.depositor != depositor {
+                continue;
+            }
+            deposits.push(InFlightDeposit {
+                deposit_id: event.deposit_id,
                 origin_chain_id,
                 destination_chain_id,
-                input_amount: event.inputAmount,
-                depositor,
+                input_amount: event.input_amount,
+                output_amount: event.output_amount,
+                depositor: event.depositor,
                 block_number: log.block_number.unwrap_or_default(),
-            })
-            .collect();
+                fill_deadline: event.fill_deadline,
+            });
+        }
 
         Ok(deposits)
     }
 
-    /// Query L2 for FilledRelay events and return the set of filled deposit IDs.
-    async fn get_filled_deposit_ids(
+    /// Whether deposit `deposit_id` from `origin_chain_id` was filled on L2 since
+    /// `from_block`, which must be at or before the deposit was made.
+    ///
+    /// Any fill of the id counts, even one whose parameters differ: callers use this to
+    /// hold off acting on a deposit, for which a false positive is the safe side.
+    pub async fn is_filled(
+        &self,
+        origin_chain_id: u64,
+        deposit_id: U256,
+        from_block: u64,
+    ) -> eyre::Result<bool> {
+        let to_block = self.l2_provider.get_block_number().await?;
+        let fills = self
+            .get_fills(origin_chain_id, Some(&[deposit_id]), from_block, to_block)
+            .await?;
+        Ok(fills.contains_key(&deposit_id))
+    }
+
+    /// Query L2 for FilledRelay events of deposits originating from `origin_chain_id`,
+    /// restricted to `deposit_ids` if given, and return them keyed by deposit ID.
+    async fn get_fills(
         &self,
         origin_chain_id: u64,
-        deposit_ids: &[U256],
+        deposit_ids: Option<&[U256]>,
         from_block: u64,
         to_block: u64,
-    ) -> eyre::Result<HashSet<U256>> {
-        if deposit_ids.is_empty() {
-            return Ok(HashSet::new());
-        }
-
-        let mut filled_ids = HashSet::new();
+    ) -> eyre::Result<HashMap<U256, Vec<DecodedFill>>> {
+        let mut fills: HashMap<U256, Vec<DecodedFill>> = HashMap::new();
 
         // Scan in chunks
         const CHUNK_SIZE: u64 = 9_500;
+        let coverage = self.scan_coverage.track("l2_fills", from_block);
         let mut current = from_block;
 
         while current <= to_block {
             let chunk_end = (current + CHUNK_SIZE - 1).min(to_block);
 
             let chunk_filled = self
-                .scan_l2_fills_chunk_with_retry(origin_chain_id, current, chunk_end)
+                .scan_l2_fills_chunk_with_retry(
+                    &coverage,
+                    origin_chain_id,
+                    deposit_ids,
+                    current,
+                    chunk_end,
+                )
                 .await?;
 
-            // Only keep fills for deposit IDs we care about
-            for id in chunk_filled {
-                if deposit_ids.contains(&id) {
-                    filled_ids.insert(id);
-                }
+            for fill in chunk_filled {
+                fills.entry(fill.deposit_id).or_default().push(fill);
             }
 
             current = chunk_end + 1;
         }
 
-        Ok(filled_ids)
+        Ok(fills)
     }
 
-    /// Scan a single L2 chunk with retry logic.
+    /// Scan a single L2 chunk with retry logic, splitting it on log result limit errors.
     async fn scan_l2_fills_chunk_with_retry(
         &self,
+        coverage: &RangeCoverage,
         origin_chain_id: u64,
+        deposit_ids: Option<&[U256]>,
         from_block: u64,
         to_block: u64,
-    ) -> eyre::Result<Vec<U256>> {
-        let retry_strategy = ExponentialBackoff::from_millis(100).take(5);
-
-        Retry::spawn(retry_strategy, || async {
-            self.scan_l2_fills_chunk(origin_chain_id, from_block, to_block)
-                .await
-                .map_err(|e| {
-                    warn!(
-                        from = from_block,
-                        to = to_block,
-                        error = %e,
-                        "L2 chunk scan failed, will retry"
-                    );
-                    e
-                })
-        })
+    ) -> eyre::Result<Vec<DecodedFill>> {
+        retry_bisecting(
+            &self.retry_budget,
+            &self.log_limit_matcher,
+            coverage,
+            from_block,
+            to_block,
+            |from, to| async move {
+                self.scan_l2_fills_chunk(origin_chain_id, deposit_ids, from, to)
+                    .await
+                    .map_err(|e| {
+                        warn!(
+                            from,
+                            to,
+                            error = %e,
+                            "L2 chunk scan failed, will retry"
+                        );
+                        e
+                    })
+            },
+        )
         .await
     }
 
@@ -308,25 +576,221 @@ where
     async fn scan_l2_fills_chunk(
         &self,
         origin_chain_id: u64,
+        deposit_ids: Option<&[U256]>,
         from_block: u64,
         to_block: u64,
-    ) -> eyre::Result<Vec<U256>> {
+    ) -> eyre::Result<Vec<DecodedFill>> {
         let contract = ISpokePool::new(self.l2_spoke_pool, &self.l2_provider);
 
-        let filter = contract
+        let mut filter = contract
             .FilledRelay_filter()
             .topic1(U256::from(origin_chain_id)) // originChainId (indexed)
             .from_block(from_block)
             .to_block(to_block);
+        if let Some(ids) = deposit_ids {
+            // depositId (indexed)
+            filter = filter.topic2(ids.iter().copied().map(B256::from).collect::<Vec<_>>());
+        }
 
         let events = filter.query().await?;
 
-        let deposit_ids: Vec<U256> = events
-            .into_iter()
-            .map(|(event, _)| event.depositId)
-            .collect();
+        Ok(events
+            .iter()
+            .map(|(event, _)| DecodedFill::from(event))
+            .collect())
+    }
+
+    /// Scan the L1 SpokePool for relayer refund leaves that pay `depositor` in `token`.
+    ///
+    /// Refunds for expired deposits are executed on the origin chain, so only leaves
+    /// for `origin_chain_id` are considered.
+    pub async fn scan_l1_refunds(
+        &self,
+        depositor: Address,
+        token: Address,
+        origin_chain_id: u64,
+        from_block: u64,
+        to_block: u64,
+    ) -> eyre::Result<Vec<RefundEntry>> {
+        const CHUNK_SIZE: u64 = 9_500;
+
+        let mut all_refunds = Vec::new();
+        let coverage = self.scan_coverage.track("l1_refunds", from_block);
+        let mut current = from_block;
+
+        while current <= to_block {
+            let chunk_end = (current + CHUNK_SIZE - 1).min(to_block);
 
-        Ok(deposit_ids)
+            let chunk_refunds = self
+                .scan_l1_refunds_chunk_with_retry(
+                    &coverage,
+                    depositor,
+                    token,
+                    origin_chain_id,
+                    current,
+                    chunk_end,
+                )
+                .await?;
+
+            all_refunds.extend(chunk_refunds);
+            current = chunk_end + 1;
+        }
+
+        Ok(all_refunds)
+    }
+
+    /// Scan a single L1 refund chunk with retry logic, splitting it on log result limit errors.
+    async fn scan_l1_refunds_chunk_with_retry(
+        &self,
+        coverage: &RangeCoverage,
+        depositor: Address,
+        token: Address,
+        origin_chain_id: u64,
+        from_block: u64,
+        to_block: u64,
+    ) -> eyre::Result<Vec<RefundEntry>> {
+        retry_bisecting(
+            &self.retry_budget,
+            &self.log_limit_matcher,
+            coverage,
+            from_block,
+            to_block,
+            |from, to| async move {
+                self.scan_l1_refunds_chunk(depositor, token, origin_chain_id, from, to)
+                    .await
+                    .map_err(|e| {
+                        warn!(
+                            from,
+                            to,
+                            error = %e,
+                            "L1 refund chunk scan failed, will retry"
+                        );
+                        e
+                    })
+            },
+        )
+        .await
+    }
+
+    /// Scan a single chunk of L1 blocks for ExecutedRelayerRefundRoot events.
+    async fn scan_l1_refunds_chunk(
+        &self,
+        depositor: Address,
+        token: Address,
+        origin_chain_id: u64,
+        from_block: u64,
+        to_block: u64,
+    ) -> eyre::Result<Vec<RefundEntry>> {
+        let contract = ISpokePool::new(self.l1_spoke_pool, &self.l1_provider);
+
+        let filter = contract
+            .ExecutedRelayerRefundRoot_filter()
+            .topic1(U256::from(origin_chain_id)) // chainId (indexed)
+            .from_block(from_block)
+            .to_block(to_block);
+
+        let events = filter.query().await?;
+
+        let mut refunds = Vec::new();
+        for (event, log) in events {
+            if event.l2TokenAddress != token {
+                continue;
+            }
+
+            let Some(tx_hash) = log.transaction_hash else {
+                continue;
+            };
+
+            for (address, amount) in event.refundAddresses.iter().zip(&event.refundAmounts) {
+                if *address == depositor {
+                    refunds.push(RefundEntry {
+                        tx_hash,
+                        block_number: log.block_number.unwrap_or_default(),
+                        amount: *amount,
+                        deferred: event.deferredRefunds,
+                    });
+                }
+            }
+        }
+
+        Ok(refunds)
+    }
+
+    /// Scan the L1 SpokePool for relayer refunds in `token` claimed by `relayer`.
+    pub async fn scan_l1_claimed_refunds(
+        &self,
+        relayer: Address,
+        token: Address,
+        from_block: u64,
+        to_block: u64,
+    ) -> eyre::Result<Vec<ClaimedRefund>> {
+        const CHUNK_SIZE: u64 = 9_500;
+
+        let mut claims = Vec::new();
+        let coverage = self.scan_coverage.track("l1_refund_claims", from_block);
+        let mut current = from_block;
+
+        while current <= to_block {
+            let chunk_end = (current + CHUNK_SIZE - 1).min(to_block);
+
+            let chunk_claims = retry_bisecting(
+                &self.retry_budget,
+                &self.log_limit_matcher,
+                &coverage,
+                current,
+                chunk_end,
+                |from, to| async move {
+                    self.scan_l1_claimed_refunds_chunk(relayer, token, from, to)
+                        .await
+                        .map_err(|e| {
+                            warn!(
+                                from,
+                                to,
+                                error = %e,
+                                "L1 refund claim chunk scan failed, will retry"
+                            );
+                            e
+                        })
+                },
+            )
+            .await?;
+
+            claims.extend(chunk_claims);
+            current = chunk_end + 1;
+        }
+
+        Ok(claims)
+    }
+
+    /// Scan a single chunk of L1 blocks for ClaimedRelayerRefund events.
+    async fn scan_l1_claimed_refunds_chunk(
+        &self,
+        relayer: Address,
+        token: Address,
+        from_block: u64,
+        to_block: u64,
+    ) -> eyre::Result<Vec<ClaimedRefund>> {
+        let contract = ISpokePool::new(self.l1_spoke_pool, &self.l1_provider);
+
+        let events = contract
+            .ClaimedRelayerRefund_filter()
+            .topic1(token) // token (indexed)
+            .topic2(relayer) // relayer (indexed)
+            .from_block(from_block)
+            .to_block(to_block)
+            .query()
+            .await?;
+
+        Ok(events
+            .into_iter()
+            .filter_map(|(event, log)| {
+                Some(ClaimedRefund {
+                    tx_hash: log.transaction_hash?,
+                    block_number: log.block_number.unwrap_or_default(),
+                    amount: event.amount,
+                })
+            })
+            .collect())
     }
 }
 
@@ -348,8 +812,8 @@ pub async fn get_inflight_deposits<P1, P2>(
     destination_chain_id: u64,
     origin_chain_id: u64,
     lookback_secs: u64,
-    l1_block_time_secs: u64,
-    l2_block_time_secs: u64,
+    l1_block_time_ms: u64,
+    l2_block_time_ms: u64,
 ) -> eyre::Result<Vec<InFlightDeposit>>
 where
     P1: Provider + Clone,
@@ -364,8 +828,8 @@ where
             destination_chain_id,
             origin_chain_id,
             lookback_secs,
-            l1_block_time_secs,
-            l2_block_time_secs,
+            l1_block_time_ms,
+            l2_block_time_ms,
         )
         .await
 }
@@ -383,8 +847,8 @@ pub async fn get_inflight_deposit_total<P1, P2>(
     destination_chain_id: u64,
     origin_chain_id: u64,
     lookback_secs: u64,
-    l1_block_time_secs: u64,
-    l2_block_time_secs: u64,
+    l1_block_time_ms: u64,
+    l2_block_time_ms: u64,
 ) -> eyre::Result<U256>
 where
     P1: Provider + Clone,
@@ -399,11 +863,383 @@ where
         destination_chain_id,
         origin_chain_id,
         lookback_secs,
-        l1_block_time_secs,
-        l2_block_time_secs,
+        l1_block_time_ms,
+        l2_block_time_ms,
     )
     .await?;
 
     let total: U256 = inflight.iter().map(|d| d.input_amount).sum();
     Ok(total)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, U64};
+    use alloy_provider::ProviderBuilder;
+    use alloy_rpc_types_eth::Log;
+    use alloy_sol_types::SolEvent;
+    use alloy_transport::mock::Asserter;
+    use binding::across::{FillType, V3RelayExecutionEventInfo};
+    use std::collections::HashSet;
+
+    const DEPOSITOR: Address = Address::repeat_byte(0xd0);
+    const L1_SPOKE_POOL: Address = Address::repeat_byte(0x51);
+    const L2_SPOKE_POOL: Address = Address::repeat_byte(0x52);
+
+    /// A deposit as [`deposit_log`] makes it.
+    fn inflight(deposit_id: u64, block_number: u64) -> InFlightDeposit {
+        InFlightDeposit {
+            deposit_id: U256::from(deposit_id),
+            origin_chain_id: 1,
+            destination_chain_id: 130,
+            input_amount: U256::from(2_000),
+            output_amount: U256::from(1_990),
+            depositor: DEPOSITOR,
+            block_number,
+            fill_deadline: 200,
+        }
+    }
+
+    fn deposit_log(deposit_id: u64, block_number: u64) -> Log {
+        deposit_log_from(DEPOSITOR, deposit_id, block_number)
+    }
+
+    fn deposit_log_from(depositor: Address, deposit_id: u64, block_number: u64) -> Log {
+        let event = ISpokePool::FundsDeposited {
+            inputToken: B256::repeat_byte(1),
+            outputToken: B256::repeat_byte(2),
+            inputAmount: U256::from(2_000),
+            outputAmount: U256::from(1_990),
+            destinationChainId: U256::from(130),
+            depositId: U256::from(deposit_id),
+            quoteTimestamp: 1,
+            fillDeadline: 200,
+            exclusivityDeadline: 0,
+            depositor: address_to_bytes32(depositor),
+            recipient: address_to_bytes32(depositor),
+            exclusiveRelayer: B256::ZERO,
+            message: Bytes::new(),
+        };
+        Log {
+            inner: alloy_primitives::Log {
+                address: L1_SPOKE_POOL,
+                data: event.encode_log_data(),
+            },
+            block_number: Some(block_number),
+            ..Default::default()
+        }
+    }
+
+    /// A `FilledRelay` log for the deposit [`deposit_log`] makes with `deposit_id`.
+    fn fill_log(deposit_id: u64) -> Log {
+        fill_log_with(deposit_id, U256::from(2_000))
+    }
+
+    /// A `FilledRelay` log for `deposit_id` with `input_amount`.
+    fn fill_log_with(deposit_id: u64, input_amount: U256) -> Log {
+        let fill = ISpokePool::FilledRelay {
+            inputToken: B256::repeat_byte(1),
+            outputToken: B256::repeat_byte(2),
+            inputAmount: input_amount,
+            outputAmount: U256::from(1_990),
+            repaymentChainId: U256::from(1),
+            originChainId: U256::from(1),
+            depositId: U256::from(deposit_id),
+            fillDeadline: 200,
+            exclusivityDeadline: 0,
+            exclusiveRelayer: B256::ZERO,
+            relayer: B256::repeat_byte(4),
+            depositor: address_to_bytes32(DEPOSITOR),
+            recipient: address_to_bytes32(DEPOSITOR),
+            messageHash: B256::ZERO,
+            relayExecutionInfo: V3RelayExecutionEventInfo {
+                updatedRecipient: address_to_bytes32(DEPOSITOR),
+                updatedMessageHash: B256::ZERO,
+                updatedOutputAmount: U256::ZERO,
+                fillType: FillType::FastFill,
+            },
+        };
+        Log {
+            inner: alloy_primitives::Log {
+                address: L2_SPOKE_POOL,
+                data: fill.encode_log_data(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_resumes_from_checkpoint() {
+        let l1 = Asserter::new();
+        let l2 = Asserter::new();
+        let coverage = ScanCoverage::default();
+        let provider = DepositStateProvider::new(
+            ProviderBuilder::new().connect_mocked_client(l1.clone()),
+            ProviderBuilder::new().connect_mocked_client(l2.clone()),
+            L1_SPOKE_POOL,
+            L2_SPOKE_POOL,
+        )
+        .with_scan_coverage(coverage.clone());
+
+        // Deposit 1 is known; deposit 0 fell out of the 12-block lookback
+        let checkpoint = DepositCheckpoint {
+            scanned_to: Some(995),
+            inflight: vec![inflight(0, 900), inflight(1, 990)],
+        };
+
+        l1.push_success(&U64::from(1_000));
+        l2.push_success(&U64::from(5_000));
+        // Only blocks 996..=1000 are scanned on L1: deposit 2 is new
+        l1.push_success(&vec![deposit_log(2, 998)]);
+        l1.push_success(&U64::from(1));
+        // Deposit 1 was filled on L2
+        l2.push_success(&vec![fill_log(1)]);
+
+        let next = provider
+            .refresh_inflight_deposits(&checkpoint, DEPOSITOR, 130, 1, 144, 12_000, 1_000)
+            .await
+            .unwrap();
+
+        assert_eq!(next.scanned_to, Some(1_000));
+        // 996..=1000 on L1 and the 144-block lookback on L2
+        assert_eq!(coverage.totals().covered_blocks, 5 + 145);
+        assert_eq!(next.inflight.len(), 1);
+        assert_eq!(next.inflight[0].deposit_id, U256::from(2));
+        assert_eq!(next.inflight[0].block_number, 998);
+        assert_eq!(next.inflight[0].input_amount, U256::from(2_000));
+        assert!(l1.read_q().is_empty());
+        assert!(l2.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_tracks_all_depositors_when_enabled() {
+        const OTHER: Address = Address::repeat_byte(0xe0);
+
+        for all_depositors in [false, true] {
+            let l1 = Asserter::new();
+            let l2 = Asserter::new();
+            let provider = DepositStateProvider::new(
+                ProviderBuilder::new().connect_mocked_client(l1.clone()),
+                ProviderBuilder::new().connect_mocked_client(l2.clone()),
+                L1_SPOKE_POOL,
+                L2_SPOKE_POOL,
+            )
+            .with_all_depositors(all_depositors);
+            // Another depositor's deposit, known from an earlier scan
+            let mut known = inflight(1, 990);
+            known.depositor = OTHER;
+            let checkpoint = DepositCheckpoint {
+                scanned_to: Some(995),
+                inflight: vec![known],
+            };
+
+            l1.push_success(&U64::from(1_000));
+            l2.push_success(&U64::from(5_000));
+            l1.push_success(&vec![
+                deposit_log_from(DEPOSITOR, 2, 998),
+                deposit_log_from(OTHER, 3, 999),
+            ]);
+            l1.push_success(&U64::from(1));
+            l2.push_success(&Vec::<Log>::new());
+
+            let next = provider
+                .refresh_inflight_deposits(&checkpoint, DEPOSITOR, 130, 1, 144, 12_000, 1_000)
+                .await
+                .unwrap();
+
+            let found: Vec<_> = next
+                .inflight
+                .iter()
+                .map(|d| (d.deposit_id.to::<u64>(), d.depositor))
+                .collect();
+            if all_depositors {
+                assert_eq!(found, [(1, OTHER), (2, DEPOSITOR), (3, OTHER)]);
+            } else {
+                assert_eq!(found, [(2, DEPOSITOR)]);
+            }
+            assert!(l1.read_q().is_empty());
+            assert!(l2.read_q().is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fill_scan_splits_range_over_log_limit() {
+        let l2 = Asserter::new();
+        let provider = DepositStateProvider::new(
+            ProviderBuilder::new().connect_mocked_client(Asserter::new()),
+            ProviderBuilder::new().connect_mocked_client(l2.clone()),
+            L1_SPOKE_POOL,
+            L2_SPOKE_POOL,
+        )
+        .with_retry_budget(RetryBudget::new(0));
+
+        // 0..=99 is over the limit; its halves are not
+        l2.push_failure_msg("query returned more than 10000 results");
+        l2.push_success(&vec![fill_log(1)]);
+        l2.push_success(&vec![fill_log(2)]);
+
+        let filled = provider.get_fills(1, None, 0, 99).await.unwrap();
+
+        let filled: HashSet<_> = filled.into_keys().collect();
+        assert_eq!(filled, HashSet::from([U256::from(1), U256::from(2)]));
+        assert!(l2.read_q().is_empty());
+    }
+
+    #[test]
+    fn test_fill_mismatches() {
+        let deposit = inflight(7, 10);
+        let fill = DecodedFill {
+            deposit_id: U256::from(7),
+            input_amount: U256::from(2_000),
+            output_amount: U256::from(1_990),
+            fill_deadline: 200,
+            depositor: DEPOSITOR,
+        };
+        assert!(deposit.fill_mismatches(&fill).is_empty());
+
+        // The same id reused by a deposit made through another pool deployment
+        let conflicting = DecodedFill {
+            input_amount: U256::from(5_000),
+            output_amount: U256::from(4_990),
+            fill_deadline: 300,
+            depositor: Address::repeat_byte(0xe0),
+            ..fill
+        };
+        assert_eq!(
+            deposit.fill_mismatches(&conflicting),
+            [
+                "input_amount",
+                "output_amount",
+                "depositor",
+                "fill_deadline"
+            ]
+        );
+
+        // Deposits recorded without an output amount compare on the rest
+        let mut unknown_output = deposit;
+        unknown_output.output_amount = U256::ZERO;
+        assert!(unknown_output
+            .fill_mismatches(&DecodedFill {
+                output_amount: U256::from(1),
+                ..fill
+            })
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_fill_leaves_deposit_inflight() {
+        let l1 = Asserter::new();
+        let l2 = Asserter::new();
+        let provider = DepositStateProvider::new(
+            ProviderBuilder::new().connect_mocked_client(l1.clone()),
+            ProviderBuilder::new().connect_mocked_client(l2.clone()),
+            L1_SPOKE_POOL,
+            L2_SPOKE_POOL,
+        );
+        let checkpoint = DepositCheckpoint {
+            scanned_to: Some(1_000),
+            inflight: vec![inflight(1, 990), inflight(2, 995)],
+        };
+
+        l1.push_success(&U64::from(1_000));
+        l2.push_success(&U64::from(5_000));
+        // Deposit 1 only has a fill of another deposit with the same id; 2 is filled
+        l2.push_success(&vec![fill_log_with(1, U256::from(5_000)), fill_log(2)]);
+
+        let next = provider
+            .refresh_inflight_deposits(&checkpoint, DEPOSITOR, 130, 1, 144, 12_000, 1_000)
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = next.inflight.iter().map(|d| d.deposit_id).collect();
+        assert_eq!(ids, [U256::from(1)]);
+        assert!(l2.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scan_claimed_refunds() {
+        let l1 = Asserter::new();
+        let provider = DepositStateProvider::new(
+            ProviderBuilder::new().connect_mocked_client(l1.clone()),
+            ProviderBuilder::new().connect_mocked_client(Asserter::new()),
+            L1_SPOKE_POOL,
+            L2_SPOKE_POOL,
+        );
+        let claim = |amount: u64, block_number: u64| Log {
+            inner: alloy_primitives::Log {
+                address: L1_SPOKE_POOL,
+                data: ISpokePool::ClaimedRelayerRefund {
+                    token: Address::repeat_byte(0xee),
+                    relayer: DEPOSITOR,
+                    amount: U256::from(amount),
+                }
+                .encode_log_data(),
+            },
+            block_number: Some(block_number),
+            transaction_hash: Some(B256::repeat_byte(block_number as u8)),
+            ..Default::default()
+        };
+
+        // Two chunks
+        l1.push_success(&vec![claim(300, 10)]);
+        l1.push_success(&vec![claim(200, 9_600)]);
+        let claims = provider
+            .scan_l1_claimed_refunds(DEPOSITOR, Address::repeat_byte(0xee), 0, 10_000)
+            .await
+            .unwrap();
+
+        let amounts: Vec<_> = claims.iter().map(|c| c.amount.to::<u64>()).collect();
+        assert_eq!(amounts, [300, 200]);
+        assert_eq!(claims[1].block_number, 9_600);
+        assert!(l1.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_is_filled() {
+        let l2 = Asserter::new();
+        let provider = DepositStateProvider::new(
+            ProviderBuilder::new().connect_mocked_client(Asserter::new()),
+            ProviderBuilder::new().connect_mocked_client(l2.clone()),
+            L1_SPOKE_POOL,
+            L2_SPOKE_POOL,
+        );
+
+        l2.push_success(&U64::from(5_000));
+        l2.push_success(&Vec::<Log>::new());
+        assert!(!provider.is_filled(1, U256::from(7), 4_000).await.unwrap());
+
+        l2.push_success(&U64::from(5_010));
+        l2.push_success(&vec![fill_log(7)]);
+        assert!(provider.is_filled(1, U256::from(7), 4_000).await.unwrap());
+        assert!(l2.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_skips_fill_scan_without_deposits() {
+        let l1 = Asserter::new();
+        let l2 = Asserter::new();
+        let provider = DepositStateProvider::new(
+            ProviderBuilder::new().connect_mocked_client(l1.clone()),
+            ProviderBuilder::new().connect_mocked_client(l2.clone()),
+            L1_SPOKE_POOL,
+            L2_SPOKE_POOL,
+        );
+
+        l1.push_success(&U64::from(1_000));
+        l2.push_success(&U64::from(5_000));
+        // Already scanned up to the head: no L1 scan, nothing to look up on L2
+        let checkpoint = DepositCheckpoint {
+            scanned_to: Some(1_000),
+            inflight: vec![],
+        };
+
+        let next = provider
+            .refresh_inflight_deposits(&checkpoint, DEPOSITOR, 130, 1, 144, 12_000, 1_000)
+            .await
+            .unwrap();
+
+        assert_eq!(next.scanned_to, Some(1_000));
+        assert!(next.inflight.is_empty());
+    }
+}
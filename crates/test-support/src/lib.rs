@@ -0,0 +1,602 @@
+//! Test doubles shared by the workspace's unit tests.
+//!
+//! [`FakeChain`] is an offline Ethereum node: tests describe the chain (balances, code,
+//! blocks, logs, receipts and `eth_call` results) and get a [`Provider`] that answers from it.
+//! Unlike alloy's mock `Asserter`, answers don't depend on the order requests are made
+//! in, so a test states what the chain looks like rather than which calls the code
+//! under test makes. Tests that pin down an exact RPC sequence, e.g. signing and
+//! broadcasting, still use the `Asserter`.
+//!
+//! [`SpendLimitedSigner`] caps what tests submitting real transactions may spend.
+//!
+//! [`Provider`]: alloy_provider::Provider
+
+mod signer;
+
+use alloy_json_rpc::{
+    ErrorPayload, RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest,
+};
+use alloy_primitives::{hex, Address, Bytes, Selector, TxHash, U256, U64};
+use alloy_provider::RootProvider;
+use alloy_rpc_client::RpcClient;
+use alloy_rpc_types_eth::{
+    Block, BlockNumberOrTag, FeeHistory, Filter, Log, TransactionReceipt, TransactionRequest,
+};
+use alloy_sol_types::SolCall;
+use alloy_transport::{TransportError, TransportFut};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
+pub use signer::{max_transaction_cost, SpendBudget, SpendLimitExceeded, SpendLimitedSigner};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+/// Base fee of every block, 1 gwei.
+pub const BASE_FEE: u128 = 1_000_000_000;
+
+/// Priority fee paid in every block, 1 gwei.
+pub const PRIORITY_FEE: u128 = 1_000_000_000;
+
+/// Gas limit of every block.
+pub const BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+/// Outcome of an `eth_call`.
+#[derive(Debug, Clone)]
+enum CallResult {
+    Return(Bytes),
+    Revert(Bytes),
+}
+
+#[derive(Debug)]
+struct ChainState {
+    chain_id: u64,
+    /// Timestamps of the mined blocks, by number
+    blocks: BTreeMap<u64, u64>,
+    balances: HashMap<Address, U256>,
+    /// Balances from a block on, by address and block number
+    balance_history: HashMap<Address, BTreeMap<u64, U256>>,
+    /// Nonces from a block on, by address and block number
+    nonces: HashMap<Address, BTreeMap<u64, u64>>,
+    code: HashMap<Address, Bytes>,
+    logs: Vec<Log>,
+    receipts: HashMap<TxHash, TransactionReceipt>,
+    /// Known transactions, with the block they were mined in if any
+    transactions: HashMap<TxHash, Option<u64>>,
+    calls: HashMap<(Address, Selector), CallResult>,
+    /// Requests served, by method
+    requests: HashMap<String, usize>,
+}
+
+/// An offline chain whose state is set up by the test.
+///
+/// Clones share the same chain, so a test can keep changing it after handing out
+/// providers. A new chain has chain ID 1 and a genesis block at timestamp 0.
+#[derive(Debug, Clone)]
+pub struct FakeChain {
+    state: Arc<Mutex<ChainState>>,
+}
+
+impl Default for FakeChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeChain {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ChainState {
+                chain_id: 1,
+                blocks: BTreeMap::from([(0, 0)]),
+                balances: HashMap::new(),
+                balance_history: HashMap::new(),
+                nonces: HashMap::new(),
+                code: HashMap::new(),
+                logs: Vec::new(),
+                receipts: HashMap::new(),
+                transactions: HashMap::new(),
+                calls: HashMap::new(),
+                requests: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Answer `eth_chainId` with `chain_id`.
+    pub fn with_chain_id(self, chain_id: u64) -> Self {
+        self.lock().chain_id = chain_id;
+        self
+    }
+
+    /// Provider reading from this chain.
+    pub fn provider(&self) -> RootProvider {
+        RootProvider::new(RpcClient::new(FakeTransport(self.clone()), true))
+    }
+
+    /// Set the native balance of `address`. Unset balances are zero.
+    pub fn set_balance(&self, address: Address, balance: U256) {
+        self.lock().balances.insert(address, balance);
+    }
+
+    /// Set the native balance of `address` as of `block`, for queries at that block or
+    /// later ones until the next balance set this way. Takes precedence over
+    /// [`set_balance`](Self::set_balance) from `block` on.
+    pub fn set_balance_at(&self, address: Address, block: u64, balance: U256) {
+        self.lock()
+            .balance_history
+            .entry(address)
+            .or_default()
+            .insert(block, balance);
+    }
+
+    /// Set the nonce of `address` as of `block`, for queries at that block or later ones
+    /// until the next nonce set. Unset nonces are zero.
+    pub fn set_nonce_at(&self, address: Address, block: u64, nonce: u64) {
+        self.lock()
+            .nonces
+            .entry(address)
+            .or_default()
+            .insert(block, nonce);
+    }
+
+    /// Deploy `code` at `address`. Unset addresses have no code.
+    pub fn set_code(&self, address: Address, code: Bytes) {
+        self.lock().code.insert(address, code);
+    }
+
+    /// Mine a block at `timestamp` on top of the latest one, returning its number.
+    pub fn mine(&self, timestamp: u64) -> u64 {
+        let mut state = self.lock();
+        let number = state.latest_block() + 1;
+        state.blocks.insert(number, timestamp);
+        number
+    }
+
+    /// Emit `log`, returned by `eth_getLogs` queries whose filter matches it.
+    ///
+    /// The log's block number is checked against the filter's range, so it must be set.
+    pub fn add_log(&self, log: Log) {
+        self.lock().logs.push(log);
+    }
+
+    /// Return `receipt` for its transaction hash from `eth_getTransactionReceipt`.
+    pub fn set_receipt(&self, receipt: TransactionReceipt) {
+        self.lock()
+            .receipts
+            .insert(receipt.transaction_hash, receipt);
+    }
+
+    /// Return transaction `hash` from `eth_getTransactionByHash`, pending while
+    /// `block_number` is `None` and mined into it otherwise. Its other fields are
+    /// placeholders. Unknown transactions are returned as `null`, like evicted ones.
+    pub fn set_transaction(&self, hash: TxHash, block_number: Option<u64>) {
+        self.lock().transactions.insert(hash, block_number);
+    }
+
+    /// Forget transaction `hash`, as a node does when it drops it from its mempool.
+    pub fn drop_transaction(&self, hash: TxHash) {
+        self.lock().transactions.remove(&hash);
+    }
+
+    /// Answer `eth_call`s of `C` to `to` with `ret`, whatever the arguments.
+    pub fn set_call<C: SolCall>(&self, to: Address, ret: &C::Return) {
+        self.set_call_output(to, C::SELECTOR.into(), C::abi_encode_returns(ret).into());
+    }
+
+    /// Answer `eth_call`s to `to` whose calldata starts with `selector` with `output`.
+    pub fn set_call_output(&self, to: Address, selector: Selector, output: Bytes) {
+        self.lock()
+            .calls
+            .insert((to, selector), CallResult::Return(output));
+    }
+
+    /// Revert `eth_call`s of `C` to `to` with `data`, e.g. an encoded `Error(string)`.
+    pub fn set_call_revert<C: SolCall>(&self, to: Address, data: Bytes) {
+        self.lock()
+            .calls
+            .insert((to, C::SELECTOR.into()), CallResult::Revert(data));
+    }
+
+    /// Number of `method` requests served so far, failed ones included.
+    pub fn request_count(&self, method: &str) -> usize {
+        self.lock().requests.get(method).copied().unwrap_or(0)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChainState> {
+        self.state.lock().expect("fake chain lock poisoned")
+    }
+
+    fn respond(&self, request: &SerializedRequest) -> Response {
+        let method = request.method();
+        let params = request.params().map_or("[]", RawValue::get);
+
+        let mut state = self.lock();
+        *state.requests.entry(method.to_string()).or_default() += 1;
+        let payload = match state.handle(method, params) {
+            Ok(result) => ResponsePayload::Success(result),
+            Err(error) => ResponsePayload::Failure(error),
+        };
+
+        Response {
+            id: request.id().clone(),
+            payload,
+        }
+    }
+}
+
+type RpcResult = Result<Box<RawValue>, ErrorPayload>;
+
+impl ChainState {
+    fn latest_block(&self) -> u64 {
+        self.blocks
+            .last_key_value()
+            .map_or(0, |(number, _)| *number)
+    }
+
+    fn handle(&self, method: &str, params: &str) -> RpcResult {
+        match method {
+            "eth_chainId" => success(&U64::from(self.chain_id)),
+            "eth_blockNumber" => success(&U64::from(self.latest_block())),
+            "eth_getBalance" => {
+                let (address, block): (Address, BlockNumberOrTag) = parse(params)?;
+                let balance = at_block(self.balance_history.get(&address), self.number(block))
+                    .or_else(|| self.balances.get(&address).copied());
+                success(&balance.unwrap_or_default())
+            }
+            "eth_getTransactionCount" => {
+                let (address, block): (Address, BlockNumberOrTag) = parse(params)?;
+                let nonce = at_block(self.nonces.get(&address), self.number(block));
+                success(&U64::from(nonce.unwrap_or_default()))
+            }
+            "eth_getCode" => {
+                let (address, _): (Address, serde_json::Value) = parse(params)?;
+                success(&self.code.get(&address).cloned().unwrap_or_default())
+            }
+            "eth_getBlockByNumber" => {
+                let (number, _): (BlockNumberOrTag, bool) = parse(params)?;
+                success(&self.block(number))
+            }
+            "eth_getLogs" => {
+                let (filter,): (Filter,) = parse(params)?;
+                let logs: Vec<&Log> = self
+                    .logs
+                    .iter()
+                    .filter(|log| {
+                        log.block_number
+                            .is_some_and(|number| filter.matches_block_range(number))
+                            && filter.matches(&log.inner)
+                    })
+                    .collect();
+                success(&logs)
+            }
+            "eth_getTransactionReceipt" => {
+                let (hash,): (TxHash,) = parse(params)?;
+                success(&self.receipts.get(&hash))
+            }
+            "eth_getTransactionByHash" => {
+                let (hash,): (TxHash,) = parse(params)?;
+                success(
+                    &self
+                        .transactions
+                        .get(&hash)
+                        .map(|block| transaction(hash, *block)),
+                )
+            }
+            "eth_call" => {
+                let (tx, _): (TransactionRequest, serde_json::Value) = parse(params)?;
+                self.call(&tx)
+            }
+            "eth_feeHistory" => success(&FeeHistory {
+                base_fee_per_gas: vec![BASE_FEE; 2],
+                gas_used_ratio: vec![0.5],
+                reward: Some(vec![vec![PRIORITY_FEE]]),
+                oldest_block: self.latest_block(),
+                ..Default::default()
+            }),
+            "eth_gasPrice" => success(&U64::from(BASE_FEE + PRIORITY_FEE)),
+            _ => Err(ErrorPayload {
+                code: -32601,
+                message: format!("FakeChain does not implement {method}").into(),
+                data: None,
+            }),
+        }
+    }
+
+    fn number(&self, block: BlockNumberOrTag) -> u64 {
+        match block {
+            BlockNumberOrTag::Number(number) => number,
+            BlockNumberOrTag::Earliest => 0,
+            _ => self.latest_block(),
+        }
+    }
+
+    fn block(&self, number: BlockNumberOrTag) -> Option<Block> {
+        let number = self.number(number);
+        let timestamp = *self.blocks.get(&number)?;
+
+        let mut block: Block = Block::default();
+        block.header.inner.number = number;
+        block.header.inner.timestamp = timestamp;
+        block.header.inner.gas_limit = BLOCK_GAS_LIMIT;
+        block.header.inner.base_fee_per_gas = Some(BASE_FEE as u64);
+        Some(block)
+    }
+
+    fn call(&self, tx: &TransactionRequest) -> RpcResult {
+        let to = tx
+            .to
+            .and_then(|kind| kind.to().copied())
+            .unwrap_or_default();
+        let input = tx.input.input().cloned().unwrap_or_default();
+        let Some(selector) = input.get(..4).map(Selector::from_slice) else {
+            return Err(internal_error(format!(
+                "eth_call to {to} without a selector"
+            )));
+        };
+
+        match self.calls.get(&(to, selector)) {
+            Some(CallResult::Return(output)) => success(output),
+            Some(CallResult::Revert(data)) => Err(ErrorPayload {
+                code: 3,
+                message: "execution reverted".into(),
+                data: Some(raw(&format!("0x{}", hex::encode(data)))?),
+            }),
+            None => Err(internal_error(format!(
+                "no eth_call result set for {to} selector {selector}"
+            ))),
+        }
+    }
+}
+
+/// A signed legacy transaction `hash` as `eth_getTransactionByHash` returns it.
+fn transaction(hash: TxHash, block_number: Option<u64>) -> serde_json::Value {
+    serde_json::json!({
+        "type": "0x0",
+        "hash": hash,
+        "nonce": "0x0",
+        "gasPrice": "0x1",
+        "gas": "0x5208",
+        "to": Address::ZERO,
+        "value": "0x0",
+        "input": "0x",
+        "v": "0x1b",
+        "r": "0x1",
+        "s": "0x1",
+        "from": Address::ZERO,
+        "blockHash": block_number.map(|_| TxHash::ZERO),
+        "blockNumber": block_number.map(U64::from),
+        "transactionIndex": block_number.map(|_| U64::ZERO),
+    })
+}
+
+/// The latest value of `history` set at or before `block`.
+fn at_block<T: Copy>(history: Option<&BTreeMap<u64, T>>, block: u64) -> Option<T> {
+    history?
+        .range(..=block)
+        .next_back()
+        .map(|(_, value)| *value)
+}
+
+fn parse<T: DeserializeOwned>(params: &str) -> Result<T, ErrorPayload> {
+    serde_json::from_str(params).map_err(|e| ErrorPayload {
+        code: -32602,
+        message: format!("invalid params: {e}").into(),
+        data: None,
+    })
+}
+
+fn raw<T: Serialize>(value: &T) -> RpcResult {
+    serde_json::value::to_raw_value(value).map_err(|e| internal_error(e.to_string()))
+}
+
+fn success<T: Serialize>(value: &T) -> RpcResult {
+    raw(value)
+}
+
+fn internal_error(message: String) -> ErrorPayload {
+    ErrorPayload {
+        code: -32603,
+        message: message.into(),
+        data: None,
+    }
+}
+
+/// Transport answering requests from a [`FakeChain`].
+#[derive(Debug, Clone)]
+struct FakeTransport(FakeChain);
+
+impl tower::Service<RequestPacket> for FakeTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let chain = self.0.clone();
+        Box::pin(async move {
+            Ok(match request {
+                RequestPacket::Single(request) => ResponsePacket::Single(chain.respond(&request)),
+                RequestPacket::Batch(requests) => ResponsePacket::Batch(
+                    requests
+                        .iter()
+                        .map(|request| chain.respond(request))
+                        .collect(),
+                ),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{b256, LogData, B256};
+    use alloy_provider::Provider;
+    use alloy_sol_types::sol;
+
+    sol! {
+        function balanceOf(address owner) external view returns (uint256);
+    }
+
+    const TOKEN: Address = Address::repeat_byte(0x11);
+    const OWNER: Address = Address::repeat_byte(0x22);
+
+    fn log_at(block: u64, topic: B256) -> Log {
+        Log {
+            inner: alloy_primitives::Log {
+                address: TOKEN,
+                data: LogData::new_unchecked(vec![topic], Bytes::new()),
+            },
+            block_number: Some(block),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocks_and_balances() {
+        let chain = FakeChain::new().with_chain_id(130);
+        let provider = chain.provider();
+        chain.mine(1_700_000_000);
+        chain.set_balance(OWNER, U256::from(5));
+
+        assert_eq!(provider.get_chain_id().await.unwrap(), 130);
+        assert_eq!(provider.get_block_number().await.unwrap(), 1);
+        let latest = provider
+            .get_block_by_number(BlockNumberOrTag::Latest)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.header.timestamp, 1_700_000_000);
+        assert!(provider
+            .get_block_by_number(BlockNumberOrTag::Number(2))
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(provider.get_balance(OWNER).await.unwrap(), U256::from(5));
+        assert_eq!(provider.get_balance(TOKEN).await.unwrap(), U256::ZERO);
+
+        chain.set_code(TOKEN, Bytes::from_static(&[0x60, 0x80]));
+        assert_eq!(provider.get_code_at(TOKEN).await.unwrap()[..], [0x60, 0x80]);
+        assert!(provider.get_code_at(OWNER).await.unwrap().is_empty());
+
+        let fees = provider.estimate_eip1559_fees().await.unwrap();
+        assert_eq!(fees.max_fee_per_gas, 2 * BASE_FEE + PRIORITY_FEE);
+        assert_eq!(chain.request_count("eth_blockNumber"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_state_by_block() {
+        let chain = FakeChain::new();
+        let provider = chain.provider();
+        chain.set_balance(OWNER, U256::from(5));
+        chain.set_balance_at(OWNER, 3, U256::from(7));
+        chain.set_nonce_at(OWNER, 2, 4);
+        for timestamp in 1..=4 {
+            chain.mine(timestamp);
+        }
+
+        let balance_at = |block: u64| provider.get_balance(OWNER).number(block);
+        assert_eq!(balance_at(2).await.unwrap(), U256::from(5));
+        assert_eq!(balance_at(3).await.unwrap(), U256::from(7));
+        assert_eq!(provider.get_balance(OWNER).await.unwrap(), U256::from(7));
+
+        let nonce_at = |block: u64| provider.get_transaction_count(OWNER).number(block);
+        assert_eq!(nonce_at(1).await.unwrap(), 0);
+        assert_eq!(nonce_at(2).await.unwrap(), 4);
+        assert_eq!(provider.get_transaction_count(OWNER).await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_calls_by_selector() {
+        let chain = FakeChain::new();
+        let provider = chain.provider();
+        chain.set_call::<balanceOfCall>(TOKEN, &U256::from(42));
+
+        let call = balanceOfCall { owner: OWNER };
+        let tx = TransactionRequest::default()
+            .to(TOKEN)
+            .input(call.abi_encode().into());
+        let output = provider.call(tx.clone()).await.unwrap();
+        assert_eq!(
+            balanceOfCall::abi_decode_returns(&output).unwrap(),
+            U256::from(42)
+        );
+
+        // Other contracts have nothing set
+        assert!(provider.call(tx.clone().to(OWNER)).await.is_err());
+
+        chain.set_call_revert::<balanceOfCall>(TOKEN, Bytes::from_static(&[0xde, 0xad]));
+        let err = provider.call(tx).await.unwrap_err();
+        let revert = err.as_error_resp().and_then(|e| e.as_revert_data());
+        assert_eq!(revert, Some(Bytes::from_static(&[0xde, 0xad])));
+    }
+
+    #[tokio::test]
+    async fn test_logs_match_filter() {
+        let transfer = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+        let chain = FakeChain::new();
+        let provider = chain.provider();
+        chain.add_log(log_at(10, transfer));
+        chain.add_log(log_at(20, transfer));
+        chain.add_log(log_at(20, B256::ZERO));
+
+        let filter = Filter::new()
+            .address(TOKEN)
+            .event_signature(transfer)
+            .from_block(15)
+            .to_block(25);
+        let logs = provider.get_logs(&filter).await.unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].block_number, Some(20));
+    }
+
+    #[tokio::test]
+    async fn test_transactions_by_hash() {
+        let chain = FakeChain::new();
+        let provider = chain.provider();
+        let hash = TxHash::repeat_byte(0xaa);
+        assert!(provider
+            .get_transaction_by_hash(hash)
+            .await
+            .unwrap()
+            .is_none());
+
+        chain.set_transaction(hash, None);
+        let pending = provider
+            .get_transaction_by_hash(hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(pending.block_number, None);
+
+        chain.set_transaction(hash, Some(7));
+        let mined = provider
+            .get_transaction_by_hash(hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(mined.block_number, Some(7));
+
+        chain.drop_transaction(hash);
+        assert!(provider
+            .get_transaction_by_hash(hash)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method_fails() {
+        let chain = FakeChain::new();
+        let err = chain
+            .provider()
+            .get_storage_at(OWNER, U256::ZERO)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not implement"));
+    }
+}
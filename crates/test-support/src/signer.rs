@@ -0,0 +1,209 @@
+//! Spend limit for signers used by tests that submit real transactions.
+//!
+//! [`SpendLimitedSigner`] wraps any [`TransactionSigner`] and refuses transactions that
+//! could take a shared [`SpendBudget`] over its limit, so a test run against a funded
+//! key can't drain it.
+
+use alloy_dyn_abi::TypedData;
+use alloy_primitives::{utils::format_ether, Address, Bytes, Signature, U256};
+use alloy_rpc_types_eth::TransactionRequest;
+use async_trait::async_trait;
+use client::TransactionSigner;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+/// Signing a transaction could spend more than is left of a [`SpendBudget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error(
+    "Refusing to sign a transaction that may spend {} ETH, {} ETH left of the budget",
+    format_ether(*cost),
+    format_ether(*remaining)
+)]
+pub struct SpendLimitExceeded {
+    pub cost: U256,
+    pub remaining: U256,
+}
+
+/// Most a transaction can spend: its value plus its gas limit at its max fee per gas.
+///
+/// `None` if the gas limit or fee isn't filled in yet, in which case it is unbounded.
+pub fn max_transaction_cost(tx: &TransactionRequest) -> Option<U256> {
+    let gas_limit = tx.gas?;
+    let fee_per_gas = tx.max_fee_per_gas.or(tx.gas_price)?;
+    let gas_cost = U256::from(gas_limit).checked_mul(U256::from(fee_per_gas))?;
+    gas_cost.checked_add(tx.value.unwrap_or_default())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Spend {
+    limit: U256,
+    spent: U256,
+    signed: u64,
+}
+
+/// Wei that signers may spend in total, shared by every clone.
+#[derive(Debug, Clone)]
+pub struct SpendBudget {
+    spend: Arc<Mutex<Spend>>,
+}
+
+impl SpendBudget {
+    pub fn new(limit: U256) -> Self {
+        Self {
+            spend: Arc::new(Mutex::new(Spend {
+                limit,
+                spent: U256::ZERO,
+                signed: 0,
+            })),
+        }
+    }
+
+    pub fn limit(&self) -> U256 {
+        self.lock().limit
+    }
+
+    /// Most the transactions signed so far can have spent.
+    pub fn spent(&self) -> U256 {
+        self.lock().spent
+    }
+
+    pub fn remaining(&self) -> U256 {
+        let spend = self.lock();
+        spend.limit.saturating_sub(spend.spent)
+    }
+
+    /// Number of transactions signed against the budget.
+    pub fn signed(&self) -> u64 {
+        self.lock().signed
+    }
+
+    /// Take the most `tx` can spend from the budget, failing if not enough is left.
+    fn reserve(&self, tx: &TransactionRequest) -> eyre::Result<()> {
+        let mut spend = self.lock();
+        let remaining = spend.limit.saturating_sub(spend.spent);
+        let Some(cost) = max_transaction_cost(tx) else {
+            eyre::bail!("Refusing to sign a transaction without a gas limit and max fee");
+        };
+        if cost > remaining {
+            return Err(SpendLimitExceeded { cost, remaining }.into());
+        }
+        spend.spent += cost;
+        spend.signed += 1;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Spend> {
+        self.spend.lock().expect("spend budget lock poisoned")
+    }
+}
+
+/// Signer that refuses transactions which could take its [`SpendBudget`] over the limit.
+///
+/// Each signed transaction is charged the most it can spend, see
+/// [`max_transaction_cost`]; what it actually paid is usually less, so the budget errs
+/// on the safe side, for tests that submit transactions with a funded key.
+pub struct SpendLimitedSigner {
+    inner: Arc<dyn TransactionSigner>,
+    budget: SpendBudget,
+}
+
+impl SpendLimitedSigner {
+    pub fn new(inner: Arc<dyn TransactionSigner>, budget: SpendBudget) -> Self {
+        Self { inner, budget }
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for SpendLimitedSigner {
+    fn address(&self) -> Address {
+        self.inner.address()
+    }
+
+    fn chain_id(&self) -> Option<u64> {
+        self.inner.chain_id()
+    }
+
+    async fn sign_transaction(&self, tx: TransactionRequest) -> eyre::Result<Bytes> {
+        self.budget.reserve(&tx)?;
+        self.inner.sign_transaction(tx).await
+    }
+
+    async fn sign_typed_data(&self, payload: &TypedData) -> eyre::Result<Signature> {
+        self.inner.sign_typed_data(payload).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::FnSigner;
+
+    fn filled_tx(value: u64, gas_limit: u64, max_fee_per_gas: u128) -> TransactionRequest {
+        TransactionRequest::default()
+            .to(Address::ZERO)
+            .value(U256::from(value))
+            .gas_limit(gas_limit)
+            .max_fee_per_gas(max_fee_per_gas)
+            .max_priority_fee_per_gas(1)
+    }
+
+    #[tokio::test]
+    async fn test_spend_limited_signer_enforces_budget() {
+        let inner: Arc<dyn TransactionSigner> = Arc::new(FnSigner::new(
+            Address::repeat_byte(1),
+            Some(11155111),
+            Arc::new(|_tx| Box::pin(async { Ok(Bytes::from_static(&[0xab])) })),
+        ));
+        let budget = SpendBudget::new(U256::from(100_000));
+        let signer = SpendLimitedSigner::new(inner, budget.clone());
+        assert_eq!(signer.address(), Address::repeat_byte(1));
+        assert_eq!(signer.chain_id(), Some(11155111));
+
+        // 10_000 value + 21_000 gas at 2 wei
+        signer
+            .sign_transaction(filled_tx(10_000, 21_000, 2))
+            .await
+            .unwrap();
+        assert_eq!(budget.spent(), U256::from(52_000));
+        assert_eq!(budget.remaining(), U256::from(48_000));
+
+        // Would go over what is left
+        let err = signer
+            .sign_transaction(filled_tx(10_000, 21_000, 2))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<SpendLimitExceeded>(),
+            Some(&SpendLimitExceeded {
+                cost: U256::from(52_000),
+                remaining: U256::from(48_000),
+            })
+        );
+        // A refused transaction is not charged
+        assert_eq!(budget.signed(), 1);
+
+        // Unbounded without a gas limit
+        assert!(signer
+            .sign_transaction(TransactionRequest::default().value(U256::from(1)))
+            .await
+            .is_err());
+
+        signer
+            .sign_transaction(filled_tx(6_000, 21_000, 2))
+            .await
+            .unwrap();
+        assert_eq!(budget.remaining(), U256::ZERO);
+        assert_eq!(budget.signed(), 2);
+    }
+
+    #[test]
+    fn test_max_transaction_cost() {
+        assert_eq!(
+            max_transaction_cost(&filled_tx(5, 21_000, 3)),
+            Some(U256::from(63_005))
+        );
+        let legacy = TransactionRequest::default().gas_limit(21_000).gas_price(4);
+        assert_eq!(max_transaction_cost(&legacy), Some(U256::from(84_000)));
+        assert_eq!(max_transaction_cost(&TransactionRequest::default()), None);
+    }
+}
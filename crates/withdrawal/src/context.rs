@@ -0,0 +1,299 @@
+//! Per-chain context for withdrawals.
+//!
+//! Withdrawals from different rollups settle through different portals and message
+//! passers while sharing one L1 provider. A [`ChainContext`] bundles one rollup's L2
+//! provider and addresses, so state reads and proofs for that rollup can't pick up
+//! another's addresses. It also holds the [`GameCache`] its covering-game
+//! searches share.
+
+use crate::{
+    game::GameCache,
+    portal::PortalParamsCache,
+    proof::{
+        find_covering_game, generate_proof, generate_proofs_for_game, wait_for_covering_game,
+        CoveringGame, GameSelectionStrategy, ProveWithdrawalParams,
+    },
+    state::WithdrawalStateProvider,
+    types::WithdrawalHash,
+};
+use alloy_contract::private::Provider;
+use alloy_primitives::{Address, BlockNumber};
+use binding::opstack::WithdrawalTransaction;
+use client::retry::RetryBudget;
+use std::time::Duration;
+
+/// Addresses of one rollup's withdrawal contracts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainAddresses {
+    /// OptimismPortal2 on L1
+    pub portal: Address,
+    /// L2ToL1MessagePasser on L2
+    pub message_passer: Address,
+}
+
+/// Providers and addresses for proving and finalizing one rollup's withdrawals.
+#[derive(Debug, Clone)]
+pub struct ChainContext<P1, P2> {
+    /// Chain ID of the rollup, for labeling logs
+    chain_id: u64,
+    l1_provider: P1,
+    l2_provider: P2,
+    addresses: ChainAddresses,
+    /// Dispute games' L2 blocks, shared by clones
+    game_cache: GameCache,
+}
+
+impl<P1, P2> ChainContext<P1, P2>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    pub fn new(chain_id: u64, l1_provider: P1, l2_provider: P2, addresses: ChainAddresses) -> Self {
+        Self {
+            chain_id,
+            l1_provider,
+            l2_provider,
+            addresses,
+            game_cache: GameCache::default(),
+        }
+    }
+
+    /// Read dispute games' L2 blocks through `cache` rather than one of its own.
+    pub fn with_game_cache(mut self, cache: GameCache) -> Self {
+        self.game_cache = cache;
+        self
+    }
+
+    pub const fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    pub const fn addresses(&self) -> &ChainAddresses {
+        &self.addresses
+    }
+
+    pub const fn l1_provider(&self) -> &P1 {
+        &self.l1_provider
+    }
+
+    pub const fn l2_provider(&self) -> &P2 {
+        &self.l2_provider
+    }
+
+    pub const fn game_cache(&self) -> &GameCache {
+        &self.game_cache
+    }
+
+    /// State provider reading this rollup's portal and message passer.
+    pub fn state_provider(&self) -> WithdrawalStateProvider<P1, P2> {
+        WithdrawalStateProvider::new(
+            self.l1_provider.clone(),
+            self.l2_provider.clone(),
+            self.addresses.portal,
+            self.addresses.message_passer,
+        )
+    }
+
+    /// [`generate_proof`] against this rollup's contracts.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn generate_proof(
+        &self,
+        portal_params: &PortalParamsCache,
+        withdrawal_hash: WithdrawalHash,
+        withdrawal: WithdrawalTransaction,
+        block_number: BlockNumber,
+        min_game_count: u64,
+        allowed_game_types: &[u32],
+        strategy: GameSelectionStrategy,
+        retry_budget: &RetryBudget,
+    ) -> eyre::Result<ProveWithdrawalParams> {
+        generate_proof(
+            &self.l1_provider,
+            &self.l2_provider,
+            self.addresses.portal,
+            self.addresses.message_passer,
+            portal_params,
+            &self.game_cache,
+            withdrawal_hash,
+            withdrawal,
+            block_number,
+            min_game_count,
+            allowed_game_types,
+            strategy,
+            retry_budget,
+        )
+        .await
+    }
+
+    /// [`find_covering_game`] in the factory this rollup's portal points to.
+    pub async fn find_covering_game(
+        &self,
+        portal_params: &PortalParamsCache,
+        block_number: BlockNumber,
+        min_game_count: u64,
+        allowed_game_types: &[u32],
+        strategy: GameSelectionStrategy,
+    ) -> eyre::Result<CoveringGame> {
+        find_covering_game(
+            &self.l1_provider,
+            self.addresses.portal,
+            portal_params,
+            &self.game_cache,
+            block_number,
+            min_game_count,
+            allowed_game_types,
+            strategy,
+        )
+        .await
+    }
+
+    /// [`wait_for_covering_game`] in the factory this rollup's portal points to, for
+    /// games of its respected type.
+    pub async fn wait_for_covering_game(
+        &self,
+        portal_params: &PortalParamsCache,
+        block_number: BlockNumber,
+        from_block: u64,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> eyre::Result<Option<Address>> {
+        let params = portal_params
+            .get(&self.l1_provider, self.addresses.portal)
+            .await?;
+        wait_for_covering_game(
+            &self.l1_provider,
+            params.dispute_game_factory,
+            params.respected_game_type,
+            block_number,
+            from_block,
+            poll_interval,
+            timeout,
+        )
+        .await
+    }
+
+    /// [`generate_proofs_for_game`] from this rollup's message passer.
+    pub async fn generate_proofs_for_game(
+        &self,
+        game: &CoveringGame,
+        withdrawals: &[(WithdrawalHash, WithdrawalTransaction)],
+        retry_budget: &RetryBudget,
+    ) -> eyre::Result<Vec<ProveWithdrawalParams>> {
+        generate_proofs_for_game(
+            &self.l2_provider,
+            self.addresses.message_passer,
+            game,
+            withdrawals,
+            retry_budget,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proof::compute_storage_slot;
+    use alloy_primitives::{Bytes, B256, U256};
+    use alloy_provider::ProviderBuilder;
+    use alloy_transport::mock::Asserter;
+
+    fn addresses(byte: u8) -> ChainAddresses {
+        ChainAddresses {
+            portal: Address::repeat_byte(byte),
+            message_passer: Address::repeat_byte(byte + 1),
+        }
+    }
+
+    fn block(number: u64) -> alloy_rpc_types_eth::Block {
+        let mut block: alloy_rpc_types_eth::Block = Default::default();
+        block.header.hash = B256::repeat_byte(0xaa);
+        block.header.inner.number = number;
+        block
+    }
+
+    /// `eth_getProof` response of `account` with `slot` set.
+    fn proof_response(account: Address, slot: B256) -> serde_json::Value {
+        serde_json::json!({
+            "address": account,
+            "balance": "0x0",
+            "codeHash": B256::ZERO,
+            "nonce": "0x0",
+            "storageHash": B256::repeat_byte(0x55),
+            "accountProof": [],
+            "storageProof": [{ "key": slot, "value": "0x1", "proof": ["0xc0"] }],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_contexts_do_not_share_addresses() {
+        let l1 = ProviderBuilder::new().connect_mocked_client(Asserter::new());
+        let (l2_a, l2_b) = (Asserter::new(), Asserter::new());
+        let a = ChainContext::new(
+            130,
+            l1.clone(),
+            ProviderBuilder::new().connect_mocked_client(l2_a.clone()),
+            addresses(0x10),
+        );
+        let b = ChainContext::new(
+            10,
+            l1,
+            ProviderBuilder::new().connect_mocked_client(l2_b.clone()),
+            addresses(0x20),
+        );
+
+        assert_eq!(a.state_provider().portal_address(), addresses(0x10).portal);
+        assert_eq!(
+            b.state_provider().message_passer_address(),
+            addresses(0x20).message_passer
+        );
+
+        let game = CoveringGame {
+            factory: Address::repeat_byte(0x12),
+            index: U256::from(7),
+            proxy: Address::repeat_byte(0x77),
+            l2_block: 1000,
+        };
+        let hash = B256::repeat_byte(1);
+        let withdrawal = WithdrawalTransaction {
+            nonce: U256::from(1),
+            sender: Address::ZERO,
+            target: Address::ZERO,
+            value: U256::from(1000),
+            gasLimit: U256::from(100000),
+            data: Bytes::new(),
+        };
+        let slot = compute_storage_slot(hash);
+
+        // Each context proves from its own L2 and message passer
+        for (context, l2, passer) in [
+            (&a, &l2_a, addresses(0x10).message_passer),
+            (&b, &l2_b, addresses(0x20).message_passer),
+        ] {
+            l2.push_success(&block(1000));
+            l2.push_success(&proof_response(passer, slot));
+            l2.push_success(&block(1000));
+            let proofs = context
+                .generate_proofs_for_game(
+                    &game,
+                    &[(hash, withdrawal.clone())],
+                    &RetryBudget::unlimited(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(proofs.len(), 1);
+            assert!(l2.read_q().is_empty());
+        }
+
+        // A proof of the other rollup's message passer is refused
+        l2_b.push_success(&block(1000));
+        l2_b.push_success(&proof_response(addresses(0x10).message_passer, slot));
+        let err = b
+            .generate_proofs_for_game(&game, &[(hash, withdrawal)], &RetryBudget::unlimited())
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&addresses(0x20).message_passer.to_string()));
+    }
+}
@@ -0,0 +1,202 @@
+//! Withdrawals known to be finalized.
+//!
+//! Finalization is permanent, yet every scan re-reads `finalizedWithdrawals` for each
+//! withdrawal it finds. A [`FinalizedSet`] remembers the hashes observed as finalized so
+//! later scans skip them without touching the portal. [`verify_finalized`] re-checks a
+//! sample against L1 in case an entry was recorded in error.
+
+use crate::types::WithdrawalHash;
+use alloy_contract::private::Provider;
+use alloy_primitives::Address;
+use binding::opstack::IOptimismPortal2;
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+use tracing::{info, warn};
+
+/// Hashes of withdrawals observed as finalized.
+///
+/// Clones share the same set, so entries added during a scan are visible to whoever
+/// persists it. Whoever persists it learns whether it changed from [`Self::take_changed`].
+#[derive(Debug, Clone, Default)]
+pub struct FinalizedSet {
+    hashes: Arc<Mutex<BTreeSet<WithdrawalHash>>>,
+    changed: Arc<AtomicBool>,
+}
+
+impl FinalizedSet {
+    pub fn from_hashes(hashes: impl IntoIterator<Item = WithdrawalHash>) -> Self {
+        Self {
+            hashes: Arc::new(Mutex::new(hashes.into_iter().collect())),
+            changed: Arc::default(),
+        }
+    }
+
+    pub fn contains(&self, hash: WithdrawalHash) -> bool {
+        self.lock().contains(&hash)
+    }
+
+    /// Record `hash` as finalized. Returns whether it was new.
+    pub fn insert(&self, hash: WithdrawalHash) -> bool {
+        let inserted = self.lock().insert(hash);
+        self.mark_changed(inserted);
+        inserted
+    }
+
+    pub fn remove(&self, hash: WithdrawalHash) -> bool {
+        let removed = self.lock().remove(&hash);
+        self.mark_changed(removed);
+        removed
+    }
+
+    /// Whether an entry was added or removed since the last call.
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+
+    fn mark_changed(&self, changed: bool) {
+        if changed {
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Every hash in the set, in ascending order.
+    pub fn hashes(&self) -> Vec<WithdrawalHash> {
+        self.lock().iter().copied().collect()
+    }
+
+    /// Up to `count` hashes, starting at position `offset` (modulo the set size) and
+    /// wrapping around, so successive sweeps with increasing offsets cover the whole set.
+    pub fn sample(&self, count: usize, offset: usize) -> Vec<WithdrawalHash> {
+        let hashes = self.lock();
+        if hashes.is_empty() {
+            return vec![];
+        }
+        let start = offset % hashes.len();
+        hashes
+            .iter()
+            .skip(start)
+            .chain(hashes.iter().take(start))
+            .take(count)
+            .copied()
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeSet<WithdrawalHash>> {
+        self.hashes.lock().expect("finalized set lock poisoned")
+    }
+}
+
+/// Re-check `hashes` from `set` against the portal, removing any that are not finalized.
+///
+/// Returns the removed hashes. Those withdrawals are picked up again by the next scan.
+pub async fn verify_finalized<P: Provider>(
+    l1_provider: &P,
+    portal_address: Address,
+    set: &FinalizedSet,
+    hashes: &[WithdrawalHash],
+) -> eyre::Result<Vec<WithdrawalHash>> {
+    let portal = IOptimismPortal2::new(portal_address, l1_provider);
+
+    let mut removed = vec![];
+    for &hash in hashes {
+        if !portal.finalizedWithdrawals(hash).call().await? {
+            warn!(
+                withdrawal_hash = %hash,
+                "Withdrawal recorded as finalized is not finalized on L1, removing it"
+            );
+            set.remove(hash);
+            removed.push(hash);
+        }
+    }
+
+    info!(
+        checked = hashes.len(),
+        removed = removed.len(),
+        known_finalized = set.len(),
+        "Verified known finalized withdrawals"
+    );
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, B256};
+    use alloy_provider::ProviderBuilder;
+    use alloy_sol_types::SolValue;
+    use alloy_transport::mock::Asserter;
+
+    const PORTAL: Address = Address::repeat_byte(0x11);
+
+    #[test]
+    fn test_clones_share_entries() {
+        let set = FinalizedSet::default();
+        let clone = set.clone();
+
+        assert!(clone.insert(B256::repeat_byte(1)));
+        assert!(!set.insert(B256::repeat_byte(1)));
+        assert!(set.contains(B256::repeat_byte(1)));
+        assert!(!set.contains(B256::repeat_byte(2)));
+    }
+
+    #[test]
+    fn test_take_changed() {
+        let set = FinalizedSet::from_hashes([B256::repeat_byte(1)]);
+        assert!(!set.take_changed());
+
+        // Replacing one entry with another keeps the size but is still a change
+        let clone = set.clone();
+        set.remove(B256::repeat_byte(1));
+        clone.insert(B256::repeat_byte(2));
+        assert!(set.take_changed());
+        assert!(!set.take_changed());
+
+        set.insert(B256::repeat_byte(2));
+        set.remove(B256::repeat_byte(3));
+        assert!(!set.take_changed());
+    }
+
+    #[test]
+    fn test_sample_wraps_around() {
+        let set = FinalizedSet::from_hashes((1..=5).map(B256::repeat_byte));
+
+        assert_eq!(
+            set.sample(3, 4),
+            [5, 1, 2]
+                .into_iter()
+                .map(B256::repeat_byte)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(set.sample(10, 0).len(), 5);
+        assert!(FinalizedSet::default().sample(3, 1).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_verify_finalized_removes_unfinalized() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let set = FinalizedSet::from_hashes([B256::repeat_byte(1), B256::repeat_byte(2)]);
+
+        asserter.push_success(&Bytes::from(true.abi_encode()));
+        asserter.push_success(&Bytes::from(false.abi_encode()));
+        let removed = verify_finalized(&provider, PORTAL, &set, &set.hashes())
+            .await
+            .unwrap();
+
+        assert_eq!(removed, [B256::repeat_byte(2)]);
+        assert_eq!(set.hashes(), [B256::repeat_byte(1)]);
+    }
+}
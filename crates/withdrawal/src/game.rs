@@ -0,0 +1,569 @@
+//! Dispute game reads used when searching for a game to prove against.
+//!
+//! Different game implementations expose slightly different ABIs. [`DisputeGameReader`]
+//! abstracts the three reads the prover needs so the right one can be picked from the
+//! portal's respected game type, or supplied by the operator for custom games.
+//! [`GameCache`] wraps any of them so games read by one covering-game search aren't
+//! read again by the next.
+
+use alloy_contract::private::Provider;
+use alloy_primitives::{Address, B256, U256};
+use binding::opstack::{
+    IDisputeGame, IDisputeGameFactory, IFaultDisputeGame, IPermissionedDisputeGame,
+};
+use eyre::Result;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::OnceCell;
+
+/// Permissionless Cannon fault dispute game.
+pub const GAME_TYPE_CANNON: u32 = 0;
+/// Cannon fault dispute game restricted to an allowlisted proposer and challenger.
+pub const GAME_TYPE_PERMISSIONED_CANNON: u32 = 1;
+
+/// A dispute game's `GameId`, as packed by the DisputeGameFactory into the `metadata`
+/// of its search results.
+///
+/// Layout, most significant bits first: game type (32 bits) | creation timestamp
+/// (64 bits) | proxy address (160 bits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GameId(B256);
+
+impl GameId {
+    pub const fn from_metadata(metadata: B256) -> Self {
+        Self(metadata)
+    }
+
+    /// Type of the game.
+    pub fn game_type(&self) -> u32 {
+        u32::from_be_bytes(self.0[0..4].try_into().expect("4 byte slice"))
+    }
+
+    /// Timestamp the game was created at.
+    pub fn timestamp(&self) -> u64 {
+        u64::from_be_bytes(self.0[4..12].try_into().expect("8 byte slice"))
+    }
+
+    /// Address of the game's proxy contract.
+    pub fn proxy_address(&self) -> Address {
+        Address::from_slice(&self.0[12..32])
+    }
+}
+
+/// Human-readable name of a game status as returned by [`DisputeGameReader::status`].
+pub const fn status_name(status: u8) -> &'static str {
+    match status {
+        0 => "in_progress",
+        1 => "challenger_wins",
+        2 => "defender_wins",
+        _ => "unknown",
+    }
+}
+
+/// Reads a dispute game's state.
+pub trait DisputeGameReader: Send + Sync {
+    /// L2 block number the game's root claim commits to.
+    fn l2_block_number(&self, game: Address) -> impl Future<Output = Result<u64>> + Send;
+
+    /// Game status (0 = in progress, 1 = challenger wins, 2 = defender wins).
+    fn status(&self, game: Address) -> impl Future<Output = Result<u8>> + Send;
+
+    /// Output root claimed by the game.
+    fn root_claim(&self, game: Address) -> impl Future<Output = Result<B256>> + Send;
+}
+
+/// Reader for games implementing `IFaultDisputeGame`.
+#[derive(Debug, Clone)]
+pub struct FaultGameReader<P> {
+    provider: P,
+}
+
+impl<P> FaultGameReader<P> {
+    pub const fn new(provider: P) -> Self {
+        Self { provider }
+    }
+}
+
+impl<P: Provider + Clone> DisputeGameReader for FaultGameReader<P> {
+    async fn l2_block_number(&self, game: Address) -> Result<u64> {
+        let contract = IFaultDisputeGame::new(game, &self.provider);
+        Ok(contract.l2BlockNumber().call().await?.to::<u64>())
+    }
+
+    async fn status(&self, game: Address) -> Result<u8> {
+        let contract = IFaultDisputeGame::new(game, &self.provider);
+        Ok(contract.status().call().await?)
+    }
+
+    async fn root_claim(&self, game: Address) -> Result<B256> {
+        let contract = IFaultDisputeGame::new(game, &self.provider);
+        Ok(contract.rootClaim().call().await?)
+    }
+}
+
+/// Reader for permissioned games.
+///
+/// Newer permissioned game implementations expose the L2 block as `l2SequenceNumber()`;
+/// older ones only have `l2BlockNumber()`, which is used as a fallback when the first
+/// call reverts or returns data that doesn't decode. Transport errors are returned as is.
+#[derive(Debug, Clone)]
+pub struct PermissionedGameReader<P> {
+    provider: P,
+}
+
+impl<P> PermissionedGameReader<P> {
+    pub const fn new(provider: P) -> Self {
+        Self { provider }
+    }
+}
+
+impl<P: Provider + Clone> DisputeGameReader for PermissionedGameReader<P> {
+    async fn l2_block_number(&self, game: Address) -> Result<u64> {
+        let contract = IPermissionedDisputeGame::new(game, &self.provider);
+        match contract.l2SequenceNumber().call().await {
+            Ok(sequence_number) => return Ok(sequence_number.to::<u64>()),
+            Err(e) if !is_missing_function(&e) => return Err(e.into()),
+            Err(_) => {}
+        }
+        FaultGameReader::new(self.provider.clone())
+            .l2_block_number(game)
+            .await
+    }
+
+    async fn status(&self, game: Address) -> Result<u8> {
+        let contract = IPermissionedDisputeGame::new(game, &self.provider);
+        Ok(contract.status().call().await?)
+    }
+
+    async fn root_claim(&self, game: Address) -> Result<B256> {
+        let contract = IPermissionedDisputeGame::new(game, &self.provider);
+        Ok(contract.rootClaim().call().await?)
+    }
+}
+
+/// Whether a failed call means the contract lacks the function: it reverted or returned
+/// data that doesn't decode, rather than failing to reach the node.
+fn is_missing_function(e: &alloy_contract::Error) -> bool {
+    match e {
+        alloy_contract::Error::ZeroData(..) | alloy_contract::Error::AbiError(_) => true,
+        alloy_contract::Error::TransportError(e) => e
+            .as_error_resp()
+            .is_some_and(|resp| resp.code == 3 || resp.message.contains("revert")),
+        _ => false,
+    }
+}
+
+/// Reader selected from the portal's respected game type.
+#[derive(Debug, Clone)]
+pub enum GameReader<P> {
+    Fault(FaultGameReader<P>),
+    Permissioned(PermissionedGameReader<P>),
+}
+
+impl<P> GameReader<P> {
+    /// Pick the reader for `game_type`. Unknown types use the fault game ABI.
+    pub const fn for_game_type(game_type: u32, provider: P) -> Self {
+        match game_type {
+            GAME_TYPE_PERMISSIONED_CANNON => {
+                Self::Permissioned(PermissionedGameReader::new(provider))
+            }
+            _ => Self::Fault(FaultGameReader::new(provider)),
+        }
+    }
+}
+
+impl<P: Provider + Clone> DisputeGameReader for GameReader<P> {
+    async fn l2_block_number(&self, game: Address) -> Result<u64> {
+        match self {
+            Self::Fault(reader) => reader.l2_block_number(game).await,
+            Self::Permissioned(reader) => reader.l2_block_number(game).await,
+        }
+    }
+
+    async fn status(&self, game: Address) -> Result<u8> {
+        match self {
+            Self::Fault(reader) => reader.status(game).await,
+            Self::Permissioned(reader) => reader.status(game).await,
+        }
+    }
+
+    async fn root_claim(&self, game: Address) -> Result<B256> {
+        match self {
+            Self::Fault(reader) => reader.root_claim(game).await,
+            Self::Permissioned(reader) => reader.root_claim(game).await,
+        }
+    }
+}
+
+/// L2 block numbers of dispute games, shared by covering-game searches.
+///
+/// A game's L2 block never changes, and withdrawals from nearby L2 blocks binary search
+/// through the same games, so each game only needs reading from L1 once. A search
+/// reading a game another is already reading waits for that read instead of repeating
+/// it. Clones share the same cache.
+#[derive(Debug, Clone, Default)]
+pub struct GameCache {
+    state: Arc<Mutex<GameCacheState>>,
+}
+
+#[derive(Debug, Default)]
+struct GameCacheState {
+    l2_blocks: HashMap<Address, Arc<OnceCell<u64>>>,
+    /// L2 block numbers requested
+    reads: u64,
+    /// Requests that had to read the game from L1
+    misses: u64,
+}
+
+/// How often a [`GameCache`] answered from memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GameCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl GameCacheStats {
+    /// Share of reads answered from the cache, `None` before any read.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let reads = self.hits + self.misses;
+        (reads > 0).then(|| self.hits as f64 / reads as f64)
+    }
+}
+
+impl GameCache {
+    /// `reader` with its L2 block numbers read through this cache.
+    pub fn reader<G>(&self, reader: G) -> CachedGameReader<G> {
+        CachedGameReader {
+            reader,
+            cache: self.clone(),
+        }
+    }
+
+    /// Hits and misses so far.
+    pub fn stats(&self) -> GameCacheStats {
+        let state = self.lock();
+        GameCacheStats {
+            hits: state.reads - state.misses,
+            misses: state.misses,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, GameCacheState> {
+        self.state.lock().expect("game cache lock poisoned")
+    }
+
+    /// L2 block number of `game`, read with `read` unless cached or being read.
+    ///
+    /// A failed read isn't cached; the next request for the game reads it again.
+    async fn l2_block_number<F>(&self, game: Address, read: F) -> Result<u64>
+    where
+        F: Future<Output = Result<u64>>,
+    {
+        let cell = {
+            let mut state = self.lock();
+            state.reads += 1;
+            state.l2_blocks.entry(game).or_default().clone()
+        };
+        let l2_block = cell
+            .get_or_try_init(|| async {
+                self.lock().misses += 1;
+                read.await
+            })
+            .await?;
+        Ok(*l2_block)
+    }
+}
+
+/// A [`DisputeGameReader`] whose L2 block numbers go through a [`GameCache`].
+///
+/// Statuses change as games resolve, so they are always read from `reader`.
+#[derive(Debug, Clone)]
+pub struct CachedGameReader<G> {
+    reader: G,
+    cache: GameCache,
+}
+
+impl<G: DisputeGameReader> DisputeGameReader for CachedGameReader<G> {
+    async fn l2_block_number(&self, game: Address) -> Result<u64> {
+        self.cache
+            .l2_block_number(game, self.reader.l2_block_number(game))
+            .await
+    }
+
+    async fn status(&self, game: Address) -> Result<u8> {
+        self.reader.status(game).await
+    }
+
+    async fn root_claim(&self, game: Address) -> Result<B256> {
+        self.reader.root_claim(game).await
+    }
+}
+
+/// Factory index of the game at `proxy`, or `None` if `factory` didn't create it.
+///
+/// Proofs record only the game proxy, but the portal is proven against by index. The
+/// factory appends games as they are created, so `gameAtIndex` timestamps never
+/// decrease: the index is found by a binary search for the game's `createdAt`, then a
+/// scan of the games created in the same second.
+pub async fn find_game_index<P: Provider>(
+    provider: &P,
+    factory: Address,
+    proxy: Address,
+) -> Result<Option<U256>> {
+    let created_at = IDisputeGame::new(proxy, provider)
+        .createdAt()
+        .call()
+        .await?;
+    let factory = IDisputeGameFactory::new(factory, provider);
+    let count: U256 = factory.gameCount().call().await?;
+
+    search_game_index(count.saturating_to(), created_at, proxy, |index| {
+        let call = factory.gameAtIndex(U256::from(index));
+        async move {
+            let game = call.call().await?;
+            Ok((game.timestamp_, game.proxy_))
+        }
+    })
+    .await
+}
+
+/// Search `count` games, read through `game_at` as `(timestamp, proxy)`, for `proxy`
+/// created at `created_at`.
+async fn search_game_index<F, Fut>(
+    count: u64,
+    created_at: u64,
+    proxy: Address,
+    mut game_at: F,
+) -> Result<Option<U256>>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<(u64, Address)>>,
+{
+    // First game created at or after `created_at`
+    let (mut low, mut high) = (0, count);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if game_at(mid).await?.0 < created_at {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    for index in low..count {
+        let (timestamp, game) = game_at(index).await?;
+        if timestamp != created_at {
+            break;
+        }
+        if game == proxy {
+            return Ok(Some(U256::from(index)));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    #[test]
+    fn test_for_game_type() {
+        assert!(matches!(
+            GameReader::for_game_type(GAME_TYPE_CANNON, ()),
+            GameReader::Fault(_)
+        ));
+        assert!(matches!(
+            GameReader::for_game_type(GAME_TYPE_PERMISSIONED_CANNON, ()),
+            GameReader::Permissioned(_)
+        ));
+        assert!(matches!(
+            GameReader::for_game_type(42, ()),
+            GameReader::Fault(_)
+        ));
+    }
+
+    #[test]
+    fn test_game_id_from_metadata() {
+        // Permissioned game created at 1_732_878_784 (0x6749a1c0)
+        let id = GameId::from_metadata(b256!(
+            "00000001000000006749a1c0c25e6a1b2f3e1b8a6f2d1e0b8c7a4f3e2d1c0b9a"
+        ));
+
+        assert_eq!(id.game_type(), GAME_TYPE_PERMISSIONED_CANNON);
+        assert_eq!(id.timestamp(), 1_732_878_784);
+        assert_eq!(
+            id.proxy_address(),
+            address!("c25e6a1b2f3e1b8a6f2d1e0b8c7a4f3e2d1c0b9a")
+        );
+    }
+
+    #[test]
+    fn test_game_id_matches_solidity_packing() {
+        // LibGameId.pack: (type << 224) | (timestamp << 160) | address
+        let game_type = 0xdead_beef_u32;
+        let timestamp = 0x0102_0304_0506_0708_u64;
+        let proxy = Address::repeat_byte(0xab);
+        let packed: U256 = (U256::from(game_type) << 224)
+            | (U256::from(timestamp) << 160)
+            | U256::from_be_slice(proxy.as_slice());
+
+        let id = GameId::from_metadata(B256::from(packed));
+
+        assert_eq!(id.game_type(), game_type);
+        assert_eq!(id.timestamp(), timestamp);
+        assert_eq!(id.proxy_address(), proxy);
+
+        // A bare address, as in older test fixtures, is type 0 at time 0
+        let bare = GameId::from_metadata(proxy.into_word());
+        assert_eq!((bare.game_type(), bare.timestamp()), (0, 0));
+        assert_eq!(bare.proxy_address(), proxy);
+    }
+
+    #[tokio::test]
+    async fn test_permissioned_l2_block_number_fallback() {
+        use alloy_json_rpc::ErrorPayload;
+        use alloy_primitives::Bytes;
+        use alloy_provider::ProviderBuilder;
+        use alloy_sol_types::SolValue;
+        use alloy_transport::mock::Asserter;
+
+        let asserter = Asserter::new();
+        let reader = PermissionedGameReader::new(
+            ProviderBuilder::new().connect_mocked_client(asserter.clone()),
+        );
+        let game = Address::repeat_byte(1);
+        let block = Bytes::from(U256::from(1234).abi_encode());
+
+        // No l2SequenceNumber(): a revert or empty return falls back to l2BlockNumber()
+        asserter.push_failure(ErrorPayload {
+            code: 3,
+            message: "execution reverted".into(),
+            data: None,
+        });
+        asserter.push_success(&block);
+        assert_eq!(reader.l2_block_number(game).await.unwrap(), 1234);
+        asserter.push_success(&Bytes::new());
+        asserter.push_success(&block);
+        assert_eq!(reader.l2_block_number(game).await.unwrap(), 1234);
+
+        // Any other error is returned without trying the other ABI
+        asserter.push_failure(ErrorPayload {
+            code: 429,
+            message: "too many requests".into(),
+            data: None,
+        });
+        asserter.push_success(&block);
+        assert!(reader.l2_block_number(game).await.is_err());
+        assert_eq!(asserter.read_q().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_game_index() {
+        let games: Vec<(u64, Address)> = vec![
+            (100, Address::with_last_byte(1)),
+            (200, Address::with_last_byte(2)),
+            (200, Address::with_last_byte(3)),
+            (200, Address::with_last_byte(4)),
+            (300, Address::with_last_byte(5)),
+        ];
+        let search = |created_at, proxy| {
+            search_game_index(games.len() as u64, created_at, proxy, |index| {
+                let game = games[index as usize];
+                async move { Ok(game) }
+            })
+        };
+
+        for (index, (created_at, proxy)) in games.iter().enumerate() {
+            assert_eq!(
+                search(*created_at, *proxy).await.unwrap(),
+                Some(U256::from(index))
+            );
+        }
+        // Created in a second the factory has games for, but not one of them
+        assert_eq!(search(200, Address::with_last_byte(9)).await.unwrap(), None);
+        // Newer than every game, e.g. a game of another factory
+        assert_eq!(search(400, Address::with_last_byte(9)).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_find_game_index() {
+        use binding::opstack::IDisputeGameFactory::gameAtIndexReturn;
+        use test_support::FakeChain;
+
+        let l1 = FakeChain::new();
+        let factory = Address::with_last_byte(0xfa);
+        let proxy = Address::with_last_byte(0x01);
+        l1.set_call::<IDisputeGame::createdAtCall>(proxy, &1_000);
+        l1.set_call::<IDisputeGameFactory::gameCountCall>(factory, &U256::from(1));
+        l1.set_call::<IDisputeGameFactory::gameAtIndexCall>(
+            factory,
+            &gameAtIndexReturn {
+                gameType_: 0,
+                timestamp_: 1_000,
+                proxy_: proxy,
+            },
+        );
+
+        let index = find_game_index(&l1.provider(), factory, proxy)
+            .await
+            .unwrap();
+        assert_eq!(index, Some(U256::ZERO));
+    }
+
+    #[test]
+    fn test_status_name() {
+        assert_eq!(status_name(0), "in_progress");
+        assert_eq!(status_name(1), "challenger_wins");
+        assert_eq!(status_name(2), "defender_wins");
+        assert_eq!(status_name(7), "unknown");
+    }
+
+    /// Reads game L2 blocks from their address's first byte, counting reads and
+    /// failing for games starting with zero.
+    #[derive(Default)]
+    struct CountingReader(std::sync::atomic::AtomicUsize);
+
+    impl DisputeGameReader for CountingReader {
+        async fn l2_block_number(&self, game: Address) -> Result<u64> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            match game[0] {
+                0 => Err(eyre::eyre!("game {game} unavailable")),
+                byte => Ok(u64::from(byte) * 100),
+            }
+        }
+
+        async fn status(&self, _game: Address) -> Result<u8> {
+            Ok(0)
+        }
+
+        async fn root_claim(&self, _game: Address) -> Result<B256> {
+            Ok(B256::ZERO)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_game_cache_reads_each_game_once() {
+        let cache = GameCache::default();
+        let reader = cache.reader(CountingReader::default());
+        let game = Address::repeat_byte(3);
+
+        // Concurrent reads of the same game wait for the one in flight
+        let (a, b) = tokio::join!(reader.l2_block_number(game), reader.l2_block_number(game));
+        assert_eq!((a.unwrap(), b.unwrap()), (300, 300));
+        assert_eq!(reader.l2_block_number(game).await.unwrap(), 300);
+        assert_eq!(reader.reader.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(cache.stats(), GameCacheStats { hits: 2, misses: 1 });
+
+        // Failures aren't cached
+        let broken = Address::ZERO;
+        assert!(reader.l2_block_number(broken).await.is_err());
+        assert!(reader.l2_block_number(broken).await.is_err());
+        assert_eq!(reader.reader.0.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert_eq!(GameCacheStats::default().hit_rate(), None);
+    }
+}
@@ -0,0 +1,36 @@
+//! Gas limit floor for L2→L1 withdrawals.
+//!
+//! The OP Stack meters a message's gas limit against its payload: a gas limit below
+//! [`minimum_gas_limit`] for the data size is rejected, which surfaces as an opaque
+//! revert when the gas limit is fixed but the payload grows. Gas limits are derived
+//! from the floor instead.
+
+use alloy_primitives::U256;
+
+/// Intrinsic gas every message pays regardless of its payload.
+pub const BASE_GAS: u64 = 21_000;
+
+/// Gas charged per byte of payload.
+pub const GAS_PER_DATA_BYTE: u64 = 40;
+
+/// Smallest gas limit accepted for a payload of `data_len` bytes.
+///
+/// Mirrors `OptimismPortal.minimumGasLimit`: `data_len * 40 + 21000`.
+pub fn minimum_gas_limit(data_len: usize) -> U256 {
+    U256::from(data_len) * U256::from(GAS_PER_DATA_BYTE) + U256::from(BASE_GAS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimum_gas_limit_spec_values() {
+        assert_eq!(minimum_gas_limit(0), U256::from(21_000));
+        assert_eq!(minimum_gas_limit(1), U256::from(21_040));
+        assert_eq!(minimum_gas_limit(32), U256::from(22_280));
+        assert_eq!(minimum_gas_limit(1_000), U256::from(61_000));
+        // The portal's calldata cap
+        assert_eq!(minimum_gas_limit(120_000), U256::from(4_821_000));
+    }
+}
@@ -2,6 +2,17 @@ use crate::types::WithdrawalHash;
 use alloy_primitives::keccak256;
 use alloy_sol_types::SolValue;
 use binding::opstack::WithdrawalTransaction;
+use thiserror::Error;
+
+/// A withdrawal hash that doesn't match the transaction it was supplied with.
+#[derive(Debug, Clone, Copy, Error)]
+#[error("Withdrawal hash mismatch: expected {expected}, computed {computed}")]
+pub struct WithdrawalHashMismatch {
+    /// Hash supplied alongside the transaction
+    pub expected: WithdrawalHash,
+    /// Hash of the transaction itself
+    pub computed: WithdrawalHash,
+}
 
 pub fn compute_withdrawal_hash(tx: &WithdrawalTransaction) -> WithdrawalHash {
     // Solidity's Hashing.hashWithdrawal uses:
@@ -20,6 +31,21 @@ pub fn compute_withdrawal_hash(tx: &WithdrawalTransaction) -> WithdrawalHash {
     keccak256(encoded)
 }
 
+/// Check that `expected` is the hash of `tx`.
+///
+/// Catches transposed fields (e.g. sender and target) when a transaction is rebuilt by
+/// hand, before the mismatch surfaces as an on-chain revert.
+pub fn verify_withdrawal_hash(
+    tx: &WithdrawalTransaction,
+    expected: WithdrawalHash,
+) -> Result<(), WithdrawalHashMismatch> {
+    let computed = compute_withdrawal_hash(tx);
+    if computed != expected {
+        return Err(WithdrawalHashMismatch { expected, computed });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,6 +105,58 @@ mod tests {
         assert_eq!(hash, expected, "Hash mismatch!");
     }
 
+    #[test]
+    fn test_from_message_passed_preserves_hash() {
+        use binding::opstack::IL2ToL1MessagePasser::MessagePassed;
+
+        // Same Unichain Mainnet withdrawal as above, as emitted on L2
+        let event = MessagePassed {
+            nonce: U256::from_be_bytes(hex!(
+                "0001000000000000000000000000000000000000000000000000000000000818"
+            )),
+            sender: Address::from_slice(&hex!("000040D6c85A13a1AA74565FDe87e499dC023C6f")),
+            target: Address::from_slice(&hex!("B03eEF386A61b5b462051636001485FFfdD3d843")),
+            value: U256::ZERO,
+            gasLimit: U256::from(200_000),
+            data: Bytes::from(hex!(
+                "095ea7b3000000000000000000000000000040d6c85a13a1aa74565fde87e499dc023c6fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+            )),
+            withdrawalHash: B256::from_slice(&hex!(
+                "49c43b60ec99e99046b54aec4c90419ff194300e567de63423c3b974ae46bd28"
+            )),
+        };
+
+        let tx = WithdrawalTransaction::from(&event);
+
+        assert_eq!(compute_withdrawal_hash(&tx), event.withdrawalHash);
+        verify_withdrawal_hash(&tx, event.withdrawalHash).unwrap();
+        assert_eq!(tx, WithdrawalTransaction::from(&event));
+    }
+
+    #[test]
+    fn test_verify_withdrawal_hash_catches_transposed_fields() {
+        let tx = WithdrawalTransaction {
+            nonce: U256::from(1),
+            sender: Address::from([0x01; 20]),
+            target: Address::from([0x02; 20]),
+            value: U256::from(1_000_000),
+            gasLimit: U256::from(100_000),
+            data: Bytes::new(),
+        };
+        let hash = compute_withdrawal_hash(&tx);
+
+        let swapped = WithdrawalTransaction {
+            sender: tx.target,
+            target: tx.sender,
+            ..tx.clone()
+        };
+        assert_ne!(swapped, tx);
+
+        let err = verify_withdrawal_hash(&swapped, hash).unwrap_err();
+        assert_eq!(err.expected, hash);
+        assert_eq!(err.computed, compute_withdrawal_hash(&swapped));
+    }
+
     #[test]
     fn test_withdrawal_hash_collision_resistance() {
         // Test that similar but different transactions produce different hashes
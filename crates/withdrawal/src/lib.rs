@@ -1,4 +1,12 @@
+pub mod context;
+pub mod finalized;
+pub mod game;
+pub mod gas;
 pub mod hash;
+pub mod portal;
 pub mod proof;
+pub mod rpc;
 pub mod state;
+pub mod submitter;
 pub mod types;
+pub mod wait;
@@ -0,0 +1,200 @@
+//! Cached OptimismPortal2 parameters.
+//!
+//! The respected game type, the dispute game factory and the proof maturity delay only
+//! change through governance, yet proving and finalizing read them for every withdrawal.
+//! [`PortalParamsCache`] keeps them for a configurable time so a cycle over many
+//! withdrawals reads them once.
+
+use alloy_contract::private::Provider;
+use alloy_primitives::{Address, U256};
+use binding::opstack::IOptimismPortal2;
+use eyre::Result;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// Portal parameters that rarely change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortalParams {
+    /// Game type the portal accepts proofs against
+    pub respected_game_type: u32,
+    /// DisputeGameFactory the portal looks a proof's game index up in
+    pub dispute_game_factory: Address,
+    /// Seconds between proving and finalizing a withdrawal
+    pub proof_maturity_delay: u64,
+}
+
+impl PortalParams {
+    /// Read the parameters of the portal at `portal_address`.
+    pub async fn fetch<P: Provider>(l1_provider: &P, portal_address: Address) -> Result<Self> {
+        let portal = IOptimismPortal2::new(portal_address, l1_provider);
+        let respected_game_type = portal.respectedGameType().call().await?;
+        let dispute_game_factory = portal.disputeGameFactory().call().await?;
+        let delay: U256 = portal.proofMaturityDelaySeconds().call().await?;
+
+        Ok(Self {
+            respected_game_type,
+            dispute_game_factory,
+            proof_maturity_delay: delay.try_into().unwrap_or(u64::MAX),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Cached {
+    portal: Address,
+    params: PortalParams,
+    fetched_at: Instant,
+}
+
+/// [`PortalParams`] shared across actions and refreshed once they are older than the TTL.
+///
+/// Clones share the same cache. The default has a zero TTL, i.e. reads the portal every
+/// time, which is what a single action run on its own wants.
+#[derive(Debug, Clone, Default)]
+pub struct PortalParamsCache {
+    ttl: Duration,
+    cached: Arc<Mutex<Option<Cached>>>,
+}
+
+impl PortalParamsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cached: Arc::default(),
+        }
+    }
+
+    /// Parameters of the portal at `portal_address`, read from L1 if not cached or stale.
+    pub async fn get<P: Provider>(
+        &self,
+        l1_provider: &P,
+        portal_address: Address,
+    ) -> Result<PortalParams> {
+        self.get_at(l1_provider, portal_address, Instant::now())
+            .await
+    }
+
+    async fn get_at<P: Provider>(
+        &self,
+        l1_provider: &P,
+        portal_address: Address,
+        now: Instant,
+    ) -> Result<PortalParams> {
+        if let Some(params) = self.fresh(portal_address, now) {
+            return Ok(params);
+        }
+
+        let params = PortalParams::fetch(l1_provider, portal_address).await?;
+        debug!(
+            portal = %portal_address,
+            respected_game_type = params.respected_game_type,
+            dispute_game_factory = %params.dispute_game_factory,
+            proof_maturity_delay = params.proof_maturity_delay,
+            "Refreshed portal parameters"
+        );
+
+        *self.cached.lock().expect("portal params lock poisoned") = Some(Cached {
+            portal: portal_address,
+            params,
+            fetched_at: now,
+        });
+        Ok(params)
+    }
+
+    /// Cached parameters for `portal_address`, unless older than the TTL.
+    fn fresh(&self, portal_address: Address, now: Instant) -> Option<PortalParams> {
+        let cached = self.cached.lock().expect("portal params lock poisoned");
+        cached
+            .as_ref()
+            .filter(|c| {
+                c.portal == portal_address && now.saturating_duration_since(c.fetched_at) < self.ttl
+            })
+            .map(|c| c.params)
+    }
+
+    /// Drop the cached parameters so the next read goes to L1.
+    pub fn invalidate(&self) {
+        *self.cached.lock().expect("portal params lock poisoned") = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_provider::ProviderBuilder;
+    use alloy_sol_types::SolValue;
+    use alloy_transport::mock::Asserter;
+
+    const PORTAL: Address = Address::repeat_byte(0x11);
+    const FACTORY: Address = Address::repeat_byte(0xfa);
+
+    /// Stub the `respectedGameType()`, `disputeGameFactory()` and
+    /// `proofMaturityDelaySeconds()` calls.
+    fn push_params(asserter: &Asserter, game_type: u32, delay: u64) {
+        asserter.push_success(&alloy_primitives::Bytes::from(game_type.abi_encode()));
+        asserter.push_success(&alloy_primitives::Bytes::from(FACTORY.abi_encode()));
+        asserter.push_success(&alloy_primitives::Bytes::from(
+            U256::from(delay).abi_encode(),
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cache_reuses_params_until_ttl() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let cache = PortalParamsCache::new(Duration::from_secs(600));
+        let start = Instant::now();
+
+        push_params(&asserter, 1, 604_800);
+        let params = cache.get_at(&provider, PORTAL, start).await.unwrap();
+        assert_eq!(
+            params,
+            PortalParams {
+                respected_game_type: 1,
+                dispute_game_factory: FACTORY,
+                proof_maturity_delay: 604_800,
+            }
+        );
+
+        // Served from the cache: no responses queued, a read would fail
+        let later = start + Duration::from_secs(599);
+        assert_eq!(
+            cache.get_at(&provider, PORTAL, later).await.unwrap(),
+            params
+        );
+
+        // Stale: re-read, picking up a governance change
+        push_params(&asserter, 0, 302_400);
+        let expired = start + Duration::from_secs(600);
+        let params = cache.get_at(&provider, PORTAL, expired).await.unwrap();
+        assert_eq!(params.respected_game_type, 0);
+        assert_eq!(params.proof_maturity_delay, 302_400);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_and_default_ttl() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let now = Instant::now();
+
+        let cache = PortalParamsCache::new(Duration::from_secs(600));
+        push_params(&asserter, 1, 604_800);
+        cache.get_at(&provider, PORTAL, now).await.unwrap();
+
+        cache.invalidate();
+        push_params(&asserter, 2, 604_800);
+        let params = cache.get_at(&provider, PORTAL, now).await.unwrap();
+        assert_eq!(params.respected_game_type, 2);
+
+        // Zero TTL never serves from the cache
+        let uncached = PortalParamsCache::default();
+        push_params(&asserter, 1, 1);
+        uncached.get_at(&provider, PORTAL, now).await.unwrap();
+        push_params(&asserter, 3, 1);
+        let params = uncached.get_at(&provider, PORTAL, now).await.unwrap();
+        assert_eq!(params.respected_game_type, 3);
+    }
+}
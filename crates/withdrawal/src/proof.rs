@@ -3,22 +3,191 @@
 //! This module generates the cryptographic proofs required to prove a withdrawal
 //! on L1 using the OP Stack's fault proof system.
 
-use crate::types::WithdrawalHash;
+use crate::{
+    game::{DisputeGameReader, GameCache, GameId, GameReader},
+    portal::PortalParamsCache,
+    rpc::traced,
+    submitter::{GAME_STATUS_CHALLENGER_WINS, GAME_STATUS_DEFENDER_WINS},
+    types::WithdrawalHash,
+};
 use alloy_contract::private::Provider;
 use alloy_primitives::{keccak256, Address, BlockNumber, Bytes, B256, U256};
-use alloy_rpc_types_eth::BlockNumberOrTag;
+use alloy_rpc_types_eth::{
+    BlockNumberOrTag, EIP1186AccountProofResponse, EIP1186StorageProof, Filter,
+};
+use alloy_sol_types::SolEvent;
+use alloy_transport::TransportError;
 use binding::opstack::{
-    IDisputeGameFactory, IFaultDisputeGame, IOptimismPortal2, OutputRootProof,
-    WithdrawalTransaction, MESSAGE_PASSER_ADDRESS, OUTPUT_VERSION_V0,
+    IDisputeGameFactory::{self, DisputeGameCreated, GameSearchResult},
+    OutputRootProof, WithdrawalTransaction, OUTPUT_VERSION_V0, SENT_MESSAGES_SLOT,
 };
+use client::retry::{retry_if, RetryBudget};
 use eyre::{eyre, Result};
-use tracing::debug;
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+/// No dispute game covers the withdrawal's L2 block yet.
+///
+/// This is an expected, transient condition on young chains (too few games exist) or
+/// for withdrawals newer than the latest game. Callers can detect it with
+/// `err.downcast_ref::<GameNotYetAvailable>()` and retry on a later cycle.
+#[derive(Debug, Clone, Error)]
+#[error("no dispute game covers L2 block {withdrawal_l2_block} yet ({game_count} games exist)")]
+pub struct GameNotYetAvailable {
+    /// L2 block the withdrawal was initiated in
+    pub withdrawal_l2_block: u64,
+    /// Total number of games in the factory at the time of the search
+    pub game_count: u64,
+    /// L1 timestamp the first game covering the withdrawal is expected to be created
+    /// at, from the game cadence. `None` when there is no cadence to go by.
+    pub expected_at: Option<u64>,
+}
+
+/// The portal's respected game type is not among the allowed game types.
+///
+/// The portal only accepts proofs against games of the type it respects, so nothing
+/// can be proven until it respects an allowed type again.
+#[derive(Debug, Clone, Error)]
+#[error("portal respects game type {game_type}, which is not among the allowed types {allowed:?}")]
+pub struct GameTypeNotAllowed {
+    /// Game type the portal respects
+    pub game_type: u32,
+    /// Game types proofs may be built against
+    pub allowed: Vec<u32>,
+}
+
+/// The L2 RPC does not implement `eth_getProof`.
+///
+/// Proving needs a storage proof from the L2ToL1MessagePasser, so the endpoint must
+/// support `eth_getProof` at historical blocks (typically an archive node).
+#[derive(Debug, Clone, Error)]
+#[error(
+    "L2 RPC does not support eth_getProof ({message}); use an archive/proof-capable \
+     endpoint, e.g. via l2_proof_rpc_url"
+)]
+pub struct GetProofUnsupported {
+    /// Error message returned by the RPC
+    pub message: String,
+}
+
+/// The L2 RPC served block data that doesn't hold together.
+///
+/// A proof built from a reorged or otherwise non-canonical header hashes correctly
+/// against that header but is rejected by the portal against the game's root claim.
+/// This points at the RPC endpoint, not at proof generation.
+#[derive(Debug, Clone, Error)]
+#[error(
+    "L2 RPC returned inconsistent data for block {block_number} ({block_hash}): {reason}; \
+     check the L2 endpoint"
+)]
+pub struct InconsistentL2Block {
+    /// Block number the proof was built at
+    pub block_number: u64,
+    /// Hash the RPC reported for that block
+    pub block_hash: B256,
+    /// What didn't match
+    pub reason: String,
+}
+
+/// JSON-RPC error code for an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Whether an RPC error response means the method itself is unavailable.
+fn is_method_unsupported(code: i64, message: &str) -> bool {
+    let message = message.to_lowercase();
+    code == METHOD_NOT_FOUND
+        || message.contains("method not found")
+        || message.contains("does not exist")
+        || message.contains("not supported")
+        || message.contains("unsupported method")
+}
+
+/// A dispute game whose root claim covers a withdrawal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoveringGame {
+    /// DisputeGameFactory that created the game
+    pub factory: Address,
+    /// Index of the game in `factory`
+    pub index: U256,
+    /// Address of the game proxy
+    pub proxy: Address,
+    /// L2 block the game's root claim commits to
+    pub l2_block: u64,
+}
+
+/// Which of the dispute games covering a withdrawal to prove against.
+///
+/// A proof can only be finalized once its game resolved for the defender, so the choice
+/// trades how soon the withdrawal finalizes against how settled the game's claim is.
+/// Under every strategy, games the challenger won are skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GameSelectionStrategy {
+    /// The game committing to the lowest L2 block at or past the withdrawal. It was
+    /// proposed first, so it usually resolves first.
+    #[default]
+    OldestCovering,
+    /// The newest game. Its root claim is furthest past the withdrawal block, so least
+    /// exposed to an L2 reorg around it, but it resolves last.
+    NewestCovering,
+    /// Of the oldest [`RESOLUTION_CANDIDATES`] covering games, one already resolved for
+    /// the defender, else the oldest still in progress.
+    MostAdvancedTowardResolution,
+}
+
+/// Covering games whose status is read, in the order the strategy prefers them, before
+/// giving up on finding one the challenger didn't win.
+pub const RESOLUTION_CANDIDATES: usize = 8;
+
+impl GameSelectionStrategy {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::OldestCovering => "oldest-covering",
+            Self::NewestCovering => "newest-covering",
+            Self::MostAdvancedTowardResolution => "most-advanced-toward-resolution",
+        }
+    }
+}
+
+impl fmt::Display for GameSelectionStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GameSelectionStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "oldest-covering" => Ok(Self::OldestCovering),
+            "newest-covering" => Ok(Self::NewestCovering),
+            "most-advanced-toward-resolution" => Ok(Self::MostAdvancedTowardResolution),
+            other => Err(format!(
+                "unknown game selection strategy {other:?}, expected oldest-covering, \
+                 newest-covering or most-advanced-toward-resolution"
+            )),
+        }
+    }
+}
 
 /// Parameters required to prove a withdrawal on L1.
 #[derive(Debug, Clone)]
 pub struct ProveWithdrawalParams {
     pub withdrawal: WithdrawalTransaction,
     pub dispute_game_index: U256,
+    /// Proxy of the game at `dispute_game_index`
+    pub dispute_game_proxy: Address,
+    /// L2 block the output root proof was built at (the game's L2 block)
+    pub l2_block: u64,
     pub output_root_proof: OutputRootProof,
     pub withdrawal_proof: Vec<Bytes>,
 }
@@ -26,49 +195,230 @@ pub struct ProveWithdrawalParams {
 /// Generate proof for a withdrawal that was initiated on L2.
 ///
 /// This function:
-/// 1. Fetches the withdrawal transaction receipt from L2
-/// 2. Gets the L2 block header containing the withdrawal
-/// 3. Finds a finalized dispute game covering this block
-/// 4. Generates a Merkle proof that the withdrawal exists in L2 state
-/// 5. Builds the output root proof structure
+/// 1. Finds a dispute game covering the withdrawal's L2 block
+/// 2. Gets the L2 block header at the game's L2 block
+/// 3. Generates a Merkle proof that the withdrawal exists in L2 state
+/// 4. Builds the output root proof structure
+///
+/// To prove several withdrawals against the same game with one storage proof request,
+/// use [`find_covering_game`] and [`generate_proofs_for_game`] instead.
 ///
 /// # Arguments
 /// * `l1_provider` - Provider for L1 queries (dispute game, portal)
 /// * `l2_provider` - Provider for L2 queries (receipt, block, proof)
 /// * `withdrawal_tx_hash` - Transaction hash of the initiateWithdrawal call on L2
 /// * `portal_address` - Address of OptimismPortal2 on L1
-/// * `factory_address` - Address of DisputeGameFactory on L1
+/// * `message_passer` - Address of the L2ToL1MessagePasser on L2
+/// * `portal_params` - Cache of the portal's respected game type
+/// * `game_cache` - Cache of dispute games' L2 blocks, shared with other searches
+/// * `min_game_count` - Minimum number of games the factory must hold before proving
+/// * `allowed_game_types` - Game types to prove against; empty allows whichever type the
+///   portal respects
+/// * `strategy` - Which covering game to prove against
+/// * `retry_budget` - Budget the storage proof request's retries are drawn from
+///
+/// Returns a [`GameNotYetAvailable`] error when no game covers the withdrawal yet,
+/// [`GameTypeNotAllowed`] when the portal respects a game type that isn't allowed,
+/// [`GetProofUnsupported`] when the L2 RPC can't serve storage proofs, and
+/// [`InconsistentL2Block`] when the block the proof was built from doesn't resolve back
+/// to the same height by hash.
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(
+    feature = "trace_rpc",
+    tracing::instrument(skip_all, fields(%withdrawal_hash, withdrawal_l2_block = block_number))
+)]
 pub async fn generate_proof<P1, P2>(
     l1_provider: &P1,
     l2_provider: &P2,
     portal_address: Address,
-    factory_address: Address,
+    message_passer: Address,
+    portal_params: &PortalParamsCache,
+    game_cache: &GameCache,
     withdrawal_hash: WithdrawalHash,
     withdrawal: WithdrawalTransaction,
     block_number: BlockNumber,
+    min_game_count: u64,
+    allowed_game_types: &[u32],
+    strategy: GameSelectionStrategy,
+    retry_budget: &RetryBudget,
 ) -> Result<ProveWithdrawalParams>
 where
     P1: Provider + Clone,
     P2: Provider + Clone,
 {
-    // 1. Find a dispute game covering the withdrawal block
+    let game = find_covering_game(
+        l1_provider,
+        portal_address,
+        portal_params,
+        game_cache,
+        block_number,
+        min_game_count,
+        allowed_game_types,
+        strategy,
+    )
+    .await?;
+
+    generate_proofs_for_game(
+        l2_provider,
+        message_passer,
+        &game,
+        &[(withdrawal_hash, withdrawal)],
+        retry_budget,
+    )
+    .await?
+    .pop()
+    .ok_or_else(|| eyre!("No storage proof returned"))
+}
+
+/// Find the dispute game to prove a withdrawal from L2 block `block_number` against.
+///
+/// Only the factory the portal reads the proof's game index from, its
+/// `disputeGameFactory()`, is searched, for a game of the portal's respected type picked
+/// by `strategy`. A chain migrating to a new factory is followed once the portal points
+/// at it. Games the challenger won are never picked.
+///
+/// Only games of a type in `allowed_game_types` are considered, unless it is empty.
+/// Games' L2 blocks are read through `game_cache`, so searches for withdrawals in nearby
+/// L2 blocks sharing it mostly don't reach L1.
+///
+/// Withdrawals that resolve to the same game can share one storage proof request; see
+/// [`generate_proofs_for_game`]. Fails with [`GameNotYetAvailable`] when no game covers
+/// the block yet, and with [`GameTypeNotAllowed`] when the respected game type isn't
+/// allowed.
+#[allow(clippy::too_many_arguments)]
+pub async fn find_covering_game<P>(
+    l1_provider: &P,
+    portal_address: Address,
+    portal_params: &PortalParamsCache,
+    game_cache: &GameCache,
+    block_number: BlockNumber,
+    min_game_count: u64,
+    allowed_game_types: &[u32],
+    strategy: GameSelectionStrategy,
+) -> Result<CoveringGame>
+where
+    P: Provider + Clone,
+{
     debug!(
         withdrawal_block = block_number,
         "Finding dispute game covering withdrawal block"
     );
-    let (dispute_game_index, game_l2_block) =
-        find_game_for_withdrawal(l1_provider, portal_address, factory_address, block_number)
-            .await?;
+    let params = portal_params.get(l1_provider, portal_address).await?;
+    let game_type = params.respected_game_type;
+    if !allowed_game_types.is_empty() && !allowed_game_types.contains(&game_type) {
+        return Err(GameTypeNotAllowed {
+            game_type,
+            allowed: allowed_game_types.to_vec(),
+        }
+        .into());
+    }
+
+    let game = find_game_for_withdrawal(
+        l1_provider,
+        game_cache,
+        game_type,
+        params.dispute_game_factory,
+        block_number,
+        min_game_count,
+        strategy,
+    )
+    .await?;
 
     debug!(
-        game_index = %dispute_game_index,
-        game_l2_block = game_l2_block,
+        game_factory = %game.factory,
+        game_index = %game.index,
+        game_proxy = %game.proxy,
+        game_l2_block = game.l2_block,
         withdrawal_block = block_number,
+        %strategy,
         "Found suitable dispute game"
     );
+    Ok(game)
+}
+
+/// Watch `factory` for a game of `game_type` covering L2 block `withdrawal_l2_block`,
+/// created from L1 block `from_block` on.
+///
+/// Polls for `DisputeGameCreated` events every `poll_interval` and returns the first new
+/// game committing to the block or beyond, or `None` if none is created within
+/// `timeout`. Use it when [`GameNotYetAvailable::expected_at`] is close, then search
+/// again with [`find_covering_game`].
+pub async fn wait_for_covering_game<P>(
+    l1_provider: &P,
+    factory: Address,
+    game_type: u32,
+    withdrawal_l2_block: u64,
+    from_block: u64,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Result<Option<Address>>
+where
+    P: Provider + Clone,
+{
+    let deadline = Instant::now() + timeout;
+    let reader = GameReader::for_game_type(game_type, l1_provider.clone());
+    let mut from_block = from_block;
+
+    loop {
+        let latest = l1_provider.get_block_number().await?;
+        if latest >= from_block {
+            let filter = Filter::new()
+                .address(factory)
+                .event_signature(DisputeGameCreated::SIGNATURE_HASH)
+                .topic2(B256::from(U256::from(game_type)))
+                .from_block(from_block)
+                .to_block(latest);
+            for log in l1_provider.get_logs(&filter).await? {
+                let created = DisputeGameCreated::decode_log(&log.inner)?;
+                let game_l2_block = traced(
+                    "l2BlockNumber",
+                    reader.l2_block_number(created.disputeProxy),
+                )
+                .await?;
+                debug!(
+                    game_proxy = %created.disputeProxy,
+                    game_l2_block,
+                    withdrawal_l2_block,
+                    "Dispute game created"
+                );
+                if game_l2_block >= withdrawal_l2_block {
+                    return Ok(Some(created.disputeProxy));
+                }
+            }
+            from_block = latest + 1;
+        }
+
+        if Instant::now() + poll_interval > deadline {
+            return Ok(None);
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
 
-    // 2. Get L2 block header for the GAME's block (not the withdrawal block!)
-    // The output root proof must match the dispute game's committed state
+/// Generate proofs for `withdrawals` against `game` with a single `eth_getProof` call.
+///
+/// Every withdrawal must have been initiated at or before the game's L2 block. Proofs
+/// are returned in the order of `withdrawals`. The storage proof request is retried
+/// with backoff while `retry_budget` allows, except when the RPC doesn't implement
+/// `eth_getProof` at all ([`GetProofUnsupported`]).
+#[cfg_attr(
+    feature = "trace_rpc",
+    tracing::instrument(skip_all, fields(game_l2_block = game.l2_block, withdrawals = withdrawals.len()))
+)]
+pub async fn generate_proofs_for_game<P: Provider>(
+    l2_provider: &P,
+    message_passer: Address,
+    game: &CoveringGame,
+    withdrawals: &[(WithdrawalHash, WithdrawalTransaction)],
+    retry_budget: &RetryBudget,
+) -> Result<Vec<ProveWithdrawalParams>> {
+    if withdrawals.is_empty() {
+        return Ok(vec![]);
+    }
+    let game_l2_block = game.l2_block;
+
+    // The output root proof must match the dispute game's committed state, so
+    // everything is read at the GAME's block (not the withdrawal block!)
     debug!(
         block = game_l2_block,
         "Fetching L2 block header for game's L2 block"
@@ -81,45 +431,185 @@ where
     let state_root = block.header.state_root;
     let block_hash = block.header.hash;
 
-    // 3. Get storage proof using eth_getProof at the GAME's block
-    // The withdrawal must exist at this block (which is >= withdrawal block)
+    // Each withdrawal must exist at this block (which is >= its withdrawal block)
     debug!(
         block = game_l2_block,
-        "Generating storage proof at game's L2 block"
+        slots = withdrawals.len(),
+        "Generating storage proofs at game's L2 block"
     );
-    let storage_slot = compute_storage_slot(withdrawal_hash);
-    let proof_result = l2_provider
-        .get_proof(MESSAGE_PASSER_ADDRESS, vec![storage_slot])
-        .block_id(BlockNumberOrTag::Number(game_l2_block).into())
-        .await?;
-
-    let message_passer_storage_root = proof_result.storage_hash;
-    let withdrawal_proof = proof_result
-        .storage_proof
-        .first()
-        .ok_or_else(|| eyre!("No storage proof returned"))?
-        .proof
-        .clone();
+    let slots: Vec<B256> = withdrawals
+        .iter()
+        .map(|(hash, _)| compute_storage_slot(*hash))
+        .collect();
+    let proof_result = get_message_passer_proof(
+        l2_provider,
+        message_passer,
+        game_l2_block,
+        &slots,
+        retry_budget,
+    )
+    .await?;
+    let withdrawal_proofs = pair_storage_proofs(&slots, &proof_result.storage_proof)?;
 
     debug!(
-        proof_nodes = withdrawal_proof.len(),
-        "Generated storage proof"
+        proof_nodes = ?withdrawal_proofs.iter().map(Vec::len).collect::<Vec<_>>(),
+        "Generated storage proofs"
     );
 
-    // 4. Build output root proof
     let output_root_proof = OutputRootProof {
         version: OUTPUT_VERSION_V0,
         stateRoot: state_root,
-        messagePasserStorageRoot: message_passer_storage_root,
+        messagePasserStorageRoot: proof_result.storage_hash,
         latestBlockhash: block_hash,
     };
 
-    Ok(ProveWithdrawalParams {
-        withdrawal,
-        dispute_game_index,
-        output_root_proof,
-        withdrawal_proof,
-    })
+    // Make sure the header we hashed is the one the RPC considers canonical
+    verify_block_hash(l2_provider, game_l2_block, block_hash).await?;
+
+    Ok(withdrawals
+        .iter()
+        .zip(withdrawal_proofs)
+        .map(
+            |((_, withdrawal), withdrawal_proof)| ProveWithdrawalParams {
+                withdrawal: withdrawal.clone(),
+                dispute_game_index: game.index,
+                dispute_game_proxy: game.proxy,
+                l2_block: game_l2_block,
+                output_root_proof: output_root_proof.clone(),
+                withdrawal_proof,
+            },
+        )
+        .collect())
+}
+
+/// `eth_getProof` for `slots` of the L2ToL1MessagePasser at `message_passer` at `block`,
+/// with retries.
+///
+/// Fails if the response is for a different account, which would prove against the
+/// wrong chain's storage root.
+async fn get_message_passer_proof<P: Provider>(
+    l2_provider: &P,
+    message_passer: Address,
+    block: u64,
+    slots: &[B256],
+    retry_budget: &RetryBudget,
+) -> Result<EIP1186AccountProofResponse> {
+    let unsupported = |e: &TransportError| {
+        e.as_error_resp()
+            .is_some_and(|resp| is_method_unsupported(resp.code, &resp.message))
+    };
+
+    let response = retry_if(
+        retry_budget,
+        || async {
+            traced(
+                "eth_getProof",
+                l2_provider
+                    .get_proof(message_passer, slots.to_vec())
+                    .block_id(BlockNumberOrTag::Number(block).into()),
+            )
+            .await
+            .inspect_err(|e| {
+                if !unsupported(e) {
+                    warn!(block, error = %e, "eth_getProof failed, will retry");
+                }
+            })
+        },
+        |e| !unsupported(e),
+    )
+    .await
+    .map_err(|e| match e.as_error_resp() {
+        Some(resp) if is_method_unsupported(resp.code, &resp.message) => GetProofUnsupported {
+            message: resp.message.to_string(),
+        }
+        .into(),
+        _ => eyre::Report::from(e),
+    })?;
+
+    if response.address != message_passer {
+        return Err(eyre!(
+            "eth_getProof returned account {} instead of the message passer {message_passer}",
+            response.address
+        ));
+    }
+    Ok(response)
+}
+
+/// Match the storage proofs of an `eth_getProof` response to the requested `slots`.
+///
+/// RPCs are not required to keep the request order, so proofs are paired by key. Fails
+/// if a slot has no proof or isn't set, i.e. the withdrawal isn't in the message passer
+/// at that block.
+fn pair_storage_proofs(slots: &[B256], proofs: &[EIP1186StorageProof]) -> Result<Vec<Vec<Bytes>>> {
+    slots
+        .iter()
+        .map(|slot| {
+            let proof = proofs
+                .iter()
+                .find(|p| p.key.as_b256() == *slot)
+                .ok_or_else(|| eyre!("No storage proof returned for slot {slot}"))?;
+            if proof.value.is_zero() {
+                return Err(eyre!(
+                    "Storage slot {slot} is not set: withdrawal not found in the message passer"
+                ));
+            }
+            Ok(proof.proof.clone())
+        })
+        .collect()
+}
+
+/// Re-fetch `block_hash` and check that it is block `block_number`.
+///
+/// Fails with [`InconsistentL2Block`] when the RPC doesn't know the hash or reports a
+/// different height for it.
+pub async fn verify_block_hash<P: Provider>(
+    l2_provider: &P,
+    block_number: u64,
+    block_hash: B256,
+) -> Result<()> {
+    let inconsistent = |reason: String| InconsistentL2Block {
+        block_number,
+        block_hash,
+        reason,
+    };
+
+    let block = l2_provider
+        .get_block_by_hash(block_hash)
+        .await?
+        .ok_or_else(|| inconsistent("hash not found when fetched by hash".to_string()))?;
+
+    if block.header.number != block_number {
+        return Err(inconsistent(format!("hash resolves to block {}", block.header.number)).into());
+    }
+
+    Ok(())
+}
+
+/// Compare the hash `secondary` reports for `block_number` with `expected`.
+///
+/// Returns false and warns on divergence: one of the two endpoints is serving a
+/// non-canonical block, so a proof built from it is likely to be rejected.
+pub async fn compare_block_hash<P: Provider>(
+    secondary: &P,
+    block_number: u64,
+    expected: B256,
+) -> Result<bool> {
+    let block = secondary
+        .get_block_by_number(BlockNumberOrTag::Number(block_number))
+        .await?
+        .ok_or_else(|| eyre!("Block not found on secondary L2 RPC: {}", block_number))?;
+
+    if block.header.hash != expected {
+        warn!(
+            block = block_number,
+            proof_rpc_hash = %expected,
+            secondary_rpc_hash = %block.header.hash,
+            "L2 RPCs disagree on the block hash the proof was built from"
+        );
+        return Ok(false);
+    }
+
+    Ok(true)
 }
 
 /// Find a dispute game that covers the withdrawal's L2 block.
@@ -127,53 +617,129 @@ where
 /// This function searches through recent dispute games to find one where:
 /// - The game's L2 block number >= withdrawal's L2 block number
 ///
+/// Which of the covering games is returned depends on `strategy`.
+///
 /// Note: For proving, we don't need the game to be finalized - we can prove
 /// against an in-flight dispute game. Finalization is only required for the
 /// finalize step after the challenge period.
 ///
-/// Games are created roughly every hour, so we typically only need to check
-/// a few dozen games even for withdrawals from weeks ago.
-/// Returns (dispute_game_index, game_l2_block_number)
+/// How many of the newest games are fetched depends on the chain's game cadence,
+/// measured by [`measure_game_cadence`], and how far behind the newest game the
+/// withdrawal is; see [`GameCadence::lookback`].
+///
+/// The game ABI is chosen from the portal's respected game type, `game_type`, and game
+/// L2 blocks are read through `game_cache`; see [`find_game_with_reader`] to supply a
+/// custom [`DisputeGameReader`].
+///
+/// Fails with [`GameNotYetAvailable`] if the factory holds fewer than `min_game_count`
+/// games or no game covers the withdrawal yet.
 async fn find_game_for_withdrawal<P>(
     l1_provider: &P,
-    portal_address: Address,
+    game_cache: &GameCache,
+    game_type: u32,
     factory_address: Address,
     withdrawal_l2_block: u64,
-) -> Result<(U256, u64)>
+    min_game_count: u64,
+    strategy: GameSelectionStrategy,
+) -> Result<CoveringGame>
 where
     P: Provider + Clone,
 {
-    // Get the respected game type from portal
-    let portal = IOptimismPortal2::new(portal_address, l1_provider);
-    let game_type = portal.respectedGameType().call().await?;
+    debug!(game_type, "Using respected game type from portal");
 
-    debug!(game_type, "Got respected game type from portal");
+    let reader = game_cache.reader(GameReader::for_game_type(game_type, l1_provider.clone()));
+    find_game_with_reader(
+        l1_provider,
+        &reader,
+        game_type,
+        factory_address,
+        withdrawal_l2_block,
+        min_game_count,
+        strategy,
+    )
+    .await
+}
 
+/// Like [`find_game_for_withdrawal`], but reads games of `game_type` through `reader`.
+///
+/// Use this for chains whose respected game type does not follow the
+/// `IFaultDisputeGame` or permissioned game ABIs.
+pub async fn find_game_with_reader<P, G>(
+    l1_provider: &P,
+    reader: &G,
+    game_type: u32,
+    factory_address: Address,
+    withdrawal_l2_block: u64,
+    min_game_count: u64,
+    strategy: GameSelectionStrategy,
+) -> Result<CoveringGame>
+where
+    P: Provider + Clone,
+    G: DisputeGameReader,
+{
     let factory = IDisputeGameFactory::new(factory_address, l1_provider);
 
     // Get total game count to start from the latest
     let game_count = factory.gameCount().call().await?;
-    if game_count == U256::ZERO {
-        return Err(eyre!("No dispute games exist"));
+    let not_yet_available = |expected_at| GameNotYetAvailable {
+        withdrawal_l2_block,
+        game_count: game_count.saturating_to(),
+        expected_at,
+    };
+
+    if game_count == U256::ZERO || game_count < U256::from(min_game_count) {
+        info!(
+            game_count = %game_count,
+            min_game_count,
+            "Chain has too few dispute games yet, skipping proof"
+        );
+        return Err(not_yet_available(None).into());
     }
     debug!(total_games = %game_count, "Starting search from latest game");
 
-    const MAX_GAMES_TO_CHECK: u64 = 1000; // ~40 days at 1 game/hour
     let start = game_count.saturating_sub(U256::from(1));
+    let lookback = match measure_game_cadence(&factory, reader, game_type, start).await? {
+        Some(cadence) if cadence.newest_l2_block < withdrawal_l2_block => {
+            info!(
+                game_type,
+                newest_game_l2_block = cadence.newest_l2_block,
+                withdrawal_l2_block,
+                "Newest dispute game does not cover the withdrawal yet"
+            );
+            let expected_at = cadence.covering_game_expected_at(withdrawal_l2_block);
+            return Err(not_yet_available(Some(expected_at)).into());
+        }
+        Some(cadence) => {
+            let lookback = cadence.lookback(withdrawal_l2_block);
+            debug!(
+                interval_secs = cadence.interval_secs,
+                l2_blocks_per_game = cadence.l2_blocks_per_game,
+                estimated_age_secs = cadence.age_secs(withdrawal_l2_block),
+                lookback,
+                "Sized game search from game cadence"
+            );
+            lookback
+        }
+        None => MAX_GAMES_TO_CHECK,
+    };
 
     debug!(
         start_index = %start,
-        lookback = %MAX_GAMES_TO_CHECK,
+        lookback,
         "Fetching batch of games"
     );
 
-    let games = factory
-        .findLatestGames(game_type, start, U256::from(MAX_GAMES_TO_CHECK))
-        .call()
-        .await?;
+    let games = traced(
+        "findLatestGames",
+        factory
+            .findLatestGames(game_type, start, U256::from(lookback))
+            .call(),
+    )
+    .await?;
 
     if games.is_empty() {
-        eyre::bail!("No games of type {} found", game_type);
+        info!(game_type, "No games of the respected type exist yet");
+        return Err(not_yet_available(None).into());
     }
 
     debug!(
@@ -184,20 +750,6 @@ where
         "Found games for binary search"
     );
 
-    // Log the newest game's L2 block to verify we can cover the withdrawal
-    if let Some(newest_game) = games.first() {
-        let newest_address = Address::from_slice(&newest_game.metadata.as_slice()[12..32]);
-        let newest_contract = IFaultDisputeGame::new(newest_address, l1_provider);
-        if let Ok(newest_l2_block) = newest_contract.l2BlockNumber().call().await {
-            debug!(
-                newest_game_index = %newest_game.index,
-                newest_game_l2_block = newest_l2_block.to::<u64>(),
-                withdrawal_l2_block,
-                "Newest game L2 block check"
-            );
-        }
-    }
-
     // Validate that all game indices are within bounds
     for game in &games {
         if game.index >= game_count {
@@ -209,7 +761,248 @@ where
         }
     }
 
-    // Binary search to find the oldest game that covers the withdrawal.
+    let Some(found) = select_covering_game(
+        reader,
+        factory_address,
+        &games,
+        withdrawal_l2_block,
+        strategy,
+    )
+    .await?
+    else {
+        // Even the newest game doesn't cover the withdrawal
+        info!(
+            game_type,
+            withdrawal_l2_block, "Newest dispute game does not cover the withdrawal yet"
+        );
+        return Err(not_yet_available(None).into());
+    };
+
+    Ok(found)
+}
+
+/// Most games fetched in one search, ~40 days at 1 game/hour. Also the number fetched
+/// when the cadence can't be measured.
+const MAX_GAMES_TO_CHECK: u64 = 1000;
+
+/// Games fetched beyond the cadence estimate, for games proposed further apart than
+/// the average.
+const GAME_LOOKBACK_MARGIN: u64 = 16;
+
+/// Newest games the cadence is averaged over, so one late or early proposal doesn't
+/// skew it.
+const CADENCE_SAMPLE_GAMES: u64 = 8;
+
+/// Average spacing of the newest games of a type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameCadence {
+    /// Average seconds between the creation of consecutive games
+    pub interval_secs: u64,
+    /// Average L2 blocks each game is ahead of the one before it
+    pub l2_blocks_per_game: u64,
+    /// L2 block of the newest game
+    pub newest_l2_block: u64,
+    /// L1 timestamp the newest game was created at
+    pub newest_created_at: u64,
+}
+
+impl GameCadence {
+    /// Games proposed since the one that first covered `withdrawal_l2_block`, at this
+    /// cadence.
+    const fn games_behind(&self, withdrawal_l2_block: u64) -> u64 {
+        let per_game = if self.l2_blocks_per_game == 0 {
+            1
+        } else {
+            self.l2_blocks_per_game
+        };
+        self.newest_l2_block
+            .saturating_sub(withdrawal_l2_block)
+            .div_ceil(per_game)
+    }
+
+    /// Estimated time since the game first covering `withdrawal_l2_block` was created.
+    pub const fn age_secs(&self, withdrawal_l2_block: u64) -> u64 {
+        self.games_behind(withdrawal_l2_block)
+            .saturating_mul(self.interval_secs)
+    }
+
+    /// L1 timestamp the first game covering `withdrawal_l2_block` is expected at, if
+    /// games keep being proposed at this cadence.
+    pub const fn covering_game_expected_at(&self, withdrawal_l2_block: u64) -> u64 {
+        let per_game = if self.l2_blocks_per_game == 0 {
+            1
+        } else {
+            self.l2_blocks_per_game
+        };
+        let games_ahead = withdrawal_l2_block
+            .saturating_sub(self.newest_l2_block)
+            .div_ceil(per_game);
+        self.newest_created_at
+            .saturating_add(games_ahead.saturating_mul(self.interval_secs))
+    }
+
+    /// Number of the newest games to search for one covering `withdrawal_l2_block`.
+    ///
+    /// Covers the games proposed since the withdrawal plus a quarter and
+    /// [`GAME_LOOKBACK_MARGIN`] more, as cadence drifts over time, up to
+    /// [`MAX_GAMES_TO_CHECK`].
+    pub const fn lookback(&self, withdrawal_l2_block: u64) -> u64 {
+        let behind = self.games_behind(withdrawal_l2_block);
+        let lookback = behind
+            .saturating_add(behind / 4)
+            .saturating_add(GAME_LOOKBACK_MARGIN + 1);
+        if lookback < MAX_GAMES_TO_CHECK {
+            lookback
+        } else {
+            MAX_GAMES_TO_CHECK
+        }
+    }
+}
+
+/// Measure the cadence of `game_type` games, averaged over the
+/// [`CADENCE_SAMPLE_GAMES`] newest at or below `start`.
+///
+/// Returns `None` if there are fewer than two such games or their L2 blocks don't
+/// advance, in which case there is no cadence to go by.
+pub async fn measure_game_cadence<P, G>(
+    factory: &IDisputeGameFactory::IDisputeGameFactoryInstance<P>,
+    reader: &G,
+    game_type: u32,
+    start: U256,
+) -> Result<Option<GameCadence>>
+where
+    P: Provider,
+    G: DisputeGameReader,
+{
+    let sample = traced(
+        "findLatestGames",
+        factory
+            .findLatestGames(game_type, start, U256::from(CADENCE_SAMPLE_GAMES))
+            .call(),
+    )
+    .await?;
+    let [newest, .., oldest] = sample.as_slice() else {
+        return Ok(None);
+    };
+    let intervals = sample.len() as u64 - 1;
+
+    let newest_l2_block = traced(
+        "l2BlockNumber",
+        reader.l2_block_number(game_address(newest)),
+    )
+    .await?;
+    let oldest_l2_block = traced(
+        "l2BlockNumber",
+        reader.l2_block_number(game_address(oldest)),
+    )
+    .await?;
+    if newest_l2_block <= oldest_l2_block {
+        return Ok(None);
+    }
+
+    let elapsed: u64 = newest
+        .timestamp
+        .saturating_sub(oldest.timestamp)
+        .saturating_to();
+    Ok(Some(GameCadence {
+        interval_secs: elapsed / intervals,
+        l2_blocks_per_game: (newest_l2_block - oldest_l2_block) / intervals,
+        newest_l2_block,
+        newest_created_at: newest.timestamp.saturating_to(),
+    }))
+}
+
+/// The game proxy address of a search result.
+fn game_address(game: &GameSearchResult) -> Address {
+    GameId::from_metadata(game.metadata).proxy_address()
+}
+
+/// Pick the game of `games`, in descending order, to prove `withdrawal_l2_block`
+/// against by `strategy`.
+///
+/// Games the challenger won are skipped under every strategy, checking at most
+/// [`RESOLUTION_CANDIDATES`] covering games. Returns `None` if even the newest game is
+/// behind the withdrawal, and fails if the challenger won every game checked.
+async fn select_covering_game<G: DisputeGameReader>(
+    reader: &G,
+    factory: Address,
+    games: &[GameSearchResult],
+    withdrawal_l2_block: u64,
+    strategy: GameSelectionStrategy,
+) -> Result<Option<CoveringGame>> {
+    let Some(oldest) = locate_covering_game(reader, factory, games, withdrawal_l2_block).await?
+    else {
+        return Ok(None);
+    };
+    let position = games
+        .iter()
+        .position(|game| game.index == oldest.index)
+        .ok_or_else(|| eyre!("Covering game {} not among the games", oldest.index))?;
+    // Every game newer than a covering game covers too
+    let covering = &games[..=position];
+
+    // Covering games in order of preference
+    let candidates: Vec<&GameSearchResult> = match strategy {
+        GameSelectionStrategy::OldestCovering
+        | GameSelectionStrategy::MostAdvancedTowardResolution => covering.iter().rev().collect(),
+        GameSelectionStrategy::NewestCovering => covering.iter().collect(),
+    };
+
+    let mut standing = None;
+    for game in candidates.into_iter().take(RESOLUTION_CANDIDATES) {
+        let address = game_address(game);
+        let status = traced("status", reader.status(address)).await?;
+        debug!(
+            game_index = %game.index,
+            game_address = %address,
+            status,
+            "Covering game status"
+        );
+        if status == GAME_STATUS_CHALLENGER_WINS {
+            continue;
+        }
+        let resolved = status == GAME_STATUS_DEFENDER_WINS;
+        if standing.is_none() {
+            standing = Some(game);
+        }
+        // Only worth looking further for a game already resolved for the defender
+        if strategy != GameSelectionStrategy::MostAdvancedTowardResolution || resolved {
+            standing = Some(game);
+            break;
+        }
+    }
+
+    match standing {
+        Some(game) if game.index == oldest.index => Ok(Some(oldest)),
+        Some(game) => {
+            let l2_block =
+                traced("l2BlockNumber", reader.l2_block_number(game_address(game))).await?;
+            Ok(Some(CoveringGame {
+                factory,
+                index: game.index,
+                proxy: game_address(game),
+                l2_block,
+            }))
+        }
+        None => Err(eyre!(
+            "Every covering dispute game checked for L2 block {withdrawal_l2_block} was lost by its proposer"
+        )),
+    }
+}
+
+/// Binary search `games` for the oldest game covering `withdrawal_l2_block`.
+///
+/// Returns `None` if even the newest game is behind the withdrawal.
+#[cfg_attr(
+    feature = "trace_rpc",
+    tracing::instrument(skip_all, fields(games = games.len(), withdrawal_l2_block))
+)]
+async fn locate_covering_game<G: DisputeGameReader>(
+    reader: &G,
+    factory: Address,
+    games: &[GameSearchResult],
+    withdrawal_l2_block: u64,
+) -> Result<Option<CoveringGame>> {
     // Games array is sorted in DESCENDING order by L2 block:
     //   games[0] = newest (highest L2 block)
     //   games[len-1] = oldest (lowest L2 block)
@@ -219,90 +1012,153 @@ where
     // then returning the game just before it.
     let mut lo = 0;
     let mut hi = games.len();
+    // L2 block of games[lo - 1], once known
+    let mut covering_l2_block = None;
 
     while lo < hi {
         let mi = lo + (hi - lo) / 2;
         let game = &games[mi];
-
-        // Extract game proxy address from metadata (GameId)
-        // GameId format: type (32 bits) | timestamp (64 bits) | proxy address (160 bits)
-        // The address is in the lower 160 bits (20 bytes)
-        let game_address = Address::from_slice(&game.metadata.as_slice()[12..32]);
+        let address = game_address(game);
 
         debug!(
             game_index = %game.index,
-            game_address = %game_address,
+            game_address = %address,
             array_index = mi,
             "Processing game from search results"
         );
 
-        let game_contract = IFaultDisputeGame::new(game_address, l1_provider);
-        let game_l2_block = game_contract.l2BlockNumber().call().await.map_err(|e| {
-            eyre!(
-                "Failed to call l2BlockNumber on game {} at address {}: {}",
-                game.index,
-                game_address,
-                e
-            )
-        })?;
+        let game_l2_block = traced("l2BlockNumber", reader.l2_block_number(address))
+            .await
+            .map_err(|e| {
+                eyre!(
+                    "Failed to read L2 block number of game {} at address {}: {}",
+                    game.index,
+                    address,
+                    e
+                )
+            })?;
 
-        let game_l2_block_num = game_l2_block.to::<u64>();
         debug!(
             game_index = %game.index,
-            game_l2_block = game_l2_block_num,
+            game_l2_block,
             withdrawal_l2_block,
-            covers = game_l2_block_num >= withdrawal_l2_block,
+            covers = game_l2_block >= withdrawal_l2_block,
             "Game L2 block comparison"
         );
 
         // In descending order: if this game covers, search right (older) for more candidates
         // If this game doesn't cover, search left (newer) for a game that does
-        if game_l2_block_num >= withdrawal_l2_block {
+        if game_l2_block >= withdrawal_l2_block {
             lo = mi + 1; // This game covers, but older games might too - search right
+            covering_l2_block = Some(game_l2_block);
         } else {
             hi = mi; // This game is too old, search left for newer games
         }
     }
 
     // lo is now pointing to the first game that DOESN'T cover (or past the end).
-    // The game we want is at lo - 1 (the last game that covers).
-    if lo == 0 {
-        // Even the newest game doesn't cover the withdrawal
-        eyre::bail!(
-            "No games of type {} found covering L2 block {} (newest game L2 block is older)",
-            game_type,
-            withdrawal_l2_block
-        );
-    }
-
-    let selected_game = &games[lo - 1];
-
-    // We need to get the L2 block for the selected game.
-    // If we happened to check it during binary search, we might have it cached,
-    // but the binary search may not have checked this exact game.
-    // Re-fetch to be safe.
-    let game_address = Address::from_slice(&selected_game.metadata.as_slice()[12..32]);
-    let game_contract = IFaultDisputeGame::new(game_address, l1_provider);
-    let game_l2_block = game_contract.l2BlockNumber().call().await?.to::<u64>();
+    // The game we want is at lo - 1 (the last game that covers), which is always the
+    // last covering game we checked. No covering game means lo == 0.
+    let Some(l2_block) = covering_l2_block else {
+        return Ok(None);
+    };
 
-    Ok((selected_game.index, game_l2_block))
+    let game = &games[lo - 1];
+    Ok(Some(CoveringGame {
+        factory,
+        index: game.index,
+        proxy: game_address(game),
+        l2_block,
+    }))
 }
 
 /// Compute the storage slot for a withdrawal hash in the L2ToL1MessagePasser contract.
 ///
 /// The storage layout is: `mapping(bytes32 => bool) public sentMessages`
 /// Solidity storage slot = keccak256(key || slot_index)
-/// For our mapping at slot 0: keccak256(withdrawalHash || 0)
+/// For the mapping at [`SENT_MESSAGES_SLOT`]: keccak256(withdrawalHash || 0).
+/// [`StorageLayoutCheck`] verifies this against the live contract.
 pub fn compute_storage_slot(withdrawal_hash: B256) -> B256 {
     let mut data = [0u8; 64];
     data[0..32].copy_from_slice(withdrawal_hash.as_slice());
-    // data[32..64] is already zeros (mapping is at slot 0)
+    data[32..64].copy_from_slice(&SENT_MESSAGES_SLOT.to_be_bytes::<32>());
     keccak256(data)
 }
 
+/// The message passer's storage doesn't match [`compute_storage_slot`]: the slot
+/// computed for a withdrawal known to be initiated is not set.
+///
+/// Proofs built from that slot would be rejected by the portal, so proving must stop
+/// until the layout assumption is fixed.
+#[derive(Debug, Clone, Error)]
+#[error(
+    "Message passer {message_passer} storage layout mismatch: slot {slot} of initiated \
+     withdrawal {withdrawal_hash} holds {value}, expected 1"
+)]
+pub struct StorageLayoutMismatch {
+    pub message_passer: Address,
+    pub withdrawal_hash: WithdrawalHash,
+    pub slot: B256,
+    pub value: U256,
+}
+
+/// Self-check of the `sentMessages` slot assumption, run before the first proof.
+///
+/// Clones share the result, and once the check passed it isn't run again.
+#[derive(Debug, Clone, Default)]
+pub struct StorageLayoutCheck {
+    verified: Arc<AtomicBool>,
+}
+
+impl StorageLayoutCheck {
+    pub fn is_verified(&self) -> bool {
+        self.verified.load(Ordering::Relaxed)
+    }
+
+    /// Check that the slot [`compute_storage_slot`] gives for `withdrawal_hash`, a
+    /// withdrawal initiated through `message_passer`, holds `true` at the latest block.
+    ///
+    /// Fails with [`StorageLayoutMismatch`] if it doesn't. Skipped once a check passed.
+    pub async fn ensure<P: Provider>(
+        &self,
+        l2_provider: &P,
+        message_passer: Address,
+        withdrawal_hash: WithdrawalHash,
+    ) -> Result<()> {
+        if self.is_verified() {
+            return Ok(());
+        }
+
+        let slot = compute_storage_slot(withdrawal_hash);
+        let value = l2_provider
+            .get_storage_at(message_passer, slot.into())
+            .await?;
+        if value != U256::from(1) {
+            return Err(StorageLayoutMismatch {
+                message_passer,
+                withdrawal_hash,
+                slot,
+                value,
+            }
+            .into());
+        }
+
+        debug!(%message_passer, %withdrawal_hash, "Message passer storage layout verified");
+        self.verified.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_provider::ProviderBuilder;
+    use alloy_rpc_types_eth::Log;
+    use alloy_sol_types::SolCall;
+    use alloy_transport::mock::Asserter;
+    use binding::opstack::{IFaultDisputeGame, MESSAGE_PASSER_ADDRESS};
+    use std::collections::HashMap;
+    use test_support::FakeChain;
 
     #[test]
     fn test_compute_storage_slot() {
@@ -344,6 +1200,8 @@ mod tests {
                 data: Bytes::new(),
             },
             dispute_game_index: U256::from(42),
+            dispute_game_proxy: Address::repeat_byte(0x42),
+            l2_block: 1000,
             output_root_proof: OutputRootProof {
                 version: OUTPUT_VERSION_V0,
                 stateRoot: B256::ZERO,
@@ -357,6 +1215,717 @@ mod tests {
         assert_eq!(params.withdrawal_proof.len(), 1);
     }
 
+    #[test]
+    fn test_game_not_yet_available_downcast() {
+        let err: eyre::Report = GameNotYetAvailable {
+            withdrawal_l2_block: 100,
+            game_count: 3,
+            expected_at: None,
+        }
+        .into();
+
+        let inner = err
+            .downcast_ref::<GameNotYetAvailable>()
+            .expect("should downcast");
+        assert_eq!(inner.withdrawal_l2_block, 100);
+        assert!(err.to_string().contains("3 games exist"));
+    }
+
+    /// RPC block with the given number and hash; other fields are defaulted.
+    fn rpc_block(number: u64, hash: B256) -> alloy_rpc_types_eth::Block {
+        let mut block: alloy_rpc_types_eth::Block = Default::default();
+        block.header.hash = hash;
+        block.header.inner.number = number;
+        block
+    }
+
+    #[tokio::test]
+    async fn test_verify_block_hash() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let hash = B256::repeat_byte(0xaa);
+
+        asserter.push_success(&rpc_block(1000, hash));
+        verify_block_hash(&provider, 1000, hash).await.unwrap();
+
+        // Hash belongs to another height, e.g. the RPC served a reorged header
+        asserter.push_success(&rpc_block(999, hash));
+        let err = verify_block_hash(&provider, 1000, hash).await.unwrap_err();
+        let inconsistent = err
+            .downcast_ref::<InconsistentL2Block>()
+            .expect("should downcast");
+        assert_eq!(inconsistent.block_number, 1000);
+        assert_eq!(inconsistent.block_hash, hash);
+        assert!(err.to_string().contains("resolves to block 999"));
+
+        // Hash unknown to the RPC
+        asserter.push_success(&Option::<alloy_rpc_types_eth::Block>::None);
+        let err = verify_block_hash(&provider, 1000, hash).await.unwrap_err();
+        assert!(err.downcast_ref::<InconsistentL2Block>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_compare_block_hash() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let hash = B256::repeat_byte(0xaa);
+
+        asserter.push_success(&rpc_block(1000, hash));
+        assert!(compare_block_hash(&provider, 1000, hash).await.unwrap());
+
+        asserter.push_success(&rpc_block(1000, B256::repeat_byte(0xbb)));
+        assert!(!compare_block_hash(&provider, 1000, hash).await.unwrap());
+    }
+
+    /// `eth_getProof` response of the message passer for `(slot, value, proof)` entries,
+    /// in the given order.
+    fn proof_response(entries: &[(B256, u64, &str)]) -> serde_json::Value {
+        account_proof_response(MESSAGE_PASSER_ADDRESS, entries)
+    }
+
+    fn account_proof_response(
+        address: Address,
+        entries: &[(B256, u64, &str)],
+    ) -> serde_json::Value {
+        let storage_proof: Vec<_> = entries
+            .iter()
+            .map(|(slot, value, node)| {
+                serde_json::json!({
+                    "key": slot,
+                    "value": format!("{value:#x}"),
+                    "proof": [node, "0xc0"],
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "address": address,
+            "balance": "0x0",
+            "codeHash": B256::ZERO,
+            "nonce": "0x0",
+            "storageHash": B256::repeat_byte(0x55),
+            "accountProof": [],
+            "storageProof": storage_proof,
+        })
+    }
+
+    fn withdrawal_tx(nonce: u64) -> WithdrawalTransaction {
+        WithdrawalTransaction {
+            nonce: U256::from(nonce),
+            sender: Address::ZERO,
+            target: Address::ZERO,
+            value: U256::from(1000),
+            gasLimit: U256::from(100000),
+            data: Bytes::new(),
+        }
+    }
+
+    #[test]
+    fn test_pair_storage_proofs_by_key() {
+        let (a, b, c) = (
+            compute_storage_slot(B256::repeat_byte(1)),
+            compute_storage_slot(B256::repeat_byte(2)),
+            compute_storage_slot(B256::repeat_byte(3)),
+        );
+        // Returned out of request order
+        let response: EIP1186AccountProofResponse = serde_json::from_value(proof_response(&[
+            (c, 1, "0xcc"),
+            (a, 1, "0xaa"),
+            (b, 1, "0xbb"),
+        ]))
+        .unwrap();
+
+        let proofs = pair_storage_proofs(&[a, b, c], &response.storage_proof).unwrap();
+        let first_nodes: Vec<_> = proofs.iter().map(|p| p[0].clone()).collect();
+        assert_eq!(
+            first_nodes,
+            vec![
+                Bytes::from(vec![0xaa]),
+                Bytes::from(vec![0xbb]),
+                Bytes::from(vec![0xcc])
+            ]
+        );
+        assert!(proofs.iter().all(|p| p.len() == 2));
+
+        // A requested slot missing from the response
+        let d = compute_storage_slot(B256::repeat_byte(4));
+        let err = pair_storage_proofs(&[a, d], &response.storage_proof).unwrap_err();
+        assert!(err.to_string().contains(&d.to_string()));
+
+        // An unset slot: the withdrawal isn't in the message passer at that block
+        let response: EIP1186AccountProofResponse =
+            serde_json::from_value(proof_response(&[(a, 0, "0xaa")])).unwrap();
+        let err = pair_storage_proofs(&[a], &response.storage_proof).unwrap_err();
+        assert!(err.to_string().contains("not set"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_proofs_for_game() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let block_hash = B256::repeat_byte(0xaa);
+        let game = CoveringGame {
+            factory: Address::repeat_byte(0xfa),
+            index: U256::from(7),
+            proxy: Address::repeat_byte(0x77),
+            l2_block: 1000,
+        };
+        let withdrawals = [
+            (B256::repeat_byte(1), withdrawal_tx(1)),
+            (B256::repeat_byte(2), withdrawal_tx(2)),
+        ];
+        let slots = withdrawals
+            .each_ref()
+            .map(|(hash, _)| compute_storage_slot(*hash));
+
+        asserter.push_success(&rpc_block(1000, block_hash));
+        // A transient eth_getProof failure is retried
+        asserter.push_failure_msg("header not found");
+        asserter.push_success(&proof_response(&[
+            (slots[1], 1, "0xbb"),
+            (slots[0], 1, "0xaa"),
+        ]));
+        asserter.push_success(&rpc_block(1000, block_hash));
+
+        let budget = RetryBudget::new(5);
+        let proofs = generate_proofs_for_game(
+            &provider,
+            MESSAGE_PASSER_ADDRESS,
+            &game,
+            &withdrawals,
+            &budget,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(budget.consumed(), 1);
+        assert_eq!(proofs.len(), 2);
+        for ((_, withdrawal), (proof, node)) in
+            withdrawals.iter().zip(proofs.iter().zip([0xaa, 0xbb]))
+        {
+            assert_eq!(&proof.withdrawal, withdrawal);
+            assert_eq!(proof.dispute_game_index, game.index);
+            assert_eq!(proof.dispute_game_proxy, game.proxy);
+            assert_eq!(proof.l2_block, 1000);
+            assert_eq!(proof.output_root_proof.latestBlockhash, block_hash);
+            assert_eq!(
+                proof.output_root_proof.messagePasserStorageRoot,
+                B256::repeat_byte(0x55)
+            );
+            assert_eq!(proof.withdrawal_proof[0], Bytes::from(vec![node]));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejects_proof_of_other_account() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let game = CoveringGame {
+            factory: Address::repeat_byte(0xfa),
+            index: U256::from(7),
+            proxy: Address::repeat_byte(0x77),
+            l2_block: 1000,
+        };
+        let withdrawals = [(B256::repeat_byte(1), withdrawal_tx(1))];
+        let slot = compute_storage_slot(B256::repeat_byte(1));
+
+        asserter.push_success(&rpc_block(1000, B256::repeat_byte(0xaa)));
+        asserter.push_success(&proof_response(&[(slot, 1, "0xaa")]));
+
+        let message_passer = Address::repeat_byte(0x16);
+        let err = generate_proofs_for_game(
+            &provider,
+            message_passer,
+            &game,
+            &withdrawals,
+            &RetryBudget::unlimited(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains(&message_passer.to_string()));
+    }
+
+    const FACTORY: Address = Address::repeat_byte(0xfa);
+
+    /// Reader backed by fixed address -> L2 block and address -> status maps. Games
+    /// without a status are in progress.
+    struct MockReader(HashMap<Address, u64>, HashMap<Address, u8>);
+
+    impl DisputeGameReader for MockReader {
+        async fn l2_block_number(&self, game: Address) -> Result<u64> {
+            self.0
+                .get(&game)
+                .copied()
+                .ok_or_else(|| eyre!("unknown game {game}"))
+        }
+
+        async fn status(&self, game: Address) -> Result<u8> {
+            Ok(self.1.get(&game).copied().unwrap_or(0))
+        }
+
+        async fn root_claim(&self, _game: Address) -> Result<B256> {
+            Ok(B256::ZERO)
+        }
+    }
+
+    /// Games in descending order, one per entry in `l2_blocks`.
+    fn games(l2_blocks: &[u64]) -> (Vec<GameSearchResult>, MockReader) {
+        let mut results = Vec::new();
+        let mut blocks = HashMap::new();
+        for (i, &l2_block) in l2_blocks.iter().enumerate() {
+            let address = Address::repeat_byte(i as u8 + 1);
+            blocks.insert(address, l2_block);
+            results.push(GameSearchResult {
+                index: U256::from(l2_blocks.len() - i - 1),
+                metadata: address.into_word(),
+                timestamp: U256::ZERO,
+                rootClaim: B256::ZERO,
+                extraData: Bytes::new(),
+            });
+        }
+        (results, MockReader(blocks, HashMap::new()))
+    }
+
+    #[tokio::test]
+    async fn test_locate_covering_game_picks_oldest_covering() {
+        let (games, reader) = games(&[400, 300, 200, 100]);
+
+        let covering = |index: u64, l2_block| {
+            Some(CoveringGame {
+                factory: FACTORY,
+                index: U256::from(index),
+                // Games are listed newest first, addresses numbered from 1
+                proxy: Address::repeat_byte(4 - index as u8),
+                l2_block,
+            })
+        };
+
+        let found = locate_covering_game(&reader, FACTORY, &games, 150)
+            .await
+            .unwrap();
+        assert_eq!(found, covering(1, 200));
+
+        let found = locate_covering_game(&reader, FACTORY, &games, 300)
+            .await
+            .unwrap();
+        assert_eq!(found, covering(2, 300));
+
+        let found = locate_covering_game(&reader, FACTORY, &games, 50)
+            .await
+            .unwrap();
+        assert_eq!(found, covering(0, 100));
+    }
+
+    #[tokio::test]
+    async fn test_select_covering_game_by_strategy() {
+        let (games, mut reader) = games(&[500, 400, 300, 200, 100]);
+        async fn select(
+            reader: &MockReader,
+            games: &[GameSearchResult],
+            strategy: GameSelectionStrategy,
+        ) -> Option<(u64, u64)> {
+            select_covering_game(reader, FACTORY, games, 150, strategy)
+                .await
+                .unwrap()
+                .map(|game| (game.index.to::<u64>(), game.l2_block))
+        }
+
+        assert_eq!(
+            select(&reader, &games, GameSelectionStrategy::OldestCovering).await,
+            Some((1, 200))
+        );
+        assert_eq!(
+            select(&reader, &games, GameSelectionStrategy::NewestCovering).await,
+            Some((4, 500))
+        );
+        // Nothing resolved yet: the oldest covering game
+        assert_eq!(
+            select(
+                &reader,
+                &games,
+                GameSelectionStrategy::MostAdvancedTowardResolution
+            )
+            .await,
+            Some((1, 200))
+        );
+
+        // Games are listed newest first, addresses numbered from 1: game 1 is at 4
+        reader
+            .1
+            .insert(Address::repeat_byte(4), GAME_STATUS_CHALLENGER_WINS);
+        assert_eq!(
+            select(
+                &reader,
+                &games,
+                GameSelectionStrategy::MostAdvancedTowardResolution
+            )
+            .await,
+            Some((2, 300))
+        );
+        // The challenger won the game each strategy would pick first
+        assert_eq!(
+            select(&reader, &games, GameSelectionStrategy::OldestCovering).await,
+            Some((2, 300))
+        );
+        reader
+            .1
+            .insert(Address::repeat_byte(1), GAME_STATUS_CHALLENGER_WINS);
+        assert_eq!(
+            select(&reader, &games, GameSelectionStrategy::NewestCovering).await,
+            Some((3, 400))
+        );
+        reader
+            .1
+            .insert(Address::repeat_byte(2), GAME_STATUS_DEFENDER_WINS);
+        assert_eq!(
+            select(
+                &reader,
+                &games,
+                GameSelectionStrategy::MostAdvancedTowardResolution
+            )
+            .await,
+            Some((3, 400))
+        );
+
+        // The challenger won every covering game
+        for byte in 1..=4 {
+            reader
+                .1
+                .insert(Address::repeat_byte(byte), GAME_STATUS_CHALLENGER_WINS);
+        }
+        assert!(select_covering_game(
+            &reader,
+            FACTORY,
+            &games,
+            150,
+            GameSelectionStrategy::MostAdvancedTowardResolution
+        )
+        .await
+        .is_err());
+        assert!(select_covering_game(
+            &reader,
+            FACTORY,
+            &games,
+            150,
+            GameSelectionStrategy::OldestCovering
+        )
+        .await
+        .is_err());
+
+        // Even the newest game is behind the withdrawal
+        let newest = select_covering_game(
+            &reader,
+            FACTORY,
+            &games,
+            501,
+            GameSelectionStrategy::NewestCovering,
+        )
+        .await
+        .unwrap();
+        assert_eq!(newest, None);
+    }
+
+    #[tokio::test]
+    async fn test_locate_covering_game_none_when_too_new() {
+        let (games, reader) = games(&[400, 300]);
+
+        let found = locate_covering_game(&reader, FACTORY, &games, 401)
+            .await
+            .unwrap();
+        assert_eq!(found, None);
+    }
+
+    #[tokio::test]
+    async fn test_measure_game_cadence() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let factory = IDisputeGameFactory::new(FACTORY, &provider);
+        // One late proposal among evenly spaced ones is averaged out
+        let (mut newest, reader) = games(&[3_600, 2_800, 2_000, 1_200]);
+        for (game, timestamp) in newest.iter_mut().zip([13_600, 10_000, 4_000, 2_800]) {
+            game.timestamp = U256::from(timestamp);
+        }
+
+        asserter.push_success(&Bytes::from(
+            IDisputeGameFactory::findLatestGamesCall::abi_encode_returns(&newest),
+        ));
+        let cadence = measure_game_cadence(&factory, &reader, 0, U256::from(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            cadence,
+            GameCadence {
+                interval_secs: 3_600,
+                l2_blocks_per_game: 800,
+                newest_l2_block: 3_600,
+                newest_created_at: 13_600,
+            }
+        );
+        assert!(asserter.read_q().is_empty());
+
+        // A single game has no cadence
+        asserter.push_success(&Bytes::from(
+            IDisputeGameFactory::findLatestGamesCall::abi_encode_returns(&newest[..1].to_vec()),
+        ));
+        let cadence = measure_game_cadence(&factory, &reader, 0, U256::ZERO)
+            .await
+            .unwrap();
+        assert_eq!(cadence, None);
+    }
+
+    #[test]
+    fn test_game_cadence_lookback() {
+        let cadence = GameCadence {
+            interval_secs: 3_600,
+            l2_blocks_per_game: 1_800,
+            newest_l2_block: 1_000_000,
+            newest_created_at: 50_000,
+        };
+
+        // Covered by the newest game
+        assert_eq!(cadence.lookback(1_000_000), GAME_LOOKBACK_MARGIN + 1);
+        assert_eq!(cadence.age_secs(1_000_000), 0);
+
+        // Two days of games behind
+        let withdrawal = 1_000_000 - 48 * 1_800;
+        assert_eq!(cadence.age_secs(withdrawal), 48 * 3_600);
+        assert_eq!(
+            cadence.lookback(withdrawal),
+            48 + 12 + GAME_LOOKBACK_MARGIN + 1
+        );
+
+        // Never more than the cap, however old
+        let cadence = GameCadence {
+            newest_l2_block: 10_000_000,
+            ..cadence
+        };
+        assert_eq!(
+            cadence.lookback(10_000_000 - 2_000 * 1_800),
+            MAX_GAMES_TO_CHECK
+        );
+    }
+
+    #[test]
+    fn test_covering_game_expected_at() {
+        let cadence = GameCadence {
+            interval_secs: 3_600,
+            l2_blocks_per_game: 1_800,
+            newest_l2_block: 1_000_000,
+            newest_created_at: 50_000,
+        };
+
+        // Covered by the next game, or the one after
+        assert_eq!(cadence.covering_game_expected_at(1_000_001), 53_600);
+        assert_eq!(cadence.covering_game_expected_at(1_001_800), 53_600);
+        assert_eq!(cadence.covering_game_expected_at(1_001_801), 57_200);
+        // Already covered
+        assert_eq!(cadence.covering_game_expected_at(999_000), 50_000);
+    }
+
+    fn game_created(proxy: Address, game_type: u32, block_number: u64) -> Log {
+        let event = DisputeGameCreated {
+            disputeProxy: proxy,
+            gameType: game_type,
+            rootClaim: B256::repeat_byte(0xcc),
+        };
+        Log {
+            inner: alloy_primitives::Log {
+                address: FACTORY,
+                data: event.encode_log_data(),
+            },
+            block_number: Some(block_number),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_covering_game() {
+        let chain = FakeChain::new();
+        let behind = Address::repeat_byte(0x01);
+        let other_type = Address::repeat_byte(0x02);
+        let covering = Address::repeat_byte(0x03);
+        let before_wait = Address::repeat_byte(0x04);
+        for (proxy, l2_block) in [
+            (behind, 900),
+            (other_type, 2_000),
+            (covering, 1_800),
+            (before_wait, 1_800),
+        ] {
+            chain.set_call::<IFaultDisputeGame::l2BlockNumberCall>(proxy, &U256::from(l2_block));
+        }
+        chain.add_log(game_created(before_wait, 0, chain.mine(100)));
+        let from_block = chain.mine(112);
+        chain.add_log(game_created(behind, 0, from_block));
+        chain.add_log(game_created(other_type, 1, chain.mine(124)));
+        chain.add_log(game_created(covering, 0, chain.mine(136)));
+
+        let found = wait_for_covering_game(
+            &chain.provider(),
+            FACTORY,
+            0,
+            1_000,
+            from_block,
+            Duration::from_secs(12),
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(found, Some(covering));
+    }
+
+    #[tokio::test]
+    async fn test_find_covering_game_skips_disallowed_game_type() {
+        use binding::opstack::IOptimismPortal2;
+
+        let chain = FakeChain::new();
+        let portal = Address::repeat_byte(0x0d);
+        chain.set_call::<IOptimismPortal2::respectedGameTypeCall>(portal, &1);
+        chain.set_call::<IOptimismPortal2::disputeGameFactoryCall>(portal, &FACTORY);
+        chain.set_call::<IOptimismPortal2::proofMaturityDelaySecondsCall>(
+            portal,
+            &U256::from(604_800),
+        );
+
+        let err = find_covering_game(
+            &chain.provider(),
+            portal,
+            &PortalParamsCache::default(),
+            &GameCache::default(),
+            100,
+            1,
+            &[0],
+            GameSelectionStrategy::default(),
+        )
+        .await
+        .unwrap_err();
+        let not_allowed = err.downcast_ref::<GameTypeNotAllowed>().unwrap();
+        assert_eq!(not_allowed.game_type, 1);
+        assert_eq!(not_allowed.allowed, [0]);
+        // Refused before searching the factory
+        assert_eq!(chain.request_count("eth_call"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_game_cache_serves_clustered_withdrawals() {
+        use binding::opstack::IOptimismPortal2;
+
+        let chain = FakeChain::new();
+        let portal = Address::repeat_byte(0x0d);
+        chain.set_call::<IOptimismPortal2::respectedGameTypeCall>(portal, &0);
+        chain.set_call::<IOptimismPortal2::disputeGameFactoryCall>(portal, &FACTORY);
+        chain.set_call::<IOptimismPortal2::proofMaturityDelaySecondsCall>(
+            portal,
+            &U256::from(604_800),
+        );
+
+        // 16 games, one every 100 L2 blocks, newest first
+        let l2_blocks: Vec<u64> = (1..=16).rev().map(|i| i * 100).collect();
+        let (results, reader) = games(&l2_blocks);
+        chain.set_call::<IDisputeGameFactory::gameCountCall>(FACTORY, &U256::from(16));
+        chain.set_call::<IDisputeGameFactory::findLatestGamesCall>(FACTORY, &results);
+        for (proxy, l2_block) in reader.0 {
+            chain.set_call::<IFaultDisputeGame::l2BlockNumberCall>(proxy, &U256::from(l2_block));
+            chain.set_call::<IFaultDisputeGame::statusCall>(proxy, &0);
+        }
+
+        let portal_params = PortalParamsCache::new(Duration::from_secs(60));
+        let cache = GameCache::default();
+        let provider = chain.provider();
+        let find = |l2_block| {
+            find_covering_game(
+                &provider,
+                portal,
+                &portal_params,
+                &cache,
+                l2_block,
+                1,
+                &[],
+                GameSelectionStrategy::default(),
+            )
+        };
+
+        // Withdrawals from nearby L2 blocks binary search through the same games
+        let first = find(1_201).await.unwrap();
+        assert_eq!(first.l2_block, 1_300);
+        let searched = cache.stats().misses;
+        assert!(searched > 0);
+
+        for l2_block in 1_202..=1_210 {
+            assert_eq!(find(l2_block).await.unwrap(), first);
+        }
+        let stats = cache.stats();
+        assert_eq!(stats.misses, searched);
+        assert_eq!(stats.hits, 9 * searched);
+        assert_eq!(stats.hit_rate(), Some(0.9));
+
+        // A withdrawal covered by another game only reads the games it hasn't seen
+        assert_eq!(find(250).await.unwrap().l2_block, 300);
+        assert!(cache.stats().misses < 2 * searched);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_covering_game_times_out() {
+        let chain = FakeChain::new();
+        let behind = Address::repeat_byte(0x01);
+        chain.set_call::<IFaultDisputeGame::l2BlockNumberCall>(behind, &U256::from(900));
+        chain.add_log(game_created(behind, 0, chain.mine(100)));
+
+        let started = Instant::now();
+        let found = wait_for_covering_game(
+            &chain.provider(),
+            FACTORY,
+            0,
+            1_000,
+            0,
+            Duration::from_millis(10),
+            Duration::from_millis(50),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(found, None);
+        assert!(started.elapsed() < Duration::from_secs(1));
+        // Polled until the timeout, each time only for blocks not searched yet
+        assert!(chain.request_count("eth_blockNumber") > 1);
+        assert_eq!(chain.request_count("eth_getLogs"), 1);
+    }
+
+    #[test]
+    fn test_game_selection_strategy_names() {
+        for strategy in [
+            GameSelectionStrategy::OldestCovering,
+            GameSelectionStrategy::NewestCovering,
+            GameSelectionStrategy::MostAdvancedTowardResolution,
+        ] {
+            assert_eq!(strategy.as_str().parse(), Ok(strategy));
+        }
+        assert!("oldest".parse::<GameSelectionStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_is_method_unsupported() {
+        assert!(is_method_unsupported(METHOD_NOT_FOUND, "whatever"));
+        assert!(is_method_unsupported(
+            -32000,
+            "the method eth_getProof does not exist/is not available"
+        ));
+        assert!(is_method_unsupported(-32000, "Method not found"));
+        assert!(!is_method_unsupported(-32000, "header not found"));
+        assert!(!is_method_unsupported(-32602, "invalid params"));
+    }
+
+    #[test]
+    fn test_get_proof_unsupported_message() {
+        let err: eyre::Report = GetProofUnsupported {
+            message: "Method not found".to_string(),
+        }
+        .into();
+
+        assert!(err.downcast_ref::<GetProofUnsupported>().is_some());
+        assert!(err.to_string().contains("archive"));
+    }
+
     #[test]
     fn test_compute_storage_slot_real_example() {
         // Test with a real withdrawal hash pattern
@@ -374,4 +1943,38 @@ mod tests {
         // Verify it's not zero (would indicate a bug)
         assert_ne!(slot, B256::ZERO);
     }
+
+    #[tokio::test]
+    async fn test_storage_layout_check() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let hash = B256::repeat_byte(1);
+
+        // The slot of an initiated withdrawal is unset: the layout moved
+        let check = StorageLayoutCheck::default();
+        asserter.push_success(&U256::ZERO);
+        let err = check
+            .ensure(&provider, MESSAGE_PASSER_ADDRESS, hash)
+            .await
+            .unwrap_err();
+        let mismatch = err
+            .downcast_ref::<StorageLayoutMismatch>()
+            .expect("should downcast");
+        assert_eq!(mismatch.slot, compute_storage_slot(hash));
+        assert!(!check.is_verified());
+
+        // Set as expected, after which the check isn't repeated
+        asserter.push_success(&U256::from(1));
+        check
+            .ensure(&provider, MESSAGE_PASSER_ADDRESS, hash)
+            .await
+            .unwrap();
+        check
+            .clone()
+            .ensure(&provider, MESSAGE_PASSER_ADDRESS, hash)
+            .await
+            .unwrap();
+        assert!(check.is_verified());
+        assert!(asserter.read_q().is_empty());
+    }
 }
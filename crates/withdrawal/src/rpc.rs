@@ -0,0 +1,24 @@
+//! Optional tracing spans around RPC calls.
+//!
+//! With the `trace_rpc` feature, [`traced`] runs a call inside an `rpc` span named after
+//! the method, so a trace shows how long each call took relative to the work around
+//! it (e.g. `eth_getProof` against the dispute game search within one prove). Without
+//! the feature the call is awaited as is.
+
+use std::future::IntoFuture;
+
+/// Await `call`, inside an `rpc` span when the `trace_rpc` feature is enabled.
+#[cfg(feature = "trace_rpc")]
+pub async fn traced<F: IntoFuture>(method: &'static str, call: F) -> F::Output {
+    use tracing::Instrument;
+
+    call.into_future()
+        .instrument(tracing::info_span!("rpc", method))
+        .await
+}
+
+/// Await `call`, inside an `rpc` span when the `trace_rpc` feature is enabled.
+#[cfg(not(feature = "trace_rpc"))]
+pub async fn traced<F: IntoFuture>(_method: &'static str, call: F) -> F::Output {
+    call.await
+}
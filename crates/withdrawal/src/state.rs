@@ -1,16 +1,59 @@
 use crate::{
-    hash::compute_withdrawal_hash,
-    types::{WithdrawalHash, WithdrawalStatus},
+    finalized::FinalizedSet,
+    hash::{verify_withdrawal_hash, WithdrawalHashMismatch},
+    portal::PortalParamsCache,
+    rpc::traced,
+    submitter::{select_proof, ProofCandidate},
+    types::{L1WithdrawalEvent, L1WithdrawalEventKind, WithdrawalHash, WithdrawalStatus},
 };
 use alloy_contract::private::Provider;
-use alloy_primitives::Address;
-use alloy_rpc_types_eth::BlockNumberOrTag;
+use alloy_primitives::{Address, TxHash, U256};
+use alloy_rpc_types_eth::{BlockNumberOrTag, Filter, Log, TransactionReceipt};
+use alloy_sol_types::SolEvent;
 use binding::opstack::{
-    IL2ToL1MessagePasser, IOptimismPortal2, IOptimismPortal2::ProvenWithdrawal,
+    IDisputeGame,
+    IL2ToL1MessagePasser::{self, MessagePassed},
+    IOptimismPortal2,
+    IOptimismPortal2::{ProvenWithdrawal, WithdrawalFinalized, WithdrawalProven},
     WithdrawalTransaction,
 };
-use tokio_retry::{strategy::ExponentialBackoff, Retry};
-use tracing::{debug, error, warn};
+use client::{
+    coverage::{RangeCoverage, ScanCoverage},
+    logs::{retry_bisecting, LogLimitMatcher},
+    retry::RetryBudget,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    time::Instant,
+};
+use tracing::{debug, error, info, warn};
+
+/// Block range size for chunked `eth_getLogs` scans (500 block safety margin for RPC limits).
+const CHUNK_SIZE: u64 = 9_500;
+
+/// Log scan progress at info level every this many chunks.
+const PROGRESS_EVERY_CHUNKS: u64 = 10;
+
+/// Withdrawals per Multicall3 batch when querying statuses in bulk.
+const STATUS_BATCH_SIZE: usize = 250;
+
+/// Proof submitters whose proofs are read per withdrawal.
+///
+/// Anyone can prove a withdrawal, so the portal's list of submitters has no bound.
+/// Proofs mature in the order they were submitted, so the first ones listed are read.
+const MAX_PROOF_SUBMITTERS: u64 = 16;
+
+/// Result of a chunked scan that may have stopped early.
+#[derive(Debug)]
+pub struct ChunkedScan<T> {
+    /// Items found in the chunks that were scanned
+    pub items: Vec<T>,
+    /// Last block that was scanned successfully, `None` if the first chunk failed
+    pub scanned_to: Option<u64>,
+    /// Whether the scan covered the whole requested range
+    pub complete: bool,
+}
 
 #[allow(dead_code)]
 pub struct WithdrawalStateProvider<P1, P2> {
@@ -18,9 +61,20 @@ pub struct WithdrawalStateProvider<P1, P2> {
     l2_provider: P2,
     portal_address: Address,
     message_passer_address: Address,
+    /// Retries available to chunk scans, shared with other users of the handle
+    retry_budget: RetryBudget,
+    /// Errors meaning a log query matched more results than the RPC returns
+    log_limit_matcher: LogLimitMatcher,
+    /// Withdrawals known to be finalized, whose status is not read from the portal
+    finalized: FinalizedSet,
+    /// Blocks covered by chunk scans, shared with other users of the handle
+    scan_coverage: ScanCoverage,
+    /// The portal's respected game type, shared with other users of the cache
+    portal_params: PortalParamsCache,
 }
 
 #[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct PendingWithdrawal {
     pub transaction: WithdrawalTransaction,
     pub hash: WithdrawalHash,
@@ -28,13 +82,38 @@ pub struct PendingWithdrawal {
     pub status: WithdrawalStatus,
 }
 
+impl PendingWithdrawal {
+    /// Check that `hash` is the hash of `transaction`.
+    pub fn validate(&self) -> Result<(), WithdrawalHashMismatch> {
+        verify_withdrawal_hash(&self.transaction, self.hash)
+    }
+}
+
+/// A withdrawal initiation decoded from an L2 `MessagePassed` event.
+#[derive(Debug, Clone)]
+pub struct InitiatedWithdrawal {
+    pub transaction: WithdrawalTransaction,
+    pub hash: WithdrawalHash,
+    pub l2_block: u64,
+    /// L2 transaction that emitted the event, if the node returned it
+    pub tx_hash: Option<TxHash>,
+}
+
+impl InitiatedWithdrawal {
+    /// Check that `hash` is the hash of `transaction`.
+    pub fn validate(&self) -> Result<(), WithdrawalHashMismatch> {
+        verify_withdrawal_hash(&self.transaction, self.hash)
+    }
+}
+
 #[allow(dead_code)]
 impl<P1, P2> WithdrawalStateProvider<P1, P2>
 where
     P1: Provider + Clone,
     P2: Provider + Clone,
 {
-    pub const fn new(
+    /// Create a provider whose scans retry without a shared budget.
+    pub fn new(
         l1_provider: P1,
         l2_provider: P2,
         portal_address: Address,
@@ -45,25 +124,252 @@ where
             l2_provider,
             portal_address,
             message_passer_address,
+            retry_budget: RetryBudget::unlimited(),
+            log_limit_matcher: LogLimitMatcher::default(),
+            finalized: FinalizedSet::default(),
+            scan_coverage: ScanCoverage::default(),
+            portal_params: PortalParamsCache::default(),
         }
     }
 
+    /// Draw chunk scan retries from `budget`, e.g. one shared across a cycle.
+    pub fn with_retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = budget;
+        self
+    }
+
+    /// Recognize the RPC's log result limit errors with `matcher` instead of the default.
+    pub fn with_log_limit_matcher(mut self, matcher: LogLimitMatcher) -> Self {
+        self.log_limit_matcher = matcher;
+        self
+    }
+
+    /// Skip portal reads for withdrawals in `set`, and add those observed as finalized.
+    pub fn with_finalized_set(mut self, set: FinalizedSet) -> Self {
+        self.finalized = set;
+        self
+    }
+
+    /// Add the blocks covered by chunk scans to `coverage`, e.g. one shared across a cycle.
+    pub fn with_scan_coverage(mut self, coverage: ScanCoverage) -> Self {
+        self.scan_coverage = coverage;
+        self
+    }
+
+    /// Read the portal's respected game type through `cache` rather than every time.
+    pub fn with_portal_params(mut self, cache: PortalParamsCache) -> Self {
+        self.portal_params = cache;
+        self
+    }
+
+    /// OptimismPortal2 the provider reads statuses from.
+    pub const fn portal_address(&self) -> Address {
+        self.portal_address
+    }
+
+    /// L2ToL1MessagePasser the provider scans for initiations.
+    pub const fn message_passer_address(&self) -> Address {
+        self.message_passer_address
+    }
+
+    /// Current status of a withdrawal.
+    ///
+    /// Proofs by every submitter are considered, not just `withdrawal_initiator`'s: a
+    /// withdrawal is `Proven` by the valid proof that matures first (see
+    /// [`select_proof`]), and `Initiated` if no valid proof exists. Withdrawals in the
+    /// [`FinalizedSet`] are `Finalized` without reading the portal.
     pub async fn query_withdrawal_status(
         &self,
         hash: WithdrawalHash,
         withdrawal_initiator: Address,
     ) -> eyre::Result<WithdrawalStatus> {
+        if self.finalized.contains(hash) {
+            return Ok(WithdrawalStatus::Finalized);
+        }
         if self.is_finalized(hash).await? {
+            self.finalized.insert(hash);
             return Ok(WithdrawalStatus::Finalized);
         }
 
-        if let Some(proven) = self.is_proven(hash, withdrawal_initiator).await? {
-            return Ok(WithdrawalStatus::Proven {
-                timestamp: proven.timestamp,
-            });
+        self.proven_status(hash, withdrawal_initiator).await
+    }
+
+    /// Status of a withdrawal that isn't finalized, from every proof submitted for it.
+    async fn proven_status(
+        &self,
+        hash: WithdrawalHash,
+        also_check: Address,
+    ) -> eyre::Result<WithdrawalStatus> {
+        let candidates = self.proof_candidates(hash, also_check).await?;
+        if candidates.is_empty() {
+            return Ok(WithdrawalStatus::Initiated);
+        }
+
+        let respected_game_type = self
+            .portal_params
+            .get(&self.l1_provider, self.portal_address)
+            .await?
+            .respected_game_type;
+        let status = status_from(false, &candidates, respected_game_type);
+        if status == WithdrawalStatus::Initiated {
+            debug!(
+                withdrawal_hash = %hash,
+                proofs = candidates.len(),
+                "No valid proof for withdrawal"
+            );
+        }
+        Ok(status)
+    }
+
+    /// Every proof submitted for `hash`, with the state of its dispute game.
+    ///
+    /// Submitters are enumerated from the portal, at most [`MAX_PROOF_SUBMITTERS`] of
+    /// them; `also_check` (typically our own address) is always included.
+    pub async fn proof_candidates(
+        &self,
+        hash: WithdrawalHash,
+        also_check: Address,
+    ) -> eyre::Result<Vec<ProofCandidate>> {
+        let portal = IOptimismPortal2::new(self.portal_address, &self.l1_provider);
+
+        let count = portal
+            .numProofSubmitters(hash)
+            .call()
+            .await?
+            .saturating_to::<u64>();
+        if count > MAX_PROOF_SUBMITTERS {
+            warn!(
+                withdrawal_hash = %hash,
+                submitters = count,
+                read = MAX_PROOF_SUBMITTERS,
+                "Withdrawal has more proof submitters than are read"
+            );
+        }
+        let mut submitters = Vec::new();
+        for index in 0..count.min(MAX_PROOF_SUBMITTERS) {
+            let submitter = portal
+                .proofSubmitters(hash, U256::from(index))
+                .call()
+                .await?;
+            if !submitters.contains(&submitter) {
+                submitters.push(submitter);
+            }
+        }
+        if !submitters.contains(&also_check) {
+            submitters.push(also_check);
         }
 
-        Ok(WithdrawalStatus::Initiated)
+        let mut candidates = vec![];
+        for submitter in submitters {
+            let Some(proven) = self.is_proven(hash, submitter).await? else {
+                continue;
+            };
+            candidates.push(
+                self.proof_candidate(submitter, proven.disputeGameProxy, proven.timestamp)
+                    .await?,
+            );
+        }
+
+        Ok(candidates)
+    }
+
+    /// The proof `submitter` submitted against `dispute_game_proxy` at `timestamp`, with
+    /// the current state of its dispute game.
+    pub async fn proof_candidate(
+        &self,
+        submitter: Address,
+        dispute_game_proxy: Address,
+        timestamp: u64,
+    ) -> eyre::Result<ProofCandidate> {
+        let portal = IOptimismPortal2::new(self.portal_address, &self.l1_provider);
+        let game = IDisputeGame::new(dispute_game_proxy, &self.l1_provider);
+        Ok(ProofCandidate {
+            submitter,
+            dispute_game_proxy,
+            timestamp,
+            game_type: game.gameType().call().await?,
+            game_status: game.status().call().await?,
+            blacklisted: portal
+                .disputeGameBlacklist(dispute_game_proxy)
+                .call()
+                .await?,
+        })
+    }
+
+    /// The valid proof for `hash` that matures first, if any. See [`select_proof`].
+    pub async fn best_proof(
+        &self,
+        hash: WithdrawalHash,
+        also_check: Address,
+        respected_game_type: u32,
+    ) -> eyre::Result<Option<ProofCandidate>> {
+        let candidates = self.proof_candidates(hash, also_check).await?;
+        Ok(select_proof(&candidates, respected_game_type))
+    }
+
+    /// Query the status of many withdrawals at once.
+    ///
+    /// Batches `finalizedWithdrawals`, `numProofSubmitters` and `withdrawal_initiator`'s
+    /// `provenWithdrawals` reads through Multicall3, so thousands of withdrawals take a
+    /// handful of `eth_call`s. Only withdrawals someone proved have their proofs read
+    /// one by one, as in [`Self::query_withdrawal_status`]. Statuses are returned in the
+    /// order of `hashes`.
+    pub async fn query_withdrawal_statuses(
+        &self,
+        hashes: &[WithdrawalHash],
+        withdrawal_initiator: Address,
+    ) -> eyre::Result<Vec<WithdrawalStatus>> {
+        let portal = IOptimismPortal2::new(self.portal_address, &self.l1_provider);
+        let mut statuses = Vec::with_capacity(hashes.len());
+
+        for batch in hashes.chunks(STATUS_BATCH_SIZE) {
+            let finalized = self
+                .l1_provider
+                .multicall()
+                .dynamic()
+                .extend(batch.iter().map(|hash| portal.finalizedWithdrawals(*hash)))
+                .aggregate()
+                .await?;
+            let submitters = self
+                .l1_provider
+                .multicall()
+                .dynamic()
+                .extend(batch.iter().map(|hash| portal.numProofSubmitters(*hash)))
+                .aggregate()
+                .await?;
+            let proven = self
+                .l1_provider
+                .multicall()
+                .dynamic()
+                .extend(
+                    batch
+                        .iter()
+                        .map(|hash| portal.provenWithdrawals(*hash, withdrawal_initiator)),
+                )
+                .aggregate()
+                .await?;
+
+            for (((hash, finalized), submitters), proven) in
+                batch.iter().zip(finalized).zip(submitters).zip(proven)
+            {
+                let status = if finalized {
+                    WithdrawalStatus::Finalized
+                } else if submitters.is_zero() && proven.timestamp == 0 {
+                    WithdrawalStatus::Initiated
+                } else {
+                    self.proven_status(*hash, withdrawal_initiator).await?
+                };
+                statuses.push(status);
+            }
+
+            debug!(
+                queried = statuses.len(),
+                total = hashes.len(),
+                "Queried withdrawal statuses"
+            );
+        }
+
+        Ok(statuses)
     }
 
     /// Get all pending withdrawals from L2 events in the given block range.
@@ -75,12 +381,12 @@ where
     /// 1. Resolves `Latest` to concrete block numbers immediately (handles load balancer inconsistency)
     /// 2. Chunks requests into 9,500 block ranges (with 500 block safety margin)
     /// 3. Filters for withdrawals initiated by `withdrawal_initiator` address
-    /// 4. Queries L1 to check if the withdrawal has been proven by `withdrawal_initiator`
+    /// 4. Queries L1 for the best valid proof of each withdrawal, by any submitter
     /// 5. Retries failed chunks with exponential backoff
     ///
     /// The `withdrawal_initiator` parameter serves dual purpose:
     /// - Filters L2 events to only withdrawals where `sender == withdrawal_initiator`
-    /// - Checks L1 proven status for proofs submitted by `withdrawal_initiator`
+    /// - Is always among the submitters whose proofs are checked on L1
     ///
     /// The safety margin and chunking handle RPC providers that may be slightly out of sync
     /// when behind a load balancer.
@@ -92,8 +398,8 @@ where
     ) -> eyre::Result<Vec<PendingWithdrawal>> {
         // CRITICAL: Resolve both endpoints to concrete block numbers FIRST
         // This creates a consistent snapshot and prevents load balancer issues
-        let from_block_num = self.resolve_block_number(from_block).await?;
-        let to_block_num = self.resolve_block_number(to_block).await?;
+        let from_block_num = resolve_block_number(&self.l2_provider, from_block).await?;
+        let to_block_num = resolve_block_number(&self.l2_provider, to_block).await?;
 
         if from_block_num > to_block_num {
             return Err(eyre::eyre!(
@@ -109,79 +415,437 @@ where
             "Scanning for withdrawals (snapshot taken)"
         );
 
-        self.scan_chunks(from_block_num, to_block_num, withdrawal_initiator)
-            .await
+        let scan = self
+            .scan_chunks(from_block_num, to_block_num, withdrawal_initiator, false)
+            .await?;
+        Ok(scan.items)
     }
 
-    /// Resolve BlockNumberOrTag to a concrete block number.
-    async fn resolve_block_number(&self, block: BlockNumberOrTag) -> eyre::Result<u64> {
-        match block {
-            BlockNumberOrTag::Number(n) => Ok(n),
-            BlockNumberOrTag::Latest => {
-                let block_num = self.l2_provider.get_block_number().await?;
-                Ok(block_num)
-            }
-            _ => Err(eyre::eyre!("Unsupported block tag: {:?}", block)),
+    /// Like [`Self::get_pending_withdrawals`], but with `tolerate_partial` a chunk that
+    /// exhausts its retries ends the scan instead of failing it.
+    ///
+    /// The withdrawals found so far are returned along with the last block scanned, so
+    /// the caller can act on them and pick up after [`ChunkedScan::scanned_to`] later.
+    /// Only use this where acting on an incomplete view is acceptable.
+    pub async fn scan_pending_withdrawals(
+        &self,
+        from_block: BlockNumberOrTag,
+        to_block: BlockNumberOrTag,
+        withdrawal_initiator: Address,
+        tolerate_partial: bool,
+    ) -> eyre::Result<ChunkedScan<PendingWithdrawal>> {
+        let from_block_num = resolve_block_number(&self.l2_provider, from_block).await?;
+        let to_block_num = resolve_block_number(&self.l2_provider, to_block).await?;
+
+        if from_block_num > to_block_num {
+            return Err(eyre::eyre!(
+                "from_block ({}) must be <= to_block ({})",
+                from_block_num,
+                to_block_num
+            ));
         }
+
+        self.scan_chunks(
+            from_block_num,
+            to_block_num,
+            withdrawal_initiator,
+            tolerate_partial,
+        )
+        .await
     }
 
-    /// Scan blocks in chunks with retry logic.
-    async fn scan_chunks(
+    /// Get every withdrawal initiated by `withdrawal_initiator` in the given L2 block
+    /// range, including finalized ones, with statuses from
+    /// [`Self::query_withdrawal_statuses`].
+    ///
+    /// Meant for one-off discovery over long ranges: L2 is scanned first without any
+    /// per-withdrawal L1 calls, then all statuses are fetched in batches.
+    pub async fn get_withdrawals_batched(
         &self,
         from_block: u64,
         to_block: u64,
         withdrawal_initiator: Address,
     ) -> eyre::Result<Vec<PendingWithdrawal>> {
-        // Use 9,500 block chunks (500 block safety margin for RPC limits)
-        const CHUNK_SIZE: u64 = 9_500;
+        let coverage = self.scan_coverage.track("message_passed", from_block);
+        let scan = scan_in_chunks(&coverage, from_block, to_block, false, |from, to| {
+            self.scan_message_passed_with_retry(&coverage, from, to, withdrawal_initiator)
+        })
+        .await?;
 
-        let mut all_withdrawals = Vec::new();
-        let mut current = from_block;
+        let hashes: Vec<WithdrawalHash> = scan.items.iter().map(|w| w.hash).collect();
+        let statuses = self
+            .query_withdrawal_statuses(&hashes, withdrawal_initiator)
+            .await?;
 
-        while current <= to_block {
-            let chunk_end = (current + CHUNK_SIZE - 1).min(to_block);
+        Ok(scan
+            .items
+            .into_iter()
+            .zip(statuses)
+            .map(|(initiated, status)| PendingWithdrawal {
+                transaction: initiated.transaction,
+                hash: initiated.hash,
+                l2_block: initiated.l2_block,
+                status,
+            })
+            .collect())
+    }
 
-            debug!(
-                from = current,
-                to = chunk_end,
-                "Scanning chunk for withdrawals"
-            );
+    /// Get every withdrawal initiated by `withdrawal_initiator` in the given L2 block
+    /// range, with the L2 transaction that initiated it. Makes no L1 calls.
+    pub async fn get_initiated_withdrawals(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        withdrawal_initiator: Address,
+    ) -> eyre::Result<Vec<InitiatedWithdrawal>> {
+        let coverage = self.scan_coverage.track("message_passed", from_block);
+        let scan = scan_in_chunks(&coverage, from_block, to_block, false, |from, to| {
+            self.scan_message_passed_with_retry(&coverage, from, to, withdrawal_initiator)
+        })
+        .await?;
+
+        Ok(scan.items)
+    }
 
-            // Retry chunk with exponential backoff on failure
-            let chunk_withdrawals = self
-                .scan_chunk_with_retry(current, chunk_end, withdrawal_initiator)
+    /// Every withdrawal initiated by L2 transaction `tx_hash`, with its current status.
+    ///
+    /// A transaction can initiate several withdrawals, so all of its `MessagePassed`
+    /// events are returned, in log order, whoever their sender. Statuses are as in
+    /// [`Self::query_withdrawal_status`].
+    pub async fn get_withdrawals_by_tx_hash(
+        &self,
+        tx_hash: TxHash,
+        withdrawal_initiator: Address,
+    ) -> eyre::Result<Vec<PendingWithdrawal>> {
+        let receipt = traced(
+            "eth_getTransactionReceipt",
+            self.l2_provider.get_transaction_receipt(tx_hash),
+        )
+        .await?
+        .ok_or_else(|| eyre::eyre!("Transaction {tx_hash} not found"))?;
+
+        let mut withdrawals = vec![];
+        for initiated in initiated_withdrawals_in_receipt(&receipt, self.message_passer_address)? {
+            let status = self
+                .query_withdrawal_status(initiated.hash, withdrawal_initiator)
                 .await?;
+            withdrawals.push(PendingWithdrawal {
+                transaction: initiated.transaction,
+                hash: initiated.hash,
+                l2_block: initiated.l2_block,
+                status,
+            });
+        }
+        Ok(withdrawals)
+    }
 
-            all_withdrawals.extend(chunk_withdrawals);
+    /// Get the hashes of all withdrawals initiated by `withdrawal_initiator` in the
+    /// given L2 block range, regardless of their L1 status.
+    ///
+    /// Unlike [`Self::get_pending_withdrawals`] this makes no L1 calls, so it is cheap
+    /// enough to use as the reference set when cross-checking L1 events.
+    pub async fn get_initiated_hashes(
+        &self,
+        from_block: BlockNumberOrTag,
+        to_block: BlockNumberOrTag,
+        withdrawal_initiator: Address,
+    ) -> eyre::Result<HashSet<WithdrawalHash>> {
+        let from_block_num = resolve_block_number(&self.l2_provider, from_block).await?;
+        let to_block_num = resolve_block_number(&self.l2_provider, to_block).await?;
+
+        let mut hashes = HashSet::new();
+        let coverage = self.scan_coverage.track("message_passed", from_block_num);
+        let mut current = from_block_num;
+
+        while current <= to_block_num {
+            let chunk_end = (current + CHUNK_SIZE - 1).min(to_block_num);
+
+            let initiated = self
+                .scan_message_passed_with_retry(&coverage, current, chunk_end, withdrawal_initiator)
+                .await?;
+
+            hashes.extend(initiated.into_iter().map(|w| w.hash));
             current = chunk_end + 1;
         }
 
-        Ok(all_withdrawals)
+        Ok(hashes)
     }
 
-    /// Scan a single chunk with retry and exponential backoff.
-    async fn scan_chunk_with_retry(
+    /// Scan OptimismPortal2 for `WithdrawalProven` and `WithdrawalFinalized` events.
+    ///
+    /// This gives an L1-side audit trail that is independent of the L2 MessagePassed scan,
+    /// and also surfaces withdrawals proven by other parties.
+    ///
+    /// When `l2_sender` is set, proven events are restricted to withdrawals whose `from`
+    /// is `l2_sender`, whoever submitted the proof. Finalized events carry no address and
+    /// are always returned in full; callers should match them against known hashes.
+    ///
+    /// Requests are chunked and retried like the L2 scans. Events are returned in
+    /// block order.
+    pub async fn scan_l1_withdrawal_events(
         &self,
-        from_block: u64,
-        to_block: u64,
+        from_block: BlockNumberOrTag,
+        to_block: BlockNumberOrTag,
+        l2_sender: Option<Address>,
+    ) -> eyre::Result<Vec<L1WithdrawalEvent>> {
+        let from_block_num = resolve_block_number(&self.l1_provider, from_block).await?;
+        let to_block_num = resolve_block_number(&self.l1_provider, to_block).await?;
+
+        if from_block_num > to_block_num {
+            return Err(eyre::eyre!(
+                "from_block ({}) must be <= to_block ({})",
+                from_block_num,
+                to_block_num
+            ));
+        }
+
+        debug!(
+            from = from_block_num,
+            to = to_block_num,
+            l2_sender = ?l2_sender,
+            "Scanning L1 for withdrawal events"
+        );
+
+        let mut all_events = Vec::new();
+        let coverage = self
+            .scan_coverage
+            .track("l1_withdrawal_events", from_block_num);
+        let mut current = from_block_num;
+
+        while current <= to_block_num {
+            let chunk_end = (current + CHUNK_SIZE - 1).min(to_block_num);
+
+            let chunk_events = self
+                .scan_l1_chunk_with_retry(&coverage, current, chunk_end, l2_sender)
+                .await?;
+
+            all_events.extend(chunk_events);
+            current = chunk_end + 1;
+        }
+
+        Ok(all_events)
+    }
+
+    /// Find withdrawals from `withdrawal_initiator` that were proven on L1 but never
+    /// finalized, over ranges much longer than a regular scan.
+    ///
+    /// Proofs are found with an indexed-topic query over the L1 range and checked
+    /// against `finalizedWithdrawals`. The withdrawal transactions are then rebuilt from
+    /// the sender's `MessagePassed` events in `l2_from..=l2_to`, which only happens when
+    /// there is something to recover. Each is recovered with the status of its best
+    /// proof by any submitter, as in [`Self::query_withdrawal_status`]; those without a
+    /// valid proof left are skipped.
+    pub async fn recover_proven_withdrawals(
+        &self,
+        l1_from: BlockNumberOrTag,
+        l1_to: BlockNumberOrTag,
+        l2_from: u64,
+        l2_to: u64,
         withdrawal_initiator: Address,
     ) -> eyre::Result<Vec<PendingWithdrawal>> {
-        // Exponential backoff: 100ms, 200ms, 400ms, 800ms, 1.6s (max 5 attempts)
-        let retry_strategy = ExponentialBackoff::from_millis(100).take(5);
+        let events = self
+            .scan_l1_withdrawal_events(l1_from, l1_to, Some(withdrawal_initiator))
+            .await?;
+
+        let mut unfinalized = HashSet::new();
+        for hash in unfinalized_proven_hashes(&events) {
+            if !self.is_finalized(hash).await? {
+                unfinalized.insert(hash);
+            }
+        }
+
+        if unfinalized.is_empty() {
+            return Ok(vec![]);
+        }
+
+        info!(
+            count = unfinalized.len(),
+            l2_from, l2_to, "Locating proven but unfinalized withdrawals on L2"
+        );
+
+        let mut recovered = vec![];
+        let mut located = 0;
+        let coverage = self.scan_coverage.track("message_passed", l2_from);
+        let mut current = l2_from;
+
+        while current <= l2_to && located < unfinalized.len() {
+            let chunk_end = (current + CHUNK_SIZE - 1).min(l2_to);
+
+            let initiated = self
+                .scan_message_passed_with_retry(&coverage, current, chunk_end, withdrawal_initiator)
+                .await?;
+
+            for InitiatedWithdrawal {
+                transaction: tx,
+                hash,
+                l2_block,
+                ..
+            } in initiated
+            {
+                if !unfinalized.contains(&hash) {
+                    continue;
+                }
+                located += 1;
+
+                let status = self.proven_status(hash, withdrawal_initiator).await?;
+                if !matches!(status, WithdrawalStatus::Proven { .. }) {
+                    continue;
+                }
+
+                recovered.push(PendingWithdrawal {
+                    transaction: tx,
+                    hash,
+                    l2_block,
+                    status,
+                });
+            }
 
-        Retry::spawn(retry_strategy, || async {
-            self.scan_chunk(from_block, to_block, withdrawal_initiator)
-                .await
-                .map_err(|e| {
+            current = chunk_end + 1;
+        }
+
+        if located < unfinalized.len() {
+            warn!(
+                missing = unfinalized.len() - located,
+                l2_from, "Proven withdrawals not found on L2, initiated before the recovery range"
+            );
+        }
+
+        Ok(recovered)
+    }
+
+    /// Scan a single L1 chunk with retry and exponential backoff,
+    /// splitting it on log result limit errors.
+    async fn scan_l1_chunk_with_retry(
+        &self,
+        coverage: &RangeCoverage,
+        from_block: u64,
+        to_block: u64,
+        l2_sender: Option<Address>,
+    ) -> eyre::Result<Vec<L1WithdrawalEvent>> {
+        retry_bisecting(
+            &self.retry_budget,
+            &self.log_limit_matcher,
+            coverage,
+            from_block,
+            to_block,
+            |from, to| async move {
+                self.scan_l1_chunk(from, to, l2_sender).await.map_err(|e| {
                     warn!(
-                        from = from_block,
-                        to = to_block,
+                        from,
+                        to,
                         error = %e,
-                        "Chunk scan failed, will retry"
+                        "L1 withdrawal event chunk scan failed, will retry"
                     );
                     e
                 })
-        })
+            },
+        )
+        .await
+    }
+
+    /// Scan a single chunk of L1 blocks for portal withdrawal events (no retry logic).
+    async fn scan_l1_chunk(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        l2_sender: Option<Address>,
+    ) -> eyre::Result<Vec<L1WithdrawalEvent>> {
+        let mut proven_filter = Filter::new()
+            .address(self.portal_address)
+            .event_signature(WithdrawalProven::SIGNATURE_HASH)
+            .from_block(from_block)
+            .to_block(to_block);
+        if let Some(l2_sender) = l2_sender {
+            proven_filter = proven_filter.topic2(l2_sender.into_word());
+        }
+
+        let finalized_filter = Filter::new()
+            .address(self.portal_address)
+            .event_signature(WithdrawalFinalized::SIGNATURE_HASH)
+            .from_block(from_block)
+            .to_block(to_block);
+
+        let mut logs = traced("eth_getLogs", self.l1_provider.get_logs(&proven_filter)).await?;
+        logs.extend(traced("eth_getLogs", self.l1_provider.get_logs(&finalized_filter)).await?);
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        let mut events: Vec<L1WithdrawalEvent> =
+            logs.iter().filter_map(decode_l1_withdrawal_event).collect();
+
+        // Not every provider returns `blockTimestamp` on logs; backfill from block headers.
+        let mut timestamps: HashMap<u64, u64> = HashMap::new();
+        for event in events.iter_mut().filter(|e| e.timestamp == 0) {
+            if let Some(timestamp) = timestamps.get(&event.block_number) {
+                event.timestamp = *timestamp;
+                continue;
+            }
+
+            let block = self
+                .l1_provider
+                .get_block_by_number(BlockNumberOrTag::Number(event.block_number))
+                .await?
+                .ok_or_else(|| eyre::eyre!("Block not found: {}", event.block_number))?;
+
+            timestamps.insert(event.block_number, block.header.timestamp);
+            event.timestamp = block.header.timestamp;
+        }
+
+        Ok(events)
+    }
+
+    /// Scan blocks in chunks with retry logic.
+    async fn scan_chunks(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        withdrawal_initiator: Address,
+        tolerate_partial: bool,
+    ) -> eyre::Result<ChunkedScan<PendingWithdrawal>> {
+        let coverage = self.scan_coverage.track("withdrawals", from_block);
+        scan_in_chunks(
+            &coverage,
+            from_block,
+            to_block,
+            tolerate_partial,
+            |from, to| {
+                debug!(from, to, "Scanning chunk for withdrawals");
+
+                // Retry chunk with exponential backoff on failure
+                self.scan_chunk_with_retry(&coverage, from, to, withdrawal_initiator)
+            },
+        )
+        .await
+    }
+
+    /// Scan a single chunk with retry and exponential backoff,
+    /// splitting it on log result limit errors.
+    async fn scan_chunk_with_retry(
+        &self,
+        coverage: &RangeCoverage,
+        from_block: u64,
+        to_block: u64,
+        withdrawal_initiator: Address,
+    ) -> eyre::Result<Vec<PendingWithdrawal>> {
+        retry_bisecting(
+            &self.retry_budget,
+            &self.log_limit_matcher,
+            coverage,
+            from_block,
+            to_block,
+            |from, to| async move {
+                self.scan_chunk(from, to, withdrawal_initiator)
+                    .await
+                    .map_err(|e| {
+                        warn!(
+                            from,
+                            to,
+                            error = %e,
+                            "Chunk scan failed, will retry"
+                        );
+                        e
+                    })
+            },
+        )
         .await
     }
 
@@ -192,61 +856,125 @@ where
         to_block: u64,
         withdrawal_initiator: Address,
     ) -> eyre::Result<Vec<PendingWithdrawal>> {
+        let initiated = self
+            .scan_message_passed(from_block, to_block, withdrawal_initiator)
+            .await?;
+
+        let mut withdrawals = vec![];
+        for InitiatedWithdrawal {
+            transaction: tx,
+            hash,
+            l2_block,
+            ..
+        } in initiated
+        {
+            // Query the current status of this withdrawal
+            let status = self
+                .query_withdrawal_status(hash, withdrawal_initiator)
+                .await?;
+
+            // Skip finalized withdrawals - nothing to do
+            if matches!(status, WithdrawalStatus::Finalized) {
+                continue;
+            }
+
+            withdrawals.push(PendingWithdrawal {
+                transaction: tx,
+                hash,
+                l2_block,
+                status,
+            })
+        }
+
+        Ok(withdrawals)
+    }
+
+    /// Scan a single chunk of MessagePassed events with retry and exponential backoff,
+    /// splitting it on log result limit errors.
+    async fn scan_message_passed_with_retry(
+        &self,
+        coverage: &RangeCoverage,
+        from_block: u64,
+        to_block: u64,
+        withdrawal_initiator: Address,
+    ) -> eyre::Result<Vec<InitiatedWithdrawal>> {
+        retry_bisecting(
+            &self.retry_budget,
+            &self.log_limit_matcher,
+            coverage,
+            from_block,
+            to_block,
+            |from, to| async move {
+                self.scan_message_passed(from, to, withdrawal_initiator)
+                    .await
+                    .map_err(|e| {
+                        warn!(
+                            from,
+                            to,
+                            error = %e,
+                            "MessagePassed chunk scan failed, will retry"
+                        );
+                        e
+                    })
+            },
+        )
+        .await
+    }
+
+    /// Decode MessagePassed events sent by `withdrawal_initiator` in a single chunk.
+    ///
+    /// Returns every withdrawal whose recomputed hash matches the one emitted on chain.
+    async fn scan_message_passed(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        withdrawal_initiator: Address,
+    ) -> eyre::Result<Vec<InitiatedWithdrawal>> {
         let contract = IL2ToL1MessagePasser::new(self.message_passer_address, &self.l2_provider);
 
         let filter = contract
             .MessagePassed_filter()
+            .topic2(withdrawal_initiator.into_word())
             .from_block(from_block)
             .to_block(to_block);
-        let events = filter.query().await?;
+        let events = traced("eth_getLogs", filter.query()).await?;
 
-        let mut withdrawals = vec![];
+        let mut initiated = vec![];
         for (event, log) in events {
             // Filter: only include withdrawals initiated by withdrawal_initiator address
             if event.sender != withdrawal_initiator {
                 continue;
             }
 
-            let tx = WithdrawalTransaction {
-                nonce: event.nonce,
-                sender: event.sender,
-                target: event.target,
-                value: event.value,
-                gasLimit: event.gasLimit,
-                data: event.data,
-            };
+            let tx = WithdrawalTransaction::from(&event);
 
-            let computed_hash = compute_withdrawal_hash(&tx);
-            if computed_hash != event.withdrawalHash {
+            if let Err(mismatch) = verify_withdrawal_hash(&tx, event.withdrawalHash) {
                 error!(
                     block = ?log.block_number,
-                    computed_hash = %computed_hash,
-                    withdrawal_hash = %event.withdrawalHash,
+                    computed_hash = %mismatch.computed,
+                    withdrawal_hash = %mismatch.expected,
                     "Error!: withdrawal hash mismatch for withdrawal"
                 );
                 // allow to continue, don't fail the entire scan.
                 continue;
             }
 
-            // Query the current status of this withdrawal
-            let status = self
-                .query_withdrawal_status(event.withdrawalHash, withdrawal_initiator)
-                .await?;
-
-            // Skip finalized withdrawals - nothing to do
-            if matches!(status, WithdrawalStatus::Finalized) {
-                continue;
-            }
-
-            withdrawals.push(PendingWithdrawal {
+            initiated.push(InitiatedWithdrawal {
                 transaction: tx,
                 hash: event.withdrawalHash,
                 l2_block: log.block_number.unwrap_or_default(),
-                status,
-            })
+                tx_hash: log.transaction_hash,
+            });
         }
 
-        Ok(withdrawals)
+        Ok(initiated)
+    }
+
+    /// Whether the portal is paused. While paused, prove and finalize calls revert.
+    pub async fn is_paused(&self) -> eyre::Result<bool> {
+        let portal = IOptimismPortal2::new(self.portal_address, &self.l1_provider);
+        let paused = portal.paused().call().await?;
+        Ok(paused)
     }
 
     pub async fn is_finalized(&self, hash: WithdrawalHash) -> eyre::Result<bool> {
@@ -276,3 +1004,667 @@ where
         }
     }
 }
+
+/// The part of [`WithdrawalStateProvider`] a withdrawal pass reads: whether the portal
+/// is paused and which withdrawals are pending.
+///
+/// Lets the orchestration built on it be tested against a fixed set of withdrawals.
+pub trait WithdrawalState: Send + Sync {
+    /// Whether the portal is paused. While paused, prove and finalize calls revert.
+    fn is_paused(&self) -> impl Future<Output = eyre::Result<bool>> + Send;
+
+    /// See [`WithdrawalStateProvider::scan_pending_withdrawals`].
+    fn scan_pending_withdrawals(
+        &self,
+        from_block: BlockNumberOrTag,
+        to_block: BlockNumberOrTag,
+        withdrawal_initiator: Address,
+        tolerate_partial: bool,
+    ) -> impl Future<Output = eyre::Result<ChunkedScan<PendingWithdrawal>>> + Send;
+}
+
+impl<P1, P2> WithdrawalState for WithdrawalStateProvider<P1, P2>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    async fn is_paused(&self) -> eyre::Result<bool> {
+        Self::is_paused(self).await
+    }
+
+    async fn scan_pending_withdrawals(
+        &self,
+        from_block: BlockNumberOrTag,
+        to_block: BlockNumberOrTag,
+        withdrawal_initiator: Address,
+        tolerate_partial: bool,
+    ) -> eyre::Result<ChunkedScan<PendingWithdrawal>> {
+        Self::scan_pending_withdrawals(
+            self,
+            from_block,
+            to_block,
+            withdrawal_initiator,
+            tolerate_partial,
+        )
+        .await
+    }
+}
+
+/// Walk `from_block..=to_block` in [`CHUNK_SIZE`] chunks, calling `scan_chunk` on each.
+///
+/// Progress is logged every [`PROGRESS_EVERY_CHUNKS`] chunks. If a chunk fails and
+/// `tolerate_partial` is set, the items found so far are returned with
+/// `complete: false`; otherwise the error is returned. `scan_chunk` records the ranges it
+/// queried in `coverage`, and `scanned_to` never moves past a gap between them.
+async fn scan_in_chunks<T, F, Fut>(
+    coverage: &RangeCoverage,
+    from_block: u64,
+    to_block: u64,
+    tolerate_partial: bool,
+    mut scan_chunk: F,
+) -> eyre::Result<ChunkedScan<T>>
+where
+    F: FnMut(u64, u64) -> Fut,
+    Fut: Future<Output = eyre::Result<Vec<T>>>,
+{
+    let total_blocks = to_block.saturating_sub(from_block) + 1;
+    let started = Instant::now();
+    let mut scan = ChunkedScan {
+        items: Vec::new(),
+        scanned_to: None,
+        complete: false,
+    };
+    let mut chunks = 0u64;
+    let mut current = from_block;
+
+    while current <= to_block {
+        let chunk_end = (current + CHUNK_SIZE - 1).min(to_block);
+
+        match scan_chunk(current, chunk_end).await {
+            Ok(items) => scan.items.extend(items),
+            Err(e) if tolerate_partial => {
+                error!(
+                    from = current,
+                    to = chunk_end,
+                    scanned_to = ?scan.scanned_to,
+                    error = %e,
+                    "Chunk failed after retries, returning partial scan"
+                );
+                return Ok(scan);
+            }
+            Err(e) => return Err(e),
+        }
+
+        scan.scanned_to = coverage.covered_to();
+        chunks += 1;
+        current = chunk_end + 1;
+
+        if chunks.is_multiple_of(PROGRESS_EVERY_CHUNKS) && current <= to_block {
+            let scanned = chunk_end - from_block + 1;
+            let elapsed = started.elapsed();
+            let eta = elapsed.mul_f64((total_blocks - scanned) as f64 / scanned as f64);
+            info!(
+                scanned_blocks = scanned,
+                total_blocks,
+                found = scan.items.len(),
+                elapsed_secs = elapsed.as_secs(),
+                eta_secs = eta.as_secs(),
+                "Scan progress"
+            );
+        }
+    }
+
+    scan.complete = true;
+    Ok(scan)
+}
+
+/// Resolve BlockNumberOrTag to a concrete block number.
+async fn resolve_block_number<P: Provider>(
+    provider: &P,
+    block: BlockNumberOrTag,
+) -> eyre::Result<u64> {
+    match block {
+        BlockNumberOrTag::Number(n) => Ok(n),
+        BlockNumberOrTag::Latest => {
+            let block_num = provider.get_block_number().await?;
+            Ok(block_num)
+        }
+        _ => Err(eyre::eyre!("Unsupported block tag: {:?}", block)),
+    }
+}
+
+/// Status of a withdrawal from the portal's `finalizedWithdrawals` flag and the proofs
+/// submitted for it.
+///
+/// `Proven` by the proof [`select_proof`] picks, whoever submitted it, and `Initiated`
+/// when no valid proof exists.
+fn status_from(
+    finalized: bool,
+    candidates: &[ProofCandidate],
+    respected_game_type: u32,
+) -> WithdrawalStatus {
+    if finalized {
+        return WithdrawalStatus::Finalized;
+    }
+    select_proof(candidates, respected_game_type).map_or(WithdrawalStatus::Initiated, |best| {
+        WithdrawalStatus::Proven {
+            timestamp: best.timestamp,
+            dispute_game_proxy: best.dispute_game_proxy,
+            proof_submitter: best.submitter,
+        }
+    })
+}
+
+/// Hashes with a `WithdrawalProven` event but no `WithdrawalFinalized` event, in the
+/// order they were first proven.
+pub fn unfinalized_proven_hashes(events: &[L1WithdrawalEvent]) -> Vec<WithdrawalHash> {
+    let finalized: HashSet<WithdrawalHash> = events
+        .iter()
+        .filter(|e| matches!(e.kind, L1WithdrawalEventKind::Finalized { .. }))
+        .map(|e| e.hash)
+        .collect();
+
+    let mut seen = HashSet::new();
+    events
+        .iter()
+        .filter(|e| matches!(e.kind, L1WithdrawalEventKind::Proven { .. }))
+        .map(|e| e.hash)
+        .filter(|hash| !finalized.contains(hash) && seen.insert(*hash))
+        .collect()
+}
+
+/// Every withdrawal initiated in `receipt`: one per `MessagePassed` log emitted by
+/// `message_passer`, in log order.
+///
+/// Errors if the receipt is still pending, or if any event's hash doesn't match its
+/// recomputed withdrawal hash.
+pub fn initiated_withdrawals_in_receipt(
+    receipt: &TransactionReceipt,
+    message_passer: Address,
+) -> eyre::Result<Vec<InitiatedWithdrawal>> {
+    let l2_block = receipt
+        .block_number
+        .ok_or_else(|| eyre::eyre!("Receipt {} has no block number", receipt.transaction_hash))?;
+
+    receipt
+        .logs()
+        .iter()
+        .filter(|log| log.address() == message_passer)
+        .filter_map(|log| MessagePassed::decode_log(&log.inner).ok())
+        .map(|event| {
+            let transaction = WithdrawalTransaction::from(&event.data);
+            verify_withdrawal_hash(&transaction, event.withdrawalHash)?;
+            Ok(InitiatedWithdrawal {
+                transaction,
+                hash: event.withdrawalHash,
+                l2_block,
+                tx_hash: Some(receipt.transaction_hash),
+            })
+        })
+        .collect()
+}
+
+/// Decode an OptimismPortal2 log into an [`L1WithdrawalEvent`].
+///
+/// Returns `None` for logs that are neither `WithdrawalProven` nor `WithdrawalFinalized`,
+/// or that are missing the transaction hash / block number of a mined log.
+/// The timestamp is taken from the log's `blockTimestamp` and is zero when the
+/// provider does not return it.
+pub fn decode_l1_withdrawal_event(log: &Log) -> Option<L1WithdrawalEvent> {
+    let tx_hash = log.transaction_hash?;
+    let block_number = log.block_number?;
+    let timestamp = log.block_timestamp.unwrap_or_default();
+
+    let (hash, kind) = match log.topic0() {
+        Some(&WithdrawalProven::SIGNATURE_HASH) => {
+            let event = WithdrawalProven::decode_log(&log.inner).ok()?;
+            (
+                event.withdrawalHash,
+                L1WithdrawalEventKind::Proven {
+                    from: event.from,
+                    to: event.to,
+                },
+            )
+        }
+        Some(&WithdrawalFinalized::SIGNATURE_HASH) => {
+            let event = WithdrawalFinalized::decode_log(&log.inner).ok()?;
+            (
+                event.withdrawalHash,
+                L1WithdrawalEventKind::Finalized {
+                    success: event.success,
+                },
+            )
+        }
+        _ => return None,
+    };
+
+    Some(L1WithdrawalEvent {
+        hash,
+        kind,
+        tx_hash,
+        block_number,
+        timestamp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hash::compute_withdrawal_hash, submitter::GAME_STATUS_CHALLENGER_WINS};
+    use alloy_primitives::{address, b256, B256};
+    use test_support::FakeChain;
+
+    const PORTAL: Address = address!("0d83dab629f0e0F9d36c0Cbc89B69a489f0751bD");
+    const MESSAGE_PASSER: Address = address!("4200000000000000000000000000000000000016");
+    const SENDER: Address = address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1");
+
+    fn fixture_log(event: &impl SolEvent, timestamp: Option<u64>) -> Log {
+        Log {
+            inner: alloy_primitives::Log {
+                address: PORTAL,
+                data: event.encode_log_data(),
+            },
+            block_number: Some(9_000_000),
+            block_timestamp: timestamp,
+            transaction_hash: Some(b256!(
+                "2222222222222222222222222222222222222222222222222222222222222222"
+            )),
+            log_index: Some(3),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_withdrawal_proven_event() {
+        let hash = b256!("1111111111111111111111111111111111111111111111111111111111111111");
+        let from = address!("5CFFA347b0aE99cc01E5c01714cA5658e54a23D1");
+        let to = address!("000000000000000000000000000000000000dEaD");
+        let log = fixture_log(
+            &WithdrawalProven {
+                withdrawalHash: hash,
+                from,
+                to,
+            },
+            Some(1_700_000_000),
+        );
+
+        let event = decode_l1_withdrawal_event(&log).expect("should decode");
+        assert_eq!(event.hash, hash);
+        assert_eq!(event.kind, L1WithdrawalEventKind::Proven { from, to });
+        assert_eq!(event.tx_hash, log.transaction_hash.unwrap());
+        assert_eq!(event.block_number, 9_000_000);
+        assert_eq!(event.timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_decode_withdrawal_finalized_event() {
+        let hash = b256!("3333333333333333333333333333333333333333333333333333333333333333");
+        let log = fixture_log(
+            &WithdrawalFinalized {
+                withdrawalHash: hash,
+                success: false,
+            },
+            None,
+        );
+
+        let event = decode_l1_withdrawal_event(&log).expect("should decode");
+        assert_eq!(event.hash, hash);
+        assert_eq!(
+            event.kind,
+            L1WithdrawalEventKind::Finalized { success: false }
+        );
+        // Missing blockTimestamp is left for the scan to backfill
+        assert_eq!(event.timestamp, 0);
+    }
+
+    #[test]
+    fn test_decode_ignores_unrelated_and_pending_logs() {
+        let proven = WithdrawalProven {
+            withdrawalHash: B256::ZERO,
+            from: Address::ZERO,
+            to: Address::ZERO,
+        };
+
+        let mut pending = fixture_log(&proven, None);
+        pending.transaction_hash = None;
+        assert!(decode_l1_withdrawal_event(&pending).is_none());
+
+        let unrelated = fixture_log(
+            &IL2ToL1MessagePasser::MessagePassed {
+                nonce: Default::default(),
+                sender: Address::ZERO,
+                target: Address::ZERO,
+                value: Default::default(),
+                gasLimit: Default::default(),
+                data: Default::default(),
+                withdrawalHash: B256::ZERO,
+            },
+            None,
+        );
+        assert!(decode_l1_withdrawal_event(&unrelated).is_none());
+    }
+
+    /// Receipt of one L2 transaction initiating a withdrawal for each of `withdrawals`,
+    /// with an unrelated log in between.
+    fn multi_withdrawal_receipt(withdrawals: &[WithdrawalTransaction]) -> TransactionReceipt {
+        use alloy_rpc_types_eth::{Receipt, ReceiptEnvelope, ReceiptWithBloom};
+
+        let message_passed = |tx: &WithdrawalTransaction| Log {
+            inner: alloy_primitives::Log {
+                address: MESSAGE_PASSER,
+                data: MessagePassed {
+                    nonce: tx.nonce,
+                    sender: tx.sender,
+                    target: tx.target,
+                    value: tx.value,
+                    gasLimit: tx.gasLimit,
+                    data: tx.data.clone(),
+                    withdrawalHash: compute_withdrawal_hash(tx),
+                }
+                .encode_log_data(),
+            },
+            ..Default::default()
+        };
+        let mut logs: Vec<Log> = withdrawals.iter().map(message_passed).collect();
+        logs.insert(
+            1,
+            fixture_log(
+                &WithdrawalFinalized {
+                    withdrawalHash: B256::ZERO,
+                    success: true,
+                },
+                None,
+            ),
+        );
+
+        TransactionReceipt {
+            inner: ReceiptEnvelope::Eip1559(ReceiptWithBloom {
+                receipt: Receipt {
+                    status: true.into(),
+                    cumulative_gas_used: 200_000,
+                    logs,
+                },
+                logs_bloom: Default::default(),
+            }),
+            transaction_hash: B256::repeat_byte(0xaa),
+            transaction_index: Some(0),
+            block_hash: None,
+            block_number: Some(42),
+            gas_used: 180_000,
+            effective_gas_price: 1_000_000,
+            blob_gas_used: None,
+            blob_gas_price: None,
+            from: SENDER,
+            to: Some(SENDER),
+            contract_address: None,
+        }
+    }
+
+    fn withdrawal(nonce: u64) -> WithdrawalTransaction {
+        WithdrawalTransaction {
+            nonce: U256::from(nonce),
+            sender: SENDER,
+            target: SENDER,
+            value: U256::from(1_000 * nonce),
+            gasLimit: U256::from(100_000),
+            data: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_initiated_withdrawals_in_receipt() {
+        let withdrawals = [withdrawal(1), withdrawal(2), withdrawal(3)];
+        let mut receipt = multi_withdrawal_receipt(&withdrawals);
+
+        let initiated = initiated_withdrawals_in_receipt(&receipt, MESSAGE_PASSER).unwrap();
+        assert_eq!(initiated.len(), 3);
+        for (initiated, tx) in initiated.iter().zip(&withdrawals) {
+            assert_eq!(initiated.transaction, *tx);
+            assert_eq!(initiated.hash, compute_withdrawal_hash(tx));
+            assert_eq!(initiated.l2_block, 42);
+            assert_eq!(initiated.tx_hash, Some(B256::repeat_byte(0xaa)));
+        }
+
+        // Only events from the message passer count
+        assert!(initiated_withdrawals_in_receipt(&receipt, PORTAL)
+            .unwrap()
+            .is_empty());
+
+        receipt.block_number = None;
+        assert!(initiated_withdrawals_in_receipt(&receipt, MESSAGE_PASSER).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scan_yields_each_withdrawal_of_a_transaction() {
+        let l2 = FakeChain::new();
+        let withdrawals = [withdrawal(1), withdrawal(2)];
+        for log in multi_withdrawal_receipt(&withdrawals).logs() {
+            l2.add_log(Log {
+                block_number: Some(42),
+                transaction_hash: Some(B256::repeat_byte(0xaa)),
+                ..log.clone()
+            });
+        }
+        let state =
+            WithdrawalStateProvider::new(l2.provider(), l2.provider(), PORTAL, MESSAGE_PASSER);
+
+        let initiated = state
+            .get_initiated_withdrawals(0, 100, SENDER)
+            .await
+            .unwrap();
+        let hashes: Vec<_> = initiated.iter().map(|w| w.hash).collect();
+        assert_eq!(
+            hashes,
+            withdrawals
+                .iter()
+                .map(compute_withdrawal_hash)
+                .collect::<Vec<_>>()
+        );
+        assert!(initiated
+            .iter()
+            .all(|w| w.tx_hash == Some(B256::repeat_byte(0xaa))));
+    }
+
+    #[tokio::test]
+    async fn test_get_withdrawals_by_tx_hash() {
+        let l1 = FakeChain::new();
+        let l2 = FakeChain::new();
+        let withdrawals = [withdrawal(1), withdrawal(2)];
+        l2.set_receipt(multi_withdrawal_receipt(&withdrawals));
+        let hashes = withdrawals.iter().map(compute_withdrawal_hash);
+        let state =
+            WithdrawalStateProvider::new(l1.provider(), l2.provider(), PORTAL, MESSAGE_PASSER)
+                .with_finalized_set(FinalizedSet::from_hashes(hashes));
+
+        let found = state
+            .get_withdrawals_by_tx_hash(B256::repeat_byte(0xaa), SENDER)
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].hash, compute_withdrawal_hash(&withdrawals[0]));
+        assert_eq!(found[1].hash, compute_withdrawal_hash(&withdrawals[1]));
+        assert!(found
+            .iter()
+            .all(|w| matches!(w.status, WithdrawalStatus::Finalized)));
+
+        assert!(state
+            .get_withdrawals_by_tx_hash(B256::repeat_byte(0xbb), SENDER)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_status_from() {
+        let proof = |submitter: u8, timestamp, game_status| ProofCandidate {
+            submitter: Address::repeat_byte(submitter),
+            dispute_game_proxy: Address::repeat_byte(submitter + 0x10),
+            timestamp,
+            game_type: 1,
+            game_status,
+            blacklisted: false,
+        };
+        let ours = proof(0x33, 200, 0);
+        let autoprover = proof(0x44, 100, 0);
+
+        assert_eq!(status_from(true, &[ours], 1), WithdrawalStatus::Finalized);
+        // Proven by someone else only: proven, not initiated
+        assert_eq!(
+            status_from(false, &[autoprover], 1),
+            WithdrawalStatus::Proven {
+                timestamp: 100,
+                dispute_game_proxy: autoprover.dispute_game_proxy,
+                proof_submitter: autoprover.submitter,
+            }
+        );
+        assert_eq!(
+            status_from(false, &[ours, autoprover], 1),
+            status_from(false, &[autoprover], 1)
+        );
+        // Every proof is against a lost game or one of another type
+        assert_eq!(
+            status_from(false, &[proof(0x33, 200, GAME_STATUS_CHALLENGER_WINS)], 1),
+            WithdrawalStatus::Initiated
+        );
+        assert_eq!(status_from(false, &[ours], 0), WithdrawalStatus::Initiated);
+        assert_eq!(status_from(false, &[], 1), WithdrawalStatus::Initiated);
+    }
+
+    #[test]
+    fn test_unfinalized_proven_hashes() {
+        let event = |hash: u8, kind| L1WithdrawalEvent {
+            hash: B256::repeat_byte(hash),
+            kind,
+            tx_hash: B256::ZERO,
+            block_number: 1,
+            timestamp: 1,
+        };
+        let proven = L1WithdrawalEventKind::Proven {
+            from: Address::ZERO,
+            to: Address::ZERO,
+        };
+        let finalized = L1WithdrawalEventKind::Finalized { success: true };
+
+        let events = [
+            event(1, proven),
+            event(2, proven),
+            event(1, finalized),
+            // Re-proven against a newer game
+            event(2, proven),
+            event(3, proven),
+        ];
+
+        assert_eq!(
+            unfinalized_proven_hashes(&events),
+            vec![B256::repeat_byte(2), B256::repeat_byte(3)]
+        );
+    }
+
+    fn track(from_block: u64) -> RangeCoverage {
+        ScanCoverage::default().track("test", from_block)
+    }
+
+    /// Scans chunks 0..=4 of a 5-chunk range, failing permanently on chunk 2.
+    async fn scan_with_failing_middle_chunk(
+        tolerate_partial: bool,
+    ) -> eyre::Result<ChunkedScan<u64>> {
+        let to_block = 5 * CHUNK_SIZE - 1;
+        let coverage = track(0);
+        scan_in_chunks(&coverage, 0, to_block, tolerate_partial, |from, to| {
+            let coverage = &coverage;
+            async move {
+                if from / CHUNK_SIZE == 2 {
+                    eyre::bail!("chunk permanently unavailable");
+                }
+                coverage.record(from, to);
+                Ok(vec![from])
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_scan_in_chunks_complete() {
+        let coverage = track(10);
+        let scan = scan_in_chunks(&coverage, 10, 2 * CHUNK_SIZE, false, |from, to| {
+            coverage.record(from, to);
+            async move { Ok(vec![(from, to)]) }
+        })
+        .await
+        .unwrap();
+
+        assert!(scan.complete);
+        assert_eq!(scan.scanned_to, Some(2 * CHUNK_SIZE));
+        assert_eq!(
+            scan.items,
+            vec![(10, CHUNK_SIZE + 9), (CHUNK_SIZE + 10, 2 * CHUNK_SIZE)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_in_chunks_partial_on_failing_chunk() {
+        let scan = scan_with_failing_middle_chunk(true).await.unwrap();
+
+        assert!(!scan.complete);
+        assert_eq!(scan.items, vec![0, CHUNK_SIZE]);
+        assert_eq!(scan.scanned_to, Some(2 * CHUNK_SIZE - 1));
+    }
+
+    #[tokio::test]
+    async fn test_scan_in_chunks_fails_without_tolerance() {
+        let err = scan_with_failing_middle_chunk(false).await.unwrap_err();
+        assert!(err.to_string().contains("permanently unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_scan_in_chunks_first_chunk_fails() {
+        let scan = scan_in_chunks::<u64, _, _>(&track(100), 100, 200, true, |_, _| async {
+            eyre::bail!("down")
+        })
+        .await
+        .unwrap();
+
+        assert!(!scan.complete);
+        assert!(scan.items.is_empty());
+        assert_eq!(scan.scanned_to, None);
+    }
+
+    #[tokio::test]
+    async fn test_known_finalized_skips_portal() {
+        let asserter = alloy_transport::mock::Asserter::new();
+        let provider =
+            alloy_provider::ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let hash = B256::repeat_byte(0x22);
+        let state = WithdrawalStateProvider::new(provider.clone(), provider, PORTAL, PORTAL)
+            .with_finalized_set(FinalizedSet::from_hashes([hash]));
+
+        // No responses queued: any RPC call would fail
+        let status = state
+            .query_withdrawal_status(hash, Address::ZERO)
+            .await
+            .unwrap();
+        assert!(matches!(status, WithdrawalStatus::Finalized));
+    }
+
+    #[tokio::test]
+    async fn test_observed_finalized_is_recorded() {
+        let asserter = alloy_transport::mock::Asserter::new();
+        let provider =
+            alloy_provider::ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        let hash = B256::repeat_byte(0x22);
+        let finalized = FinalizedSet::default();
+        let state = WithdrawalStateProvider::new(provider.clone(), provider, PORTAL, PORTAL)
+            .with_finalized_set(finalized.clone());
+
+        asserter.push_success(&alloy_primitives::Bytes::from(
+            alloy_sol_types::SolValue::abi_encode(&true),
+        ));
+        let status = state
+            .query_withdrawal_status(hash, Address::ZERO)
+            .await
+            .unwrap();
+
+        assert!(matches!(status, WithdrawalStatus::Finalized));
+        assert!(asserter.read_q().is_empty());
+        assert!(finalized.contains(hash));
+    }
+}
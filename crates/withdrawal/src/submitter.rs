@@ -0,0 +1,298 @@
+//! Choosing which proof to finalize a withdrawal with.
+//!
+//! The portal keys proofs by `(withdrawal hash, submitter)`, so one withdrawal can be
+//! proven several times: by us and by an autoprover, each against its own dispute game
+//! and with its own maturity clock. Finalizing names a single submitter, so
+//! [`select_proof`] picks the valid proof that can be finalized soonest.
+
+use alloy_primitives::Address;
+
+/// Game status of a dispute game that has not resolved yet.
+pub const GAME_STATUS_IN_PROGRESS: u8 = 0;
+
+/// Game status of a dispute game whose root claim was successfully challenged.
+pub const GAME_STATUS_CHALLENGER_WINS: u8 = 1;
+
+/// Game status of a dispute game whose root claim stood.
+pub const GAME_STATUS_DEFENDER_WINS: u8 = 2;
+
+/// A proof of a withdrawal by one submitter, with the state of its dispute game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofCandidate {
+    /// Address that submitted the proof
+    pub submitter: Address,
+    /// Dispute game the proof was submitted against
+    pub dispute_game_proxy: Address,
+    /// When the proof was submitted; the maturity delay counts from here
+    pub timestamp: u64,
+    /// Type of the dispute game
+    pub game_type: u32,
+    /// Status of the dispute game (0 = in progress, 1 = challenger wins, 2 = defender wins)
+    pub game_status: u8,
+    /// Whether the guardian blacklisted the dispute game
+    pub blacklisted: bool,
+}
+
+/// Why a proof can't be finalized against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofRejection {
+    /// The dispute game was blacklisted
+    Blacklisted,
+    /// The dispute game's root claim was successfully challenged
+    ChallengerWins,
+    /// The dispute game is not of the portal's respected type
+    GameTypeNotRespected { game_type: u32 },
+}
+
+impl ProofRejection {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Blacklisted => "blacklisted",
+            Self::ChallengerWins => "challenger_wins",
+            Self::GameTypeNotRespected { .. } => "game_type_not_respected",
+        }
+    }
+}
+
+/// Whether a proof still looks like it can be finalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofHealth {
+    /// Valid, and its game resolved for the defender or is within its expected run
+    Healthy,
+    /// Valid so far, but its game is still in progress past its expected resolution
+    AtRisk,
+    /// Can never be finalized; the withdrawal must be proven again
+    Invalid(ProofRejection),
+}
+
+impl ProofHealth {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::AtRisk => "at_risk",
+            Self::Invalid(_) => "invalid",
+        }
+    }
+}
+
+impl ProofCandidate {
+    /// Why this proof is invalid, or `None` if it can be finalized once mature.
+    pub const fn rejection(&self, respected_game_type: u32) -> Option<ProofRejection> {
+        if self.blacklisted {
+            Some(ProofRejection::Blacklisted)
+        } else if self.game_status == GAME_STATUS_CHALLENGER_WINS {
+            Some(ProofRejection::ChallengerWins)
+        } else if self.game_type != respected_game_type {
+            Some(ProofRejection::GameTypeNotRespected {
+                game_type: self.game_type,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Health of this proof at `now`.
+    ///
+    /// A game that is still in progress `at_risk_after_secs` after the proof was
+    /// submitted is being disputed, or its resolution is stuck; either way the proof
+    /// may yet be invalidated.
+    pub const fn health(
+        &self,
+        respected_game_type: u32,
+        now: u64,
+        at_risk_after_secs: u64,
+    ) -> ProofHealth {
+        if let Some(rejection) = self.rejection(respected_game_type) {
+            return ProofHealth::Invalid(rejection);
+        }
+        if self.game_status == GAME_STATUS_IN_PROGRESS
+            && now.saturating_sub(self.timestamp) >= at_risk_after_secs
+        {
+            return ProofHealth::AtRisk;
+        }
+        ProofHealth::Healthy
+    }
+
+    /// Timestamp from which the proof can be finalized, once its game resolved.
+    pub const fn matures_at(&self, proof_maturity_delay: u64) -> u64 {
+        self.timestamp.saturating_add(proof_maturity_delay)
+    }
+
+    /// Whether the proof's game resolved for the defender. The portal refuses to
+    /// finalize against a game still in progress, however mature the proof.
+    pub const fn game_resolved(&self) -> bool {
+        self.game_status == GAME_STATUS_DEFENDER_WINS
+    }
+}
+
+/// The valid proof that can be finalized soonest, if any.
+///
+/// Proofs against games resolved for the defender come first, since one against a game
+/// still in progress can't be finalized until the game resolves. Every proof matures
+/// after the same portal-wide delay, so among those the earliest submitted is picked;
+/// ties go to the earlier entry in `candidates`. A proof against a game in progress is
+/// only picked when no game resolved, so check [`ProofCandidate::game_resolved`] before
+/// finalizing with it.
+pub fn select_proof(
+    candidates: &[ProofCandidate],
+    respected_game_type: u32,
+) -> Option<ProofCandidate> {
+    candidates
+        .iter()
+        .filter(|c| c.rejection(respected_game_type).is_none())
+        .min_by_key(|c| (!c.game_resolved(), c.timestamp))
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPECTED: u32 = 1;
+    const DEFENDER_WINS: u8 = 2;
+
+    fn candidate(submitter: u8, timestamp: u64) -> ProofCandidate {
+        ProofCandidate {
+            submitter: Address::repeat_byte(submitter),
+            dispute_game_proxy: Address::repeat_byte(submitter + 0x10),
+            timestamp,
+            game_type: RESPECTED,
+            game_status: 0,
+            blacklisted: false,
+        }
+    }
+
+    #[test]
+    fn test_prefers_earliest_maturity() {
+        let ours = candidate(1, 2_000);
+        let autoprover = candidate(2, 1_000);
+
+        let best = select_proof(&[ours, autoprover], RESPECTED).unwrap();
+
+        assert_eq!(best.submitter, autoprover.submitter);
+        assert_eq!(best.matures_at(604_800), 605_800);
+    }
+
+    #[test]
+    fn test_skips_blacklisted_game() {
+        // The autoprover proved first, but against a game the guardian blacklisted
+        let ours = candidate(1, 2_000);
+        let autoprover = ProofCandidate {
+            blacklisted: true,
+            ..candidate(2, 1_000)
+        };
+
+        assert_eq!(
+            autoprover.rejection(RESPECTED),
+            Some(ProofRejection::Blacklisted)
+        );
+        assert_eq!(select_proof(&[autoprover, ours], RESPECTED), Some(ours));
+    }
+
+    #[test]
+    fn test_skips_lost_and_unrespected_games() {
+        let challenged = ProofCandidate {
+            game_status: GAME_STATUS_CHALLENGER_WINS,
+            ..candidate(1, 1_000)
+        };
+        let old_type = ProofCandidate {
+            game_type: 0,
+            ..candidate(2, 1_500)
+        };
+        let resolved = ProofCandidate {
+            game_status: DEFENDER_WINS,
+            ..candidate(3, 3_000)
+        };
+
+        assert_eq!(
+            challenged.rejection(RESPECTED),
+            Some(ProofRejection::ChallengerWins)
+        );
+        assert_eq!(
+            old_type.rejection(RESPECTED),
+            Some(ProofRejection::GameTypeNotRespected { game_type: 0 })
+        );
+        assert_eq!(
+            select_proof(&[challenged, old_type, resolved], RESPECTED),
+            Some(resolved)
+        );
+    }
+
+    #[test]
+    fn test_health() {
+        const AT_RISK_AFTER: u64 = 302_400;
+        let proof = candidate(1, 1_000);
+        let resolved = ProofCandidate {
+            game_status: DEFENDER_WINS,
+            ..proof
+        };
+        let challenged = ProofCandidate {
+            game_status: GAME_STATUS_CHALLENGER_WINS,
+            ..proof
+        };
+        let old_type = ProofCandidate {
+            game_type: 0,
+            ..proof
+        };
+
+        assert_eq!(
+            proof.health(RESPECTED, 1_000 + AT_RISK_AFTER - 1, AT_RISK_AFTER),
+            ProofHealth::Healthy
+        );
+        assert_eq!(
+            proof.health(RESPECTED, 1_000 + AT_RISK_AFTER, AT_RISK_AFTER),
+            ProofHealth::AtRisk
+        );
+        // A resolved game is no longer at risk, however long it took
+        assert_eq!(
+            resolved.health(RESPECTED, 1_000_000, AT_RISK_AFTER),
+            ProofHealth::Healthy
+        );
+        assert_eq!(
+            challenged.health(RESPECTED, 2_000, AT_RISK_AFTER),
+            ProofHealth::Invalid(ProofRejection::ChallengerWins)
+        );
+        // The portal moved on to another game type since the proof
+        assert_eq!(
+            old_type.health(RESPECTED, 2_000, AT_RISK_AFTER),
+            ProofHealth::Invalid(ProofRejection::GameTypeNotRespected { game_type: 0 })
+        );
+    }
+
+    #[test]
+    fn test_no_valid_proof() {
+        let blacklisted = ProofCandidate {
+            blacklisted: true,
+            ..candidate(1, 1_000)
+        };
+
+        assert_eq!(select_proof(&[blacklisted], RESPECTED), None);
+        assert_eq!(select_proof(&[], RESPECTED), None);
+    }
+
+    #[test]
+    fn test_prefers_resolved_game() {
+        // Proven first, but its game is still in progress
+        let in_progress = candidate(1, 1_000);
+        let resolved = ProofCandidate {
+            game_status: DEFENDER_WINS,
+            ..candidate(2, 2_000)
+        };
+
+        assert!(!in_progress.game_resolved());
+        assert_eq!(
+            select_proof(&[in_progress, resolved], RESPECTED),
+            Some(resolved)
+        );
+        // Still proven while no game has resolved
+        assert_eq!(select_proof(&[in_progress], RESPECTED), Some(in_progress));
+    }
+
+    #[test]
+    fn test_ties_keep_first_candidate() {
+        let first = candidate(1, 1_000);
+        let second = candidate(2, 1_000);
+
+        assert_eq!(select_proof(&[first, second], RESPECTED), Some(first));
+    }
+}
@@ -1,10 +1,40 @@
-use alloy_primitives::B256;
+use alloy_primitives::{Address, TxHash, B256};
 
 pub type WithdrawalHash = B256;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum WithdrawalStatus {
     Initiated,
-    Proven { timestamp: u64 },
+    Proven {
+        timestamp: u64,
+        /// Dispute game the proof was submitted against
+        dispute_game_proxy: Address,
+        /// Submitter of the proof; finalizing must name it
+        proof_submitter: Address,
+    },
     Finalized,
 }
+
+/// A withdrawal lifecycle event emitted by OptimismPortal2 on L1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L1WithdrawalEvent {
+    /// Hash of the withdrawal the event refers to
+    pub hash: WithdrawalHash,
+    /// Which portal event was emitted
+    pub kind: L1WithdrawalEventKind,
+    /// L1 transaction that emitted the event
+    pub tx_hash: TxHash,
+    /// L1 block containing the transaction
+    pub block_number: u64,
+    /// Timestamp of the L1 block
+    pub timestamp: u64,
+}
+
+/// The portal event variants we track on L1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum L1WithdrawalEventKind {
+    /// `WithdrawalProven(withdrawalHash, from, to)`
+    Proven { from: Address, to: Address },
+    /// `WithdrawalFinalized(withdrawalHash, success)`
+    Finalized { success: bool },
+}
@@ -0,0 +1,318 @@
+//! Waiting for a withdrawal to be finalized on L1.
+//!
+//! For services that need to block until a withdrawal's funds have arrived without
+//! reimplementing the portal polling. [`wait_for_finalization`] polls the portal until the
+//! withdrawal is finalized or the timeout passes, reporting each [`FinalizationStage`] it
+//! moves through.
+
+use crate::{
+    portal::PortalParams,
+    state::{initiated_withdrawals_in_receipt, WithdrawalStateProvider},
+    submitter::select_proof,
+    types::{L1WithdrawalEventKind, WithdrawalHash},
+};
+use alloy_contract::private::Provider;
+use alloy_primitives::{Address, TxHash};
+use alloy_rpc_types_eth::BlockNumberOrTag;
+use binding::opstack::MESSAGE_PASSER_ADDRESS;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// L1 blocks searched for the `WithdrawalFinalized` event of a withdrawal that was already
+/// finalized when the wait started (about a week at 12s blocks).
+const FINALIZED_EVENT_LOOKBACK_BLOCKS: u64 = 50_400;
+
+/// Where a withdrawal is on its way to finalization.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalizationStage {
+    /// Initiated on L2, without a valid proof on L1
+    Initiated,
+    /// Proven; the proof that can be finalized first matures at `matures_at`, and may
+    /// still wait on its game to resolve after that
+    Proven {
+        matures_at: u64,
+        proof_submitter: Address,
+    },
+    /// The proof matured, its game resolved and the withdrawal can be finalized
+    ReadyToFinalize,
+    /// Finalized by `tx_hash`; `success` is false if the withdrawal's call reverted
+    Finalized { success: bool, tx_hash: TxHash },
+}
+
+/// How [`wait_for_finalization`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalizationOutcome {
+    /// Finalized and the funds were delivered
+    Succeeded { tx_hash: TxHash },
+    /// Finalized, but the withdrawal's call reverted. The portal does not allow a retry.
+    Failed { tx_hash: TxHash },
+    /// Not finalized before the timeout
+    TimedOut { stage: FinalizationStage },
+}
+
+/// Hash of the withdrawal initiated by L2 transaction `tx_hash`.
+///
+/// Errors if the transaction initiated no withdrawal or several, since it is then
+/// ambiguous which one to wait for.
+pub async fn withdrawal_hash_of_tx<P: Provider>(
+    l2_provider: &P,
+    tx_hash: TxHash,
+) -> eyre::Result<WithdrawalHash> {
+    let receipt = l2_provider
+        .get_transaction_receipt(tx_hash)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Transaction {tx_hash} not found"))?;
+
+    let initiated = initiated_withdrawals_in_receipt(&receipt, MESSAGE_PASSER_ADDRESS)?;
+    match initiated.as_slice() {
+        [withdrawal] => Ok(withdrawal.hash),
+        [] => eyre::bail!("Transaction {tx_hash} did not initiate a withdrawal"),
+        several => {
+            let hashes: Vec<String> = several.iter().map(|w| w.hash.to_string()).collect();
+            eyre::bail!(
+                "Transaction {tx_hash} initiated {} withdrawals, pick one by hash: {}",
+                several.len(),
+                hashes.join(", ")
+            )
+        }
+    }
+}
+
+/// Poll the portal every `poll_interval` until withdrawal `hash` is finalized or `timeout`
+/// passes.
+///
+/// `on_progress` is called with the first stage observed and again on every change.
+/// Proofs by every submitter are considered, as in
+/// [`WithdrawalStateProvider::query_withdrawal_status`].
+pub async fn wait_for_finalization<P1, P2, F>(
+    l1_provider: P1,
+    l2_provider: P2,
+    portal: Address,
+    hash: WithdrawalHash,
+    poll_interval: Duration,
+    timeout: Duration,
+    mut on_progress: F,
+) -> eyre::Result<FinalizationOutcome>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+    F: FnMut(&FinalizationStage),
+{
+    let deadline = Instant::now() + timeout;
+    let state = WithdrawalStateProvider::new(
+        l1_provider.clone(),
+        l2_provider,
+        portal,
+        MESSAGE_PASSER_ADDRESS,
+    );
+
+    // L1 block at which the withdrawal was last seen unfinalized
+    let mut unfinalized_at: Option<u64> = None;
+    let mut last_stage: Option<FinalizationStage> = None;
+
+    loop {
+        let l1_block = l1_provider.get_block_number().await?;
+        let stage =
+            current_stage(&state, &l1_provider, portal, hash, l1_block, unfinalized_at).await?;
+
+        if last_stage != Some(stage) {
+            on_progress(&stage);
+            last_stage = Some(stage);
+        }
+
+        match stage {
+            FinalizationStage::Finalized {
+                success: true,
+                tx_hash,
+            } => return Ok(FinalizationOutcome::Succeeded { tx_hash }),
+            FinalizationStage::Finalized {
+                success: false,
+                tx_hash,
+            } => return Ok(FinalizationOutcome::Failed { tx_hash }),
+            _ => {}
+        }
+
+        unfinalized_at = Some(l1_block);
+        if Instant::now() + poll_interval > deadline {
+            return Ok(FinalizationOutcome::TimedOut { stage });
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Stage of withdrawal `hash` as of L1 block `l1_block` or later.
+async fn current_stage<P1, P2>(
+    state: &WithdrawalStateProvider<P1, P2>,
+    l1_provider: &P1,
+    portal: Address,
+    hash: WithdrawalHash,
+    l1_block: u64,
+    unfinalized_at: Option<u64>,
+) -> eyre::Result<FinalizationStage>
+where
+    P1: Provider + Clone,
+    P2: Provider + Clone,
+{
+    if state.is_finalized(hash).await? {
+        // Only the event says whether the withdrawal's call succeeded
+        let from = unfinalized_at
+            .unwrap_or_else(|| l1_block.saturating_sub(FINALIZED_EVENT_LOOKBACK_BLOCKS));
+        let events = state
+            .scan_l1_withdrawal_events(
+                BlockNumberOrTag::Number(from),
+                BlockNumberOrTag::Latest,
+                None,
+            )
+            .await?;
+        let (success, tx_hash) = events
+            .iter()
+            .filter(|event| event.hash == hash)
+            .find_map(|event| match event.kind {
+                L1WithdrawalEventKind::Finalized { success } => Some((success, event.tx_hash)),
+                L1WithdrawalEventKind::Proven { .. } => None,
+            })
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "Withdrawal {hash} is finalized but no WithdrawalFinalized event was found from block {from}"
+                )
+            })?;
+        return Ok(FinalizationStage::Finalized { success, tx_hash });
+    }
+
+    // The portal enumerates every submitter; there is no address of our own to add
+    let candidates = state.proof_candidates(hash, Address::ZERO).await?;
+    if candidates.is_empty() {
+        return Ok(FinalizationStage::Initiated);
+    }
+
+    let params = PortalParams::fetch(l1_provider, portal).await?;
+    let Some(best) = select_proof(&candidates, params.respected_game_type) else {
+        debug!(
+            withdrawal_hash = %hash,
+            proofs = candidates.len(),
+            "No valid proof for withdrawal"
+        );
+        return Ok(FinalizationStage::Initiated);
+    };
+
+    let now = l1_provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Failed to get latest block"))?
+        .header
+        .timestamp;
+    let matures_at = best.matures_at(params.proof_maturity_delay);
+    if now >= matures_at && best.game_resolved() {
+        return Ok(FinalizationStage::ReadyToFinalize);
+    }
+
+    Ok(FinalizationStage::Proven {
+        matures_at,
+        proof_submitter: best.submitter,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, B256, U256};
+    use alloy_provider::ProviderBuilder;
+    use alloy_rpc_types_eth::Log;
+    use alloy_sol_types::{SolEvent, SolValue};
+    use alloy_transport::mock::Asserter;
+    use binding::opstack::IOptimismPortal2::WithdrawalFinalized;
+
+    const PORTAL: Address = Address::repeat_byte(0x11);
+    const HASH: WithdrawalHash = B256::repeat_byte(0x22);
+
+    fn push_call(asserter: &Asserter, value: impl SolValue) {
+        asserter.push_success(&Bytes::from(value.abi_encode()));
+    }
+
+    #[tokio::test]
+    async fn test_times_out_while_initiated() {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+
+        asserter.push_success(&U256::from(100));
+        push_call(&asserter, false); // finalizedWithdrawals
+        push_call(&asserter, U256::ZERO); // numProofSubmitters
+        push_call(&asserter, (Address::ZERO, 0u64)); // provenWithdrawals
+
+        let mut stages = vec![];
+        let outcome = wait_for_finalization(
+            provider.clone(),
+            provider,
+            PORTAL,
+            HASH,
+            Duration::from_secs(60),
+            Duration::ZERO,
+            |stage| stages.push(*stage),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            outcome,
+            FinalizationOutcome::TimedOut {
+                stage: FinalizationStage::Initiated
+            }
+        );
+        assert_eq!(stages, [FinalizationStage::Initiated]);
+        assert!(asserter.read_q().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_distinguishes_failed_finalization() {
+        for success in [true, false] {
+            let asserter = Asserter::new();
+            let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+
+            let finalize_tx = B256::repeat_byte(0x33);
+            let log = Log {
+                inner: alloy_primitives::Log {
+                    address: PORTAL,
+                    data: WithdrawalFinalized {
+                        withdrawalHash: HASH,
+                        success,
+                    }
+                    .encode_log_data(),
+                },
+                block_number: Some(90),
+                block_timestamp: Some(1_700_000_000),
+                transaction_hash: Some(finalize_tx),
+                log_index: Some(0),
+                ..Default::default()
+            };
+
+            asserter.push_success(&U256::from(100));
+            push_call(&asserter, true); // finalizedWithdrawals
+            asserter.push_success(&U256::from(100)); // latest block of the event scan
+            asserter.push_success(&Vec::<Log>::new()); // WithdrawalProven
+            asserter.push_success(&vec![log]); // WithdrawalFinalized
+
+            let outcome = wait_for_finalization(
+                provider.clone(),
+                provider,
+                PORTAL,
+                HASH,
+                Duration::from_secs(60),
+                Duration::from_secs(60),
+                |_| {},
+            )
+            .await
+            .unwrap();
+
+            let expected = if success {
+                FinalizationOutcome::Succeeded {
+                    tx_hash: finalize_tx,
+                }
+            } else {
+                FinalizationOutcome::Failed {
+                    tx_hash: finalize_tx,
+                }
+            };
+            assert_eq!(outcome, expected);
+        }
+    }
+}